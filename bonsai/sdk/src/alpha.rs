@@ -12,17 +12,32 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{fs::File, path::Path};
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    fs::File,
+    hash::{BuildHasher, Hasher},
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+    time::Duration,
+};
 
-use reqwest::{blocking::Client as BlockingClient, header};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use bytes::Bytes;
+use reqwest::{
+    blocking::{Body, Client as BlockingClient, RequestBuilder, Response},
+    header, StatusCode,
+};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 
 use self::responses::{
     CreateSessRes, ImgUploadRes, ProofReq, Quotas, ReceiptDownload, SessionStatusRes, SnarkReq,
     SnarkStatusRes, UploadRes, VersionInfo,
 };
-use crate::{API_KEY_ENVVAR, API_KEY_HEADER, API_URL_ENVVAR, VERSION_HEADER};
+use crate::{
+    API_KEY_ENVVAR, API_KEY_HEADER, API_URL_ENVVAR, IDEMPOTENCY_KEY_HEADER, VERSION_HEADER,
+};
 
 /// Bonsai Alpha SDK error classes
 #[derive(Debug, Error)]
@@ -48,6 +63,12 @@ pub enum SdkErr {
     /// Receipt not found
     #[error("Receipt not found")]
     ReceiptNotFound,
+    /// Downloaded data does not match its length or checksum
+    #[error("download is corrupt: {0}")]
+    CorruptDownload(String),
+    /// A session or SNARK workflow ended without succeeding
+    #[error("workflow {0} exited: {1}")]
+    WorkflowFailed(String, String),
 }
 
 /// Collection of serialization object for the REST api
@@ -90,7 +111,7 @@ pub mod responses {
     }
 
     /// Session statistics metadata file
-    #[derive(Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize)]
     pub struct SessionStats {
         /// Count of segments in this proof request
         pub segments: usize,
@@ -231,7 +252,7 @@ impl SessionId {
     /// Fetches the current status of the Session
     pub fn status(&self, client: &Client) -> Result<SessionStatusRes, SdkErr> {
         let url = format!("{}/sessions/status/{}", client.url, self.uuid);
-        let res = client.client.get(url).send()?;
+        let res = client.send(|| client.client.get(&url))?;
 
         if !res.status().is_success() {
             let body = res.text()?;
@@ -250,7 +271,7 @@ impl SessionId {
     /// from `env::log`
     pub fn logs(&self, client: &Client) -> Result<String, SdkErr> {
         let url = format!("{}/sessions/logs/{}", client.url, self.uuid);
-        let res = client.client.get(url).send()?;
+        let res = client.send(|| client.client.get(&url))?;
 
         if !res.status().is_success() {
             let body = res.text()?;
@@ -262,7 +283,7 @@ impl SessionId {
     /// Stops a running proving session
     pub fn stop(&self, client: &Client) -> Result<(), SdkErr> {
         let url = format!("{}/sessions/stop/{}", client.url, self.uuid);
-        let res = client.client.get(url).send()?;
+        let res = client.send(|| client.client.get(&url))?;
         if !res.status().is_success() {
             let body = res.text()?;
             return Err(SdkErr::InternalServerErr(body));
@@ -287,7 +308,7 @@ impl SnarkId {
     /// Fetches the current status of the Snark Session
     pub fn status(&self, client: &Client) -> Result<SnarkStatusRes, SdkErr> {
         let url = format!("{}/snark/status/{}", client.url, self.uuid);
-        let res = client.client.get(url).send()?;
+        let res = client.send(|| client.client.get(&url))?;
 
         if !res.status().is_success() {
             let body = res.text()?;
@@ -297,11 +318,200 @@ impl SnarkId {
     }
 }
 
+/// How a [Client] retries requests that fail transiently
+///
+/// Connection errors, timeouts and responses with a 5xx or 429 status are
+/// retried. Before each retry the client waits for the `Retry-After` of the
+/// response, if any, and otherwise for an exponentially growing backoff with
+/// random jitter, so that many clients failing together do not retry in step.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Number of retries after the first attempt
+    pub max_retries: u32,
+    /// Backoff before the first retry, doubled for each retry after it
+    pub initial_backoff: Duration,
+    /// Upper bound on the backoff before any retry
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that makes every request once
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    /// The backoff before the retry numbered `attempt`, counting from 0
+    ///
+    /// This is between half and all of the exponential backoff for the
+    /// attempt, capped at [RetryPolicy::max_backoff].
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(1 << attempt.min(31))
+            .min(self.max_backoff);
+        backoff / 2 + backoff.mul_f64(random_u64() as f64 / u64::MAX as f64) / 2
+    }
+
+    /// The delay before the retry numbered `attempt`, which honors the
+    /// `Retry-After` of the failed response, if any
+    pub(crate) fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        match retry_after {
+            Some(retry_after) => retry_after.min(self.max_backoff),
+            None => self.backoff(attempt),
+        }
+    }
+}
+
+/// A random number from the randomly keyed hasher of the standard library,
+/// which is plenty for jitter and for idempotency keys.
+fn random_u64() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+pub(crate) fn idempotency_key() -> String {
+    format!("{:016x}{:016x}", random_u64(), random_u64())
+}
+
+/// Why an attempt at a request failed
+pub(crate) enum Failure {
+    /// A failure that may not recur, with how long the server asked to wait
+    Transient(SdkErr, Option<Duration>),
+    Permanent(SdkErr),
+}
+
+impl Failure {
+    pub(crate) fn request(err: reqwest::Error) -> Self {
+        if err.is_timeout() || err.is_connect() || err.is_request() || err.is_body() {
+            Self::Transient(err.into(), None)
+        } else {
+            Self::Permanent(err.into())
+        }
+    }
+
+    fn status(res: Response) -> Self {
+        let status = res.status();
+        let retry_after = retry_after(res.headers());
+        Self::from_status(status, retry_after, res.text())
+    }
+
+    /// The failure for a response with an error `status` and `body`
+    pub(crate) fn from_status(
+        status: StatusCode,
+        retry_after: Option<Duration>,
+        body: Result<String, reqwest::Error>,
+    ) -> Self {
+        let err = match body {
+            Ok(body) => SdkErr::InternalServerErr(body),
+            Err(err) => err.into(),
+        };
+        if is_transient(status) {
+            Self::Transient(err, retry_after)
+        } else {
+            Self::Permanent(err)
+        }
+    }
+}
+
+pub(crate) fn is_transient(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+pub(crate) fn retry_after(headers: &header::HeaderMap) -> Option<Duration> {
+    headers
+        .get(header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok()?.parse().ok())
+        .map(Duration::from_secs)
+}
+
+/// The checksum header of S3, with the base64 SHA-256 of the object
+pub(crate) const CHECKSUM_HEADER: &str = "x-amz-checksum-sha256";
+
+/// Check downloaded `data` against the `Content-Length` and checksum of the
+/// response it came in
+pub(crate) fn check_download(
+    data: &[u8],
+    len: Option<u64>,
+    checksum: Option<&header::HeaderValue>,
+) -> Result<(), Failure> {
+    let corrupt = |msg| Failure::Transient(SdkErr::CorruptDownload(msg), None);
+    if let Some(len) = len.filter(|&len| len != data.len() as u64) {
+        return Err(corrupt(format!("received {} bytes of {len}", data.len())));
+    }
+    if let Some(checksum) = checksum {
+        let actual = BASE64.encode(Sha256::digest(data));
+        if checksum.as_bytes() != actual.as_bytes() {
+            return Err(corrupt(format!(
+                "SHA-256 is {actual}, expected {checksum:?}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Data to upload, which can be read again from any offset to retry or resume
+/// an upload
+enum UploadBody<'a> {
+    Buffer(Bytes),
+    File(&'a Path),
+}
+
+impl UploadBody<'_> {
+    fn len(&self) -> Result<u64, SdkErr> {
+        match self {
+            Self::Buffer(buf) => Ok(buf.len() as u64),
+            Self::File(path) => Ok(std::fs::metadata(path)?.len()),
+        }
+    }
+
+    fn slice(&self, start: u64, len: u64) -> Result<Body, SdkErr> {
+        match self {
+            Self::Buffer(buf) => Ok(buf.slice(start as usize..(start + len) as usize).into()),
+            Self::File(path) => {
+                let mut file = File::open(path)?;
+                file.seek(SeekFrom::Start(start))?;
+                Ok(Body::sized(file.take(len), len))
+            }
+        }
+    }
+}
+
+/// The number of bytes that a server reports having stored in the `Range`
+/// header of a `308 Resume Incomplete` response
+fn stored_len(res: &Response) -> u64 {
+    res.headers()
+        .get(header::RANGE)
+        .and_then(|range| {
+            let (_, end) = range
+                .to_str()
+                .ok()?
+                .strip_prefix("bytes=")?
+                .split_once('-')?;
+            end.parse::<u64>().ok()
+        })
+        .map_or(0, |end| end + 1)
+}
+
 /// Represents a client of the REST api
 #[derive(Clone)]
 pub struct Client {
     pub(crate) url: String,
     pub(crate) client: BlockingClient,
+    retry: RetryPolicy,
+    chunk_size: Option<u64>,
+    trace_context: header::HeaderMap,
 }
 
 enum ImageExistsOpt {
@@ -309,14 +519,18 @@ enum ImageExistsOpt {
     New(ImgUploadRes),
 }
 
-/// Creates a [reqwest::Client] for internal connection pooling
-fn construct_req_client(api_key: &str, version: &str) -> Result<BlockingClient, SdkErr> {
+/// The headers sent with every request
+pub(crate) fn default_headers(api_key: &str, version: &str) -> Result<header::HeaderMap, SdkErr> {
     let mut headers = header::HeaderMap::new();
     headers.insert(API_KEY_HEADER, header::HeaderValue::from_str(api_key)?);
     headers.insert(VERSION_HEADER, header::HeaderValue::from_str(version)?);
+    Ok(headers)
+}
 
+/// Creates a [reqwest::Client] for internal connection pooling
+fn construct_req_client(api_key: &str, version: &str) -> Result<BlockingClient, SdkErr> {
     Ok(BlockingClient::builder()
-        .default_headers(headers)
+        .default_headers(default_headers(api_key, version)?)
         .pool_max_idle_per_host(0)
         .build()?)
 }
@@ -345,6 +559,9 @@ impl Client {
         Ok(Self {
             url: api_url.to_string(),
             client,
+            retry: RetryPolicy::default(),
+            chunk_size: None,
+            trace_context: header::HeaderMap::new(),
         })
     }
 
@@ -362,15 +579,93 @@ impl Client {
     pub fn from_parts(url: String, key: String, risc0_version: &str) -> Result<Self, SdkErr> {
         let client = construct_req_client(&key, risc0_version)?;
         let url = url.strip_suffix('/').unwrap_or(&url).to_string();
-        Ok(Self { url, client })
+        Ok(Self {
+            url,
+            client,
+            retry: RetryPolicy::default(),
+            chunk_size: None,
+            trace_context: header::HeaderMap::new(),
+        })
+    }
+
+    /// Set how the client retries requests that fail transiently
+    ///
+    /// By default, the client uses [RetryPolicy::default].
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Upload images, inputs and receipts larger than `chunk_size` bytes in
+    /// chunks, resuming from the last stored chunk when an upload fails
+    ///
+    /// Chunks are sent as `PUT` requests with a `Content-Range` header, as in
+    /// the resumable uploads of Google Cloud Storage: the server answers every
+    /// chunk but the last with `308 Resume Incomplete` and a `Range` header
+    /// covering the bytes it has stored, and a `PUT` with a `Content-Range` of
+    /// `bytes */<total>` asks it for that range after a failure. Use this only
+    /// with upload URLs that support this protocol; by default, every upload is
+    /// a single request.
+    pub fn with_chunk_size(mut self, chunk_size: u64) -> Self {
+        self.chunk_size = Some(chunk_size.max(1));
+        self
+    }
+
+    /// Send a trace context, such as the W3C `traceparent` and `tracestate` of
+    /// the caller, as headers of every request, so that the server can
+    /// continue its trace
+    ///
+    /// Entries that are not valid headers are skipped.
+    pub fn with_trace_context(mut self, trace_context: &HashMap<String, String>) -> Self {
+        self.trace_context = trace_context
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((
+                    header::HeaderName::from_bytes(name.as_bytes()).ok()?,
+                    header::HeaderValue::from_str(value).ok()?,
+                ))
+            })
+            .collect();
+        self
+    }
+
+    /// Make an attempt at a request until it succeeds, fails permanently or
+    /// runs out of retries
+    fn retry<T>(&self, mut attempt: impl FnMut() -> Result<T, Failure>) -> Result<T, SdkErr> {
+        let mut retries = 0;
+        loop {
+            match attempt() {
+                Ok(value) => return Ok(value),
+                Err(Failure::Permanent(err)) => return Err(err),
+                Err(Failure::Transient(err, _)) if retries >= self.retry.max_retries => {
+                    return Err(err)
+                }
+                Err(Failure::Transient(_, retry_after)) => {
+                    std::thread::sleep(self.retry.delay(retries, retry_after));
+                    retries += 1;
+                }
+            }
+        }
+    }
+
+    /// Send a request, retrying it on transient failures
+    ///
+    /// The response may still have an error status, which callers check.
+    pub(crate) fn send(&self, request: impl Fn() -> RequestBuilder) -> Result<Response, SdkErr> {
+        self.retry(|| {
+            let request = request().headers(self.trace_context.clone());
+            match request.send() {
+                Ok(res) if is_transient(res.status()) => Err(Failure::status(res)),
+                Ok(res) => Ok(res),
+                Err(err) => Err(Failure::request(err)),
+            }
+        })
     }
 
     /// Fetch a upload presigned url for a given route
     fn get_upload_url(&self, route: &str) -> Result<UploadRes, SdkErr> {
-        let res = self
-            .client
-            .get(format!("{}/{}/upload", self.url, route))
-            .send()?;
+        let url = format!("{}/{}/upload", self.url, route);
+        let res = self.send(|| self.client.get(&url))?;
 
         if !res.status().is_success() {
             let body = res.text()?;
@@ -381,10 +676,8 @@ impl Client {
     }
 
     fn get_image_upload_url(&self, image_id: &str) -> Result<ImageExistsOpt, SdkErr> {
-        let res = self
-            .client
-            .get(format!("{}/images/upload/{}", self.url, image_id))
-            .send()?;
+        let url = format!("{}/images/upload/{}", self.url, image_id);
+        let res = self.send(|| self.client.get(&url))?;
 
         if res.status() == 204 {
             return Ok(ImageExistsOpt::Exists);
@@ -399,16 +692,91 @@ impl Client {
     }
 
     /// Upload body to a given URL
-    fn put_data<T: Into<reqwest::blocking::Body>>(&self, url: &str, body: T) -> Result<(), SdkErr> {
-        let res = self.client.put(url).body(body).send()?;
-        if !res.status().is_success() {
-            let body = res.text()?;
-            return Err(SdkErr::InternalServerErr(body));
+    fn put_data(&self, url: &str, body: UploadBody) -> Result<(), SdkErr> {
+        let len = body.len()?;
+        match self.chunk_size {
+            Some(chunk_size) if len > chunk_size => self.put_chunks(url, &body, len, chunk_size),
+            _ => self.retry(|| {
+                let body = body.slice(0, len).map_err(Failure::Permanent)?;
+                let res = self
+                    .client
+                    .put(url)
+                    .body(body)
+                    .send()
+                    .map_err(Failure::request)?;
+                if !res.status().is_success() {
+                    return Err(Failure::status(res));
+                }
+                Ok(())
+            }),
         }
+    }
 
+    /// Upload body to a given URL in chunks, resuming after failures from
+    /// where the server stopped storing it
+    fn put_chunks(
+        &self,
+        url: &str,
+        body: &UploadBody,
+        len: u64,
+        chunk_size: u64,
+    ) -> Result<(), SdkErr> {
+        let mut stored = 0;
+        while stored < len {
+            let mut resume = false;
+            stored = self.retry(|| {
+                let mut start = stored;
+                // After a failure, the server may have kept none, part or all of the
+                // chunk, so ask it where to continue from.
+                if std::mem::replace(&mut resume, true) {
+                    start = self.query_stored_len(url, len)?;
+                    if start == len {
+                        return Ok(len);
+                    }
+                }
+                let end = len.min(start + chunk_size);
+                let chunk = body.slice(start, end - start).map_err(Failure::Permanent)?;
+                let res = self
+                    .client
+                    .put(url)
+                    .header(
+                        header::CONTENT_RANGE,
+                        format!("bytes {start}-{}/{len}", end - 1),
+                    )
+                    .body(chunk)
+                    .send()
+                    .map_err(Failure::request)?;
+                match res.status() {
+                    status if status.is_success() => Ok(len),
+                    StatusCode::PERMANENT_REDIRECT if stored_len(&res) > start => {
+                        Ok(stored_len(&res))
+                    }
+                    StatusCode::PERMANENT_REDIRECT => Err(Failure::Transient(
+                        SdkErr::InternalServerErr(format!("upload stalled at byte {start}")),
+                        None,
+                    )),
+                    _ => Err(Failure::status(res)),
+                }
+            })?;
+        }
         Ok(())
     }
 
+    /// Ask the server how many bytes of a chunked upload it has stored
+    fn query_stored_len(&self, url: &str, len: u64) -> Result<u64, Failure> {
+        let res = self
+            .client
+            .put(url)
+            .header(header::CONTENT_RANGE, format!("bytes */{len}"))
+            .send()
+            .map_err(Failure::request)?;
+        match res.status() {
+            status if status.is_success() => Ok(len),
+            StatusCode::PERMANENT_REDIRECT => Ok(stored_len(&res)),
+            _ => Err(Failure::status(res)),
+        }
+    }
+
     // - /images
 
     /// Upload a image buffer to the /images/ route
@@ -423,7 +791,7 @@ impl Client {
         match res_or_exists {
             ImageExistsOpt::Exists => Ok(true),
             ImageExistsOpt::New(upload_res) => {
-                self.put_data(&upload_res.url, buf)?;
+                self.put_data(&upload_res.url, UploadBody::Buffer(buf.into()))?;
                 Ok(false)
             }
         }
@@ -441,8 +809,7 @@ impl Client {
         match res_or_exists {
             ImageExistsOpt::Exists => Ok(true),
             ImageExistsOpt::New(upload_res) => {
-                let fd = File::open(path)?;
-                self.put_data(&upload_res.url, fd)?;
+                self.put_data(&upload_res.url, UploadBody::File(path))?;
                 Ok(false)
             }
         }
@@ -453,16 +820,14 @@ impl Client {
     /// Upload a input buffer to the /inputs/ route
     pub fn upload_input(&self, buf: Vec<u8>) -> Result<String, SdkErr> {
         let upload_data = self.get_upload_url("inputs")?;
-        self.put_data(&upload_data.url, buf)?;
+        self.put_data(&upload_data.url, UploadBody::Buffer(buf.into()))?;
         Ok(upload_data.uuid)
     }
 
     /// Upload a input file to the /inputs/ route
     pub fn upload_input_file(&self, path: &Path) -> Result<String, SdkErr> {
         let upload_data = self.get_upload_url("inputs")?;
-
-        let fd = File::open(path)?;
-        self.put_data(&upload_data.url, fd)?;
+        self.put_data(&upload_data.url, UploadBody::File(path))?;
 
         Ok(upload_data.uuid)
     }
@@ -472,16 +837,14 @@ impl Client {
     /// Upload a receipt buffer to the /receipts/ route
     pub fn upload_receipt(&self, buf: Vec<u8>) -> Result<String, SdkErr> {
         let upload_data = self.get_upload_url("receipts")?;
-        self.put_data(&upload_data.url, buf)?;
+        self.put_data(&upload_data.url, UploadBody::Buffer(buf.into()))?;
         Ok(upload_data.uuid)
     }
 
     /// Upload a receipt file to the /receipts/ route
     pub fn upload_receipt_file(&self, path: &Path) -> Result<String, SdkErr> {
         let upload_data = self.get_upload_url("receipts")?;
-
-        let fd = File::open(path)?;
-        self.put_data(&upload_data.url, fd)?;
+        self.put_data(&upload_data.url, UploadBody::File(path))?;
 
         Ok(upload_data.uuid)
     }
@@ -490,10 +853,8 @@ impl Client {
     ///
     /// Allows download of older receipts without checking the current session status.
     pub fn receipt_download(&self, session_id: &SessionId) -> Result<Vec<u8>, SdkErr> {
-        let res = self
-            .client
-            .get(format!("{}/receipts/{}", self.url, session_id.uuid))
-            .send()?;
+        let url = format!("{}/receipts/{}", self.url, session_id.uuid);
+        let res = self.send(|| self.client.get(&url))?;
 
         if !res.status().is_success() {
             if res.status() == reqwest::StatusCode::NOT_FOUND {
@@ -511,10 +872,8 @@ impl Client {
     ///
     /// Allows deletion of a specified image_id.
     pub fn image_delete(&self, image_id: &str) -> Result<(), SdkErr> {
-        let res = self
-            .client
-            .delete(format!("{}/images/{}", self.url, image_id))
-            .send()?;
+        let url = format!("{}/images/{}", self.url, image_id);
+        let res = self.send(|| self.client.delete(&url))?;
 
         if !res.status().is_success() {
             let body = res.text()?;
@@ -528,10 +887,8 @@ impl Client {
     ///
     /// Allows deletion of a specified input Uuid.
     pub fn input_delete(&self, input_uuid: &str) -> Result<(), SdkErr> {
-        let res = self
-            .client
-            .delete(format!("{}/inputs/{}", self.url, input_uuid))
-            .send()?;
+        let url = format!("{}/inputs/{}", self.url, input_uuid);
+        let res = self.send(|| self.client.delete(&url))?;
 
         if !res.status().is_success() {
             let body = res.text()?;
@@ -547,6 +904,9 @@ impl Client {
     ///
     /// Supply the image_id and input_id created from uploading those files in
     /// previous steps
+    ///
+    /// The request carries an idempotency key, so that retrying it after a
+    /// failure does not start a second session.
     pub fn create_session(
         &self,
         img_id: String,
@@ -561,7 +921,13 @@ impl Client {
             assumptions,
         };
 
-        let res = self.client.post(url).json(&req).send()?;
+        let key = idempotency_key();
+        let res = self.send(|| {
+            self.client
+                .post(&url)
+                .header(IDEMPOTENCY_KEY_HEADER, &key)
+                .json(&req)
+        })?;
 
         if !res.status().is_success() {
             let body = res.text()?;
@@ -578,10 +944,22 @@ impl Client {
     /// Download a given url to a buffer
     ///
     /// Useful to download a [SessionId] receipt_url
+    ///
+    /// The download is checked against the `Content-Length` of the response,
+    /// and against its `x-amz-checksum-sha256` if the server sends one, and is
+    /// retried if it does not match.
     pub fn download(&self, url: &str) -> Result<Vec<u8>, SdkErr> {
-        let data = self.client.get(url).send()?.bytes()?;
-
-        Ok(data.into())
+        self.retry(|| {
+            let res = self.client.get(url).send().map_err(Failure::request)?;
+            if !res.status().is_success() {
+                return Err(Failure::status(res));
+            }
+            let len = res.content_length();
+            let checksum = res.headers().get(CHECKSUM_HEADER).cloned();
+            let data = res.bytes().map_err(Failure::request)?;
+            check_download(&data, len, checksum.as_ref())?;
+            Ok(data.into())
+        })
     }
 
     // - /snark
@@ -590,12 +968,20 @@ impl Client {
     ///
     /// Supply a completed sessionId to convert the risc0 STARK proof into
     /// a SNARK proof that can be validated on ethereum-like blockchains
+    ///
+    /// Like [Client::create_session], the request carries an idempotency key.
     pub fn create_snark(&self, session_id: String) -> Result<SnarkId, SdkErr> {
         let url = format!("{}/snark/create", self.url);
 
         let snark_req = SnarkReq { session_id };
 
-        let res = self.client.post(url).json(&snark_req).send()?;
+        let key = idempotency_key();
+        let res = self.send(|| {
+            self.client
+                .post(&url)
+                .header(IDEMPOTENCY_KEY_HEADER, &key)
+                .json(&snark_req)
+        })?;
 
         if !res.status().is_success() {
             let body = res.text()?;
@@ -615,11 +1001,8 @@ impl Client {
     /// Fetches the risc0 zkvm supported versions as well as other
     /// sub-components of bonsai
     pub fn version(&self) -> Result<VersionInfo, SdkErr> {
-        Ok(self
-            .client
-            .get(format!("{}/version", self.url))
-            .send()?
-            .json::<VersionInfo>()?)
+        let url = format!("{}/version", self.url);
+        Ok(self.send(|| self.client.get(&url))?.json::<VersionInfo>()?)
     }
 
     // - /user
@@ -628,16 +1011,19 @@ impl Client {
     ///
     /// Returns the [Quotas] structure with relevant data on cycle budget, quotas etc.
     pub fn quotas(&self) -> Result<Quotas, SdkErr> {
-        Ok(self
-            .client
-            .get(format!("{}/user/quotas", self.url))
-            .send()?
-            .json::<Quotas>()?)
+        let url = format!("{}/user/quotas", self.url);
+        Ok(self.send(|| self.client.get(&url))?.json::<Quotas>()?)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        sync::{Arc, Mutex},
+    };
+
     use httpmock::prelude::*;
     use uuid::Uuid;
 
@@ -647,6 +1033,201 @@ mod tests {
     const TEST_ID: &str = "0x5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03";
     const TEST_VERSION: &str = "0.1.0";
 
+    fn fast_retries(max_retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_retries,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(10),
+        }
+    }
+
+    /// A request received by [scripted_server]: its request line, headers and body
+    struct Request {
+        line: String,
+        headers: Vec<(String, String)>,
+        body: Vec<u8>,
+    }
+
+    impl Request {
+        fn header(&self, name: &str) -> Option<&str> {
+            self.headers
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.as_str())
+        }
+    }
+
+    /// Serve one connection for each of `responses`, which are written verbatim
+    /// after the status line, and record the requests.
+    fn scripted_server(responses: Vec<&'static str>) -> (String, Arc<Mutex<Vec<Request>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        std::thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let mut headers = Vec::new();
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    match header.trim_end().split_once(": ") {
+                        Some((key, value)) => headers.push((key.to_string(), value.to_string())),
+                        None => break,
+                    }
+                }
+                let len = headers
+                    .iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case("content-length"))
+                    .map_or(0, |(_, value)| value.parse().unwrap());
+                let mut body = vec![0; len];
+                reader.read_exact(&mut body).unwrap();
+                recorded.lock().unwrap().push(Request {
+                    line: line.trim_end().to_string(),
+                    headers,
+                    body,
+                });
+                write!(stream, "HTTP/1.1 {response}").unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    #[test]
+    fn backoff_bounds() {
+        let retry = RetryPolicy::default();
+        for attempt in 0..40 {
+            let cap = (retry.initial_backoff * 2u32.pow(attempt.min(16))).min(retry.max_backoff);
+            let backoff = retry.backoff(attempt);
+            assert!(
+                cap / 2 <= backoff && backoff <= cap,
+                "{attempt}: {backoff:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn retry_transient_errors() {
+        let server = MockServer::start();
+        let unavailable = server.mock(|when, then| {
+            when.method(GET).path("/version");
+            then.status(503).body("busy");
+        });
+        let url = format!("http://{}", server.address());
+        let client = super::Client::from_parts(url, TEST_KEY.to_string(), TEST_VERSION)
+            .unwrap()
+            .with_retry_policy(fast_retries(2));
+        let err = client.version().err().unwrap();
+        assert!(matches!(err, SdkErr::InternalServerErr(body) if body == "busy"));
+        unavailable.assert_hits(3);
+
+        let not_found = server.mock(|when, then| {
+            when.method(DELETE).path(format!("/images/{TEST_ID}"));
+            then.status(404);
+        });
+        client.image_delete(TEST_ID).unwrap_err();
+        not_found.assert_hits(1);
+    }
+
+    #[test]
+    fn session_create_retry_is_idempotent() {
+        let (url, requests) = scripted_server(vec![
+            "503 Service Unavailable\r\nretry-after: 0\r\ncontent-length: 0\r\n\r\n",
+            "200 OK\r\ncontent-type: application/json\r\ncontent-length: 15\r\n\r\n{\"uuid\":\"abcd\"}",
+        ]);
+        let client = super::Client::from_parts(url, TEST_KEY.to_string(), TEST_VERSION)
+            .unwrap()
+            .with_retry_policy(fast_retries(1));
+        let session = client
+            .create_session(TEST_ID.to_string(), "input".to_string(), vec![])
+            .unwrap();
+        assert_eq!(session.uuid, "abcd");
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        let key = requests[0].header(IDEMPOTENCY_KEY_HEADER).unwrap();
+        assert_eq!(key.len(), 32);
+        assert_eq!(requests[1].header(IDEMPOTENCY_KEY_HEADER), Some(key));
+        assert_eq!(requests[0].body, requests[1].body);
+    }
+
+    #[test]
+    fn chunked_upload_resumes() {
+        let (url, requests) = scripted_server(vec![
+            "308 Resume Incomplete\r\nrange: bytes=0-3\r\ncontent-length: 0\r\n\r\n",
+            "500 Internal Server Error\r\ncontent-length: 0\r\n\r\n",
+            // The server kept half of the failed chunk.
+            "308 Resume Incomplete\r\nrange: bytes=0-5\r\ncontent-length: 0\r\n\r\n",
+            "200 OK\r\ncontent-length: 0\r\n\r\n",
+        ]);
+        let client = super::Client::from_parts(url.clone(), TEST_KEY.to_string(), TEST_VERSION)
+            .unwrap()
+            .with_retry_policy(fast_retries(1))
+            .with_chunk_size(4);
+        let data: Vec<u8> = (0..10).collect();
+        client
+            .put_data(
+                &format!("{url}/upload"),
+                UploadBody::Buffer(data.clone().into()),
+            )
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        let sent: Vec<_> = requests
+            .iter()
+            .map(|req| {
+                assert_eq!(req.line, "PUT /upload HTTP/1.1");
+                (req.header("content-range").unwrap(), req.body.as_slice())
+            })
+            .collect();
+        assert_eq!(
+            sent,
+            [
+                ("bytes 0-3/10", &data[0..4]),
+                ("bytes 4-7/10", &data[4..8]),
+                ("bytes */10", &[][..]),
+                ("bytes 6-9/10", &data[6..10]),
+            ]
+        );
+    }
+
+    #[test]
+    fn download_checks_integrity() {
+        let server = MockServer::start();
+        let data = b"receipt";
+        let checksum = BASE64.encode(Sha256::digest(data));
+        server.mock(|when, then| {
+            when.method(GET).path("/good");
+            then.status(200)
+                .header(CHECKSUM_HEADER, &checksum)
+                .body(data);
+        });
+        let corrupt = server.mock(|when, then| {
+            when.method(GET).path("/corrupt");
+            then.status(200)
+                .header(CHECKSUM_HEADER, &checksum)
+                .body(b"receipu");
+        });
+        let missing = server.mock(|when, then| {
+            when.method(GET).path("/missing");
+            then.status(404);
+        });
+
+        let url = format!("http://{}", server.address());
+        let client = super::Client::from_parts(url.clone(), TEST_KEY.to_string(), TEST_VERSION)
+            .unwrap()
+            .with_retry_policy(fast_retries(1));
+        assert_eq!(client.download(&format!("{url}/good")).unwrap(), data);
+        let err = client.download(&format!("{url}/corrupt")).unwrap_err();
+        assert!(matches!(err, SdkErr::CorruptDownload(_)), "{err}");
+        corrupt.assert_hits(2);
+        client.download(&format!("{url}/missing")).unwrap_err();
+        missing.assert_hits(1);
+    }
+
     #[test]
     fn client_from_parts() {
         let url = "http://127.0.0.1/stage".to_string();
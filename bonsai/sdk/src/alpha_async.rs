@@ -12,9 +12,25 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::alpha::{
-    responses::{Quotas, SessionStatusRes, SnarkStatusRes},
-    Client, SdkErr, SessionId, SnarkId,
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use tokio::{sync::mpsc, task::JoinHandle};
+
+use crate::{
+    alpha::{
+        check_download, default_headers, idempotency_key,
+        responses::{
+            CreateSessRes, ImgUploadRes, ProofReq, Quotas, SessionStats, SessionStatusRes,
+            SnarkReceipt, SnarkReq, SnarkStatusRes, UploadRes,
+        },
+        retry_after, Client, Failure, RetryPolicy, SdkErr, SessionId, SnarkId, CHECKSUM_HEADER,
+    },
+    API_KEY_ENVVAR, API_URL_ENVVAR, IDEMPOTENCY_KEY_HEADER,
 };
 
 /// Construct a Bonsai SDK Client from env var
@@ -142,3 +158,674 @@ pub async fn quotas(bonsai_client: Client) -> Result<Quotas, SdkErr> {
         .await
         .map_err(|err| SdkErr::InternalServerErr(format!("{err}")))?
 }
+
+/// A client of the REST api that makes its requests with async I/O
+///
+/// Unlike the functions above, which run a [Client] on the blocking thread
+/// pool of tokio, this client never blocks a thread, and [AsyncClient::prove]
+/// runs a whole proving workflow as a stream of [ProveStatus] updates. It
+/// retries requests as [Client] does, but uploads are always single requests.
+#[derive(Clone)]
+pub struct AsyncClient {
+    url: String,
+    client: reqwest::Client,
+    retry: RetryPolicy,
+    poll_interval: Duration,
+}
+
+impl AsyncClient {
+    /// Construct an [AsyncClient] from the BONSAI_API_URL and BONSAI_API_KEY
+    /// environment variables
+    pub fn from_env(risc0_version: &str) -> Result<Self, SdkErr> {
+        let url = std::env::var(API_URL_ENVVAR).map_err(|_| SdkErr::MissingApiUrl)?;
+        let key = std::env::var(API_KEY_ENVVAR).map_err(|_| SdkErr::MissingApiKey)?;
+        Self::from_parts(url, key, risc0_version)
+    }
+
+    /// Construct an [AsyncClient] from url, api key, and zkvm version
+    pub fn from_parts(url: String, key: String, risc0_version: &str) -> Result<Self, SdkErr> {
+        let client = reqwest::Client::builder()
+            .default_headers(default_headers(&key, risc0_version)?)
+            .build()?;
+        Ok(Self {
+            url: url.strip_suffix('/').unwrap_or(&url).to_string(),
+            client,
+            retry: RetryPolicy::default(),
+            poll_interval: Duration::from_secs(5),
+        })
+    }
+
+    /// Set how the client retries requests that fail transiently
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Set how often [AsyncClient::prove] polls the status of a workflow,
+    /// which is every 5 seconds by default
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    async fn retry<T, F>(&self, mut attempt: impl FnMut() -> F) -> Result<T, SdkErr>
+    where
+        F: Future<Output = Result<T, Failure>>,
+    {
+        let mut retries = 0;
+        loop {
+            match attempt().await {
+                Ok(value) => return Ok(value),
+                Err(Failure::Permanent(err)) => return Err(err),
+                Err(Failure::Transient(err, _)) if retries >= self.retry.max_retries => {
+                    return Err(err)
+                }
+                Err(Failure::Transient(_, retry_after)) => {
+                    tokio::time::sleep(self.retry.delay(retries, retry_after)).await;
+                    retries += 1;
+                }
+            }
+        }
+    }
+
+    /// Send a request, retrying it on transient failures, and fail unless it
+    /// succeeds
+    async fn send(
+        &self,
+        request: impl Fn() -> reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, SdkErr> {
+        self.retry(|| async {
+            let res = request().send().await.map_err(Failure::request)?;
+            if !res.status().is_success() {
+                let status = res.status();
+                let retry_after = retry_after(res.headers());
+                return Err(Failure::from_status(status, retry_after, res.text().await));
+            }
+            Ok(res)
+        })
+        .await
+    }
+
+    async fn put_data(&self, url: &str, buf: Vec<u8>) -> Result<(), SdkErr> {
+        let buf = bytes::Bytes::from(buf);
+        self.send(|| self.client.put(url).body(buf.clone())).await?;
+        Ok(())
+    }
+
+    async fn upload(&self, route: &str, buf: Vec<u8>) -> Result<String, SdkErr> {
+        let url = format!("{}/{route}/upload", self.url);
+        let res: UploadRes = self.send(|| self.client.get(&url)).await?.json().await?;
+        self.put_data(&res.url, buf).await?;
+        Ok(res.uuid)
+    }
+
+    /// Upload an image, unless Bonsai already has it
+    ///
+    /// The boolean return indicates if the image already exists in bonsai
+    pub async fn upload_img(&self, image_id: &str, buf: Vec<u8>) -> Result<bool, SdkErr> {
+        let url = format!("{}/images/upload/{image_id}", self.url);
+        let res = self.send(|| self.client.get(&url)).await?;
+        if res.status() == reqwest::StatusCode::NO_CONTENT {
+            return Ok(true);
+        }
+        let res: ImgUploadRes = res.json().await?;
+        self.put_data(&res.url, buf).await?;
+        Ok(false)
+    }
+
+    /// Upload an input buffer, returning its UUID
+    pub async fn upload_input(&self, buf: Vec<u8>) -> Result<String, SdkErr> {
+        self.upload("inputs", buf).await
+    }
+
+    /// Upload a receipt buffer, returning its UUID
+    pub async fn upload_receipt(&self, buf: Vec<u8>) -> Result<String, SdkErr> {
+        self.upload("receipts", buf).await
+    }
+
+    /// Create a new proof request Session, with an idempotency key
+    pub async fn create_session(
+        &self,
+        img_id: String,
+        input_id: String,
+        assumptions: Vec<String>,
+    ) -> Result<SessionId, SdkErr> {
+        let url = format!("{}/sessions/create", self.url);
+        let req = ProofReq {
+            img: img_id,
+            input: input_id,
+            assumptions,
+        };
+        let key = idempotency_key();
+        let res: CreateSessRes = self
+            .send(|| {
+                self.client
+                    .post(&url)
+                    .header(IDEMPOTENCY_KEY_HEADER, &key)
+                    .json(&req)
+            })
+            .await?
+            .json()
+            .await?;
+        Ok(SessionId::new(res.uuid))
+    }
+
+    /// Fetches the current status of the Session
+    pub async fn session_status(&self, session: &SessionId) -> Result<SessionStatusRes, SdkErr> {
+        let url = format!("{}/sessions/status/{}", self.url, session.uuid);
+        Ok(self.send(|| self.client.get(&url)).await?.json().await?)
+    }
+
+    /// Stops a running proving session
+    pub async fn session_stop(&self, session: &SessionId) -> Result<(), SdkErr> {
+        let url = format!("{}/sessions/stop/{}", self.url, session.uuid);
+        self.send(|| self.client.get(&url)).await?;
+        Ok(())
+    }
+
+    /// Requests a SNARK proof be created from a existing sessionId, with an
+    /// idempotency key
+    pub async fn create_snark(&self, session_id: String) -> Result<SnarkId, SdkErr> {
+        let url = format!("{}/snark/create", self.url);
+        let req = SnarkReq { session_id };
+        let key = idempotency_key();
+        let res: CreateSessRes = self
+            .send(|| {
+                self.client
+                    .post(&url)
+                    .header(IDEMPOTENCY_KEY_HEADER, &key)
+                    .json(&req)
+            })
+            .await?
+            .json()
+            .await?;
+        Ok(SnarkId::new(res.uuid))
+    }
+
+    /// Fetches the current status of the Snark Session
+    pub async fn snark_status(&self, snark: &SnarkId) -> Result<SnarkStatusRes, SdkErr> {
+        let url = format!("{}/snark/status/{}", self.url, snark.uuid);
+        Ok(self.send(|| self.client.get(&url)).await?.json().await?)
+    }
+
+    /// Download a given url to a buffer, checking it as [Client::download]
+    /// does
+    pub async fn download(&self, url: &str) -> Result<Vec<u8>, SdkErr> {
+        self.retry(|| async {
+            let res = self
+                .client
+                .get(url)
+                .send()
+                .await
+                .map_err(Failure::request)?;
+            if !res.status().is_success() {
+                let status = res.status();
+                let retry_after = retry_after(res.headers());
+                return Err(Failure::from_status(status, retry_after, res.text().await));
+            }
+            let len = res.content_length();
+            let checksum = res.headers().get(CHECKSUM_HEADER).cloned();
+            let data = res.bytes().await.map_err(Failure::request)?;
+            check_download(&data, len, checksum.as_ref())?;
+            Ok(data.into())
+        })
+        .await
+    }
+
+    /// Prove an image on Bonsai, reporting the progress of the workflow
+    ///
+    /// The workflow runs on a tokio task, which uploads the image, input and
+    /// assumptions, starts a session, polls its status and downloads the
+    /// receipt. The returned stream yields a [ProveStatus] each time the
+    /// workflow moves on, and ends after [ProveStatus::Succeeded] or an error.
+    /// Dropping the stream, or calling [ProveStream::cancel], stops the session.
+    pub fn prove(&self, request: ProveRequest) -> ProveStream {
+        let (updates, rx) = mpsc::channel(16);
+        let client = self.clone();
+        let task = tokio::spawn(async move {
+            let mut running = None;
+            let outcome = tokio::select! {
+                result = client.run(request, &updates, &mut running) => Some(result),
+                _ = updates.closed() => None,
+            };
+            match outcome {
+                Some(Ok(())) => Ok(()),
+                Some(Err(err)) => {
+                    updates.send(Err(err)).await.ok();
+                    Ok(())
+                }
+                // The stream was cancelled or dropped.
+                None => match running {
+                    Some(session) => client.session_stop(&session).await,
+                    None => Ok(()),
+                },
+            }
+        });
+        ProveStream { updates: rx, task }
+    }
+
+    /// Run the workflow of [AsyncClient::prove], keeping the ID of the session
+    /// in `running` while it runs
+    async fn run(
+        &self,
+        request: ProveRequest,
+        updates: &mpsc::Sender<Result<ProveStatus, SdkErr>>,
+        running: &mut Option<SessionId>,
+    ) -> Result<(), SdkErr> {
+        // Sending only fails once the stream is gone, which ends the workflow.
+        let update = |status| async move {
+            updates.send(Ok(status)).await.ok();
+        };
+
+        update(ProveStatus::Uploading).await;
+        self.upload_img(&request.image_id, request.image).await?;
+        let input_id = self.upload_input(request.input).await?;
+        let mut assumptions = Vec::with_capacity(request.assumptions.len());
+        for receipt in request.assumptions {
+            assumptions.push(self.upload_receipt(receipt).await?);
+        }
+        let session = self
+            .create_session(request.image_id, input_id, assumptions)
+            .await?;
+        *running = Some(session.clone());
+        update(ProveStatus::Submitted(session.clone())).await;
+
+        let mut reported = None;
+        let res = loop {
+            let res = self.session_status(&session).await?;
+            if res.status != "RUNNING" {
+                break res;
+            }
+            if reported.as_ref() != Some(&res.state) {
+                update(ProveStatus::from_state(res.state.as_deref())).await;
+                reported = Some(res.state);
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        };
+        *running = None;
+        if res.status != "SUCCEEDED" {
+            return Err(SdkErr::WorkflowFailed(
+                session.uuid,
+                format!("{} err: {}", res.status, res.error_msg.unwrap_or_default()),
+            ));
+        }
+        let receipt_url = res.receipt_url.ok_or_else(|| {
+            SdkErr::InternalServerErr("missing receipt on completed session".into())
+        })?;
+        let receipt = self.download(&receipt_url).await?;
+
+        let snark = if request.snark {
+            update(ProveStatus::Compressing).await;
+            let snark = self.create_snark(session.uuid.clone()).await?;
+            loop {
+                let res = self.snark_status(&snark).await?;
+                match res.status.as_str() {
+                    "RUNNING" => tokio::time::sleep(self.poll_interval).await,
+                    "SUCCEEDED" => {
+                        break Some(res.output.ok_or_else(|| {
+                            SdkErr::InternalServerErr("missing output on completed snark".into())
+                        })?)
+                    }
+                    _ => {
+                        return Err(SdkErr::WorkflowFailed(
+                            snark.uuid,
+                            format!("{} err: {}", res.status, res.error_msg.unwrap_or_default()),
+                        ))
+                    }
+                }
+            }
+        } else {
+            None
+        };
+
+        update(ProveStatus::Succeeded(ProveOutput {
+            session,
+            receipt,
+            stats: res.stats,
+            snark,
+        }))
+        .await;
+        Ok(())
+    }
+}
+
+/// What to prove with [AsyncClient::prove]
+pub struct ProveRequest {
+    /// The image ID, in hex
+    pub image_id: String,
+    /// The image: ELF file bytes, or a bincode encoded MemoryImage
+    pub image: Vec<u8>,
+    /// The input of the guest
+    pub input: Vec<u8>,
+    /// Receipts of the assumptions of the guest, encoded with bincode
+    pub assumptions: Vec<Vec<u8>>,
+    /// Whether to compress the STARK receipt of the session into a SNARK
+    pub snark: bool,
+}
+
+/// The progress of a proving workflow, yielded by a [ProveStream]
+#[derive(Debug)]
+pub enum ProveStatus {
+    /// Uploading the image, input and assumptions
+    Uploading,
+    /// Bonsai created the session with this ID
+    Submitted(SessionId),
+    /// The session is waiting to run
+    Queued,
+    /// The session is executing the guest, to split it into segments
+    Executing,
+    /// The session is proving its segments
+    ProvingSegments {
+        /// The number of segments proven
+        proven: u32,
+        /// The number of segments
+        total: u32,
+    },
+    /// The session is lifting and joining the proofs of its segments, and
+    /// resolving its assumptions, into a single receipt
+    Joining,
+    /// The session succeeded, and its receipt is being compressed into a SNARK
+    Compressing,
+    /// The session is in a state that this version of the SDK does not know
+    Running(String),
+    /// The workflow succeeded
+    Succeeded(ProveOutput),
+}
+
+impl ProveStatus {
+    fn from_state(state: Option<&str>) -> Self {
+        let Some(state) = state else {
+            return Self::Queued;
+        };
+        if let Some((proven, total)) = state
+            .strip_prefix("ProveSegments: ")
+            .and_then(|progress| progress.split_once('/'))
+        {
+            if let (Ok(proven), Ok(total)) = (proven.parse(), total.parse()) {
+                return Self::ProvingSegments { proven, total };
+            }
+        }
+        match state {
+            "Setup" => Self::Queued,
+            "Executor" => Self::Executing,
+            "Planner" | "Recursion" | "Resolve" | "Finalize" => Self::Joining,
+            _ if state.starts_with("RecursionJoin") => Self::Joining,
+            _ => Self::Running(state.to_string()),
+        }
+    }
+}
+
+/// The result of a proving workflow
+#[derive(Debug)]
+pub struct ProveOutput {
+    /// The session that proved the image
+    pub session: SessionId,
+    /// The receipt of the session, encoded with bincode
+    pub receipt: Vec<u8>,
+    /// Statistics of the session
+    pub stats: Option<SessionStats>,
+    /// The SNARK receipt, if it was requested
+    pub snark: Option<SnarkReceipt>,
+}
+
+/// The stream of [ProveStatus] updates of a workflow started by
+/// [AsyncClient::prove]
+///
+/// Besides [ProveStream::next], this implements [futures_core::Stream], for
+/// use with stream combinators and server-sent events.
+pub struct ProveStream {
+    updates: mpsc::Receiver<Result<ProveStatus, SdkErr>>,
+    task: JoinHandle<Result<(), SdkErr>>,
+}
+
+impl ProveStream {
+    /// Wait for the next update, or [None] once the workflow has ended
+    pub async fn next(&mut self) -> Option<Result<ProveStatus, SdkErr>> {
+        self.updates.recv().await
+    }
+
+    /// Stop the workflow, and the session on Bonsai if it is still running
+    pub async fn cancel(mut self) -> Result<(), SdkErr> {
+        self.updates.close();
+        match (&mut self.task).await {
+            Ok(result) => result,
+            Err(err) => Err(SdkErr::InternalServerErr(format!("{err}"))),
+        }
+    }
+}
+
+impl futures_core::Stream for ProveStream {
+    type Item = Result<ProveStatus, SdkErr>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.updates.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use httpmock::prelude::*;
+
+    use super::*;
+
+    const TEST_KEY: &str = "TESTKEY";
+    const TEST_ID: &str = "5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03";
+    const TEST_VERSION: &str = "0.1.0";
+
+    fn running(state: Option<&str>) -> SessionStatusRes {
+        SessionStatusRes {
+            status: "RUNNING".to_string(),
+            receipt_url: None,
+            error_msg: None,
+            state: state.map(str::to_string),
+            elapsed_time: None,
+            stats: None,
+        }
+    }
+
+    /// Mock the routes that [AsyncClient::prove] uses to start a session with
+    /// the UUID `session`.
+    async fn mock_submission(server: &MockServer, session: &str) {
+        let upload = UploadRes {
+            url: server.url("/upload"),
+            uuid: "input".to_string(),
+        };
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/images/upload/{TEST_ID}"));
+                then.status(204);
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/inputs/upload");
+                then.status(200).json_body_obj(&upload);
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(PUT).path("/upload").body("input");
+                then.status(200);
+            })
+            .await;
+        let created = CreateSessRes {
+            uuid: session.to_string(),
+        };
+        server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path("/sessions/create")
+                    .header_exists(IDEMPOTENCY_KEY_HEADER);
+                then.status(200).json_body_obj(&created);
+            })
+            .await;
+    }
+
+    fn client(server: &MockServer) -> AsyncClient {
+        AsyncClient::from_parts(server.base_url(), TEST_KEY.to_string(), TEST_VERSION)
+            .unwrap()
+            .with_poll_interval(Duration::from_millis(5))
+    }
+
+    fn request() -> ProveRequest {
+        ProveRequest {
+            image_id: TEST_ID.to_string(),
+            image: vec![],
+            input: b"input".to_vec(),
+            assumptions: vec![],
+            snark: false,
+        }
+    }
+
+    #[test]
+    fn status_from_state() {
+        assert!(matches!(ProveStatus::from_state(None), ProveStatus::Queued));
+        assert!(matches!(
+            ProveStatus::from_state(Some("Executor")),
+            ProveStatus::Executing
+        ));
+        assert!(matches!(
+            ProveStatus::from_state(Some("ProveSegments: 3/10")),
+            ProveStatus::ProvingSegments {
+                proven: 3,
+                total: 10
+            }
+        ));
+        assert!(matches!(
+            ProveStatus::from_state(Some("RecursionJoin: 1/4")),
+            ProveStatus::Joining
+        ));
+        assert!(
+            matches!(ProveStatus::from_state(Some("InProgress")), ProveStatus::Running(state) if state == "InProgress")
+        );
+    }
+
+    #[tokio::test]
+    async fn prove_streams_status() {
+        let server = MockServer::start_async().await;
+        mock_submission(&server, "session").await;
+        let mut status = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/sessions/status/session");
+                then.status(200).json_body_obj(&running(Some("Executor")));
+            })
+            .await;
+
+        let mut stream = client(&server).prove(request());
+        assert!(matches!(
+            stream.next().await,
+            Some(Ok(ProveStatus::Uploading))
+        ));
+        assert!(
+            matches!(stream.next().await, Some(Ok(ProveStatus::Submitted(session))) if session.uuid == "session")
+        );
+        assert!(matches!(
+            stream.next().await,
+            Some(Ok(ProveStatus::Executing))
+        ));
+
+        // Move the session on, adding the next status before removing the last
+        // so that every poll finds one.
+        let succeeded = SessionStatusRes {
+            status: "SUCCEEDED".to_string(),
+            receipt_url: Some(server.url("/receipt")),
+            error_msg: None,
+            state: None,
+            elapsed_time: None,
+            stats: None,
+        };
+        for (state, res) in [
+            (
+                Some("ProveSegments: 1/2"),
+                running(Some("ProveSegments: 1/2")),
+            ),
+            (None, succeeded),
+        ] {
+            let next = server
+                .mock_async(|when, then| {
+                    when.method(GET).path("/sessions/status/session");
+                    then.status(200).json_body_obj(&res);
+                })
+                .await;
+            status.delete_async().await;
+            status = next;
+            if state.is_some() {
+                assert!(matches!(
+                    stream.next().await,
+                    Some(Ok(ProveStatus::ProvingSegments {
+                        proven: 1,
+                        total: 2
+                    }))
+                ));
+            } else {
+                server
+                    .mock_async(|when, then| {
+                        when.method(GET).path("/receipt");
+                        then.status(200).body("receipt");
+                    })
+                    .await;
+            }
+        }
+
+        let Some(Ok(ProveStatus::Succeeded(output))) = stream.next().await else {
+            panic!("workflow did not succeed");
+        };
+        assert_eq!(output.session.uuid, "session");
+        assert_eq!(output.receipt, b"receipt");
+        assert!(output.snark.is_none());
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn prove_reports_failure() {
+        let server = MockServer::start_async().await;
+        mock_submission(&server, "session").await;
+        let failed = SessionStatusRes {
+            status: "FAILED".to_string(),
+            error_msg: Some("guest panicked".to_string()),
+            ..running(None)
+        };
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/sessions/status/session");
+                then.status(200).json_body_obj(&failed);
+            })
+            .await;
+
+        let mut stream = client(&server).prove(request());
+        let err = loop {
+            match stream.next().await {
+                Some(Ok(_)) => continue,
+                Some(Err(err)) => break err,
+                None => panic!("workflow did not fail"),
+            }
+        };
+        assert_eq!(
+            err.to_string(),
+            "workflow session exited: FAILED err: guest panicked"
+        );
+    }
+
+    #[tokio::test]
+    async fn prove_cancel_stops_session() {
+        let server = MockServer::start_async().await;
+        mock_submission(&server, "session").await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/sessions/status/session");
+                then.status(200).json_body_obj(&running(Some("Executor")));
+            })
+            .await;
+        let stop = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/sessions/stop/session");
+                then.status(200);
+            })
+            .await;
+
+        let mut stream = client(&server).prove(request());
+        while !matches!(stream.next().await, Some(Ok(ProveStatus::Executing))) {}
+        stream.cancel().await.unwrap();
+        stop.assert_hits_async(1).await;
+    }
+}
@@ -130,6 +130,30 @@
 //!     Ok(())
 //! }
 //! ```
+//!
+//! ## Flaky networks
+//!
+//! The client retries requests that fail with a connection error, a timeout, or a
+//! 5xx or 429 status, with exponential backoff and jitter, and sends an
+//! idempotency key with requests that create sessions so that a retry does not
+//! start a second one. Downloads are checked against their length and SHA-256
+//! checksum. Large uploads can be split into resumable chunks, for upload URLs
+//! that support them:
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use bonsai_sdk::alpha::{Client, RetryPolicy};
+//!
+//! let client = Client::from_env(risc0_zkvm::VERSION)
+//!     .expect("Failed to construct sdk client")
+//!     .with_retry_policy(RetryPolicy {
+//!         max_retries: 10,
+//!         initial_backoff: Duration::from_secs(1),
+//!         max_backoff: Duration::from_secs(60),
+//!     })
+//!     .with_chunk_size(64 << 20);
+//! ```
 
 /// Bonsai Alpha SDK
 pub mod alpha;
@@ -141,6 +165,8 @@ pub mod alpha_async;
 pub const API_KEY_HEADER: &str = "x-api-key";
 /// HTTP header for the risc0 version string
 pub const VERSION_HEADER: &str = "x-risc0-version";
+/// HTTP header carrying the key that lets Bonsai deduplicate retried requests
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
 /// Environment variable name for the API url
 pub const API_URL_ENVVAR: &str = "BONSAI_API_URL";
 /// Environment variable name for the API key
@@ -15,10 +15,12 @@ CRATES = [
     ("risc0-circuit-rv32im", "default"),
     ("risc0-circuit-rv32im-sys", "default"),
     ("risc0-core", None),
+    ("risc0-ffi", None),
     ("risc0-groth16", None),
     ("risc0-r0vm", None),
     ("risc0-sys", None),
     ("risc0-tools", None),
+    ("risc0-wasm", None),
     ("risc0-zkp", "prove"),
     ("risc0-zkvm", "prove"),
     ("risc0-zkvm-platform", None),
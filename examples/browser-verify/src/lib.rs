@@ -12,4 +12,35 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-// This is intentionally blank, there may be content here later.
+//! JavaScript bindings for verifying RISC Zero receipts in the browser or in Node.js.
+//!
+//! Receipts are passed as [Uint8Array]s in the bincode encoding of [Receipt], and image IDs as
+//! hex strings. Every binding throws an `Error` with a description of the failure.
+//!
+//! [Uint8Array]: https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Global_Objects/Uint8Array
+
+use hex::FromHex;
+use risc0_zkvm::{sha::Digest, Receipt};
+use wasm_bindgen::prelude::*;
+
+fn decode_receipt(receipt: &[u8]) -> Result<Receipt, JsError> {
+    bincode::deserialize(receipt).map_err(|err| JsError::new(&format!("malformed receipt: {err}")))
+}
+
+/// Verify that the receipt proves a successful execution of the image with the given ID.
+#[wasm_bindgen(js_name = verifyReceipt)]
+pub fn verify_receipt(receipt: &[u8], image_id: &str) -> Result<(), JsError> {
+    let image_id = Digest::from_hex(image_id)
+        .map_err(|err| JsError::new(&format!("malformed image ID: {err}")))?;
+    decode_receipt(receipt)?
+        .verify(image_id)
+        .map_err(|err| JsError::new(&err.to_string()))
+}
+
+/// Return the journal of the receipt.
+///
+/// The journal is only authenticated once the receipt is verified with [verify_receipt].
+#[wasm_bindgen(js_name = receiptJournal)]
+pub fn receipt_journal(receipt: &[u8]) -> Result<Vec<u8>, JsError> {
+    Ok(decode_receipt(receipt)?.journal.bytes)
+}
@@ -12,7 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use risc0_zkvm::Receipt;
+use browser_verify::{receipt_journal, verify_receipt};
+use risc0_zkvm::{sha::Digest, Receipt};
 use risc0_zkvm_receipts::{FIB_ID, FIB_RECEIPT};
 use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
 
@@ -24,3 +25,14 @@ fn test_verify() {
     let receipt: Receipt = bincode::deserialize(FIB_RECEIPT).unwrap();
     receipt.verify(FIB_ID).unwrap();
 }
+
+#[wasm_bindgen_test]
+fn test_bindings() {
+    let image_id = Digest::from(FIB_ID).to_string();
+    verify_receipt(FIB_RECEIPT, &image_id).unwrap();
+    assert!(verify_receipt(FIB_RECEIPT, &Digest::ZERO.to_string()).is_err());
+    assert!(verify_receipt(&FIB_RECEIPT[1..], &image_id).is_err());
+
+    let receipt: Receipt = bincode::deserialize(FIB_RECEIPT).unwrap();
+    assert_eq!(receipt_journal(FIB_RECEIPT).unwrap(), receipt.journal.bytes);
+}
@@ -28,7 +28,7 @@ EXTENSIONS = [
 ]
 
 SKIP_DIRS = [
-    str(Path.cwd()) + "/templates/rust-starter",
+    str(Path.cwd()) + "/templates",
     str(Path.cwd()) + "/risc0/circuit/rv32im-sys/cxx/vendor",
 ]
 
@@ -0,0 +1,101 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate alloc;
+
+use alloc::{borrow::Cow, vec::Vec};
+
+use anyhow::{anyhow, ensure, Result};
+use miniz_oxide::{deflate::compress_to_vec, inflate::decompress_to_vec_with_limit};
+use risc0_zkvm_platform::memory::GUEST_MAX_MEM;
+
+// Compressed ELFs start with this magic number, which no ELF does, and the
+// length of the ELF, which bounds how much is decompressed.
+const MAGIC: [u8; 4] = *b"R0ZE";
+const HEADER_LEN: usize = MAGIC.len() + 4;
+
+// The longest ELF decompressed. Its header says how long it is, and without a
+// bound a small input could claim up to 4 GiB. The loadable segments of a guest
+// fit in its memory, which leaves ample room for the rest of the ELF.
+const MAX_ELF_LEN: usize = GUEST_MAX_MEM;
+
+/// Compress an ELF binary, such as a guest embedded in a host binary.
+///
+/// The compressed ELF is decompressed when it is loaded by [Program::load_elf]
+/// or [compute_image_id], so it can be passed wherever an ELF is, and its image
+/// ID is that of the ELF. Tools that parse the ELF themselves, and releases of
+/// risc0-binfmt before this format, cannot read it: ELFs for them, such as for
+/// a remote prover, should be passed through [decompress_elf].
+///
+/// [Program::load_elf]: crate::Program::load_elf
+/// [compute_image_id]: crate::compute_image_id
+pub fn compress_elf(elf: &[u8]) -> Vec<u8> {
+    let mut compressed = Vec::with_capacity(HEADER_LEN + elf.len() / 4);
+    compressed.extend(MAGIC);
+    compressed.extend((elf.len() as u32).to_le_bytes());
+    compressed.extend(compress_to_vec(elf, 9));
+    compressed
+}
+
+/// Return the ELF binary compressed by [compress_elf], or the input itself if
+/// it is not compressed.
+pub fn decompress_elf(input: &[u8]) -> Result<Cow<'_, [u8]>> {
+    let Some(header) = input
+        .get(..HEADER_LEN)
+        .filter(|header| header[..4] == MAGIC)
+    else {
+        return Ok(Cow::Borrowed(input));
+    };
+    let len = u32::from_le_bytes(header[4..].try_into().unwrap()) as usize;
+    ensure!(
+        len <= MAX_ELF_LEN,
+        "Invalid compressed ELF: {len} bytes exceeds the limit of {MAX_ELF_LEN}"
+    );
+    // Decompression stops as soon as the output passes `len`.
+    let elf = decompress_to_vec_with_limit(&input[HEADER_LEN..], len)
+        .map_err(|err| anyhow!("Invalid compressed ELF: {err}"))?;
+    ensure!(elf.len() == len, "Invalid compressed ELF: truncated");
+    Ok(Cow::Owned(elf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compress_elf, decompress_elf, MAX_ELF_LEN};
+
+    #[test]
+    fn round_trip() {
+        let elf: Vec<u8> = (0..4096u32).flat_map(|i| (i % 7).to_le_bytes()).collect();
+        let compressed = compress_elf(&elf);
+        assert!(compressed.len() < elf.len() / 4);
+        assert_eq!(decompress_elf(&compressed).unwrap(), elf);
+        assert_eq!(decompress_elf(&elf).unwrap(), elf);
+
+        assert!(decompress_elf(&compressed[..compressed.len() / 2]).is_err());
+        let mut long = compressed.clone();
+        long[5] -= 1;
+        assert!(decompress_elf(&long).is_err());
+    }
+
+    #[test]
+    fn reject_bombs() {
+        let mut bomb = compress_elf(&[0; 1 << 16]);
+        bomb[4..8].copy_from_slice(&(MAX_ELF_LEN as u32 + 1).to_le_bytes());
+        let err = decompress_elf(&bomb).unwrap_err().to_string();
+        assert!(err.contains("exceeds the limit"), "{err}");
+
+        // A header that understates the length stops decompression at it.
+        bomb[4..8].copy_from_slice(&1024u32.to_le_bytes());
+        assert!(decompress_elf(&bomb).is_err());
+    }
+}
@@ -14,24 +14,34 @@
 
 extern crate alloc;
 
-use alloc::collections::BTreeMap;
+use alloc::{collections::BTreeMap, vec::Vec};
 
 use anyhow::{anyhow, bail, Context, Result};
 use elf::{endian::LittleEndian, file::Class, ElfBytes};
 use risc0_zkvm_platform::WORD_SIZE;
 
+use crate::decompress_elf;
+
+const EHDR_SIZE: usize = 52;
+const PHDR_SIZE: usize = 32;
+
 /// A RISC Zero program
 pub struct Program {
     /// The entrypoint of the program
     pub entry: u32,
 
     /// The initial memory image
+    ///
+    /// This is sparse: any word not present is zero.
     pub image: BTreeMap<u32, u32>,
 }
 
 impl Program {
-    /// Initialize a RISC Zero Program from an appropriate ELF file
+    /// Initialize a RISC Zero Program from an appropriate ELF file, or one
+    /// compressed by [compress_elf](crate::compress_elf).
     pub fn load_elf(input: &[u8], max_mem: u32) -> Result<Program> {
+        let elf_bytes = decompress_elf(input)?;
+        let input: &[u8] = &elf_bytes;
         let mut image: BTreeMap<u32, u32> = BTreeMap::new();
         let elf = ElfBytes::<LittleEndian>::minimal_parse(input)
             .map_err(|err| anyhow!("Elf parse error: {err}"))?;
@@ -88,21 +98,180 @@ impl Program {
                     bail!("Address [0x{addr:08x}] exceeds maximum address for guest programs [0x{max_mem:08x}]");
                 }
                 if i >= file_size {
-                    // Past the file size, all zeros.
-                    image.insert(addr, 0);
-                } else {
-                    let mut word = 0;
-                    // Don't read past the end of the file.
-                    let len = core::cmp::min(file_size - i, WORD_SIZE as u32);
-                    for j in 0..len {
-                        let offset = (offset + i + j) as usize;
-                        let byte = input.get(offset).context("Invalid segment offset")?;
-                        word |= (*byte as u32) << (j * 8);
-                    }
-                    image.insert(addr, word);
+                    // Past the file size, all zeros. Memory is zero-initialized, so these words
+                    // are left out of the image rather than materialized.
+                    continue;
+                }
+                let mut word = 0;
+                // Don't read past the end of the file.
+                let len = core::cmp::min(file_size - i, WORD_SIZE as u32);
+                for j in 0..len {
+                    let offset = (offset + i + j) as usize;
+                    let byte = input.get(offset).context("Invalid segment offset")?;
+                    word |= (*byte as u32) << (j * 8);
+                }
+                image.insert(addr, word);
+            }
+        }
+        Ok(Program { entry, image })
+    }
+
+    /// Initialize a RISC Zero Program from a flat binary, such as the output
+    /// of `objcopy -O binary`, loaded at `load_addr` and entered at `entry`.
+    pub fn load_binary(input: &[u8], load_addr: u32, entry: u32, max_mem: u32) -> Result<Program> {
+        Self::load_regions([(load_addr, input)], entry, max_mem)
+    }
+
+    /// Initialize a RISC Zero Program from regions of bytes, each loaded at
+    /// the given address, such as a pre-linked kernel image and the user
+    /// program it starts.
+    ///
+    /// Each region must start on a word boundary, and the regions must not
+    /// overlap. The entrypoint must be in one of the regions.
+    pub fn load_regions<'a>(
+        regions: impl IntoIterator<Item = (u32, &'a [u8])>,
+        entry: u32,
+        max_mem: u32,
+    ) -> Result<Program> {
+        if entry >= max_mem || entry % WORD_SIZE as u32 != 0 {
+            bail!("Invalid entrypoint");
+        }
+        let mut image: BTreeMap<u32, u32> = BTreeMap::new();
+        // The end of each region, by its start.
+        let mut ranges: BTreeMap<u32, u32> = BTreeMap::new();
+        for (addr, bytes) in regions {
+            if addr % WORD_SIZE as u32 != 0 {
+                bail!("Region address {addr:08x} is unaligned");
+            }
+            let end = u32::try_from(bytes.len())
+                .ok()
+                .and_then(|len| addr.checked_add(len))
+                .filter(|&end| end <= max_mem)
+                .ok_or_else(|| {
+                    anyhow!("Region at [0x{addr:08x}] exceeds maximum address for guest programs [0x{max_mem:08x}]")
+                })?;
+            let prev = ranges.range(..end).next_back();
+            if prev.is_some_and(|(_, &prev_end)| prev_end > addr) {
+                bail!("Region at [0x{addr:08x}] overlaps another region");
+            }
+            ranges.insert(addr, end);
+            for (i, chunk) in bytes.chunks(WORD_SIZE).enumerate() {
+                let mut word = [0; WORD_SIZE];
+                word[..chunk.len()].copy_from_slice(chunk);
+                let word = u32::from_le_bytes(word);
+                // Memory is zero-initialized, so zero words are left out of the image.
+                if word != 0 {
+                    image.insert(addr + (i * WORD_SIZE) as u32, word);
                 }
             }
         }
+        let in_region = ranges
+            .range(..=entry)
+            .next_back()
+            .is_some_and(|(_, &end)| entry < end);
+        if !in_region {
+            bail!("Entrypoint [0x{entry:08x}] is not in a loaded region");
+        }
         Ok(Program { entry, image })
     }
 }
+
+/// Strip an ELF binary down to what the zkVM loads: its header and its
+/// loadable segments, without sections, symbols or debug info.
+///
+/// The stripped ELF has the same image ID. It cannot be profiled, as profiles
+/// name functions from the symbols and debug info.
+pub fn strip_elf(input: &[u8]) -> Result<Vec<u8>> {
+    let elf = ElfBytes::<LittleEndian>::minimal_parse(input)
+        .map_err(|err| anyhow!("Elf parse error: {err}"))?;
+    if elf.ehdr.class != Class::ELF32 {
+        bail!("Not a 32-bit ELF");
+    }
+    let segments = elf.segments().ok_or(anyhow!("Missing segment table"))?;
+    let loads: Vec<_> = segments
+        .iter()
+        .filter(|x| x.p_type == elf::abi::PT_LOAD)
+        .collect();
+
+    let mut output = Vec::new();
+    output.extend(&input[..16]);
+    output.extend(elf.ehdr.e_type.to_le_bytes());
+    output.extend(elf.ehdr.e_machine.to_le_bytes());
+    output.extend(elf.ehdr.version.to_le_bytes());
+    for word in [elf.ehdr.e_entry, EHDR_SIZE as u64, 0] {
+        output.extend((word as u32).to_le_bytes());
+    }
+    output.extend(elf.ehdr.e_flags.to_le_bytes());
+    for half in [EHDR_SIZE, PHDR_SIZE, loads.len(), 0, 0, 0] {
+        output.extend((half as u16).to_le_bytes());
+    }
+
+    // The segments follow the program headers, each on a word boundary.
+    let mut offset = EHDR_SIZE + PHDR_SIZE * loads.len();
+    let mut data = Vec::new();
+    for segment in &loads {
+        let start = segment.p_offset as usize;
+        let bytes = start
+            .checked_add(segment.p_filesz as usize)
+            .and_then(|end| input.get(start..end))
+            .context("Invalid segment offset")?;
+        for word in [
+            segment.p_type as u64,
+            offset as u64,
+            segment.p_vaddr,
+            segment.p_paddr,
+            segment.p_filesz,
+            segment.p_memsz,
+            segment.p_flags as u64,
+            WORD_SIZE as u64,
+        ] {
+            output.extend((word as u32).to_le_bytes());
+        }
+        data.extend(bytes);
+        data.resize(data.len().next_multiple_of(WORD_SIZE), 0);
+        offset = EHDR_SIZE + PHDR_SIZE * loads.len() + data.len();
+    }
+    output.extend(data);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Program;
+
+    const MAX_MEM: u32 = 0x1000_0000;
+
+    #[test]
+    fn load_binary() {
+        let program =
+            Program::load_binary(&[1, 2, 3, 4, 0, 0, 0, 0, 5], 0x1000, 0x1000, MAX_MEM).unwrap();
+        assert_eq!(program.entry, 0x1000);
+        assert_eq!(
+            program.image.into_iter().collect::<Vec<_>>(),
+            [(0x1000, 0x0403_0201), (0x1008, 5)]
+        );
+
+        assert!(Program::load_binary(&[1; 8], 0x1002, 0x1000, MAX_MEM).is_err());
+        assert!(Program::load_binary(&[1; 8], 0x1000, 0x1008, MAX_MEM).is_err());
+        assert!(Program::load_binary(&[1; 8], MAX_MEM - 4, MAX_MEM - 4, MAX_MEM).is_err());
+    }
+
+    #[test]
+    fn load_regions() {
+        let kernel = [1; 16];
+        let user = [2; 8];
+        let program = Program::load_regions(
+            [(0x2000, &user[..]), (0x1000, &kernel[..])],
+            0x1000,
+            MAX_MEM,
+        )
+        .unwrap();
+        assert_eq!(program.image.len(), 6);
+        assert_eq!(program.image[&0x2004], 0x0202_0202);
+
+        let overlapping = [(0x1000, &kernel[..]), (0x100c, &user[..])];
+        assert!(Program::load_regions(overlapping, 0x1000, MAX_MEM).is_err());
+        let overlapping = [(0x100c, &user[..]), (0x1000, &kernel[..])];
+        assert!(Program::load_regions(overlapping, 0x1000, MAX_MEM).is_err());
+    }
+}
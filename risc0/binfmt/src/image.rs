@@ -14,7 +14,12 @@
 
 extern crate alloc;
 
-use alloc::{collections::BTreeMap, vec, vec::Vec};
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    vec,
+    vec::Vec,
+};
 
 use anyhow::{ensure, Result};
 use risc0_zkp::core::{
@@ -34,10 +39,16 @@ use crate::{elf::Program, Digestible, SystemState};
 /// This is an image of the full memory state of the zkVM, including the data,
 /// text, inputs, page table, and system memory. In addition to the memory image
 /// proper, this includes some metadata about the page table.
+///
+/// Pages are copy-on-write: cloning an image shares its pages with the clone,
+/// and a page is only copied when one of the images writes to it. Executors
+/// constructed from clones of the same image share the pages the guest only
+/// reads, and each holds its own copy of just the pages the guest writes. The
+/// partial image of each segment shares its pages with the executor too.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct MemoryImage {
-    /// Sparse memory memory image as a map from page index to page.
-    pub pages: BTreeMap<u32, Vec<u8>>,
+    // Sparse memory image as a map from page index to page.
+    pages: BTreeMap<u32, Arc<Vec<u8>>>,
 
     /// Metadata about the structure of the page table
     pub info: PageTableInfo,
@@ -169,6 +180,11 @@ impl MemoryImage {
     /// The result is a MemoryImage with the ELF of `program` loaded (but
     /// execution not yet begun), and with the page table Merkle tree
     /// constructed.
+    ///
+    /// Zero-filled pages are skipped: they are not stored, and take the hash of
+    /// the zero page. Each page holding non-zero words is stored and hashed
+    /// here rather than when the guest first touches it, since the image ID,
+    /// and the pre-state of the first segment, depend on every page.
     pub fn new(program: &Program, page_size: u32) -> Result<Self> {
         // Compute the page table hashes except for the very last root hash.
         let info = PageTableInfo::new(PAGE_TABLE.start() as u32, page_size)?;
@@ -183,7 +199,11 @@ impl MemoryImage {
             if addr as usize >= GUEST_MAX_MEM {
                 anyhow::bail!("Invalid Elf Program, address outside GUEST_MAX_MEM");
             }
-            img.store_region_in_page(addr, &data.to_le_bytes());
+            // Pages are zero-initialized, so only pages with non-zero data need to be
+            // materialized. Pages that are left out hash to the zero page hash.
+            if data != 0 {
+                img.store_region_in_page(addr, &data.to_le_bytes());
+            }
         }
 
         img.hash_pages();
@@ -195,10 +215,36 @@ impl MemoryImage {
     pub fn load_page(&self, page_idx: u32) -> Vec<u8> {
         self.pages
             .get(&page_idx)
-            .cloned()
+            .map(|page| page.to_vec())
             .unwrap_or_else(|| vec![0; self.info.page_size as usize])
     }
 
+    /// Like [MemoryImage::load_page], but shares the page with this image
+    /// rather than copying it.
+    pub fn share_page(&self, page_idx: u32) -> Arc<Vec<u8>> {
+        self.pages
+            .get(&page_idx)
+            .cloned()
+            .unwrap_or_else(|| Arc::new(vec![0; self.info.page_size as usize]))
+    }
+
+    /// Replace the page specified by page_idx, without updating the image
+    /// merkle tree.
+    pub fn set_page(&mut self, page_idx: u32, page: Arc<Vec<u8>>) {
+        self.pages.insert(page_idx, page);
+    }
+
+    /// Whether the page specified by page_idx is stored in this image, rather
+    /// than taken to be zero.
+    pub fn has_page(&self, page_idx: u32) -> bool {
+        self.pages.contains_key(&page_idx)
+    }
+
+    /// The indices of the pages stored in this image, in ascending order.
+    pub fn page_indices(&self) -> impl Iterator<Item = u32> + '_ {
+        self.pages.keys().copied()
+    }
+
     /// Writes the given byte array in this memory image at the given
     /// address.  The caller is responsible for ensuring the bytes do
     /// not overlap a page boundary.
@@ -208,8 +254,9 @@ impl MemoryImage {
             if addr as usize >= MEM_SIZE {
                 panic!("address {addr:08X} outside MEM_SIZE")
             }
-            vec![0_u8; self.info.page_size as usize]
+            Arc::new(vec![0_u8; self.info.page_size as usize])
         });
+        let page = Arc::make_mut(page);
         let page_start = self.info.get_page_addr(page_idx);
         page[(addr - page_start) as usize..(addr - page_start) as usize + bytes.len()]
             .clone_from_slice(bytes);
@@ -237,6 +284,48 @@ impl MemoryImage {
         Ok(())
     }
 
+    /// Write `bytes` to guest memory at `addr`, and update the image merkle
+    /// tree.
+    ///
+    /// Only the pages written to and the page table pages on their paths to
+    /// the root are rehashed, so patching a few bytes of a large image, such as
+    /// configuration embedded in the program, is much cheaper than building
+    /// the image again. The bytes may span pages, but must lie below
+    /// [GUEST_MAX_MEM].
+    pub fn patch(&mut self, addr: u32, bytes: &[u8]) -> Result<()> {
+        let end = addr as usize + bytes.len();
+        ensure!(
+            end <= GUEST_MAX_MEM,
+            "patch at {addr:08X} of {} bytes exceeds GUEST_MAX_MEM ({GUEST_MAX_MEM:08X})",
+            bytes.len()
+        );
+
+        let mut dirty = BTreeSet::new();
+        let mut addr = addr;
+        let mut bytes = bytes;
+        while !bytes.is_empty() {
+            let page_idx = self.info.get_page_index(addr);
+            let page_end = self.info.get_page_addr(page_idx) + self.info.page_size;
+            let len = bytes.len().min((page_end - addr) as usize);
+            self.store_region_in_page(addr, &bytes[..len]);
+            dirty.insert(page_idx);
+            addr += len as u32;
+            bytes = &bytes[len..];
+        }
+
+        // The entry of a page is in a page table page with a higher index, so
+        // rehashing in ascending order updates each page after all its entries.
+        // The root page is hashed by compute_root_hash.
+        while let Some(page_idx) = dirty.pop_first() {
+            if page_idx < self.info.root_idx {
+                self.update_page(page_idx);
+                let entry_addr = self.info.get_page_entry_addr(page_idx);
+                dirty.insert(self.info.get_page_index(entry_addr));
+            }
+        }
+        Ok(())
+    }
+
     /// Calculate and update the image merkle tree within this image.
     pub fn hash_pages(&mut self) {
         self.hash_pages_iter(0..self.info.num_pages)
@@ -266,13 +355,13 @@ impl MemoryImage {
         }
     }
 
-    /// Verify the integrity of the MemoryImage.
+    /// Verify the integrity of the page holding `addr`.
     ///
-    /// Confirms that the page table is a valid Merkle tree with the expected
-    /// root and that the data from each page hashes to the expected page table
-    /// entry.
-    #[cfg(test)]
-    fn check(&self, addr: u32) -> Result<()> {
+    /// Confirms that the data of the page, and of each page table page on its
+    /// path to the root, hashes to the expected page table entry. Pages that
+    /// are not in the image are taken to be zero, so a page table page that
+    /// is missing from a partial image fails the check.
+    pub fn check(&self, addr: u32) -> Result<()> {
         let mut page_idx = self.info.get_page_index(addr);
         while page_idx < self.info.root_idx {
             let page_addr = self.info.get_page_addr(page_idx);
@@ -280,7 +369,7 @@ impl MemoryImage {
             let entry_addr = self.info.get_page_entry_addr(page_idx);
             let mut entry = [0_u8; DIGEST_BYTES];
             self.load_region_in_page(entry_addr, &mut entry)?;
-            let actual = Digest::try_from(entry)?;
+            let actual = Digest::from(entry);
             tracing::debug!(
                 "page_idx: {page_idx}, page_addr: 0x{page_addr:08x} entry_addr: 0x{entry_addr:08x}"
             );
@@ -290,6 +379,10 @@ impl MemoryImage {
             page_idx = self.info.get_page_index(entry_addr);
         }
 
+        ensure!(
+            self.pages.contains_key(&self.info.root_idx),
+            "Missing root page"
+        );
         let root_page_addr = self.info.root_page_addr;
         let root_page_bytes = self.info.num_root_entries * DIGEST_BYTES as u32;
         let mut root_page = vec![0_u8; root_page_bytes as usize];
@@ -303,6 +396,49 @@ impl MemoryImage {
         Ok(())
     }
 
+    /// Return a partial image with only the given pages of this image, shared
+    /// with this image. Pages that are not in this image are stored as zero
+    /// pages.
+    ///
+    /// Unlike [MemoryImage::open], this does not include the page table pages
+    /// needed to check the given pages.
+    pub fn select(&self, page_idxs: impl IntoIterator<Item = u32>) -> MemoryImage {
+        MemoryImage {
+            pages: page_idxs
+                .into_iter()
+                .map(|page_idx| (page_idx, self.share_page(page_idx)))
+                .collect(),
+            info: self.info.clone(),
+            pc: self.pc,
+        }
+    }
+
+    /// Return a partial image with only the given pages of this image, the
+    /// page table pages on their paths to the root, and the root page.
+    ///
+    /// The partial image has the same [SystemState] as this image, and
+    /// [MemoryImage::check] succeeds on it for each of the given pages.
+    pub fn open(&self, page_idxs: impl IntoIterator<Item = u32>) -> MemoryImage {
+        let mut pages = BTreeMap::new();
+        for mut page_idx in page_idxs {
+            while page_idx < self.info.root_idx {
+                if let Some(page) = self.pages.get(&page_idx) {
+                    pages.insert(page_idx, page.clone());
+                }
+                page_idx = self
+                    .info
+                    .get_page_index(self.info.get_page_entry_addr(page_idx));
+            }
+        }
+        let root_idx = self.info.root_idx;
+        pages.insert(root_idx, self.share_page(root_idx));
+        MemoryImage {
+            pages,
+            info: self.info.clone(),
+            pc: self.pc,
+        }
+    }
+
     /// Compute and return the root merkle entry of this image.
     pub fn compute_root_hash(&self) -> Digest {
         let root_page = self
@@ -339,6 +475,8 @@ fn hash_page_bytes(page: &[u8]) -> Digest {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use risc0_zkvm_methods::MULTI_TEST_ELF;
     use risc0_zkvm_platform::{
         memory::{GUEST_MAX_MEM, PAGE_TABLE, STACK_TOP, SYSTEM, TEXT_START},
@@ -371,6 +509,87 @@ mod tests {
         image.check(image.info.root_page_addr).unwrap();
     }
 
+    #[test]
+    fn patch() {
+        const PAGE_SIZE: u32 = 1024;
+        let code = [0x13; 4096];
+        let mut program =
+            Program::load_binary(&code, TEXT_START, TEXT_START, GUEST_MAX_MEM as u32).unwrap();
+        let mut image = MemoryImage::new(&program, PAGE_SIZE).unwrap();
+
+        // Patch across a page boundary, into a page the program does not use.
+        let addr = STACK_TOP - 8;
+        let bytes = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
+        image.patch(addr, &bytes).unwrap();
+        image.check(addr).unwrap();
+        image.check(addr + 8).unwrap();
+
+        for (i, word) in bytes.chunks(4).enumerate() {
+            let word = u32::from_le_bytes(word.try_into().unwrap());
+            program.image.insert(addr + 4 * i as u32, word);
+        }
+        let expected = MemoryImage::new(&program, PAGE_SIZE).unwrap();
+        assert_eq!(image.compute_id(), expected.compute_id());
+
+        assert!(image.patch(GUEST_MAX_MEM as u32 - 2, &[0; 4]).is_err());
+    }
+
+    #[test]
+    fn zero_pages_not_materialized() {
+        const PAGE_SIZE: u32 = 1024;
+        let mut program = Program::load_elf(MULTI_TEST_ELF, GUEST_MAX_MEM as u32).unwrap();
+        let image = MemoryImage::new(&program, PAGE_SIZE).unwrap();
+
+        // Adding explicit zeros over the unused page between the stack and text does not
+        // change the image or materialize the page.
+        let page_addr = image
+            .info
+            .get_page_addr(image.info.get_page_index(STACK_TOP));
+        for addr in (page_addr..page_addr + PAGE_SIZE).step_by(4) {
+            program.image.insert(addr, 0);
+        }
+        let zeroed = MemoryImage::new(&program, PAGE_SIZE).unwrap();
+        assert_eq!(zeroed.compute_id(), image.compute_id());
+        assert!(zeroed.page_indices().eq(image.page_indices()));
+    }
+
+    #[test]
+    fn open() {
+        const PAGE_SIZE: u32 = 1024;
+        let program = Program::load_elf(MULTI_TEST_ELF, GUEST_MAX_MEM as u32).unwrap();
+        let image = MemoryImage::new(&program, PAGE_SIZE).unwrap();
+
+        let partial = image.open([image.info.get_page_index(TEXT_START)]);
+        assert!(partial.page_indices().count() < image.page_indices().count());
+        assert_eq!(partial.compute_id(), image.compute_id());
+        partial.check(TEXT_START).unwrap();
+        assert!(partial.check(SYSTEM.start() as u32).is_err());
+    }
+
+    #[test]
+    fn clone_shares_pages() {
+        const PAGE_SIZE: u32 = 1024;
+        let program = Program::load_elf(MULTI_TEST_ELF, GUEST_MAX_MEM as u32).unwrap();
+        let image = MemoryImage::new(&program, PAGE_SIZE).unwrap();
+        let page_idx = image.info.get_page_index(TEXT_START);
+
+        let mut clone = image.clone();
+        assert!(Arc::ptr_eq(
+            &image.pages[&page_idx],
+            &clone.pages[&page_idx]
+        ));
+
+        // Writing to the clone copies the page, leaving the original untouched.
+        let original = image.load_page(page_idx);
+        clone.store_region_in_page(TEXT_START, &[0xff; 4]);
+        assert!(!Arc::ptr_eq(
+            &image.pages[&page_idx],
+            &clone.pages[&page_idx]
+        ));
+        assert_eq!(image.load_page(page_idx), original);
+        assert_ne!(clone.load_page(page_idx), original);
+    }
+
     #[test]
     fn page_table_info() {
         const PAGE_SIZE_1K: u32 = 1024;
@@ -13,9 +13,13 @@
 // limitations under the License.
 
 //! Manages formatted binaries used by the RISC Zero zkVM
+//!
+//! [compute_image_id] computes the image ID of a guest ELF, which identifies
+//! the guest in its receipts.
 
 #![cfg_attr(all(not(feature = "std"), not(test)), no_std)]
 
+mod compress;
 mod elf;
 mod exit_code;
 mod hash;
@@ -26,13 +30,31 @@ mod sys_state;
 #[cfg(not(target_os = "zkvm"))]
 pub use self::image::{MemoryImage, PageTableInfo};
 pub use crate::{
-    elf::Program,
+    compress::{compress_elf, decompress_elf},
+    elf::{strip_elf, Program},
     exit_code::{ExitCode, InvalidExitCodeError},
     hash::{tagged_list, tagged_list_cons, tagged_struct, Digestible},
     sys_state::{read_sha_halfs, write_sha_halfs, DecodeError, SystemState},
 };
 
 /// Compute and return the ImageID of the specified ELF binary.
+///
+/// The image ID identifies a guest in its receipts: it is the digest of the
+/// [SystemState] the guest starts in, which is the Merkle root of the memory
+/// image that [MemoryImage::new] makes from the ELF with 1 KiB pages, and the
+/// entrypoint. It depends only on the bytes the ELF loads and where, so
+/// rebuilding audited source reproducibly gives the same ID, and it can be
+/// checked without running or proving the guest, such as with
+/// `cargo risczero image-id`.
+///
+/// The derivation is fixed by the rv32im circuit, whose receipts commit to it,
+/// so the same ELF has the same image ID in every release of the zkVM that
+/// verifies receipts of this circuit. A change to it would be a breaking
+/// change to the circuit, made only with a new major version.
+///
+/// The [Display](core::fmt::Display) form of the digest is the lower-case hex
+/// of its bytes, in the order of the `[u32; 8]` that `risc0-build` generates
+/// for a guest's `_ID` constant, with each word in little-endian.
 #[cfg(not(target_os = "zkvm"))]
 pub fn compute_image_id(elf: &[u8]) -> anyhow::Result<risc0_zkp::core::digest::Digest> {
     use risc0_zkvm_platform::{memory::GUEST_MAX_MEM, PAGE_SIZE};
@@ -41,3 +63,65 @@ pub fn compute_image_id(elf: &[u8]) -> anyhow::Result<risc0_zkp::core::digest::D
     let image = MemoryImage::new(&program, PAGE_SIZE as u32)?;
     Ok(image.compute_id())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{compress_elf, compute_image_id, strip_elf};
+
+    // Build a minimal RISC-V executable that loads `code` at `entry`.
+    fn elf(entry: u32, code: &[u8]) -> Vec<u8> {
+        let mut elf = vec![0x7f, b'E', b'L', b'F', 1, 1, 1];
+        elf.resize(16, 0);
+        for half in [2u16, 243] {
+            elf.extend(half.to_le_bytes());
+        }
+        for word in [1, entry, 52, 0, 0] {
+            elf.extend(word.to_le_bytes());
+        }
+        for half in [52u16, 32, 1, 40, 0, 0] {
+            elf.extend(half.to_le_bytes());
+        }
+        let len = code.len() as u32;
+        for word in [1, 84, entry, entry, len, len, 5, 4] {
+            elf.extend(word.to_le_bytes());
+        }
+        elf.extend(code);
+        elf
+    }
+
+    // The image ID of a program is stable across releases: if this test fails,
+    // every image ID registered for a deployed guest has changed.
+    #[test]
+    fn image_id_is_stable() {
+        let code: Vec<u8> = [0x0000_0013u32, 0x0000_0073]
+            .iter()
+            .flat_map(|insn| insn.to_le_bytes())
+            .collect();
+        let image_id = compute_image_id(&elf(0x0020_0800, &code)).unwrap();
+        assert_eq!(
+            image_id.to_string(),
+            "e0110da9f41cfcbe7e8c22d610504e5a37a689603b5834f9fe6664df1f7fb87e"
+        );
+
+        let moved = compute_image_id(&elf(0x0020_0c00, &code)).unwrap();
+        assert_ne!(moved, image_id);
+        assert!(compute_image_id(&code).is_err());
+    }
+
+    #[test]
+    fn strip_and_compress() {
+        let code = [0x13; 64];
+        let mut unstripped = elf(0x0020_0800, &code);
+        // Non-loadable data, such as symbols, after the segments.
+        unstripped.extend([0xab; 4096]);
+        let image_id = compute_image_id(&unstripped).unwrap();
+
+        let stripped = strip_elf(&unstripped).unwrap();
+        assert_eq!(stripped.len(), 52 + 32 + code.len());
+        assert_eq!(compute_image_id(&stripped).unwrap(), image_id);
+
+        let compressed = compress_elf(&stripped);
+        assert!(compressed.len() < stripped.len());
+        assert_eq!(compute_image_id(&compressed).unwrap(), image_id);
+    }
+}
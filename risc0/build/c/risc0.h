@@ -0,0 +1,74 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// The interface between C and C++ guests and the RISC Zero zkVM.
+//
+// Guests define `int main(void)`. Its return value is the exit code of the
+// guest, and the journal is committed when it returns or calls risc0_exit.
+
+#pragma once
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+#define RISC0_NORETURN [[noreturn]]
+extern "C" {
+#else
+#define RISC0_NORETURN _Noreturn
+#endif
+
+// File descriptors for risc0_write.
+#define RISC0_STDOUT 1
+#define RISC0_STDERR 2
+#define RISC0_JOURNAL 3
+
+// Read up to `len` bytes of input from the host into `buf`. Returns the number
+// of bytes read, which is less than `len` only at the end of the input.
+size_t risc0_read(void* buf, size_t len);
+
+// Write `len` bytes from `buf` to a file descriptor on the host. Writing to
+// RISC0_JOURNAL is the same as calling risc0_commit.
+void risc0_write(uint32_t fd, const void* buf, size_t len);
+
+// Append `len` bytes from `buf` to the journal, which is public in the receipt.
+void risc0_commit(const void* buf, size_t len);
+
+// Return the number of cycles the guest has run so far. This is reported by
+// the host and is not checked by the proof.
+uint64_t risc0_cycle_count(void);
+
+// Halt the guest with the given exit code.
+RISC0_NORETURN void risc0_exit(int code);
+
+// Abort the guest with a message. The execution fails, and no receipt can be
+// produced.
+RISC0_NORETURN void risc0_abort(const char* msg);
+
+// The runtime also provides malloc, calloc, realloc and free, and the C++
+// operators new and delete, on top of the guest heap.
+void* malloc(size_t size);
+void* calloc(size_t count, size_t size);
+void* realloc(void* ptr, size_t size);
+void free(void* ptr);
+
+// And memcpy, memmove, memset and memcmp, as with any freestanding target.
+void* memcpy(void* dst, const void* src, size_t len);
+void* memmove(void* dst, const void* src, size_t len);
+void* memset(void* dst, int value, size_t len);
+int memcmp(const void* a, const void* b, size_t len);
+
+#ifdef __cplusplus
+}
+#endif
@@ -0,0 +1,218 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    env, fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use risc0_zkvm_platform::memory;
+
+use crate::{build_staticlib, get_env_var, get_guest_dir, risc0_data, GuestListEntry};
+
+const HEADER: &str = include_str!("../c/risc0.h");
+const CRT0: &str = include_str!("../c/crt0.S");
+const LINKER_SCRIPT: &str = include_str!("../c/risc0.ld");
+
+const CFLAGS: &[&str] = &[
+    "-march=rv32im",
+    "-mabi=ilp32",
+    "-ffreestanding",
+    "-ffunction-sections",
+    "-fdata-sections",
+];
+
+// Flags for C++ sources, in addition to CFLAGS. The runtime does not support
+// unwinding, and RTTI is only needed by exceptions and dynamic_cast.
+const CXXFLAGS: &[&str] = &["-fno-exceptions", "-fno-rtti", "-fno-threadsafe-statics"];
+
+/// A guest program written in C or C++, for [embed_c_methods].
+#[derive(Clone, Debug, Default)]
+pub struct CGuest {
+    /// The name of the guest, from which the names of the generated constants
+    /// are derived.
+    pub name: String,
+
+    /// The source files of the guest. Files ending in `.cc`, `.cpp` or `.cxx`
+    /// are compiled as C++, and all others as C.
+    pub sources: Vec<PathBuf>,
+
+    /// Additional directories to search for headers.
+    pub include_dirs: Vec<PathBuf>,
+
+    /// Additional flags for the compiler, such as `-Os` or `-DNDEBUG`.
+    pub flags: Vec<String>,
+}
+
+impl CGuest {
+    /// Construct a guest with the given name and source files.
+    pub fn new(name: &str, sources: impl IntoIterator<Item = impl Into<PathBuf>>) -> Self {
+        Self {
+            name: name.to_string(),
+            sources: sources.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Embeds guests written in C or C++ for use by host-side dependencies.
+///
+/// Each guest is compiled and linked against the zkVM runtime into an ELF
+/// image, and its constants are written to `c_methods.rs` in the output
+/// directory, in the same form as [embed_methods](crate::embed_methods)
+/// writes to `methods.rs`.
+///
+/// Guests include `<risc0.h>` and define `main`. The runtime is built from
+/// `risc0-zkvm` with the `c-runtime` feature, so `risc0-zkvm` must be a
+/// dependency of some crate in the workspace.
+///
+/// The C toolchain installed by `cargo risczero install` is used, unless one is
+/// set with the `CC_riscv32im_risc0_zkvm_elf` and `CXX_riscv32im_risc0_zkvm_elf`
+/// environment variables.
+///
+/// ```no_run
+/// use risc0_build::{embed_c_methods, CGuest};
+///
+/// embed_c_methods(&[CGuest::new("sum", ["guest/sum.c"])]);
+/// ```
+pub fn embed_c_methods(guests: &[CGuest]) -> Vec<GuestListEntry> {
+    let out_dir_env = env::var_os("OUT_DIR").unwrap();
+    let out_dir = Path::new(&out_dir_env);
+    let c_dir = get_guest_dir().join("c");
+    let methods_path = out_dir.join("c_methods.rs");
+    let mut methods_file = fs::File::create(&methods_path).unwrap();
+
+    let skip = !get_env_var("RISC0_SKIP_BUILD").is_empty();
+    let runtime = (!skip).then(|| {
+        write_runtime_files(&c_dir);
+        build_staticlib("risc0-zkvm", &["c-runtime"])
+    });
+
+    let mut entries = Vec::new();
+    for guest in guests {
+        for path in guest.sources.iter().chain(guest.include_dirs.iter()) {
+            println!("cargo:rerun-if-changed={}", path.display());
+        }
+
+        let elf_path = c_dir.join(&guest.name).join(&guest.name);
+        if let Some(runtime) = &runtime {
+            build_c_guest(guest, &c_dir, runtime, &elf_path);
+        }
+
+        let entry = GuestListEntry::build(&guest.name, elf_path.to_str().unwrap())
+            .unwrap_or_else(|err| panic!("failed to load C guest {}: {err}", guest.name));
+        methods_file
+            .write_all(entry.codegen_consts().as_bytes())
+            .unwrap();
+        entries.push(entry);
+    }
+
+    println!("cargo:rerun-if-changed={}", methods_path.display());
+    entries
+}
+
+fn write_runtime_files(c_dir: &Path) {
+    let linker_script = LINKER_SCRIPT
+        .replace("{STACK_TOP}", &format!("0x{:08X}", memory::STACK_TOP))
+        .replace("{TEXT_START}", &format!("0x{:08X}", memory::TEXT_START));
+
+    fs::create_dir_all(c_dir.join("include")).unwrap();
+    fs::write(c_dir.join("include/risc0.h"), HEADER).unwrap();
+    fs::write(c_dir.join("crt0.S"), CRT0).unwrap();
+    fs::write(c_dir.join("risc0.ld"), linker_script).unwrap();
+}
+
+fn build_c_guest(guest: &CGuest, c_dir: &Path, runtime: &str, elf_path: &Path) {
+    let obj_dir = elf_path.parent().unwrap();
+    fs::create_dir_all(obj_dir).unwrap();
+
+    let crt0 = c_dir.join("crt0.S");
+    let uses_cxx = guest.sources.iter().any(|path| is_cxx(path));
+
+    // Objects are numbered so that sources with the same file name do not
+    // collide.
+    let objects: Vec<PathBuf> = std::iter::once(&crt0)
+        .chain(&guest.sources)
+        .enumerate()
+        .map(|(i, source)| {
+            let stem = source.file_stem().unwrap().to_string_lossy();
+            let object = obj_dir.join(format!("{i}-{stem}.o"));
+            compile(guest, c_dir, source, &object);
+            object
+        })
+        .collect();
+
+    let mut cmd = Command::new(compiler(uses_cxx));
+    cmd.args(CFLAGS)
+        .args(["-nostdlib", "-nostartfiles", "-static"])
+        .arg("-T")
+        .arg(c_dir.join("risc0.ld"))
+        .args(["-Wl,--gc-sections", "-Wl,--fatal-warnings"])
+        .args(&objects)
+        .arg(runtime)
+        .arg("-lgcc")
+        .arg("-o")
+        .arg(elf_path);
+    run(cmd, &guest.name);
+}
+
+fn compile(guest: &CGuest, c_dir: &Path, source: &Path, object: &Path) {
+    let cxx = is_cxx(source);
+    let mut cmd = Command::new(compiler(cxx));
+    cmd.args(CFLAGS).arg("-O2");
+    if cxx {
+        cmd.args(CXXFLAGS);
+    }
+    cmd.arg("-I").arg(c_dir.join("include"));
+    for dir in &guest.include_dirs {
+        cmd.arg("-I").arg(dir);
+    }
+    cmd.args(&guest.flags)
+        .arg("-c")
+        .arg(source)
+        .arg("-o")
+        .arg(object);
+    run(cmd, &guest.name);
+}
+
+fn is_cxx(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("cc" | "cpp" | "cxx")
+    )
+}
+
+fn compiler(cxx: bool) -> PathBuf {
+    let (var, tool) = match cxx {
+        true => ("CXX_riscv32im_risc0_zkvm_elf", "riscv32-unknown-elf-g++"),
+        false => ("CC_riscv32im_risc0_zkvm_elf", "riscv32-unknown-elf-gcc"),
+    };
+    let configured = get_env_var(var);
+    if !configured.is_empty() {
+        return configured.into();
+    }
+    risc0_data().unwrap().join("cpp/bin").join(tool)
+}
+
+fn run(mut cmd: Command, name: &str) {
+    eprintln!("Building C guest {name}: {cmd:?}");
+    let status = cmd
+        .status()
+        .unwrap_or_else(|err| panic!("failed to run {:?}: {err}", cmd.get_program()));
+    if !status.success() {
+        panic!("failed to build C guest {name}");
+    }
+}
@@ -0,0 +1,233 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use cargo_metadata::{MetadataCommand, Package};
+use risc0_zkp::core::{
+    digest::Digest,
+    hash::sha::{cpu::Impl, Sha256},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{docker, get_env_var, rustc_version, GuestListEntry, GuestOptions};
+
+/// A record of the last build of a guest package, which lets
+/// [embed_methods_with_options](crate::embed_methods_with_options) skip
+/// rebuilding it while its sources, toolchain and options are unchanged.
+///
+/// Records are written to `target/riscv-guest/cache/{name}.json`. Setting
+/// `RISC0_FORCE_REBUILD` builds the guest regardless.
+pub(crate) struct BuildCache {
+    path: PathBuf,
+    key: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Record {
+    key: String,
+    elfs: Vec<ElfRecord>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ElfRecord {
+    path: String,
+    sha256: String,
+}
+
+impl BuildCache {
+    /// The cache of the given guest package, or `None` if its key cannot be
+    /// computed, in which case the guest is always built.
+    pub(crate) fn new(pkg: &Package, guest_dir: &Path, guest_opts: &GuestOptions) -> Option<Self> {
+        match build_key(pkg, guest_opts) {
+            Ok(key) => Some(Self {
+                path: guest_dir.join("cache").join(format!("{}.json", pkg.name)),
+                key: key.to_string(),
+            }),
+            Err(err) => {
+                eprintln!("Not caching the build of {}: {err:#}", pkg.name);
+                None
+            }
+        }
+    }
+
+    /// Whether the ELFs of the last build were built from the same inputs, and
+    /// are still as they were built.
+    pub(crate) fn is_fresh(&self) -> bool {
+        if !get_env_var("RISC0_FORCE_REBUILD").is_empty() {
+            return false;
+        }
+        let Some(record) = fs::read(&self.path)
+            .ok()
+            .and_then(|record| serde_json::from_slice::<Record>(&record).ok())
+        else {
+            return false;
+        };
+        record.key == self.key
+            && record.elfs.iter().all(|elf| {
+                fs::read(&elf.path)
+                    .is_ok_and(|contents| elf_digest(&contents).to_string() == elf.sha256)
+            })
+    }
+
+    /// Record the ELFs of a build.
+    pub(crate) fn store(&self, methods: &[GuestListEntry]) -> Result<()> {
+        let record = Record {
+            key: self.key.clone(),
+            elfs: methods
+                .iter()
+                .map(|method| ElfRecord {
+                    path: method.path.to_string(),
+                    sha256: elf_digest(&method.elf).to_string(),
+                })
+                .collect(),
+        };
+        fs::create_dir_all(self.path.parent().unwrap())?;
+        fs::write(&self.path, serde_json::to_vec_pretty(&record)?)
+            .with_context(|| format!("failed to write {}", self.path.display()))
+    }
+}
+
+fn elf_digest(elf: &[u8]) -> Digest {
+    *Impl::hash_bytes(elf)
+}
+
+// Hash everything the guest's ELFs are built from: the sources of the local
+// packages it depends on, its lockfile, the toolchain and the build options.
+fn build_key(pkg: &Package, guest_opts: &GuestOptions) -> Result<Digest> {
+    let meta = MetadataCommand::new()
+        .manifest_path(&pkg.manifest_path)
+        .exec()
+        .context("failed to read the guest's metadata")?;
+
+    let mut dirs: Vec<PathBuf> = meta
+        .packages
+        .iter()
+        .filter(|pkg| pkg.source.is_none())
+        .map(|pkg| pkg.manifest_path.parent().unwrap().into())
+        .collect();
+    dirs.sort();
+    dirs.dedup();
+
+    let mut input = vec![];
+    for dir in &dirs {
+        // A package within another package's directory is hashed with it.
+        if dirs
+            .iter()
+            .any(|other| other != dir && dir.starts_with(other))
+        {
+            continue;
+        }
+        hash_dir(dir, dir, &mut input)?;
+    }
+    let lockfile = meta.workspace_root.join("Cargo.lock");
+    if lockfile.exists() {
+        hash_file(lockfile.as_std_path(), lockfile.as_std_path(), &mut input)?;
+    }
+
+    let toolchain = match guest_opts.use_docker {
+        Some(_) => docker::BUILDER_IMAGE.to_string(),
+        None => rustc_version(),
+    };
+    let linker_script = match &guest_opts.linker_script {
+        Some(path) => fs::read(path).with_context(|| format!("failed to read {path:?}"))?,
+        None => vec![],
+    };
+    let options = [
+        env!("CARGO_PKG_VERSION").to_string(),
+        toolchain,
+        guest_opts.features.join(","),
+        guest_opts.profile().0.to_string(),
+        guest_opts.rustflags().join("\x1f"),
+        guest_opts.bare_metal.to_string(),
+        get_env_var("RISC0_RUST_SRC"),
+    ];
+    for option in options {
+        input.extend_from_slice(option.as_bytes());
+        input.push(0);
+    }
+    input.extend_from_slice(&linker_script);
+
+    Ok(*Impl::hash_bytes(&input))
+}
+
+// Append the path and digest of each file under `dir`, in order, skipping
+// build outputs and hidden files such as `.git`.
+fn hash_dir(root: &Path, dir: &Path, input: &mut Vec<u8>) -> Result<()> {
+    let mut entries = fs::read_dir(dir)
+        .with_context(|| format!("failed to read {}", dir.display()))?
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let name = entry.file_name();
+        if name.to_string_lossy().starts_with('.') || name == "target" {
+            continue;
+        }
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            hash_dir(root, &path, input)?;
+        } else {
+            hash_file(root, &path, input)?;
+        }
+    }
+    Ok(())
+}
+
+fn hash_file(root: &Path, path: &Path, input: &mut Vec<u8>) -> Result<()> {
+    let contents = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let name = path.strip_prefix(root).unwrap_or(path);
+    input.extend_from_slice(root.to_string_lossy().as_bytes());
+    input.push(0);
+    input.extend_from_slice(name.to_string_lossy().as_bytes());
+    input.push(0);
+    input.extend_from_slice(Impl::hash_bytes(&contents).as_bytes());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::hash_dir;
+
+    fn hash(dir: &std::path::Path) -> Vec<u8> {
+        let mut input = vec![];
+        hash_dir(dir, dir, &mut input).unwrap();
+        input
+    }
+
+    #[test]
+    fn sources() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::create_dir_all(dir.path().join("target/release")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+        let before = hash(dir.path());
+
+        // Build outputs and hidden files are not sources.
+        fs::write(dir.path().join("target/release/guest"), "elf").unwrap();
+        fs::write(dir.path().join(".swp"), "").unwrap();
+        assert_eq!(hash(dir.path()), before);
+
+        fs::write(dir.path().join("src/main.rs"), "fn main() { main() }").unwrap();
+        assert_ne!(hash(dir.path()), before);
+        fs::write(dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(dir.path().join("src/lib.rs"), "").unwrap();
+        assert_ne!(hash(dir.path()), before);
+    }
+}
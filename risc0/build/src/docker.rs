@@ -14,10 +14,14 @@
 
 use std::{fs, path::Path, process::Command};
 
-use anyhow::{bail, Context, Result};
+use anyhow::{bail, ensure, Context, Result};
 use cargo_metadata::MetadataCommand;
 use docker_generate::DockerFile;
 use risc0_binfmt::{MemoryImage, Program};
+use risc0_zkp::core::{
+    digest::Digest,
+    hash::sha::{cpu::Impl, Sha256},
+};
 use risc0_zkvm_platform::{
     memory::{GUEST_MAX_MEM, TEXT_START},
     PAGE_SIZE,
@@ -37,6 +41,9 @@ const DOCKER_IGNORE: &str = r#"
 /// The target directory for the ELF binaries.
 pub const TARGET_DIR: &str = "target/riscv-guest/riscv32im-risc0-zkvm-elf/docker";
 
+/// The pinned image that guests are built in.
+pub(crate) const BUILDER_IMAGE: &str = "risczero/risc0-guest-builder:v2024-04-22.0";
+
 /// Indicates weather the build was successful or skipped.
 pub enum BuildStatus {
     /// The build was successful.
@@ -89,7 +96,7 @@ pub fn docker_build(
         let temp_path = temp_dir.path();
         let rel_manifest_path = manifest_path.strip_prefix(&src_dir)?;
         create_dockerfile(rel_manifest_path, temp_path, pkg_name.as_str(), features)?;
-        build(&src_dir, temp_path)?;
+        build(&src_dir, temp_path, &src_dir.join(TARGET_DIR), false)?;
     }
     println!("ELFs ready at:");
 
@@ -145,7 +152,7 @@ fn create_dockerfile(
     .join(" ");
 
     let build = DockerFile::new()
-        .from_alias("build", "risczero/risc0-guest-builder:v2024-04-22.0")
+        .from_alias("build", BUILDER_IMAGE)
         .workdir("/src")
         .copy(".", ".")
         .env(manifest_env)
@@ -176,12 +183,14 @@ fn create_dockerfile(
 /// Build the dockerfile and outputs the ELF.
 ///
 /// Overwrites if an ELF with the same name already exists.
-fn build(src_dir: &Path, temp_dir: &Path) -> Result<()> {
-    let target_dir = src_dir.join(TARGET_DIR);
+fn build(src_dir: &Path, temp_dir: &Path, target_dir: &Path, no_cache: bool) -> Result<()> {
     let target_dir = target_dir.to_str().unwrap();
-    if Command::new("docker")
-        .arg("build")
-        .arg(format!("--output={target_dir}"))
+    let mut cmd = Command::new("docker");
+    cmd.arg("build").arg(format!("--output={target_dir}"));
+    if no_cache {
+        cmd.arg("--no-cache");
+    }
+    if cmd
         .arg("-f")
         .arg(temp_dir.join("Dockerfile"))
         .arg(src_dir)
@@ -195,6 +204,144 @@ fn build(src_dir: &Path, temp_dir: &Path) -> Result<()> {
     }
 }
 
+/// A guest binary built by [docker_build_reproducible].
+#[derive(Clone, Debug)]
+pub struct ReproducibleGuest {
+    /// The name of the binary.
+    pub name: String,
+
+    /// The ELF binary.
+    pub elf: Vec<u8>,
+
+    /// The image ID of the binary.
+    pub image_id: Digest,
+}
+
+/// The output of [docker_build_reproducible].
+#[derive(Clone, Debug)]
+pub struct ReproducibleBuild {
+    /// The binaries of the package.
+    pub guests: Vec<ReproducibleGuest>,
+
+    /// A digest of everything the build depends on other than the source: the
+    /// builder image, the Dockerfile, and the `Cargo.lock` of the package.
+    ///
+    /// Two builds of the same source with the same environment digest produce
+    /// the same binaries.
+    pub environment_digest: Digest,
+}
+
+impl ReproducibleBuild {
+    /// Returns the binary with the given name.
+    pub fn guest(&self, name: &str) -> Option<&ReproducibleGuest> {
+        self.guests.iter().find(|guest| guest.name == name)
+    }
+
+    /// Check that the binary with the given name has the expected image ID.
+    pub fn check_image_id(&self, name: &str, expected: impl Into<Digest>) -> Result<()> {
+        let guest = self
+            .guest(name)
+            .with_context(|| format!("the build has no binary named {name}"))?;
+        let expected = expected.into();
+        ensure!(
+            guest.image_id == expected,
+            "{name} has image ID {}, but {expected} was expected",
+            guest.image_id
+        );
+        Ok(())
+    }
+}
+
+/// Build the package in the manifest path in the pinned docker environment, and
+/// check that the build is reproducible.
+///
+/// The package is built twice, the second time without the docker cache, and
+/// this fails if the two builds produce different binaries. Unlike
+/// [docker_build], this requires a `Cargo.lock`, and does not honor
+/// `RISC0_SKIP_BUILD`.
+///
+/// ```no_run
+/// use std::path::Path;
+///
+/// use risc0_build::docker_build_reproducible;
+///
+/// // The image ID of the release, as published.
+/// const MULTIPLY_ID: [u32; 8] = [
+///     0xcbeb_be62, 0x5c29_bd97, 0x34e7_ecf8, 0x6264_6beb,
+///     0x628a_2a0f, 0xf2a7_ad2d, 0xeb92_c56c, 0xba67_a920,
+/// ];
+///
+/// let build = docker_build_reproducible(Path::new("guest/Cargo.toml"), Path::new("."), &[])?;
+/// build.check_image_id("multiply", MULTIPLY_ID)?;
+/// # anyhow::Ok(())
+/// ```
+pub fn docker_build_reproducible(
+    manifest_path: &Path,
+    src_dir: &Path,
+    features: &[String],
+) -> Result<ReproducibleBuild> {
+    let manifest_path = manifest_path
+        .canonicalize()
+        .context(format!("manifest_path: {manifest_path:?}"))?;
+    let src_dir = src_dir.canonicalize().context("src_dir")?;
+    let meta = MetadataCommand::new()
+        .manifest_path(&manifest_path)
+        .exec()
+        .context("Manifest not found")?;
+    let root_pkg = meta.root_package().context("Failed to parse Cargo.toml")?;
+    let pkg_name = root_pkg.name.replace('-', "_");
+    check_cargo_lock(&manifest_path)?;
+    let cargo_lock = fs::read(manifest_path.parent().unwrap().join("Cargo.lock"))?;
+
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+    let rel_manifest_path = manifest_path.strip_prefix(&src_dir)?;
+    create_dockerfile(rel_manifest_path, temp_path, &pkg_name, features)?;
+    let dockerfile = fs::read(temp_path.join("Dockerfile"))?;
+
+    let (first, second) = (temp_path.join("first"), temp_path.join("second"));
+    build(&src_dir, temp_path, &first, false)?;
+    build(&src_dir, temp_path, &second, true)?;
+
+    let mut guests = vec![];
+    for target in root_pkg.targets.iter().filter(|t| t.is_bin()) {
+        let elf = fs::read(first.join(&pkg_name).join(&target.name))?;
+        let rebuilt = fs::read(second.join(&pkg_name).join(&target.name))?;
+        ensure!(
+            elf == rebuilt,
+            "{} is not reproducible: two builds of it in {BUILDER_IMAGE} produced different \
+             binaries",
+            target.name
+        );
+        guests.push(ReproducibleGuest {
+            name: target.name.clone(),
+            image_id: risc0_binfmt::compute_image_id(&elf)?,
+            elf,
+        });
+    }
+
+    let environment = [
+        *Impl::hash_bytes(builder_image_id()?.as_bytes()),
+        *Impl::hash_bytes(&dockerfile),
+        *Impl::hash_bytes(&cargo_lock),
+    ];
+    Ok(ReproducibleBuild {
+        guests,
+        environment_digest: *Impl::hash_raw_data_slice(&environment),
+    })
+}
+
+// The content-addressed ID of the builder image, which unlike its tag cannot
+// be moved to another image.
+fn builder_image_id() -> Result<String> {
+    let output = Command::new("docker")
+        .args(["image", "inspect", "--format", "{{.Id}}", BUILDER_IMAGE])
+        .output()
+        .context("docker failed to execute")?;
+    ensure!(output.status.success(), "docker image inspect failed");
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
 fn check_cargo_lock(manifest_path: &Path) -> Result<()> {
     let lock_file = manifest_path
         .parent()
@@ -222,14 +369,16 @@ fn compute_image_id(elf_path: &Path) -> Result<String> {
 mod test {
     use std::path::Path;
 
-    use super::{docker_build, TARGET_DIR};
+    use risc0_zkp::core::digest::Digest;
+
+    use super::{docker_build, docker_build_reproducible, TARGET_DIR};
 
     const SRC_DIR: &str = "../..";
 
     fn build(manifest_path: &str) {
         let src_dir = Path::new(SRC_DIR);
         let manifest_path = Path::new(manifest_path);
-        self::docker_build(manifest_path, &src_dir, &[]).unwrap();
+        self::docker_build(manifest_path, src_dir, &[]).unwrap();
     }
 
     fn compare_image_id(bin_path: &str, expected: &str) {
@@ -253,4 +402,21 @@ mod test {
             "62beebcb97bd295cf8ece734eb6469620f2a8a2dad27a7f26cc592eb20a967ba",
         );
     }
+
+    #[test]
+    fn test_reproducible_build_api() {
+        let build = docker_build_reproducible(
+            Path::new("../../risc0/zkvm/methods/guest/Cargo.toml"),
+            Path::new(SRC_DIR),
+            &[],
+        )
+        .unwrap();
+        let guest = build.guest("multi_test").unwrap();
+        assert_eq!(
+            guest.image_id.to_string(),
+            "62beebcb97bd295cf8ece734eb6469620f2a8a2dad27a7f26cc592eb20a967ba"
+        );
+        build.check_image_id("multi_test", guest.image_id).unwrap();
+        assert!(build.check_image_id("multi_test", Digest::ZERO).is_err());
+    }
 }
@@ -16,7 +16,11 @@
 #![deny(missing_docs)]
 #![deny(rustdoc::broken_intra_doc_links)]
 
+mod c;
+mod cache;
 mod docker;
+mod manifest;
+mod verify;
 
 use std::{
     borrow::Cow,
@@ -29,14 +33,23 @@ use std::{
     process::{Command, Stdio},
 };
 
-use anyhow::{Context, Result};
+use anyhow::{ensure, Context, Result};
+use cache::BuildCache;
 use cargo_metadata::{Message, MetadataCommand, Package};
 use risc0_binfmt::compute_image_id;
-use risc0_zkp::core::digest::DIGEST_WORDS;
+use risc0_zkp::core::digest::{Digest, DIGEST_WORDS};
 use risc0_zkvm_platform::memory;
 use serde::Deserialize;
 
-pub use docker::{docker_build, BuildStatus, TARGET_DIR};
+pub use c::{embed_c_methods, CGuest};
+pub use docker::{
+    docker_build, docker_build_reproducible, BuildStatus, ReproducibleBuild, ReproducibleGuest,
+    TARGET_DIR,
+};
+pub use manifest::{GuestManifest, Toolchain};
+pub use verify::{
+    diff_guest, verify_build, Artifact, BuildVerification, Difference, ExpectedGuest,
+};
 
 const RUSTUP_TOOLCHAIN_NAME: &str = "risc0";
 
@@ -72,7 +85,8 @@ impl Risc0Metadata {
 pub struct GuestListEntry {
     /// The name of the guest binary
     pub name: Cow<'static, str>,
-    /// The compiled ELF guest binary
+    /// The compiled ELF guest binary, stripped or compressed if its
+    /// [GuestOptions] ask for it
     pub elf: Cow<'static, [u8]>,
     /// The image id of the guest
     pub image_id: [u32; DIGEST_WORDS],
@@ -199,18 +213,23 @@ fn is_debug() -> bool {
 }
 
 /// Returns all methods associated with the given guest crate.
-fn guest_methods(pkg: &Package, target_dir: impl AsRef<Path>) -> Vec<GuestListEntry> {
-    let profile = if is_debug() { "debug" } else { "release" };
+fn guest_methods(
+    pkg: &Package,
+    target_dir: impl AsRef<Path>,
+    guest_opts: &GuestOptions,
+) -> Vec<GuestListEntry> {
+    let (_, profile_dir) = guest_opts.profile();
+    let prefix = guest_opts.const_prefix.as_deref().unwrap_or_default();
     pkg.targets
         .iter()
         .filter(|target| target.kind.iter().any(|kind| kind == "bin"))
         .map(|target| {
             GuestListEntry::build(
-                &target.name,
+                &format!("{prefix}{}", target.name),
                 target_dir
                     .as_ref()
                     .join("riscv32im-risc0-zkvm-elf")
-                    .join(profile)
+                    .join(profile_dir)
                     .join(&target.name)
                     .to_str()
                     .context("elf path contains invalid unicode")
@@ -222,16 +241,21 @@ fn guest_methods(pkg: &Package, target_dir: impl AsRef<Path>) -> Vec<GuestListEn
 }
 
 /// Returns all methods associated with the given guest crate.
-fn guest_methods_docker<P>(pkg: &Package, target_dir: P) -> Vec<GuestListEntry>
+fn guest_methods_docker<P>(
+    pkg: &Package,
+    target_dir: P,
+    guest_opts: &GuestOptions,
+) -> Vec<GuestListEntry>
 where
     P: AsRef<Path>,
 {
+    let prefix = guest_opts.const_prefix.as_deref().unwrap_or_default();
     pkg.targets
         .iter()
         .filter(|target| target.kind.iter().any(|kind| kind == "bin"))
         .map(|target| {
             GuestListEntry::build(
-                &target.name,
+                &format!("{prefix}{}", target.name),
                 target_dir
                     .as_ref()
                     .join("riscv32im-risc0-zkvm-elf")
@@ -266,14 +290,14 @@ fn sanitized_cmd(tool: &str) -> Command {
 /// Creates a std::process::Command to execute the given cargo
 /// command in an environment suitable for targeting the zkvm guest.
 pub fn cargo_command(subcmd: &str, rust_flags: &[&str]) -> Command {
-    let rustc = sanitized_cmd("rustup")
-        .args(["+risc0", "which", "rustc"])
-        .output()
-        .expect("rustup failed to find risc0 toolchain")
-        .stdout;
+    guest_cargo_command(subcmd, rust_flags, &GuestOptions::default())
+}
 
-    let rustc = String::from_utf8(rustc).unwrap();
-    let rustc = rustc.trim();
+// Like [cargo_command], for a guest built with the given options: a linker
+// script replaces the default text address, and bare-metal guests build no
+// standard library from source.
+fn guest_cargo_command(subcmd: &str, rust_flags: &[&str], guest_opts: &GuestOptions) -> Command {
+    let rustc = risc0_rustc();
     println!("Using rustc: {rustc}");
 
     let mut cmd = sanitized_cmd("cargo");
@@ -286,7 +310,10 @@ pub fn cargo_command(subcmd: &str, rust_flags: &[&str]) -> Command {
     let rust_src = get_env_var("RISC0_RUST_SRC");
     if !rust_src.is_empty() {
         args.push("-Z");
-        args.push("build-std=alloc,core,proc_macro,panic_abort,std");
+        args.push(match guest_opts.bare_metal {
+            true => "build-std=alloc,core",
+            false => "build-std=alloc,core,proc_macro,panic_abort,std",
+        });
         args.push("-Z");
         args.push("build-std-features=compiler-builtins-mem");
         cmd.env("__CARGO_TESTS_ONLY_SRC_ROOT", rust_src);
@@ -294,29 +321,27 @@ pub fn cargo_command(subcmd: &str, rust_flags: &[&str]) -> Command {
 
     println!("Building guest package: cargo {}", args.join(" "));
 
-    let rustflags_envvar = [
-        rust_flags,
-        &[
-            // Replace atomic ops with nonatomic versions since the guest is single threaded.
-            "-C",
-            "passes=loweratomic",
-            // Specify where to start loading the program in
-            // memory.  The clang linker understands the same
-            // command line arguments as the GNU linker does; see
-            // https://ftp.gnu.org/old-gnu/Manuals/ld-2.9.1/html_mono/ld.html#SEC3
-            // for details.
-            "-C",
-            &format!("link-arg=-Ttext=0x{:08X}", memory::TEXT_START),
-            // Apparently not having an entry point is only a linker warning(!), so
-            // error out in this case.
-            "-C",
-            "link-arg=--fatal-warnings",
-            "-C",
-            "panic=abort",
-        ],
-    ]
-    .concat()
-    .join("\x1f");
+    let text_start = format!("link-arg=-Ttext=0x{:08X}", memory::TEXT_START);
+    let mut rustflags = rust_flags.to_vec();
+    // Replace atomic ops with nonatomic versions since the guest is single threaded.
+    rustflags.extend(["-C", "passes=loweratomic"]);
+    if guest_opts.linker_script.is_none() {
+        // Specify where to start loading the program in
+        // memory.  The clang linker understands the same
+        // command line arguments as the GNU linker does; see
+        // https://ftp.gnu.org/old-gnu/Manuals/ld-2.9.1/html_mono/ld.html#SEC3
+        // for details.
+        rustflags.extend(["-C", &text_start]);
+    }
+    rustflags.extend([
+        // Apparently not having an entry point is only a linker warning(!), so
+        // error out in this case.
+        "-C",
+        "link-arg=--fatal-warnings",
+        "-C",
+        "panic=abort",
+    ]);
+    let rustflags_envvar = rustflags.join("\x1f");
 
     if !cpp_toolchain_override() {
         let cc_path = risc0_data()
@@ -332,6 +357,25 @@ pub fn cargo_command(subcmd: &str, rust_flags: &[&str]) -> Command {
     cmd
 }
 
+/// Returns the path to rustc in the risc0 toolchain.
+fn risc0_rustc() -> String {
+    let rustc = sanitized_cmd("rustup")
+        .args(["+risc0", "which", "rustc"])
+        .output()
+        .expect("rustup failed to find risc0 toolchain")
+        .stdout;
+    String::from_utf8(rustc).unwrap().trim().to_string()
+}
+
+fn rustc_version() -> String {
+    let version = Command::new(risc0_rustc())
+        .arg("--version")
+        .output()
+        .expect("failed to run rustc --version")
+        .stdout;
+    String::from_utf8(version).unwrap().trim().to_string()
+}
+
 fn cpp_toolchain_override() -> bool {
     // detect if there's an attempt to override the Cpp toolchain.
     // Overriding the toolchain useful for troubleshooting crates.
@@ -431,11 +475,12 @@ fn build_guest_package<P>(
 
     fs::create_dir_all(target_dir.as_ref()).unwrap();
 
-    let mut cmd = if let Some(lib) = runtime_lib {
-        cargo_command("build", &["-C", &format!("link_arg={}", lib)])
-    } else {
-        cargo_command("build", &[])
-    };
+    let mut rustflags = guest_opts.rustflags();
+    if let Some(lib) = runtime_lib {
+        rustflags.extend(["-C".to_string(), format!("link_arg={}", lib)]);
+    }
+    let rustflags: Vec<&str> = rustflags.iter().map(String::as_str).collect();
+    let mut cmd = guest_cargo_command("build", &rustflags, guest_opts);
 
     let features_str = guest_opts.features.join(",");
     if !features_str.is_empty() {
@@ -449,9 +494,8 @@ fn build_guest_package<P>(
         target_dir.as_ref().to_str().unwrap(),
     ]);
 
-    if !is_debug() {
-        cmd.args(["--release"]);
-    }
+    let (profile, _) = guest_opts.profile();
+    cmd.args(["--profile", profile]);
 
     let mut child = cmd
         .stderr(Stdio::piped())
@@ -528,13 +572,215 @@ pub struct DockerOptions {
 
 /// Options defining how to embed a guest package in
 /// [`embed_methods_with_options`].
-#[derive(Default)]
+///
+/// ```no_run
+/// use std::collections::HashMap;
+///
+/// use risc0_build::{embed_methods_with_options, GuestOptions};
+///
+/// embed_methods_with_options(HashMap::from([
+///     (
+///         "fast-guest",
+///         GuestOptions::default()
+///             .with_profile("release-lto")
+///             .with_rustflags(["-C", "debug-assertions"]),
+///     ),
+///     (
+///         "small-guest",
+///         GuestOptions::default()
+///             .with_features(["no-std"])
+///             .with_const_prefix("SMALL_"),
+///     ),
+/// ]));
+/// ```
+#[derive(Clone, Default)]
 pub struct GuestOptions {
     /// Features for cargo to build the guest with.
     pub features: Vec<String>,
 
     /// Use a docker environment for building.
+    ///
+    /// Docker builds only support [GuestOptions::features]: setting any of
+    /// the other build options with docker is an error.
     pub use_docker: Option<DockerOptions>,
+
+    /// The cargo profile to build the guest with, such as a profile with LTO
+    /// and a higher opt-level defined in the guest's workspace.
+    ///
+    /// When `None`, the guest is built with the `release` profile, or `dev`
+    /// if `RISC0_BUILD_DEBUG` is set to 1.
+    pub profile: Option<String>,
+
+    /// Flags for rustc, in addition to those needed to target the zkVM.
+    pub rustflags: Vec<String>,
+
+    /// A linker script to link the guest with. The script places the guest
+    /// in memory, so the text address of the default layout is not passed to
+    /// the linker.
+    pub linker_script: Option<PathBuf>,
+
+    /// The symbol the guest is entered at, for guests that define their own
+    /// entrypoint rather than `_start`.
+    pub entry: Option<String>,
+
+    /// Build the guest as a bare-metal `#![no_std]` and `#![no_main]` binary,
+    /// such as one using the `bare-metal` runtime of `risc0-zkvm-platform`
+    /// rather than `risc0-zkvm`.
+    ///
+    /// When the standard library is built from source with `RISC0_RUST_SRC`,
+    /// only `core` and `alloc` are built for bare-metal guests.
+    pub bare_metal: bool,
+
+    /// A prefix for the names of the constants generated for the guest's
+    /// binaries, such as `FAST_` for `FAST_MULTIPLY_ID`. This keeps constants
+    /// distinct when guest packages have binaries with the same name.
+    pub const_prefix: Option<String>,
+
+    /// Write a [GuestManifest] for each of the guest's binaries.
+    pub manifest: bool,
+
+    /// Strip the embedded ELF down to the segments the zkVM loads, with
+    /// [risc0_binfmt::strip_elf]. The ELF at the generated `_PATH` keeps its
+    /// symbols, for profiling.
+    pub strip: bool,
+
+    /// Compress the embedded ELF with [risc0_binfmt::compress_elf]. It is
+    /// decompressed when risc0-binfmt loads it into a memory image, so it can
+    /// be passed to the executor and provers of this release as it is; other
+    /// consumers, such as provers of earlier releases, need it passed through
+    /// [risc0_binfmt::decompress_elf].
+    pub compress: bool,
+}
+
+impl GuestOptions {
+    /// Build the guest with the given cargo features.
+    pub fn with_features(mut self, features: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.features = features.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Build the guest in a docker environment.
+    pub fn with_docker(mut self, docker_opts: DockerOptions) -> Self {
+        self.use_docker = Some(docker_opts);
+        self
+    }
+
+    /// Build the guest with the given cargo profile.
+    pub fn with_profile(mut self, profile: impl Into<String>) -> Self {
+        self.profile = Some(profile.into());
+        self
+    }
+
+    /// Pass the given additional flags to rustc.
+    pub fn with_rustflags(mut self, flags: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.rustflags = flags.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Link the guest with the given linker script.
+    pub fn with_linker_script(mut self, path: impl Into<PathBuf>) -> Self {
+        self.linker_script = Some(path.into());
+        self
+    }
+
+    /// Enter the guest at the given symbol.
+    pub fn with_entry(mut self, symbol: impl Into<String>) -> Self {
+        self.entry = Some(symbol.into());
+        self
+    }
+
+    /// Build the guest as a bare-metal `#![no_std]` and `#![no_main]` binary.
+    ///
+    /// ```no_run
+    /// use std::collections::HashMap;
+    ///
+    /// use risc0_build::{embed_methods_with_options, GuestOptions};
+    ///
+    /// embed_methods_with_options(HashMap::from([(
+    ///     "kernel",
+    ///     GuestOptions::default()
+    ///         .with_bare_metal()
+    ///         .with_linker_script("kernel/kernel.ld")
+    ///         .with_entry("kernel_start"),
+    /// )]));
+    /// ```
+    pub fn with_bare_metal(mut self) -> Self {
+        self.bare_metal = true;
+        self
+    }
+
+    /// Prefix the names of the constants generated for the guest.
+    pub fn with_const_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.const_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Write a [GuestManifest] for each of the guest's binaries.
+    pub fn with_manifest(mut self) -> Self {
+        self.manifest = true;
+        self
+    }
+
+    /// Strip the embedded ELF down to the segments the zkVM loads.
+    pub fn with_strip(mut self) -> Self {
+        self.strip = true;
+        self
+    }
+
+    /// Compress the embedded ELF.
+    pub fn with_compress(mut self) -> Self {
+        self.compress = true;
+        self
+    }
+
+    // The cargo profile and the name of its output directory.
+    fn profile(&self) -> (&str, &str) {
+        match self.profile.as_deref() {
+            Some("dev") => ("dev", "debug"),
+            Some(profile) => (profile, profile),
+            None if is_debug() => ("dev", "debug"),
+            None => ("release", "release"),
+        }
+    }
+
+    fn rustflags(&self) -> Vec<String> {
+        let mut flags = self.rustflags.clone();
+        if let Some(script) = &self.linker_script {
+            flags.push("-C".into());
+            flags.push(format!("link-arg=-T{}", script.display()));
+        }
+        if let Some(entry) = &self.entry {
+            flags.push("-C".into());
+            flags.push(format!("link-arg=--entry={entry}"));
+        }
+        flags
+    }
+
+    fn has_build_options(&self) -> bool {
+        self.profile.is_some()
+            || !self.rustflags.is_empty()
+            || self.linker_script.is_some()
+            || self.entry.is_some()
+            || self.bare_metal
+    }
+}
+
+// Strip and compress the ELF of a guest for embedding, as its options ask.
+fn postprocess_elf(entry: &mut GuestListEntry, guest_opts: &GuestOptions) -> Result<()> {
+    if guest_opts.strip {
+        let stripped = risc0_binfmt::strip_elf(&entry.elf)?;
+        // Stripping keeps everything the image ID is computed from.
+        ensure!(
+            compute_image_id(&stripped)? == Digest::from(entry.image_id),
+            "stripping {} changed its image ID",
+            entry.name
+        );
+        entry.elf = Cow::Owned(stripped);
+    }
+    if guest_opts.compress {
+        entry.elf = Cow::Owned(risc0_binfmt::compress_elf(&entry.elf));
+    }
+    Ok(())
 }
 
 fn get_guest_dir() -> PathBuf {
@@ -556,6 +802,11 @@ fn get_guest_dir() -> PathBuf {
 /// Embeds methods built for RISC-V for use by host-side dependencies.
 /// Specify custom options for a guest package by defining its [GuestOptions].
 /// See [embed_methods].
+///
+/// A guest is only rebuilt when the sources of its local packages, its
+/// `Cargo.lock`, its toolchain or its options have changed since the last
+/// build; otherwise the ELFs of that build are embedded again. Set
+/// `RISC0_FORCE_REBUILD` to rebuild every guest regardless.
 pub fn embed_methods_with_options(
     mut guest_pkg_to_options: HashMap<&str, GuestOptions>,
 ) -> Vec<GuestListEntry> {
@@ -589,23 +840,70 @@ pub fn embed_methods_with_options(
             .remove(guest_pkg.name.as_str())
             .unwrap_or_default();
 
-        let methods = if let Some(docker_opts) = guest_opts.use_docker {
+        // Builds skipped with RISC0_SKIP_BUILD leave whatever ELFs are on disk,
+        // so they are not recorded as built from the current sources.
+        let cache = match get_env_var("RISC0_SKIP_BUILD").is_empty() {
+            true => BuildCache::new(&guest_pkg, &guest_dir, &guest_opts),
+            false => None,
+        };
+        let fresh = cache.as_ref().is_some_and(BuildCache::is_fresh);
+        if fresh {
+            eprintln!(
+                "Skipping build of {} because it is unchanged",
+                guest_pkg.name
+            );
+        }
+
+        let methods = if let Some(docker_opts) = &guest_opts.use_docker {
+            if guest_opts.has_build_options() {
+                panic!(
+                    "{}: docker builds do not support a profile, rustflags, a linker script, an \
+                    entry or bare-metal builds",
+                    guest_pkg.name
+                );
+            }
             let src_dir = docker_opts
                 .root_dir
+                .clone()
                 .unwrap_or_else(|| std::env::current_dir().unwrap());
-            docker_build(
-                guest_pkg.manifest_path.as_std_path(),
-                &src_dir,
-                &guest_opts.features,
-            )
-            .unwrap();
-            guest_methods_docker(&guest_pkg, &guest_dir)
+            if !fresh {
+                docker_build(
+                    guest_pkg.manifest_path.as_std_path(),
+                    &src_dir,
+                    &guest_opts.features,
+                )
+                .unwrap();
+            }
+            guest_methods_docker(&guest_pkg, &guest_dir, &guest_opts)
         } else {
-            build_guest_package(&guest_pkg, &guest_dir, &guest_opts, None);
-            guest_methods(&guest_pkg, &guest_dir)
+            if !fresh {
+                build_guest_package(&guest_pkg, &guest_dir, &guest_opts, None);
+            }
+            guest_methods(&guest_pkg, &guest_dir, &guest_opts)
         };
+        if let (false, Some(cache)) = (fresh, &cache) {
+            cache.store(&methods).unwrap();
+        }
+
+        if guest_opts.manifest {
+            let (profile, rustc, docker_image) = match &guest_opts.use_docker {
+                Some(_) => ("release", None, Some(docker::BUILDER_IMAGE.to_string())),
+                None => (guest_opts.profile().0, Some(rustc_version()), None),
+            };
+            let toolchain = Toolchain {
+                risc0_build: env!("CARGO_PKG_VERSION").to_string(),
+                rustc,
+                docker_image,
+            };
+            for method in &methods {
+                GuestManifest::new(method, &guest_opts.features, profile, toolchain.clone())
+                    .and_then(|manifest| manifest.write(&[out_dir, &guest_dir.join("manifests")]))
+                    .unwrap();
+            }
+        }
 
-        for method in methods {
+        for mut method in methods {
+            postprocess_elf(&mut method, &guest_opts).unwrap();
             methods_file
                 .write_all(method.codegen_consts().as_bytes())
                 .unwrap();
@@ -658,3 +956,31 @@ pub fn embed_methods_with_options(
 pub fn embed_methods() -> Vec<GuestListEntry> {
     embed_methods_with_options(HashMap::new())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::GuestOptions;
+
+    #[test]
+    fn rustflags() {
+        assert!(GuestOptions::default().rustflags().is_empty());
+        let opts = GuestOptions::default()
+            .with_rustflags(["-C", "opt-level=z"])
+            .with_linker_script("kernel.ld")
+            .with_entry("kernel_start");
+        assert_eq!(
+            opts.rustflags(),
+            [
+                "-C",
+                "opt-level=z",
+                "-C",
+                "link-arg=-Tkernel.ld",
+                "-C",
+                "link-arg=--entry=kernel_start"
+            ]
+        );
+        assert!(GuestOptions::default()
+            .with_bare_metal()
+            .has_build_options());
+    }
+}
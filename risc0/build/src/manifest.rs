@@ -0,0 +1,168 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fs, path::Path};
+
+use anyhow::{anyhow, Context, Result};
+use elf::{
+    abi::{PF_X, PT_LOAD},
+    endian::LittleEndian,
+    ElfBytes,
+};
+use risc0_zkp::{
+    core::{
+        digest::Digest,
+        hash::sha::{cpu::Impl, Sha256, BLOCK_BYTES},
+    },
+    MIN_CYCLES_PO2, ZK_CYCLES,
+};
+use risc0_zkvm_platform::PAGE_SIZE;
+use serde::{Deserialize, Serialize};
+
+use crate::GuestListEntry;
+
+// The cycles the rv32im executor reserves in each segment for its init and
+// fini phases, and spends paging in a page. These mirror the executor, which
+// risc0-build does not depend on.
+const RESERVED_CYCLES: u64 = 1592 + 1 + 1 + 6 + ZK_CYCLES as u64;
+const PAGE_IN_CYCLES: u64 = 1 + 5 + (16 + 52) * (PAGE_SIZE / BLOCK_BYTES) as u64;
+
+/// Metadata about a guest binary, written as JSON by
+/// [embed_methods_with_options](crate::embed_methods_with_options) when
+/// [GuestOptions::manifest](crate::GuestOptions::manifest) is set.
+///
+/// Manifests are written to `OUT_DIR` and to
+/// `target/riscv-guest/manifests/{name}.json` in the workspace, for deployment
+/// tooling and registries of image IDs.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GuestManifest {
+    /// The name of the guest binary, with any constant prefix.
+    pub name: String,
+
+    /// The image ID of the guest, in hex.
+    pub image_id: String,
+
+    /// The path to the ELF binary.
+    pub elf_path: String,
+
+    /// The SHA-256 digest of the ELF binary, in hex.
+    pub elf_sha256: String,
+
+    /// The cargo features the guest was built with.
+    pub features: Vec<String>,
+
+    /// The cargo profile the guest was built with.
+    pub profile: String,
+
+    /// The tools the guest was built with.
+    pub toolchain: Toolchain,
+
+    /// The size in bytes of the executable segments of the ELF.
+    pub code_size: u64,
+
+    /// The size in bytes of the other loadable segments of the ELF, including
+    /// zero-initialized data.
+    pub data_size: u64,
+
+    /// An estimate of the smallest segment po2 whose first segment can page in
+    /// the whole program: lower po2s split the guest into more segments while
+    /// it is paged in.
+    pub estimated_min_po2: u32,
+}
+
+/// The tools recorded in a [GuestManifest].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Toolchain {
+    /// The version of risc0-build.
+    pub risc0_build: String,
+
+    /// The output of `rustc --version` for the risc0 toolchain, for local
+    /// builds.
+    pub rustc: Option<String>,
+
+    /// The image the guest was built in, for docker builds.
+    pub docker_image: Option<String>,
+}
+
+impl GuestManifest {
+    pub(crate) fn new(
+        entry: &GuestListEntry,
+        features: &[String],
+        profile: &str,
+        toolchain: Toolchain,
+    ) -> Result<Self> {
+        let (code_size, data_size) = segment_sizes(&entry.elf)?;
+        Ok(Self {
+            name: entry.name.to_string(),
+            image_id: Digest::from(entry.image_id).to_string(),
+            elf_path: entry.path.to_string(),
+            elf_sha256: Impl::hash_bytes(&entry.elf).to_string(),
+            features: features.to_vec(),
+            profile: profile.to_string(),
+            toolchain,
+            code_size,
+            data_size,
+            estimated_min_po2: estimate_min_po2(code_size + data_size),
+        })
+    }
+
+    /// Write the manifest as `{name}.json` in each of the given directories.
+    pub(crate) fn write(&self, dirs: &[&Path]) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        for dir in dirs {
+            fs::create_dir_all(dir)?;
+            fs::write(dir.join(format!("{}.json", self.name)), &json)
+                .with_context(|| format!("failed to write manifest to {}", dir.display()))?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn segment_sizes(elf: &[u8]) -> Result<(u64, u64)> {
+    let elf = ElfBytes::<LittleEndian>::minimal_parse(elf)
+        .map_err(|err| anyhow!("invalid ELF: {err}"))?;
+    let segments = elf.segments().context("ELF has no program headers")?;
+    let (mut code, mut data) = (0, 0);
+    for segment in segments.iter().filter(|segment| segment.p_type == PT_LOAD) {
+        match segment.p_flags & PF_X {
+            0 => data += segment.p_memsz,
+            _ => code += segment.p_memsz,
+        }
+    }
+    Ok((code, data))
+}
+
+fn estimate_min_po2(image_size: u64) -> u32 {
+    let pages = image_size.div_ceil(PAGE_SIZE as u64);
+    let cycles = RESERVED_CYCLES + pages * PAGE_IN_CYCLES;
+    cycles
+        .next_power_of_two()
+        .trailing_zeros()
+        .max(MIN_CYCLES_PO2 as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkp::MIN_CYCLES_PO2;
+
+    use super::estimate_min_po2;
+
+    #[test]
+    fn min_po2() {
+        assert_eq!(estimate_min_po2(0), MIN_CYCLES_PO2 as u32);
+        // 64 pages of 1094 cycles need a po2 of 17.
+        assert_eq!(estimate_min_po2(64 * 1024), 17);
+        assert!(estimate_min_po2(1 << 20) > estimate_min_po2(1 << 16));
+    }
+}
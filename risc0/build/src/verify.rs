@@ -0,0 +1,485 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    path::Path,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use elf::{
+    abi::{PF_X, PT_LOAD},
+    endian::LittleEndian,
+    ElfBytes,
+};
+use risc0_zkp::core::{
+    digest::Digest,
+    hash::sha::{cpu::Impl, Sha256},
+};
+
+use crate::{
+    docker::{docker_build_reproducible, ReproducibleGuest, BUILDER_IMAGE},
+    manifest::segment_sizes,
+    GuestManifest,
+};
+
+/// The build that [verify_build] checks a rebuilt guest against.
+///
+/// Only the image ID decides whether the rebuilt guest matches. The manifest
+/// and ELF of the expected build, when they are known, are used to report
+/// which artifacts differ when it does not.
+#[derive(Clone, Debug)]
+pub struct ExpectedGuest {
+    /// The name of the guest binary.
+    pub name: String,
+
+    /// The expected image ID, such as one registered on-chain.
+    pub image_id: Digest,
+
+    /// The manifest written by the expected build.
+    pub manifest: Option<GuestManifest>,
+
+    /// The ELF binary of the expected build.
+    pub elf: Option<Vec<u8>>,
+}
+
+impl ExpectedGuest {
+    /// Expect the guest binary with the given name to have the given image ID.
+    pub fn new(name: impl Into<String>, image_id: impl Into<Digest>) -> Self {
+        Self {
+            name: name.into(),
+            image_id: image_id.into(),
+            manifest: None,
+            elf: None,
+        }
+    }
+
+    /// Compare the rebuilt guest with the manifest of the expected build.
+    pub fn with_manifest(mut self, manifest: GuestManifest) -> Self {
+        self.manifest = Some(manifest);
+        self
+    }
+
+    /// Compare the rebuilt guest with the ELF binary of the expected build,
+    /// segment by segment.
+    pub fn with_elf(mut self, elf: impl Into<Vec<u8>>) -> Self {
+        self.elf = Some(elf.into());
+        self
+    }
+}
+
+/// An artifact of a guest build that can differ from the expected build.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Artifact {
+    /// The image ID.
+    ImageId,
+
+    /// The SHA-256 digest of the ELF binary.
+    Elf,
+
+    /// The entry point of the ELF binary.
+    EntryPoint,
+
+    /// A loadable segment of the ELF binary, by its virtual address.
+    Segment {
+        /// The virtual address of the segment.
+        vaddr: u64,
+
+        /// Whether the segment holds code, rather than data.
+        executable: bool,
+    },
+
+    /// The total size of the code segments, as recorded in a manifest.
+    CodeSize,
+
+    /// The total size of the data segments, as recorded in a manifest.
+    DataSize,
+
+    /// The image the guest was built in.
+    BuilderImage,
+
+    /// The cargo features the guest was built with.
+    Features,
+
+    /// The cargo profile the guest was built with.
+    Profile,
+}
+
+impl fmt::Display for Artifact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Artifact::ImageId => write!(f, "image ID"),
+            Artifact::Elf => write!(f, "ELF digest"),
+            Artifact::EntryPoint => write!(f, "entry point"),
+            Artifact::Segment {
+                vaddr,
+                executable: true,
+            } => write!(f, "code segment at 0x{vaddr:08x}"),
+            Artifact::Segment {
+                vaddr,
+                executable: false,
+            } => write!(f, "data segment at 0x{vaddr:08x}"),
+            Artifact::CodeSize => write!(f, "code size"),
+            Artifact::DataSize => write!(f, "data size"),
+            Artifact::BuilderImage => write!(f, "builder image"),
+            Artifact::Features => write!(f, "features"),
+            Artifact::Profile => write!(f, "profile"),
+        }
+    }
+}
+
+/// An artifact that differs between the expected and the rebuilt guest.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Difference {
+    /// The artifact that differs.
+    pub artifact: Artifact,
+
+    /// The artifact of the expected build.
+    pub expected: String,
+
+    /// The artifact of the rebuilt guest.
+    pub actual: String,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: expected {}, rebuilt {}",
+            self.artifact, self.expected, self.actual
+        )
+    }
+}
+
+/// The result of [verify_build].
+#[derive(Clone, Debug)]
+pub struct BuildVerification {
+    /// The rebuilt guest.
+    pub guest: ReproducibleGuest,
+
+    /// The environment digest of the rebuild, as in
+    /// [ReproducibleBuild](crate::ReproducibleBuild).
+    pub environment_digest: Digest,
+
+    /// The artifacts that differ from the expected build, starting with the
+    /// image ID if it differs.
+    pub differences: Vec<Difference>,
+}
+
+impl BuildVerification {
+    /// Whether the rebuilt guest has the expected image ID.
+    pub fn is_match(&self) -> bool {
+        !self
+            .differences
+            .iter()
+            .any(|difference| difference.artifact == Artifact::ImageId)
+    }
+
+    /// Fail with a report of the artifacts that differ, unless the rebuilt
+    /// guest has the expected image ID.
+    pub fn check(&self) -> Result<()> {
+        if self.is_match() {
+            return Ok(());
+        }
+        let report: Vec<_> = self
+            .differences
+            .iter()
+            .map(|difference| format!("  {difference}"))
+            .collect();
+        bail!(
+            "{} does not match the expected build:\n{}",
+            self.guest.name,
+            report.join("\n")
+        )
+    }
+}
+
+/// Rebuild a guest from source in the reproducible docker environment, and
+/// compare it with an expected build.
+///
+/// The guest is built with [docker_build_reproducible], so this also fails if
+/// the build is not reproducible. Check the result with
+/// [BuildVerification::check].
+///
+/// ```no_run
+/// use std::path::Path;
+///
+/// use risc0_build::{verify_build, ExpectedGuest};
+///
+/// // The image ID registered on-chain.
+/// const MULTIPLY_ID: [u32; 8] = [
+///     0xcbeb_be62, 0x5c29_bd97, 0x34e7_ecf8, 0x6264_6beb,
+///     0x628a_2a0f, 0xf2a7_ad2d, 0xeb92_c56c, 0xba67_a920,
+/// ];
+///
+/// let expected = ExpectedGuest::new("multiply", MULTIPLY_ID);
+/// verify_build(Path::new("guest/Cargo.toml"), Path::new("."), &[], &expected)?.check()?;
+/// # anyhow::Ok(())
+/// ```
+pub fn verify_build(
+    manifest_path: &Path,
+    src_dir: &Path,
+    features: &[String],
+    expected: &ExpectedGuest,
+) -> Result<BuildVerification> {
+    let build = docker_build_reproducible(manifest_path, src_dir, features)?;
+    let guest = build
+        .guest(&expected.name)
+        .with_context(|| format!("the build has no binary named {}", expected.name))?
+        .clone();
+    let differences = diff_guest(expected, &guest.elf, features)?;
+    Ok(BuildVerification {
+        guest,
+        environment_digest: build.environment_digest,
+        differences,
+    })
+}
+
+/// List the artifacts of a guest ELF, built in the reproducible docker
+/// environment with the given features, that differ from an expected build.
+pub fn diff_guest(
+    expected: &ExpectedGuest,
+    elf: &[u8],
+    features: &[String],
+) -> Result<Vec<Difference>> {
+    let mut differences = vec![];
+    let mut diff = |artifact, expected: String, actual: String| {
+        if expected != actual {
+            differences.push(Difference {
+                artifact,
+                expected,
+                actual,
+            });
+        }
+    };
+
+    let image_id = risc0_binfmt::compute_image_id(elf)?;
+    diff(
+        Artifact::ImageId,
+        expected.image_id.to_string(),
+        image_id.to_string(),
+    );
+
+    let elf_sha256 = Impl::hash_bytes(elf).to_string();
+    if let Some(expected_elf) = &expected.elf {
+        diff(
+            Artifact::Elf,
+            Impl::hash_bytes(expected_elf).to_string(),
+            elf_sha256.clone(),
+        );
+        let (expected_entry, expected_segments) = load_segments(expected_elf)?;
+        let (entry, segments) = load_segments(elf)?;
+        diff(
+            Artifact::EntryPoint,
+            format!("0x{expected_entry:08x}"),
+            format!("0x{entry:08x}"),
+        );
+        let vaddrs: Vec<_> = expected_segments
+            .keys()
+            .chain(segments.keys())
+            .copied()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        for vaddr in vaddrs {
+            let expected_segment = expected_segments.get(&vaddr);
+            let segment = segments.get(&vaddr);
+            let executable = expected_segment.or(segment).unwrap().executable;
+            diff(
+                Artifact::Segment { vaddr, executable },
+                describe_segment(expected_segment),
+                describe_segment(segment),
+            );
+        }
+    }
+
+    if let Some(manifest) = &expected.manifest {
+        if expected.elf.is_none() {
+            diff(Artifact::Elf, manifest.elf_sha256.clone(), elf_sha256);
+        }
+        let (code_size, data_size) = segment_sizes(elf)?;
+        diff(
+            Artifact::CodeSize,
+            format!("{} bytes", manifest.code_size),
+            format!("{code_size} bytes"),
+        );
+        diff(
+            Artifact::DataSize,
+            format!("{} bytes", manifest.data_size),
+            format!("{data_size} bytes"),
+        );
+        let builder_image = match (&manifest.toolchain.docker_image, &manifest.toolchain.rustc) {
+            (Some(image), _) => image.clone(),
+            (None, Some(rustc)) => format!("a local build with {rustc}"),
+            (None, None) => "a local build".to_string(),
+        };
+        diff(
+            Artifact::BuilderImage,
+            builder_image,
+            BUILDER_IMAGE.to_string(),
+        );
+        diff(
+            Artifact::Features,
+            format!("{:?}", manifest.features),
+            format!("{features:?}"),
+        );
+        diff(
+            Artifact::Profile,
+            manifest.profile.clone(),
+            "release".to_string(),
+        );
+    }
+
+    Ok(differences)
+}
+
+struct LoadSegment {
+    executable: bool,
+    memsz: u64,
+    digest: Digest,
+}
+
+fn load_segments(elf: &[u8]) -> Result<(u64, BTreeMap<u64, LoadSegment>)> {
+    let elf = ElfBytes::<LittleEndian>::minimal_parse(elf)
+        .map_err(|err| anyhow!("invalid ELF: {err}"))?;
+    let mut segments = BTreeMap::new();
+    for segment in elf
+        .segments()
+        .context("ELF has no program headers")?
+        .iter()
+        .filter(|segment| segment.p_type == PT_LOAD)
+    {
+        let data = elf
+            .segment_data(&segment)
+            .map_err(|err| anyhow!("invalid ELF segment: {err}"))?;
+        segments.insert(
+            segment.p_vaddr,
+            LoadSegment {
+                executable: segment.p_flags & PF_X != 0,
+                memsz: segment.p_memsz,
+                digest: *Impl::hash_bytes(data),
+            },
+        );
+    }
+    Ok((elf.ehdr.e_entry, segments))
+}
+
+fn describe_segment(segment: Option<&LoadSegment>) -> String {
+    match segment {
+        Some(segment) => format!("{} bytes with digest {}", segment.memsz, segment.digest),
+        None => "no segment".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkp::core::digest::Digest;
+
+    use super::{diff_guest, Artifact, ExpectedGuest};
+    use crate::{GuestManifest, Toolchain};
+
+    // A minimal rv32im ELF with one code segment at 0x10000 that halts.
+    fn elf(code: &[u8]) -> Vec<u8> {
+        const HEADER: usize = 52 + 32;
+        let mut elf = vec![0u8; HEADER];
+        elf[..16].copy_from_slice(b"\x7fELF\x01\x01\x01\0\0\0\0\0\0\0\0\0");
+        let put16 = |elf: &mut Vec<u8>, at: usize, value: u16| {
+            elf[at..at + 2].copy_from_slice(&value.to_le_bytes())
+        };
+        let put32 = |elf: &mut Vec<u8>, at: usize, value: u32| {
+            elf[at..at + 4].copy_from_slice(&value.to_le_bytes())
+        };
+        put16(&mut elf, 16, 2); // e_type: EXEC
+        put16(&mut elf, 18, 0xf3); // e_machine: RISC-V
+        put32(&mut elf, 20, 1); // e_version
+        put32(&mut elf, 24, 0x10000); // e_entry
+        put32(&mut elf, 28, 52); // e_phoff
+        put16(&mut elf, 40, 52); // e_ehsize
+        put16(&mut elf, 42, 32); // e_phentsize
+        put16(&mut elf, 44, 1); // e_phnum
+        put16(&mut elf, 46, 40); // e_shentsize
+        put32(&mut elf, 52, 1); // p_type: LOAD
+        put32(&mut elf, 56, HEADER as u32); // p_offset
+        put32(&mut elf, 60, 0x10000); // p_vaddr
+        put32(&mut elf, 64, 0x10000); // p_paddr
+        put32(&mut elf, 68, code.len() as u32); // p_filesz
+        put32(&mut elf, 72, code.len() as u32); // p_memsz
+        put32(&mut elf, 76, 5); // p_flags: R+X
+        put32(&mut elf, 80, 4); // p_align
+        elf.extend_from_slice(code);
+        elf
+    }
+
+    #[test]
+    fn reports_differing_artifacts() {
+        // `ecall` and `nop; ecall`.
+        let expected_elf = elf(&[0x73, 0, 0, 0]);
+        let rebuilt_elf = elf(&[0x13, 0, 0, 0, 0x73, 0, 0, 0]);
+        let image_id = risc0_binfmt::compute_image_id(&expected_elf).unwrap();
+
+        let expected = ExpectedGuest::new("guest", image_id).with_elf(expected_elf.clone());
+        assert!(diff_guest(&expected, &expected_elf, &[])
+            .unwrap()
+            .is_empty());
+
+        let differences = diff_guest(&expected, &rebuilt_elf, &[]).unwrap();
+        let artifacts: Vec<_> = differences.iter().map(|d| d.artifact.clone()).collect();
+        assert_eq!(
+            artifacts,
+            [
+                Artifact::ImageId,
+                Artifact::Elf,
+                Artifact::Segment {
+                    vaddr: 0x10000,
+                    executable: true
+                }
+            ]
+        );
+        assert_eq!(differences[0].expected, image_id.to_string());
+
+        let manifest = GuestManifest {
+            name: "guest".into(),
+            image_id: image_id.to_string(),
+            elf_path: String::new(),
+            elf_sha256: String::new(),
+            features: vec!["std".into()],
+            profile: "release".into(),
+            toolchain: Toolchain {
+                risc0_build: String::new(),
+                rustc: Some("rustc 1.77.0".into()),
+                docker_image: None,
+            },
+            code_size: 4,
+            data_size: 0,
+            estimated_min_po2: 0,
+        };
+        let expected = ExpectedGuest::new("guest", Digest::ZERO).with_manifest(manifest);
+        let artifacts: Vec<_> = diff_guest(&expected, &rebuilt_elf, &[])
+            .unwrap()
+            .into_iter()
+            .map(|d| d.artifact)
+            .collect();
+        assert_eq!(
+            artifacts,
+            [
+                Artifact::ImageId,
+                Artifact::Elf,
+                Artifact::CodeSize,
+                Artifact::BuilderImage,
+                Artifact::Features,
+            ]
+        );
+    }
+}
@@ -26,11 +26,18 @@ fn main() -> Result<()> {
 
     let Cargo::Risczero(args) = Cargo::parse();
     match args.command {
+        #[cfg(feature = "prove")]
+        RisczeroCmd::Benchmark(cmd) => cmd.run(),
         RisczeroCmd::Build(cmd) => cmd.run(),
         RisczeroCmd::BuildToolchain(cmd) => cmd.run(),
         RisczeroCmd::Install(cmd) => cmd.run(),
         RisczeroCmd::New(cmd) => cmd.run(),
         RisczeroCmd::Deploy(cmd) => cmd.run(),
+        RisczeroCmd::ImageId(cmd) => cmd.run(),
+        RisczeroCmd::Inspect(cmd) => cmd.run(),
+        RisczeroCmd::Profile(cmd) => cmd.run(),
+        RisczeroCmd::SolidityVerifier(cmd) => cmd.run(),
+        RisczeroCmd::VerifyBuild(cmd) => cmd.run(),
         #[cfg(feature = "experimental")]
         RisczeroCmd::BuildCrate(build) => build.run(BuildSubcommand::Build),
         #[cfg(feature = "experimental")]
@@ -0,0 +1,306 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fs, path::PathBuf, rc::Rc, time::Instant};
+
+use anyhow::{bail, Context, Result};
+use clap::{Parser, ValueEnum};
+use risc0_binfmt::{MemoryImage, Program};
+use risc0_zkp::hal::tracker;
+use risc0_zkvm::{
+    available_hals, get_prover_server_with_hal, inspect, is_dev_mode, ExecutorEnv, ExecutorImpl,
+    ProverOpts, ProverServer, VerifierContext, GUEST_MAX_MEM, PAGE_SIZE,
+};
+use serde::Serialize;
+
+// Where the workloads are loaded, where the paging workload starts storing,
+// and the zeroed memory the workloads halt with as their output digest.
+const TEXT_ADDR: u32 = 0x0020_0800;
+const DATA_ADDR: u32 = 0x0040_0000;
+const OUTPUT_ADDR: u32 = 0x0010_0000;
+
+// Each iteration of the paging workload pages a page in and, at the end of the
+// segment, out again, at about 1100 cycles each.
+const PAGING_CYCLES: u32 = 2300;
+
+/// `cargo risczero benchmark`
+///
+/// Proves standard workloads with the HALs of this build at a range of segment
+/// sizes, and reports the throughput, proving time and memory of each. The
+/// workloads are assembled here rather than built as guests, so they are the
+/// same in every release and need no toolchain, and their results can be
+/// compared between machines and with the numbers published for a release.
+#[derive(Parser)]
+pub struct BenchmarkCommand {
+    /// The segment po2s to prove the workloads at.
+    #[arg(long, value_delimiter = ',', default_values_t = [16, 18, 20])]
+    pub po2: Vec<u32>,
+
+    /// The HALs to prove with, from those of this build. Defaults to all of them.
+    #[arg(long, value_delimiter = ',')]
+    pub hal: Vec<String>,
+
+    /// The workloads to prove. Defaults to all of them.
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub workload: Vec<Workload>,
+
+    /// The hash function to prove with.
+    #[arg(long, default_value = "poseidon2")]
+    pub hashfn: String,
+
+    /// Write the results as JSON to this file, for tracking regressions.
+    #[arg(long)]
+    pub json: Option<PathBuf>,
+}
+
+/// A standard workload of [BenchmarkCommand], which runs for about one segment
+/// at each po2.
+#[derive(Clone, Copy, Debug, ValueEnum, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Workload {
+    /// Additions and branches, which touch no new memory.
+    Loop,
+    /// A store to a new page in each iteration, which spends most of its cycles
+    /// paging.
+    Paging,
+}
+
+#[derive(Serialize)]
+struct Report {
+    version: &'static str,
+    hashfn: String,
+    results: Vec<BenchmarkResult>,
+}
+
+#[derive(Serialize)]
+struct BenchmarkResult {
+    workload: Workload,
+    hal: String,
+    po2: u32,
+    segments: usize,
+    user_cycles: u64,
+    total_cycles: u64,
+    execute_secs: f64,
+    prove_secs: f64,
+    cycles_per_sec: f64,
+    peak_memory: usize,
+    seal_size: usize,
+}
+
+impl BenchmarkCommand {
+    pub fn run(&self) -> Result<()> {
+        if is_dev_mode() {
+            bail!("benchmarks prove nothing in dev mode; unset RISC0_DEV_MODE");
+        }
+        let hals: Vec<String> = match self.hal.is_empty() {
+            true => available_hals().iter().map(|hal| hal.to_string()).collect(),
+            false => self.hal.clone(),
+        };
+        let workloads = match self.workload.is_empty() {
+            true => Workload::value_variants().to_vec(),
+            false => self.workload.clone(),
+        };
+
+        // Construct the provers first, so that an unknown HAL or hash function
+        // fails before any work is done.
+        let opts = ProverOpts::composite().with_hashfn(self.hashfn.clone());
+        let provers = hals
+            .iter()
+            .map(|hal| Ok((hal, get_prover_server_with_hal(hal, &opts)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        println!(
+            "{:<8} {:<6} {:>3} {:>8} {:>12} {:>10} {:>12} {:>12}",
+            "workload",
+            "hal",
+            "po2",
+            "segments",
+            "cycles",
+            "prove time",
+            "cycles/sec",
+            "peak memory"
+        );
+        let mut results = vec![];
+        for workload in workloads {
+            for (hal, prover) in &provers {
+                for &po2 in &self.po2 {
+                    let result = run(workload, hal, prover, po2).with_context(|| {
+                        format!("failed to benchmark {workload:?} with {hal} at po2 {po2}")
+                    })?;
+                    println!(
+                        "{:<8} {:<6} {:>3} {:>8} {:>12} {:>9.2}s {:>12.0} {:>8} MiB",
+                        format!("{workload:?}").to_lowercase(),
+                        result.hal,
+                        result.po2,
+                        result.segments,
+                        result.total_cycles,
+                        result.prove_secs,
+                        result.cycles_per_sec,
+                        result.peak_memory >> 20
+                    );
+                    results.push(result);
+                }
+            }
+        }
+
+        if let Some(path) = &self.json {
+            let report = Report {
+                version: risc0_zkvm::VERSION,
+                hashfn: self.hashfn.clone(),
+                results,
+            };
+            fs::write(path, serde_json::to_string_pretty(&report)?)
+                .with_context(|| format!("failed to write results to {}", path.display()))?;
+            println!("Wrote results to {}", path.display());
+        }
+
+        Ok(())
+    }
+}
+
+fn run(
+    workload: Workload,
+    hal: &str,
+    prover: &Rc<dyn ProverServer>,
+    po2: u32,
+) -> Result<BenchmarkResult> {
+    let image = workload.image(po2)?;
+    let image_id = image.compute_id();
+    let env = ExecutorEnv::builder().segment_limit_po2(po2).build()?;
+    let start = Instant::now();
+    let session = ExecutorImpl::new(env, image)?.run()?;
+    let execute_secs = start.elapsed().as_secs_f64();
+
+    tracker().lock().unwrap().reset();
+    let start = Instant::now();
+    let info = prover.prove_session(&VerifierContext::default(), &session)?;
+    let prove_secs = start.elapsed().as_secs_f64();
+    let peak_memory = tracker().lock().unwrap().peak;
+    info.receipt
+        .verify(image_id)
+        .context("the receipt does not verify")?;
+
+    Ok(BenchmarkResult {
+        workload,
+        hal: hal.to_string(),
+        po2,
+        segments: session.segments.len(),
+        user_cycles: session.user_cycles,
+        total_cycles: session.total_cycles,
+        execute_secs,
+        prove_secs,
+        cycles_per_sec: session.total_cycles as f64 / prove_secs,
+        peak_memory,
+        seal_size: inspect(&info.receipt).seal_size(),
+    })
+}
+
+impl Workload {
+    // The number of iterations that fill about half of a segment, leaving the
+    // rest for paging in the code, the output and the page table, and for the
+    // cycles the executor reserves.
+    fn iterations(self, po2: u32) -> u32 {
+        let cycles = 1 << (po2 - 1);
+        match self {
+            Workload::Loop => cycles / 2,
+            Workload::Paging => (cycles / PAGING_CYCLES).max(1),
+        }
+    }
+
+    fn image(self, po2: u32) -> Result<MemoryImage> {
+        let mut code = vec![];
+        code.extend(li(A0, self.iterations(po2)));
+        code.extend(li(A1, DATA_ADDR));
+        match self {
+            Workload::Loop => code.extend([addi(A0, A0, -1), bne(A0, ZERO, -4)]),
+            Workload::Paging => code.extend([
+                sw(A0, A1, 0),
+                addi(A1, A1, PAGE_SIZE as i32),
+                addi(A0, A0, -1),
+                bne(A0, ZERO, -12),
+            ]),
+        }
+        // Halt with exit code 0, which the loops leave in a0.
+        code.extend(li(A1, OUTPUT_ADDR));
+        code.extend([addi(T0, ZERO, 0), ECALL]);
+
+        let bytes: Vec<u8> = code.iter().flat_map(|insn| insn.to_le_bytes()).collect();
+        let program = Program::load_binary(&bytes, TEXT_ADDR, TEXT_ADDR, GUEST_MAX_MEM as u32)?;
+        MemoryImage::new(&program, PAGE_SIZE as u32)
+    }
+}
+
+// The registers the workloads use.
+const ZERO: u32 = 0;
+const T0: u32 = 5;
+const A0: u32 = 10;
+const A1: u32 = 11;
+
+const ECALL: u32 = 0x0000_0073;
+
+fn addi(rd: u32, rs1: u32, imm: i32) -> u32 {
+    ((imm as u32 & 0xfff) << 20) | (rs1 << 15) | (rd << 7) | 0x13
+}
+
+fn lui(rd: u32, imm: u32) -> u32 {
+    (imm << 12) | (rd << 7) | 0x37
+}
+
+fn sw(rs2: u32, rs1: u32, imm: i32) -> u32 {
+    let imm = imm as u32 & 0xfff;
+    ((imm >> 5) << 25) | (rs2 << 20) | (rs1 << 15) | (2 << 12) | ((imm & 0x1f) << 7) | 0x23
+}
+
+fn bne(rs1: u32, rs2: u32, offset: i32) -> u32 {
+    let imm = offset as u32;
+    (((imm >> 12) & 1) << 31)
+        | (((imm >> 5) & 0x3f) << 25)
+        | (rs2 << 20)
+        | (rs1 << 15)
+        | (1 << 12)
+        | (((imm >> 1) & 0xf) << 8)
+        | (((imm >> 11) & 1) << 7)
+        | 0x63
+}
+
+// Load a 32-bit value, rounding the upper half so that the sign-extended lower
+// half makes up the difference.
+fn li(rd: u32, value: u32) -> [u32; 2] {
+    let upper = value.wrapping_add(0x800) >> 12;
+    let lower = value.wrapping_sub(upper << 12) as i32;
+    [lui(rd, upper), addi(rd, rd, lower)]
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::ValueEnum;
+    use risc0_zkvm::{ExecutorEnv, ExecutorImpl, ExitCode};
+
+    use super::Workload;
+
+    #[test]
+    fn workloads_fill_a_segment() {
+        for workload in Workload::value_variants() {
+            let image = workload.image(16).unwrap();
+            let env = ExecutorEnv::builder()
+                .segment_limit_po2(16)
+                .build()
+                .unwrap();
+            let session = ExecutorImpl::new(env, image).unwrap().run().unwrap();
+            assert_eq!(session.exit_code, ExitCode::Halted(0), "{workload:?}");
+            assert_eq!(session.segments.len(), 1, "{workload:?}");
+            assert_eq!(session.total_cycles, 1 << 16, "{workload:?}");
+        }
+    }
+}
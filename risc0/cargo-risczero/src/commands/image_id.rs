@@ -0,0 +1,70 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use hex::FromHex;
+use risc0_zkvm::sha::Digest;
+
+/// `cargo risczero image-id`
+///
+/// Prints the image IDs of guest ELFs, such as to check in CI that a guest
+/// rebuilt from audited source matches the image ID that is deployed.
+///
+/// The output is one line for each ELF: its image ID in lower-case hex and,
+/// when more than one ELF is given, two spaces and its path, as `sha256sum`
+/// prints. Scripts can rely on this format, which is kept the same in every
+/// release.
+#[derive(Parser)]
+pub struct ImageIdCommand {
+    /// The guest ELFs.
+    #[arg(required = true)]
+    pub elf: Vec<PathBuf>,
+
+    /// Exit with an error unless every ELF has this image ID, in hex.
+    #[arg(long)]
+    pub expected: Option<String>,
+}
+
+impl ImageIdCommand {
+    pub fn run(&self) -> Result<()> {
+        let expected = self
+            .expected
+            .as_ref()
+            .map(|expected| Digest::from_hex(expected).context("Invalid expected image ID"))
+            .transpose()?;
+
+        for path in &self.elf {
+            let elf = std::fs::read(path)
+                .with_context(|| format!("Failed to read ELF file at path: {}", path.display()))?;
+            let image_id = risc0_binfmt::compute_image_id(&elf)
+                .with_context(|| format!("Failed to load ELF file at path: {}", path.display()))?;
+            match self.elf.len() {
+                1 => println!("{image_id}"),
+                _ => println!("{image_id}  {}", path.display()),
+            }
+            if let Some(expected) = expected {
+                if image_id != expected {
+                    bail!(
+                        "the image ID of {} is {image_id}, not the expected {expected}",
+                        path.display()
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use risc0_zkvm::Receipt;
+
+/// `cargo risczero inspect`
+///
+/// Prints the structure of a receipt of any kind, without verifying it.
+#[derive(Parser)]
+pub struct InspectCommand {
+    /// The receipt to inspect, encoded with bincode, as written by `r0vm
+    /// --receipt`.
+    pub receipt: PathBuf,
+}
+
+impl InspectCommand {
+    pub fn run(&self) -> Result<()> {
+        let data = std::fs::read(&self.receipt).with_context(|| {
+            format!("Failed to read receipt at path: {}", self.receipt.display())
+        })?;
+        let receipt: Receipt = bincode::deserialize(&data).context("Failed to decode receipt")?;
+        println!("{}", risc0_zkvm::inspect(&receipt));
+        Ok(())
+    }
+}
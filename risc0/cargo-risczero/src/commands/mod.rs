@@ -12,10 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "prove")]
+pub mod benchmark;
 #[cfg(feature = "experimental")]
 pub mod build;
 pub mod build_guest;
 pub mod build_toolchain;
 pub mod deploy;
+pub mod image_id;
+pub mod inspect;
 pub mod install;
 pub mod new;
+pub mod profile;
+pub mod solidity_verifier;
+pub mod verify_build;
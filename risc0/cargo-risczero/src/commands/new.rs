@@ -14,39 +14,63 @@
 
 use std::path::PathBuf;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use cargo_generate::{GenerateArgs, TemplatePath, Vcs};
 use clap::Parser;
 use const_format::concatcp;
 use text_io::read;
 
 const RISC0_GH_REPO: &str = "https://github.com/risc0/risc0";
-const RISC0_TEMPLATE_DIR: &str = "templates/rust-starter";
+const RISC0_TEMPLATES_DIR: &str = "templates";
 const RISC0_DEFAULT_VERSION: &str = env!("CARGO_PKG_VERSION");
 const RISC0_RELEASE_TAG: &str = concatcp!("v", RISC0_DEFAULT_VERSION);
+// The version of bonsai-sdk released with this version of risc0.
+const BONSAI_SDK_VERSION: &str = "0.8.0-rc.1";
+
+// The templates in the `templates` directory of the risc0 repository, which
+// can be named with `--template`.
+const BUILTIN_TEMPLATES: &[&str] = &["rust-starter", "foundry", "axum", "bonsai"];
 
 /// `cargo risczero new`
+///
+/// Generates a project from a template. The CLI sets the parameters that
+/// templates share, so that any template can depend on the risc0 crates of
+/// this release, of a git branch or of a local checkout in the same way:
+///
+/// * `risc0_build`, `risc0_zkvm` and `bonsai_sdk`: dependency specs for the
+///   crates, such as `version = "1.0.0"`.
+/// * `guest_package_name`, `guest_id` and `guest_elf`: the name of the guest
+///   package, and of the image ID and ELF constants generated for it.
+/// * `risc0_std` and `risc0_feature_std`: set when `--std` is passed.
+///
+/// Other parameters of a template are set with `--define`.
 #[derive(Parser)]
 pub struct NewCommand {
     /// Name which will be used as the output project name.
     #[arg()]
     pub name: String,
 
-    /// GH repository URL.
-    #[arg(long, short, default_value = RISC0_GH_REPO)]
+    /// The template to generate the project from.
+    ///
+    /// Either the name of a built-in template, which are `rust-starter`,
+    /// `foundry`, `axum` and `bonsai`, or the URL of a git repository or the
+    /// path of a directory holding a cargo-generate template.
+    #[arg(long, short, default_value = "rust-starter")]
     pub template: String,
 
     /// Location of the template
     ///
     /// The subdirectory location of the template used for generating the new
-    /// project. This path is relative to the base repository specified by
-    /// --template
-    #[arg(long, default_value = RISC0_TEMPLATE_DIR)]
-    pub templ_subdir: String,
+    /// project. This path is relative to the repository or directory specified
+    /// by --template, and cannot be used with the built-in templates.
+    #[arg(long)]
+    pub templ_subdir: Option<String>,
 
     /// template git tag.
-    #[arg(long, default_value = RISC0_RELEASE_TAG)]
-    pub tag: String,
+    ///
+    /// Default: the tag of this release, for the built-in templates.
+    #[arg(long)]
+    pub tag: Option<String>,
 
     /// template git branch, overrides `tag` option
     #[arg(long, default_value = "")]
@@ -79,6 +103,8 @@ pub struct NewCommand {
     pub std: bool,
 
     /// Use a path dependency for risc0.
+    ///
+    /// The built-in templates are also taken from this checkout.
     #[arg(long)]
     pub path: Option<PathBuf>,
 
@@ -87,6 +113,10 @@ pub struct NewCommand {
     /// Sets the name of the guest method. This must be a valid rust identifier.
     #[arg(long)]
     pub guest_name: Option<String>,
+
+    /// Set a parameter of the template, as `name=value`.
+    #[arg(long, short)]
+    pub define: Vec<String>,
 }
 
 impl NewCommand {
@@ -98,21 +128,7 @@ impl NewCommand {
             std::env::current_dir().expect("Failed to fetch cwd")
         };
 
-        let mut template_path = TemplatePath {
-            auto_path: Some(self.template.clone()),
-            subfolder: Some(self.templ_subdir.clone()),
-            git: None,
-            branch: None,
-            path: None,
-            favorite: None,
-            tag: Some(self.tag.clone()),
-            test: false,
-        };
-
-        if !self.branch.is_empty() {
-            template_path.branch = Some(self.branch.clone());
-            template_path.tag = None;
-        }
+        let template_path = self.template_path()?;
 
         let risc0_version = std::env::var("CARGO_PKG_VERSION")
             .unwrap_or_else(|_| RISC0_DEFAULT_VERSION.to_string());
@@ -123,16 +139,20 @@ impl NewCommand {
                 format!("git = \"https://github.com/risc0/risc0.git\", branch = \"{branch}\"");
             template_variables.push(format!("risc0_build={spec}"));
             template_variables.push(format!("risc0_zkvm={spec}"));
+            template_variables.push(format!("bonsai_sdk={spec}"));
         } else if let Some(path) = self.path.as_ref() {
             let path = path.to_str().unwrap();
             let build = format!("path = \"{path}/risc0/build\"");
             let zkvm = format!("path = \"{path}/risc0/zkvm\"");
+            let bonsai_sdk = format!("path = \"{path}/bonsai/sdk\"");
             template_variables.push(format!("risc0_build={build}"));
             template_variables.push(format!("risc0_zkvm={zkvm}"));
+            template_variables.push(format!("bonsai_sdk={bonsai_sdk}"));
         } else {
             let spec = format!("version = \"{risc0_version}\"");
             template_variables.push(format!("risc0_build={spec}"));
             template_variables.push(format!("risc0_zkvm={spec}"));
+            template_variables.push(format!("bonsai_sdk=version = \"{BONSAI_SDK_VERSION}\""));
         }
 
         let guest_name = match &self.guest_name {
@@ -167,6 +187,13 @@ impl NewCommand {
             template_variables.push("risc0_feature_std=, features = ['std']".to_string());
         }
 
+        for define in &self.define {
+            if !define.contains('=') {
+                bail!("template parameter [{define}] must be set as name=value");
+            }
+            template_variables.push(define.clone());
+        }
+
         cargo_generate::generate(GenerateArgs {
             template_path,
             list_favorites: false,
@@ -196,6 +223,47 @@ impl NewCommand {
 
         Ok(())
     }
+
+    // Resolve `--template` to a built-in template, or to a git repository or
+    // directory.
+    fn template_path(&self) -> Result<TemplatePath> {
+        let builtin = BUILTIN_TEMPLATES.contains(&self.template.as_str());
+        let mut template_path = TemplatePath {
+            auto_path: Some(self.template.clone()),
+            subfolder: self.templ_subdir.clone(),
+            git: None,
+            branch: None,
+            path: None,
+            favorite: None,
+            tag: self.tag.clone(),
+            test: false,
+        };
+        if builtin {
+            if self.templ_subdir.is_some() {
+                bail!(
+                    "--templ-subdir cannot be used with the built-in template {}",
+                    self.template
+                );
+            }
+            let subdir = format!("{RISC0_TEMPLATES_DIR}/{}", self.template);
+            if let Some(path) = &self.path {
+                template_path.auto_path = Some(path.join(subdir).to_string_lossy().into_owned());
+                template_path.tag = None;
+                return Ok(template_path);
+            }
+            template_path.auto_path = Some(RISC0_GH_REPO.to_string());
+            template_path.subfolder = Some(subdir);
+            template_path.tag = template_path
+                .tag
+                .or_else(|| Some(RISC0_RELEASE_TAG.to_string()));
+        }
+
+        if !self.branch.is_empty() {
+            template_path.branch = Some(self.branch.clone());
+            template_path.tag = None;
+        }
+        Ok(template_path)
+    }
 }
 
 #[cfg(test)]
@@ -341,4 +409,63 @@ mod tests {
             &proj_path.join("methods/guest/Cargo.toml")
         ));
     }
+
+    #[test]
+    fn builtin_template_path() {
+        let new = NewCommand::parse_from(["new", "--template", "axum", "my_project"]);
+        let template_path = new.template_path().unwrap();
+        assert_eq!(template_path.auto_path.as_deref(), Some(RISC0_GH_REPO));
+        assert_eq!(template_path.subfolder.as_deref(), Some("templates/axum"));
+        assert_eq!(template_path.tag.as_deref(), Some(RISC0_RELEASE_TAG));
+
+        let new = NewCommand::parse_from([
+            "new",
+            "--template",
+            "https://github.com/example/template",
+            "my_project",
+        ]);
+        let template_path = new.template_path().unwrap();
+        assert_eq!(template_path.subfolder, None);
+        assert_eq!(template_path.tag, None);
+
+        let new = NewCommand::parse_from([
+            "new",
+            "--template",
+            "bonsai",
+            "--templ-subdir",
+            "templates",
+            "my_project",
+        ]);
+        assert!(new.template_path().is_err());
+    }
+
+    #[test]
+    fn generate_builtin() {
+        let (tmpdir, template_path, proj_name) = make_test_env();
+
+        let new = NewCommand::parse_from([
+            "new",
+            "--template",
+            "bonsai",
+            "--path",
+            &template_path.to_string_lossy(),
+            "--dest",
+            &tmpdir.path().to_string_lossy(),
+            "--no-git",
+            "--guest-name",
+            "method",
+            proj_name,
+        ]);
+
+        new.run().unwrap();
+
+        let proj_path = tmpdir.path().join(proj_name);
+        let host_manifest = proj_path.join("host/Cargo.toml");
+        assert!(find_in_file("bonsai-sdk = { path = ", &host_manifest));
+        assert!(find_in_file("/risc0/zkvm\"", &host_manifest));
+        assert!(find_in_file(
+            "METHOD_ELF",
+            &proj_path.join("host/src/main.rs")
+        ));
+    }
 }
@@ -0,0 +1,151 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+    process::Stdio,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use cargo_metadata::{Artifact, Message};
+use clap::Parser;
+use risc0_zkvm::{default_executor, ExecutorEnv};
+
+/// `cargo risczero profile`
+///
+/// Builds a guest binary, runs it in the executor with the profiler enabled,
+/// and writes a pprof profile and folded stacks for a flamegraph of its
+/// cycles. Cycles spent in ecalls and in paging memory show up as the frames
+/// `[ecall ...]` and `[paging]`.
+#[derive(Parser)]
+pub struct ProfileCommand {
+    /// The guest binary to profile.
+    #[arg(long)]
+    pub bin: String,
+
+    /// Location of the Cargo.toml for the guest code.
+    #[arg(long, default_value = "Cargo.toml")]
+    pub manifest_path: PathBuf,
+
+    /// Feature flags passed to cargo.
+    #[arg(long, value_delimiter = ',')]
+    pub features: Vec<String>,
+
+    /// Profile this ELF instead of building the guest.
+    #[arg(long)]
+    pub elf: Option<PathBuf>,
+
+    /// A file whose contents are passed to the guest as stdin.
+    #[arg(long)]
+    pub input: Option<PathBuf>,
+
+    /// The directory to write `{bin}.pb` and `{bin}.folded` to.
+    #[arg(long, default_value = ".")]
+    pub output_dir: PathBuf,
+
+    /// Arguments passed to the guest.
+    #[arg(last = true)]
+    pub args: Vec<String>,
+}
+
+impl ProfileCommand {
+    pub fn run(&self) -> Result<()> {
+        let elf_path = match &self.elf {
+            Some(elf) => elf.clone(),
+            None => self.build()?,
+        };
+        let elf = fs::read(&elf_path)
+            .with_context(|| format!("Failed to read ELF file at path: {}", elf_path.display()))?;
+        let input = match &self.input {
+            Some(path) => fs::read(path)
+                .with_context(|| format!("Failed to read input at path: {}", path.display()))?,
+            None => vec![],
+        };
+
+        fs::create_dir_all(&self.output_dir)?;
+        let pprof_out = self.output_dir.join(format!("{}.pb", self.bin));
+        let flamegraph_out = self.output_dir.join(format!("{}.folded", self.bin));
+
+        let env = ExecutorEnv::builder()
+            .args(&[self.bin.clone()])
+            .args(&self.args)
+            .stdin(input.as_slice())
+            .enable_profiler(&pprof_out)
+            .enable_flamegraph(&flamegraph_out)
+            .build()?;
+        let session = default_executor().execute(env, &elf)?;
+
+        let cycles: u64 = session.segments.iter().map(|s| s.cycles as u64).sum();
+        let total: u64 = session.segments.iter().map(|s| 1 << s.po2).sum();
+        println!(
+            "Executed `{}` in {} segments: {cycles} user cycles, {total} total cycles, exit code {:?}",
+            self.bin,
+            session.segments.len(),
+            session.exit_code
+        );
+        println!("Wrote pprof profile to {}", pprof_out.display());
+        println!("Wrote flamegraph stacks to {}", flamegraph_out.display());
+
+        Ok(())
+    }
+
+    // Build the guest binary with debug info, so that the profile can name the
+    // functions and lines the cycles are spent in.
+    fn build(&self) -> Result<PathBuf> {
+        let mut cmd = risc0_build::cargo_command("build", &[]);
+        cmd.env("CARGO_PROFILE_RELEASE_DEBUG", "true")
+            .arg("--release")
+            .arg("--message-format=json")
+            .args(["--bin", &self.bin])
+            .arg("--manifest-path")
+            .arg(&self.manifest_path);
+        if !self.features.is_empty() {
+            cmd.args(["--features", &self.features.join(",")]);
+        }
+
+        let mut child = cmd.stdout(Stdio::piped()).spawn()?;
+        let reader = io::BufReader::new(
+            child
+                .stdout
+                .take()
+                .ok_or(anyhow!("failed to read from cmd stdout"))?,
+        );
+        let mut elf = None;
+        for message in Message::parse_stream(reader) {
+            match message? {
+                Message::CompilerArtifact(Artifact {
+                    executable: Some(exec_path),
+                    target,
+                    ..
+                }) if target.name == self.bin => {
+                    elf = Some(exec_path.into_std_path_buf());
+                }
+                Message::CompilerMessage(msg) => {
+                    write!(io::stderr(), "{}", msg)?;
+                }
+                _ => (),
+            }
+        }
+
+        let output = child
+            .wait()
+            .with_context(|| "couldn't get cargo's exit status")?;
+        if !output.success() {
+            bail!("failed to build guest")
+        }
+        elf.ok_or_else(|| anyhow!("cargo built no binary named `{}`", self.bin))
+    }
+}
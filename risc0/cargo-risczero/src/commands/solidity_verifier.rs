@@ -0,0 +1,87 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use hex::FromHex;
+use risc0_groth16::{fr_from_hex_string, VerifyingKeyJson};
+use risc0_zkvm::{sha::Digest, CompactReceipt};
+
+/// `cargo risczero solidity-verifier`
+#[derive(Parser)]
+pub struct SolidityVerifierCommand {
+    /// Path to write the contract to.
+    ///
+    /// Default: stdout
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+
+    /// Name of the generated contract.
+    #[arg(long, default_value = "RiscZeroGroth16Verifier")]
+    pub contract_name: String,
+
+    /// Control root to bind the verifier to, as hex.
+    ///
+    /// Default: the control root of this release.
+    #[arg(long)]
+    pub control_root: Option<String>,
+
+    /// Control ID of the identity_p254 recursion program, as hex.
+    ///
+    /// Default: the BN254 control ID of this release.
+    #[arg(long)]
+    pub bn254_control_id: Option<String>,
+
+    /// Path to a Groth16 verification key exported by snarkjs.
+    ///
+    /// Default: the verifying key of this release.
+    #[arg(long)]
+    pub verification_key: Option<PathBuf>,
+}
+
+impl SolidityVerifierCommand {
+    pub fn run(&self) -> Result<()> {
+        let mut params = CompactReceipt::verifier_parameters()?;
+        if let Some(control_root) = &self.control_root {
+            params.control_root = Digest::from_hex(control_root.trim_start_matches("0x"))
+                .context("Invalid control root")?;
+        }
+        if let Some(control_id) = &self.bn254_control_id {
+            params.bn254_control_id = fr_from_hex_string(control_id.trim_start_matches("0x"))?;
+        }
+        if let Some(path) = &self.verification_key {
+            let contents = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let vk: VerifyingKeyJson = serde_json::from_str(&contents)?;
+            params.verifying_key = vk.prepared_verifying_key()?.vk;
+        }
+
+        let source = params.solidity_verifier(&self.contract_name)?;
+        match &self.output {
+            Some(path) => std::fs::write(path, source)
+                .with_context(|| format!("Failed to write {}", path.display()))?,
+            None => print!("{source}"),
+        }
+        eprintln!(
+            "Generated {} with selector 0x{} for verifier parameters 0x{}.",
+            self.contract_name,
+            hex::encode(params.selector()),
+            params.digest()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use hex::FromHex;
+use risc0_build::{ExpectedGuest, GuestManifest};
+use risc0_zkvm::sha::Digest;
+
+/// `cargo risczero verify-build`
+///
+/// Rebuilds a guest from source in the reproducible docker environment, and
+/// checks its image ID against an expected one, such as one registered
+/// on-chain. When they differ, each artifact that differs from the expected
+/// manifest or ELF is listed.
+///
+/// NOTE: Requires Docker to be installed and running.
+#[derive(Parser)]
+pub struct VerifyBuildCommand {
+    /// Location of the Cargo.toml for the guest code.
+    ///
+    /// This path is relative to the current directory.
+    #[arg(long)]
+    pub manifest_path: PathBuf,
+
+    /// Feature flags passed to cargo.
+    #[arg(long, value_delimiter = ',')]
+    pub features: Vec<String>,
+
+    /// The name of the guest binary to verify.
+    #[arg(long)]
+    pub guest: String,
+
+    /// The expected image ID, in hex.
+    ///
+    /// Defaults to the image ID of the guest manifest.
+    #[arg(long, required_unless_present = "guest_manifest")]
+    pub image_id: Option<String>,
+
+    /// A guest manifest written by the expected build.
+    #[arg(long)]
+    pub guest_manifest: Option<PathBuf>,
+
+    /// The ELF binary of the expected build, to compare segment by segment.
+    #[arg(long)]
+    pub elf: Option<PathBuf>,
+}
+
+impl VerifyBuildCommand {
+    pub fn run(&self) -> Result<()> {
+        let manifest = self
+            .guest_manifest
+            .as_ref()
+            .map(|path| -> Result<GuestManifest> {
+                let json = std::fs::read(path)
+                    .with_context(|| format!("Failed to read manifest: {}", path.display()))?;
+                serde_json::from_slice(&json)
+                    .with_context(|| format!("Invalid manifest: {}", path.display()))
+            })
+            .transpose()?;
+        let image_id = match (&self.image_id, &manifest) {
+            (Some(image_id), _) => image_id,
+            (None, Some(manifest)) => &manifest.image_id,
+            (None, None) => unreachable!("clap requires an image ID or a manifest"),
+        };
+        let image_id = Digest::from_hex(image_id).context("Invalid expected image ID")?;
+
+        let mut expected = ExpectedGuest::new(&self.guest, image_id);
+        if let Some(manifest) = manifest {
+            expected = expected.with_manifest(manifest);
+        }
+        if let Some(path) = &self.elf {
+            let elf = std::fs::read(path)
+                .with_context(|| format!("Failed to read ELF file at path: {}", path.display()))?;
+            expected = expected.with_elf(elf);
+        }
+
+        let src_dir = std::env::current_dir()?;
+        let verification =
+            risc0_build::verify_build(&self.manifest_path, &src_dir, &self.features, &expected)?;
+        verification.check()?;
+        // With a matching image ID, any differences are in artifacts that do not
+        // affect it, such as a stripped ELF.
+        for difference in &verification.differences {
+            eprintln!("note: {difference}");
+        }
+        println!(
+            "{} matches image ID {} (environment {})",
+            verification.guest.name, verification.guest.image_id, verification.environment_digest
+        );
+        Ok(())
+    }
+}
@@ -24,11 +24,15 @@ pub use self::commands::build::BuildSubcommand;
 
 use clap::{Parser, Subcommand};
 
+#[cfg(feature = "prove")]
+use self::commands::benchmark::BenchmarkCommand;
 #[cfg(feature = "experimental")]
 use self::commands::build::BuildCommand;
 use self::commands::{
     build_guest::BuildGuest, build_toolchain::BuildToolchain, deploy::DeployCommand,
-    install::Install, new::NewCommand,
+    image_id::ImageIdCommand, inspect::InspectCommand, install::Install, new::NewCommand,
+    profile::ProfileCommand, solidity_verifier::SolidityVerifierCommand,
+    verify_build::VerifyBuildCommand,
 };
 
 #[derive(Parser)]
@@ -51,6 +55,9 @@ pub struct Risczero {
 #[derive(Subcommand)]
 /// Primary commands  of `cargo risczero`.
 pub enum RisczeroCmd {
+    /// Benchmark proving on this machine.
+    #[cfg(feature = "prove")]
+    Benchmark(BenchmarkCommand),
     /// Build guest code.
     Build(BuildGuest),
     /// Build the riscv32im-risc0-zkvm-elf toolchain.
@@ -61,6 +68,16 @@ pub enum RisczeroCmd {
     New(NewCommand),
     /// Uploads the guest code to Bonsai.
     Deploy(DeployCommand),
+    /// Print the image IDs of guest ELFs.
+    ImageId(ImageIdCommand),
+    /// Print the structure of a receipt.
+    Inspect(InspectCommand),
+    /// Profile the cycles of a guest.
+    Profile(ProfileCommand),
+    /// Generates a Solidity verifier contract for Groth16 receipts.
+    SolidityVerifier(SolidityVerifierCommand),
+    /// Rebuild a guest from source and check it against an expected image ID.
+    VerifyBuild(VerifyBuildCommand),
     /// Build a crate for RISC Zero.
     #[cfg(feature = "experimental")]
     BuildCrate(BuildCommand),
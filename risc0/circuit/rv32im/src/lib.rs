@@ -19,7 +19,7 @@ pub mod control_id;
 mod info;
 pub mod layout;
 pub mod poly_ext;
-#[cfg(feature = "prove")]
+#[cfg(any(feature = "execute", feature = "prove"))]
 pub mod prove;
 mod taps;
 pub mod trace;
@@ -16,7 +16,7 @@ use std::{fmt, ops};
 
 use risc0_zkvm_platform::WORD_SIZE;
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct ByteAddr(pub u32);
 
 #[derive(Clone, Copy, PartialEq)]
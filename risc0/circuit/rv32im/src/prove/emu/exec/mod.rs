@@ -44,12 +44,13 @@ use super::{
     addr::{ByteAddr, WordAddr},
     pager::PagedMemory,
     rv32im::{DecodedInstruction, EmuContext, Emulator, Instruction, TrapCause},
+    trap::Trap,
     BIGINT_CYCLES, SYSTEM_START,
 };
 use crate::{
     prove::{
+        emu::loader::{FINI_CYCLES, INIT_CYCLES},
         emu::sha_cycles,
-        engine::loader::{FINI_CYCLES, INIT_CYCLES},
         segment::{Segment, SyscallRecord},
     },
     trace::{TraceCallback, TraceEvent},
@@ -66,6 +67,13 @@ pub trait Syscall {
         ctx: &mut dyn SyscallContext,
         into_guest: &mut [u32],
     ) -> Result<(u32, u32)>;
+
+    /// Returns the number of cycles run outside of the executor on behalf of
+    /// the guest, such as by other programs it called. These count towards the
+    /// session limit along with the cycles of the guest itself.
+    fn charged_cycles(&self) -> u64 {
+        0
+    }
 }
 
 /// Access to memory and machine state for syscalls.
@@ -90,6 +98,13 @@ pub trait SyscallContext {
 
     /// Returns the current cycle count.
     fn get_cycle(&self) -> u64;
+
+    /// Returns the indices of the pages of guest memory touched so far in this
+    /// execution, across all segments.
+    fn touched_pages(&self) -> BTreeSet<u32>;
+
+    /// Returns the program counter of the syscall being executed.
+    fn get_pc(&self) -> ByteAddr;
 }
 
 pub struct ExecutorResult {
@@ -101,12 +116,43 @@ pub struct ExecutorResult {
     pub pre_state: SystemState,
     pub post_state: SystemState,
     pub output_digest: Option<Digest>,
+    /// The trap that ended execution, when [Executor::set_split_on_trap] is
+    /// set.
+    pub trap: Option<Trap>,
 }
 
+/// Returned when a guest runs for longer than the configured no-progress
+/// limit without making a syscall.
+///
+/// Journal writes and all other host interaction happen through syscalls, so a
+/// guest that makes none is not observably making progress.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoProgressError {
+    /// The user cycle at which the guest was stopped.
+    pub cycle: u64,
+
+    /// The number of cycles run since the last syscall.
+    pub limit: u64,
+}
+
+impl core::fmt::Display for NoProgressError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "No progress: guest ran {} cycles without a syscall (stopped at cycle {})",
+            self.limit, self.cycle
+        )
+    }
+}
+
+impl std::error::Error for NoProgressError {}
+
 #[derive(Default)]
 struct SessionCycles {
     user: usize,
     total: usize,
+    // The user cycle count when the last syscall completed.
+    progress: usize,
 }
 
 pub struct SimpleSession {
@@ -137,6 +183,10 @@ pub struct Executor<'a, 'b, S: Syscall> {
     pending: PendingState,
     trace: Vec<Rc<RefCell<dyn TraceCallback + 'b>>>,
     cycles: SessionCycles,
+    no_progress_limit: Option<u64>,
+    split_on_trap: bool,
+    // Pages touched in the segments that have already been split off.
+    touched_pages: BTreeSet<u32>,
 }
 
 impl PendingState {
@@ -177,9 +227,27 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
             },
             trace,
             cycles: SessionCycles::default(),
+            no_progress_limit: None,
+            split_on_trap: false,
+            touched_pages: BTreeSet::new(),
         }
     }
 
+    /// Stop execution with a [NoProgressError] if the guest runs for more than
+    /// `limit` user cycles without making a syscall.
+    pub fn set_no_progress_limit(&mut self, limit: Option<u64>) {
+        self.no_progress_limit = limit;
+    }
+
+    /// End execution at a [Trap] rather than failing with it.
+    ///
+    /// The trapping instruction does not run, and the last segment ends with
+    /// [ExitCode::SystemSplit] at its pc, so that the execution up to the trap
+    /// can be proven. The trap is returned in [ExecutorResult::trap].
+    pub fn set_split_on_trap(&mut self, split_on_trap: bool) {
+        self.split_on_trap = split_on_trap;
+    }
+
     pub fn run<F: FnMut(Segment) -> Result<()>>(
         &mut self,
         segment_po2: usize,
@@ -200,6 +268,7 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
         let mut emu = Emulator::new();
         let mut segments = 0;
         let initial_state = self.pager.image.get_system_state();
+        let mut trap = None;
 
         loop {
             if self.exit_code.is_some() {
@@ -207,12 +276,36 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
             }
 
             if let Some(max_cycles) = max_cycles {
-                if self.cycles.user >= max_cycles as usize {
+                let cycles = self.cycles.user as u64 + self.syscall_handler.charged_cycles();
+                if cycles >= max_cycles {
                     bail!("Session limit exceeded");
                 }
             }
 
-            emu.step(self)?;
+            if let Some(limit) = self.no_progress_limit {
+                if (self.cycles.user - self.cycles.progress) as u64 >= limit {
+                    return Err(NoProgressError {
+                        cycle: self.cycles.user as u64,
+                        limit,
+                    }
+                    .into());
+                }
+            }
+
+            match emu.step(self) {
+                Ok(()) => {}
+                Err(err) if self.split_on_trap && err.is::<Trap>() => {
+                    // A trap is raised before the instruction changes any
+                    // state, so only its page reads are undone.
+                    self.pager.undo();
+                    self.pending.reset(self.pc);
+                    self.pending.events.clear();
+                    self.exit_code = Some(ExitCode::SystemSplit);
+                    trap = err.downcast().ok();
+                    break;
+                }
+                Err(err) => return Err(err),
+            }
 
             let segment_cycles = self.insn_cycles + self.pager.cycles + self.pending.cycles;
             if segment_cycles < segment_limit {
@@ -249,6 +342,7 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
                 })?;
                 segments += 1;
                 self.cycles.total += 1 << segment_po2;
+                self.touched_pages.extend(self.pager.get_faults().reads);
                 self.pager.clear();
                 self.insn_cycles = 0;
 
@@ -296,6 +390,7 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
             pre_state: initial_state,
             post_state,
             output_digest: self.output_digest,
+            trap,
         })
     }
 
@@ -312,6 +407,13 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
             for event in &self.pending.events {
                 trace.borrow_mut().trace_callback(event.clone()).unwrap();
             }
+
+            let paging_cycles = self.pager.step_cycles();
+            if paging_cycles > 0 {
+                trace.borrow_mut().trace_callback(TraceEvent::Paging {
+                    cycles: paging_cycles as u64,
+                })?;
+            }
         }
 
         self.pc = self.pending.pc;
@@ -321,6 +423,7 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
         self.pending.events.clear();
         if let Some(syscall) = self.pending.syscall.take() {
             self.syscalls.push(syscall);
+            self.cycles.progress = self.cycles.user;
         }
         self.output_digest = self.pending.output_digest.take();
         self.exit_code = self.pending.exit_code.take();
@@ -337,6 +440,7 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
         self.pending.reset(self.pc);
         self.cycles.user = 0;
         self.cycles.total = 0;
+        self.cycles.progress = 0;
     }
 }
 
@@ -640,9 +744,12 @@ impl<'a, 'b, S: Syscall> EmuContext for Executor<'a, 'b, S> {
     }
 
     fn trap(&self, cause: TrapCause) -> Result<bool> {
-        let msg = format!("Trap: {cause:08x?}, pc: {:?}", self.pc);
-        tracing::info!("{msg}");
-        bail!("{msg}");
+        let trap = Trap {
+            pc: self.pc.0,
+            cause,
+        };
+        tracing::info!("{trap}");
+        Err(trap.into())
     }
 
     fn check_data_load(&self, addr: ByteAddr) -> bool {
@@ -715,6 +822,18 @@ impl<'a, 'b, S: Syscall> SyscallContext for Executor<'a, 'b, S> {
         self.cycles.user as u64
     }
 
+    fn get_pc(&self) -> ByteAddr {
+        self.pending.pc
+    }
+
+    fn touched_pages(&self) -> BTreeSet<u32> {
+        let mut pages = self.touched_pages.clone();
+        pages.extend(self.pager.get_faults().reads);
+        // Leave out the pages of the page table itself.
+        pages.retain(|&page_idx| (page_idx as usize) * PAGE_SIZE < GUEST_MAX_MEM);
+        pages
+    }
+
     fn peek_register(&mut self, idx: usize) -> Result<u32> {
         if idx >= REG_MAX {
             bail!("invalid register: x{idx}");
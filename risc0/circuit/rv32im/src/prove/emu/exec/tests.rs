@@ -27,7 +27,9 @@ use super::{Syscall, SyscallContext};
 use crate::prove::emu::{
     addr::ByteAddr,
     exec::DEFAULT_SEGMENT_LIMIT_PO2,
+    rv32im::TrapCause,
     testutil::{self, DEFAULT_SESSION_LIMIT},
+    trap::{self, Trap},
 };
 
 #[derive(Default, Clone)]
@@ -129,3 +131,70 @@ fn system_split() {
         segments[0].post_state.digest::<ShaImpl>()
     );
 }
+
+#[test]
+fn no_progress_limit() {
+    let program = testutil::simple_loop();
+    let image = MemoryImage::new(&program, PAGE_SIZE as u32).unwrap();
+
+    let syscall_handler = BasicSyscall::default();
+    let mut exec = super::Executor::new(image, &syscall_handler, None, Vec::new());
+    exec.set_no_progress_limit(Some(1000));
+    let err = exec
+        .run(DEFAULT_SEGMENT_LIMIT_PO2, DEFAULT_SESSION_LIMIT, |_| Ok(()))
+        .err()
+        .unwrap();
+    let err = err.downcast_ref::<super::NoProgressError>().unwrap();
+    assert_eq!(err.limit, 1000);
+    assert!(err.cycle >= 1000);
+}
+
+#[test]
+fn split_on_trap() {
+    let program = testutil::misaligned_load();
+    let image = MemoryImage::new(&program, PAGE_SIZE as u32).unwrap();
+    let expected = Trap {
+        pc: 0x4004,
+        cause: TrapCause::LoadAddressMisaligned,
+    };
+
+    let syscall_handler = BasicSyscall::default();
+    let mut exec = super::Executor::new(image.clone(), &syscall_handler, None, Vec::new());
+    let err = exec
+        .run(DEFAULT_SEGMENT_LIMIT_PO2, DEFAULT_SESSION_LIMIT, |_| Ok(()))
+        .err()
+        .unwrap();
+    assert_eq!(err.downcast_ref::<Trap>(), Some(&expected));
+
+    let mut exec = super::Executor::new(image, &syscall_handler, None, Vec::new());
+    exec.set_split_on_trap(true);
+    let mut segments = Vec::new();
+    let result = exec
+        .run(
+            DEFAULT_SEGMENT_LIMIT_PO2,
+            DEFAULT_SESSION_LIMIT,
+            |segment| {
+                segments.push(segment);
+                Ok(())
+            },
+        )
+        .unwrap();
+    assert_eq!(result.exit_code, ExitCode::SystemSplit);
+    assert_eq!(result.trap, Some(expected.clone()));
+    assert_eq!(result.post_state.pc, expected.pc);
+    assert_eq!(segments.last().unwrap().exit_code, ExitCode::SystemSplit);
+
+    // The trap replays from the opened pages of the post state alone.
+    let (trap, pages) = trap::open(&result.post_image).unwrap();
+    assert_eq!(trap, expected);
+    assert!(pages.page_indices().count() < result.post_image.page_indices().count());
+    assert_eq!(
+        pages.get_system_state().digest::<ShaImpl>(),
+        result.post_state.digest::<ShaImpl>()
+    );
+    assert_eq!(trap::replay(&pages).unwrap(), expected);
+
+    // Replay fails without the pages the instruction reads.
+    let root = pages.open([]);
+    assert!(trap::replay(&root).is_err());
+}
@@ -14,11 +14,13 @@
 
 pub mod addr;
 pub mod exec;
+pub mod loader;
 pub mod mux;
 mod pager;
 pub mod preflight;
 pub mod rv32im;
 pub mod testutil;
+pub mod trap;
 
 use risc0_zkvm_platform::{memory::SYSTEM, syscall::DIGEST_WORDS};
 
@@ -15,6 +15,7 @@
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
     mem::take,
+    sync::Arc,
 };
 
 use anyhow::Result;
@@ -37,7 +38,8 @@ const fn cycles_per_page(blocks_per_page: usize) -> usize {
     1 + SHA_INIT + (SHA_LOAD + SHA_MAIN) * blocks_per_page
 }
 
-struct Page(Vec<u8>);
+// Pages are shared with the image until they are first written.
+struct Page(Arc<Vec<u8>>);
 
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 enum PageState {
@@ -133,26 +135,17 @@ impl PagedMemory {
 
     pub fn commit(&mut self, pc: ByteAddr) -> (SystemState, MemoryImage, SystemState) {
         let pre_state = self.image.get_system_state();
-        let info = &self.image.info;
 
-        let mut image = MemoryImage {
-            pages: BTreeMap::new(),
-            info: info.clone(),
-            pc: pre_state.pc,
-        };
+        // Share 'original' version of all pages, this is just the subset of
+        // pages for the previous segment.
+        let image = self.image.select(self.page_states.keys().copied());
 
         for (page_idx, page_state) in &self.page_states {
-            // Copy 'original' version of all pages, this is just the subset of
-            // pages for the previous segment.
-            image
-                .pages
-                .insert(*page_idx, self.image.load_page(*page_idx));
-
             // Update all 'dirty' pages into the image that accumulates over
             // segments.
             if *page_state == PageState::Dirty {
                 let page = self.page_cache.get(page_idx).unwrap();
-                self.image.pages.insert(*page_idx, page.0.clone());
+                self.image.set_page(*page_idx, page.0.clone());
             }
         }
 
@@ -196,6 +189,17 @@ impl PagedMemory {
         }
     }
 
+    /// The paging cycles spent by the current step.
+    pub fn step_cycles(&self) -> usize {
+        self.pending_actions
+            .iter()
+            .map(|action| match action {
+                Action::PageRead(_, cycles) | Action::PageWrite(_, cycles, _) => *cycles,
+                Action::Store(..) => 0,
+            })
+            .sum()
+    }
+
     pub fn commit_step(&mut self) {
         self.pending_actions.clear();
     }
@@ -220,7 +224,7 @@ impl PagedMemory {
 
     fn load_page(&mut self, page_idx: u32) {
         tracing::trace!("load_page: 0x{page_idx:05x}");
-        let page = self.image.load_page(page_idx);
+        let page = self.image.share_page(page_idx);
         self.page_cache.insert(page_idx, Page(page));
         self.update(page_idx, PageState::Loaded);
         self.page_changed(page_idx, PageState::Loaded);
@@ -238,7 +242,7 @@ impl PagedMemory {
                     self.page_changed(parent_idx, goal);
                 }
             } else {
-                let page = self.image.load_page(parent_idx);
+                let page = self.image.share_page(parent_idx);
                 self.page_cache.insert(parent_idx, Page(page));
                 self.page_changed(parent_idx, goal);
             }
@@ -284,6 +288,7 @@ impl Page {
         let word_addr = (addr.0 % PAGE_WORDS as u32) as usize;
         let byte_addr = word_addr * WORD_SIZE;
         // tracing::trace!("store({addr:?}, 0x{data:08x})");
-        self.0[byte_addr..byte_addr + WORD_SIZE].clone_from_slice(&data.to_le_bytes());
+        Arc::make_mut(&mut self.0)[byte_addr..byte_addr + WORD_SIZE]
+            .clone_from_slice(&data.to_le_bytes());
     }
 }
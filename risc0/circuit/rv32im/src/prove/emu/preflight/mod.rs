@@ -30,7 +30,7 @@ use risc0_zkp::{
 use risc0_zkvm_platform::{
     syscall::{
         bigint, ecall, halt,
-        reg_abi::{REG_A0, REG_A1, REG_A2, REG_A3, REG_A4, REG_T0},
+        reg_abi::{REG_A0, REG_A1, REG_A2, REG_A3, REG_A4, REG_MAX, REG_T0},
         IO_CHUNK_WORDS,
     },
     WORD_SIZE,
@@ -44,7 +44,7 @@ use super::{
     ByteAddr, WordAddr, SHA_INIT, SHA_MAIN_FINI, SHA_MAIN_MIX, SYSTEM_START,
 };
 use crate::prove::{
-    engine::loader::{
+    emu::loader::{
         FINI_CYCLES, RAM_LOAD_CYCLES, SETUP_CYCLES, SHA_INIT_OFFSET, SHA_K, SHA_K_OFFSET,
         ZEROS_OFFSET,
     },
@@ -98,6 +98,17 @@ pub struct PreflightTrace {
     pub body: PreflightStage,
 }
 
+/// The state of the machine before an instruction of a [Segment] executes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InstructionRow {
+    /// Program counter of the instruction.
+    pub pc: u32,
+    /// Encoded instruction.
+    pub insn: u32,
+    /// Values of the registers x0 to x31.
+    pub registers: [u32; REG_MAX],
+}
+
 struct Preflight {
     steps: usize,
     pager: PagedMemory,
@@ -170,6 +181,20 @@ impl Preflight {
         }
     }
 
+    // Read the state through the pager rather than with load_u32, so that it is
+    // not recorded as a memory transaction.
+    fn instruction_row(&mut self) -> InstructionRow {
+        let mut registers = [0; REG_MAX];
+        for (idx, register) in registers.iter_mut().enumerate().skip(1) {
+            *register = self.pager.load(SYSTEM_START + idx);
+        }
+        InstructionRow {
+            pc: self.pc.0,
+            insn: self.pager.load(self.pc.waddr()),
+            registers,
+        }
+    }
+
     fn load_u32(&mut self, addr: WordAddr) -> Result<u32> {
         let data = self.pager.load(addr);
         // tracing::trace!("load_u32({addr:?}) -> 0x{data:08x}");
@@ -800,4 +825,21 @@ impl Segment {
 
         Ok(preflight.trace)
     }
+
+    /// Replay this segment, returning the state before each instruction it
+    /// executes, including the instruction that halts or pauses it.
+    pub fn instruction_trace(&self) -> Result<Vec<InstructionRow>> {
+        let mut preflight = Preflight::new(self);
+        let mut emu = Emulator::new();
+        let mut rows = Vec::with_capacity(self.insn_cycles);
+
+        preflight.pre_steps();
+        while preflight.trace.body.cycles.len() < self.insn_cycles && preflight.halted.is_none() {
+            rows.push(preflight.instruction_row());
+            emu.step(&mut preflight)?;
+            preflight.pager.commit_step();
+        }
+
+        Ok(rows)
+    }
 }
@@ -119,6 +119,32 @@ fn basic() {
     assert_eq!(trace.body.extras.len(), 0);
 }
 
+#[test]
+fn instruction_trace() {
+    let program = testutil::basic();
+    let image = MemoryImage::new(&program, PAGE_SIZE as u32).unwrap();
+
+    let result = execute(
+        image,
+        DEFAULT_SEGMENT_LIMIT_PO2,
+        DEFAULT_SESSION_LIMIT,
+        &NullSyscall::default(),
+        None,
+    )
+    .unwrap();
+    let rows = result.segments[0].instruction_trace().unwrap();
+
+    let pcs: Vec<_> = rows.iter().map(|row| row.pc).collect();
+    assert_eq!(pcs, [0x4000, 0x4004, 0x4008, 0x400c, 0x4010]);
+    assert_eq!(rows[2].insn, 0x003100b3);
+    assert_eq!(rows[2].registers[2], 0x1234b000);
+    assert_eq!(rows[2].registers[3], 0xf387e000);
+    assert_eq!(rows[2].registers[1], 0);
+    assert_eq!(rows[3].registers[1], 0x1234b000u32.wrapping_add(0xf387e000));
+    assert_eq!(rows[4].registers[11], 0x4000);
+    assert!(rows.iter().all(|row| row.registers[0] == 0));
+}
+
 #[test]
 fn system_split() {
     let program = testutil::simple_loop();
@@ -72,7 +72,7 @@ pub struct Emulator {
     table: FastDecodeTable,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TrapCause {
     InstructionAddressMisaligned,
     InstructionAccessFault,
@@ -78,6 +78,18 @@ pub fn simple_loop() -> Program {
     }
 }
 
+pub fn misaligned_load() -> Program {
+    Program {
+        entry: 0x4000,
+        image: BTreeMap::from([
+            (0x4000, 0x00004737), // lui a4, 0x4
+            (0x4004, 0x00172783), // lw a5, 1(a4)
+            (0x4008, 0x000045b7), // lui a1, 0x4
+            (0x400c, 0x00000073), // ecall(halt)
+        ]),
+    }
+}
+
 pub fn large_text() -> Program {
     let entry = 0x4000;
     let mut image = BTreeMap::new();
@@ -0,0 +1,180 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Traps raised by the guest, and replaying them from memory.
+//!
+//! The circuit has no exit code for a trap, so a session that traps is proven
+//! up to the trapping instruction, ending with [ExitCode::SystemSplit] in the
+//! state the instruction traps in. The trap itself is shown by replaying that
+//! one instruction against the pages of the post state it reads, each opened
+//! against the Merkle root of the post state.
+//!
+//! [ExitCode::SystemSplit]: risc0_binfmt::ExitCode::SystemSplit
+
+use std::{cell::Cell, collections::BTreeSet};
+
+use anyhow::{anyhow, bail, ensure, Result};
+use risc0_binfmt::MemoryImage;
+use risc0_zkvm_platform::{memory::is_guest_memory, WORD_SIZE};
+
+use super::{
+    addr::{ByteAddr, WordAddr},
+    rv32im::{DecodedInstruction, EmuContext, Emulator, Instruction, TrapCause},
+    SYSTEM_START,
+};
+
+/// Returned when the guest raises a trap, such as by running an illegal
+/// instruction or by a misaligned access.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trap {
+    /// The address of the instruction that raised the trap.
+    pub pc: u32,
+
+    /// The cause of the trap.
+    pub cause: TrapCause,
+}
+
+impl core::fmt::Display for Trap {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Trap: {:08x?}, pc: 0x{:08x}", self.cause, self.pc)
+    }
+}
+
+impl std::error::Error for Trap {}
+
+/// Replays the instruction at the pc of `image`, and returns the trap it
+/// raises along with the part of `image` that [replay] needs to raise it
+/// again: the pages the instruction reads, the page table pages that prove
+/// them, and the root page.
+pub fn open(image: &MemoryImage) -> Result<(Trap, MemoryImage)> {
+    let mut replay = Replay::new(image);
+    let trap = replay.run()?;
+    Ok((trap, image.open(replay.pages)))
+}
+
+/// Replays the instruction at the pc of `image`, and returns the trap it
+/// raises.
+///
+/// `image` may hold only some pages, such as one returned by [open]. Every
+/// page read by the instruction is checked against the page table of `image`,
+/// and replay fails if one is missing, so the trap holds for every image with
+/// the same [SystemState](risc0_binfmt::SystemState) as `image`.
+pub fn replay(image: &MemoryImage) -> Result<Trap> {
+    ensure!(
+        image.has_page(image.info.root_idx),
+        "image is missing its root page"
+    );
+    Replay::new(image).run()
+}
+
+struct Replay<'a> {
+    image: &'a MemoryImage,
+    pc: ByteAddr,
+    // The pages read by the instruction.
+    pages: BTreeSet<u32>,
+    cause: Cell<Option<TrapCause>>,
+}
+
+impl<'a> Replay<'a> {
+    fn new(image: &'a MemoryImage) -> Self {
+        Self {
+            image,
+            pc: ByteAddr(image.pc),
+            pages: BTreeSet::new(),
+            cause: Cell::new(None),
+        }
+    }
+
+    fn run(&mut self) -> Result<Trap> {
+        Emulator::new().step(self)?;
+        let cause = self
+            .cause
+            .get()
+            .ok_or_else(|| anyhow!("instruction at {:?} does not trap", self.pc))?;
+        Ok(Trap {
+            pc: self.pc.0,
+            cause,
+        })
+    }
+
+    fn load(&mut self, addr: WordAddr) -> Result<u32> {
+        let addr = addr.baddr().0;
+        self.image.check(addr)?;
+        self.pages.insert(self.image.info.get_page_index(addr));
+        let mut word = [0u8; WORD_SIZE];
+        self.image.load_region_in_page(addr, &mut word)?;
+        Ok(u32::from_le_bytes(word))
+    }
+
+    // Every check that traps comes before the instruction stores its result,
+    // so an instruction that stores anything does not trap.
+    fn no_trap(&self) -> Result<()> {
+        bail!("instruction at {:?} does not trap", self.pc)
+    }
+}
+
+impl<'a> EmuContext for Replay<'a> {
+    fn ecall(&mut self) -> Result<bool> {
+        self.no_trap()?;
+        Ok(false)
+    }
+
+    fn mret(&self) -> Result<bool> {
+        self.no_trap()?;
+        Ok(false)
+    }
+
+    fn trap(&self, cause: TrapCause) -> Result<bool> {
+        self.cause.set(Some(cause));
+        Ok(false)
+    }
+
+    fn on_insn_decoded(&self, _insn: &Instruction, _decoded: &DecodedInstruction) {}
+
+    fn on_normal_end(&mut self, _insn: &Instruction, _decoded: &DecodedInstruction) {}
+
+    fn get_pc(&self) -> ByteAddr {
+        self.pc
+    }
+
+    fn set_pc(&mut self, _addr: ByteAddr) {}
+
+    fn load_register(&mut self, idx: usize) -> Result<u32> {
+        self.load(SYSTEM_START + idx)
+    }
+
+    fn store_register(&mut self, _idx: usize, _data: u32) -> Result<()> {
+        self.no_trap()
+    }
+
+    fn load_memory(&mut self, addr: WordAddr) -> Result<u32> {
+        self.load(addr)
+    }
+
+    fn store_memory(&mut self, _addr: WordAddr, _data: u32) -> Result<()> {
+        self.no_trap()
+    }
+
+    fn check_insn_load(&self, addr: ByteAddr) -> bool {
+        is_guest_memory(addr.0)
+    }
+
+    fn check_data_load(&self, addr: ByteAddr) -> bool {
+        is_guest_memory(addr.0)
+    }
+
+    fn check_data_store(&self, addr: ByteAddr) -> bool {
+        is_guest_memory(addr.0)
+    }
+}
@@ -30,54 +30,14 @@ use risc0_zkp::{
     prove::poly_group::PolyGroup,
     MAX_CYCLES_PO2, MIN_CYCLES_PO2, ZK_CYCLES,
 };
-use risc0_zkvm_platform::{memory, WORD_SIZE};
+use risc0_zkvm_platform::WORD_SIZE;
 
 use crate::CIRCUIT;
 
-pub const SHA_K_OFFSET: usize = memory::PRE_LOAD.start();
-pub const SHA_K_SIZE: usize = 64;
-pub const SHA_INIT_OFFSET: usize = SHA_K_OFFSET + SHA_K_SIZE * WORD_SIZE;
-pub const ZEROS_OFFSET: usize = SHA_INIT_OFFSET + DIGEST_WORDS * WORD_SIZE;
-
-// TODO: generate from zirgen
-pub const SETUP_STEP_REGS: usize = 84;
-pub const SETUP_CYCLES: usize = setup_count(SETUP_STEP_REGS);
-pub const RAM_LOAD_CYCLES: usize = 27;
-
-// The number of cycles needed before the body phase.
-// BytesInit: 1
-// BytesSetup: 1561
-// RamInit: 1
-// RamLoad: 27
-// Reset(0): 2
-pub const INIT_CYCLES: usize = 1 + SETUP_CYCLES + 1 + RAM_LOAD_CYCLES + 2;
-
-// The number of cycles needed after the body phase.
-// Reset(1): 2
-// Reset(2): 2
-// RamFini: 1
-// BytesFini: 1
-pub const FINI_CYCLES: usize = 2 + 2 + 1 + 1;
-
-pub static SHA_K: [u32; SHA_K_SIZE] = [
-    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
-    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
-    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
-    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
-    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
-    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
-    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
-    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
-];
-
-const fn div_ceil(a: usize, b: usize) -> usize {
-    (a + b - 1) / b
-}
-
-const fn setup_count(regs: usize) -> usize {
-    let pairs = regs / 4;
-    div_ceil(32 * 1024, pairs)
-}
+pub use crate::prove::emu::loader::{
+    FINI_CYCLES, INIT_CYCLES, RAM_LOAD_CYCLES, SETUP_CYCLES, SETUP_STEP_REGS, SHA_INIT_OFFSET,
+    SHA_K, SHA_K_OFFSET, SHA_K_SIZE, ZEROS_OFFSET,
+};
 
 /// These are the registers of the control group.
 #[derive(Copy, Clone)]
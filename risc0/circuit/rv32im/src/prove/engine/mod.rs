@@ -66,7 +66,7 @@ where
     H: Hal<Field = BabyBear, Elem = BabyBearElem, ExtElem = BabyBearExtElem>,
     C: CircuitHal<H>,
 {
-    #[tracing::instrument(skip_all)]
+    #[tracing::instrument(skip_all, fields(po2 = segment.po2, index = segment.index))]
     fn prove_segment(&self, segment: &Segment) -> Result<Seal> {
         nvtx::range_push!("prove_segment");
 
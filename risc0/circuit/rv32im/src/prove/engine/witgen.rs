@@ -61,7 +61,7 @@ impl WitnessGenerator {
         }
     }
 
-    #[tracing::instrument(skip_all)]
+    #[tracing::instrument(name = "witgen", skip_all, fields(steps = self.steps))]
     pub fn execute(&mut self, trace: PreflightTrace) -> Result<()> {
         nvtx::range_push!("witgen");
 
@@ -13,21 +13,28 @@
 // limitations under the License.
 
 pub mod emu;
+#[cfg(feature = "prove")]
 pub mod engine;
+#[cfg(feature = "prove")]
 pub mod hal;
 pub mod segment;
 
+#[cfg(feature = "prove")]
 use anyhow::Result;
+#[cfg(feature = "prove")]
 use cfg_if::cfg_if;
 
+#[cfg(feature = "prove")]
 use self::segment::Segment;
 
 pub type Seal = Vec<u32>;
 
+#[cfg(feature = "prove")]
 pub trait SegmentProver {
     fn prove_segment(&self, segment: &Segment) -> Result<Seal>;
 }
 
+#[cfg(feature = "prove")]
 pub fn get_segment_prover() -> Box<dyn SegmentProver> {
     cfg_if! {
         if #[cfg(feature = "cuda")] {
@@ -47,6 +47,14 @@ pub enum TraceEvent {
         /// Data that's been written
         region: Vec<u8>,
     },
+
+    /// Cycles were spent paging memory in, or marking it dirty to be paged
+    /// out, for the instruction that started last. These cycles are not
+    /// counted in [TraceEvent::InstructionStart::cycle].
+    Paging {
+        /// The number of paging cycles
+        cycles: u64,
+    },
 }
 
 /// A callback used to collect [TraceEvent]s.
@@ -70,6 +78,7 @@ impl core::fmt::Debug for TraceEvent {
             Self::MemorySet { addr, region } => {
                 write!(f, "MemorySet(0x{addr:08X}, {region:#04X?})")
             }
+            Self::Paging { cycles } => write!(f, "Paging({cycles})"),
         }
     }
 }
@@ -0,0 +1,3 @@
+module github.com/risc0/risc0/risc0/ffi/go
+
+go 1.21
@@ -0,0 +1,25 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//go:build risc0_system
+
+package risc0
+
+// With the risc0_system build tag, link the library found by the linker, in
+// its default search path or in a directory given with CGO_LDFLAGS=-L<dir>.
+
+/*
+#cgo LDFLAGS: -lrisc0_ffi
+*/
+import "C"
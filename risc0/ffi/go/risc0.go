@@ -0,0 +1,257 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Package risc0 decodes and verifies RISC Zero receipts, by calling the C ABI
+// of the risc0-ffi crate through cgo.
+//
+// Receipts are in the bincode encoding produced by risc0-zkvm, r0vm and
+// Bonsai. The package is released with risc0-zkvm, and only links a library
+// built from the same release, whose seal format it shares.
+package risc0
+
+/*
+#cgo CFLAGS: -I${SRCDIR}/../include
+#cgo linux LDFLAGS: -lgcc_s -lutil -lrt -lpthread -lm -ldl
+#cgo darwin LDFLAGS: -framework Security -framework CoreFoundation -liconv -lm
+#include <stdlib.h>
+#include "risc0.h"
+*/
+import "C"
+
+import (
+	"crypto/sha256"
+	"encoding/binary"
+	"encoding/hex"
+	"fmt"
+	"runtime"
+	"strings"
+	"unsafe"
+)
+
+// LibraryVersion is the release of risc0-zkvm, and of risc0-ffi, that this
+// package is versioned with.
+const LibraryVersion = "1.0.0-rc.3"
+
+// ABIVersion is the version of the C ABI that this package was built with.
+const ABIVersion = C.RISC0_ABI_VERSION
+
+func init() {
+	if abi := uint32(C.risc0_abi_version()); abi != ABIVersion {
+		panic(fmt.Sprintf("risc0: linked risc0-ffi has ABI version %d, but this package uses %d", abi, ABIVersion))
+	}
+	if version := Version(); version != LibraryVersion {
+		panic(fmt.Sprintf("risc0: linked risc0-ffi %s, but this package is for %s", version, LibraryVersion))
+	}
+}
+
+// Version returns the release of risc0-zkvm that the linked library was built
+// from.
+func Version() string {
+	return C.GoString(C.risc0_version())
+}
+
+// Status is the outcome of a call into the library.
+type Status int
+
+// The statuses of failed calls.
+const (
+	StatusInvalidArgument Status = C.RISC0_STATUS_INVALID_ARGUMENT
+	StatusExecuteFailed   Status = C.RISC0_STATUS_EXECUTE_FAILED
+	StatusProveFailed     Status = C.RISC0_STATUS_PROVE_FAILED
+	StatusVerifyFailed    Status = C.RISC0_STATUS_VERIFY_FAILED
+	StatusSerializeFailed Status = C.RISC0_STATUS_SERIALIZE_FAILED
+	StatusPanic           Status = C.RISC0_STATUS_PANIC
+)
+
+// Error is the error of a failed call into the library.
+type Error struct {
+	Status  Status
+	Message string
+}
+
+func (e *Error) Error() string {
+	return "risc0: " + e.Message
+}
+
+// Is reports whether target is an *Error with the same status, so that
+// errors.Is(err, ErrVerifyFailed) matches every failed verification.
+func (e *Error) Is(target error) bool {
+	t, ok := target.(*Error)
+	return ok && t.Status == e.Status
+}
+
+// Errors to compare with errors.Is.
+var (
+	ErrInvalidArgument  = &Error{Status: StatusInvalidArgument, Message: "invalid argument"}
+	ErrVerifyFailed     = &Error{Status: StatusVerifyFailed, Message: "verification failed"}
+	ErrMalformedReceipt = &Error{Status: StatusSerializeFailed, Message: "malformed receipt"}
+)
+
+// call runs f, which makes one call into the library, and returns its error.
+// The library keeps the message of the error in a thread-local, so the
+// goroutine stays on one OS thread until it has read it.
+func call(f func() C.Risc0Status) error {
+	runtime.LockOSThread()
+	defer runtime.UnlockOSThread()
+	if status := f(); status != C.RISC0_STATUS_OK {
+		return &Error{Status: Status(status), Message: C.GoString(C.risc0_last_error())}
+	}
+	return nil
+}
+
+func bytesPtr(b []byte) *C.uint8_t {
+	if len(b) == 0 {
+		return nil
+	}
+	return (*C.uint8_t)(unsafe.Pointer(&b[0]))
+}
+
+// take copies the bytes of a buffer filled in by the library, and releases it.
+func take(buffer C.Risc0Buffer) []byte {
+	defer C.risc0_buffer_free(buffer)
+	return C.GoBytes(unsafe.Pointer(buffer.data), C.int(buffer.len))
+}
+
+// Digest is a SHA-256 digest, such as an image ID or the digest of a journal.
+type Digest [32]byte
+
+// ParseDigest parses a digest from 64 hex digits, optionally prefixed by 0x.
+func ParseDigest(s string) (Digest, error) {
+	var d Digest
+	b, err := hex.DecodeString(strings.TrimPrefix(s, "0x"))
+	if err != nil {
+		return d, fmt.Errorf("risc0: malformed digest: %w", err)
+	}
+	if len(b) != len(d) {
+		return d, fmt.Errorf("risc0: a digest is 32 bytes, not %d", len(b))
+	}
+	copy(d[:], b)
+	return d, nil
+}
+
+// DigestFromWords converts the [u32; 8] form of an image ID, as generated for
+// guests by risc0-build, to a digest.
+func DigestFromWords(words [8]uint32) Digest {
+	var d Digest
+	for i, word := range words {
+		binary.LittleEndian.PutUint32(d[4*i:], word)
+	}
+	return d
+}
+
+// JournalDigest returns the digest of a journal that receipts commit to.
+func JournalDigest(journal []byte) Digest {
+	return sha256.Sum256(journal)
+}
+
+func (d Digest) String() string {
+	return hex.EncodeToString(d[:])
+}
+
+func (d *Digest) ptr() *C.uint8_t {
+	return (*C.uint8_t)(unsafe.Pointer(&d[0]))
+}
+
+// Receipt is a receipt decoded by the library.
+//
+// A receipt may be used by many goroutines at once, but not while it is being
+// closed. Receipts that are not closed are released when they are garbage
+// collected.
+type Receipt struct {
+	ptr *C.Risc0Receipt
+}
+
+// DecodeReceipt decodes a receipt from its bincode encoding.
+func DecodeReceipt(data []byte) (*Receipt, error) {
+	var ptr *C.Risc0Receipt
+	err := call(func() C.Risc0Status {
+		return C.risc0_receipt_deserialize(bytesPtr(data), C.size_t(len(data)), &ptr)
+	})
+	if err != nil {
+		return nil, err
+	}
+	r := &Receipt{ptr: ptr}
+	runtime.SetFinalizer(r, (*Receipt).Close)
+	return r, nil
+}
+
+// Close releases the receipt. Closing it again does nothing.
+func (r *Receipt) Close() {
+	if r.ptr != nil {
+		C.risc0_receipt_free(r.ptr)
+		r.ptr = nil
+		runtime.SetFinalizer(r, nil)
+	}
+}
+
+// Verify checks that the receipt proves a successful execution of the image
+// with ID imageID. It returns an error matching ErrVerifyFailed if not.
+func (r *Receipt) Verify(imageID Digest) error {
+	defer runtime.KeepAlive(r)
+	return call(func() C.Risc0Status {
+		return C.risc0_receipt_verify(r.ptr, imageID.ptr())
+	})
+}
+
+// VerifyJournal checks the receipt as Verify does, and also that its journal
+// has the digest journalDigest.
+func (r *Receipt) VerifyJournal(imageID, journalDigest Digest) error {
+	defer runtime.KeepAlive(r)
+	return call(func() C.Risc0Status {
+		return C.risc0_receipt_verify_journal(r.ptr, imageID.ptr(), journalDigest.ptr())
+	})
+}
+
+// Journal returns the journal of the receipt, which is only authenticated once
+// the receipt is verified.
+func (r *Receipt) Journal() ([]byte, error) {
+	defer runtime.KeepAlive(r)
+	var journal C.Risc0Buffer
+	if err := call(func() C.Risc0Status { return C.risc0_receipt_journal(r.ptr, &journal) }); err != nil {
+		return nil, err
+	}
+	return take(journal), nil
+}
+
+// JournalDigest returns the digest of the journal of the receipt.
+func (r *Receipt) JournalDigest() (Digest, error) {
+	defer runtime.KeepAlive(r)
+	var d Digest
+	err := call(func() C.Risc0Status { return C.risc0_receipt_journal_digest(r.ptr, d.ptr()) })
+	return d, err
+}
+
+// Encode returns the bincode encoding of the receipt.
+func (r *Receipt) Encode() ([]byte, error) {
+	defer runtime.KeepAlive(r)
+	var bytes C.Risc0Buffer
+	if err := call(func() C.Risc0Status { return C.risc0_receipt_serialize(r.ptr, &bytes) }); err != nil {
+		return nil, err
+	}
+	return take(bytes), nil
+}
+
+// VerifyReceipt decodes a receipt, verifies that it proves a successful
+// execution of the image with ID imageID, and returns its journal.
+func VerifyReceipt(data []byte, imageID Digest) ([]byte, error) {
+	r, err := DecodeReceipt(data)
+	if err != nil {
+		return nil, err
+	}
+	defer r.Close()
+	if err := r.Verify(imageID); err != nil {
+		return nil, err
+	}
+	return r.Journal()
+}
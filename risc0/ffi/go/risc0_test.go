@@ -0,0 +1,72 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+package risc0
+
+import (
+	"errors"
+	"testing"
+)
+
+const testID = "0100000002000000030000000400000005000000060000000700000008000000"
+
+func TestDigest(t *testing.T) {
+	want := DigestFromWords([8]uint32{1, 2, 3, 4, 5, 6, 7, 8})
+	if want.String() != testID {
+		t.Fatalf("DigestFromWords = %s", want)
+	}
+	for _, s := range []string{testID, "0x" + testID} {
+		d, err := ParseDigest(s)
+		if err != nil || d != want {
+			t.Errorf("ParseDigest(%q) = %s, %v", s, d, err)
+		}
+	}
+	for _, s := range []string{"", "0x1234", testID[1:], "zz" + testID[2:]} {
+		if _, err := ParseDigest(s); err == nil {
+			t.Errorf("ParseDigest(%q) succeeded", s)
+		}
+	}
+	if JournalDigest(nil).String() != "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855" {
+		t.Errorf("JournalDigest(nil) = %s", JournalDigest(nil))
+	}
+}
+
+func TestVersion(t *testing.T) {
+	if Version() != LibraryVersion {
+		t.Errorf("Version() = %s, want %s", Version(), LibraryVersion)
+	}
+}
+
+func TestMalformedReceipt(t *testing.T) {
+	for _, data := range [][]byte{nil, {0xff, 0xff, 0xff, 0xff}} {
+		_, err := DecodeReceipt(data)
+		if !errors.Is(err, ErrMalformedReceipt) {
+			t.Errorf("DecodeReceipt(%x) = %v", data, err)
+		}
+		if _, err := VerifyReceipt(data, Digest{}); !errors.Is(err, ErrMalformedReceipt) {
+			t.Errorf("VerifyReceipt(%x) = %v", data, err)
+		}
+	}
+}
+
+func TestClosedReceipt(t *testing.T) {
+	r := &Receipt{}
+	r.Close()
+	if err := r.Verify(Digest{}); !errors.Is(err, ErrInvalidArgument) {
+		t.Errorf("Verify on a closed receipt = %v", err)
+	}
+	if _, err := r.Journal(); !errors.Is(err, ErrInvalidArgument) {
+		t.Errorf("Journal on a closed receipt = %v", err)
+	}
+}
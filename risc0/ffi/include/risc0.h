@@ -0,0 +1,277 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// The C ABI of the RISC Zero zkVM, implemented by the risc0-ffi library.
+//
+// Memory:
+// - Pointers passed to the library are borrowed for the duration of the call.
+//   A null pointer may be passed for any byte string of length zero.
+// - A Risc0Buffer filled in by the library is owned by the caller, who
+//   releases it with risc0_buffer_free.
+// - A Risc0Receipt made by the library is owned by the caller, who releases it
+//   with risc0_receipt_free.
+// - Out parameters are only written when a call succeeds.
+//
+// Errors: every fallible function returns a Risc0Status. When it is not
+// RISC0_STATUS_OK, risc0_last_error returns a message owned by the library,
+// which is valid until the next call into the library on the same thread.
+//
+// The library may be called from any thread. Each call is independent, and
+// receipts may be shared between threads as long as none of them frees the
+// receipt while it is in use.
+
+#ifndef RISC0_H
+#define RISC0_H
+
+// This file is generated by cbindgen from src/lib.rs, do not edit it by hand.
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+// The version of the ABI declared in `include/risc0.h`.
+//
+// The version changes whenever a declaration of the header does, other than
+// by a new function or a new status.
+#define RISC0_ABI_VERSION 1
+
+// The kind of receipt for [risc0_prove] to make, from the least to the most
+// compressed.
+#define RISC0_RECEIPT_KIND_COMPOSITE 0
+
+// A succinct receipt, see [RISC0_RECEIPT_KIND_COMPOSITE].
+#define RISC0_RECEIPT_KIND_SUCCINCT 1
+
+// A PLONK receipt, see [RISC0_RECEIPT_KIND_COMPOSITE].
+#define RISC0_RECEIPT_KIND_PLONK 2
+
+// A compact (Groth16) receipt, see [RISC0_RECEIPT_KIND_COMPOSITE].
+#define RISC0_RECEIPT_KIND_COMPACT 3
+
+// How the session of [risc0_execute] ended.
+typedef enum Risc0ExitKind {
+  // The guest halted, with the user code of [Risc0SessionInfo].
+  RISC0_EXIT_KIND_HALTED = 0,
+  // The guest paused, with the user code of [Risc0SessionInfo].
+  RISC0_EXIT_KIND_PAUSED = 1,
+  // The execution was split by the system.
+  RISC0_EXIT_KIND_SYSTEM_SPLIT = 2,
+  // The execution reached its session limit.
+  RISC0_EXIT_KIND_SESSION_LIMIT = 3,
+} Risc0ExitKind;
+
+// The outcome of a call into the library.
+typedef enum Risc0Status {
+  // The call succeeded.
+  RISC0_STATUS_OK = 0,
+  // An argument was null, out of range, or otherwise invalid.
+  RISC0_STATUS_INVALID_ARGUMENT = 1,
+  // The guest could not be loaded or executed.
+  RISC0_STATUS_EXECUTE_FAILED = 2,
+  // The guest could not be proven.
+  RISC0_STATUS_PROVE_FAILED = 3,
+  // A receipt did not verify.
+  RISC0_STATUS_VERIFY_FAILED = 4,
+  // A receipt could not be serialized or deserialized.
+  RISC0_STATUS_SERIALIZE_FAILED = 5,
+  // The library panicked, which is a bug in the library.
+  RISC0_STATUS_PANIC = 6,
+} Risc0Status;
+
+// A receipt owned by the caller, released with [risc0_receipt_free].
+typedef struct Risc0Receipt Risc0Receipt;
+
+// A byte string owned by the caller, released with [risc0_buffer_free].
+typedef struct Risc0Buffer {
+  // The bytes of the string.
+  uint8_t *data;
+  // The number of bytes at `data`.
+  size_t len;
+} Risc0Buffer;
+
+// Options for [risc0_prove], made by [risc0_prove_options_default].
+typedef struct Risc0ProveOptions {
+  // The size of this struct as the caller was built, so that later versions
+  // of the ABI can add fields.
+  uint32_t size;
+  // One of the `RISC0_RECEIPT_KIND_*` constants.
+  uint32_t receipt_kind;
+  // The po2 of the segments, or zero for the default.
+  uint32_t segment_po2;
+  // The most cycles to execute, or zero for no limit.
+  uint64_t session_limit;
+  // Whether to prove sessions in which the guest exited with an error.
+  bool prove_guest_errors;
+} Risc0ProveOptions;
+
+// A summary of the session run by [risc0_execute].
+typedef struct Risc0SessionInfo {
+  // How the session ended.
+  enum Risc0ExitKind exit_kind;
+  // The exit code of the guest, when it halted or paused.
+  uint32_t exit_user_code;
+  // The number of segments the session was split into.
+  uint32_t segments;
+  // The number of cycles the guest ran, without the overhead of
+  // continuations and padding.
+  uint64_t user_cycles;
+} Risc0SessionInfo;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Return [RISC0_ABI_VERSION], for callers to check against the version of
+// the header they were built with.
+uint32_t risc0_abi_version(void);
+
+// Return the version of `risc0-zkvm` that the library was built from, such as
+// `1.0.0`.
+//
+// Receipts are only compatible between libraries built from releases whose
+// seal formats agree, so callers that exchange receipts with other hosts can
+// check them against this.
+const char *risc0_version(void);
+
+// Return the error message of the last failed call on this thread, or null
+// if the last call succeeded.
+//
+// The message is owned by the library and valid until the next call into the
+// library on this thread.
+const char *risc0_last_error(void);
+
+// Release a buffer filled in by the library. Releasing a buffer whose data is
+// null does nothing.
+//
+// # Safety
+//
+// `buffer` must have been filled in by the library and not released before.
+void risc0_buffer_free(struct Risc0Buffer buffer);
+
+// Return the default options of [risc0_prove], which make a composite
+// receipt.
+struct Risc0ProveOptions risc0_prove_options_default(void);
+
+// Compute the image ID of a guest ELF, writing its 32 bytes to `image_id`.
+//
+// # Safety
+//
+// `elf` must point to `elf_len` bytes, and `image_id` to 32 writable bytes.
+enum Risc0Status risc0_compute_image_id(const uint8_t *elf, size_t elf_len, uint8_t *image_id);
+
+// Execute a guest ELF with `input` as its stdin, without proving it.
+//
+// On success, fills in `info` and sets `journal` to the journal of the
+// session. A guest that exits with an error succeeds, with its exit code in
+// `info`.
+//
+// # Safety
+//
+// `elf` and `input` must point to `elf_len` and `input_len` bytes, and
+// `info` and `journal` must be valid for writes.
+enum Risc0Status risc0_execute(const uint8_t *elf,
+                               size_t elf_len,
+                               const uint8_t *input,
+                               size_t input_len,
+                               struct Risc0SessionInfo *info,
+                               struct Risc0Buffer *journal);
+
+// Execute and prove a guest ELF with `input` as its stdin, setting `receipt`
+// to the receipt of the session.
+//
+// `options` may be null for the defaults of [risc0_prove_options_default].
+//
+// # Safety
+//
+// `elf` and `input` must point to `elf_len` and `input_len` bytes,
+// `options` must be null or point to options whose `size` is set, and
+// `receipt` must be valid for writes.
+enum Risc0Status risc0_prove(const uint8_t *elf,
+                             size_t elf_len,
+                             const uint8_t *input,
+                             size_t input_len,
+                             const struct Risc0ProveOptions *options,
+                             struct Risc0Receipt **receipt);
+
+// Verify that `receipt` proves an execution of the guest with the 32-byte
+// `image_id`, returning [Risc0Status::VerifyFailed] if it does not.
+//
+// # Safety
+//
+// `receipt` must have been made by the library, and `image_id` must point to
+// 32 bytes.
+enum Risc0Status risc0_receipt_verify(const struct Risc0Receipt *receipt, const uint8_t *image_id);
+
+// Verify `receipt` as [risc0_receipt_verify] does, and also that the SHA-256
+// digest of its journal is the 32-byte `journal_digest`, returning
+// [Risc0Status::VerifyFailed] if either check fails.
+//
+// # Safety
+//
+// `receipt` must have been made by the library, and `image_id` and
+// `journal_digest` must each point to 32 bytes.
+enum Risc0Status risc0_receipt_verify_journal(const struct Risc0Receipt *receipt,
+                                              const uint8_t *image_id,
+                                              const uint8_t *journal_digest);
+
+// Write the 32 bytes of the SHA-256 digest of the journal of `receipt` to
+// `digest`.
+//
+// # Safety
+//
+// `receipt` must have been made by the library, and `digest` must point to 32
+// writable bytes.
+enum Risc0Status risc0_receipt_journal_digest(const struct Risc0Receipt *receipt, uint8_t *digest);
+
+// Set `journal` to the journal of `receipt`.
+//
+// # Safety
+//
+// `receipt` must have been made by the library, and `journal` must be valid
+// for writes.
+enum Risc0Status risc0_receipt_journal(const struct Risc0Receipt *receipt,
+                                       struct Risc0Buffer *journal);
+
+// Serialize `receipt`, setting `bytes` to its encoding.
+//
+// # Safety
+//
+// `receipt` must have been made by the library, and `bytes` must be valid for
+// writes.
+enum Risc0Status risc0_receipt_serialize(const struct Risc0Receipt *receipt,
+                                         struct Risc0Buffer *bytes);
+
+// Deserialize a receipt from the encoding of [risc0_receipt_serialize],
+// setting `receipt` to it.
+//
+// # Safety
+//
+// `data` must point to `len` bytes, and `receipt` must be valid for writes.
+enum Risc0Status risc0_receipt_deserialize(const uint8_t *data,
+                                           size_t len,
+                                           struct Risc0Receipt **receipt);
+
+// Release a receipt made by the library. Releasing null does nothing.
+//
+// # Safety
+//
+// `receipt` must be null or have been made by the library and not released
+// before.
+void risc0_receipt_free(struct Risc0Receipt *receipt);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* RISC0_H */
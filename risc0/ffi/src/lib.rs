@@ -0,0 +1,687 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![deny(missing_docs)]
+
+//! A C ABI for the zkVM, so that hosts written in Go, Java, C++ and other
+//! languages can execute and prove guests and verify receipts in-process.
+//!
+//! The ABI is declared in `include/risc0.h`, which is the reference for
+//! callers. Every function returns a [Risc0Status], and on failure leaves a
+//! message for [risc0_last_error]. The rules for memory are:
+//!
+//! - Pointers passed into the library are borrowed for the duration of the
+//!   call. A null pointer may be passed for any byte string of length zero.
+//! - A [Risc0Buffer] filled in by the library is owned by the caller, who
+//!   releases it with [risc0_buffer_free].
+//! - A [Risc0Receipt] made by the library is owned by the caller, who releases
+//!   it with [risc0_receipt_free].
+//! - Out parameters are only written when a call succeeds.
+//!
+//! Receipts are serialized with bincode, as they are by `r0vm` and Bonsai.
+//! Provers and executors are chosen as by [risc0_zkvm::default_prover], so
+//! `RISC0_DEV_MODE` and the other environment variables of the zkVM apply.
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr, slice,
+};
+
+use anyhow::anyhow;
+use risc0_zkvm::{
+    compute_image_id, default_executor, default_prover,
+    sha::{Digest, Digestible},
+    ExecutorEnv, ExitCode, ProverOpts, Receipt, ReceiptKind, VerifierContext,
+};
+
+/// The version of the ABI declared in `include/risc0.h`.
+///
+/// The version changes whenever a declaration of the header does, other than
+/// by a new function or a new status.
+pub const RISC0_ABI_VERSION: u32 = 1;
+
+/// The outcome of a call into the library.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Risc0Status {
+    /// The call succeeded.
+    Ok = 0,
+    /// An argument was null, out of range, or otherwise invalid.
+    InvalidArgument = 1,
+    /// The guest could not be loaded or executed.
+    ExecuteFailed = 2,
+    /// The guest could not be proven.
+    ProveFailed = 3,
+    /// A receipt did not verify.
+    VerifyFailed = 4,
+    /// A receipt could not be serialized or deserialized.
+    SerializeFailed = 5,
+    /// The library panicked, which is a bug in the library.
+    Panic = 6,
+}
+
+/// A byte string owned by the caller, released with [risc0_buffer_free].
+#[repr(C)]
+pub struct Risc0Buffer {
+    /// The bytes of the string.
+    pub data: *mut u8,
+    /// The number of bytes at `data`.
+    pub len: usize,
+}
+
+impl Risc0Buffer {
+    fn new(bytes: Vec<u8>) -> Self {
+        let len = bytes.len();
+        let data = Box::into_raw(bytes.into_boxed_slice()) as *mut u8;
+        Self { data, len }
+    }
+}
+
+/// How the session of [risc0_execute] ended.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Risc0ExitKind {
+    /// The guest halted, with the user code of [Risc0SessionInfo].
+    Halted = 0,
+    /// The guest paused, with the user code of [Risc0SessionInfo].
+    Paused = 1,
+    /// The execution was split by the system.
+    SystemSplit = 2,
+    /// The execution reached its session limit.
+    SessionLimit = 3,
+}
+
+/// A summary of the session run by [risc0_execute].
+#[repr(C)]
+pub struct Risc0SessionInfo {
+    /// How the session ended.
+    pub exit_kind: Risc0ExitKind,
+    /// The exit code of the guest, when it halted or paused.
+    pub exit_user_code: u32,
+    /// The number of segments the session was split into.
+    pub segments: u32,
+    /// The number of cycles the guest ran, without the overhead of
+    /// continuations and padding.
+    pub user_cycles: u64,
+}
+
+/// The kind of receipt for [risc0_prove] to make, from the least to the most
+/// compressed.
+pub const RISC0_RECEIPT_KIND_COMPOSITE: u32 = 0;
+/// A succinct receipt, see [RISC0_RECEIPT_KIND_COMPOSITE].
+pub const RISC0_RECEIPT_KIND_SUCCINCT: u32 = 1;
+/// A PLONK receipt, see [RISC0_RECEIPT_KIND_COMPOSITE].
+pub const RISC0_RECEIPT_KIND_PLONK: u32 = 2;
+/// A compact (Groth16) receipt, see [RISC0_RECEIPT_KIND_COMPOSITE].
+pub const RISC0_RECEIPT_KIND_COMPACT: u32 = 3;
+
+/// Options for [risc0_prove], made by [risc0_prove_options_default].
+#[repr(C)]
+pub struct Risc0ProveOptions {
+    /// The size of this struct as the caller was built, so that later versions
+    /// of the ABI can add fields.
+    pub size: u32,
+    /// One of the `RISC0_RECEIPT_KIND_*` constants.
+    pub receipt_kind: u32,
+    /// The po2 of the segments, or zero for the default.
+    pub segment_po2: u32,
+    /// The most cycles to execute, or zero for no limit.
+    pub session_limit: u64,
+    /// Whether to prove sessions in which the guest exited with an error.
+    pub prove_guest_errors: bool,
+}
+
+/// A receipt owned by the caller, released with [risc0_receipt_free].
+pub struct Risc0Receipt(Receipt);
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+struct Error(Risc0Status, anyhow::Error);
+
+type Result<T> = std::result::Result<T, Error>;
+
+trait Context<T> {
+    fn status(self, status: Risc0Status) -> Result<T>;
+}
+
+impl<T, E: Into<anyhow::Error>> Context<T> for std::result::Result<T, E> {
+    fn status(self, status: Risc0Status) -> Result<T> {
+        self.map_err(|err| Error(status, err.into()))
+    }
+}
+
+fn invalid(message: &str) -> Error {
+    Error(Risc0Status::InvalidArgument, anyhow!("{message}"))
+}
+
+// Run the body of an exported function, recording its error for
+// `risc0_last_error` and keeping panics from unwinding into the caller.
+fn call(f: impl FnOnce() -> Result<()>) -> Risc0Status {
+    LAST_ERROR.with(|last| last.borrow_mut().take());
+    let (status, message) = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => return Risc0Status::Ok,
+        Ok(Err(Error(status, err))) => (status, format!("{err:#}")),
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|msg| msg.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            (Risc0Status::Panic, format!("panicked: {message}"))
+        }
+    };
+    let message = CString::new(message.replace('\0', "")).unwrap();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+    status
+}
+
+// Borrow `len` bytes at `data` as a slice.
+unsafe fn bytes<'a>(data: *const u8, len: usize, name: &str) -> Result<&'a [u8]> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+    if data.is_null() {
+        return Err(invalid(&format!("{name} is null")));
+    }
+    Ok(slice::from_raw_parts(data, len))
+}
+
+unsafe fn out<'a, T>(ptr: *mut T, name: &str) -> Result<&'a mut T> {
+    ptr.as_mut()
+        .ok_or_else(|| invalid(&format!("{name} is null")))
+}
+
+unsafe fn receipt<'a>(receipt: *const Risc0Receipt) -> Result<&'a Receipt> {
+    receipt
+        .as_ref()
+        .map(|receipt| &receipt.0)
+        .ok_or_else(|| invalid("receipt is null"))
+}
+
+/// Return [RISC0_ABI_VERSION], for callers to check against the version of
+/// the header they were built with.
+#[no_mangle]
+pub extern "C" fn risc0_abi_version() -> u32 {
+    RISC0_ABI_VERSION
+}
+
+/// Return the version of `risc0-zkvm` that the library was built from, such as
+/// `1.0.0`.
+///
+/// Receipts are only compatible between libraries built from releases whose
+/// seal formats agree, so callers that exchange receipts with other hosts can
+/// check them against this.
+#[no_mangle]
+pub extern "C" fn risc0_version() -> *const c_char {
+    // The crate is versioned with the rest of the workspace, so this is the
+    // version of risc0-zkvm too.
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// Return the error message of the last failed call on this thread, or null
+/// if the last call succeeded.
+///
+/// The message is owned by the library and valid until the next call into the
+/// library on this thread.
+#[no_mangle]
+pub extern "C" fn risc0_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Release a buffer filled in by the library. Releasing a buffer whose data is
+/// null does nothing.
+///
+/// # Safety
+///
+/// `buffer` must have been filled in by the library and not released before.
+#[no_mangle]
+pub unsafe extern "C" fn risc0_buffer_free(buffer: Risc0Buffer) {
+    if !buffer.data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            buffer.data,
+            buffer.len,
+        )));
+    }
+}
+
+/// Return the default options of [risc0_prove], which make a composite
+/// receipt.
+#[no_mangle]
+pub extern "C" fn risc0_prove_options_default() -> Risc0ProveOptions {
+    Risc0ProveOptions {
+        size: std::mem::size_of::<Risc0ProveOptions>() as u32,
+        receipt_kind: RISC0_RECEIPT_KIND_COMPOSITE,
+        segment_po2: 0,
+        session_limit: 0,
+        prove_guest_errors: false,
+    }
+}
+
+/// Compute the image ID of a guest ELF, writing its 32 bytes to `image_id`.
+///
+/// # Safety
+///
+/// `elf` must point to `elf_len` bytes, and `image_id` to 32 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn risc0_compute_image_id(
+    elf: *const u8,
+    elf_len: usize,
+    image_id: *mut u8,
+) -> Risc0Status {
+    call(|| {
+        let elf = bytes(elf, elf_len, "elf")?;
+        if image_id.is_null() {
+            return Err(invalid("image_id is null"));
+        }
+        let digest = compute_image_id(elf).status(Risc0Status::ExecuteFailed)?;
+        ptr::copy_nonoverlapping(digest.as_bytes().as_ptr(), image_id, 32);
+        Ok(())
+    })
+}
+
+fn env(input: &[u8], segment_po2: u32, session_limit: u64) -> Result<ExecutorEnv<'_>> {
+    let mut builder = ExecutorEnv::builder();
+    builder
+        .stdin(input)
+        .session_limit((session_limit != 0).then_some(session_limit));
+    if segment_po2 != 0 {
+        builder.segment_limit_po2(segment_po2);
+    }
+    builder.build().status(Risc0Status::InvalidArgument)
+}
+
+/// Execute a guest ELF with `input` as its stdin, without proving it.
+///
+/// On success, fills in `info` and sets `journal` to the journal of the
+/// session. A guest that exits with an error succeeds, with its exit code in
+/// `info`.
+///
+/// # Safety
+///
+/// `elf` and `input` must point to `elf_len` and `input_len` bytes, and
+/// `info` and `journal` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn risc0_execute(
+    elf: *const u8,
+    elf_len: usize,
+    input: *const u8,
+    input_len: usize,
+    info: *mut Risc0SessionInfo,
+    journal: *mut Risc0Buffer,
+) -> Risc0Status {
+    call(|| {
+        let elf = bytes(elf, elf_len, "elf")?;
+        let input = bytes(input, input_len, "input")?;
+        let info = out(info, "info")?;
+        let journal = out(journal, "journal")?;
+
+        let session = default_executor()
+            .execute(env(input, 0, 0)?, elf)
+            .status(Risc0Status::ExecuteFailed)?;
+        let (exit_kind, exit_user_code) = match session.exit_code {
+            ExitCode::Halted(code) => (Risc0ExitKind::Halted, code),
+            ExitCode::Paused(code) => (Risc0ExitKind::Paused, code),
+            ExitCode::SystemSplit => (Risc0ExitKind::SystemSplit, 0),
+            ExitCode::SessionLimit => (Risc0ExitKind::SessionLimit, 0),
+        };
+        *info = Risc0SessionInfo {
+            exit_kind,
+            exit_user_code,
+            segments: session.segments.len() as u32,
+            user_cycles: session
+                .segments
+                .iter()
+                .map(|segment| segment.cycles as u64)
+                .sum(),
+        };
+        *journal = Risc0Buffer::new(session.journal.bytes);
+        Ok(())
+    })
+}
+
+/// Execute and prove a guest ELF with `input` as its stdin, setting `receipt`
+/// to the receipt of the session.
+///
+/// `options` may be null for the defaults of [risc0_prove_options_default].
+///
+/// # Safety
+///
+/// `elf` and `input` must point to `elf_len` and `input_len` bytes,
+/// `options` must be null or point to options whose `size` is set, and
+/// `receipt` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn risc0_prove(
+    elf: *const u8,
+    elf_len: usize,
+    input: *const u8,
+    input_len: usize,
+    options: *const Risc0ProveOptions,
+    receipt: *mut *mut Risc0Receipt,
+) -> Risc0Status {
+    call(|| {
+        let elf = bytes(elf, elf_len, "elf")?;
+        let input = bytes(input, input_len, "input")?;
+        let receipt = out(receipt, "receipt")?;
+        let defaults = risc0_prove_options_default();
+        let options = match options.as_ref() {
+            None => &defaults,
+            Some(options) if options.size as usize >= std::mem::size_of::<Risc0ProveOptions>() => {
+                options
+            }
+            Some(_) => return Err(invalid("options.size is too small")),
+        };
+        let receipt_kind = match options.receipt_kind {
+            RISC0_RECEIPT_KIND_COMPOSITE => ReceiptKind::Composite,
+            RISC0_RECEIPT_KIND_SUCCINCT => ReceiptKind::Succinct,
+            RISC0_RECEIPT_KIND_PLONK => ReceiptKind::Plonk,
+            RISC0_RECEIPT_KIND_COMPACT => ReceiptKind::Compact,
+            kind => return Err(invalid(&format!("unknown receipt kind {kind}"))),
+        };
+        let opts = ProverOpts::default()
+            .with_receipt_kind(receipt_kind)
+            .with_prove_guest_errors(options.prove_guest_errors);
+
+        let env = env(input, options.segment_po2, options.session_limit)?;
+        let info = default_prover()
+            .prove_with_ctx(env, &VerifierContext::default(), elf, &opts)
+            .status(Risc0Status::ProveFailed)?;
+        *receipt = Box::into_raw(Box::new(Risc0Receipt(info.receipt)));
+        Ok(())
+    })
+}
+
+/// Verify that `receipt` proves an execution of the guest with the 32-byte
+/// `image_id`, returning [Risc0Status::VerifyFailed] if it does not.
+///
+/// # Safety
+///
+/// `receipt` must have been made by the library, and `image_id` must point to
+/// 32 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn risc0_receipt_verify(
+    receipt: *const Risc0Receipt,
+    image_id: *const u8,
+) -> Risc0Status {
+    call(|| {
+        let receipt = self::receipt(receipt)?;
+        let image_id = bytes(image_id, 32, "image_id")?;
+        let image_id = Digest::try_from(image_id).status(Risc0Status::InvalidArgument)?;
+        receipt.verify(image_id).status(Risc0Status::VerifyFailed)
+    })
+}
+
+/// Verify `receipt` as [risc0_receipt_verify] does, and also that the SHA-256
+/// digest of its journal is the 32-byte `journal_digest`, returning
+/// [Risc0Status::VerifyFailed] if either check fails.
+///
+/// # Safety
+///
+/// `receipt` must have been made by the library, and `image_id` and
+/// `journal_digest` must each point to 32 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn risc0_receipt_verify_journal(
+    receipt: *const Risc0Receipt,
+    image_id: *const u8,
+    journal_digest: *const u8,
+) -> Risc0Status {
+    call(|| {
+        let receipt = self::receipt(receipt)?;
+        let image_id = bytes(image_id, 32, "image_id")?;
+        let image_id = Digest::try_from(image_id).status(Risc0Status::InvalidArgument)?;
+        let expected = bytes(journal_digest, 32, "journal_digest")?;
+        let expected = Digest::try_from(expected).status(Risc0Status::InvalidArgument)?;
+        receipt.verify(image_id).status(Risc0Status::VerifyFailed)?;
+        let actual = receipt.journal.digest();
+        if actual != expected {
+            return Err(Error(
+                Risc0Status::VerifyFailed,
+                anyhow!("journal digest is {actual}, expected {expected}"),
+            ));
+        }
+        Ok(())
+    })
+}
+
+/// Write the 32 bytes of the SHA-256 digest of the journal of `receipt` to
+/// `digest`.
+///
+/// # Safety
+///
+/// `receipt` must have been made by the library, and `digest` must point to 32
+/// writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn risc0_receipt_journal_digest(
+    receipt: *const Risc0Receipt,
+    digest: *mut u8,
+) -> Risc0Status {
+    call(|| {
+        let receipt = self::receipt(receipt)?;
+        if digest.is_null() {
+            return Err(invalid("digest is null"));
+        }
+        let journal_digest = receipt.journal.digest();
+        ptr::copy_nonoverlapping(journal_digest.as_bytes().as_ptr(), digest, 32);
+        Ok(())
+    })
+}
+
+/// Set `journal` to the journal of `receipt`.
+///
+/// # Safety
+///
+/// `receipt` must have been made by the library, and `journal` must be valid
+/// for writes.
+#[no_mangle]
+pub unsafe extern "C" fn risc0_receipt_journal(
+    receipt: *const Risc0Receipt,
+    journal: *mut Risc0Buffer,
+) -> Risc0Status {
+    call(|| {
+        let receipt = self::receipt(receipt)?;
+        *out(journal, "journal")? = Risc0Buffer::new(receipt.journal.bytes.clone());
+        Ok(())
+    })
+}
+
+/// Serialize `receipt`, setting `bytes` to its encoding.
+///
+/// # Safety
+///
+/// `receipt` must have been made by the library, and `bytes` must be valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn risc0_receipt_serialize(
+    receipt: *const Risc0Receipt,
+    bytes: *mut Risc0Buffer,
+) -> Risc0Status {
+    call(|| {
+        let receipt = self::receipt(receipt)?;
+        let encoded = bincode::serialize(receipt).status(Risc0Status::SerializeFailed)?;
+        *out(bytes, "bytes")? = Risc0Buffer::new(encoded);
+        Ok(())
+    })
+}
+
+/// Deserialize a receipt from the encoding of [risc0_receipt_serialize],
+/// setting `receipt` to it.
+///
+/// # Safety
+///
+/// `data` must point to `len` bytes, and `receipt` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn risc0_receipt_deserialize(
+    data: *const u8,
+    len: usize,
+    receipt: *mut *mut Risc0Receipt,
+) -> Risc0Status {
+    call(|| {
+        let data = bytes(data, len, "data")?;
+        let receipt = out(receipt, "receipt")?;
+        let decoded: Receipt = bincode::deserialize(data).status(Risc0Status::SerializeFailed)?;
+        *receipt = Box::into_raw(Box::new(Risc0Receipt(decoded)));
+        Ok(())
+    })
+}
+
+/// Release a receipt made by the library. Releasing null does nothing.
+///
+/// # Safety
+///
+/// `receipt` must be null or have been made by the library and not released
+/// before.
+#[no_mangle]
+pub unsafe extern "C" fn risc0_receipt_free(receipt: *mut Risc0Receipt) {
+    if !receipt.is_null() {
+        drop(Box::from_raw(receipt));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CStr;
+
+    use super::*;
+
+    fn last_error() -> String {
+        let message = risc0_last_error();
+        assert!(!message.is_null());
+        unsafe { CStr::from_ptr(message) }
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn invalid_arguments() {
+        let mut receipt = ptr::null_mut();
+        let status =
+            unsafe { risc0_prove(ptr::null(), 4, ptr::null(), 0, ptr::null(), &mut receipt) };
+        assert_eq!(status, Risc0Status::InvalidArgument);
+        assert_eq!(last_error(), "elf is null");
+        assert!(receipt.is_null());
+
+        let mut options = risc0_prove_options_default();
+        options.receipt_kind = 7;
+        let status =
+            unsafe { risc0_prove([0].as_ptr(), 1, ptr::null(), 0, &options, &mut receipt) };
+        assert_eq!(status, Risc0Status::InvalidArgument);
+        assert_eq!(last_error(), "unknown receipt kind 7");
+
+        options.size = 4;
+        let status =
+            unsafe { risc0_prove([0].as_ptr(), 1, ptr::null(), 0, &options, &mut receipt) };
+        assert_eq!(status, Risc0Status::InvalidArgument);
+
+        let status = unsafe { risc0_receipt_verify(ptr::null(), [0; 32].as_ptr()) };
+        assert_eq!(status, Risc0Status::InvalidArgument);
+        assert_eq!(last_error(), "receipt is null");
+    }
+
+    #[test]
+    fn malformed_receipt() {
+        let mut receipt = ptr::null_mut();
+        let data = [0xff; 16];
+        let status = unsafe { risc0_receipt_deserialize(data.as_ptr(), data.len(), &mut receipt) };
+        assert_eq!(status, Risc0Status::SerializeFailed);
+        assert!(receipt.is_null());
+        assert!(!last_error().is_empty());
+
+        let mut image_id = [0; 32];
+        let status =
+            unsafe { risc0_compute_image_id(data.as_ptr(), data.len(), image_id.as_mut_ptr()) };
+        assert_eq!(status, Risc0Status::ExecuteFailed);
+        assert!(!last_error().is_empty());
+    }
+
+    #[test]
+    fn journal_digest() {
+        use risc0_zkvm::{
+            sha::{Impl, Sha256},
+            InnerReceipt, ReceiptClaim,
+        };
+
+        let claim = ReceiptClaim::ok(Digest::ZERO, b"journal".to_vec());
+        let receipt = Risc0Receipt(Receipt::new(
+            InnerReceipt::Fake { claim },
+            b"journal".to_vec(),
+        ));
+        let mut digest = [0; 32];
+        let status = unsafe { risc0_receipt_journal_digest(&receipt, digest.as_mut_ptr()) };
+        assert_eq!(status, Risc0Status::Ok);
+        assert_eq!(digest, *Impl::hash_bytes(b"journal").as_bytes());
+
+        let status = unsafe { risc0_receipt_journal_digest(&receipt, ptr::null_mut()) };
+        assert_eq!(status, Risc0Status::InvalidArgument);
+        let status =
+            unsafe { risc0_receipt_verify_journal(&receipt, [0; 32].as_ptr(), ptr::null()) };
+        assert_eq!(status, Risc0Status::InvalidArgument);
+        assert_eq!(last_error(), "journal_digest is null");
+    }
+
+    #[test]
+    fn version() {
+        let version = unsafe { CStr::from_ptr(risc0_version()) };
+        assert_eq!(version.to_str().unwrap(), risc0_zkvm::VERSION);
+    }
+
+    #[test]
+    fn buffers() {
+        for bytes in [vec![], vec![1, 2, 3]] {
+            let buffer = Risc0Buffer::new(bytes.clone());
+            assert_eq!(
+                unsafe { slice::from_raw_parts(buffer.data, buffer.len) },
+                bytes
+            );
+            unsafe { risc0_buffer_free(buffer) };
+        }
+        unsafe {
+            risc0_buffer_free(Risc0Buffer {
+                data: ptr::null_mut(),
+                len: 0,
+            })
+        };
+    }
+
+    #[test]
+    fn go_module_is_versioned_with_the_crate() {
+        let go = include_str!("../go/risc0.go");
+        let version = env!("CARGO_PKG_VERSION");
+        assert!(go.contains(&format!("const LibraryVersion = \"{version}\"")));
+    }
+
+    #[test]
+    fn header_is_generated() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+        let config = cbindgen::Config::from_file(dir.join("cbindgen.toml")).unwrap();
+        let mut header = Vec::new();
+        cbindgen::Builder::new()
+            .with_config(config)
+            .with_src(dir.join("src/lib.rs"))
+            .generate()
+            .unwrap()
+            .write(&mut header);
+        assert!(
+            header == include_bytes!("../include/risc0.h"),
+            "include/risc0.h is out of date, regenerate it as described in cbindgen.toml"
+        );
+    }
+}
@@ -0,0 +1,145 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "execute")]
+
+use std::{ptr, slice};
+
+use risc0_ffi::*;
+use risc0_zkvm::{serde::to_vec, sha::Digest};
+use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF, MULTI_TEST_ID};
+
+fn input(spec: MultiTestSpec) -> Vec<u8> {
+    bytemuck::cast_slice(&to_vec(&spec).unwrap()).to_vec()
+}
+
+// Copy the bytes of `buffer` and release it.
+fn take(buffer: Risc0Buffer) -> Vec<u8> {
+    let bytes = unsafe { slice::from_raw_parts(buffer.data, buffer.len) }.to_vec();
+    unsafe { risc0_buffer_free(buffer) };
+    bytes
+}
+
+#[test]
+fn execute() {
+    let mut image_id = [0; 32];
+    let status = unsafe {
+        risc0_compute_image_id(
+            MULTI_TEST_ELF.as_ptr(),
+            MULTI_TEST_ELF.len(),
+            image_id.as_mut_ptr(),
+        )
+    };
+    assert_eq!(status, Risc0Status::Ok);
+    assert_eq!(Digest::from(MULTI_TEST_ID).as_bytes(), image_id);
+
+    let input = input(MultiTestSpec::Echo {
+        bytes: b"hello".to_vec(),
+    });
+    let mut info = Risc0SessionInfo {
+        exit_kind: Risc0ExitKind::SessionLimit,
+        exit_user_code: 0,
+        segments: 0,
+        user_cycles: 0,
+    };
+    let mut journal = Risc0Buffer {
+        data: ptr::null_mut(),
+        len: 0,
+    };
+    let status = unsafe {
+        risc0_execute(
+            MULTI_TEST_ELF.as_ptr(),
+            MULTI_TEST_ELF.len(),
+            input.as_ptr(),
+            input.len(),
+            &mut info,
+            &mut journal,
+        )
+    };
+    assert_eq!(status, Risc0Status::Ok);
+    assert_eq!(info.exit_kind, Risc0ExitKind::Halted);
+    assert_eq!(info.exit_user_code, 0);
+    assert!(info.segments > 0 && info.user_cycles > 0);
+    assert_eq!(take(journal), b"hello");
+}
+
+#[test]
+#[cfg(all(feature = "prove", not(feature = "disable-dev-mode")))]
+fn prove_and_verify() {
+    std::env::set_var("RISC0_DEV_MODE", "1");
+    let input = input(MultiTestSpec::DoNothing);
+    let options = risc0_prove_options_default();
+    let mut receipt = ptr::null_mut();
+    let status = unsafe {
+        risc0_prove(
+            MULTI_TEST_ELF.as_ptr(),
+            MULTI_TEST_ELF.len(),
+            input.as_ptr(),
+            input.len(),
+            &options,
+            &mut receipt,
+        )
+    };
+    assert_eq!(status, Risc0Status::Ok);
+
+    let mut bytes = Risc0Buffer {
+        data: ptr::null_mut(),
+        len: 0,
+    };
+    assert_eq!(
+        unsafe { risc0_receipt_serialize(receipt, &mut bytes) },
+        Risc0Status::Ok
+    );
+    unsafe { risc0_receipt_free(receipt) };
+    let bytes = take(bytes);
+    let mut receipt = ptr::null_mut();
+    assert_eq!(
+        unsafe { risc0_receipt_deserialize(bytes.as_ptr(), bytes.len(), &mut receipt) },
+        Risc0Status::Ok
+    );
+
+    let image_id = Digest::from(MULTI_TEST_ID);
+    assert_eq!(
+        unsafe { risc0_receipt_verify(receipt, image_id.as_bytes().as_ptr()) },
+        Risc0Status::Ok
+    );
+    assert_eq!(
+        unsafe { risc0_receipt_verify(receipt, [0; 32].as_ptr()) },
+        Risc0Status::VerifyFailed
+    );
+    assert!(!risc0_last_error().is_null());
+
+    let mut journal_digest = [0; 32];
+    assert_eq!(
+        unsafe { risc0_receipt_journal_digest(receipt, journal_digest.as_mut_ptr()) },
+        Risc0Status::Ok
+    );
+    assert_eq!(
+        unsafe {
+            risc0_receipt_verify_journal(
+                receipt,
+                image_id.as_bytes().as_ptr(),
+                journal_digest.as_ptr(),
+            )
+        },
+        Risc0Status::Ok
+    );
+    assert_eq!(
+        unsafe {
+            risc0_receipt_verify_journal(receipt, image_id.as_bytes().as_ptr(), [0; 32].as_ptr())
+        },
+        Risc0Status::VerifyFailed
+    );
+    unsafe { risc0_receipt_free(receipt) };
+}
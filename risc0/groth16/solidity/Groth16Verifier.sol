@@ -0,0 +1,244 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+// This file is automatically generated by:
+// cargo risczero solidity-verifier
+//
+// Verifier parameters digest: {PARAMETERS_DIGEST}
+
+pragma solidity ^0.8.9;
+
+/// @notice Verifier of RISC Zero receipts wrapped in a Groth16 proof over BN254.
+/// @dev A seal is the 4-byte SELECTOR followed by the 256-byte Groth16 proof,
+/// as the points a, b and c in the layout expected by the precompiles. The
+/// selector is derived from the verifier parameters, so seals produced for a
+/// different control root or verifying key are rejected before any pairing is
+/// computed, and a router can dispatch seals by their first four bytes.
+contract {CONTRACT_NAME} {
+    /// @notice Prefix of the seals accepted by this verifier.
+    bytes4 public constant SELECTOR = {SELECTOR};
+
+    /// @notice Digest of the control root, BN254 control ID and verifying key.
+    bytes32 public constant VERIFIER_PARAMETERS_DIGEST = {PARAMETERS_DIGEST};
+
+    /// @notice Root of the Merkle tree of allowed recursion control IDs.
+    bytes32 public constant CONTROL_ROOT = {CONTROL_ROOT};
+
+    /// @notice Control ID of the program that produces the seal verified by
+    /// the Groth16 circuit.
+    uint256 public constant BN254_CONTROL_ID = {BN254_CONTROL_ID};
+
+    // The control root split into the two public inputs of the circuit.
+    uint256 internal constant CONTROL_ROOT_0 = {CONTROL_ROOT_0};
+    uint256 internal constant CONTROL_ROOT_1 = {CONTROL_ROOT_1};
+
+    bytes32 internal constant RECEIPT_CLAIM_TAG = sha256("risc0.ReceiptClaim");
+    bytes32 internal constant OUTPUT_TAG = sha256("risc0.Output");
+
+    // Scalar field size
+    uint256 internal constant R =
+        21888242871839275222246405745257275088548364400416034343698204186575808495617;
+    // Base field size
+    uint256 internal constant Q =
+        21888242871839275222246405745257275088696311157297823662689037894645226208583;
+
+    // Verifying key
+    uint256 internal constant ALPHA_X = {ALPHA_X};
+    uint256 internal constant ALPHA_Y = {ALPHA_Y};
+    uint256 internal constant BETA_X1 = {BETA_X1};
+    uint256 internal constant BETA_X2 = {BETA_X2};
+    uint256 internal constant BETA_Y1 = {BETA_Y1};
+    uint256 internal constant BETA_Y2 = {BETA_Y2};
+    uint256 internal constant GAMMA_X1 = {GAMMA_X1};
+    uint256 internal constant GAMMA_X2 = {GAMMA_X2};
+    uint256 internal constant GAMMA_Y1 = {GAMMA_Y1};
+    uint256 internal constant GAMMA_Y2 = {GAMMA_Y2};
+    uint256 internal constant DELTA_X1 = {DELTA_X1};
+    uint256 internal constant DELTA_X2 = {DELTA_X2};
+    uint256 internal constant DELTA_Y1 = {DELTA_Y1};
+    uint256 internal constant DELTA_Y2 = {DELTA_Y2};
+    uint256 internal constant IC0_X = {IC0_X};
+    uint256 internal constant IC0_Y = {IC0_Y};
+    uint256 internal constant IC1_X = {IC1_X};
+    uint256 internal constant IC1_Y = {IC1_Y};
+    uint256 internal constant IC2_X = {IC2_X};
+    uint256 internal constant IC2_Y = {IC2_Y};
+    uint256 internal constant IC3_X = {IC3_X};
+    uint256 internal constant IC3_Y = {IC3_Y};
+    uint256 internal constant IC4_X = {IC4_X};
+    uint256 internal constant IC4_Y = {IC4_Y};
+    uint256 internal constant IC5_X = {IC5_X};
+    uint256 internal constant IC5_Y = {IC5_Y};
+
+    /// @notice The seal was produced for a verifier with other parameters.
+    error SelectorMismatch(bytes4 received, bytes4 expected);
+
+    /// @notice The seal is malformed or does not prove the claim.
+    error VerificationFailed();
+
+    /// @notice Verify that the seal proves a successful execution of the
+    /// given image, ending in the given post state and committing a journal
+    /// with the given digest.
+    /// @dev Reverts if the seal is not valid. The claim is that of an
+    /// execution with no input digest that halted with exit code zero.
+    function verify(bytes calldata seal, bytes32 imageId, bytes32 postStateDigest, bytes32 journalDigest)
+        external
+        view
+    {
+        verifyIntegrity(seal, claimDigest(imageId, postStateDigest, journalDigest));
+    }
+
+    /// @notice Verify that the seal proves the receipt claim with the given
+    /// digest.
+    /// @dev Reverts if the seal is not valid.
+    function verifyIntegrity(bytes calldata seal, bytes32 claim) public view {
+        if (seal.length < 4) {
+            revert VerificationFailed();
+        }
+        bytes4 selector = bytes4(seal[:4]);
+        if (selector != SELECTOR) {
+            revert SelectorMismatch(selector, SELECTOR);
+        }
+        if (seal.length != 260) {
+            revert VerificationFailed();
+        }
+
+        (uint256[2] memory a, uint256[2][2] memory b, uint256[2] memory c) =
+            abi.decode(seal[4:], (uint256[2], uint256[2][2], uint256[2]));
+        (uint256 claim0, uint256 claim1) = splitDigest(claim);
+        uint256[5] memory inputs = [CONTROL_ROOT_0, CONTROL_ROOT_1, claim0, claim1, BN254_CONTROL_ID];
+        if (!verifyProof(a, b, c, inputs)) {
+            revert VerificationFailed();
+        }
+    }
+
+    /// @notice Compute the digest of the receipt claim of a successful
+    /// execution, as in ReceiptClaim::digest.
+    function claimDigest(bytes32 imageId, bytes32 postStateDigest, bytes32 journalDigest)
+        public
+        pure
+        returns (bytes32)
+    {
+        // The output has no assumptions and no channels.
+        bytes32 output = sha256(abi.encodePacked(OUTPUT_TAG, journalDigest, bytes32(0), uint16(2) << 8));
+        return sha256(
+            abi.encodePacked(
+                RECEIPT_CLAIM_TAG,
+                // input
+                bytes32(0),
+                imageId,
+                postStateDigest,
+                output,
+                // system and user exit codes, for ExitCode::Halted(0)
+                uint32(0),
+                uint32(0),
+                uint16(4) << 8
+            )
+        );
+    }
+
+    // Split a digest into two 128-bit halves, as in risc0_groth16::split_digest.
+    function splitDigest(bytes32 digest) internal pure returns (uint256, uint256) {
+        uint256 reversed = reverseByteOrder(uint256(digest));
+        return (uint256(uint128(reversed)), reversed >> 128);
+    }
+
+    function reverseByteOrder(uint256 input) internal pure returns (uint256 v) {
+        v = input;
+        v = ((v & 0xFF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00) >> 8)
+            | ((v & 0x00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF) << 8);
+        v = ((v & 0xFFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000) >> 16)
+            | ((v & 0x0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF) << 16);
+        v = ((v & 0xFFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000) >> 32)
+            | ((v & 0x00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF) << 32);
+        v = ((v & 0xFFFFFFFFFFFFFFFF0000000000000000FFFFFFFFFFFFFFFF0000000000000000) >> 64)
+            | ((v & 0x0000000000000000FFFFFFFFFFFFFFFF0000000000000000FFFFFFFFFFFFFFFF) << 64);
+        v = (v >> 128) | (v << 128);
+    }
+
+    function verifyProof(
+        uint256[2] memory a,
+        uint256[2][2] memory b,
+        uint256[2] memory c,
+        uint256[5] memory inputs
+    ) internal view returns (bool) {
+        for (uint256 i = 0; i < 5; i++) {
+            if (inputs[i] >= R) {
+                return false;
+            }
+        }
+
+        // The linear combination of the inputs with the IC points.
+        uint256[2] memory vkX = [IC0_X, IC0_Y];
+        if (
+            !mulAcc(vkX, IC1_X, IC1_Y, inputs[0]) || !mulAcc(vkX, IC2_X, IC2_Y, inputs[1])
+                || !mulAcc(vkX, IC3_X, IC3_Y, inputs[2]) || !mulAcc(vkX, IC4_X, IC4_Y, inputs[3])
+                || !mulAcc(vkX, IC5_X, IC5_Y, inputs[4])
+        ) {
+            return false;
+        }
+
+        // e(-A, B) * e(alpha, beta) * e(vkX, gamma) * e(C, delta) == 1
+        uint256[24] memory pairing = [
+            a[0],
+            (Q - (a[1] % Q)) % Q,
+            b[0][0],
+            b[0][1],
+            b[1][0],
+            b[1][1],
+            ALPHA_X,
+            ALPHA_Y,
+            BETA_X1,
+            BETA_X2,
+            BETA_Y1,
+            BETA_Y2,
+            vkX[0],
+            vkX[1],
+            GAMMA_X1,
+            GAMMA_X2,
+            GAMMA_Y1,
+            GAMMA_Y2,
+            c[0],
+            c[1],
+            DELTA_X1,
+            DELTA_X2,
+            DELTA_Y1,
+            DELTA_Y2
+        ];
+        uint256[1] memory result;
+        bool success;
+        assembly {
+            success := staticcall(gas(), 8, pairing, 768, result, 32)
+        }
+        return success && result[0] == 1;
+    }
+
+    // Set p to p + s * (x, y), returning false if a precompile fails.
+    function mulAcc(uint256[2] memory p, uint256 x, uint256 y, uint256 s) internal view returns (bool success) {
+        uint256[4] memory buf = [x, y, s, 0];
+        assembly {
+            success := staticcall(gas(), 7, buf, 96, buf, 64)
+        }
+        if (!success) {
+            return false;
+        }
+        buf[2] = p[0];
+        buf[3] = p[1];
+        assembly {
+            success := staticcall(gas(), 6, buf, 128, p, 64)
+        }
+    }
+}
@@ -156,6 +156,11 @@ pub struct VerifyingKeyJson {
 impl VerifyingKeyJson {
     /// Computes the prepared verifying key
     pub fn prepared_verifying_key(&self) -> Result<PreparedVerifyingKey<Bn254>, Error> {
+        Ok(prepare_verifying_key(&self.verifying_key()?))
+    }
+
+    /// Decodes the verifying key
+    pub fn verifying_key(&self) -> Result<VerifyingKey<Bn254>, Error> {
         if self.vk_alpha_1.len() < 2 {
             return Err(anyhow!("Malformed G1 element field: vk_alpha_1"));
         }
@@ -220,15 +225,13 @@ impl VerifyingKeyJson {
             })
             .collect::<Result<Vec<_>, _>>()?;
 
-        let vk = VerifyingKey::<Bn254> {
+        Ok(VerifyingKey::<Bn254> {
             alpha_g1,
             beta_g2,
             gamma_g2,
             delta_g2,
             gamma_abc_g1,
-        };
-
-        Ok(prepare_verifying_key(&vk))
+        })
     }
 }
 
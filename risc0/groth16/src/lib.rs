@@ -25,15 +25,24 @@ use risc0_zkp::core::digest::Digest;
 pub mod data_structures;
 #[cfg(feature = "prove")]
 pub mod docker;
+pub mod plonk;
+#[cfg(feature = "prove")]
+pub mod prove;
 #[cfg(feature = "prove")]
 mod seal_format;
 #[cfg(feature = "prove")]
 mod seal_to_json;
+pub mod solana;
+pub mod solidity;
 pub mod verifier;
 
 pub use data_structures::{ProofJson, PublicInputsJson, Seal, VerifyingKeyJson};
+pub use plonk::{PlonkProofJson, PlonkSeal, PlonkVerifyingKey, PlonkVerifyingKeyJson};
+#[cfg(feature = "prove")]
+pub use prove::{stark_to_plonk, stark_to_snark};
 #[cfg(feature = "prove")]
 pub use seal_to_json::to_json;
+pub use solidity::{verifying_key_digest, VerifierParameters, VerifyCall};
 pub use verifier::Verifier;
 
 // Deserialize a scalar field from bytes in big-endian format
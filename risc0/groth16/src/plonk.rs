@@ -0,0 +1,769 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! PLONK proofs over the BN_254 curve, as produced and verified by snarkjs.
+//!
+//! Unlike Groth16, PLONK needs no setup specific to the circuit. Its verifying
+//! key is computed from the circuit and a universal powers-of-tau ceremony,
+//! so anyone can reproduce it from public inputs.
+
+extern crate alloc;
+
+use alloc::{string::String, vec, vec::Vec};
+use core::str::FromStr;
+
+use anyhow::{anyhow, ensure, Error, Result};
+use ark_bn254::{Bn254, Fr, G1Affine, G2Affine};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, FftField, Field, One, PrimeField, Zero};
+use serde::{Deserialize, Serialize};
+use tiny_keccak::{Hasher, Keccak};
+
+use crate::{fr_from_bytes, from_u256, g1_from_bytes, g2_from_bytes};
+
+/// PLONK seal object encoded in big endian.
+///
+/// The commitments and evaluations are in the order of the snarkjs verifier
+/// contract: `A`, `B`, `C`, `Z`, `T1`, `T2`, `T3`, `Wxi` and `Wxiw`, followed
+/// by the evaluations of `a`, `b`, `c`, `s1` and `s2` at the challenge `xi`,
+/// and of `z` at `xi * w`.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct PlonkSeal {
+    /// The commitments, each a G1 point as its `x` and `y` coordinates.
+    pub commitments: Vec<Vec<Vec<u8>>>,
+    /// The evaluations, each a scalar.
+    pub evaluations: Vec<Vec<u8>>,
+}
+
+impl PlonkSeal {
+    const ELEMENT_SIZE: usize = 32;
+    const COMMITMENTS: usize = 9;
+    const EVALUATIONS: usize = 6;
+    const SIZE: usize = Self::ELEMENT_SIZE * (2 * Self::COMMITMENTS + Self::EVALUATIONS);
+
+    /// Serialize the PLONK `PlonkSeal` into a `Vec<u8>`
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(Self::SIZE);
+        for point in &self.commitments {
+            for item in point {
+                result.extend(item);
+            }
+        }
+        for item in &self.evaluations {
+            result.extend(item);
+        }
+        result
+    }
+
+    /// Method to convert back from a `Vec<u8>`
+    pub fn from_vec(data: &[u8]) -> Result<PlonkSeal, Error> {
+        if data.len() != Self::SIZE {
+            return Err(anyhow!("Data length mismatch"));
+        }
+        let mut elements = data.chunks_exact(Self::ELEMENT_SIZE).map(<[u8]>::to_vec);
+        let commitments = (0..Self::COMMITMENTS)
+            .map(|_| vec![elements.next().unwrap(), elements.next().unwrap()])
+            .collect();
+        let evaluations = elements.collect();
+        Ok(PlonkSeal {
+            commitments,
+            evaluations,
+        })
+    }
+}
+
+impl TryFrom<PlonkProofJson> for PlonkSeal {
+    type Error = Error;
+    fn try_from(proof: PlonkProofJson) -> Result<Self, Error> {
+        let commitments = [
+            &proof.a,
+            &proof.b,
+            &proof.c,
+            &proof.z,
+            &proof.t1,
+            &proof.t2,
+            &proof.t3,
+            &proof.wxi,
+            &proof.wxiw,
+        ]
+        .into_iter()
+        .map(|point| {
+            if point.len() < 2 {
+                return Err(anyhow!("Malformed G1 element field"));
+            }
+            Ok(vec![from_u256(&point[0])?, from_u256(&point[1])?])
+        })
+        .collect::<Result<_>>()?;
+        let evaluations = [
+            &proof.eval_a,
+            &proof.eval_b,
+            &proof.eval_c,
+            &proof.eval_s1,
+            &proof.eval_s2,
+            &proof.eval_zw,
+        ]
+        .into_iter()
+        .map(|value| from_u256(value))
+        .collect::<Result<_>>()?;
+        Ok(PlonkSeal {
+            commitments,
+            evaluations,
+        })
+    }
+}
+
+/// PLONK Proof encoded as JSON.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlonkProofJson {
+    #[serde(rename = "A")]
+    a: Vec<String>,
+    #[serde(rename = "B")]
+    b: Vec<String>,
+    #[serde(rename = "C")]
+    c: Vec<String>,
+    #[serde(rename = "Z")]
+    z: Vec<String>,
+    #[serde(rename = "T1")]
+    t1: Vec<String>,
+    #[serde(rename = "T2")]
+    t2: Vec<String>,
+    #[serde(rename = "T3")]
+    t3: Vec<String>,
+    #[serde(rename = "Wxi")]
+    wxi: Vec<String>,
+    #[serde(rename = "Wxiw")]
+    wxiw: Vec<String>,
+    eval_a: String,
+    eval_b: String,
+    eval_c: String,
+    eval_s1: String,
+    eval_s2: String,
+    eval_zw: String,
+    protocol: Option<String>,
+    curve: Option<String>,
+}
+
+/// PLONK Verifying Key encoded as JSON.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlonkVerifyingKeyJson {
+    protocol: String,
+    curve: String,
+    #[serde(rename = "nPublic")]
+    n_public: u32,
+    power: u32,
+    k1: String,
+    k2: String,
+    #[serde(rename = "Qm")]
+    qm: Vec<String>,
+    #[serde(rename = "Ql")]
+    ql: Vec<String>,
+    #[serde(rename = "Qr")]
+    qr: Vec<String>,
+    #[serde(rename = "Qo")]
+    qo: Vec<String>,
+    #[serde(rename = "Qc")]
+    qc: Vec<String>,
+    #[serde(rename = "S1")]
+    s1: Vec<String>,
+    #[serde(rename = "S2")]
+    s2: Vec<String>,
+    #[serde(rename = "S3")]
+    s3: Vec<String>,
+    #[serde(rename = "X_2")]
+    x_2: Vec<Vec<String>>,
+    w: String,
+}
+
+impl PlonkVerifyingKeyJson {
+    /// Decode the verifying key.
+    pub fn verifying_key(&self) -> Result<PlonkVerifyingKey, Error> {
+        ensure!(self.protocol == "plonk", "not a PLONK verifying key");
+        let g1 = |point: &[String], name: &str| {
+            if point.len() < 2 {
+                return Err(anyhow!("Malformed G1 element field: {name}"));
+            }
+            // snarkjs writes points in projective coordinates, where the point
+            // at infinity has z = 0.
+            if point.get(2).is_some_and(|z| z == "0") {
+                return Ok(G1Affine::identity());
+            }
+            g1_from_bytes(&[from_u256(&point[0])?, from_u256(&point[1])?])
+        };
+        if self.x_2.len() < 2 || self.x_2[0].len() < 2 || self.x_2[1].len() < 2 {
+            return Err(anyhow!("Malformed G2 element field: X_2"));
+        }
+        let x_2 = g2_from_bytes(&[
+            vec![from_u256(&self.x_2[0][1])?, from_u256(&self.x_2[0][0])?],
+            vec![from_u256(&self.x_2[1][1])?, from_u256(&self.x_2[1][0])?],
+        ])?;
+        let scalar = |value: &str| Fr::from_str(value).map_err(|_| anyhow!("Malformed scalar"));
+
+        let vk = PlonkVerifyingKey {
+            n_public: self.n_public as usize,
+            power: self.power,
+            k1: scalar(&self.k1)?,
+            k2: scalar(&self.k2)?,
+            qm: g1(&self.qm, "Qm")?,
+            ql: g1(&self.ql, "Ql")?,
+            qr: g1(&self.qr, "Qr")?,
+            qo: g1(&self.qo, "Qo")?,
+            qc: g1(&self.qc, "Qc")?,
+            s1: g1(&self.s1, "S1")?,
+            s2: g1(&self.s2, "S2")?,
+            s3: g1(&self.s3, "S3")?,
+            x_2,
+        };
+        ensure!(
+            vk.root() == Some(scalar(&self.w)?),
+            "verifying key root of unity does not match its domain"
+        );
+        Ok(vk)
+    }
+}
+
+/// A PLONK verifying key over the BN_254 curve.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlonkVerifyingKey {
+    /// The number of public inputs.
+    pub n_public: usize,
+    /// The log2 of the size of the evaluation domain.
+    pub power: u32,
+    /// The coset shift that distinguishes the `b` wires in the permutation.
+    pub k1: Fr,
+    /// The coset shift that distinguishes the `c` wires in the permutation.
+    pub k2: Fr,
+    /// Commitment to the multiplication selector.
+    pub qm: G1Affine,
+    /// Commitment to the left selector.
+    pub ql: G1Affine,
+    /// Commitment to the right selector.
+    pub qr: G1Affine,
+    /// Commitment to the output selector.
+    pub qo: G1Affine,
+    /// Commitment to the constant selector.
+    pub qc: G1Affine,
+    /// Commitment to the first permutation polynomial.
+    pub s1: G1Affine,
+    /// Commitment to the second permutation polynomial.
+    pub s2: G1Affine,
+    /// Commitment to the third permutation polynomial.
+    pub s3: G1Affine,
+    /// The powers-of-tau secret in G2.
+    pub x_2: G2Affine,
+}
+
+impl PlonkVerifyingKey {
+    fn root(&self) -> Option<Fr> {
+        Fr::get_root_of_unity(1u64.checked_shl(self.power)?)
+    }
+
+    /// Verify a PLONK proof of the given public inputs.
+    pub fn verify(&self, seal: &PlonkSeal, public_inputs: &[Fr]) -> Result<(), Error> {
+        ensure!(
+            public_inputs.len() == self.n_public,
+            "expected {} public inputs, got {}",
+            self.n_public,
+            public_inputs.len()
+        );
+        let proof = Proof::from_seal(seal)?;
+        let w = self
+            .root()
+            .ok_or_else(|| anyhow!("Unsupported domain size"))?;
+        let ch = Challenges::new(self, &proof, public_inputs);
+
+        let n = 1u64 << self.power;
+        let xin = ch.xi.pow([n]);
+        let zh = xin - Fr::one();
+
+        // The Lagrange basis polynomials of the first rows, where the public
+        // inputs are placed, at xi.
+        let mut lagrange = Vec::with_capacity(public_inputs.len().max(1));
+        let mut root = Fr::one();
+        for _ in 0..public_inputs.len().max(1) {
+            let denom = Fr::from(n) * (ch.xi - root);
+            lagrange.push(root * zh * denom.inverse().ok_or_else(|| anyhow!("Invalid proof"))?);
+            root *= w;
+        }
+        let l1 = lagrange[0];
+        let pi: Fr = -public_inputs
+            .iter()
+            .zip(&lagrange)
+            .map(|(input, l)| *input * l)
+            .sum::<Fr>();
+
+        let alpha2 = ch.alpha.square();
+        let e3a = proof.eval_a + ch.beta * proof.eval_s1 + ch.gamma;
+        let e3b = proof.eval_b + ch.beta * proof.eval_s2 + ch.gamma;
+        let r0 =
+            pi - l1 * alpha2 - e3a * e3b * (proof.eval_c + ch.gamma) * proof.eval_zw * ch.alpha;
+
+        // The commitment to the linearization polynomial, with `u * Z` folded
+        // in for the opening at `xi * w`.
+        let betaxi = ch.beta * ch.xi;
+        let d2 = (proof.eval_a + betaxi + ch.gamma)
+            * (proof.eval_b + betaxi * self.k1 + ch.gamma)
+            * (proof.eval_c + betaxi * self.k2 + ch.gamma)
+            * ch.alpha
+            + l1 * alpha2
+            + ch.u;
+        let d3 = e3a * e3b * ch.alpha * ch.beta * proof.eval_zw;
+        let d4 = (proof.t1 + proof.t2 * xin + proof.t3 * xin.square()) * zh;
+        let d = self.qm * (proof.eval_a * proof.eval_b)
+            + self.ql * proof.eval_a
+            + self.qr * proof.eval_b
+            + self.qo * proof.eval_c
+            + self.qc
+            + proof.z * d2
+            - self.s3 * d3
+            - d4;
+
+        let v = ch.v;
+        let f =
+            d + proof.a * v[0] + proof.b * v[1] + proof.c * v[2] + self.s1 * v[3] + self.s2 * v[4];
+        let e = G1Affine::generator()
+            * (-r0
+                + v[0] * proof.eval_a
+                + v[1] * proof.eval_b
+                + v[2] * proof.eval_c
+                + v[3] * proof.eval_s1
+                + v[4] * proof.eval_s2
+                + ch.u * proof.eval_zw);
+
+        let a1 = proof.wxi + proof.wxiw * ch.u;
+        let b1 = proof.wxi * ch.xi + proof.wxiw * (ch.u * ch.xi * w) + f - e;
+        let pairing = Bn254::multi_pairing(
+            [(-a1).into_affine(), b1.into_affine()],
+            [self.x_2, G2Affine::generator()],
+        );
+        match pairing.is_zero() {
+            true => Ok(()),
+            false => Err(anyhow!("Invalid proof")),
+        }
+    }
+}
+
+struct Proof {
+    a: G1Affine,
+    b: G1Affine,
+    c: G1Affine,
+    z: G1Affine,
+    t1: G1Affine,
+    t2: G1Affine,
+    t3: G1Affine,
+    wxi: G1Affine,
+    wxiw: G1Affine,
+    eval_a: Fr,
+    eval_b: Fr,
+    eval_c: Fr,
+    eval_s1: Fr,
+    eval_s2: Fr,
+    eval_zw: Fr,
+}
+
+impl Proof {
+    fn from_seal(seal: &PlonkSeal) -> Result<Self> {
+        ensure!(
+            seal.commitments.len() == PlonkSeal::COMMITMENTS
+                && seal.evaluations.len() == PlonkSeal::EVALUATIONS,
+            "Malformed PLONK seal"
+        );
+        let points = seal
+            .commitments
+            .iter()
+            .map(|point| g1_from_bytes(point))
+            .collect::<Result<Vec<_>>>()?;
+        let evals = seal
+            .evaluations
+            .iter()
+            .map(|value| fr_from_bytes(value))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            a: points[0],
+            b: points[1],
+            c: points[2],
+            z: points[3],
+            t1: points[4],
+            t2: points[5],
+            t3: points[6],
+            wxi: points[7],
+            wxiw: points[8],
+            eval_a: evals[0],
+            eval_b: evals[1],
+            eval_c: evals[2],
+            eval_s1: evals[3],
+            eval_s2: evals[4],
+            eval_zw: evals[5],
+        })
+    }
+}
+
+// The Fiat-Shamir challenges, derived with the Keccak transcript of snarkjs so
+// that proofs verify on chain.
+struct Challenges {
+    beta: Fr,
+    gamma: Fr,
+    alpha: Fr,
+    xi: Fr,
+    v: [Fr; 5],
+    u: Fr,
+}
+
+impl Challenges {
+    fn new(vk: &PlonkVerifyingKey, proof: &Proof, public_inputs: &[Fr]) -> Self {
+        let mut transcript = Transcript::default();
+        for point in [vk.qm, vk.ql, vk.qr, vk.qo, vk.qc, vk.s1, vk.s2, vk.s3] {
+            transcript.point(&point);
+        }
+        for input in public_inputs {
+            transcript.scalar(input);
+        }
+        for point in [proof.a, proof.b, proof.c] {
+            transcript.point(&point);
+        }
+        let beta = transcript.challenge();
+
+        transcript.scalar(&beta);
+        let gamma = transcript.challenge();
+
+        transcript.scalar(&beta);
+        transcript.scalar(&gamma);
+        transcript.point(&proof.z);
+        let alpha = transcript.challenge();
+
+        transcript.scalar(&alpha);
+        for point in [proof.t1, proof.t2, proof.t3] {
+            transcript.point(&point);
+        }
+        let xi = transcript.challenge();
+
+        transcript.scalar(&xi);
+        for eval in [
+            proof.eval_a,
+            proof.eval_b,
+            proof.eval_c,
+            proof.eval_s1,
+            proof.eval_s2,
+            proof.eval_zw,
+        ] {
+            transcript.scalar(&eval);
+        }
+        let v1 = transcript.challenge();
+        let mut v = [v1; 5];
+        for i in 1..5 {
+            v[i] = v[i - 1] * v1;
+        }
+
+        transcript.point(&proof.wxi);
+        transcript.point(&proof.wxiw);
+        let u = transcript.challenge();
+
+        Self {
+            beta,
+            gamma,
+            alpha,
+            xi,
+            v,
+            u,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Transcript(Vec<u8>);
+
+impl Transcript {
+    fn point(&mut self, point: &G1Affine) {
+        match point.xy() {
+            Some((x, y)) => {
+                self.0.extend(x.into_bigint().to_bytes_be());
+                self.0.extend(y.into_bigint().to_bytes_be());
+            }
+            // snarkjs marks the point at infinity with a flag bit.
+            None => {
+                let mut infinity = [0u8; 64];
+                infinity[0] = 0x40;
+                self.0.extend(infinity);
+            }
+        }
+    }
+
+    fn scalar(&mut self, scalar: &Fr) {
+        self.0.extend(scalar.into_bigint().to_bytes_be());
+    }
+
+    // Hash what has been added since the last challenge.
+    fn challenge(&mut self) -> Fr {
+        let mut keccak = Keccak::v256();
+        keccak.update(&self.0);
+        let mut hash = [0u8; 32];
+        keccak.finalize(&mut hash);
+        self.0.clear();
+        Fr::from_be_bytes_mod_order(&hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::{Fr, G1Affine, G2Affine};
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_ff::{BigInteger, FftField, Field, One, PrimeField, Zero};
+
+    use super::{Challenges, PlonkSeal, PlonkVerifyingKey, Proof};
+
+    const POWER: u32 = 2;
+    const N: usize = 1 << POWER;
+
+    // The circuit `x * y = z` with `z` public. Row 0 binds the public input to
+    // `a`, row 1 is the multiplication gate, and its output wire is copied to
+    // row 0.
+    struct Circuit {
+        tau: Fr,
+        w: Fr,
+        k1: Fr,
+        k2: Fr,
+        qm: [Fr; N],
+        ql: [Fr; N],
+        qo: [Fr; N],
+        sigma: [[Fr; N]; 3],
+    }
+
+    impl Circuit {
+        fn new() -> Self {
+            let w = Fr::get_root_of_unity(N as u64).unwrap();
+            let k1 = Fr::from(2u64);
+            let k2 = Fr::from(3u64);
+            let id = |k: Fr| core::array::from_fn(|i| k * w.pow([i as u64]));
+            let mut sigma = [id(Fr::one()), id(k1), id(k2)];
+            sigma[0][0] = k2 * w;
+            sigma[2][1] = Fr::one();
+            let mut qm = [Fr::zero(); N];
+            let mut ql = [Fr::zero(); N];
+            let mut qo = [Fr::zero(); N];
+            ql[0] = Fr::one();
+            qm[1] = Fr::one();
+            qo[1] = -Fr::one();
+            Self {
+                tau: Fr::from(0x1234_5678u64),
+                w,
+                k1,
+                k2,
+                qm,
+                ql,
+                qo,
+                sigma,
+            }
+        }
+
+        // The value at `x` of the polynomial with the given evaluations on the
+        // domain.
+        fn interpolate(&self, evals: &[Fr; N], x: Fr) -> Fr {
+            let zh = x.pow([N as u64]) - Fr::one();
+            (0..N)
+                .map(|i| {
+                    let root = self.w.pow([i as u64]);
+                    evals[i] * root * zh / (Fr::from(N as u64) * (x - root))
+                })
+                .sum()
+        }
+
+        fn commit(value: Fr) -> G1Affine {
+            (G1Affine::generator() * value).into_affine()
+        }
+
+        fn verifying_key(&self) -> PlonkVerifyingKey {
+            let commit = |evals: &[Fr; N]| Self::commit(self.interpolate(evals, self.tau));
+            PlonkVerifyingKey {
+                n_public: 1,
+                power: POWER,
+                k1: self.k1,
+                k2: self.k2,
+                qm: commit(&self.qm),
+                ql: commit(&self.ql),
+                qr: G1Affine::identity(),
+                qo: commit(&self.qo),
+                qc: G1Affine::identity(),
+                s1: commit(&self.sigma[0]),
+                s2: commit(&self.sigma[1]),
+                s3: commit(&self.sigma[2]),
+                x_2: (G2Affine::generator() * self.tau).into_affine(),
+            }
+        }
+
+        // Prove with knowledge of tau, evaluating every polynomial directly
+        // instead of committing to it through powers of tau. The quotient is
+        // computed from the gate and permutation constraints, independently
+        // of the verifier's linearization.
+        fn prove(&self, x: u64, y: u64) -> Proof {
+            let vk = self.verifying_key();
+            let public = Fr::from(x * y);
+            let mut a = [Fr::zero(); N];
+            let mut b = [Fr::zero(); N];
+            let mut c = [Fr::zero(); N];
+            [a[0], a[1], b[1], c[1]] = [public, Fr::from(x), Fr::from(y), public];
+            let mut proof = Proof {
+                a: Self::commit(self.interpolate(&a, self.tau)),
+                b: Self::commit(self.interpolate(&b, self.tau)),
+                c: Self::commit(self.interpolate(&c, self.tau)),
+                z: G1Affine::generator(),
+                t1: G1Affine::generator(),
+                t2: G1Affine::generator(),
+                t3: G1Affine::generator(),
+                wxi: G1Affine::generator(),
+                wxiw: G1Affine::generator(),
+                eval_a: Fr::zero(),
+                eval_b: Fr::zero(),
+                eval_c: Fr::zero(),
+                eval_s1: Fr::zero(),
+                eval_s2: Fr::zero(),
+                eval_zw: Fr::zero(),
+            };
+            let ch = |proof: &Proof| Challenges::new(&vk, proof, &[public]);
+            let (beta, gamma) = (ch(&proof).beta, ch(&proof).gamma);
+
+            let mut z = [Fr::one(); N];
+            for i in 0..N - 1 {
+                let root = self.w.pow([i as u64]);
+                let num = (a[i] + beta * root + gamma)
+                    * (b[i] + beta * self.k1 * root + gamma)
+                    * (c[i] + beta * self.k2 * root + gamma);
+                let den = (a[i] + beta * self.sigma[0][i] + gamma)
+                    * (b[i] + beta * self.sigma[1][i] + gamma)
+                    * (c[i] + beta * self.sigma[2][i] + gamma);
+                z[i + 1] = z[i] * num / den;
+            }
+            proof.z = Self::commit(self.interpolate(&z, self.tau));
+            let alpha = ch(&proof).alpha;
+
+            let mut l1 = [Fr::zero(); N];
+            l1[0] = Fr::one();
+            let p = |evals: &[Fr; N], x: Fr| self.interpolate(evals, x);
+            let t = |x: Fr| {
+                let pi = -public * p(&l1, x);
+                let gate = p(&self.qm, x) * p(&a, x) * p(&b, x)
+                    + p(&self.ql, x) * p(&a, x)
+                    + p(&self.qo, x) * p(&c, x)
+                    + pi;
+                let num = (p(&a, x) + beta * x + gamma)
+                    * (p(&b, x) + beta * self.k1 * x + gamma)
+                    * (p(&c, x) + beta * self.k2 * x + gamma)
+                    * p(&z, x);
+                let den = (p(&a, x) + beta * p(&self.sigma[0], x) + gamma)
+                    * (p(&b, x) + beta * p(&self.sigma[1], x) + gamma)
+                    * (p(&c, x) + beta * p(&self.sigma[2], x) + gamma)
+                    * p(&z, x * self.w);
+                let start = (p(&z, x) - Fr::one()) * p(&l1, x);
+                (gate + alpha * (num - den) + alpha.square() * start)
+                    / (x.pow([N as u64]) - Fr::one())
+            };
+            // Split t as T1 + X^n T2 + X^2n T3 with constant T2 and T3.
+            let (t2, t3) = (Fr::from(5u64), Fr::from(7u64));
+            let t1 = |x: Fr| t(x) - x.pow([N as u64]) * t2 - x.pow([2 * N as u64]) * t3;
+            proof.t1 = Self::commit(t1(self.tau));
+            proof.t2 = Self::commit(t2);
+            proof.t3 = Self::commit(t3);
+            let xi = ch(&proof).xi;
+
+            proof.eval_a = p(&a, xi);
+            proof.eval_b = p(&b, xi);
+            proof.eval_c = p(&c, xi);
+            proof.eval_s1 = p(&self.sigma[0], xi);
+            proof.eval_s2 = p(&self.sigma[1], xi);
+            proof.eval_zw = p(&z, xi * self.w);
+            let v = ch(&proof).v;
+
+            let xin = xi.pow([N as u64]);
+            let zh = xin - Fr::one();
+            let (ea, eb, ec) = (proof.eval_a, proof.eval_b, proof.eval_c);
+            let linearization = |x: Fr| {
+                p(&self.qm, x) * ea * eb
+                    + p(&self.ql, x) * ea
+                    + p(&self.qo, x) * ec
+                    + p(&z, x)
+                        * ((ea + beta * xi + gamma)
+                            * (eb + beta * self.k1 * xi + gamma)
+                            * (ec + beta * self.k2 * xi + gamma)
+                            * alpha
+                            + p(&l1, xi) * alpha.square())
+                    - p(&self.sigma[2], x)
+                        * (ea + beta * proof.eval_s1 + gamma)
+                        * (eb + beta * proof.eval_s2 + gamma)
+                        * alpha
+                        * beta
+                        * proof.eval_zw
+                    - zh * (t1(x) + xin * t2 + xin.square() * t3)
+            };
+            let opened = |x: Fr| {
+                linearization(x)
+                    + v[0] * p(&a, x)
+                    + v[1] * p(&b, x)
+                    + v[2] * p(&c, x)
+                    + v[3] * p(&self.sigma[0], x)
+                    + v[4] * p(&self.sigma[1], x)
+            };
+            proof.wxi = Self::commit((opened(self.tau) - opened(xi)) / (self.tau - xi));
+            proof.wxiw = Self::commit((p(&z, self.tau) - proof.eval_zw) / (self.tau - xi * self.w));
+            proof
+        }
+    }
+
+    fn seal(proof: &Proof) -> PlonkSeal {
+        let be = |value: &[u64; 4]| ark_ff::BigInt(*value).to_bytes_be();
+        let point = |p: &G1Affine| {
+            let (x, y) = p.xy().unwrap();
+            vec![be(&x.into_bigint().0), be(&y.into_bigint().0)]
+        };
+        PlonkSeal {
+            commitments: [
+                proof.a, proof.b, proof.c, proof.z, proof.t1, proof.t2, proof.t3, proof.wxi,
+                proof.wxiw,
+            ]
+            .iter()
+            .map(point)
+            .collect(),
+            evaluations: [
+                proof.eval_a,
+                proof.eval_b,
+                proof.eval_c,
+                proof.eval_s1,
+                proof.eval_s2,
+                proof.eval_zw,
+            ]
+            .iter()
+            .map(|value| be(&value.into_bigint().0))
+            .collect(),
+        }
+    }
+
+    #[test]
+    fn verify() {
+        let circuit = Circuit::new();
+        let vk = circuit.verifying_key();
+        let seal = seal(&circuit.prove(3, 5));
+        vk.verify(&seal, &[Fr::from(15u64)]).unwrap();
+        assert!(vk.verify(&seal, &[Fr::from(16u64)]).is_err());
+        assert!(vk.verify(&seal, &[]).is_err());
+
+        let decoded = PlonkSeal::from_vec(&seal.to_vec()).unwrap();
+        assert_eq!(decoded, seal);
+    }
+
+    #[test]
+    fn tampered_evaluation() {
+        let circuit = Circuit::new();
+        let vk = circuit.verifying_key();
+        let mut seal = seal(&circuit.prove(3, 5));
+        seal.evaluations[5][31] ^= 1;
+        assert!(vk.verify(&seal, &[Fr::from(15u64)]).is_err());
+    }
+}
@@ -0,0 +1,471 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A native Groth16 prover for the STARK-to-SNARK circuit.
+//!
+//! The prover consumes the proving key and witness produced by the circom and
+//! snarkjs toolchain, in the `.zkey` and `.wtns` formats, and computes the
+//! proof in Rust, so it runs on any host, including Linux aarch64 and macOS.
+//!
+//! [stark_to_snark] uses it when the `RISC0_GROTH16_DIR` environment variable
+//! names a directory holding the circuit artifacts:
+//!
+//! * `stark_verify`, the circom witness generator for the circuit, built for
+//!   the host, and
+//! * `stark_verify_final.zkey`, the proving key.
+//!
+//! These are the same artifacts used by the Docker image, from which they can
+//! be extracted. When the variable is not set, [stark_to_snark] falls back to
+//! the Docker prover.
+//!
+//! Deployments that bring their own proving key, from their own ceremony or a
+//! newer version of the circuit, can pin it by setting
+//! `RISC0_GROTH16_ZKEY_DIGEST` to the hex SHA-256 digest of the `.zkey` file,
+//! which is then checked before the key is used.
+//!
+//! [stark_to_plonk] wraps the same circuit in a PLONK proof instead, which
+//! needs no trusted setup specific to the circuit.
+
+mod zkey;
+
+use std::{
+    fs::File,
+    io::{BufReader, Cursor},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{anyhow, bail, ensure, Result};
+use ark_bn254::{Bn254, Fq, Fr, G1Projective, G2Projective};
+use ark_ec::{AffineRepr, VariableBaseMSM};
+use ark_ff::{BigInteger, Field, PrimeField, UniformRand, Zero};
+use ark_groth16::Proof;
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+use hex::FromHex;
+use rand::{rngs::OsRng, RngCore};
+use risc0_zkp::core::{
+    digest::Digest,
+    hash::sha::{cpu::Impl, Sha256},
+};
+use tempfile::tempdir;
+
+pub use self::zkey::{read_witness, ProvingKey};
+use crate::{to_json, PlonkProofJson, PlonkSeal, PlonkVerifyingKey, PlonkVerifyingKeyJson, Seal};
+
+const WITNESS_GENERATOR: &str = "stark_verify";
+const PROVING_KEY: &str = "stark_verify_final.zkey";
+const PLONK_PROVING_KEY: &str = "stark_verify_plonk.zkey";
+const PLONK_VERIFYING_KEY: &str = "stark_verify_plonk_vkey.json";
+
+/// Compact a given seal of an `identity_p254` receipt into a Groth16 `Seal`.
+///
+/// This uses the native prover if `RISC0_GROTH16_DIR` is set, and the Docker
+/// prover otherwise.
+pub fn stark_to_snark(identity_p254_seal_bytes: &[u8]) -> Result<Seal> {
+    match std::env::var_os("RISC0_GROTH16_DIR") {
+        Some(dir) => native_stark_to_snark(identity_p254_seal_bytes, Path::new(&dir)),
+        None => crate::docker::stark_to_snark(identity_p254_seal_bytes),
+    }
+}
+
+/// Compact a given seal of an `identity_p254` receipt into a Groth16 `Seal`,
+/// using the native prover with the circuit artifacts in `artifacts_dir`.
+pub fn native_stark_to_snark(
+    identity_p254_seal_bytes: &[u8],
+    artifacts_dir: &Path,
+) -> Result<Seal> {
+    let work_dir = tempdir()?;
+    let witness_path = generate_witness(identity_p254_seal_bytes, artifacts_dir, work_dir.path())?;
+    let witness = read_witness(BufReader::new(File::open(witness_path)?))?;
+
+    tracing::debug!("loading proving key");
+    let path = artifacts_dir.join(PROVING_KEY);
+    let key = match std::env::var("RISC0_GROTH16_ZKEY_DIGEST") {
+        Ok(digest) => read_proving_key(&path, &Digest::from_hex(digest.trim())?)?,
+        Err(_) => ProvingKey::read(BufReader::new(File::open(path)?))?,
+    };
+
+    tracing::debug!("groth16 prove");
+    let proof = prove(&key, &witness, &mut OsRng)?;
+    Ok(to_seal(&proof))
+}
+
+/// Read the proving key in the `.zkey` file at `path`, checking that the
+/// SHA-256 digest of the file is `digest`.
+pub fn read_proving_key(path: &Path, digest: &Digest) -> Result<ProvingKey> {
+    let contents = std::fs::read(path)?;
+    let actual = *Impl::hash_bytes(&contents);
+    ensure!(
+        actual == *digest,
+        "proving key {} has digest {actual}, expected {digest}",
+        path.display()
+    );
+    ProvingKey::read(Cursor::new(contents))
+}
+
+/// Compact a given seal of an `identity_p254` receipt into a [PlonkSeal].
+///
+/// `RISC0_PLONK_DIR` must name a directory holding the witness generator, as
+/// for the native Groth16 prover, and the PLONK proving key
+/// `stark_verify_plonk.zkey`, made by `snarkjs plonk setup` from the circuit
+/// and a powers-of-tau file, with no ceremony specific to the circuit. The
+/// proof is computed by snarkjs, which is run as `snarkjs`, or as set by the
+/// `RISC0_SNARKJS` environment variable.
+pub fn stark_to_plonk(identity_p254_seal_bytes: &[u8]) -> Result<PlonkSeal> {
+    let artifacts_dir = plonk_dir()?;
+    let artifacts_dir = artifacts_dir.as_path();
+    let snarkjs = std::env::var_os("RISC0_SNARKJS").unwrap_or_else(|| "snarkjs".into());
+
+    let work_dir = tempdir()?;
+    let witness_path = generate_witness(identity_p254_seal_bytes, artifacts_dir, work_dir.path())?;
+    let proof_path = work_dir.path().join("proof.json");
+
+    tracing::debug!("plonk prove");
+    let status = Command::new(&snarkjs)
+        .args(["plonk", "prove"])
+        .arg(artifacts_dir.join(PLONK_PROVING_KEY))
+        .arg(&witness_path)
+        .arg(&proof_path)
+        .arg(work_dir.path().join("public.json"))
+        .status()
+        .map_err(|err| anyhow!("failed to run {}: {err}", snarkjs.to_string_lossy()))?;
+    if !status.success() {
+        bail!("snarkjs returned failure exit code: {:?}", status.code());
+    }
+
+    tracing::debug!("Parsing proof");
+    let contents = std::fs::read_to_string(proof_path)?;
+    let proof_json: PlonkProofJson = serde_json::from_str(&contents)?;
+    proof_json.try_into()
+}
+
+/// Load the PLONK verifying key from `stark_verify_plonk_vkey.json` in
+/// `RISC0_PLONK_DIR`, as exported by `snarkjs zkey export verificationkey`.
+pub fn plonk_verifying_key() -> Result<PlonkVerifyingKey> {
+    let contents = std::fs::read_to_string(plonk_dir()?.join(PLONK_VERIFYING_KEY))?;
+    let vk: PlonkVerifyingKeyJson = serde_json::from_str(&contents)?;
+    vk.verifying_key()
+}
+
+fn plonk_dir() -> Result<PathBuf> {
+    match std::env::var_os("RISC0_PLONK_DIR") {
+        Some(dir) => Ok(dir.into()),
+        None => {
+            bail!("RISC0_PLONK_DIR must be set to the directory of the PLONK circuit artifacts")
+        }
+    }
+}
+
+// Run the circuit's witness generator on the seal, returning the path of the
+// witness it wrote to `work_dir`.
+fn generate_witness(
+    identity_p254_seal_bytes: &[u8],
+    artifacts_dir: &Path,
+    work_dir: &Path,
+) -> Result<PathBuf> {
+    let input_path = work_dir.join("input.json");
+    let witness_path = work_dir.join("output.wtns");
+
+    tracing::debug!("seal-to-json");
+    let mut seal_json = Vec::new();
+    to_json(identity_p254_seal_bytes, &mut seal_json)?;
+    std::fs::write(&input_path, seal_json)?;
+
+    tracing::debug!("witness generation");
+    let generator = artifacts_dir.join(WITNESS_GENERATOR);
+    let status = Command::new(&generator)
+        .arg(&input_path)
+        .arg(&witness_path)
+        .status()
+        .map_err(|err| anyhow!("failed to run {}: {err}", generator.display()))?;
+    if !status.success() {
+        bail!(
+            "witness generator returned failure exit code: {:?}",
+            status.code()
+        );
+    }
+    Ok(witness_path)
+}
+
+/// Prove that `witness` satisfies the circuit of `key`.
+///
+/// The witness is the full assignment of the circuit's signals, beginning with
+/// the constant one and the public signals, as read by [read_witness].
+pub fn prove(key: &ProvingKey, witness: &[Fr], rng: &mut impl RngCore) -> Result<Proof<Bn254>> {
+    ensure!(
+        witness.len() == key.n_vars,
+        "witness has {} signals, but the circuit has {}",
+        witness.len(),
+        key.n_vars
+    );
+    ensure!(
+        witness[0] == Fr::ONE,
+        "witness does not begin with the constant one"
+    );
+
+    let r = Fr::rand(rng);
+    let s = Fr::rand(rng);
+
+    let h = quotient_evaluations(key, witness);
+    let witness: Vec<_> = witness.iter().map(|w| w.into_bigint()).collect();
+    let h: Vec<_> = h.iter().map(|h| h.into_bigint()).collect();
+
+    let a = G1Projective::msm_bigint(&key.a_query, &witness) + key.alpha_g1 + key.delta_g1 * r;
+    let b_g1 = G1Projective::msm_bigint(&key.b_g1_query, &witness) + key.beta_g1 + key.delta_g1 * s;
+    let b_g2 = G2Projective::msm_bigint(&key.b_g2_query, &witness) + key.beta_g2 + key.delta_g2 * s;
+    let c = G1Projective::msm_bigint(&key.c_query, &witness[key.n_public + 1..])
+        + G1Projective::msm_bigint(&key.h_query, &h)
+        + a * s
+        + b_g1 * r
+        - key.delta_g1 * (r * s);
+
+    Ok(Proof {
+        a: a.into(),
+        b: b_g2.into(),
+        c: c.into(),
+    })
+}
+
+// Evaluate the quotient polynomial on the odd powers of the 2n-th root of
+// unity, which is what the H section of a snarkjs key is computed against.
+//
+// The A and B polynomials are known by their evaluations on the domain, and C
+// by their products, since the witness satisfies every constraint. Each is
+// interpolated and then evaluated on the coset, where the vanishing polynomial
+// is constant and so is folded into the key.
+fn quotient_evaluations(key: &ProvingKey, witness: &[Fr]) -> Vec<Fr> {
+    let domain = Radix2EvaluationDomain::<Fr>::new(key.domain_size).unwrap();
+    let root = Radix2EvaluationDomain::<Fr>::new(2 * key.domain_size)
+        .unwrap()
+        .group_gen;
+
+    let mut a = vec![Fr::zero(); key.domain_size];
+    let mut b = vec![Fr::zero(); key.domain_size];
+    for coeff in &key.coeffs {
+        let evals = if coeff.in_b { &mut b } else { &mut a };
+        evals[coeff.constraint] += coeff.value * witness[coeff.signal];
+    }
+    let mut c: Vec<_> = a.iter().zip(&b).map(|(a, b)| *a * b).collect();
+
+    for evals in [&mut a, &mut b, &mut c] {
+        domain.ifft_in_place(evals);
+        let mut power = Fr::ONE;
+        for coeff in evals.iter_mut() {
+            *coeff *= power;
+            power *= root;
+        }
+        domain.fft_in_place(evals);
+    }
+
+    a.iter()
+        .zip(&b)
+        .zip(&c)
+        .map(|((a, b), c)| *a * b - c)
+        .collect()
+}
+
+fn to_bytes(value: &Fq) -> Vec<u8> {
+    value.into_bigint().to_bytes_be()
+}
+
+/// Encode a proof as a [Seal], in the order the verifier contract expects.
+pub fn to_seal(proof: &Proof<Bn254>) -> Seal {
+    let zero = Fq::zero();
+    let (a_x, a_y) = proof.a.xy().unwrap_or((&zero, &zero));
+    let (c_x, c_y) = proof.c.xy().unwrap_or((&zero, &zero));
+    let (b_x, b_y) = match proof.b.xy() {
+        Some((x, y)) => (*x, *y),
+        None => Default::default(),
+    };
+    Seal {
+        a: vec![to_bytes(a_x), to_bytes(a_y)],
+        b: vec![
+            vec![to_bytes(&b_x.c1), to_bytes(&b_x.c0)],
+            vec![to_bytes(&b_y.c1), to_bytes(&b_y.c0)],
+        ],
+        c: vec![to_bytes(c_x), to_bytes(c_y)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::{Bn254, Fr, G1Affine, G2Affine};
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_ff::{Field, One, Zero};
+    use ark_groth16::{prepare_verifying_key, Groth16, VerifyingKey};
+    use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use risc0_zkp::core::{
+        digest::Digest,
+        hash::sha::{cpu::Impl, Sha256},
+    };
+
+    use super::{
+        prove, read_proving_key, to_seal,
+        zkey::tests::{coeff, g1, g2, prime, SectionWriter},
+        ProvingKey,
+    };
+    use crate::{g1_from_bytes, g2_from_bytes};
+
+    const N: usize = 4;
+    const N_VARS: usize = 4;
+    const N_PUBLIC: usize = 1;
+
+    // The circuit `x * y = z`, with the signals [1, z, x, y] and `z` public.
+    // As snarkjs does, the constant and public signals are bound by extra
+    // constraints in A, after the circuit's own.
+    const A: &[(usize, usize)] = &[(0, 2), (1, 0), (2, 1)];
+    const B: &[(usize, usize)] = &[(0, 3)];
+    const C: &[(usize, usize)] = &[(0, 1)];
+
+    // A trusted setup with fixed toxic waste, producing the key in the format
+    // of snarkjs and the matching verifying key.
+    fn setup() -> (Vec<u8>, VerifyingKey<Bn254>) {
+        let [tau, alpha, beta, gamma, delta] = [11u64, 13, 17, 19, 23].map(Fr::from);
+        let g = G1Affine::generator();
+        let h = G2Affine::generator();
+        let g1_at = |value: Fr| (g * value).into_affine();
+        let g2_at = |value: Fr| (h * value).into_affine();
+
+        let domain = Radix2EvaluationDomain::<Fr>::new(N).unwrap();
+        let n = Fr::from(N as u64);
+        let z_tau = tau.pow([N as u64]) - Fr::one();
+        let lagrange = |i: usize| {
+            let w = domain.element(i);
+            z_tau * w / (n * (tau - w))
+        };
+        let poly = |matrix: &[(usize, usize)], signal: usize| -> Fr {
+            matrix
+                .iter()
+                .filter(|(_, s)| *s == signal)
+                .map(|(c, _)| lagrange(*c))
+                .sum()
+        };
+        let u: Vec<_> = (0..N_VARS).map(|j| poly(A, j)).collect();
+        let v: Vec<_> = (0..N_VARS).map(|j| poly(B, j)).collect();
+        let w: Vec<_> = (0..N_VARS).map(|j| poly(C, j)).collect();
+        let ic = |j: usize| beta * u[j] + alpha * v[j] + w[j];
+
+        let mut header = Vec::new();
+        prime::<ark_bn254::Fq>(&mut header);
+        prime::<Fr>(&mut header);
+        for value in [N_VARS, N_PUBLIC, N] {
+            header.extend((value as u32).to_le_bytes());
+        }
+        g1(&mut header, g1_at(alpha));
+        g1(&mut header, g1_at(beta));
+        g2(&mut header, g2_at(beta));
+        g2(&mut header, g2_at(gamma));
+        g1(&mut header, g1_at(delta));
+        g2(&mut header, g2_at(delta));
+
+        let mut coeffs = ((A.len() + B.len()) as u32).to_le_bytes().to_vec();
+        for (matrix, entries) in [(0, A), (1, B)] {
+            for (constraint, signal) in entries {
+                coeff(
+                    &mut coeffs,
+                    matrix,
+                    *constraint as u32,
+                    *signal as u32,
+                    Fr::one(),
+                );
+            }
+        }
+
+        let points = |values: &mut dyn Iterator<Item = Fr>| {
+            let mut out = Vec::new();
+            values.for_each(|value| g1(&mut out, g1_at(value)));
+            out
+        };
+        let mut b_g2 = Vec::new();
+        v.iter().for_each(|value| g2(&mut b_g2, g2_at(*value)));
+
+        // The quotient is evaluated on the coset of odd powers of the 2n-th
+        // root, where the vanishing polynomial is -2.
+        let shift = Radix2EvaluationDomain::<Fr>::new(2 * N).unwrap().group_gen;
+        let h_query = (0..N).map(|i| {
+            let c = shift * domain.element(i);
+            let coset_lagrange = -(tau.pow([N as u64]) + Fr::one()) * c / (n * (tau - c));
+            coset_lagrange * z_tau / (-Fr::from(2u64) * delta)
+        });
+
+        let mut file = SectionWriter::new();
+        file.section(1, 1u32.to_le_bytes().to_vec());
+        file.section(2, header);
+        file.section(4, coeffs);
+        file.section(5, points(&mut u.iter().copied()));
+        file.section(6, points(&mut v.iter().copied()));
+        file.section(7, b_g2);
+        file.section(
+            8,
+            points(&mut (N_PUBLIC + 1..N_VARS).map(|j| ic(j) / delta)),
+        );
+        file.section(9, points(&mut h_query.into_iter()));
+
+        let vk = VerifyingKey {
+            alpha_g1: g1_at(alpha),
+            beta_g2: g2_at(beta),
+            gamma_g2: g2_at(gamma),
+            delta_g2: g2_at(delta),
+            gamma_abc_g1: (0..=N_PUBLIC).map(|j| g1_at(ic(j) / gamma)).collect(),
+        };
+        (file.finish(b"zkey"), vk)
+    }
+
+    fn witness(x: u64, y: u64, z: u64) -> Vec<Fr> {
+        [1, z, x, y].map(Fr::from).to_vec()
+    }
+
+    #[test]
+    fn proves_against_snarkjs_key() {
+        let (zkey, vk) = setup();
+        let key = ProvingKey::read(std::io::Cursor::new(zkey)).unwrap();
+        let pvk = prepare_verifying_key(&vk);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let proof = prove(&key, &witness(3, 5, 15), &mut rng).unwrap();
+        assert!(Groth16::<Bn254>::verify_proof(&pvk, &proof, &[Fr::from(15u64)]).unwrap());
+        assert!(!Groth16::<Bn254>::verify_proof(&pvk, &proof, &[Fr::from(16u64)]).unwrap());
+
+        let seal = to_seal(&proof);
+        assert_eq!(g1_from_bytes(&seal.a).unwrap(), proof.a);
+        assert_eq!(g2_from_bytes(&seal.b).unwrap(), proof.b);
+        assert_eq!(g1_from_bytes(&seal.c).unwrap(), proof.c);
+    }
+
+    #[test]
+    fn unsatisfied_witness_does_not_verify() {
+        let (zkey, vk) = setup();
+        let key = ProvingKey::read(std::io::Cursor::new(zkey)).unwrap();
+        let pvk = prepare_verifying_key(&vk);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let proof = prove(&key, &witness(3, 5, 16), &mut rng).unwrap();
+        assert!(!Groth16::<Bn254>::verify_proof(&pvk, &proof, &[Fr::from(16u64)]).unwrap());
+        assert!(prove(&key, &[Fr::zero(); N_VARS], &mut rng).is_err());
+    }
+
+    #[test]
+    fn pinned_proving_key() {
+        let (zkey, _) = setup();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("key.zkey");
+        std::fs::write(&path, &zkey).unwrap();
+
+        let digest = *Impl::hash_bytes(&zkey);
+        read_proving_key(&path, &digest).unwrap();
+        assert!(read_proving_key(&path, &Digest::ZERO).is_err());
+    }
+}
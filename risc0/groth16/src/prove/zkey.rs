@@ -0,0 +1,378 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Readers for the binary `.zkey` and `.wtns` formats written by snarkjs and
+//! circom witness generators.
+
+use std::{
+    collections::BTreeMap,
+    io::{Read, Seek, SeekFrom},
+};
+
+use anyhow::{bail, ensure, Context, Result};
+use ark_bn254::{Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ff::{BigInt, BigInteger, PrimeField};
+
+const FIELD_BYTES: usize = 32;
+
+// snarkjs identifies the proving protocol in the header section.
+const GROTH16_PROTOCOL: u32 = 1;
+
+/// A Groth16 proving key, as written by snarkjs.
+pub struct ProvingKey {
+    pub(crate) n_vars: usize,
+    pub(crate) n_public: usize,
+    pub(crate) domain_size: usize,
+    pub(crate) alpha_g1: G1Affine,
+    pub(crate) beta_g1: G1Affine,
+    pub(crate) beta_g2: G2Affine,
+    pub(crate) delta_g1: G1Affine,
+    pub(crate) delta_g2: G2Affine,
+    pub(crate) coeffs: Vec<Coeff>,
+    pub(crate) a_query: Vec<G1Affine>,
+    pub(crate) b_g1_query: Vec<G1Affine>,
+    pub(crate) b_g2_query: Vec<G2Affine>,
+    pub(crate) c_query: Vec<G1Affine>,
+    pub(crate) h_query: Vec<G1Affine>,
+}
+
+/// A nonzero entry of the A or B matrix of the constraint system.
+pub(crate) struct Coeff {
+    pub(crate) in_b: bool,
+    pub(crate) constraint: usize,
+    pub(crate) signal: usize,
+    pub(crate) value: Fr,
+}
+
+impl ProvingKey {
+    /// Read a proving key in the `.zkey` format.
+    pub fn read<R: Read + Seek>(reader: R) -> Result<Self> {
+        let mut file = SectionFile::open(reader, b"zkey")?;
+
+        let protocol = read_u32(&mut file.section(1)?)?;
+        ensure!(
+            protocol == GROTH16_PROTOCOL,
+            "zkey is for protocol {protocol}, expected Groth16"
+        );
+
+        let mut header = file.section(2)?;
+        read_prime::<Fq>(&mut header)?;
+        read_prime::<Fr>(&mut header)?;
+        let n_vars = read_u32(&mut header)? as usize;
+        let n_public = read_u32(&mut header)? as usize;
+        let domain_size = read_u32(&mut header)? as usize;
+        ensure!(
+            domain_size.is_power_of_two(),
+            "zkey domain size {domain_size} is not a power of two"
+        );
+        ensure!(
+            n_public < n_vars,
+            "zkey has more public inputs than signals"
+        );
+        let alpha_g1 = read_g1(&mut header)?;
+        let beta_g1 = read_g1(&mut header)?;
+        let beta_g2 = read_g2(&mut header)?;
+        let _gamma_g2 = read_g2(&mut header)?;
+        let delta_g1 = read_g1(&mut header)?;
+        let delta_g2 = read_g2(&mut header)?;
+
+        let mut section = file.section(4)?;
+        let n_coeffs = read_u32(&mut section)? as usize;
+        let mut coeffs = Vec::with_capacity(n_coeffs);
+        for _ in 0..n_coeffs {
+            let matrix = read_u32(&mut section)?;
+            let constraint = read_u32(&mut section)? as usize;
+            let signal = read_u32(&mut section)? as usize;
+            // Coefficients are stored in Montgomery form, premultiplied by R.
+            let value =
+                Fr::new_unchecked(Fr::new_unchecked(read_bigint(&mut section)?).into_bigint());
+            ensure!(
+                matrix < 2,
+                "zkey coefficient is for unknown matrix {matrix}"
+            );
+            ensure!(
+                constraint < domain_size && signal < n_vars,
+                "zkey coefficient is out of range"
+            );
+            coeffs.push(Coeff {
+                in_b: matrix == 1,
+                constraint,
+                signal,
+                value,
+            });
+        }
+
+        Ok(Self {
+            n_vars,
+            n_public,
+            domain_size,
+            alpha_g1,
+            beta_g1,
+            beta_g2,
+            delta_g1,
+            delta_g2,
+            coeffs,
+            a_query: read_points(&mut file.section(5)?, n_vars, read_g1)?,
+            b_g1_query: read_points(&mut file.section(6)?, n_vars, read_g1)?,
+            b_g2_query: read_points(&mut file.section(7)?, n_vars, read_g2)?,
+            c_query: read_points(&mut file.section(8)?, n_vars - n_public - 1, read_g1)?,
+            h_query: read_points(&mut file.section(9)?, domain_size, read_g1)?,
+        })
+    }
+}
+
+/// Read a witness in the `.wtns` format.
+pub fn read_witness<R: Read + Seek>(reader: R) -> Result<Vec<Fr>> {
+    let mut file = SectionFile::open(reader, b"wtns")?;
+
+    let mut header = file.section(1)?;
+    ensure!(
+        read_u32(&mut header)? as usize == FIELD_BYTES,
+        "witness is not over the BN254 scalar field"
+    );
+    ensure!(
+        read_bigint(&mut header)? == Fr::MODULUS,
+        "witness is not over the BN254 scalar field"
+    );
+    let len = read_u32(&mut header)? as usize;
+
+    let mut values = file.section(2)?;
+    (0..len)
+        .map(|_| Fr::from_bigint(read_bigint(&mut values)?).context("witness value is not reduced"))
+        .collect()
+}
+
+// The container format shared by `.zkey` and `.wtns` files: a magic number, a
+// version, and a list of sections, each tagged with its type and size.
+struct SectionFile<R> {
+    reader: R,
+    sections: BTreeMap<u32, (u64, u64)>,
+}
+
+impl<R: Read + Seek> SectionFile<R> {
+    fn open(mut reader: R, magic: &[u8; 4]) -> Result<Self> {
+        let mut found = [0u8; 4];
+        reader.read_exact(&mut found)?;
+        ensure!(
+            &found == magic,
+            "not a {} file",
+            String::from_utf8_lossy(magic)
+        );
+        let _version = read_u32(&mut reader)?;
+        let n_sections = read_u32(&mut reader)?;
+
+        let mut sections = BTreeMap::new();
+        for _ in 0..n_sections {
+            let kind = read_u32(&mut reader)?;
+            let size = read_u64(&mut reader)?;
+            let offset = reader.stream_position()?;
+            if sections.insert(kind, (offset, size)).is_some() {
+                bail!("section {kind} appears more than once");
+            }
+            reader.seek(SeekFrom::Current(size as i64))?;
+        }
+        Ok(Self { reader, sections })
+    }
+
+    fn section(&mut self, kind: u32) -> Result<std::io::Take<&mut R>> {
+        let (offset, size) = *self
+            .sections
+            .get(&kind)
+            .with_context(|| format!("missing section {kind}"))?;
+        self.reader.seek(SeekFrom::Start(offset))?;
+        Ok(self.reader.by_ref().take(size))
+    }
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(reader: &mut impl Read) -> Result<u64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_bigint(reader: &mut impl Read) -> Result<BigInt<4>> {
+    let mut buf = [0u8; FIELD_BYTES];
+    reader.read_exact(&mut buf)?;
+    let mut limbs = [0u64; 4];
+    for (limb, bytes) in limbs.iter_mut().zip(buf.chunks_exact(8)) {
+        *limb = u64::from_le_bytes(bytes.try_into().unwrap());
+    }
+    Ok(BigInt::new(limbs))
+}
+
+fn read_prime<F: PrimeField<BigInt = BigInt<4>>>(reader: &mut impl Read) -> Result<()> {
+    let size = read_u32(reader)? as usize;
+    ensure!(size == FIELD_BYTES, "zkey is not over BN254");
+    ensure!(read_bigint(reader)? == F::MODULUS, "zkey is not over BN254");
+    Ok(())
+}
+
+// Points are stored as affine coordinates in Montgomery form, with the point at
+// infinity encoded as zeros.
+fn read_fq(reader: &mut impl Read) -> Result<Fq> {
+    let value = read_bigint(reader)?;
+    ensure!(value < Fq::MODULUS, "zkey coordinate is not reduced");
+    Ok(Fq::new_unchecked(value))
+}
+
+fn read_g1(reader: &mut impl Read) -> Result<G1Affine> {
+    let x = read_fq(reader)?;
+    let y = read_fq(reader)?;
+    if x.0.is_zero() && y.0.is_zero() {
+        return Ok(G1Affine::identity());
+    }
+    let point = G1Affine::new_unchecked(x, y);
+    ensure!(point.is_on_curve(), "zkey G1 point is not on the curve");
+    Ok(point)
+}
+
+fn read_g2(reader: &mut impl Read) -> Result<G2Affine> {
+    let x = Fq2::new(read_fq(reader)?, read_fq(reader)?);
+    let y = Fq2::new(read_fq(reader)?, read_fq(reader)?);
+    if x == Fq2::from(0u64) && y == Fq2::from(0u64) {
+        return Ok(G2Affine::identity());
+    }
+    let point = G2Affine::new_unchecked(x, y);
+    ensure!(point.is_on_curve(), "zkey G2 point is not on the curve");
+    Ok(point)
+}
+
+fn read_points<R: Read, P>(
+    reader: &mut R,
+    len: usize,
+    read: fn(&mut R) -> Result<P>,
+) -> Result<Vec<P>> {
+    (0..len).map(|_| read(reader)).collect()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    //! Writers for the same formats, for building keys in tests.
+
+    use ark_bn254::{Fq, Fr, G1Affine, G2Affine};
+    use ark_ec::AffineRepr;
+    use ark_ff::{BigInt, PrimeField, Zero};
+
+    use super::FIELD_BYTES;
+
+    const G1_BYTES: usize = 2 * FIELD_BYTES;
+    const G2_BYTES: usize = 4 * FIELD_BYTES;
+
+    pub(crate) struct SectionWriter {
+        sections: Vec<(u32, Vec<u8>)>,
+    }
+
+    impl SectionWriter {
+        pub(crate) fn new() -> Self {
+            Self {
+                sections: Vec::new(),
+            }
+        }
+
+        pub(crate) fn section(&mut self, kind: u32, data: Vec<u8>) {
+            self.sections.push((kind, data));
+        }
+
+        pub(crate) fn finish(self, magic: &[u8; 4]) -> Vec<u8> {
+            let mut out = magic.to_vec();
+            out.extend(1u32.to_le_bytes());
+            out.extend((self.sections.len() as u32).to_le_bytes());
+            for (kind, data) in self.sections {
+                out.extend(kind.to_le_bytes());
+                out.extend((data.len() as u64).to_le_bytes());
+                out.extend(data);
+            }
+            out
+        }
+    }
+
+    pub(crate) fn bigint(out: &mut Vec<u8>, value: BigInt<4>) {
+        for limb in value.0 {
+            out.extend(limb.to_le_bytes());
+        }
+    }
+
+    pub(crate) fn prime<F: PrimeField<BigInt = BigInt<4>>>(out: &mut Vec<u8>) {
+        out.extend(32u32.to_le_bytes());
+        bigint(out, F::MODULUS);
+    }
+
+    pub(crate) fn coeff(out: &mut Vec<u8>, matrix: u32, constraint: u32, signal: u32, value: Fr) {
+        out.extend(matrix.to_le_bytes());
+        out.extend(constraint.to_le_bytes());
+        out.extend(signal.to_le_bytes());
+        bigint(out, Fr::from_bigint(value.0).unwrap().0);
+    }
+
+    fn fq(out: &mut Vec<u8>, value: Fq) {
+        bigint(out, value.0);
+    }
+
+    pub(crate) fn g1(out: &mut Vec<u8>, point: G1Affine) {
+        let start = out.len();
+        match point.xy() {
+            Some((x, y)) => {
+                fq(out, *x);
+                fq(out, *y);
+            }
+            None => out.extend([0; G1_BYTES]),
+        }
+        assert_eq!(out.len() - start, G1_BYTES);
+    }
+
+    pub(crate) fn g2(out: &mut Vec<u8>, point: G2Affine) {
+        match point.xy() {
+            Some((x, y)) => {
+                for value in [x.c0, x.c1, y.c0, y.c1] {
+                    fq(out, value);
+                }
+            }
+            None => out.extend([0; G2_BYTES]),
+        }
+    }
+
+    pub(crate) fn witness(values: &[Fr]) -> Vec<u8> {
+        let mut header = Vec::new();
+        prime::<Fr>(&mut header);
+        header.extend((values.len() as u32).to_le_bytes());
+        let mut data = Vec::new();
+        for value in values {
+            bigint(&mut data, value.into_bigint());
+        }
+        let mut file = SectionWriter::new();
+        file.section(1, header);
+        file.section(2, data);
+        file.finish(b"wtns")
+    }
+
+    #[test]
+    fn witness_round_trip() {
+        let values = [Fr::from(1u64), Fr::zero(), -Fr::from(7u64)];
+        let read = super::read_witness(std::io::Cursor::new(witness(&values))).unwrap();
+        assert_eq!(read, values);
+    }
+
+    #[test]
+    fn rejects_wrong_magic() {
+        let mut bytes = witness(&[Fr::from(1u64)]);
+        bytes[..4].copy_from_slice(b"zkey");
+        assert!(super::read_witness(std::io::Cursor::new(bytes)).is_err());
+    }
+}
@@ -0,0 +1,397 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Encoding of Groth16 receipts for verification in Solana programs.
+//!
+//! A Solana program verifies a Groth16 seal with the `alt_bn128` syscalls and
+//! computes the claim digest with the `sha256` syscall, within the compute
+//! budget of a transaction and without growing its heap. [SolanaReceipt] lays
+//! out a receipt so that a program can read it in place: the seal, with its
+//! first point negated as the pairing check expects, followed by the image ID,
+//! the post state digest and the journal.
+//!
+//! A transaction carries at most 1232 bytes, so a receipt with more than a few
+//! hundred bytes of journal is written to a buffer account across several
+//! transactions. [SolanaReceipt::partition] splits an encoded receipt into the
+//! [Chunk]s of those transactions, under a [BufferHeader] that the first one
+//! allocates the buffer with. Once every chunk is written, the program checks
+//! the buffer against the digest in the header and verifies it in a last
+//! transaction. [reassemble] is the reference for that process.
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use anyhow::{bail, ensure, Result};
+use ark_bn254::{G1Affine, G2Affine};
+use risc0_zkp::core::{
+    digest::Digest,
+    hash::sha::{cpu::Impl, Sha256},
+};
+
+use crate::{
+    g1_from_bytes,
+    solidity::{fr_bytes, g1_elems, g2_elems, tagged_struct},
+    split_digest, Seal, VerifierParameters,
+};
+
+/// Size of a seal encoded for the `alt_bn128` syscalls, in bytes.
+pub const SOLANA_SEAL_SIZE: usize = 256;
+
+// The seal, image ID and post state digest that precede the journal.
+const HEADER_SIZE: usize = SOLANA_SEAL_SIZE + 2 * 32;
+
+/// Limits that a receipt must fit to be verified by a Solana program.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SolanaProfile {
+    /// Most bytes of an encoded receipt written by one transaction, leaving
+    /// room in the transaction for its signature, accounts and instruction
+    /// header.
+    pub max_chunk_len: usize,
+
+    /// Longest journal accepted, which bounds the size of the buffer account
+    /// and the compute units spent hashing the journal in the verifying
+    /// transaction.
+    pub max_journal_len: usize,
+}
+
+impl Default for SolanaProfile {
+    fn default() -> Self {
+        Self {
+            max_chunk_len: 900,
+            max_journal_len: 10 * 1024,
+        }
+    }
+}
+
+/// A Groth16 receipt, encoded for verification in a Solana program.
+///
+/// The claim is that of an execution of `image_id` that halted with exit code
+/// zero, with no input, assumptions or channels, as with the Solidity
+/// verifier.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SolanaReceipt {
+    /// The seal, as the points `-A`, `B` and `C` encoded for the `alt_bn128`
+    /// syscalls.
+    pub seal: [u8; SOLANA_SEAL_SIZE],
+
+    /// Image ID of the guest.
+    pub image_id: Digest,
+
+    /// Digest of the system state at the end of the execution.
+    pub post_state_digest: Digest,
+
+    /// The journal committed by the guest.
+    pub journal: Vec<u8>,
+}
+
+/// The header of a buffer account that a receipt is written to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BufferHeader {
+    /// Length of the encoded receipt, which the buffer is allocated with.
+    pub len: u32,
+
+    /// SHA-256 digest of the encoded receipt, which the buffer must match
+    /// before it is verified.
+    pub digest: Digest,
+}
+
+/// Part of an encoded receipt, written to a buffer account by one
+/// transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Chunk {
+    /// Offset of the data in the buffer.
+    pub offset: u32,
+
+    /// The data to write.
+    pub data: Vec<u8>,
+}
+
+impl Chunk {
+    /// Encode this chunk as instruction data: the offset as a little-endian
+    /// `u32`, followed by the data.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoded = self.offset.to_le_bytes().to_vec();
+        encoded.extend_from_slice(&self.data);
+        encoded
+    }
+}
+
+/// An encoded receipt split into the chunks of the transactions that write it
+/// to a buffer account.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Partition {
+    /// The header to allocate the buffer with.
+    pub header: BufferHeader,
+
+    /// The chunks to write, in order.
+    pub chunks: Vec<Chunk>,
+}
+
+impl SolanaReceipt {
+    /// Encode a Groth16 seal, and the claim it proves, for a Solana program.
+    pub fn new(
+        seal: &Seal,
+        image_id: Digest,
+        post_state_digest: Digest,
+        journal: Vec<u8>,
+    ) -> Result<Self> {
+        let bytes = seal.to_vec();
+        ensure!(bytes.len() == SOLANA_SEAL_SIZE, "malformed Groth16 seal");
+        let a: G1Affine = -g1_from_bytes(&seal.a)?;
+        let mut encoded = [0u8; SOLANA_SEAL_SIZE];
+        encoded[..64].copy_from_slice(&g1_elems(&a).concat());
+        encoded[64..].copy_from_slice(&bytes[64..]);
+        Ok(Self {
+            seal: encoded,
+            image_id,
+            post_state_digest,
+            journal,
+        })
+    }
+
+    /// Encode this receipt, as read by a Solana program.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoded = Vec::with_capacity(HEADER_SIZE + self.journal.len());
+        encoded.extend_from_slice(&self.seal);
+        encoded.extend_from_slice(self.image_id.as_bytes());
+        encoded.extend_from_slice(self.post_state_digest.as_bytes());
+        encoded.extend_from_slice(&self.journal);
+        encoded
+    }
+
+    /// Decode a receipt encoded by [SolanaReceipt::encode].
+    pub fn decode(encoded: &[u8]) -> Result<Self> {
+        ensure!(
+            encoded.len() >= HEADER_SIZE,
+            "encoded receipt is shorter than its header"
+        );
+        let (header, journal) = encoded.split_at(HEADER_SIZE);
+        Ok(Self {
+            seal: header[..SOLANA_SEAL_SIZE].try_into().unwrap(),
+            image_id: Digest::try_from(&header[SOLANA_SEAL_SIZE..SOLANA_SEAL_SIZE + 32]).unwrap(),
+            post_state_digest: Digest::try_from(&header[SOLANA_SEAL_SIZE + 32..]).unwrap(),
+            journal: journal.to_vec(),
+        })
+    }
+
+    /// Digest of the claim proven by this receipt.
+    pub fn claim_digest(&self) -> Digest {
+        let output = tagged_struct(
+            "risc0.Output",
+            &[*Impl::hash_bytes(&self.journal), Digest::ZERO],
+            &[],
+        );
+        tagged_struct(
+            "risc0.ReceiptClaim",
+            &[Digest::ZERO, self.image_id, self.post_state_digest, output],
+            &[0, 0],
+        )
+    }
+
+    /// The public inputs of the Groth16 circuit for this receipt, as 32-byte
+    /// big-endian scalars, in the order the program passes them to the
+    /// pairing check.
+    pub fn public_inputs(&self, params: &VerifierParameters) -> Result<[[u8; 32]; 5]> {
+        let (a0, a1) = split_digest(params.control_root)?;
+        let (c0, c1) = split_digest(self.claim_digest())?;
+        Ok([a0, a1, c0, c1, params.bn254_control_id].map(|fr| fr_bytes(&fr)))
+    }
+
+    /// Split this receipt into the chunks of the transactions that write it to
+    /// a buffer account.
+    pub fn partition(&self, profile: &SolanaProfile) -> Result<Partition> {
+        ensure!(profile.max_chunk_len > 0, "chunks must not be empty");
+        ensure!(
+            self.journal.len() <= profile.max_journal_len,
+            "journal of {} bytes is longer than the limit of {} bytes",
+            self.journal.len(),
+            profile.max_journal_len
+        );
+        let encoded = self.encode();
+        let chunks = encoded
+            .chunks(profile.max_chunk_len)
+            .enumerate()
+            .map(|(i, data)| Chunk {
+                offset: (i * profile.max_chunk_len) as u32,
+                data: data.to_vec(),
+            })
+            .collect();
+        Ok(Partition {
+            header: BufferHeader {
+                len: encoded.len() as u32,
+                digest: *Impl::hash_bytes(&encoded),
+            },
+            chunks,
+        })
+    }
+}
+
+/// The verifying key of the Groth16 circuit, encoded for the `alt_bn128`
+/// syscalls, to embed in a Solana program.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SolanaVerifyingKey {
+    /// The point `alpha` in G1.
+    pub alpha_g1: [u8; 64],
+
+    /// The point `beta` in G2.
+    pub beta_g2: [u8; 128],
+
+    /// The point `gamma` in G2.
+    pub gamma_g2: [u8; 128],
+
+    /// The point `delta` in G2.
+    pub delta_g2: [u8; 128],
+
+    /// The points in G1 that the public inputs are multiplied with.
+    pub ic: Vec<[u8; 64]>,
+}
+
+impl SolanaVerifyingKey {
+    /// Encode the verifying key of the given parameters.
+    pub fn new(params: &VerifierParameters) -> Self {
+        let vk = &params.verifying_key;
+        let g1 = |point: &G1Affine| -> [u8; 64] { g1_elems(point).concat().try_into().unwrap() };
+        let g2 = |point: &G2Affine| -> [u8; 128] { g2_elems(point).concat().try_into().unwrap() };
+        Self {
+            alpha_g1: g1(&vk.alpha_g1),
+            beta_g2: g2(&vk.beta_g2),
+            gamma_g2: g2(&vk.gamma_g2),
+            delta_g2: g2(&vk.delta_g2),
+            ic: vk.gamma_abc_g1.iter().map(g1).collect(),
+        }
+    }
+}
+
+/// Reassemble an encoded receipt from the chunks written to its buffer, as a
+/// Solana program does before verifying it.
+///
+/// Chunks may arrive in any order, but must cover the buffer exactly once, and
+/// the result must match the digest in the header.
+pub fn reassemble(header: &BufferHeader, chunks: &[Chunk]) -> Result<Vec<u8>> {
+    let len = header.len as usize;
+    let mut buffer = alloc::vec![0u8; len];
+    let mut written = alloc::vec![false; len];
+    for chunk in chunks {
+        let start = chunk.offset as usize;
+        let Some(end) = start
+            .checked_add(chunk.data.len())
+            .filter(|&end| end <= len)
+        else {
+            bail!("chunk at offset {start} overruns the buffer of {len} bytes");
+        };
+        ensure!(
+            !written[start..end].iter().any(|&w| w),
+            "chunk at offset {start} overlaps another chunk"
+        );
+        buffer[start..end].copy_from_slice(&chunk.data);
+        written[start..end].fill(true);
+    }
+    ensure!(written.iter().all(|&w| w), "buffer is not fully written");
+    ensure!(
+        *Impl::hash_bytes(&buffer) == header.digest,
+        "buffer does not match the digest of the receipt"
+    );
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::G1Affine;
+    use ark_ec::AffineRepr;
+    use risc0_zkp::core::digest::Digest;
+
+    use super::{reassemble, SolanaProfile, SolanaReceipt, SolanaVerifyingKey};
+    use crate::{
+        solidity::{fq_bytes, g1_elems},
+        verifier::verifying_key,
+        Seal, VerifierParameters,
+    };
+
+    fn receipt(journal_len: usize) -> SolanaReceipt {
+        let [x, y] = g1_elems(&G1Affine::generator());
+        let mut bytes = [x, y].concat();
+        bytes.extend([5; 192]);
+        let seal = Seal::from_vec(&bytes).unwrap();
+        SolanaReceipt::new(
+            &seal,
+            Digest::from([1; 8]),
+            Digest::from([2; 8]),
+            (0..journal_len).map(|i| i as u8).collect(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn negates_a() {
+        let receipt = receipt(0);
+        let generator = G1Affine::generator();
+        assert_eq!(receipt.seal[..32], fq_bytes(&generator.x));
+        assert_eq!(receipt.seal[32..64], fq_bytes(&-generator.y));
+        assert_ne!(fq_bytes(&-generator.y), fq_bytes(&generator.y));
+        assert_eq!(receipt.seal[64..], [5; 192]);
+    }
+
+    #[test]
+    fn encode_round_trip() {
+        let receipt = receipt(100);
+        let encoded = receipt.encode();
+        assert_eq!(encoded.len(), 256 + 64 + 100);
+        assert_eq!(SolanaReceipt::decode(&encoded).unwrap(), receipt);
+        assert!(SolanaReceipt::decode(&encoded[..300]).is_err());
+    }
+
+    #[test]
+    fn partition() {
+        let receipt = receipt(2000);
+        let profile = SolanaProfile::default();
+        let partition = receipt.partition(&profile).unwrap();
+        assert_eq!(partition.chunks.len(), 3);
+        assert_eq!(partition.chunks[1].offset, 900);
+        assert_eq!(partition.chunks[1].encode()[..4], 900u32.to_le_bytes());
+        assert!(partition
+            .chunks
+            .iter()
+            .all(|chunk| chunk.data.len() <= profile.max_chunk_len));
+
+        let mut chunks = partition.chunks.clone();
+        chunks.reverse();
+        let encoded = reassemble(&partition.header, &chunks).unwrap();
+        assert_eq!(SolanaReceipt::decode(&encoded).unwrap(), receipt);
+
+        // Missing, repeated and altered chunks are rejected.
+        assert!(reassemble(&partition.header, &chunks[1..]).is_err());
+        chunks.push(chunks[0].clone());
+        assert!(reassemble(&partition.header, &chunks).is_err());
+        chunks.pop();
+        chunks[0].data[0] ^= 1;
+        assert!(reassemble(&partition.header, &chunks).is_err());
+
+        assert!(self::receipt(20_000).partition(&profile).is_err());
+    }
+
+    #[test]
+    fn verifying_key_encoding() {
+        let params = VerifierParameters {
+            control_root: Digest::ZERO,
+            bn254_control_id: crate::fr_from_hex_string("01").unwrap(),
+            verifying_key: verifying_key().unwrap(),
+        };
+        let vk = SolanaVerifyingKey::new(&params);
+        assert_eq!(vk.ic.len(), 6);
+        let inputs = receipt(0).public_inputs(&params).unwrap();
+        assert_eq!(inputs[4][31], 1);
+        assert_ne!(inputs[2], inputs[3]);
+    }
+}
@@ -0,0 +1,453 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generation of Solidity verifier contracts.
+//!
+//! A generated contract is bound to one set of [VerifierParameters]: the
+//! control root and BN254 control ID of the recursion circuit, and the
+//! verifying key of the Groth16 circuit. It accepts seals made by
+//! [VerifierParameters::encode_seal], which start with the selector of the
+//! parameters, and checks them against a receipt claim computed from an image
+//! ID, a post state digest and a journal digest.
+//!
+//! [VerifyCall] encodes a call to the `verify` function of a generated
+//! contract, as the calldata of a transaction or of an `eth_call`, and decodes
+//! it again.
+
+extern crate alloc;
+
+use alloc::{format, string::String, vec::Vec};
+
+use anyhow::{anyhow, bail, ensure, Result};
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::{prepare_verifying_key, PreparedVerifyingKey, VerifyingKey};
+use risc0_zkp::core::{
+    digest::Digest,
+    hash::sha::{cpu::Impl, Sha256},
+};
+use tiny_keccak::{Hasher, Keccak};
+
+use crate::{split_digest, Seal};
+
+const TEMPLATE: &str = include_str!("../solidity/Groth16Verifier.sol");
+
+// The public inputs of the circuit are the two halves of the control root,
+// the two halves of the claim digest, and the BN254 control ID.
+const NUM_PUBLIC_INPUTS: usize = 5;
+
+/// Signature of the `verify` function of a generated verifier contract, from
+/// which the function selector of [VerifyCall] is derived.
+pub const VERIFY_SIGNATURE: &str = "verify(bytes,bytes32,bytes32,bytes32)";
+
+// The head of the ABI encoding of a call to `verify`: the offset of the seal,
+// then the three digests.
+const VERIFY_HEAD_LEN: usize = 4 * 32;
+
+/// The parameters that a Groth16 receipt is verified against.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VerifierParameters {
+    /// Root of the Merkle tree of allowed recursion control IDs.
+    pub control_root: Digest,
+
+    /// Control ID of the recursion program whose seal is verified by the
+    /// Groth16 circuit.
+    pub bn254_control_id: Fr,
+
+    /// Verifying key of the Groth16 circuit.
+    pub verifying_key: VerifyingKey<Bn254>,
+}
+
+impl VerifierParameters {
+    /// Digest of the parameters, which identifies a verifier on-chain.
+    pub fn digest(&self) -> Digest {
+        let vk_digest = verifying_key_digest(&self.verifying_key);
+        let control_id = Digest::try_from(fr_bytes(&self.bn254_control_id).as_slice()).unwrap();
+
+        tagged_struct(
+            "risc0.Groth16ReceiptVerifierParameters",
+            &[self.control_root, control_id, vk_digest],
+            &[],
+        )
+    }
+
+    /// The verifying key, prepared for verifying proofs.
+    pub fn prepared_verifying_key(&self) -> PreparedVerifyingKey<Bn254> {
+        prepare_verifying_key(&self.verifying_key)
+    }
+
+    /// The first four bytes of the parameters digest, which prefix the seals
+    /// accepted by a verifier for these parameters.
+    pub fn selector(&self) -> [u8; 4] {
+        self.digest().as_bytes()[..4].try_into().unwrap()
+    }
+
+    /// Encode a Groth16 seal for a verifier contract for these parameters.
+    pub fn encode_seal(&self, seal: &Seal) -> Vec<u8> {
+        let mut encoded = self.selector().to_vec();
+        encoded.extend(seal.to_vec());
+        encoded
+    }
+
+    /// The call to the `verify` function of a verifier contract for these
+    /// parameters that checks `seal` against the claim of an execution of
+    /// `image_id` ending in the state with `post_state_digest` and committing
+    /// the journal with `journal_digest`.
+    pub fn verify_call(
+        &self,
+        seal: &Seal,
+        image_id: Digest,
+        post_state_digest: Digest,
+        journal_digest: Digest,
+    ) -> VerifyCall {
+        VerifyCall {
+            seal: self.encode_seal(seal),
+            image_id,
+            post_state_digest,
+            journal_digest,
+        }
+    }
+
+    /// Generate the source of a Solidity contract named `contract_name` that
+    /// verifies Groth16 receipts against these parameters.
+    pub fn solidity_verifier(&self, contract_name: &str) -> Result<String> {
+        let mut chars = contract_name.chars();
+        if !chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            bail!("invalid contract name: {contract_name}");
+        }
+        let vk = &self.verifying_key;
+        if vk.gamma_abc_g1.len() != NUM_PUBLIC_INPUTS + 1 {
+            bail!(
+                "verifying key has {} public inputs, expected {NUM_PUBLIC_INPUTS}",
+                vk.gamma_abc_g1.len().saturating_sub(1)
+            );
+        }
+        let (control_root_0, control_root_1) = split_digest(self.control_root)?;
+
+        let mut values: Vec<(String, String)> = Vec::new();
+        let mut set = |name: &str, value: String| values.push((format!("{{{name}}}"), value));
+        set("CONTRACT_NAME", contract_name.into());
+        set("SELECTOR", format!("0x{}", hex::encode(self.selector())));
+        set("PARAMETERS_DIGEST", format!("0x{}", self.digest()));
+        set("CONTROL_ROOT", format!("0x{}", self.control_root));
+        set(
+            "BN254_CONTROL_ID",
+            uint256(&fr_bytes(&self.bn254_control_id)),
+        );
+        set("CONTROL_ROOT_0", uint256(&fr_bytes(&control_root_0)));
+        set("CONTROL_ROOT_1", uint256(&fr_bytes(&control_root_1)));
+
+        let [x, y] = g1_elems(&vk.alpha_g1);
+        set("ALPHA_X", uint256(&x));
+        set("ALPHA_Y", uint256(&y));
+        for (name, point) in [
+            ("BETA", &vk.beta_g2),
+            ("GAMMA", &vk.gamma_g2),
+            ("DELTA", &vk.delta_g2),
+        ] {
+            let [x1, x2, y1, y2] = g2_elems(point);
+            set(&format!("{name}_X1"), uint256(&x1));
+            set(&format!("{name}_X2"), uint256(&x2));
+            set(&format!("{name}_Y1"), uint256(&y1));
+            set(&format!("{name}_Y2"), uint256(&y2));
+        }
+        for (i, point) in vk.gamma_abc_g1.iter().enumerate() {
+            let [x, y] = g1_elems(point);
+            set(&format!("IC{i}_X"), uint256(&x));
+            set(&format!("IC{i}_Y"), uint256(&y));
+        }
+
+        Ok(values
+            .iter()
+            .fold(TEMPLATE.into(), |source: String, (placeholder, value)| {
+                source.replace(placeholder, value)
+            }))
+    }
+}
+
+/// A call to the `verify` function of a generated verifier contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifyCall {
+    /// The seal, as encoded by [VerifierParameters::encode_seal].
+    pub seal: Vec<u8>,
+
+    /// Image ID of the guest.
+    pub image_id: Digest,
+
+    /// Digest of the system state at the end of the execution.
+    pub post_state_digest: Digest,
+
+    /// Digest of the journal committed by the guest.
+    pub journal_digest: Digest,
+}
+
+impl VerifyCall {
+    /// The function selector of `verify`, which prefixes its calldata.
+    pub fn selector() -> [u8; 4] {
+        let mut hash = [0u8; 32];
+        let mut keccak = Keccak::v256();
+        keccak.update(VERIFY_SIGNATURE.as_bytes());
+        keccak.finalize(&mut hash);
+        hash[..4].try_into().unwrap()
+    }
+
+    /// Encode this call as calldata: the function selector followed by the
+    /// ABI encoding of the arguments.
+    pub fn abi_encode(&self) -> Vec<u8> {
+        let padded_len = self.seal.len().next_multiple_of(32);
+        let mut calldata = Vec::with_capacity(4 + VERIFY_HEAD_LEN + 32 + padded_len);
+        calldata.extend_from_slice(&Self::selector());
+        calldata.extend_from_slice(&abi_uint(VERIFY_HEAD_LEN));
+        calldata.extend_from_slice(self.image_id.as_bytes());
+        calldata.extend_from_slice(self.post_state_digest.as_bytes());
+        calldata.extend_from_slice(self.journal_digest.as_bytes());
+        calldata.extend_from_slice(&abi_uint(self.seal.len()));
+        calldata.extend_from_slice(&self.seal);
+        calldata.resize(calldata.len() + padded_len - self.seal.len(), 0);
+        calldata
+    }
+
+    /// Decode the calldata of a call to `verify`, as made by
+    /// [VerifyCall::abi_encode].
+    ///
+    /// Only the canonical encoding is accepted, with the seal directly after
+    /// the head and padded with zeros.
+    pub fn abi_decode(calldata: &[u8]) -> Result<Self> {
+        let Some((selector, args)) = calldata.split_first_chunk::<4>() else {
+            bail!("calldata is too short for a function selector");
+        };
+        ensure!(
+            *selector == Self::selector(),
+            "calldata is not a call to {VERIFY_SIGNATURE}: selector 0x{}",
+            hex::encode(selector)
+        );
+        ensure!(
+            args.len() >= VERIFY_HEAD_LEN + 32,
+            "calldata is too short for the arguments of {VERIFY_SIGNATURE}"
+        );
+        let word = |i: usize| &args[i * 32..(i + 1) * 32];
+        ensure!(
+            read_abi_uint(word(0))? == VERIFY_HEAD_LEN,
+            "seal does not directly follow the head of the calldata"
+        );
+        let seal_len = read_abi_uint(word(4))?;
+        let tail = &args[VERIFY_HEAD_LEN + 32..];
+        ensure!(
+            tail.len() == seal_len.next_multiple_of(32),
+            "calldata has {} bytes after the seal length, expected {}",
+            tail.len(),
+            seal_len.next_multiple_of(32)
+        );
+        let (seal, padding) = tail.split_at(seal_len);
+        ensure!(
+            padding.iter().all(|&b| b == 0),
+            "seal is not padded with zeros"
+        );
+        Ok(Self {
+            seal: seal.to_vec(),
+            image_id: Digest::try_from(word(1)).unwrap(),
+            post_state_digest: Digest::try_from(word(2)).unwrap(),
+            journal_digest: Digest::try_from(word(3)).unwrap(),
+        })
+    }
+}
+
+/// Digest of a Groth16 verifying key, over the coordinates of its points in the
+/// order used by the Solidity verifier.
+///
+/// This identifies a verifying key independently of how it is encoded, and is
+/// the digest that [read_verifying_key](crate::verifier::read_verifying_key)
+/// checks a loaded key against.
+pub fn verifying_key_digest(vk: &VerifyingKey<Bn254>) -> Digest {
+    let mut vk_bytes = Vec::new();
+    for elem in g1_elems(&vk.alpha_g1)
+        .into_iter()
+        .chain(g2_elems(&vk.beta_g2))
+        .chain(g2_elems(&vk.gamma_g2))
+        .chain(g2_elems(&vk.delta_g2))
+        .chain(vk.gamma_abc_g1.iter().flat_map(g1_elems))
+    {
+        vk_bytes.extend_from_slice(&elem);
+    }
+    *Impl::hash_bytes(&vk_bytes)
+}
+
+// A length or offset as an ABI-encoded uint256.
+fn abi_uint(value: usize) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&(value as u64).to_be_bytes());
+    word
+}
+
+fn read_abi_uint(word: &[u8]) -> Result<usize> {
+    ensure!(
+        word[..24].iter().all(|&b| b == 0),
+        "ABI-encoded length is out of range"
+    );
+    u64::from_be_bytes(word[24..].try_into().unwrap())
+        .try_into()
+        .map_err(|_| anyhow!("ABI-encoded length is out of range"))
+}
+
+fn uint256(bytes: &[u8; 32]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+pub(crate) fn fr_bytes(fr: &Fr) -> [u8; 32] {
+    fr.into_bigint().to_bytes_be().try_into().unwrap()
+}
+
+pub(crate) fn fq_bytes(fq: &Fq) -> [u8; 32] {
+    fq.into_bigint().to_bytes_be().try_into().unwrap()
+}
+
+// The coordinates of a G1 point, in the order expected by the precompiles.
+pub(crate) fn g1_elems(point: &G1Affine) -> [[u8; 32]; 2] {
+    [fq_bytes(&point.x), fq_bytes(&point.y)]
+}
+
+// The coordinates of a G2 point, in the order expected by the precompiles,
+// which put the imaginary part of each coordinate first.
+pub(crate) fn g2_elems(point: &G2Affine) -> [[u8; 32]; 4] {
+    let fq2 = |c: &Fq2| [fq_bytes(&c.c1), fq_bytes(&c.c0)];
+    let [x1, x0] = fq2(&point.x);
+    let [y1, y0] = fq2(&point.y);
+    [x1, x0, y1, y0]
+}
+
+// The struct hashing routine of risc0-binfmt.
+pub(crate) fn tagged_struct(tag: &str, down: &[Digest], data: &[u32]) -> Digest {
+    let mut all = Vec::new();
+    all.extend_from_slice(Impl::hash_bytes(tag.as_bytes()).as_bytes());
+    for digest in down {
+        all.extend_from_slice(digest.as_bytes());
+    }
+    for word in data {
+        all.extend_from_slice(&word.to_le_bytes());
+    }
+    all.extend_from_slice(&(down.len() as u16).to_le_bytes());
+    *Impl::hash_bytes(&all)
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_binfmt::tagged_struct as binfmt_tagged_struct;
+    use risc0_zkp::core::{digest::Digest, hash::sha::cpu::Impl};
+
+    use super::{tagged_struct, VerifierParameters, VerifyCall};
+    use crate::{fr_from_hex_string, verifier::verifying_key, Seal};
+
+    fn params() -> VerifierParameters {
+        VerifierParameters {
+            control_root: Digest::from([1, 2, 3, 4, 5, 6, 7, 8]),
+            bn254_control_id: fr_from_hex_string(
+                "10ff834dbef62ccbba201ecd26a772e3036a075aacbaf47200679a11dcdcf10d",
+            )
+            .unwrap(),
+            verifying_key: verifying_key().unwrap(),
+        }
+    }
+
+    #[test]
+    fn tagged_struct_matches_binfmt() {
+        let down = [Digest::from([1; 8]), Digest::from([2; 8])];
+        assert_eq!(
+            tagged_struct("risc0.Test", &down, &[3, 4]),
+            binfmt_tagged_struct::<Impl>("risc0.Test", &down, &[3, 4])
+        );
+    }
+
+    #[test]
+    fn solidity_verifier() {
+        let params = params();
+        let source = params.solidity_verifier("TestVerifier").unwrap();
+        assert!(source.contains("contract TestVerifier {"));
+        assert!(source.contains(&format!(
+            "bytes4 public constant SELECTOR = 0x{};",
+            hex::encode(params.selector())
+        )));
+        assert!(source.contains(
+            "uint256 public constant BN254_CONTROL_ID = \
+             0x10ff834dbef62ccbba201ecd26a772e3036a075aacbaf47200679a11dcdcf10d;"
+        ));
+        assert!(!source.contains("{CONTRACT_NAME}") && !source.contains("{IC5_Y}"));
+
+        // Changing any parameter changes the selector.
+        let mut other = params.clone();
+        other.control_root = Digest::ZERO;
+        assert_ne!(other.selector(), params.selector());
+        let mut other = params.clone();
+        other.verifying_key.gamma_abc_g1.swap(1, 2);
+        assert_ne!(other.selector(), params.selector());
+    }
+
+    #[test]
+    fn rejects_invalid_parameters() {
+        let mut params = params();
+        assert!(params.solidity_verifier("1Verifier").is_err());
+        assert!(params.solidity_verifier("My Verifier").is_err());
+        params.verifying_key.gamma_abc_g1.pop();
+        assert!(params.solidity_verifier("Verifier").is_err());
+    }
+
+    #[test]
+    fn encode_seal() {
+        let seal = Seal::from_vec(&[7; 256]).unwrap();
+        let params = params();
+        let encoded = params.encode_seal(&seal);
+        assert_eq!(encoded[..4], params.selector());
+        assert_eq!(Seal::from_vec(&encoded[4..]).unwrap(), seal);
+    }
+
+    #[test]
+    fn verify_selector() {
+        // cast sig "verify(bytes,bytes32,bytes32,bytes32)"
+        assert_eq!(hex::encode(VerifyCall::selector()), "6efef009");
+    }
+
+    #[test]
+    fn verify_calldata() {
+        let seal = Seal::from_vec(&[7; 256]).unwrap();
+        let params = params();
+        let call = params.verify_call(
+            &seal,
+            Digest::from([1; 8]),
+            Digest::from([2; 8]),
+            Digest::from([3; 8]),
+        );
+        let calldata = call.abi_encode();
+
+        // Selector, head of four words, seal length, then the 260-byte seal
+        // padded to 288 bytes.
+        assert_eq!(calldata.len(), 4 + 5 * 32 + 288);
+        assert_eq!(calldata[..4], VerifyCall::selector());
+        assert_eq!(calldata[4 + 31], 0x80);
+        assert_eq!(calldata[4 + 32..4 + 64], *Digest::from([1; 8]).as_bytes());
+        assert_eq!(calldata[4 + 4 * 32 + 30..4 + 5 * 32], [1, 4]);
+        assert_eq!(calldata[4 + 5 * 32..][..4], params.selector());
+        assert_eq!(VerifyCall::abi_decode(&calldata).unwrap(), call);
+
+        let mut bad = calldata.clone();
+        bad[0] ^= 1;
+        assert!(VerifyCall::abi_decode(&bad).is_err());
+        let mut bad = calldata.clone();
+        *bad.last_mut().unwrap() = 1;
+        assert!(VerifyCall::abi_decode(&bad).is_err());
+        assert!(VerifyCall::abi_decode(&calldata[..calldata.len() - 32]).is_err());
+    }
+}
@@ -12,14 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use anyhow::{anyhow, Error, Result};
+use anyhow::{anyhow, ensure, Error, Result};
 use ark_bn254::{Bn254, Fr, G1Projective};
 use ark_groth16::{prepare_verifying_key, Groth16, PreparedVerifyingKey, Proof, VerifyingKey};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use risc0_zkp::core::digest::Digest;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    from_u256, g1_from_bytes, g2_from_bytes, ProofJson, PublicInputsJson, Seal, VerifyingKeyJson,
+    from_u256, g1_from_bytes, g2_from_bytes, solidity::verifying_key_digest, ProofJson,
+    PublicInputsJson, Seal, VerifyingKeyJson,
 };
 
 // Constants from: risc0-ethereum/contracts/src/groth16/Groth16Verifier.sol
@@ -147,6 +149,11 @@ impl Verifier {
 
 /// Computes the default prepared verifying key, used by Bonsai.
 pub fn prepared_verifying_key() -> Result<PreparedVerifyingKey<Bn254>, Error> {
+    Ok(prepare_verifying_key(&verifying_key()?))
+}
+
+/// Returns the default verifying key, used by Bonsai.
+pub fn verifying_key() -> Result<VerifyingKey<Bn254>, Error> {
     let alpha_g1 = g1_from_bytes(&[from_u256(ALPHA_X)?, from_u256(ALPHA_Y)?])?;
     let beta_g2 = g2_from_bytes(&[
         vec![from_u256(BETA_X1)?, from_u256(BETA_X2)?],
@@ -169,13 +176,58 @@ pub fn prepared_verifying_key() -> Result<PreparedVerifyingKey<Bn254>, Error> {
     let ic5 = g1_from_bytes(&[from_u256(IC5_X)?, from_u256(IC5_Y)?])?;
     let gamma_abc_g1 = vec![ic0, ic1, ic2, ic3, ic4, ic5];
 
-    let vk = VerifyingKey::<Bn254> {
+    Ok(VerifyingKey::<Bn254> {
         alpha_g1,
         beta_g2,
         gamma_g2,
         delta_g2,
         gamma_abc_g1,
-    };
+    })
+}
 
-    Ok(prepare_verifying_key(&vk))
+/// Check that a verifying key has the pinned digest, as computed by
+/// [verifying_key_digest].
+pub fn check_verifying_key(vk: &VerifyingKey<Bn254>, digest: &Digest) -> Result<()> {
+    let actual = verifying_key_digest(vk);
+    ensure!(
+        actual == *digest,
+        "verifying key digest {actual} does not match the pinned digest {digest}"
+    );
+    Ok(())
+}
+
+/// Load a verifying key exported by `snarkjs zkey export verificationkey`,
+/// checking it against a pinned digest.
+///
+/// Deployments that run their own ceremony, or use another version of the
+/// circuit, load their key with this instead of using [verifying_key].
+#[cfg(feature = "std")]
+pub fn read_verifying_key(
+    path: impl AsRef<std::path::Path>,
+    digest: &Digest,
+) -> Result<VerifyingKey<Bn254>> {
+    let contents = std::fs::read_to_string(path)?;
+    let vk: VerifyingKeyJson = serde_json::from_str(&contents)?;
+    let vk = vk.verifying_key()?;
+    check_verifying_key(&vk, digest)?;
+    Ok(vk)
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkp::core::digest::Digest;
+
+    use super::{check_verifying_key, verifying_key};
+    use crate::solidity::verifying_key_digest;
+
+    #[test]
+    fn pinned_verifying_key() {
+        let vk = verifying_key().unwrap();
+        check_verifying_key(&vk, &verifying_key_digest(&vk)).unwrap();
+        assert!(check_verifying_key(&vk, &Digest::ZERO).is_err());
+
+        let mut other = vk.clone();
+        other.gamma_abc_g1.swap(0, 1);
+        assert!(check_verifying_key(&other, &verifying_key_digest(&vk)).is_err());
+    }
 }
@@ -0,0 +1,361 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A Q32.32 fixed-point number type.
+
+use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use crate::kernel;
+
+// 2 / pi with 64 fractional bits.
+const FRAC_2_PI: u64 = 0xa2f9836e4e441529;
+
+/// A signed fixed-point number with 32 integer and 32 fractional bits.
+///
+/// A [Fixed] holds numbers in `[-2^31, 2^31)` with a resolution of `2^-32`.
+/// Arithmetic follows the integer operations on the underlying `i64`: it
+/// panics on overflow in debug builds, and wraps in release builds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed(i64);
+
+impl Fixed {
+    /// The number of fractional bits.
+    pub const FRAC_BITS: u32 = 32;
+
+    /// Zero.
+    pub const ZERO: Self = Self(0);
+
+    /// One.
+    pub const ONE: Self = Self(1 << Self::FRAC_BITS);
+
+    /// The largest representable value, `2^31 - 2^-32`.
+    pub const MAX: Self = Self(i64::MAX);
+
+    /// The smallest representable value, `-2^31`.
+    pub const MIN: Self = Self(i64::MIN);
+
+    /// Archimedes' constant, pi.
+    pub const PI: Self = Self(0x3243f6a89);
+
+    /// Euler's number, e.
+    pub const E: Self = Self(0x2b7e15163);
+
+    /// The natural logarithm of 2.
+    pub const LN_2: Self = Self(0xb17217f8);
+
+    /// Construct a [Fixed] from an integer.
+    pub const fn from_int(n: i32) -> Self {
+        Self((n as i64) << Self::FRAC_BITS)
+    }
+
+    /// Construct a [Fixed] from its raw representation, which is the value
+    /// multiplied by `2^32`.
+    pub const fn from_bits(bits: i64) -> Self {
+        Self(bits)
+    }
+
+    /// Return the raw representation, which is the value multiplied by `2^32`.
+    pub const fn to_bits(self) -> i64 {
+        self.0
+    }
+
+    /// Return the largest integer less than or equal to `self`.
+    pub const fn to_int(self) -> i32 {
+        (self.0 >> Self::FRAC_BITS) as i32
+    }
+
+    /// Return the absolute value of `self`.
+    pub const fn abs(self) -> Self {
+        Self(self.0.abs())
+    }
+
+    /// Compute `e^self`.
+    ///
+    /// The result saturates to [Fixed::MAX] when it is too large to
+    /// represent. Costs 20 multiplications.
+    pub fn exp(self) -> Self {
+        // e^x = 2^k e^(f ln 2), where k + f = x log2(e) and f is in [0, 1).
+        let y = self.0 as i128 * kernel::LOG2_E as i128;
+        let k = (y >> (Self::FRAC_BITS + kernel::FRAC_BITS)) as i64;
+        let f = ((y >> Self::FRAC_BITS) as i64) & (kernel::ONE - 1);
+        let mant = kernel::exp(kernel::mul(f, kernel::LN_2));
+
+        // mant is in [1, 2) with 62 fractional bits, and needs 32.
+        let shift = (kernel::FRAC_BITS - Self::FRAC_BITS) as i64 - k;
+        if shift < 0 {
+            Self::MAX
+        } else if shift == 0 {
+            Self(mant)
+        } else if shift < 64 {
+            Self(round_shift(mant as u64, shift as u32) as i64)
+        } else {
+            Self::ZERO
+        }
+    }
+
+    /// Compute the natural logarithm of `self`, or [None] if `self` is not
+    /// positive.
+    ///
+    /// Costs one 128-bit division and 14 multiplications.
+    pub fn ln(self) -> Option<Self> {
+        if self.0 <= 0 {
+            return None;
+        }
+        // self = m 2^e, where m is in [sqrt(2) / 2, sqrt(2)].
+        let high = 63 - self.0.leading_zeros();
+        let mut e = high as i64 - Self::FRAC_BITS as i64;
+        let mut m = self.0 << (kernel::FRAC_BITS - high);
+        if m > kernel::SQRT_2 {
+            m >>= 1;
+            e += 1;
+        }
+        let total = e as i128 * kernel::LN_2 as i128 + kernel::ln(m) as i128;
+        Some(Self(round_shift_signed(total)))
+    }
+
+    /// Compute the square root of `self`, rounded to the nearest representable
+    /// value, or [None] if `self` is negative.
+    ///
+    /// This uses no multiplications, and a loop of 48 shifts and subtractions.
+    pub fn sqrt(self) -> Option<Self> {
+        if self.0 < 0 {
+            return None;
+        }
+        let n = (self.0 as u128) << Self::FRAC_BITS;
+        let root = kernel::isqrt(n);
+        // root + 1/2 rounds up exactly when n > root^2 + root.
+        let root = if n - root * root > root {
+            root + 1
+        } else {
+            root
+        };
+        Some(Self(root as i64))
+    }
+
+    /// Compute the sine of `self`, in radians.
+    ///
+    /// Costs 12 multiplications.
+    pub fn sin(self) -> Self {
+        let (quadrant, r) = self.reduce();
+        Self(round_shift_signed(match quadrant {
+            0 => kernel::sin(r),
+            1 => kernel::cos(r),
+            2 => -kernel::sin(r),
+            _ => -kernel::cos(r),
+        } as i128))
+    }
+
+    /// Compute the cosine of `self`, in radians.
+    ///
+    /// Costs 12 multiplications.
+    pub fn cos(self) -> Self {
+        let (quadrant, r) = self.reduce();
+        Self(round_shift_signed(match quadrant {
+            0 => kernel::cos(r),
+            1 => -kernel::sin(r),
+            2 => -kernel::cos(r),
+            _ => kernel::sin(r),
+        } as i128))
+    }
+
+    // Write self as (k + f) pi / 2, where f is in [-1/2, 1/2), and return k
+    // mod 4 and f pi / 2 with 62 fractional bits.
+    fn reduce(self) -> (u32, i64) {
+        let y = self.0 as i128 * FRAC_2_PI as i128;
+        let mut k = (y >> (Self::FRAC_BITS + 64)) as i64;
+        let frac = (y >> Self::FRAC_BITS) as i64;
+        if frac < 0 {
+            // The fraction is at least 1/2, so round k up.
+            k += 1;
+        }
+        let r = kernel::mul(frac >> 2, kernel::FRAC_PI_2);
+        ((k & 3) as u32, r)
+    }
+}
+
+// Shift the 62-bit fraction `x` down to 32 bits, rounding to nearest.
+fn round_shift_signed(x: i128) -> i64 {
+    let shift = kernel::FRAC_BITS - Fixed::FRAC_BITS;
+    ((x + (1 << (shift - 1))) >> shift) as i64
+}
+
+fn round_shift(x: u64, shift: u32) -> u64 {
+    (x >> shift) + ((x >> (shift - 1)) & 1)
+}
+
+impl From<i32> for Fixed {
+    fn from(n: i32) -> Self {
+        Self::from_int(n)
+    }
+}
+
+impl Add for Fixed {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Fixed {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for Fixed {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Neg for Fixed {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(-self.0)
+    }
+}
+
+impl Mul for Fixed {
+    type Output = Self;
+
+    /// Multiply, truncating the result towards negative infinity.
+    fn mul(self, rhs: Self) -> Self {
+        Self(((self.0 as i128 * rhs.0 as i128) >> Self::FRAC_BITS) as i64)
+    }
+}
+
+impl MulAssign for Fixed {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl Div for Fixed {
+    type Output = Self;
+
+    /// Divide, truncating the result towards zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rhs` is zero.
+    fn div(self, rhs: Self) -> Self {
+        Self((((self.0 as i128) << Self::FRAC_BITS) / rhs.0 as i128) as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Fixed;
+
+    const TOLERANCE: f64 = 1.0 / (1u64 << 28) as f64;
+
+    fn to_f64(x: Fixed) -> f64 {
+        x.to_bits() as f64 / (1u64 << 32) as f64
+    }
+
+    fn from_f64(x: f64) -> Fixed {
+        Fixed::from_bits((x * (1u64 << 32) as f64) as i64)
+    }
+
+    fn samples() -> impl Iterator<Item = f64> {
+        (-2000..=2000).map(|i| i as f64 * 0.0173)
+    }
+
+    fn assert_close(actual: Fixed, expected: f64) {
+        let actual = to_f64(actual);
+        let tolerance = TOLERANCE * expected.abs().max(1.0);
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "{actual} != {expected}"
+        );
+    }
+
+    #[test]
+    fn constants() {
+        assert_close(Fixed::PI, core::f64::consts::PI);
+        assert_close(Fixed::E, core::f64::consts::E);
+        assert_close(Fixed::LN_2, core::f64::consts::LN_2);
+        assert_eq!(Fixed::from_int(-3).to_int(), -3);
+        assert_eq!(from_f64(-2.5).to_int(), -3);
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = from_f64(3.25);
+        let b = from_f64(-1.5);
+        assert_eq!(a + b, from_f64(1.75));
+        assert_eq!(a - b, from_f64(4.75));
+        assert_eq!(a * b, from_f64(-4.875));
+        assert_eq!(a / b, from_f64(3.25 / -1.5));
+        assert_eq!(-a, from_f64(-3.25));
+    }
+
+    #[test]
+    fn exp() {
+        for x in samples().filter(|x| *x < 21.0) {
+            assert_close(from_f64(x).exp(), x.exp());
+        }
+        assert_eq!(Fixed::ZERO.exp(), Fixed::ONE);
+        assert_eq!(Fixed::from_int(22).exp(), Fixed::MAX);
+        assert_eq!(Fixed::from_int(-30).exp(), Fixed::ZERO);
+    }
+
+    #[test]
+    fn ln() {
+        for x in samples().filter(|x| *x > 0.0) {
+            assert_close(from_f64(x).ln().unwrap(), x.ln());
+        }
+        assert_eq!(Fixed::ONE.ln(), Some(Fixed::ZERO));
+        assert_close(
+            Fixed::MAX.ln().unwrap(),
+            (i64::MAX as f64 / (1u64 << 32) as f64).ln(),
+        );
+        assert_close(
+            Fixed::from_bits(1).ln().unwrap(),
+            -32.0 * core::f64::consts::LN_2,
+        );
+        assert_eq!(Fixed::ZERO.ln(), None);
+        assert_eq!(Fixed::from_int(-1).ln(), None);
+    }
+
+    #[test]
+    fn sqrt() {
+        for x in samples().filter(|x| *x >= 0.0) {
+            assert_close(from_f64(x).sqrt().unwrap(), x.sqrt());
+        }
+        assert_eq!(Fixed::from_int(16).sqrt(), Some(Fixed::from_int(4)));
+        assert_eq!(Fixed::ZERO.sqrt(), Some(Fixed::ZERO));
+        assert_eq!(Fixed::from_int(-1).sqrt(), None);
+    }
+
+    #[test]
+    fn sin_cos() {
+        for x in samples().chain([1e3, -1e5, 2e9]) {
+            assert_close(from_f64(x).sin(), x.sin());
+            assert_close(from_f64(x).cos(), x.cos());
+        }
+        assert_eq!(Fixed::ZERO.sin(), Fixed::ZERO);
+        assert_eq!(Fixed::ZERO.cos(), Fixed::ONE);
+    }
+}
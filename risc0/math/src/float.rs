@@ -0,0 +1,355 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Elementary functions on `f32`, computed without floating point operations.
+//!
+//! Each function inspects the bits of its argument, so it does not rely on
+//! soft-float routines even for comparisons or NaN checks. Results are
+//! accurate to within one unit in the last place, and are almost always
+//! correctly rounded. [sqrt] is always correctly rounded. Results that would
+//! be subnormal are flushed to zero.
+
+use crate::kernel;
+
+const SIGN_MASK: u32 = 0x8000_0000;
+const INFINITY_BITS: u32 = 0x7f80_0000;
+
+// 2 / pi with 256 fractional bits, least significant word first.
+const FRAC_2_PI: [u64; 4] = [
+    0xfe5163abdebbc561,
+    0xdb6295993c439041,
+    0xfc2757d1f534ddc0,
+    0xa2f9836e4e441529,
+];
+
+// Split a finite, nonzero `x` into its sign and a mantissa and exponent with
+// |x| = mant 2^exp.
+fn decompose(x: f32) -> (bool, u32, i32) {
+    let bits = x.to_bits();
+    let neg = bits & SIGN_MASK != 0;
+    let biased = ((bits >> 23) & 0xff) as i32;
+    let frac = bits & 0x7f_ffff;
+    if biased == 0 {
+        (neg, frac, -149)
+    } else {
+        (neg, frac | (1 << 23), biased - 150)
+    }
+}
+
+// Round mag 2^exp to the nearest `f32`, with ties to even.
+fn to_f32(neg: bool, mag: u128, exp: i32) -> f32 {
+    let sign = if neg { SIGN_MASK } else { 0 };
+    if mag == 0 {
+        return f32::from_bits(sign);
+    }
+    let high = 127 - mag.leading_zeros() as i32;
+    let shift = high - 23;
+    let mut biased = high + exp + 127;
+    let mut mant = if shift > 0 {
+        let shift = shift as u32;
+        let mant = mag >> shift;
+        let rem = mag & ((1 << shift) - 1);
+        let half = 1 << (shift - 1);
+        if rem > half || (rem == half && mant & 1 == 1) {
+            mant + 1
+        } else {
+            mant
+        }
+    } else {
+        mag << -shift
+    };
+    if mant == 1 << 24 {
+        mant >>= 1;
+        biased += 1;
+    }
+    let mant = mant as u32;
+    if biased >= 0xff {
+        f32::from_bits(sign | INFINITY_BITS)
+    } else if biased <= 0 {
+        f32::from_bits(sign)
+    } else {
+        f32::from_bits(sign | (biased as u32) << 23 | (mant & 0x7f_ffff))
+    }
+}
+
+/// Compute `e^x`.
+///
+/// Costs 20 multiplications.
+pub fn exp(x: f32) -> f32 {
+    let bits = x.to_bits();
+    let abs = bits & !SIGN_MASK;
+    if abs > INFINITY_BITS {
+        return x;
+    }
+    // |x| >= 128 overflows or underflows, which also covers infinities.
+    if abs >= 0x4300_0000 {
+        return if bits & SIGN_MASK == 0 {
+            f32::INFINITY
+        } else {
+            0.0
+        };
+    }
+    if abs == 0 {
+        return 1.0;
+    }
+
+    // e^x = 2^k e^(f ln 2), where k + f = x log2(e) and f is in [0, 1).
+    let (neg, mant, exp2) = decompose(x);
+    let prod = mant as i128 * kernel::LOG2_E as i128;
+    let shift = -exp2 as u32;
+    let mag = if shift < 128 { prod >> shift } else { 0 };
+    let y = if neg { -mag } else { mag };
+    let k = (y >> kernel::FRAC_BITS) as i32;
+    let f = (y as i64) & (kernel::ONE - 1);
+    let mant = kernel::exp(kernel::mul(f, kernel::LN_2));
+    to_f32(false, mant as u128, k - kernel::FRAC_BITS as i32)
+}
+
+/// Compute the natural logarithm of `x`.
+///
+/// Returns negative infinity for zero, and NaN for negative numbers. Costs one
+/// 128-bit division and 14 multiplications.
+pub fn ln(x: f32) -> f32 {
+    let bits = x.to_bits();
+    let abs = bits & !SIGN_MASK;
+    if abs == 0 {
+        return f32::NEG_INFINITY;
+    }
+    if abs > INFINITY_BITS {
+        return x;
+    }
+    if bits & SIGN_MASK != 0 {
+        return f32::NAN;
+    }
+    if bits == INFINITY_BITS {
+        return x;
+    }
+
+    // x = m 2^e, where m is in [sqrt(2) / 2, sqrt(2)].
+    let (_, mant, exp2) = decompose(x);
+    let high = 31 - mant.leading_zeros();
+    let mut e = high as i32 + exp2;
+    let mut m = (mant as i64) << (kernel::FRAC_BITS - high);
+    if m > kernel::SQRT_2 {
+        m >>= 1;
+        e += 1;
+    }
+    let total = e as i128 * kernel::LN_2 as i128 + kernel::ln(m) as i128;
+    to_f32(total < 0, total.unsigned_abs(), -(kernel::FRAC_BITS as i32))
+}
+
+/// Compute the square root of `x`, correctly rounded.
+///
+/// Returns NaN for negative numbers other than negative zero. This uses no
+/// multiplications, and a loop of 32 shifts and subtractions.
+pub fn sqrt(x: f32) -> f32 {
+    let bits = x.to_bits();
+    let abs = bits & !SIGN_MASK;
+    if abs == 0 || abs > INFINITY_BITS {
+        return x;
+    }
+    if bits & SIGN_MASK != 0 {
+        return f32::NAN;
+    }
+    if bits == INFINITY_BITS {
+        return x;
+    }
+
+    // Normalize subnormals, and make the exponent even.
+    let (_, mut mant, mut exp2) = decompose(x);
+    let high = 31 - mant.leading_zeros();
+    if high < 23 {
+        mant <<= 23 - high;
+        exp2 -= (23 - high) as i32;
+    }
+    let mut mant = mant as u64;
+    if exp2 & 1 != 0 {
+        mant <<= 1;
+        exp2 -= 1;
+    }
+
+    // Widen the mantissa so that the root has more bits than needed, and
+    // append a sticky bit for an inexact root so that rounding is correct.
+    let n = mant << 38;
+    let root = kernel::isqrt(n as u128);
+    let inexact = root * root != n as u128;
+    to_f32(false, (root << 1) | inexact as u128, (exp2 - 38) / 2 - 1)
+}
+
+/// Compute the sine of `x`, in radians.
+///
+/// Returns NaN for infinities. Costs 15 multiplications.
+pub fn sin(x: f32) -> f32 {
+    let abs = x.to_bits() & !SIGN_MASK;
+    if abs >= INFINITY_BITS {
+        return f32::NAN;
+    }
+    // sin(x) rounds to x when |x| < 2^-12.
+    if abs < 0x3980_0000 {
+        return x;
+    }
+    let (neg, quadrant, r) = reduce(x);
+    let v = match quadrant {
+        0 => kernel::sin(r),
+        1 => kernel::cos(r),
+        2 => -kernel::sin(r),
+        _ => -kernel::cos(r),
+    };
+    to_f32(
+        neg != (v < 0),
+        v.unsigned_abs() as u128,
+        -(kernel::FRAC_BITS as i32),
+    )
+}
+
+/// Compute the cosine of `x`, in radians.
+///
+/// Returns NaN for infinities. Costs 15 multiplications.
+pub fn cos(x: f32) -> f32 {
+    let abs = x.to_bits() & !SIGN_MASK;
+    if abs >= INFINITY_BITS {
+        return f32::NAN;
+    }
+    // cos(x) rounds to 1 when |x| < 2^-12.
+    if abs < 0x3980_0000 {
+        return 1.0;
+    }
+    let (_, quadrant, r) = reduce(x);
+    let v = match quadrant {
+        0 => kernel::cos(r),
+        1 => -kernel::sin(r),
+        2 => -kernel::cos(r),
+        _ => kernel::sin(r),
+    };
+    to_f32(v < 0, v.unsigned_abs() as u128, -(kernel::FRAC_BITS as i32))
+}
+
+// Write |x| as (k + f) pi / 2, where f is in [-1/2, 1/2), and return the sign
+// of x, k mod 4, and f pi / 2 with 62 fractional bits.
+//
+// Large arguments need many bits of 2 / pi, since the leading bits of the
+// product only contribute multiples of 4 to k. The product of the mantissa
+// and the 256-bit table is exact, so the fraction is accurate for every
+// finite `f32`.
+fn reduce(x: f32) -> (bool, u32, i64) {
+    let (neg, mant, exp2) = decompose(x);
+
+    // prod = mant * FRAC_2_PI, which is |x| 2 / pi with 256 - exp2 fractional
+    // bits.
+    let mut prod = [0u64; 5];
+    let mut carry = 0u64;
+    for (out, word) in prod.iter_mut().zip(FRAC_2_PI) {
+        let wide = mant as u128 * word as u128 + carry as u128;
+        *out = wide as u64;
+        carry = (wide >> 64) as u64;
+    }
+    prod[4] = carry;
+
+    let point = (256 - exp2) as u32;
+    let mut k = bits_at(&prod, point) as u32;
+    let frac = bits_at(&prod, point - 64) as i64;
+    if frac < 0 {
+        // The fraction is at least 1/2, so round k up.
+        k += 1;
+    }
+    let r = kernel::mul(frac >> 2, kernel::FRAC_PI_2);
+    (neg, k & 3, r)
+}
+
+// Return the 64 bits of `words` starting at bit `lo`.
+fn bits_at(words: &[u64; 5], lo: u32) -> u64 {
+    let idx = (lo / 64) as usize;
+    let off = lo % 64;
+    let low = words.get(idx).copied().unwrap_or(0) >> off;
+    if off == 0 {
+        low
+    } else {
+        low | words.get(idx + 1).copied().unwrap_or(0) << (64 - off)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Check that `actual` is within one unit in the last place of `expected`.
+    fn assert_ulp(actual: f32, expected: f64, x: f32) {
+        let rounded = expected as f32;
+        let ulps = (actual.to_bits() as i64 - rounded.to_bits() as i64).abs();
+        assert!(ulps <= 1, "f({x}): {actual} != {expected}");
+    }
+
+    fn samples() -> impl Iterator<Item = f32> {
+        (0..20000u32)
+            .map(|i| f32::from_bits(0x3000_0000 + i * 0x1_0001))
+            .chain((0..20000).map(|i| (i as f32 - 10000.0) * 0.00731))
+            .flat_map(|x| [x, -x])
+    }
+
+    #[test]
+    fn exp() {
+        for x in samples().filter(|x| x.abs() < 87.0) {
+            assert_ulp(super::exp(x), (x as f64).exp(), x);
+        }
+        assert_eq!(super::exp(0.0), 1.0);
+        assert_eq!(super::exp(89.0), f32::INFINITY);
+        assert_eq!(super::exp(f32::INFINITY), f32::INFINITY);
+        assert_eq!(super::exp(f32::NEG_INFINITY), 0.0);
+        assert_eq!(super::exp(-1000.0), 0.0);
+        assert!(super::exp(f32::NAN).is_nan());
+    }
+
+    #[test]
+    fn ln() {
+        let subnormal = f32::from_bits(0x0000_1234);
+        for x in samples().filter(|x| *x > 0.0).chain([subnormal, f32::MAX]) {
+            assert_ulp(super::ln(x), (x as f64).ln(), x);
+        }
+        assert_eq!(super::ln(1.0), 0.0);
+        assert_eq!(super::ln(0.0), f32::NEG_INFINITY);
+        assert_eq!(super::ln(f32::INFINITY), f32::INFINITY);
+        assert!(super::ln(-1.0).is_nan());
+        assert!(super::ln(f32::NAN).is_nan());
+    }
+
+    #[test]
+    fn sqrt() {
+        let subnormal = f32::from_bits(0x0000_1234);
+        for x in samples().filter(|x| *x >= 0.0).chain([subnormal, f32::MAX]) {
+            assert_eq!(super::sqrt(x), x.sqrt(), "sqrt({x})");
+        }
+        assert_eq!(super::sqrt(-0.0).to_bits(), (-0.0f32).to_bits());
+        assert_eq!(super::sqrt(f32::INFINITY), f32::INFINITY);
+        assert!(super::sqrt(-1.0).is_nan());
+    }
+
+    #[test]
+    fn sin_cos() {
+        let pi = core::f32::consts::PI;
+        let large = [
+            1e6,
+            12345.678,
+            1e20,
+            3.0e38,
+            f32::MAX,
+            pi / 2.0,
+            pi,
+            100.0 * pi,
+        ];
+        for x in samples().chain(large) {
+            assert_ulp(super::sin(x), (x as f64).sin(), x);
+            assert_ulp(super::cos(x), (x as f64).cos(), x);
+        }
+        assert!(super::sin(f32::INFINITY).is_nan());
+        assert!(super::cos(f32::NAN).is_nan());
+    }
+}
@@ -0,0 +1,161 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Polynomial kernels shared by the fixed-point and floating-point functions.
+//!
+//! Kernels work on Q2.62 values: an `i64` with 62 fractional bits, which holds
+//! numbers in `[-2, 2)` to within `2^-62`. Each kernel takes an argument that
+//! has already been reduced to a small interval, where a short Taylor series
+//! is accurate to about `2^-60`.
+
+pub(crate) const FRAC_BITS: u32 = 62;
+
+pub(crate) const ONE: i64 = 1 << FRAC_BITS;
+
+pub(crate) const LN_2: i64 = 0x2c5c85fdf473de6b;
+
+pub(crate) const LOG2_E: i64 = 0x5c551d94ae0bf85e;
+
+pub(crate) const FRAC_PI_2: i64 = 0x6487ed5110b4611a;
+
+pub(crate) const SQRT_2: i64 = 0x5a827999fcef3242;
+
+// 1 / n! for n in 0..=18.
+const INV_FACTORIAL: [i64; 19] = [
+    0x4000000000000000,
+    0x4000000000000000,
+    0x2000000000000000,
+    0x0aaaaaaaaaaaaaab,
+    0x02aaaaaaaaaaaaab,
+    0x0088888888888889,
+    0x0016c16c16c16c17,
+    0x0003403403403403,
+    0x0000680680680680,
+    0x00000b8ef1d2ab64,
+    0x00000127e4fb778a,
+    0x0000001ae64567f5,
+    0x000000023ddb1dff,
+    0x000000002c248c27,
+    0x0000000003272e95,
+    0x000000000035cfe8,
+    0x0000000000035cfe,
+    0x00000000000032a6,
+    0x00000000000002d0,
+];
+
+// 1 / (2k + 1) for k in 0..12.
+const INV_ODD: [i64; 12] = [
+    0x4000000000000000,
+    0x1555555555555555,
+    0x0ccccccccccccccd,
+    0x0924924924924925,
+    0x071c71c71c71c71c,
+    0x05d1745d1745d174,
+    0x04ec4ec4ec4ec4ec,
+    0x0444444444444444,
+    0x03c3c3c3c3c3c3c4,
+    0x035e50d79435e50d,
+    0x030c30c30c30c30c,
+    0x02c8590b21642c86,
+];
+
+// (-1)^k / (2k + 1)! for k in 0..9.
+const SIN_COEFFS: [i64; 9] = [
+    0x4000000000000000,
+    -0x0aaaaaaaaaaaaaab,
+    0x0088888888888889,
+    -0x0003403403403403,
+    0x00000b8ef1d2ab64,
+    -0x0000001ae64567f5,
+    0x000000002c248c27,
+    -0x000000000035cfe8,
+    0x00000000000032a6,
+];
+
+// (-1)^k / (2k)! for k in 0..10.
+const COS_COEFFS: [i64; 10] = [
+    0x4000000000000000,
+    -0x2000000000000000,
+    0x02aaaaaaaaaaaaab,
+    -0x0016c16c16c16c17,
+    0x0000680680680680,
+    -0x00000127e4fb778a,
+    0x000000023ddb1dff,
+    -0x0000000003272e95,
+    0x0000000000035cfe,
+    -0x00000000000002d0,
+];
+
+/// Multiply two Q2.62 values.
+pub(crate) fn mul(a: i64, b: i64) -> i64 {
+    ((a as i128 * b as i128) >> FRAC_BITS) as i64
+}
+
+// Evaluate sum(coeffs[i] * x^i) by Horner's rule.
+fn horner(x: i64, coeffs: &[i64]) -> i64 {
+    let (last, rest) = coeffs.split_last().unwrap();
+    rest.iter()
+        .rev()
+        .fold(*last, |acc, &coeff| coeff + mul(x, acc))
+}
+
+/// `e^t` for `t` in `[0, ln 2)`, which is in `[1, 2)`.
+///
+/// Costs 18 multiplications.
+pub(crate) fn exp(t: i64) -> i64 {
+    horner(t, &INV_FACTORIAL)
+}
+
+/// `ln(m)` for `m` in `[sqrt(2) / 2, sqrt(2)]`.
+///
+/// This uses `ln(m) = 2 atanh((m - 1) / (m + 1))`, whose series converges
+/// quickly on this interval. Costs one 128-bit division and 13 multiplications.
+pub(crate) fn ln(m: i64) -> i64 {
+    let s = (((m - ONE) as i128) << FRAC_BITS) / (m as i128 + ONE as i128);
+    let s = s as i64;
+    2 * mul(s, horner(mul(s, s), &INV_ODD))
+}
+
+/// `sin(r)` for `r` in `[-pi/4, pi/4]`.
+///
+/// Costs 10 multiplications.
+pub(crate) fn sin(r: i64) -> i64 {
+    mul(r, horner(mul(r, r), &SIN_COEFFS))
+}
+
+/// `cos(r)` for `r` in `[-pi/4, pi/4]`.
+///
+/// Costs 10 multiplications.
+pub(crate) fn cos(r: i64) -> i64 {
+    horner(mul(r, r), &COS_COEFFS)
+}
+
+/// The integer square root of `n`, rounded down.
+///
+/// This computes one bit per iteration with shifts and subtractions only.
+pub(crate) fn isqrt(n: u128) -> u128 {
+    let mut rem = n;
+    let mut root = 0u128;
+    let mut bit = 1u128 << ((127 - n.leading_zeros().min(127)) & !1);
+    while bit != 0 {
+        if rem >= root + bit {
+            rem -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+    root
+}
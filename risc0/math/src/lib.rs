@@ -0,0 +1,56 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fixed-point and floating-point math for guests of the RISC Zero zkVM.
+//!
+//! The zkVM implements rv32im, which has no floating point unit. Floating
+//! point operations in a guest are compiled to calls into a soft-float
+//! library, where a single `f64` multiplication is dozens of instructions and
+//! functions such as `exp` or `sin` from `libm` cost thousands of cycles.
+//!
+//! This crate computes the common elementary functions with integer
+//! arithmetic only:
+//!
+//! * [fixed::Fixed] is a Q32.32 fixed-point number. Addition is a single
+//!   integer addition, and multiplication a widening integer multiplication
+//!   and a shift.
+//! * The functions in [float] take and return `f32`, but never perform a
+//!   floating point operation. They decompose their argument into its integer
+//!   fields, evaluate the same kernels as [fixed::Fixed], and round the result
+//!   back to an `f32`.
+//!
+//! Every transcendental function reduces its argument to a small interval and
+//! evaluates a polynomial there with 64-bit integer multiplications, so its
+//! cost does not depend on the argument. Each function documents its cost in
+//! multiplications, which is the dominant term.
+//!
+//! ```
+//! use risc0_math::{fixed::Fixed, float};
+//!
+//! let x = Fixed::from_int(9);
+//! assert_eq!(x.sqrt(), Some(Fixed::from_int(3)));
+//! assert_eq!(Fixed::ZERO.exp(), Fixed::ONE);
+//!
+//! assert_eq!(float::sqrt(2.25), 1.5);
+//! ```
+
+#![no_std]
+#![deny(missing_docs)]
+
+#[cfg(test)]
+extern crate std;
+
+pub mod fixed;
+pub mod float;
+mod kernel;
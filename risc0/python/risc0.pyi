@@ -0,0 +1,52 @@
+from typing import List, Optional, Union
+
+__version__: str
+
+class Risc0Error(Exception): ...
+class VerificationError(Risc0Error): ...
+
+class ExecutorEnv:
+    def __init__(self) -> None: ...
+    def write(self, data: bytes) -> "ExecutorEnv": ...
+    def write_words(self, words: List[int]) -> "ExecutorEnv": ...
+    def env_var(self, name: str, value: str) -> "ExecutorEnv": ...
+    def args(self, args: List[str]) -> "ExecutorEnv": ...
+    def segment_limit_po2(self, po2: int) -> "ExecutorEnv": ...
+    def session_limit(self, cycles: Optional[int]) -> "ExecutorEnv": ...
+    def add_assumption(self, receipt: "Receipt") -> "ExecutorEnv": ...
+
+class SessionInfo:
+    journal: bytes
+    exit_code: str
+    segments: int
+    cycles: int
+
+class Inspection:
+    kind: str
+    image_id: Optional[str]
+    exit_code: Optional[str]
+    claim_digest: Optional[str]
+    journal_digest: str
+    seal_size: int
+    hashfns: List[str]
+
+class Receipt:
+    @property
+    def journal(self) -> bytes: ...
+    def verify(self, image_id: Union[bytes, str]) -> None: ...
+    def inspect(self) -> Inspection: ...
+    def to_bytes(self) -> bytes: ...
+    @classmethod
+    def from_bytes(cls, data: bytes) -> "Receipt": ...
+
+def compute_image_id(elf: bytes) -> bytes: ...
+def execute(elf: bytes, env: Optional[ExecutorEnv] = None) -> SessionInfo: ...
+def prove(
+    elf: bytes,
+    env: Optional[ExecutorEnv] = None,
+    *,
+    receipt_kind: str = "composite",
+    prove_guest_errors: bool = False,
+) -> Receipt: ...
+def is_dev_mode() -> bool: ...
+def set_dev_mode(enabled: bool) -> None: ...
@@ -0,0 +1,377 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Python bindings for the host API of the zkVM, built into the `risc0` module
+//! with maturin.
+//!
+//! Guests are executed and proven with the GIL released, so other Python
+//! threads keep running while a proof is made. Provers and executors are
+//! chosen as by [risc0_zkvm::default_prover], so `RISC0_DEV_MODE` and the
+//! other environment variables of the zkVM apply.
+
+use std::fmt::Display;
+
+use hex::FromHex;
+use pyo3::{
+    create_exception,
+    exceptions::{PyException, PyValueError},
+    prelude::*,
+    types::{PyBytes, PyType},
+};
+use risc0_zkvm::{
+    default_executor, default_prover, inspect,
+    sha::{Digest, Digestible},
+    Receipt as InnerReceipt, ReceiptKind, VerifierContext,
+};
+
+create_exception!(
+    risc0,
+    Risc0Error,
+    PyException,
+    "An error from executing, proving or decoding."
+);
+create_exception!(
+    risc0,
+    VerificationError,
+    Risc0Error,
+    "A receipt that did not verify."
+);
+
+fn error(err: impl Display) -> PyErr {
+    Risc0Error::new_err(format!("{err:#}"))
+}
+
+// Read a digest from 32 bytes or from 64 hex digits.
+fn digest(value: &Bound<'_, PyAny>) -> PyResult<Digest> {
+    if let Ok(hex) = value.extract::<String>() {
+        return Digest::from_hex(hex.trim_start_matches("0x"))
+            .map_err(|err| PyValueError::new_err(format!("invalid digest: {err}")));
+    }
+    let bytes = value.downcast::<PyBytes>()?.as_bytes();
+    Digest::try_from(bytes).map_err(|_| PyValueError::new_err("a digest is 32 bytes"))
+}
+
+fn receipt_kind(kind: &str) -> PyResult<ReceiptKind> {
+    Ok(match kind {
+        "composite" => ReceiptKind::Composite,
+        "succinct" => ReceiptKind::Succinct,
+        "plonk" => ReceiptKind::Plonk,
+        "compact" => ReceiptKind::Compact,
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "unknown receipt kind {kind:?}, expected composite, succinct, plonk or compact"
+            )))
+        }
+    })
+}
+
+/// The environment a guest runs in: its stdin, environment variables and
+/// arguments, its limits, and the receipts it may verify.
+///
+/// Every method returns the environment, so that calls can be chained.
+#[pyclass(module = "risc0")]
+#[derive(Clone, Default)]
+struct ExecutorEnv {
+    stdin: Vec<u8>,
+    env_vars: Vec<(String, String)>,
+    args: Vec<String>,
+    segment_po2: Option<u32>,
+    session_limit: Option<u64>,
+    assumptions: Vec<InnerReceipt>,
+}
+
+#[pymethods]
+impl ExecutorEnv {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append bytes to the stdin of the guest.
+    fn write<'py>(mut slf: PyRefMut<'py, Self>, data: &[u8]) -> PyRefMut<'py, Self> {
+        slf.stdin.extend_from_slice(data);
+        slf
+    }
+
+    /// Append 32-bit words to the stdin of the guest, such as the encoding of
+    /// a value that the guest reads with `env::read`.
+    fn write_words(mut slf: PyRefMut<'_, Self>, words: Vec<u32>) -> PyRefMut<'_, Self> {
+        slf.stdin
+            .extend(words.iter().flat_map(|word| word.to_le_bytes()));
+        slf
+    }
+
+    /// Set an environment variable of the guest.
+    fn env_var(mut slf: PyRefMut<'_, Self>, name: String, value: String) -> PyRefMut<'_, Self> {
+        slf.env_vars.push((name, value));
+        slf
+    }
+
+    /// Append arguments to those of the guest.
+    fn args(mut slf: PyRefMut<'_, Self>, args: Vec<String>) -> PyRefMut<'_, Self> {
+        slf.args.extend(args);
+        slf
+    }
+
+    /// Set the size of segments, in powers of 2 cycles.
+    fn segment_limit_po2(mut slf: PyRefMut<'_, Self>, po2: u32) -> PyRefMut<'_, Self> {
+        slf.segment_po2 = Some(po2);
+        slf
+    }
+
+    /// Set the most cycles the guest may run for, or `None` for no limit.
+    fn session_limit(mut slf: PyRefMut<'_, Self>, cycles: Option<u64>) -> PyRefMut<'_, Self> {
+        slf.session_limit = cycles;
+        slf
+    }
+
+    /// Add a receipt for the guest to verify with `env::verify`.
+    fn add_assumption<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        receipt: PyRef<'_, Receipt>,
+    ) -> PyRefMut<'py, Self> {
+        slf.assumptions.push(receipt.0.clone());
+        slf
+    }
+}
+
+impl ExecutorEnv {
+    fn build(&self) -> risc0_zkvm::Result<risc0_zkvm::ExecutorEnv<'_>> {
+        let mut builder = risc0_zkvm::ExecutorEnv::builder();
+        builder
+            .stdin(self.stdin.as_slice())
+            .args(&self.args)
+            .session_limit(self.session_limit);
+        for (name, value) in &self.env_vars {
+            builder.env_var(name, value);
+        }
+        if let Some(po2) = self.segment_po2 {
+            builder.segment_limit_po2(po2);
+        }
+        for receipt in &self.assumptions {
+            builder.add_assumption(receipt.clone());
+        }
+        builder.build()
+    }
+}
+
+/// A summary of a session run by `execute`.
+#[pyclass(module = "risc0", get_all, frozen)]
+struct SessionInfo {
+    /// The journal the guest committed.
+    journal: Py<PyBytes>,
+    /// How the session ended, such as `Halted(0)`.
+    exit_code: String,
+    /// The number of segments the session was split into.
+    segments: usize,
+    /// The number of cycles the guest ran, without the overhead of
+    /// continuations and padding.
+    cycles: u64,
+}
+
+#[pymethods]
+impl SessionInfo {
+    fn __repr__(&self) -> String {
+        format!(
+            "SessionInfo(exit_code={}, segments={}, cycles={})",
+            self.exit_code, self.segments, self.cycles
+        )
+    }
+}
+
+/// A receipt, proving that a guest ran and committed its journal.
+#[pyclass(module = "risc0", frozen)]
+struct Receipt(InnerReceipt);
+
+#[pymethods]
+impl Receipt {
+    /// The journal the guest committed.
+    #[getter]
+    fn journal<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, &self.0.journal.bytes)
+    }
+
+    /// Verify that this receipt proves an execution of the guest with
+    /// `image_id`, given as 32 bytes or as hex, raising `VerificationError`
+    /// if it does not.
+    fn verify(&self, py: Python<'_>, image_id: &Bound<'_, PyAny>) -> PyResult<()> {
+        let image_id = digest(image_id)?;
+        py.allow_threads(|| self.0.verify(image_id))
+            .map_err(|err| VerificationError::new_err(err.to_string()))
+    }
+
+    /// Describe the kind, claim and seals of this receipt.
+    fn inspect(&self) -> Inspection {
+        let inspection = inspect(&self.0);
+        Inspection {
+            kind: inspection.kind.to_string(),
+            image_id: inspection
+                .claim
+                .as_ref()
+                .map(|claim| claim.pre.digest().to_string()),
+            exit_code: inspection
+                .claim
+                .as_ref()
+                .map(|claim| format!("{:?}", claim.exit_code)),
+            claim_digest: inspection
+                .claim
+                .as_ref()
+                .map(|claim| claim.digest().to_string()),
+            journal_digest: inspection.journal_digest.to_string(),
+            seal_size: inspection.seal_size(),
+            hashfns: inspection
+                .hashfns()
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            text: inspection.to_string(),
+        }
+    }
+
+    /// Encode this receipt with bincode, as `r0vm` and Bonsai do.
+    fn to_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let bytes = bincode::serialize(&self.0).map_err(error)?;
+        Ok(PyBytes::new_bound(py, &bytes))
+    }
+
+    /// Decode a receipt encoded by `to_bytes`.
+    #[classmethod]
+    fn from_bytes(_cls: &Bound<'_, PyType>, data: &[u8]) -> PyResult<Self> {
+        Ok(Self(bincode::deserialize(data).map_err(error)?))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Receipt(kind={:?})", inspect(&self.0).kind)
+    }
+}
+
+/// A description of a receipt, made by `Receipt.inspect`.
+///
+/// The claim fields are `None` for receipts whose claim cannot be extracted.
+#[pyclass(module = "risc0", get_all, frozen)]
+struct Inspection {
+    /// The kind of the receipt, such as `composite receipt`.
+    kind: String,
+    /// The image ID of the guest, in hex.
+    image_id: Option<String>,
+    /// How the session ended, such as `Halted(0)`.
+    exit_code: Option<String>,
+    /// The digest of the claim, in hex.
+    claim_digest: Option<String>,
+    /// The digest of the journal, in hex.
+    journal_digest: String,
+    /// The total size of the seals, in bytes.
+    seal_size: usize,
+    /// The hash functions, or proof systems, the seals are verified with.
+    hashfns: Vec<String>,
+    text: String,
+}
+
+#[pymethods]
+impl Inspection {
+    fn __str__(&self) -> String {
+        self.text.clone()
+    }
+}
+
+/// Compute the image ID of a guest ELF, as 32 bytes.
+#[pyfunction]
+fn compute_image_id<'py>(py: Python<'py>, elf: &[u8]) -> PyResult<Bound<'py, PyBytes>> {
+    let image_id = risc0_zkvm::compute_image_id(elf).map_err(error)?;
+    Ok(PyBytes::new_bound(py, image_id.as_bytes()))
+}
+
+/// Execute a guest ELF without proving it.
+#[pyfunction]
+#[pyo3(signature = (elf, env = None))]
+fn execute(py: Python<'_>, elf: &[u8], env: Option<ExecutorEnv>) -> PyResult<SessionInfo> {
+    let env = env.unwrap_or_default();
+    let session = py
+        .allow_threads(|| default_executor().execute(env.build()?, elf))
+        .map_err(error)?;
+    Ok(SessionInfo {
+        journal: PyBytes::new_bound(py, &session.journal.bytes).unbind(),
+        exit_code: format!("{:?}", session.exit_code),
+        segments: session.segments.len(),
+        cycles: session
+            .segments
+            .iter()
+            .map(|segment| segment.cycles as u64)
+            .sum(),
+    })
+}
+
+/// Execute and prove a guest ELF, returning its receipt.
+///
+/// `receipt_kind` is one of `composite`, `succinct`, `plonk` or `compact`.
+/// Sessions in which the guest exits with an error are only proven with
+/// `prove_guest_errors`.
+#[pyfunction]
+#[pyo3(signature = (elf, env = None, *, receipt_kind = "composite", prove_guest_errors = false))]
+fn prove(
+    py: Python<'_>,
+    elf: &[u8],
+    env: Option<ExecutorEnv>,
+    receipt_kind: &str,
+    prove_guest_errors: bool,
+) -> PyResult<Receipt> {
+    let env = env.unwrap_or_default();
+    let opts = risc0_zkvm::ProverOpts::default()
+        .with_receipt_kind(self::receipt_kind(receipt_kind)?)
+        .with_prove_guest_errors(prove_guest_errors);
+    let info = py
+        .allow_threads(|| {
+            default_prover().prove_with_ctx(env.build()?, &VerifierContext::default(), elf, &opts)
+        })
+        .map_err(error)?;
+    Ok(Receipt(info.receipt))
+}
+
+/// Whether dev mode is enabled, in which receipts are fake and proving is
+/// skipped.
+#[pyfunction]
+fn is_dev_mode() -> bool {
+    risc0_zkvm::is_dev_mode()
+}
+
+/// Enable or disable dev mode for this process, by setting `RISC0_DEV_MODE`.
+#[pyfunction]
+fn set_dev_mode(enabled: bool) {
+    match enabled {
+        true => std::env::set_var("RISC0_DEV_MODE", "1"),
+        false => std::env::remove_var("RISC0_DEV_MODE"),
+    }
+}
+
+/// The host API of the RISC Zero zkVM.
+#[pymodule]
+fn risc0(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("__version__", risc0_zkvm::VERSION)?;
+    m.add("Risc0Error", py.get_type_bound::<Risc0Error>())?;
+    m.add(
+        "VerificationError",
+        py.get_type_bound::<VerificationError>(),
+    )?;
+    m.add_class::<ExecutorEnv>()?;
+    m.add_class::<Inspection>()?;
+    m.add_class::<Receipt>()?;
+    m.add_class::<SessionInfo>()?;
+    m.add_function(wrap_pyfunction!(compute_image_id, m)?)?;
+    m.add_function(wrap_pyfunction!(execute, m)?)?;
+    m.add_function(wrap_pyfunction!(is_dev_mode, m)?)?;
+    m.add_function(wrap_pyfunction!(prove, m)?)?;
+    m.add_function(wrap_pyfunction!(set_dev_mode, m)?)?;
+    Ok(())
+}
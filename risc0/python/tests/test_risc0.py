@@ -0,0 +1,59 @@
+# Copyright 2024 RISC Zero, Inc.
+#
+# Licensed under the Apache License, Version 2.0 (the "License");
+# you may not use this file except in compliance with the License.
+# You may obtain a copy of the License at
+#
+#     http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing, software
+# distributed under the License is distributed on an "AS IS" BASIS,
+# WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+# See the License for the specific language governing permissions and
+# limitations under the License.
+
+import pytest
+
+import risc0
+
+
+def test_exceptions():
+    assert issubclass(risc0.VerificationError, risc0.Risc0Error)
+    assert issubclass(risc0.Risc0Error, Exception)
+
+
+def test_dev_mode():
+    risc0.set_dev_mode(True)
+    assert risc0.is_dev_mode()
+    risc0.set_dev_mode(False)
+    assert not risc0.is_dev_mode()
+
+
+def test_executor_env_chains():
+    env = risc0.ExecutorEnv()
+    assert (
+        env.write(b"abc")
+        .write_words([1, 2])
+        .env_var("NAME", "value")
+        .args(["guest", "-v"])
+        .segment_limit_po2(16)
+        .session_limit(None)
+        is env
+    )
+
+
+def test_invalid_elf():
+    with pytest.raises(risc0.Risc0Error, match="Elf parse error"):
+        risc0.compute_image_id(b"not an elf")
+    with pytest.raises(risc0.Risc0Error):
+        risc0.execute(b"not an elf", risc0.ExecutorEnv().write(b"input"))
+
+
+def test_invalid_receipt_kind():
+    with pytest.raises(ValueError, match="unknown receipt kind"):
+        risc0.prove(b"", receipt_kind="tiny")
+
+
+def test_invalid_receipt():
+    with pytest.raises(risc0.Risc0Error):
+        risc0.Receipt.from_bytes(b"\xff" * 16)
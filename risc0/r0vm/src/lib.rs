@@ -12,7 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{fs, io, path::PathBuf, rc::Rc};
+pub mod rpc;
+mod scheduler;
+mod service;
+mod worker;
+
+use std::{fs, io, net::SocketAddr, path::PathBuf, rc::Rc};
 
 use clap::{Args, Parser, ValueEnum};
 use risc0_zkvm::{
@@ -67,6 +72,12 @@ struct Cli {
     /// The receipt kind produced by the r0vm prover
     #[arg(long, value_enum, default_value_t = ReceiptKind::Composite)]
     receipt_kind: ReceiptKind,
+
+    #[command(flatten)]
+    service: service::ServiceArgs,
+
+    #[command(flatten)]
+    worker: worker::WorkerArgs,
 }
 
 #[derive(Args)]
@@ -82,6 +93,22 @@ struct Mode {
     /// The image to execute
     #[arg(long)]
     image: Option<PathBuf>,
+
+    /// Serve a REST API that executes and proves uploaded guests on this
+    /// address, such as `127.0.0.1:8080`.
+    #[arg(long)]
+    serve: Option<SocketAddr>,
+
+    /// Serve a JSON-RPC API that executes guests without proving them on this
+    /// address, such as `127.0.0.1:8545`.
+    #[arg(long)]
+    rpc: Option<SocketAddr>,
+
+    /// Prove jobs claimed from the queue at this URL, such as
+    /// `redis://127.0.0.1/jobs`, `nats://127.0.0.1/jobs` or the URL of a job
+    /// server polled over HTTP.
+    #[arg(long)]
+    worker: Option<String>,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
@@ -100,6 +127,8 @@ enum ReceiptKind {
     Succinct,
     #[value(name = "compact")]
     Compact,
+    #[value(name = "plonk")]
+    Plonk,
 }
 
 pub fn main() {
@@ -112,6 +141,27 @@ pub fn main() {
         run_server(port);
         return;
     }
+    if let Some(addr) = args.mode.serve {
+        let prover_opts = args.prover_opts();
+        service::serve(addr, args.service, prover_opts).unwrap();
+        return;
+    }
+    if let Some(addr) = args.mode.rpc {
+        let defaults = rpc::RpcLimits::default();
+        let limits = rpc::RpcLimits {
+            max_request_size: args.service.max_upload_size,
+            max_session_cycles: Some(args.service.max_session_cycles),
+            max_segment_po2: args.service.max_segment_po2,
+            ..defaults
+        };
+        rpc::RpcServer::new(limits).serve(addr).unwrap();
+        return;
+    }
+    if let Some(url) = &args.mode.worker {
+        let prover_opts = args.prover_opts();
+        worker::work(url, args.worker, args.service, prover_opts).unwrap();
+        return;
+    }
 
     let env = {
         let mut builder = ExecutorEnv::builder();
@@ -170,19 +220,23 @@ pub fn main() {
 
 impl Cli {
     fn get_prover(&self) -> Rc<dyn ProverServer> {
+        get_prover_server(&self.prover_opts()).unwrap()
+    }
+
+    fn prover_opts(&self) -> ProverOpts {
         let hashfn = match self.hashfn {
             HashFn::Sha256 => "sha-256",
             HashFn::Poseidon2 => "poseidon2",
         };
-        let opts = ProverOpts::default()
+        ProverOpts::default()
             .with_hashfn(hashfn.to_string())
             .with_prove_guest_errors(self.prove_guest_errors)
             .with_receipt_kind(match self.receipt_kind {
                 ReceiptKind::Composite => risc0_zkvm::ReceiptKind::Composite,
                 ReceiptKind::Succinct => risc0_zkvm::ReceiptKind::Succinct,
                 ReceiptKind::Compact => risc0_zkvm::ReceiptKind::Compact,
-            });
-        get_prover_server(&opts).unwrap()
+                ReceiptKind::Plonk => risc0_zkvm::ReceiptKind::Plonk,
+            })
     }
 }
 
@@ -0,0 +1,444 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A JSON-RPC 2.0 server that executes guests without proving them, for
+//! iterating on an application against the zkVM from any language.
+//!
+//! Requests are posted to `/` as JSON-RPC objects, or batches of them. Bytes
+//! are passed in hex. The methods are:
+//!
+//! - `load_image`: `{"elf": ...}`, returning `{"image_id": ...}`. Loaded
+//!   images are kept in memory, so that each execution need not send the ELF.
+//!   The server keeps at most [RpcLimits::max_images] of them, forgetting the
+//!   image loaded longest ago to make room for another.
+//! - `execute`: an [ExecuteParams], returning an [ExecuteResult] with the
+//!   journal, the output of the guest and the stats of its session.
+//!
+//! A guest that fails to execute, such as by exceeding its cycle limit,
+//! returns the error `-32000` with the message of the failure.
+//!
+//! The server is started with `r0vm --rpc <addr>`, or embedded with
+//! [RpcServer::handle], which answers requests over any transport.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use anyhow::Result;
+use hyper::{
+    body::HttpBody,
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use risc0_zkvm::{compute_image_id, ExecutorEnv, ExecutorImpl, NullSegmentRef};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::service::json_response;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const EXECUTION_FAILED: i64 = -32000;
+
+/// The limits of an [RpcServer].
+#[derive(Clone, Debug)]
+pub struct RpcLimits {
+    /// The largest request accepted, in bytes.
+    pub max_request_size: usize,
+
+    /// The most cycles an execution may run, or none for no limit.
+    pub max_session_cycles: Option<u64>,
+
+    /// The largest segment size an execution may use, in powers of 2 cycles.
+    pub max_segment_po2: Option<u32>,
+
+    /// The most images kept loaded at once.
+    pub max_images: usize,
+}
+
+impl Default for RpcLimits {
+    fn default() -> Self {
+        Self {
+            max_request_size: 64 << 20,
+            max_session_cycles: Some(1 << 30),
+            max_segment_po2: None,
+            max_images: 64,
+        }
+    }
+}
+
+/// The parameters of `execute`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExecuteParams {
+    /// The image ID of a loaded image, or none to pass the ELF.
+    pub image_id: Option<String>,
+
+    /// The ELF to execute, in hex, or none to execute a loaded image.
+    pub elf: Option<String>,
+
+    /// The stdin of the guest, in hex.
+    #[serde(default)]
+    pub input: String,
+
+    /// The arguments of the guest.
+    #[serde(default)]
+    pub args: Vec<String>,
+
+    /// The environment variables of the guest.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+
+    /// The most cycles to execute, within the limit of the server.
+    pub session_cycles: Option<u64>,
+
+    /// The segment size, within the limit of the server.
+    pub segment_po2: Option<u32>,
+}
+
+/// The result of `execute`.
+#[derive(Clone, Debug, Serialize)]
+pub struct ExecuteResult {
+    /// The image ID of the guest.
+    pub image_id: String,
+
+    /// The exit code of the guest, such as `Halted(0)`.
+    pub exit_code: String,
+
+    /// The journal, in hex.
+    pub journal: String,
+
+    /// What the guest wrote to stdout.
+    pub stdout: String,
+
+    /// What the guest wrote to stderr.
+    pub stderr: String,
+
+    /// The number of segments of the session.
+    pub segments: usize,
+
+    /// The po2 of each segment.
+    pub segment_po2s: Vec<usize>,
+
+    /// The cycles executed by the guest.
+    pub user_cycles: u64,
+
+    /// The cycles of the session, including paging and padding.
+    pub total_cycles: u64,
+
+    /// How long execution took, in milliseconds.
+    pub elapsed_ms: u64,
+}
+
+#[derive(Deserialize)]
+struct RpcRequest {
+    jsonrpc: String,
+    method: String,
+    #[serde(default)]
+    params: Value,
+    id: Option<Value>,
+}
+
+struct RpcError(i64, String);
+
+/// A server for the execute-only JSON-RPC API.
+pub struct RpcServer {
+    limits: RpcLimits,
+    images: Mutex<Images>,
+}
+
+// The loaded images, along with their IDs in the order they were loaded.
+#[derive(Default)]
+struct Images {
+    elfs: HashMap<String, Arc<Vec<u8>>>,
+    order: VecDeque<String>,
+}
+
+impl RpcServer {
+    /// Construct a server with the given limits.
+    pub fn new(limits: RpcLimits) -> Self {
+        Self {
+            limits,
+            images: Mutex::default(),
+        }
+    }
+
+    /// Answer a JSON-RPC request or batch, returning the JSON of the response,
+    /// or none if the request was only notifications.
+    ///
+    /// Executions run on the calling thread.
+    pub fn handle(&self, request: &[u8]) -> Option<Vec<u8>> {
+        let response = match serde_json::from_slice::<Value>(request) {
+            Err(err) => Some(error_response(Value::Null, PARSE_ERROR, err.to_string())),
+            Ok(Value::Array(batch)) if batch.is_empty() => Some(error_response(
+                Value::Null,
+                INVALID_REQUEST,
+                "empty batch".into(),
+            )),
+            Ok(Value::Array(batch)) => {
+                let responses: Vec<Value> =
+                    batch.into_iter().filter_map(|x| self.call(x)).collect();
+                (!responses.is_empty()).then_some(Value::Array(responses))
+            }
+            Ok(request) => self.call(request),
+        };
+        response.map(|response| serde_json::to_vec(&response).unwrap())
+    }
+
+    fn call(&self, request: Value) -> Option<Value> {
+        let request: RpcRequest = match serde_json::from_value(request) {
+            Ok(request) => request,
+            Err(err) => {
+                return Some(error_response(
+                    Value::Null,
+                    INVALID_REQUEST,
+                    err.to_string(),
+                ))
+            }
+        };
+        let result = match request.method.as_str() {
+            _ if request.jsonrpc != "2.0" => {
+                Err(RpcError(INVALID_REQUEST, "jsonrpc must be \"2.0\"".into()))
+            }
+            "load_image" => self.load_image(request.params),
+            "execute" => params(request.params).and_then(|params| {
+                let result = self.execute(params)?;
+                Ok(serde_json::to_value(result).unwrap())
+            }),
+            method => Err(RpcError(
+                METHOD_NOT_FOUND,
+                format!("no method named {method}"),
+            )),
+        };
+        // Notifications are answered with nothing, even if they fail.
+        let id = request.id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+            Err(RpcError(code, message)) => error_response(id, code, message),
+        })
+    }
+
+    fn load_image(&self, params: Value) -> Result<Value, RpcError> {
+        #[derive(Deserialize)]
+        struct LoadImageParams {
+            elf: String,
+        }
+        let LoadImageParams { elf } = self::params(params)?;
+        let elf = decode_hex("elf", &elf)?;
+        let image_id = compute_image_id(&elf)
+            .map_err(|err| RpcError(INVALID_PARAMS, format!("{err:#}")))?
+            .to_string();
+        let mut images = self.images.lock().unwrap();
+        if images
+            .elfs
+            .insert(image_id.clone(), Arc::new(elf))
+            .is_some()
+        {
+            images.order.retain(|id| *id != image_id);
+        }
+        images.order.push_back(image_id.clone());
+        while images.order.len() > self.limits.max_images {
+            let id = images.order.pop_front().unwrap();
+            images.elfs.remove(&id);
+        }
+        Ok(json!({ "image_id": image_id }))
+    }
+
+    fn execute(&self, params: ExecuteParams) -> Result<ExecuteResult, RpcError> {
+        let elf = match (&params.image_id, &params.elf) {
+            (Some(image_id), None) => self
+                .images
+                .lock()
+                .unwrap()
+                .elfs
+                .get(image_id)
+                .cloned()
+                .ok_or_else(|| RpcError(INVALID_PARAMS, format!("no image with ID {image_id}")))?,
+            (None, Some(elf)) => Arc::new(decode_hex("elf", elf)?),
+            _ => {
+                return Err(RpcError(
+                    INVALID_PARAMS,
+                    "exactly one of image_id and elf must be given".into(),
+                ))
+            }
+        };
+        let input = decode_hex("input", &params.input)?;
+        let session_cycles = checked(
+            "session_cycles",
+            params.session_cycles,
+            self.limits.max_session_cycles,
+        )?;
+        let segment_po2 = checked(
+            "segment_po2",
+            params.segment_po2,
+            self.limits.max_segment_po2,
+        )?;
+
+        let start = Instant::now();
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let mut segment_po2s = Vec::new();
+        let session = (|| {
+            let env = {
+                let mut builder = ExecutorEnv::builder();
+                builder
+                    .stdin(input.as_slice())
+                    .stdout(&mut stdout)
+                    .stderr(&mut stderr)
+                    .args(&params.args)
+                    .env_vars(params.env.clone())
+                    .session_limit(session_cycles);
+                if let Some(po2) = segment_po2 {
+                    builder.segment_limit_po2(po2);
+                }
+                builder.build()?
+            };
+            // Segments are counted and dropped, since they are not proven.
+            ExecutorImpl::from_elf(env, &elf)?.run_with_callback(|segment| {
+                segment_po2s.push(segment.po2());
+                Ok(Box::new(NullSegmentRef))
+            })
+        })()
+        .map_err(|err| RpcError(EXECUTION_FAILED, format!("{err:#}")))?;
+
+        Ok(ExecuteResult {
+            image_id: compute_image_id(&elf)
+                .map_err(|err| RpcError(INVALID_PARAMS, format!("{err:#}")))?
+                .to_string(),
+            exit_code: format!("{:?}", session.exit_code),
+            journal: session
+                .journal
+                .as_ref()
+                .map_or(String::new(), |journal| hex::encode(&journal.bytes)),
+            stdout: String::from_utf8_lossy(&stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&stderr).into_owned(),
+            segments: session.segments.len(),
+            segment_po2s,
+            user_cycles: session.user_cycles,
+            total_cycles: session.total_cycles,
+            elapsed_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Serve the API over HTTP on `addr` until the process is stopped.
+    pub fn serve(self, addr: SocketAddr) -> Result<()> {
+        let server = Arc::new(self);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        runtime.block_on(async move {
+            let make_service = make_service_fn(move |_| {
+                let server = server.clone();
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |req| {
+                        let server = server.clone();
+                        async move { Ok::<_, Infallible>(server.handle_http(req).await) }
+                    }))
+                }
+            });
+            let server = Server::try_bind(&addr)?.serve(make_service);
+            tracing::info!(
+                "r0vm JSON-RPC server listening on http://{}",
+                server.local_addr()
+            );
+            server.await?;
+            Ok::<_, anyhow::Error>(())
+        })
+    }
+
+    async fn handle_http(self: Arc<Self>, req: Request<Body>) -> Response<Body> {
+        if req.method() != Method::POST || req.uri().path() != "/" {
+            return json_response(
+                StatusCode::NOT_FOUND,
+                &json!({ "error": "JSON-RPC requests are posted to /" }),
+            );
+        }
+        let mut body = req.into_body();
+        let mut request = Vec::new();
+        while let Some(chunk) = body.data().await {
+            let Ok(chunk) = chunk else {
+                return json_response(
+                    StatusCode::BAD_REQUEST,
+                    &json!({ "error": "failed to read request" }),
+                );
+            };
+            if request.len() + chunk.len() > self.limits.max_request_size {
+                return json_response(
+                    StatusCode::PAYLOAD_TOO_LARGE,
+                    &json!({ "error": format!(
+                        "requests are limited to {} bytes",
+                        self.limits.max_request_size
+                    ) }),
+                );
+            }
+            request.extend_from_slice(&chunk);
+        }
+        // Executing a guest takes long enough to hold up other requests.
+        let response = tokio::task::spawn_blocking(move || self.handle(&request)).await;
+        match response {
+            Ok(Some(response)) => Response::builder()
+                .header(CONTENT_TYPE, "application/json")
+                .body(response.into())
+                .unwrap(),
+            Ok(None) => Response::builder()
+                .status(StatusCode::NO_CONTENT)
+                .body(Body::empty())
+                .unwrap(),
+            Err(err) => json_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                &json!({ "error": err.to_string() }),
+            ),
+        }
+    }
+}
+
+fn params<T: for<'de> Deserialize<'de>>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|err| RpcError(INVALID_PARAMS, err.to_string()))
+}
+
+fn decode_hex(name: &str, value: &str) -> Result<Vec<u8>, RpcError> {
+    hex::decode(value.trim_start_matches("0x"))
+        .map_err(|err| RpcError(INVALID_PARAMS, format!("invalid hex for {name}: {err}")))
+}
+
+// The value of a limit requested by a client, or the limit of the server if
+// the client requested none.
+fn checked<T: PartialOrd + std::fmt::Display + Copy>(
+    name: &str,
+    requested: Option<T>,
+    max: Option<T>,
+) -> Result<Option<T>, RpcError> {
+    match (requested, max) {
+        (Some(requested), Some(max)) if requested > max => Err(RpcError(
+            INVALID_PARAMS,
+            format!("{name} is limited to {max}"),
+        )),
+        (requested, max) => Ok(requested.or(max)),
+    }
+}
+
+fn error_response(id: Value, code: i64, message: String) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "error": { "code": code, "message": message },
+        "id": id,
+    })
+}
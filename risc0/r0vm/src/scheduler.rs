@@ -0,0 +1,340 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The order in which the jobs of the REST service run, shared fairly among
+//! the tenants that submit them.
+//!
+//! Each job belongs to a tenant and has a [Priority]. The next job to run is
+//! one of the highest priority waiting, taken from the tenant with the fewest
+//! jobs running, and otherwise in the order the jobs were submitted. A
+//! [TenantQuota] limits how many jobs of a tenant run and wait at once, how
+//! many it may submit each minute, and the highest priority it may ask for.
+//!
+//! A proving job that is running when a job of a higher priority is waiting
+//! for a worker is preempted at its next segment boundary, and waits again
+//! with the place in the queue it had when it was submitted.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context as _, Result};
+use serde::Deserialize;
+
+/// The priority class of a job.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+/// The limits of one tenant. Limits that are not given are those of the
+/// command line.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct TenantQuota {
+    /// The most jobs of the tenant that run at once.
+    pub(crate) max_running_jobs: Option<usize>,
+
+    /// The most jobs of the tenant that wait for a worker at once.
+    pub(crate) max_queued_jobs: Option<usize>,
+
+    /// The most jobs the tenant may submit in a minute.
+    pub(crate) jobs_per_minute: Option<usize>,
+
+    /// The highest priority the jobs of the tenant may have.
+    pub(crate) max_priority: Option<Priority>,
+}
+
+impl TenantQuota {
+    /// Read the quotas of each tenant from a JSON object of tenant IDs to
+    /// quotas.
+    pub(crate) fn load(path: &Path) -> Result<HashMap<String, TenantQuota>> {
+        let contents = fs::read(path)
+            .with_context(|| format!("failed to read tenant quotas from {}", path.display()))?;
+        serde_json::from_slice(&contents)
+            .with_context(|| format!("invalid tenant quotas in {}", path.display()))
+    }
+
+    fn or(&self, default: &TenantQuota) -> TenantQuota {
+        TenantQuota {
+            max_running_jobs: self.max_running_jobs.or(default.max_running_jobs),
+            max_queued_jobs: self.max_queued_jobs.or(default.max_queued_jobs),
+            jobs_per_minute: self.jobs_per_minute.or(default.jobs_per_minute),
+            max_priority: self.max_priority.or(default.max_priority),
+        }
+    }
+}
+
+/// Why a job was not admitted to the queue.
+#[derive(Debug)]
+pub(crate) enum Rejection {
+    /// The job asked for a priority above the quota of its tenant.
+    Priority(Priority),
+
+    /// The tenant is over its quota.
+    Quota(String),
+}
+
+/// A job waiting for a worker.
+#[derive(Clone, Debug)]
+pub(crate) struct Entry {
+    pub(crate) job_id: String,
+    pub(crate) tenant: String,
+    pub(crate) priority: Priority,
+    seq: u64,
+}
+
+#[derive(Default)]
+struct Tenant {
+    running: usize,
+    queued: usize,
+    // When each job of the last minute was submitted, oldest first.
+    submitted: VecDeque<Instant>,
+}
+
+/// The queue of the jobs waiting for a worker, and the jobs of each tenant.
+#[derive(Default)]
+pub(crate) struct Scheduler {
+    default_quota: TenantQuota,
+    quotas: HashMap<String, TenantQuota>,
+    tenants: HashMap<String, Tenant>,
+    queue: Vec<Entry>,
+    next_seq: u64,
+}
+
+impl Scheduler {
+    /// Construct a scheduler with a quota for each tenant in `quotas`, and
+    /// `default_quota` for the rest.
+    pub(crate) fn new(default_quota: TenantQuota, quotas: HashMap<String, TenantQuota>) -> Self {
+        Self {
+            default_quota,
+            quotas,
+            ..Default::default()
+        }
+    }
+
+    /// The number of jobs waiting for a worker.
+    pub(crate) fn queued_jobs(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Check a job against the quota of its tenant, and queue it.
+    pub(crate) fn submit(
+        &mut self,
+        job_id: &str,
+        tenant: &str,
+        priority: Priority,
+    ) -> Result<(), Rejection> {
+        let quota = self.quota(tenant);
+        if priority > quota.max_priority.unwrap_or(Priority::High) {
+            return Err(Rejection::Priority(priority));
+        }
+
+        let now = Instant::now();
+        let state = self.tenants.entry(tenant.to_string()).or_default();
+        while let Some(submitted) = state.submitted.front() {
+            if now.duration_since(*submitted) < Duration::from_secs(60) {
+                break;
+            }
+            state.submitted.pop_front();
+        }
+        if let Some(max) = quota.max_queued_jobs {
+            if state.queued >= max {
+                return Err(Rejection::Quota(format!(
+                    "tenant {tenant} is limited to {max} queued jobs"
+                )));
+            }
+        }
+        if let Some(max) = quota.jobs_per_minute {
+            if state.submitted.len() >= max {
+                return Err(Rejection::Quota(format!(
+                    "tenant {tenant} is limited to {max} jobs a minute"
+                )));
+            }
+        }
+        state.submitted.push_back(now);
+
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.push(Entry {
+            job_id: job_id.to_string(),
+            tenant: tenant.to_string(),
+            priority,
+            seq,
+        });
+        Ok(())
+    }
+
+    /// Take the next job to run, if any job may run.
+    pub(crate) fn pop(&mut self) -> Option<Entry> {
+        let idx = self
+            .queue
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| self.may_run(&entry.tenant))
+            .max_by_key(|(_, entry)| {
+                let running = self.tenants[&entry.tenant].running;
+                (
+                    entry.priority,
+                    std::cmp::Reverse(running),
+                    std::cmp::Reverse(entry.seq),
+                )
+            })?
+            .0;
+        let entry = self.queue.remove(idx);
+        let tenant = self.tenants.get_mut(&entry.tenant).unwrap();
+        tenant.queued -= 1;
+        tenant.running += 1;
+        Some(entry)
+    }
+
+    /// Record that a job taken with [Scheduler::pop] has stopped running.
+    pub(crate) fn finish(&mut self, entry: &Entry) {
+        self.tenants.get_mut(&entry.tenant).unwrap().running -= 1;
+    }
+
+    /// Stop a job taken with [Scheduler::pop], and queue it again in its
+    /// place.
+    pub(crate) fn preempt(&mut self, entry: Entry) {
+        self.finish(&entry);
+        self.push(entry);
+    }
+
+    /// Returns true if a job of a higher priority than `entry` is waiting, and
+    /// could run in its place.
+    pub(crate) fn should_preempt(&self, entry: &Entry) -> bool {
+        self.queue.iter().any(|waiting| {
+            waiting.priority > entry.priority
+                && (waiting.tenant == entry.tenant || self.may_run(&waiting.tenant))
+        })
+    }
+
+    fn push(&mut self, entry: Entry) {
+        self.tenants.entry(entry.tenant.clone()).or_default().queued += 1;
+        self.queue.push(entry);
+    }
+
+    fn may_run(&self, tenant: &str) -> bool {
+        let running = self.tenants.get(tenant).map_or(0, |state| state.running);
+        self.quota(tenant)
+            .max_running_jobs
+            .map_or(true, |max| running < max)
+    }
+
+    fn quota(&self, tenant: &str) -> TenantQuota {
+        self.quotas.get(tenant).map_or_else(
+            || self.default_quota.clone(),
+            |quota| quota.or(&self.default_quota),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{Priority, Rejection, Scheduler, TenantQuota};
+
+    #[test]
+    fn fair_order() {
+        let quota = TenantQuota {
+            max_running_jobs: Some(1),
+            ..Default::default()
+        };
+        let mut scheduler = Scheduler::new(quota, HashMap::new());
+        for (job_id, tenant, priority) in [
+            ("a1", "a", Priority::Normal),
+            ("a2", "a", Priority::Normal),
+            ("b1", "b", Priority::Normal),
+            ("a3", "a", Priority::High),
+        ] {
+            scheduler.submit(job_id, tenant, priority).unwrap();
+        }
+
+        // The high priority job runs first, after which tenant a may run no
+        // more jobs until it finishes.
+        let a3 = scheduler.pop().unwrap();
+        assert_eq!(a3.job_id, "a3");
+        let b1 = scheduler.pop().unwrap();
+        assert_eq!(b1.job_id, "b1");
+        assert!(scheduler.pop().is_none());
+
+        scheduler.finish(&a3);
+        assert_eq!(scheduler.pop().unwrap().job_id, "a1");
+    }
+
+    #[test]
+    fn preemption() {
+        let mut scheduler = Scheduler::default();
+        scheduler.submit("low", "a", Priority::Low).unwrap();
+        scheduler.submit("normal", "a", Priority::Normal).unwrap();
+        let normal = scheduler.pop().unwrap();
+        let low = scheduler.pop().unwrap();
+        assert!(!scheduler.should_preempt(&normal));
+
+        scheduler.submit("high", "b", Priority::High).unwrap();
+        assert!(scheduler.should_preempt(&normal));
+        assert!(scheduler.should_preempt(&low));
+
+        // A preempted job keeps its place ahead of later jobs of its class.
+        scheduler.preempt(low);
+        scheduler.submit("low2", "a", Priority::Low).unwrap();
+        assert_eq!(scheduler.pop().unwrap().job_id, "high");
+        assert_eq!(scheduler.pop().unwrap().job_id, "low");
+    }
+
+    #[test]
+    fn quotas() {
+        let quotas = HashMap::from([(
+            "batch".to_string(),
+            TenantQuota {
+                max_priority: Some(Priority::Low),
+                ..Default::default()
+            },
+        )]);
+        let quota = TenantQuota {
+            max_queued_jobs: Some(2),
+            jobs_per_minute: Some(3),
+            ..Default::default()
+        };
+        let mut scheduler = Scheduler::new(quota, quotas);
+
+        assert!(matches!(
+            scheduler.submit("1", "batch", Priority::Normal),
+            Err(Rejection::Priority(Priority::Normal))
+        ));
+        scheduler.submit("2", "batch", Priority::Low).unwrap();
+
+        scheduler.submit("3", "a", Priority::Normal).unwrap();
+        scheduler.submit("4", "a", Priority::Normal).unwrap();
+        assert!(matches!(
+            scheduler.submit("5", "a", Priority::Normal),
+            Err(Rejection::Quota(_))
+        ));
+        scheduler.pop().unwrap();
+        scheduler.submit("6", "a", Priority::Normal).unwrap();
+        scheduler.pop().unwrap();
+        let Err(Rejection::Quota(error)) = scheduler.submit("7", "a", Priority::Normal) else {
+            panic!("expected tenant a to be rate limited");
+        };
+        assert_eq!(error, "tenant a is limited to 3 jobs a minute");
+    }
+}
@@ -0,0 +1,707 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A REST service that executes and proves guests for remote clients.
+//!
+//! Clients upload an ELF and the input of the guest, start a job, poll it and
+//! download its receipt:
+//!
+//! - `PUT /v1/images`: upload an ELF, returning its image ID.
+//! - `PUT /v1/inputs`: upload the stdin of a guest, returning its input ID.
+//! - `POST /v1/jobs`: start a job described by a JSON [JobRequest], returning
+//!   its job ID.
+//! - `GET /v1/jobs/{job_id}`: the [JobStatus] of a job.
+//! - `GET /v1/jobs/{job_id}/receipt`: the receipt of a proving job, encoded
+//!   with bincode.
+//! - `POST /v1/verify/{image_id}`: verify a receipt, encoded with bincode.
+//!
+//! Jobs wait in a queue of bounded length for one of a fixed number of
+//! workers. Everything is kept in memory, and lost when the service stops,
+//! except that proving jobs journal their segments to the temporary directory
+//! until they finish. The service keeps a bounded number of images, inputs and
+//! finished jobs, forgetting the oldest to make room for more, so that clients
+//! should fetch the results of their jobs soon after they finish.
+//!
+//! A job belongs to the tenant named by the `x-tenant-id` header of the request
+//! that started it, or to the tenant `anonymous`. Jobs are scheduled among
+//! tenants by priority and within their quotas, as described in
+//! [crate::scheduler]. A job submitted over the quota of its tenant is rejected
+//! with `429 Too Many Requests`, and one with a priority above what its tenant
+//! may ask for with `403 Forbidden`.
+//!
+//! With the `otel` feature, a job runs in the trace of the `traceparent` and
+//! `tracestate` headers of the request that started it.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::Infallible,
+    fs,
+    net::SocketAddr,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    sync::{Arc, Condvar, Mutex},
+    thread,
+};
+
+use anyhow::{anyhow, bail, Result};
+use clap::Args;
+use hyper::{
+    body::HttpBody,
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use risc0_zkvm::{
+    compute_image_id, get_prover_server, is_dev_mode,
+    sha::{Digest, Digestible, Impl, Sha256},
+    ExecutorEnv, ExecutorImpl, ProveInfo, ProverOpts, ProvingJob, Receipt, VerifierContext,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::scheduler::{Entry, Priority, Rejection, Scheduler, TenantQuota};
+
+const TENANT_HEADER: &str = "x-tenant-id";
+const DEFAULT_TENANT: &str = "anonymous";
+
+/// The limits of the service.
+#[derive(Args, Clone)]
+#[command(next_help_heading = "Service")]
+pub(crate) struct ServiceArgs {
+    /// The number of jobs of the service that run at once.
+    #[arg(long, default_value_t = 1)]
+    max_concurrent_jobs: usize,
+
+    /// The number of jobs that can wait for a worker; more are rejected.
+    #[arg(long, default_value_t = 64)]
+    max_queued_jobs: usize,
+
+    /// The largest ELF, input or receipt that can be uploaded, in bytes.
+    #[arg(long, default_value_t = 64 << 20)]
+    pub(crate) max_upload_size: usize,
+
+    /// The most cycles a job of the service or worker may execute.
+    #[arg(long, default_value_t = 1 << 30)]
+    pub(crate) max_session_cycles: u64,
+
+    /// The largest segment size a job of the service or worker may use, in
+    /// powers of 2 cycles.
+    #[arg(long)]
+    pub(crate) max_segment_po2: Option<u32>,
+
+    /// The number of uploaded ELFs the service keeps; uploading more forgets
+    /// the one uploaded longest ago.
+    #[arg(long, default_value_t = 64)]
+    max_images: usize,
+
+    /// The number of uploaded inputs the service keeps; uploading more forgets
+    /// the one uploaded longest ago.
+    #[arg(long, default_value_t = 256)]
+    max_inputs: usize,
+
+    /// The number of finished jobs whose status and receipt the service keeps;
+    /// finishing more forgets the one that finished longest ago.
+    #[arg(long, default_value_t = 256)]
+    max_finished_jobs: usize,
+
+    /// The number of jobs of each tenant of the service that run at once.
+    #[arg(long)]
+    max_running_jobs_per_tenant: Option<usize>,
+
+    /// The number of jobs of each tenant of the service that can wait for a
+    /// worker; more are rejected.
+    #[arg(long)]
+    max_queued_jobs_per_tenant: Option<usize>,
+
+    /// The number of jobs each tenant of the service may submit in a minute;
+    /// more are rejected.
+    #[arg(long)]
+    tenant_jobs_per_minute: Option<usize>,
+
+    /// A JSON file of the quotas of particular tenants, which override the
+    /// limits above for those tenants.
+    ///
+    /// The file holds an object of tenant IDs to quotas, each with any of
+    /// `max_running_jobs`, `max_queued_jobs`, `jobs_per_minute` and
+    /// `max_priority`, which is one of `low`, `normal` or `high`.
+    #[arg(long)]
+    tenant_quotas: Option<PathBuf>,
+}
+
+impl ServiceArgs {
+    /// Check the cycles and segment size of a job against the limits.
+    pub(crate) fn check(
+        &self,
+        session_cycles: Option<u64>,
+        segment_po2: Option<u32>,
+    ) -> Result<()> {
+        if let Some(cycles) = session_cycles {
+            let max = self.max_session_cycles;
+            if cycles > max {
+                bail!("jobs are limited to {max} cycles");
+            }
+        }
+        if let (Some(po2), Some(max)) = (segment_po2, self.max_segment_po2) {
+            if po2 > max {
+                bail!("segments are limited to a po2 of {max}");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A job to run, as posted to `/v1/jobs`.
+#[derive(Clone, Deserialize)]
+struct JobRequest {
+    kind: JobKind,
+
+    /// The image ID of an uploaded ELF.
+    image_id: String,
+
+    /// The ID of an uploaded input, or none for an empty stdin.
+    input_id: Option<String>,
+
+    /// The most cycles to execute, within the limit of the service.
+    session_cycles: Option<u64>,
+
+    /// The segment size, within the limit of the service.
+    segment_po2: Option<u32>,
+
+    /// The priority class of the job, within the quota of its tenant.
+    #[serde(default)]
+    priority: Priority,
+}
+
+#[derive(Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum JobKind {
+    Execute,
+    Prove,
+}
+
+/// The status of a job, as returned by `/v1/jobs/{job_id}`.
+#[derive(Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum JobStatus {
+    Queued,
+    Running,
+    /// A proving job stopped at a segment boundary for a job of a higher
+    /// priority, which waits to continue.
+    Preempted {
+        proven_segments: usize,
+        segments: usize,
+    },
+    Succeeded {
+        exit_code: String,
+        segments: usize,
+        user_cycles: u64,
+        total_cycles: u64,
+        /// The journal, in hex.
+        journal: String,
+        /// The digest of the claim of the receipt, for proving jobs.
+        claim: Option<String>,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+struct Job {
+    request: JobRequest,
+    trace_context: HashMap<String, String>,
+    elf: Arc<Vec<u8>>,
+    input: Arc<Vec<u8>>,
+    status: JobStatus,
+    receipt: Option<Vec<u8>>,
+    // The progress of a preempted proving job.
+    paused: Option<ProvingJob>,
+}
+
+/// How a job that ran stopped, unless it failed.
+enum Outcome {
+    Finished(JobStatus, Option<Vec<u8>>),
+    Preempted(ProvingJob),
+}
+
+// Uploads, along with their IDs in the order they were uploaded.
+#[derive(Default)]
+struct Uploads {
+    data: HashMap<String, Arc<Vec<u8>>>,
+    order: VecDeque<String>,
+}
+
+impl Uploads {
+    // Keep an upload, forgetting the oldest beyond `max`.
+    fn insert(&mut self, id: String, data: Vec<u8>, max: usize) {
+        if self.data.insert(id.clone(), Arc::new(data)).is_some() {
+            self.order.retain(|old| *old != id);
+        }
+        self.order.push_back(id);
+        while self.order.len() > max {
+            let id = self.order.pop_front().unwrap();
+            self.data.remove(&id);
+        }
+    }
+
+    fn get(&self, id: &str) -> Option<&Arc<Vec<u8>>> {
+        self.data.get(id)
+    }
+}
+
+#[derive(Default)]
+struct State {
+    images: Uploads,
+    inputs: Uploads,
+    jobs: HashMap<String, Job>,
+    // The IDs of finished jobs, in the order they finished.
+    finished: VecDeque<String>,
+    scheduler: Scheduler,
+    // The number of workers waiting for a job.
+    idle: usize,
+}
+
+struct Service {
+    args: ServiceArgs,
+    prover_opts: ProverOpts,
+    state: Mutex<State>,
+    queued: Condvar,
+}
+
+/// An error returned to the client, as JSON.
+struct HttpError(StatusCode, String);
+
+impl HttpError {
+    fn bad_request(err: impl ToString) -> Self {
+        Self(StatusCode::BAD_REQUEST, err.to_string())
+    }
+
+    fn not_found(what: &str, id: &str) -> Self {
+        Self(StatusCode::NOT_FOUND, format!("no {what} with ID {id}"))
+    }
+}
+
+/// Serve the REST API on `addr`, proving with `prover_opts`, until the
+/// process is stopped.
+pub(crate) fn serve(addr: SocketAddr, args: ServiceArgs, prover_opts: ProverOpts) -> Result<()> {
+    let quotas = match &args.tenant_quotas {
+        Some(path) => TenantQuota::load(path)?,
+        None => HashMap::new(),
+    };
+    let default_quota = TenantQuota {
+        max_running_jobs: args.max_running_jobs_per_tenant,
+        max_queued_jobs: args.max_queued_jobs_per_tenant,
+        jobs_per_minute: args.tenant_jobs_per_minute,
+        max_priority: None,
+    };
+    let service = Arc::new(Service {
+        args,
+        prover_opts,
+        state: Mutex::new(State {
+            scheduler: Scheduler::new(default_quota, quotas),
+            ..Default::default()
+        }),
+        queued: Condvar::new(),
+    });
+    for _ in 0..service.args.max_concurrent_jobs.max(1) {
+        let service = service.clone();
+        thread::spawn(move || service.work());
+    }
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(async move {
+        let make_service = make_service_fn(move |_| {
+            let service = service.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    let service = service.clone();
+                    async move {
+                        let response = service.handle(req).await;
+                        Ok::<_, Infallible>(response.unwrap_or_else(|HttpError(status, error)| {
+                            json_response(status, &json!({ "error": error }))
+                        }))
+                    }
+                }))
+            }
+        });
+        let server = Server::try_bind(&addr)?.serve(make_service);
+        tracing::info!("r0vm service listening on http://{}", server.local_addr());
+        server.await?;
+        Ok::<_, anyhow::Error>(())
+    })
+}
+
+impl Service {
+    async fn handle(&self, req: Request<Body>) -> Result<Response<Body>, HttpError> {
+        let (parts, body) = req.into_parts();
+        let path: Vec<&str> = parts.uri.path().trim_matches('/').split('/').collect();
+        match (&parts.method, path.as_slice()) {
+            (&Method::PUT, ["v1", "images"]) => {
+                let elf = self.read_body(body).await?;
+                // Hashing the pages of a large ELF would hold up other requests.
+                let (elf, image_id) = tokio::task::spawn_blocking(move || {
+                    let image_id = compute_image_id(&elf);
+                    (elf, image_id)
+                })
+                .await
+                .map_err(|err| HttpError(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+                let image_id = image_id.map_err(HttpError::bad_request)?.to_string();
+                let mut state = self.state.lock().unwrap();
+                state
+                    .images
+                    .insert(image_id.clone(), elf, self.args.max_images);
+                Ok(json_response(
+                    StatusCode::OK,
+                    &json!({ "image_id": image_id }),
+                ))
+            }
+            (&Method::PUT, ["v1", "inputs"]) => {
+                let input = self.read_body(body).await?;
+                let input_id = Impl::hash_bytes(&input).to_string();
+                let mut state = self.state.lock().unwrap();
+                state
+                    .inputs
+                    .insert(input_id.clone(), input, self.args.max_inputs);
+                Ok(json_response(
+                    StatusCode::OK,
+                    &json!({ "input_id": input_id }),
+                ))
+            }
+            (&Method::POST, ["v1", "jobs"]) => {
+                let trace_context = ["traceparent", "tracestate"]
+                    .into_iter()
+                    .filter_map(|name| {
+                        let value = parts.headers.get(name)?.to_str().ok()?;
+                        Some((name.to_string(), value.to_string()))
+                    })
+                    .collect();
+                let tenant = match parts.headers.get(TENANT_HEADER) {
+                    Some(tenant) => tenant
+                        .to_str()
+                        .map_err(|_| HttpError::bad_request("invalid tenant ID"))?
+                        .to_string(),
+                    None => DEFAULT_TENANT.to_string(),
+                };
+                let request = serde_json::from_slice(&self.read_body(body).await?)
+                    .map_err(HttpError::bad_request)?;
+                let job_id = self.submit(request, tenant, trace_context)?;
+                Ok(json_response(
+                    StatusCode::ACCEPTED,
+                    &json!({ "job_id": job_id }),
+                ))
+            }
+            (&Method::GET, ["v1", "jobs", job_id]) => {
+                let state = self.state.lock().unwrap();
+                let job = state
+                    .jobs
+                    .get(*job_id)
+                    .ok_or_else(|| HttpError::not_found("job", job_id))?;
+                Ok(json_response(StatusCode::OK, &job.status))
+            }
+            (&Method::GET, ["v1", "jobs", job_id, "receipt"]) => {
+                let state = self.state.lock().unwrap();
+                let job = state
+                    .jobs
+                    .get(*job_id)
+                    .ok_or_else(|| HttpError::not_found("job", job_id))?;
+                let receipt = job.receipt.clone().ok_or_else(|| {
+                    HttpError(StatusCode::CONFLICT, format!("job {job_id} has no receipt"))
+                })?;
+                Ok(Response::builder()
+                    .header(CONTENT_TYPE, "application/octet-stream")
+                    .body(receipt.into())
+                    .unwrap())
+            }
+            (&Method::POST, ["v1", "verify", image_id]) => {
+                let image_id = hex::decode(image_id)
+                    .ok()
+                    .and_then(|bytes| Digest::try_from(bytes.as_slice()).ok())
+                    .ok_or_else(|| {
+                        HttpError::bad_request(format!("invalid image ID {image_id}"))
+                    })?;
+                let receipt: Receipt = bincode::deserialize(&self.read_body(body).await?)
+                    .map_err(HttpError::bad_request)?;
+                // Verifying a receipt takes long enough to hold up other requests.
+                let result = tokio::task::spawn_blocking(move || receipt.verify(image_id))
+                    .await
+                    .map_err(|err| HttpError(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+                Ok(json_response(
+                    StatusCode::OK,
+                    &match result {
+                        Ok(()) => json!({ "verified": true }),
+                        Err(err) => json!({ "verified": false, "error": err.to_string() }),
+                    },
+                ))
+            }
+            _ => Err(HttpError(
+                StatusCode::NOT_FOUND,
+                format!("no route for {} {}", parts.method, parts.uri.path()),
+            )),
+        }
+    }
+
+    async fn read_body(&self, mut body: Body) -> Result<Vec<u8>, HttpError> {
+        let mut bytes = Vec::new();
+        while let Some(chunk) = body.data().await {
+            let chunk = chunk.map_err(HttpError::bad_request)?;
+            if bytes.len() + chunk.len() > self.args.max_upload_size {
+                return Err(HttpError(
+                    StatusCode::PAYLOAD_TOO_LARGE,
+                    format!("uploads are limited to {} bytes", self.args.max_upload_size),
+                ));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        Ok(bytes)
+    }
+
+    /// Check a job against the limits of the service and the quota of its
+    /// tenant, and queue it.
+    fn submit(
+        &self,
+        request: JobRequest,
+        tenant: String,
+        trace_context: HashMap<String, String>,
+    ) -> Result<String, HttpError> {
+        self.args
+            .check(request.session_cycles, request.segment_po2)
+            .map_err(HttpError::bad_request)?;
+
+        let mut state = self.state.lock().unwrap();
+        if state.scheduler.queued_jobs() >= self.args.max_queued_jobs {
+            return Err(HttpError(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "too many jobs are queued, try again later".into(),
+            ));
+        }
+        let elf = state
+            .images
+            .get(&request.image_id)
+            .ok_or_else(|| HttpError::not_found("image", &request.image_id))?
+            .clone();
+        let input = match &request.input_id {
+            Some(input_id) => state
+                .inputs
+                .get(input_id)
+                .ok_or_else(|| HttpError::not_found("input", input_id))?
+                .clone(),
+            None => Arc::default(),
+        };
+
+        let job_id = uuid::Uuid::new_v4().to_string();
+        state
+            .scheduler
+            .submit(&job_id, &tenant, request.priority)
+            .map_err(|rejection| match rejection {
+                Rejection::Priority(priority) => HttpError(
+                    StatusCode::FORBIDDEN,
+                    format!("tenant {tenant} may not submit {priority:?} priority jobs"),
+                ),
+                Rejection::Quota(error) => HttpError(StatusCode::TOO_MANY_REQUESTS, error),
+            })?;
+        state.jobs.insert(
+            job_id.clone(),
+            Job {
+                request,
+                trace_context,
+                elf,
+                input,
+                status: JobStatus::Queued,
+                receipt: None,
+                paused: None,
+            },
+        );
+        self.queued.notify_one();
+        Ok(job_id)
+    }
+
+    /// Run queued jobs, one at a time, forever.
+    fn work(&self) {
+        loop {
+            let (entry, request, trace_context, elf, input, paused) = {
+                let mut state = self.state.lock().unwrap();
+                state.idle += 1;
+                let entry = loop {
+                    if let Some(entry) = state.scheduler.pop() {
+                        break entry;
+                    }
+                    state = self.queued.wait(state).unwrap();
+                };
+                state.idle -= 1;
+                let job = state.jobs.get_mut(&entry.job_id).unwrap();
+                job.status = JobStatus::Running;
+                (
+                    entry,
+                    job.request.clone(),
+                    job.trace_context.clone(),
+                    job.elf.clone(),
+                    job.input.clone(),
+                    job.paused.take(),
+                )
+            };
+
+            let job_id = entry.job_id.clone();
+            tracing::info!("running job {job_id}");
+            let span = job_span(&job_id, &trace_context);
+            // A job that panics fails, instead of taking its worker down.
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                span.in_scope(|| self.run(&entry, &request, &elf, &input, paused))
+            }))
+            .unwrap_or_else(|_| Err(anyhow!("job panicked")));
+
+            let mut state = self.state.lock().unwrap();
+            let job = state.jobs.get_mut(&job_id).unwrap();
+            match result {
+                Ok(Outcome::Preempted(paused)) => {
+                    tracing::info!("job {job_id} preempted");
+                    job.status = JobStatus::Preempted {
+                        proven_segments: paused.proven_count(),
+                        segments: paused.segment_count(),
+                    };
+                    job.paused = Some(paused);
+                    state.scheduler.preempt(entry);
+                    self.queued.notify_all();
+                    continue;
+                }
+                Ok(Outcome::Finished(status, receipt)) => {
+                    job.status = status;
+                    job.receipt = receipt;
+                }
+                Err(err) => {
+                    tracing::info!("job {job_id} failed: {err:?}");
+                    job.status = JobStatus::Failed {
+                        error: format!("{err:#}"),
+                    };
+                }
+            }
+            state.scheduler.finish(&entry);
+            state.finished.push_back(job_id.clone());
+            while state.finished.len() > self.args.max_finished_jobs {
+                let id = state.finished.pop_front().unwrap();
+                state.jobs.remove(&id);
+            }
+            // A job of the same tenant may have been waiting for this one.
+            self.queued.notify_all();
+            drop(state);
+            fs::remove_dir_all(job_dir(&job_id)).ok();
+        }
+    }
+
+    fn run(
+        &self,
+        entry: &Entry,
+        request: &JobRequest,
+        elf: &[u8],
+        input: &[u8],
+        paused: Option<ProvingJob>,
+    ) -> Result<Outcome> {
+        let session_cycles = request
+            .session_cycles
+            .unwrap_or(self.args.max_session_cycles);
+        let env = {
+            let mut builder = ExecutorEnv::builder();
+            builder.stdin(input).session_limit(Some(session_cycles));
+            if let Some(po2) = request.segment_po2.or(self.args.max_segment_po2) {
+                builder.segment_limit_po2(po2);
+            }
+            builder.build()?
+        };
+
+        match request.kind {
+            JobKind::Execute => {
+                let session = ExecutorImpl::from_elf(env, elf)?.run()?;
+                let status = JobStatus::Succeeded {
+                    exit_code: format!("{:?}", session.exit_code),
+                    segments: session.segments.len(),
+                    user_cycles: session.user_cycles,
+                    total_cycles: session.total_cycles,
+                    journal: session
+                        .journal
+                        .as_ref()
+                        .map_or(String::new(), |journal| hex::encode(&journal.bytes)),
+                    claim: None,
+                };
+                Ok(Outcome::Finished(status, None))
+            }
+            JobKind::Prove if is_dev_mode() => {
+                // The dev mode prover fakes the receipt of a whole session at
+                // once, and so its jobs are never preempted.
+                let session = ExecutorImpl::from_elf(env, elf)?.run()?;
+                let prover = get_prover_server(&self.prover_opts)?;
+                let info = prover.prove_session(&VerifierContext::default(), &session)?;
+                proven(info)
+            }
+            JobKind::Prove => {
+                // The segments of a proving job are kept on disk, so that the
+                // job can stop at a segment boundary and continue later.
+                let mut job = match paused {
+                    Some(job) => job,
+                    None => ProvingJob::execute(job_dir(&entry.job_id), env, elf)?,
+                };
+                let prover = get_prover_server(&self.prover_opts)?;
+                let ctx = VerifierContext::default();
+                if !job.prove_segments(prover.as_ref(), &ctx, || self.should_preempt(entry))? {
+                    return Ok(Outcome::Preempted(job));
+                }
+                proven(job.prove(prover.as_ref(), &ctx, &self.prover_opts)?)
+            }
+        }
+    }
+
+    /// Returns true if a running job should stop for one of a higher priority
+    /// that no idle worker can take.
+    fn should_preempt(&self, entry: &Entry) -> bool {
+        let state = self.state.lock().unwrap();
+        state.idle == 0 && state.scheduler.should_preempt(entry)
+    }
+}
+
+/// The outcome of a proving job that made a receipt.
+fn proven(info: ProveInfo) -> Result<Outcome> {
+    let claim = info.receipt.claim()?;
+    let status = JobStatus::Succeeded {
+        exit_code: format!("{:?}", claim.exit_code),
+        segments: info.stats.segments,
+        user_cycles: info.stats.user_cycles,
+        total_cycles: info.stats.total_cycles,
+        journal: hex::encode(&info.receipt.journal.bytes),
+        claim: Some(claim.digest().to_string()),
+    };
+    Ok(Outcome::Finished(
+        status,
+        Some(bincode::serialize(&info.receipt)?),
+    ))
+}
+
+/// The directory that a proving job journals its progress to.
+fn job_dir(job_id: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("r0vm-job-{job_id}"))
+}
+
+/// The span that a job runs in, which continues the trace of its caller.
+pub(crate) fn job_span(job_id: &str, _trace_context: &HashMap<String, String>) -> tracing::Span {
+    let span = tracing::info_span!("job", job_id);
+    #[cfg(feature = "otel")]
+    risc0_zkvm::set_trace_parent(&span, _trace_context);
+    span
+}
+
+pub(crate) fn json_response(status: StatusCode, value: &impl Serialize) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(serde_json::to_vec(value).unwrap().into())
+        .unwrap()
+}
@@ -0,0 +1,81 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A job server polled over HTTP, whose endpoints are under the URL passed to
+//! `--worker`:
+//!
+//! - `POST {url}/claim`, with the JSON `{"worker_id": ...}`: the JSON of the
+//!   next job, or `204 No Content` when there is none.
+//! - `PUT {url}/jobs/{job_id}/receipt`: the receipt of a job.
+//! - `PUT {url}/jobs/{job_id}/error`: the error a job failed with, as text.
+
+use std::{thread, time::Duration};
+
+use anyhow::Result;
+use reqwest::{
+    blocking::{Body, Client},
+    StatusCode, Url,
+};
+use serde_json::json;
+
+use super::JobQueue;
+
+pub(crate) struct HttpQueue {
+    client: Client,
+    url: String,
+    worker_id: String,
+}
+
+impl HttpQueue {
+    pub(crate) fn new(url: Url, worker_id: &str) -> Result<Self> {
+        Ok(Self {
+            client: Client::builder().build()?,
+            url: url.as_str().trim_end_matches('/').to_string(),
+            worker_id: worker_id.to_string(),
+        })
+    }
+
+    fn put(&self, job_id: &str, what: &str, body: impl Into<Body>) -> Result<()> {
+        self.client
+            .put(format!("{}/jobs/{job_id}/{what}", self.url))
+            .body(body)
+            .send()?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+impl JobQueue for HttpQueue {
+    fn claim(&mut self, wait: Duration) -> Result<Option<Vec<u8>>> {
+        let response = self
+            .client
+            .post(format!("{}/claim", self.url))
+            .json(&json!({ "worker_id": self.worker_id }))
+            .send()?
+            .error_for_status()?;
+        if response.status() == StatusCode::NO_CONTENT {
+            thread::sleep(wait);
+            return Ok(None);
+        }
+        Ok(Some(response.bytes()?.to_vec()))
+    }
+
+    fn complete(&mut self, job_id: &str, receipt: &[u8]) -> Result<()> {
+        self.put(job_id, "receipt", receipt.to_vec())
+    }
+
+    fn fail(&mut self, job_id: &str, error: &str) -> Result<()> {
+        self.put(job_id, "error", error.to_string())
+    }
+}
@@ -0,0 +1,297 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A worker that proves jobs claimed from a queue, so that proving scales out
+//! over as many machines as run a worker.
+//!
+//! The queue is named by the URL passed to `--worker`:
+//!
+//! - `http://` or `https://`: a job server polled over HTTP, see [http].
+//! - `redis://host:port/key`: a Redis list, see [redis].
+//! - `nats://host:port/subject`: a NATS subject, see [nats].
+//!
+//! Each queue carries jobs as the JSON of a [Job], and takes back the receipt
+//! of each job encoded with bincode, or the error it failed with. A worker
+//! claims one job at a time and proves it with the HAL of its build. Workers
+//! prove jobs in the order their queue hands them out, and so priorities and
+//! tenant quotas are for the queue to enforce, unlike those of the service.
+//!
+//! On `SIGTERM` or `SIGINT`, a worker stops claiming jobs, finishes the job it
+//! is running and exits. A worker given `--drain-timeout` fails the job it is
+//! running instead once the timeout passes, so that the queue learns of it
+//! before the worker is killed. With `--probe-addr`, a worker also serves the
+//! liveness and readiness probes of [probe].
+
+mod http;
+mod nats;
+mod probe;
+mod redis;
+
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    panic::{self, AssertUnwindSafe},
+    sync::{atomic::Ordering, mpsc, Arc},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Args;
+use reqwest::Url;
+use risc0_zkvm::{
+    available_hals, get_prover_server, is_dev_mode, ExecutorEnv, ExecutorImpl, ProverOpts, Segment,
+    VerifierContext,
+};
+use serde::Deserialize;
+use signal_hook::consts::{SIGINT, SIGTERM};
+
+use crate::service::{job_span, ServiceArgs};
+
+#[derive(Args, Clone)]
+#[command(next_help_heading = "Worker")]
+pub(crate) struct WorkerArgs {
+    /// How long a worker waits for a job before asking its queue again, in
+    /// seconds.
+    #[arg(long, default_value_t = 5)]
+    poll_interval: u64,
+
+    /// Serve `/healthz` and `/readyz` probes on this address, such as
+    /// `0.0.0.0:8081`.
+    #[arg(long)]
+    probe_addr: Option<SocketAddr>,
+
+    /// How long a stopping worker may spend on the job it is running before
+    /// failing it, in seconds. Without a timeout, the job is finished.
+    #[arg(long)]
+    drain_timeout: Option<u64>,
+}
+
+/// A job claimed from a queue.
+#[derive(Deserialize)]
+struct Job {
+    /// The ID that the queue knows the job by.
+    job_id: String,
+
+    /// The W3C trace context of the caller, such as `traceparent`, which the
+    /// job is proven in with the `otel` feature.
+    #[serde(default)]
+    trace_context: HashMap<String, String>,
+
+    #[serde(flatten)]
+    task: Task,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Task {
+    /// Execute a guest and prove its session, making a `Receipt`.
+    Prove {
+        /// The ELF of the guest, in hex.
+        #[serde(with = "hex")]
+        elf: Vec<u8>,
+
+        /// The stdin of the guest, in hex.
+        #[serde(with = "hex", default)]
+        input: Vec<u8>,
+
+        /// The most cycles to execute, within the limit of the worker.
+        session_cycles: Option<u64>,
+
+        /// The segment size, within the limit of the worker.
+        segment_po2: Option<u32>,
+    },
+
+    /// Prove a segment executed elsewhere, making a `SegmentReceipt`.
+    Segment {
+        /// The segment, encoded with bincode, in hex.
+        #[serde(with = "hex")]
+        segment: Vec<u8>,
+    },
+}
+
+/// A queue of jobs, which workers claim one at a time.
+trait JobQueue {
+    /// Claim the next job, waiting up to `wait` for one, and return its JSON.
+    fn claim(&mut self, wait: Duration) -> Result<Option<Vec<u8>>>;
+
+    /// Hand back the receipt of a job, encoded with bincode.
+    fn complete(&mut self, job_id: &str, receipt: &[u8]) -> Result<()>;
+
+    /// Hand back the error a job failed with.
+    fn fail(&mut self, job_id: &str, error: &str) -> Result<()>;
+}
+
+fn connect(url: &str, worker_id: &str) -> Result<Box<dyn JobQueue>> {
+    let url = Url::parse(url).with_context(|| format!("invalid queue URL {url}"))?;
+    Ok(match url.scheme() {
+        "http" | "https" => Box::new(http::HttpQueue::new(url, worker_id)?),
+        "redis" => Box::new(redis::RedisQueue::new(url)?),
+        "nats" => Box::new(nats::NatsQueue::new(url)?),
+        scheme => bail!("unsupported queue {scheme}, expected http, https, redis or nats"),
+    })
+}
+
+/// Prove jobs from the queue at `url` with `prover_opts`, within the job
+/// limits of `limits`, until the process is asked to stop.
+pub(crate) fn work(
+    url: &str,
+    args: WorkerArgs,
+    limits: ServiceArgs,
+    prover_opts: ProverOpts,
+) -> Result<()> {
+    let probes = Arc::new(probe::Probes::new(limits.max_segment_po2, is_dev_mode()));
+    for signal in [SIGTERM, SIGINT] {
+        // A second signal stops the worker without waiting for its job.
+        signal_hook::flag::register_conditional_shutdown(signal, 1, probes.draining.clone())?;
+        signal_hook::flag::register(signal, probes.draining.clone())?;
+    }
+    if let Some(addr) = args.probe_addr {
+        probe::serve(addr, probes.clone())?;
+    }
+
+    // Starting the prover finds the GPU and loads its kernels, which fails or
+    // panics on a machine without one.
+    let started = panic::catch_unwind(|| get_prover_server(&prover_opts).map(|_| ()))
+        .unwrap_or_else(|_| Err(anyhow!("the prover panicked while starting")));
+    let hal = if is_dev_mode() {
+        "dev mode"
+    } else {
+        available_hals()[0]
+    };
+    match started {
+        Ok(()) => probes.prover_started(Ok(hal.to_string())),
+        Err(err) => {
+            tracing::error!("failed to start the {hal} prover: {err:#}");
+            probes.prover_started(Err(format!("{err:#}")));
+        }
+    }
+
+    let worker_id = uuid::Uuid::new_v4().to_string();
+    let mut queue = connect(url, &worker_id)?;
+    let worker = Arc::new(Worker {
+        limits,
+        prover_opts,
+    });
+    let wait = Duration::from_secs(args.poll_interval.max(1));
+    let drain_timeout = args.drain_timeout.map(Duration::from_secs);
+    tracing::info!("r0vm worker {worker_id} proving jobs from {url}");
+
+    while !probes.draining.load(Ordering::Relaxed) {
+        // The queue may be down for a while, which the worker outlasts.
+        let job = match queue.claim(wait) {
+            Ok(Some(job)) => job,
+            Ok(None) => continue,
+            Err(err) => {
+                tracing::warn!("failed to claim a job: {err:#}");
+                thread::sleep(wait);
+                continue;
+            }
+        };
+        let job: Job = match serde_json::from_slice(&job) {
+            Ok(job) => job,
+            Err(err) => {
+                tracing::warn!("dropping a malformed job: {err}");
+                continue;
+            }
+        };
+
+        tracing::info!("running job {}", job.job_id);
+        // The job runs on a thread of its own, so that a stopping worker can
+        // give up on it once its drain timeout passes.
+        let (sender, receiver) = mpsc::channel();
+        let task_worker = worker.clone();
+        let span = job_span(&job.job_id, &job.trace_context);
+        thread::spawn(move || {
+            // A job that panics fails, instead of taking the worker down.
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                span.in_scope(|| task_worker.run(job.task))
+            }))
+            .unwrap_or_else(|_| Err(anyhow!("job panicked")));
+            sender.send(result).ok();
+        });
+
+        let mut deadline = None;
+        let result = loop {
+            match receiver.recv_timeout(Duration::from_millis(100)) {
+                Ok(result) => break result,
+                Err(mpsc::RecvTimeoutError::Disconnected) => break Err(anyhow!("job panicked")),
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+            }
+            let Some(timeout) = drain_timeout else {
+                continue;
+            };
+            if probes.draining.load(Ordering::Relaxed) {
+                let deadline = *deadline.get_or_insert_with(|| Instant::now() + timeout);
+                if Instant::now() >= deadline {
+                    break Err(anyhow!("the worker stopped before the job finished"));
+                }
+            }
+        };
+        let handed_back = match result {
+            Ok(receipt) => queue.complete(&job.job_id, &receipt),
+            Err(err) => {
+                tracing::info!("job {} failed: {err:?}", job.job_id);
+                queue.fail(&job.job_id, &format!("{err:#}"))
+            }
+        };
+        if let Err(err) = handed_back {
+            tracing::warn!("failed to hand back job {}: {err:#}", job.job_id);
+        }
+    }
+
+    tracing::info!("r0vm worker {worker_id} stopped");
+    Ok(())
+}
+
+struct Worker {
+    limits: ServiceArgs,
+    prover_opts: ProverOpts,
+}
+
+impl Worker {
+    fn run(&self, task: Task) -> Result<Vec<u8>> {
+        let prover = get_prover_server(&self.prover_opts)?;
+        let ctx = VerifierContext::default();
+        match task {
+            Task::Prove {
+                elf,
+                input,
+                session_cycles,
+                segment_po2,
+            } => {
+                self.limits.check(session_cycles, segment_po2)?;
+                let env = {
+                    let mut builder = ExecutorEnv::builder();
+                    builder.stdin(input.as_slice()).session_limit(Some(
+                        session_cycles.unwrap_or(self.limits.max_session_cycles),
+                    ));
+                    if let Some(po2) = segment_po2.or(self.limits.max_segment_po2) {
+                        builder.segment_limit_po2(po2);
+                    }
+                    builder.build()?
+                };
+                let session = ExecutorImpl::from_elf(env, &elf)?.run()?;
+                let receipt = prover.prove_session(&ctx, &session)?.receipt;
+                Ok(bincode::serialize(&receipt)?)
+            }
+            Task::Segment { segment } => {
+                let segment: Segment = bincode::deserialize(&segment).context("invalid segment")?;
+                self.limits.check(None, Some(segment.po2() as u32))?;
+                let receipt = prover.prove_segment(&ctx, &segment)?;
+                Ok(bincode::serialize(&receipt)?)
+            }
+        }
+    }
+}
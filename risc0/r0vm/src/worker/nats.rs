@@ -0,0 +1,193 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A NATS subject that jobs are published to, named by the path of the URL
+//! passed to `--worker`, such as `nats://127.0.0.1:4222/jobs`.
+//!
+//! Workers subscribe to the subject in the queue group `r0vm`, so that each
+//! job is delivered to one of them, and take one message at a time. The
+//! receipt of a job is published to `jobs.receipt.{job_id}`, or its error to
+//! `jobs.error.{job_id}`.
+//!
+//! Core NATS does not redeliver messages, so a job is lost if its worker dies
+//! while running it. The worker speaks just enough of the NATS protocol for
+//! this, without TLS.
+
+use std::{
+    io::{BufRead, BufReader, ErrorKind, Read, Write},
+    net::TcpStream,
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use reqwest::Url;
+use serde_json::json;
+
+use super::JobQueue;
+
+const QUEUE_GROUP: &str = "r0vm";
+
+pub(crate) struct NatsQueue {
+    url: Url,
+    subject: String,
+}
+
+impl NatsQueue {
+    pub(crate) fn new(url: Url) -> Result<Self> {
+        let subject = url.path().trim_start_matches('/').to_string();
+        if subject.is_empty() || subject.contains(char::is_whitespace) {
+            bail!("the NATS URL {url} names no subject, such as nats://127.0.0.1/jobs");
+        }
+        Ok(Self { url, subject })
+    }
+
+    fn publish(&mut self, subject: &str, payload: &[u8]) -> Result<()> {
+        let mut connection = Connection::open(&self.url)?;
+        let mut message = format!("PUB {subject} {}\r\n", payload.len()).into_bytes();
+        message.extend_from_slice(payload);
+        message.extend_from_slice(b"\r\n");
+        connection.writer.write_all(&message)?;
+        // The server has handled the message once it answers a later ping.
+        connection.ping()?;
+        Ok(())
+    }
+}
+
+impl JobQueue for NatsQueue {
+    // Each claim has a connection of its own, as jobs run for longer than the
+    // server waits for an idle connection to answer its pings.
+    fn claim(&mut self, wait: Duration) -> Result<Option<Vec<u8>>> {
+        let mut connection = Connection::open(&self.url)?;
+        connection.writer.write_all(
+            format!("SUB {} {QUEUE_GROUP} 1\r\nUNSUB 1 1\r\n", self.subject).as_bytes(),
+        )?;
+        connection.reader.get_ref().set_read_timeout(Some(wait))?;
+        match connection.next_message() {
+            Ok(message) => return Ok(message),
+            Err(err) if is_timeout(&err) => {}
+            Err(err) => return Err(err),
+        }
+
+        // A job may have been delivered before the server saw the
+        // unsubscription, in which case it comes before the answer to a ping.
+        connection.reader.get_ref().set_read_timeout(None)?;
+        connection.writer.write_all(b"UNSUB 1\r\nPING\r\n")?;
+        connection.next_message()
+    }
+
+    fn complete(&mut self, job_id: &str, receipt: &[u8]) -> Result<()> {
+        self.publish(&format!("{}.receipt.{job_id}", self.subject), receipt)
+    }
+
+    fn fail(&mut self, job_id: &str, error: &str) -> Result<()> {
+        self.publish(
+            &format!("{}.error.{job_id}", self.subject),
+            error.as_bytes(),
+        )
+    }
+}
+
+fn is_timeout(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<std::io::Error>()
+        .is_some_and(|err| matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut))
+}
+
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    fn open(url: &Url) -> Result<Self> {
+        let host = url.host_str().unwrap_or("127.0.0.1");
+        let port = url.port().unwrap_or(4222);
+        let stream = TcpStream::connect((host, port))
+            .with_context(|| format!("failed to connect to NATS at {host}:{port}"))?;
+        let mut connection = Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        };
+
+        let info = connection.read_line()?;
+        if !info.starts_with("INFO ") {
+            bail!("unexpected greeting from NATS: {info}");
+        }
+        let mut options = json!({ "verbose": false, "pedantic": false, "name": "r0vm" });
+        if let Some(password) = url.password() {
+            options["user"] = url.username().into();
+            options["pass"] = password.into();
+        } else if !url.username().is_empty() {
+            options["auth_token"] = url.username().into();
+        }
+        connection
+            .writer
+            .write_all(format!("CONNECT {options}\r\n").as_bytes())?;
+        connection.ping()?;
+        Ok(connection)
+    }
+
+    fn read_line(&mut self) -> Result<String> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            bail!("NATS closed the connection");
+        }
+        Ok(line.trim_end().to_string())
+    }
+
+    fn ping(&mut self) -> Result<()> {
+        self.writer.write_all(b"PING\r\n")?;
+        loop {
+            match self.read_line()?.as_str() {
+                "PONG" => return Ok(()),
+                line => self.handle(line)?,
+            }
+        }
+    }
+
+    // Read until a message arrives, returning its payload, or until the server
+    // answers a ping.
+    fn next_message(&mut self) -> Result<Option<Vec<u8>>> {
+        loop {
+            let line = self.read_line()?;
+            if let Some(args) = line.strip_prefix("MSG ") {
+                // MSG <subject> <sid> [reply-to] <size>
+                let size: usize = args
+                    .split_whitespace()
+                    .last()
+                    .context("malformed MSG from NATS")?
+                    .parse()?;
+                let mut payload = vec![0; size + 2];
+                self.reader.read_exact(&mut payload)?;
+                payload.truncate(size);
+                return Ok(Some(payload));
+            }
+            match line.as_str() {
+                "PONG" => return Ok(None),
+                line => self.handle(line)?,
+            }
+        }
+    }
+
+    // Handle a line that is not what the worker is waiting for.
+    fn handle(&mut self, line: &str) -> Result<()> {
+        match line {
+            "PING" => self.writer.write_all(b"PONG\r\n")?,
+            "+OK" => {}
+            line if line.starts_with("-ERR") => bail!("NATS error: {line}"),
+            line if line.starts_with("INFO ") => {}
+            line => bail!("unexpected message from NATS: {line}"),
+        }
+        Ok(())
+    }
+}
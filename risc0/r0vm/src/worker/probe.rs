@@ -0,0 +1,193 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Probes for running workers under an orchestrator such as Kubernetes,
+//! served on the address passed to `--probe-addr`:
+//!
+//! - `GET /healthz`: `200 OK` while the worker runs.
+//! - `GET /readyz`: the JSON of the [Readiness] of the worker, with `200 OK`
+//!   when it is ready to take jobs and `503 Service Unavailable` when not.
+//!
+//! A worker is ready once its prover has started, which finds the GPU and
+//! loads its kernels in builds with one, while enough memory is available to
+//! prove the largest segment it takes, and until it is asked to stop.
+
+use std::{
+    convert::Infallible,
+    fs,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use anyhow::Result;
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use serde::Serialize;
+
+use crate::service::json_response;
+
+// Proving takes about this much memory per cycle of a segment, as in the
+// calibrations of `risc0_zkvm::estimate`.
+const BYTES_PER_CYCLE: u64 = 9 * 1024;
+
+// The segment size of jobs that do not pick one.
+const DEFAULT_SEGMENT_PO2: u32 = 20;
+
+/// The state of a worker, as reported by its probes.
+pub(crate) struct Probes {
+    /// The result of starting the prover, once it has been started.
+    prover: Mutex<Option<Result<String, String>>>,
+
+    /// Set when the worker is asked to stop.
+    pub(crate) draining: Arc<AtomicBool>,
+
+    /// The memory needed to prove the largest segment, or none when proving
+    /// needs no memory to speak of, as in dev mode.
+    required_memory: Option<u64>,
+}
+
+/// The readiness of a worker, as returned by `/readyz`.
+#[derive(Serialize)]
+struct Readiness {
+    ready: bool,
+
+    /// The HAL the prover started with, or the error it failed with.
+    prover: Check,
+
+    /// The memory available against the memory needed to prove a segment.
+    memory: Check,
+
+    /// Whether the worker is finishing its last job before it stops.
+    draining: bool,
+}
+
+#[derive(Serialize)]
+struct Check {
+    ok: bool,
+    detail: String,
+}
+
+impl Probes {
+    pub(crate) fn new(max_segment_po2: Option<u32>, dev_mode: bool) -> Self {
+        let po2 = max_segment_po2.unwrap_or(DEFAULT_SEGMENT_PO2);
+        Self {
+            prover: Mutex::new(None),
+            draining: Arc::default(),
+            required_memory: (!dev_mode).then_some(BYTES_PER_CYCLE << po2),
+        }
+    }
+
+    /// Record the result of starting the prover.
+    pub(crate) fn prover_started(&self, result: Result<String, String>) {
+        *self.prover.lock().unwrap() = Some(result);
+    }
+
+    fn readiness(&self) -> Readiness {
+        let prover = match &*self.prover.lock().unwrap() {
+            None => Check {
+                ok: false,
+                detail: "starting".into(),
+            },
+            Some(Ok(hal)) => Check {
+                ok: true,
+                detail: hal.clone(),
+            },
+            Some(Err(err)) => Check {
+                ok: false,
+                detail: err.clone(),
+            },
+        };
+        let memory = match (self.required_memory, available_memory()) {
+            (Some(required), Some(available)) => Check {
+                ok: available >= required,
+                detail: format!("{available} bytes available, {required} bytes needed"),
+            },
+            (None, _) => Check {
+                ok: true,
+                detail: "no memory needed".into(),
+            },
+            (_, None) => Check {
+                ok: true,
+                detail: "available memory unknown".into(),
+            },
+        };
+        let draining = self.draining.load(Ordering::Relaxed);
+        Readiness {
+            ready: prover.ok && memory.ok && !draining,
+            prover,
+            memory,
+            draining,
+        }
+    }
+
+    fn handle(&self, req: Request<Body>) -> Response<Body> {
+        match (req.method(), req.uri().path()) {
+            (&Method::GET, "/healthz") => json_response(StatusCode::OK, &"ok"),
+            (&Method::GET, "/readyz") => {
+                let readiness = self.readiness();
+                let status = if readiness.ready {
+                    StatusCode::OK
+                } else {
+                    StatusCode::SERVICE_UNAVAILABLE
+                };
+                json_response(status, &readiness)
+            }
+            _ => json_response(StatusCode::NOT_FOUND, &"no such probe"),
+        }
+    }
+}
+
+/// Serve the probes of a worker on `addr` from a thread of their own.
+pub(crate) fn serve(addr: SocketAddr, probes: Arc<Probes>) -> Result<()> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    // Bind before returning, so that a worker with a taken address fails.
+    let server = runtime.block_on(async { Server::try_bind(&addr) })?;
+    thread::spawn(move || {
+        runtime.block_on(async move {
+            let make_service = make_service_fn(move |_| {
+                let probes = probes.clone();
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |req| {
+                        let response = probes.handle(req);
+                        async move { Ok::<_, Infallible>(response) }
+                    }))
+                }
+            });
+            let server = server.serve(make_service);
+            tracing::info!("r0vm worker probes on http://{}", server.local_addr());
+            if let Err(err) = server.await {
+                tracing::warn!("worker probes stopped: {err}");
+            }
+        })
+    });
+    Ok(())
+}
+
+/// The memory available to new allocations, where the platform reports it.
+fn available_memory() -> Option<u64> {
+    let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemAvailable:"))?;
+    let kib: u64 = line.trim().trim_end_matches("kB").trim().parse().ok()?;
+    Some(kib * 1024)
+}
@@ -0,0 +1,183 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A Redis list of jobs, at the key in the path of the URL passed to
+//! `--worker`, such as `redis://:password@127.0.0.1:6379/jobs`.
+//!
+//! Producers push the JSON of jobs with `LPUSH jobs`. A worker moves each job
+//! it claims to the list `jobs:claimed`, where it stays until the worker hands
+//! it back, so that jobs of workers that died can be requeued. The receipt of
+//! a job is stored at `jobs:receipt:{job_id}`, or its error at
+//! `jobs:error:{job_id}`, and then its ID is pushed to `jobs:done`.
+//!
+//! This needs Redis 6.2 or later, and speaks just enough of RESP, the
+//! protocol of Redis, for these commands.
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use reqwest::Url;
+
+use super::JobQueue;
+
+pub(crate) struct RedisQueue {
+    url: Url,
+    key: String,
+    connection: Option<Connection>,
+    // The JSON of the job being run, as it is in the claimed list.
+    claimed: Option<Vec<u8>>,
+}
+
+impl RedisQueue {
+    pub(crate) fn new(url: Url) -> Result<Self> {
+        let key = url.path().trim_start_matches('/').to_string();
+        if key.is_empty() {
+            bail!("the Redis URL {url} names no key, such as redis://127.0.0.1/jobs");
+        }
+        Ok(Self {
+            url,
+            key,
+            connection: None,
+            claimed: None,
+        })
+    }
+
+    // Run a command, reconnecting first if the last command failed.
+    fn command(&mut self, args: &[&[u8]]) -> Result<Value> {
+        if self.connection.is_none() {
+            self.connection = Some(Connection::open(&self.url)?);
+        }
+        let result = self.connection.as_mut().unwrap().command(args);
+        if result.is_err() {
+            self.connection = None;
+        }
+        result
+    }
+
+    fn finish(&mut self, job_id: &str, kind: &str, value: &[u8]) -> Result<()> {
+        let result_key = format!("{}:{kind}:{job_id}", self.key);
+        self.command(&[b"SET", result_key.as_bytes(), value])?;
+        let done = format!("{}:done", self.key);
+        self.command(&[b"RPUSH", done.as_bytes(), job_id.as_bytes()])?;
+        if let Some(job) = self.claimed.take() {
+            let claimed = format!("{}:claimed", self.key);
+            self.command(&[b"LREM", claimed.as_bytes(), b"1", &job])?;
+        }
+        Ok(())
+    }
+}
+
+impl JobQueue for RedisQueue {
+    fn claim(&mut self, wait: Duration) -> Result<Option<Vec<u8>>> {
+        let claimed = format!("{}:claimed", self.key);
+        let timeout = wait.as_secs().to_string();
+        let key = self.key.clone();
+        let job = match self.command(&[
+            b"BLMOVE",
+            key.as_bytes(),
+            claimed.as_bytes(),
+            b"RIGHT",
+            b"LEFT",
+            timeout.as_bytes(),
+        ])? {
+            Value::Bytes(job) => job,
+            Value::Nil => return Ok(None),
+            _ => bail!("unexpected reply to BLMOVE"),
+        };
+        self.claimed = Some(job.clone());
+        Ok(Some(job))
+    }
+
+    fn complete(&mut self, job_id: &str, receipt: &[u8]) -> Result<()> {
+        self.finish(job_id, "receipt", receipt)
+    }
+
+    fn fail(&mut self, job_id: &str, error: &str) -> Result<()> {
+        self.finish(job_id, "error", error.as_bytes())
+    }
+}
+
+// A reply, of which the worker only reads bulk strings.
+enum Value {
+    Nil,
+    Bytes(Vec<u8>),
+    Other,
+}
+
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    fn open(url: &Url) -> Result<Self> {
+        let host = url.host_str().unwrap_or("127.0.0.1");
+        let port = url.port().unwrap_or(6379);
+        let stream = TcpStream::connect((host, port))
+            .with_context(|| format!("failed to connect to Redis at {host}:{port}"))?;
+        let mut connection = Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        };
+        if let Some(password) = url.password() {
+            match url.username() {
+                "" => connection.command(&[b"AUTH", password.as_bytes()])?,
+                user => connection.command(&[b"AUTH", user.as_bytes(), password.as_bytes()])?,
+            };
+        }
+        Ok(connection)
+    }
+
+    fn command(&mut self, args: &[&[u8]]) -> Result<Value> {
+        let mut request = format!("*{}\r\n", args.len()).into_bytes();
+        for arg in args {
+            request.extend_from_slice(format!("${}\r\n", arg.len()).as_bytes());
+            request.extend_from_slice(arg);
+            request.extend_from_slice(b"\r\n");
+        }
+        self.writer.write_all(&request)?;
+        self.read()
+    }
+
+    fn read(&mut self) -> Result<Value> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            bail!("Redis closed the connection");
+        }
+        let line = line.trim_end();
+        let (kind, rest) = line.split_at(1.min(line.len()));
+        Ok(match kind {
+            "+" | ":" => Value::Other,
+            "-" => bail!("Redis error: {rest}"),
+            "$" | "*" if rest == "-1" => Value::Nil,
+            "$" => {
+                let mut bytes = vec![0; rest.parse::<usize>()? + 2];
+                self.reader.read_exact(&mut bytes)?;
+                bytes.truncate(bytes.len() - 2);
+                Value::Bytes(bytes)
+            }
+            "*" => {
+                for _ in 0..rest.parse::<usize>()? {
+                    self.read()?;
+                }
+                Value::Other
+            }
+            _ => bail!("unexpected reply from Redis: {line}"),
+        })
+    }
+}
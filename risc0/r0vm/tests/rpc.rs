@@ -0,0 +1,180 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    process::{Child, Command},
+    thread,
+    time::Duration,
+};
+
+use risc0_r0vm::rpc::{RpcLimits, RpcServer};
+use risc0_zkvm::serde::to_vec;
+use risc0_zkvm_methods::{
+    multi_test::MultiTestSpec, HELLO_COMMIT_ELF, MULTI_TEST_ELF, MULTI_TEST_ID,
+};
+use serde_json::{json, Value};
+
+struct Server {
+    child: Child,
+    port: u16,
+}
+
+impl Server {
+    fn start() -> Self {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let child = Command::new(assert_cmd::cargo::cargo_bin("r0vm"))
+            .arg("--rpc")
+            .arg(format!("127.0.0.1:{port}"))
+            .spawn()
+            .unwrap();
+        Self { child, port }
+    }
+
+    fn post(&self, body: &Value) -> (u16, Vec<u8>) {
+        let body = body.to_string();
+        let mut stream = (0..100)
+            .find_map(|_| {
+                TcpStream::connect(("127.0.0.1", self.port))
+                    .map_err(|_| thread::sleep(Duration::from_millis(100)))
+                    .ok()
+            })
+            .expect("server did not start");
+        write!(
+            stream,
+            "POST / HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).unwrap();
+        let split = response.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+        let status = std::str::from_utf8(&response[9..12])
+            .unwrap()
+            .parse()
+            .unwrap();
+        (status, response[split + 4..].to_vec())
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.child.kill().unwrap();
+    }
+}
+
+fn do_nothing_input() -> String {
+    hex::encode(bytemuck::cast_slice(
+        &to_vec(&MultiTestSpec::DoNothing).unwrap(),
+    ))
+}
+
+#[test]
+fn execute() {
+    let server = Server::start();
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "method": "load_image",
+        "params": { "elf": hex::encode(MULTI_TEST_ELF) },
+        "id": 1,
+    });
+    let (status, response) = server.post(&request);
+    assert_eq!(status, 200);
+    let response: Value = serde_json::from_slice(&response).unwrap();
+    let image_id = risc0_zkvm::sha::Digest::from(MULTI_TEST_ID).to_string();
+    assert_eq!(response["result"]["image_id"], image_id, "{response}");
+
+    let request = json!([
+        {
+            "jsonrpc": "2.0",
+            "method": "execute",
+            "params": { "image_id": image_id, "input": do_nothing_input() },
+            "id": 2,
+        },
+        { "jsonrpc": "2.0", "method": "prove", "id": 3 },
+    ]);
+    let (status, response) = server.post(&request);
+    assert_eq!(status, 200);
+    let response: Value = serde_json::from_slice(&response).unwrap();
+    let result = &response[0]["result"];
+    assert_eq!(result["exit_code"], "Halted(0)", "{response}");
+    assert_eq!(result["segments"], 1);
+    assert!(result["user_cycles"].as_u64().unwrap() > 0);
+    assert_eq!(response[0]["id"], 2);
+    assert_eq!(response[1]["error"]["code"], -32601);
+
+    // Notifications get no response.
+    let request = json!({ "jsonrpc": "2.0", "method": "load_image", "params": {} });
+    let (status, response) = server.post(&request);
+    assert_eq!(status, 204);
+    assert!(response.is_empty());
+}
+
+#[test]
+fn embedded() {
+    let server = RpcServer::new(RpcLimits {
+        max_session_cycles: Some(1000),
+        ..Default::default()
+    });
+    let request = json!({
+        "jsonrpc": "2.0",
+        "method": "execute",
+        "params": {
+            "elf": hex::encode(MULTI_TEST_ELF),
+            "input": do_nothing_input(),
+            "session_cycles": 2000,
+        },
+        "id": "a",
+    });
+    let response: Value =
+        serde_json::from_slice(&server.handle(request.to_string().as_bytes()).unwrap()).unwrap();
+    assert_eq!(response["error"]["code"], -32602, "{response}");
+    assert_eq!(response["id"], "a");
+
+    let response: Value = serde_json::from_slice(&server.handle(b"{").unwrap()).unwrap();
+    assert_eq!(response["error"]["code"], -32700);
+}
+
+#[test]
+fn evict_images() {
+    let server = RpcServer::new(RpcLimits {
+        max_images: 1,
+        ..Default::default()
+    });
+    let call = |method: &str, params: Value| -> Value {
+        let request = json!({ "jsonrpc": "2.0", "method": method, "params": params, "id": 1 });
+        serde_json::from_slice(&server.handle(request.to_string().as_bytes()).unwrap()).unwrap()
+    };
+
+    let image_id = risc0_zkvm::sha::Digest::from(MULTI_TEST_ID).to_string();
+    let response = call("load_image", json!({ "elf": hex::encode(MULTI_TEST_ELF) }));
+    assert_eq!(response["result"]["image_id"], image_id, "{response}");
+    let response = call(
+        "load_image",
+        json!({ "elf": hex::encode(HELLO_COMMIT_ELF) }),
+    );
+    assert!(response["result"]["image_id"].is_string(), "{response}");
+    assert_ne!(response["result"]["image_id"], image_id);
+
+    let params = json!({ "image_id": image_id, "input": do_nothing_input() });
+    let response = call("execute", params);
+    assert_eq!(response["error"]["code"], -32602, "{response}");
+}
@@ -0,0 +1,249 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    process::{Child, Command},
+    thread,
+    time::Duration,
+};
+
+use assert_fs::{fixture::FileWriteStr, NamedTempFile};
+use risc0_zkvm::{serde::to_vec, Receipt};
+use risc0_zkvm_methods::{
+    multi_test::MultiTestSpec, HELLO_COMMIT_ELF, MULTI_TEST_ELF, MULTI_TEST_ID,
+};
+use serde_json::Value;
+
+struct Service {
+    child: Child,
+    port: u16,
+}
+
+impl Service {
+    fn start(args: &[&str]) -> Self {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let child = Command::new(assert_cmd::cargo::cargo_bin("r0vm"))
+            .env("RISC0_DEV_MODE", "1")
+            .arg("--serve")
+            .arg(format!("127.0.0.1:{port}"))
+            .args(args)
+            .spawn()
+            .unwrap();
+        Self { child, port }
+    }
+
+    fn request(&self, method: &str, path: &str, body: &[u8]) -> (u16, Vec<u8>) {
+        self.request_with_headers(method, path, "", body)
+    }
+
+    // `headers` are written as they are, each ending in `\r\n`.
+    fn request_with_headers(
+        &self,
+        method: &str,
+        path: &str,
+        headers: &str,
+        body: &[u8],
+    ) -> (u16, Vec<u8>) {
+        let mut stream = (0..100)
+            .find_map(|_| {
+                TcpStream::connect(("127.0.0.1", self.port))
+                    .map_err(|_| thread::sleep(Duration::from_millis(100)))
+                    .ok()
+            })
+            .expect("service did not start");
+        write!(
+            stream,
+            "{method} {path} HTTP/1.1\r\nHost: localhost\r\n{headers}Content-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        )
+        .unwrap();
+        stream.write_all(body).unwrap();
+
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).unwrap();
+        let split = response.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+        let status = std::str::from_utf8(&response[9..12])
+            .unwrap()
+            .parse()
+            .unwrap();
+        (status, response[split + 4..].to_vec())
+    }
+
+    fn json(&self, method: &str, path: &str, body: &[u8]) -> (u16, Value) {
+        let (status, body) = self.request(method, path, body);
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn wait(&self, job_id: &str) -> Value {
+        loop {
+            let (status, job) = self.json("GET", &format!("/v1/jobs/{job_id}"), &[]);
+            assert_eq!(status, 200);
+            match job["status"].as_str().unwrap() {
+                "queued" | "running" | "preempted" => thread::sleep(Duration::from_millis(100)),
+                _ => return job,
+            }
+        }
+    }
+}
+
+impl Drop for Service {
+    fn drop(&mut self) {
+        self.child.kill().unwrap();
+    }
+}
+
+#[test]
+#[cfg(not(feature = "disable-dev-mode"))]
+fn prove_and_verify() {
+    let service = Service::start(&[]);
+
+    let (status, image) = service.json("PUT", "/v1/images", MULTI_TEST_ELF);
+    assert_eq!(status, 200);
+    let image_id = image["image_id"].as_str().unwrap();
+    assert_eq!(
+        image_id,
+        risc0_zkvm::sha::Digest::from(MULTI_TEST_ID).to_string()
+    );
+    let input = to_vec(&MultiTestSpec::DoNothing).unwrap();
+    let (status, input) = service.json("PUT", "/v1/inputs", bytemuck::cast_slice(&input));
+    assert_eq!(status, 200);
+    let input_id = input["input_id"].as_str().unwrap();
+
+    let job = serde_json::json!({ "kind": "prove", "image_id": image_id, "input_id": input_id });
+    let (status, job) = service.json("POST", "/v1/jobs", job.to_string().as_bytes());
+    assert_eq!(status, 202);
+    let job_id = job["job_id"].as_str().unwrap();
+    let job = service.wait(job_id);
+    assert_eq!(job["status"], "succeeded", "{job}");
+    assert_eq!(job["exit_code"], "Halted(0)");
+
+    let (status, receipt) = service.request("GET", &format!("/v1/jobs/{job_id}/receipt"), &[]);
+    assert_eq!(status, 200);
+    bincode::deserialize::<Receipt>(&receipt).unwrap();
+
+    let (status, verified) = service.json("POST", &format!("/v1/verify/{image_id}"), &receipt);
+    assert_eq!(status, 200);
+    assert_eq!(verified["verified"], true, "{verified}");
+}
+
+#[test]
+fn limits() {
+    let service = Service::start(&[
+        "--max-session-cycles",
+        "100000",
+        "--max-upload-size",
+        "4096",
+    ]);
+
+    let (status, _) = service.json("PUT", "/v1/inputs", &[0; 8192]);
+    assert_eq!(status, 413);
+    let (status, _) = service.json("PUT", "/v1/inputs", &[0; 16]);
+    assert_eq!(status, 200);
+
+    let job = serde_json::json!({ "kind": "execute", "image_id": "00", "session_cycles": 200000 });
+    let (status, error) = service.json("POST", "/v1/jobs", job.to_string().as_bytes());
+    assert_eq!(status, 400, "{error}");
+    let job = serde_json::json!({ "kind": "execute", "image_id": "00" });
+    let (status, _) = service.json("POST", "/v1/jobs", job.to_string().as_bytes());
+    assert_eq!(status, 404);
+    let (status, _) = service.json("GET", "/v1/jobs/missing", &[]);
+    assert_eq!(status, 404);
+}
+
+#[test]
+fn forget_old_uploads_and_jobs() {
+    let service = Service::start(&["--max-images", "1", "--max-finished-jobs", "1"]);
+
+    let (status, image) = service.json("PUT", "/v1/images", MULTI_TEST_ELF);
+    assert_eq!(status, 200);
+    let old_image_id = image["image_id"].as_str().unwrap();
+    let (status, image) = service.json("PUT", "/v1/images", HELLO_COMMIT_ELF);
+    assert_eq!(status, 200);
+    let image_id = image["image_id"].as_str().unwrap();
+    let job = serde_json::json!({ "kind": "execute", "image_id": old_image_id });
+    let (status, _) = service.json("POST", "/v1/jobs", job.to_string().as_bytes());
+    assert_eq!(status, 404);
+
+    let submit = || {
+        let job = serde_json::json!({ "kind": "execute", "image_id": image_id });
+        let (status, job) = service.json("POST", "/v1/jobs", job.to_string().as_bytes());
+        assert_eq!(status, 202, "{job}");
+        let job_id = job["job_id"].as_str().unwrap().to_string();
+        service.wait(&job_id);
+        job_id
+    };
+    let old_job_id = submit();
+    let job_id = submit();
+    let (status, _) = service.json("GET", &format!("/v1/jobs/{old_job_id}"), &[]);
+    assert_eq!(status, 404);
+    let (status, _) = service.json("GET", &format!("/v1/jobs/{job_id}"), &[]);
+    assert_eq!(status, 200);
+}
+
+#[test]
+fn tenant_quotas() {
+    let quotas = NamedTempFile::new("quotas.json").unwrap();
+    quotas
+        .write_str(r#"{ "batch": { "max_priority": "low" } }"#)
+        .unwrap();
+    let service = Service::start(&[
+        "--tenant-jobs-per-minute",
+        "2",
+        "--tenant-quotas",
+        quotas.path().to_str().unwrap(),
+    ]);
+
+    let (status, image) = service.json("PUT", "/v1/images", MULTI_TEST_ELF);
+    assert_eq!(status, 200);
+    let image_id = image["image_id"].as_str().unwrap();
+    let input = to_vec(&MultiTestSpec::DoNothing).unwrap();
+    let (status, input) = service.json("PUT", "/v1/inputs", bytemuck::cast_slice(&input));
+    assert_eq!(status, 200);
+    let input_id = input["input_id"].as_str().unwrap();
+    let submit = |tenant: &str, priority: &str| {
+        let job = serde_json::json!({
+            "kind": "execute",
+            "image_id": image_id,
+            "input_id": input_id,
+            "priority": priority,
+        });
+        let headers = format!("X-Tenant-Id: {tenant}\r\n");
+        let (status, body) =
+            service.request_with_headers("POST", "/v1/jobs", &headers, job.to_string().as_bytes());
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        (status, body)
+    };
+
+    let (status, error) = submit("batch", "high");
+    assert_eq!(status, 403, "{error}");
+    let (status, job) = submit("batch", "low");
+    assert_eq!(status, 202, "{job}");
+    assert_eq!(
+        service.wait(job["job_id"].as_str().unwrap())["status"],
+        "succeeded"
+    );
+
+    assert_eq!(submit("a", "high").0, 202);
+    assert_eq!(submit("a", "normal").0, 202);
+    let (status, error) = submit("a", "normal");
+    assert_eq!(status, 429);
+    assert_eq!(error["error"], "tenant a is limited to 2 jobs a minute");
+    assert_eq!(submit("b", "normal").0, 202);
+}
@@ -0,0 +1,153 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    process::Command,
+};
+
+use risc0_zkvm::{serde::to_vec, Receipt};
+use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF, MULTI_TEST_ID};
+
+// Read a request from `stream`, returning its request line and body.
+fn read_request(stream: &TcpStream) -> (String, Vec<u8>) {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).unwrap();
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).unwrap();
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap();
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).unwrap();
+    (request_line.trim_end().to_string(), body)
+}
+
+fn respond(mut stream: &TcpStream, status: &str, body: &[u8]) {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    )
+    .unwrap();
+    stream.write_all(body).unwrap();
+}
+
+#[test]
+#[cfg(not(feature = "disable-dev-mode"))]
+fn http_queue() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let mut child = Command::new(assert_cmd::cargo::cargo_bin("r0vm"))
+        .env("RISC0_DEV_MODE", "1")
+        .arg("--worker")
+        .arg(format!("http://127.0.0.1:{port}/queue"))
+        .spawn()
+        .unwrap();
+
+    let input = to_vec(&MultiTestSpec::DoNothing).unwrap();
+    let job = serde_json::json!({
+        "job_id": "job-1",
+        "kind": "prove",
+        "elf": hex::encode(MULTI_TEST_ELF),
+        "input": hex::encode(bytemuck::cast_slice(&input)),
+    });
+    let mut claimed = false;
+    let receipt = loop {
+        let (stream, _) = listener.accept().unwrap();
+        let (request_line, body) = read_request(&stream);
+        match request_line.split_whitespace().take(2).collect::<Vec<_>>()[..] {
+            ["POST", "/queue/claim"] if !claimed => {
+                claimed = true;
+                respond(&stream, "200 OK", job.to_string().as_bytes());
+            }
+            ["POST", "/queue/claim"] => respond(&stream, "204 No Content", &[]),
+            ["PUT", "/queue/jobs/job-1/receipt"] => {
+                respond(&stream, "200 OK", &[]);
+                break body;
+            }
+            _ => panic!(
+                "unexpected request {request_line}: {}",
+                String::from_utf8_lossy(&body)
+            ),
+        }
+    };
+    child.kill().unwrap();
+
+    let receipt: Receipt = bincode::deserialize(&receipt).unwrap();
+    receipt.verify(MULTI_TEST_ID).unwrap();
+}
+
+#[test]
+#[cfg(all(unix, not(feature = "disable-dev-mode")))]
+fn probes_and_drain() {
+    use std::{thread, time::Duration};
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = stream.unwrap();
+            read_request(&stream);
+            respond(&stream, "204 No Content", &[]);
+        }
+    });
+    let probe_port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut child = Command::new(assert_cmd::cargo::cargo_bin("r0vm"))
+        .env("RISC0_DEV_MODE", "1")
+        .arg("--worker")
+        .arg(format!("http://127.0.0.1:{port}/queue"))
+        .arg("--poll-interval=1")
+        .arg(format!("--probe-addr=127.0.0.1:{probe_port}"))
+        .spawn()
+        .unwrap();
+
+    let probe = |path: &str| {
+        reqwest::blocking::get(format!("http://127.0.0.1:{probe_port}{path}"))
+            .map(|response| response.status().as_u16())
+            .ok()
+    };
+    let mut ready = false;
+    for _ in 0..100 {
+        if probe("/readyz") == Some(200) {
+            ready = true;
+            break;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    assert!(ready);
+    assert_eq!(probe("/healthz"), Some(200));
+
+    Command::new("kill")
+        .arg("-TERM")
+        .arg(child.id().to_string())
+        .status()
+        .unwrap();
+    assert!(child.wait().unwrap().success());
+}
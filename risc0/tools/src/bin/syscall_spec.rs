@@ -0,0 +1,233 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Writes the guest ABI of the zkVM as JSON, and as C and AssemblyScript
+//! headers.
+//!
+//! Runtimes for languages other than Rust can run this tool as part of their
+//! build, so that their syscall names and numbers follow the zkVM version they
+//! target. The ABI is described by `risc0_zkvm_platform::spec::SPEC`.
+
+use std::{fmt::Write, fs};
+
+use clap::Parser;
+use risc0_zkvm_platform::spec::{Arg, Spec, Syscall, SPEC};
+
+/// Writes the guest ABI of the zkVM.
+#[derive(Parser)]
+#[clap(about, version, author)]
+struct Args {
+    /// Write the spec as JSON to this file.
+    #[arg(long)]
+    json: Option<String>,
+
+    /// Write a C header to this file.
+    #[arg(long)]
+    c: Option<String>,
+
+    /// Write an AssemblyScript module to this file.
+    #[arg(long)]
+    assemblyscript: Option<String>,
+}
+
+fn main() {
+    let args = Args::parse();
+    if args.json.is_none() && args.c.is_none() && args.assemblyscript.is_none() {
+        println!("{}", json(&SPEC));
+        return;
+    }
+    if let Some(path) = args.json {
+        fs::write(path, json(&SPEC)).unwrap();
+    }
+    if let Some(path) = args.c {
+        fs::write(path, c_header(&SPEC)).unwrap();
+    }
+    if let Some(path) = args.assemblyscript {
+        fs::write(path, assemblyscript(&SPEC)).unwrap();
+    }
+}
+
+fn json(spec: &Spec) -> String {
+    let mut out = String::new();
+    spec.write_json(&mut out).unwrap();
+    out
+}
+
+fn describe_args(out: &mut String, prefix: &str, args: &[Arg]) {
+    for arg in args {
+        writeln!(out, "{prefix}{} {}: {}", arg.register, arg.name, arg.doc).unwrap();
+    }
+}
+
+fn describe_syscall(out: &mut String, prefix: &str, syscall: &Syscall) {
+    writeln!(out, "{prefix}{}", syscall.doc).unwrap();
+    describe_args(out, &format!("{prefix}  "), syscall.args);
+    if !syscall.from_host.is_empty() {
+        writeln!(out, "{prefix}  from_host: {}", syscall.from_host).unwrap();
+    }
+    describe_args(out, &format!("{prefix}  returns "), syscall.returns);
+}
+
+fn c_header(spec: &Spec) -> String {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "// Generated by syscall_spec from risc0-zkvm-platform {}. Do not edit.\n",
+        spec.version
+    )
+    .unwrap();
+    out.push_str("#pragma once\n\n#include <stdint.h>\n");
+
+    for group in spec.constants {
+        let prefix = match group.name {
+            "platform" => "RISC0_".to_string(),
+            name => format!("RISC0_{}_", name.to_uppercase()),
+        };
+        out.push('\n');
+        for constant in group.constants {
+            writeln!(out, "// {}", constant.doc).unwrap();
+            writeln!(out, "#define {prefix}{} {}u", constant.name, constant.value).unwrap();
+        }
+    }
+
+    out.push_str("\n// Ecalls, selected by the number in t0.\n");
+    for ecall in spec.ecalls {
+        writeln!(out, "\n// {}", ecall.doc).unwrap();
+        describe_args(&mut out, "//   ", ecall.args);
+        describe_args(&mut out, "//   returns ", ecall.returns);
+        writeln!(out, "#define RISC0_ECALL_{} {}u", ecall.name, ecall.number).unwrap();
+    }
+
+    out.push_str("\n// Software syscalls, made with RISC0_ECALL_SOFTWARE.\n");
+    describe_args(&mut out, "//   ", spec.software_args);
+    describe_args(&mut out, "//   returns ", spec.software_returns);
+    for syscall in spec.syscalls {
+        out.push('\n');
+        describe_syscall(&mut out, "// ", syscall);
+        writeln!(
+            out,
+            "#define RISC0_{} \"{}\"",
+            syscall.short_name(),
+            syscall.name.as_str()
+        )
+        .unwrap();
+    }
+
+    out.push_str(
+        r#"
+typedef struct {
+  uint32_t a0;
+  uint32_t a1;
+} risc0_syscall_return;
+
+#ifdef __riscv
+// Make a software syscall with the given name, such as RISC0_SYS_READ.
+static inline risc0_syscall_return risc0_syscall(const char* name,
+                                                 uint32_t* from_host,
+                                                 uint32_t from_host_words,
+                                                 uint32_t a3,
+                                                 uint32_t a4,
+                                                 uint32_t a5,
+                                                 uint32_t a6,
+                                                 uint32_t a7) {
+  register uint32_t t0_reg __asm__("t0") = RISC0_ECALL_SOFTWARE;
+  register uint32_t a0_reg __asm__("a0") = (uint32_t)(uintptr_t)from_host;
+  register uint32_t a1_reg __asm__("a1") = from_host_words;
+  register uint32_t a2_reg __asm__("a2") = (uint32_t)(uintptr_t)name;
+  register uint32_t a3_reg __asm__("a3") = a3;
+  register uint32_t a4_reg __asm__("a4") = a4;
+  register uint32_t a5_reg __asm__("a5") = a5;
+  register uint32_t a6_reg __asm__("a6") = a6;
+  register uint32_t a7_reg __asm__("a7") = a7;
+  __asm__ volatile("ecall"
+                   : "+r"(a0_reg), "+r"(a1_reg)
+                   : "r"(t0_reg), "r"(a2_reg), "r"(a3_reg), "r"(a4_reg),
+                     "r"(a5_reg), "r"(a6_reg), "r"(a7_reg)
+                   : "memory");
+  risc0_syscall_return ret = {a0_reg, a1_reg};
+  return ret;
+}
+#endif
+"#,
+    );
+    out
+}
+
+fn assemblyscript(spec: &Spec) -> String {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "// Generated by syscall_spec from risc0-zkvm-platform {}. Do not edit.",
+        spec.version
+    )
+    .unwrap();
+
+    for group in spec.constants {
+        let indent = match group.name {
+            "platform" => "",
+            name => {
+                writeln!(out, "\nexport namespace {name} {{").unwrap();
+                "  "
+            }
+        };
+        for constant in group.constants {
+            writeln!(out, "{indent}/** {} */", constant.doc).unwrap();
+            writeln!(
+                out,
+                "{indent}export const {}: u32 = {};",
+                constant.name, constant.value
+            )
+            .unwrap();
+        }
+        if !indent.is_empty() {
+            out.push_str("}\n");
+        }
+    }
+
+    out.push_str("\n/** Ecalls, selected by the number in t0. */\nexport namespace ecall {\n");
+    for ecall in spec.ecalls {
+        writeln!(out, "  /**\n   * {}", ecall.doc).unwrap();
+        describe_args(&mut out, "   *   ", ecall.args);
+        describe_args(&mut out, "   *   returns ", ecall.returns);
+        writeln!(
+            out,
+            "   */\n  export const {}: u32 = {};",
+            ecall.name, ecall.number
+        )
+        .unwrap();
+    }
+    out.push_str("}\n");
+
+    out.push_str(
+        "\n/**\n * Names of the software syscalls, made with ecall.SOFTWARE. Names are passed \
+         to\n * the host NUL-terminated and UTF-8 encoded, as with\n * \
+         `String.UTF8.encode(name, true)`.\n",
+    );
+    describe_args(&mut out, " *   ", spec.software_args);
+    describe_args(&mut out, " *   returns ", spec.software_returns);
+    out.push_str(" */\nexport namespace nr {\n");
+    for syscall in spec.syscalls {
+        out.push_str("  /**\n");
+        describe_syscall(&mut out, "   * ", syscall);
+        writeln!(
+            out,
+            "   */\n  export const {}: string = \"{}\";",
+            syscall.short_name(),
+            syscall.name.as_str()
+        )
+        .unwrap();
+    }
+    out.push_str("}\n");
+    out
+}
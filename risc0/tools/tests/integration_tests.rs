@@ -31,3 +31,40 @@ fn run_make_id() {
     make_id_cmd.arg(elf_path).arg(id_path);
     make_id_cmd.assert().success();
 }
+
+#[test]
+fn run_syscall_spec() {
+    let temp_dir = tempdir().unwrap();
+    let json_path = temp_dir.path().join("spec.json");
+    let c_path = temp_dir.path().join("risc0_syscalls.h");
+    let as_path = temp_dir.path().join("syscalls.ts");
+
+    let mut cmd = Command::cargo_bin("syscall_spec").unwrap();
+    cmd.arg("--json")
+        .arg(&json_path)
+        .arg("--c")
+        .arg(&c_path)
+        .arg("--assemblyscript")
+        .arg(&as_path);
+    cmd.assert().success();
+
+    let spec: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(json_path).unwrap()).unwrap();
+    let syscalls = spec["syscalls"].as_array().unwrap();
+    assert!(syscalls
+        .iter()
+        .any(|syscall| syscall["name"] == "risc0_zkvm_platform::syscall::nr::SYS_READ"));
+    assert_eq!(spec["ecalls"][2]["name"], "SOFTWARE");
+
+    let header = std::fs::read_to_string(c_path).unwrap();
+    assert!(
+        header.contains("#define RISC0_SYS_READ \"risc0_zkvm_platform::syscall::nr::SYS_READ\"\n")
+    );
+    assert!(header.contains("#define RISC0_ECALL_SOFTWARE 2u\n"));
+    assert!(header.contains("#define RISC0_FILENO_JOURNAL 3u\n"));
+
+    let module = std::fs::read_to_string(as_path).unwrap();
+    assert!(module.contains(
+        "export const SYS_WRITE: string = \"risc0_zkvm_platform::syscall::nr::SYS_WRITE\";\n"
+    ));
+}
@@ -0,0 +1,191 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![deny(missing_docs)]
+
+//! Verification of RISC Zero receipts in CosmWasm contracts.
+//!
+//! A contract takes a [VerifyMsg] as JSON, in an execute or query message,
+//! and verifies it with [verify_json], which returns the journal for the
+//! contract to act on. Verification is deterministic, with no floating point,
+//! and its memory is bounded by the [Limits] that messages are checked against
+//! before they are parsed.
+//!
+//! ```ignore
+//! #[entry_point]
+//! pub fn query(_deps: Deps, _env: Env, msg: Binary) -> StdResult<Binary> {
+//!     let response = risc0_verifier_cosmwasm::verify_json(&msg, &Limits::default())
+//!         .map_err(|err| StdError::generic_err(err.to_string()))?;
+//!     to_json_binary(&response)
+//! }
+//! ```
+
+use std::fmt;
+
+use risc0_verifier::{Digest, Receipt, VerificationError};
+use serde::{Deserialize, Serialize};
+
+/// A request to verify a receipt.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifyMsg {
+    /// The receipt to verify, as converted from a `risc0-zkvm` receipt with
+    /// `Receipt::to_verifier_receipt`.
+    pub receipt: Receipt,
+
+    /// The image ID that the receipt must prove an execution of.
+    pub image_id: Digest,
+}
+
+/// The result of verifying a receipt.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifyResponse {
+    /// Digest of the claim proven by the receipt.
+    pub claim_digest: Digest,
+
+    /// The journal committed by the guest.
+    pub journal: Vec<u8>,
+}
+
+/// Bounds on the messages a contract verifies.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Limits {
+    /// Largest message accepted, in bytes of JSON.
+    ///
+    /// The default fits a succinct receipt with a journal of a few kilobytes.
+    pub max_msg_len: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_msg_len: 1024 * 1024,
+        }
+    }
+}
+
+/// An error from verifying a message.
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// The message is larger than [Limits::max_msg_len].
+    MessageTooLarge {
+        /// Length of the message.
+        len: usize,
+
+        /// The limit it exceeds.
+        max: usize,
+    },
+
+    /// The message is not valid JSON for a [VerifyMsg].
+    InvalidMessage(String),
+
+    /// The receipt failed to verify.
+    Verification(VerificationError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::MessageTooLarge { len, max } => {
+                write!(f, "message of {len} bytes exceeds the limit of {max} bytes")
+            }
+            Error::InvalidMessage(err) => write!(f, "invalid message: {err}"),
+            Error::Verification(err) => write!(f, "verification failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<VerificationError> for Error {
+    fn from(err: VerificationError) -> Self {
+        Error::Verification(err)
+    }
+}
+
+/// Parse a [VerifyMsg] from JSON and verify it.
+pub fn verify_json(msg: &[u8], limits: &Limits) -> Result<VerifyResponse, Error> {
+    if msg.len() > limits.max_msg_len {
+        return Err(Error::MessageTooLarge {
+            len: msg.len(),
+            max: limits.max_msg_len,
+        });
+    }
+    let msg: VerifyMsg =
+        serde_json_wasm::from_slice(msg).map_err(|err| Error::InvalidMessage(err.to_string()))?;
+    verify(msg)
+}
+
+/// Verify that the receipt of a [VerifyMsg] proves a successful execution of
+/// its image ID.
+pub fn verify(msg: VerifyMsg) -> Result<VerifyResponse, Error> {
+    msg.receipt.verify(&msg.image_id)?;
+    Ok(VerifyResponse {
+        claim_digest: msg.receipt.claim.digest(),
+        journal: msg.receipt.journal,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_verifier::{Claim, Digest, ExitCode, Receipt, Seal};
+
+    use super::{verify_json, Error, Limits, VerifyMsg};
+
+    fn msg() -> VerifyMsg {
+        VerifyMsg {
+            receipt: Receipt {
+                seal: Seal::Succinct { seal: vec![0; 16] },
+                claim: Claim {
+                    image_id: Digest::from([1; 8]),
+                    post_state_digest: Digest::ZERO,
+                    exit_code: ExitCode::Halted(0),
+                    input: Digest::ZERO,
+                    output: risc0_verifier::output_digest(b"journal"),
+                },
+                journal: b"journal".to_vec(),
+            },
+            image_id: Digest::from([1; 8]),
+        }
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let json = serde_json_wasm::to_vec(&msg()).unwrap();
+        let parsed: VerifyMsg = serde_json_wasm::from_slice(&json).unwrap();
+        assert_eq!(parsed, msg());
+    }
+
+    #[test]
+    fn rejects_invalid_messages() {
+        let json = serde_json_wasm::to_vec(&msg()).unwrap();
+        let limits = Limits {
+            max_msg_len: json.len() - 1,
+        };
+        assert_eq!(
+            verify_json(&json, &limits),
+            Err(Error::MessageTooLarge {
+                len: json.len(),
+                max: json.len() - 1
+            })
+        );
+        assert!(matches!(
+            verify_json(b"{\"image_id\": 1.5}", &Limits::default()),
+            Err(Error::InvalidMessage(_))
+        ));
+        assert!(matches!(
+            verify_json(&json, &Limits::default()),
+            Err(Error::Verification(_))
+        ));
+    }
+}
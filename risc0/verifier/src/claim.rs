@@ -0,0 +1,93 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use risc0_binfmt::{tagged_struct, ExitCode};
+use risc0_zkp::{
+    core::{
+        digest::Digest,
+        hash::sha::{cpu::Impl, Sha256},
+    },
+    verify::VerificationError,
+};
+
+/// The claim of a receipt, with every field reduced to its digest.
+///
+/// This has the same digest as the `ReceiptClaim` of `risc0-zkvm` with the
+/// same fields.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Claim {
+    /// Digest of the system state before execution, which is the image ID.
+    pub image_id: Digest,
+
+    /// Digest of the system state after execution.
+    pub post_state_digest: Digest,
+
+    /// The exit code of the execution.
+    pub exit_code: ExitCode,
+
+    /// Digest of the input to the guest.
+    pub input: Digest,
+
+    /// Digest of the output of the guest, which is zero if there is no
+    /// output.
+    pub output: Digest,
+}
+
+impl Claim {
+    /// Digest of the claim, which is what a succinct seal commits to.
+    pub fn digest(&self) -> Digest {
+        let (sys_exit, user_exit) = self.exit_code.into_pair();
+        tagged_struct::<Impl>(
+            "risc0.ReceiptClaim",
+            &[
+                self.input,
+                self.image_id,
+                self.post_state_digest,
+                self.output,
+            ],
+            &[sys_exit, user_exit],
+        )
+    }
+
+    /// Check that the claim is of a successful execution of the given image,
+    /// with the given journal and no assumptions or channels.
+    ///
+    /// These are the checks made by `Receipt::verify` of `risc0-zkvm` after
+    /// the seal is verified.
+    pub fn verify_journal(
+        &self,
+        image_id: &Digest,
+        journal: &[u8],
+    ) -> Result<(), VerificationError> {
+        if self.image_id != *image_id {
+            return Err(VerificationError::ImageVerificationError);
+        }
+        if !self.exit_code.is_ok() {
+            return Err(VerificationError::UnexpectedExitCode);
+        }
+        let empty_output = self.output == Digest::ZERO && journal.is_empty();
+        if self.output != output_digest(journal) && !empty_output {
+            return Err(VerificationError::JournalDigestMismatch);
+        }
+        Ok(())
+    }
+}
+
+/// Digest of the output of a guest that committed the given journal, and has
+/// no assumptions or channels.
+pub fn output_digest(journal: &[u8]) -> Digest {
+    let journal = *Impl::hash_bytes(journal);
+    tagged_struct::<Impl>("risc0.Output", &[journal, Digest::ZERO], &[])
+}
@@ -0,0 +1,66 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![deny(missing_docs)]
+
+//! A minimal verifier for RISC Zero receipts, for hosts that cannot depend on
+//! the full `risc0-zkvm` crate, such as Substrate runtimes and
+//! microcontrollers.
+//!
+//! This crate verifies the two seals a standalone verifier is handed in
+//! practice: the seal of a single segment, and the seal of a succinct receipt
+//! produced by recursion. It works on raw seals and on [Claim], a flat form of
+//! the receipt claim with each field reduced to its digest, and has no
+//! dependency on the prover.
+//!
+//! `risc0-zkvm` decodes segment seals with this crate, so the two agree on the
+//! seal format and on the claim digest of every release.
+//!
+//! A [Receipt] bundles a seal with its claim and journal. With the `scale`
+//! feature, receipts are SCALE-encoded for Substrate runtimes and ink!
+//! contracts, which verify them with [verify_encoded]. With the `serde`
+//! feature, they are (de)serialized with `serde`, as the
+//! `risc0-verifier-cosmwasm` crate does to take them as JSON.
+//!
+//! ```no_run
+//! # fn main() -> Result<(), risc0_zkp::verify::VerificationError> {
+//! # let (seal, image_id, journal): (Vec<u32>, risc0_zkp::core::digest::Digest, Vec<u8>) =
+//! #     unimplemented!();
+//! let claim = risc0_verifier::verify_segment(&seal, "poseidon2")?;
+//! claim.verify_journal(&image_id, &journal)?;
+//! # Ok(())
+//! # }
+//! ```
+
+extern crate alloc;
+
+mod claim;
+mod receipt;
+#[cfg(feature = "scale")]
+mod scale;
+mod segment;
+mod succinct;
+
+pub use risc0_binfmt::{ExitCode, SystemState};
+pub use risc0_zkp::{core::digest::Digest, verify::VerificationError};
+
+#[cfg(feature = "scale")]
+pub use self::scale::verify_encoded;
+pub use self::{
+    claim::{output_digest, Claim},
+    receipt::{Receipt, Seal},
+    segment::{decode_segment_claim, verify_segment},
+    succinct::verify_succinct,
+};
@@ -0,0 +1,86 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::{string::String, vec::Vec};
+
+use risc0_zkp::{core::digest::Digest, verify::VerificationError};
+
+use crate::{verify_segment, verify_succinct, Claim};
+
+/// The seal of a [Receipt].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "scale", derive(codec::Encode, codec::Decode))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Seal {
+    /// The seal of an execution that fit in a single segment.
+    Segment {
+        /// The seal, as verified by [verify_segment].
+        seal: Vec<u32>,
+
+        /// Name of the hash function the seal was made with.
+        hashfn: String,
+    },
+
+    /// The seal of a succinct receipt, as verified by [verify_succinct].
+    Succinct {
+        /// The seal, as verified by [verify_succinct].
+        seal: Vec<u32>,
+    },
+}
+
+/// A receipt in the form this crate verifies: a seal, the flat claim it
+/// proves, and the journal committed by the guest.
+///
+/// `risc0-zkvm` converts its receipts into this form with
+/// `Receipt::to_verifier_receipt`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "scale", derive(codec::Encode, codec::Decode))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Receipt {
+    /// The seal that proves the claim.
+    pub seal: Seal,
+
+    /// The claim proven by the seal.
+    pub claim: Claim,
+
+    /// The journal committed by the guest.
+    pub journal: Vec<u8>,
+}
+
+impl Receipt {
+    /// Verify that this receipt proves a successful execution of the given
+    /// image, committing its journal.
+    ///
+    /// This makes the same checks as `Receipt::verify` of `risc0-zkvm`.
+    pub fn verify(&self, image_id: &Digest) -> Result<(), VerificationError> {
+        self.verify_integrity()?;
+        self.claim.verify_journal(image_id, &self.journal)
+    }
+
+    /// Verify that the seal of this receipt proves its claim.
+    ///
+    /// As with `verify_integrity` in `risc0-zkvm`, this does not check the
+    /// image ID, the exit code or the journal.
+    pub fn verify_integrity(&self) -> Result<(), VerificationError> {
+        match &self.seal {
+            Seal::Segment { seal, hashfn } => {
+                if verify_segment(seal, hashfn)? != self.claim {
+                    return Err(VerificationError::ReceiptFormatError);
+                }
+                Ok(())
+            }
+            Seal::Succinct { seal } => verify_succinct(seal, &self.claim),
+        }
+    }
+}
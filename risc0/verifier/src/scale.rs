@@ -0,0 +1,100 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SCALE encoding of receipts, for the runtimes of Substrate chains and ink!
+//! contracts.
+//!
+//! A [Receipt] is SCALE-encoded with `parity-scale-codec`, so that it can be
+//! passed to an extrinsic, kept in storage or sent between contracts. A
+//! [Claim] is encoded as its five fields in order, with each digest as 32
+//! bytes and the exit code as its system and user codes.
+//!
+//! A pallet can take receipts as opaque bytes and verify them with
+//! [verify_encoded], which returns the journal for the pallet to decode:
+//!
+//! ```ignore
+//! #[pallet::call_index(0)]
+//! #[pallet::weight(T::WeightInfo::verify())]
+//! pub fn verify(origin: OriginFor<T>, receipt: Vec<u8>) -> DispatchResult {
+//!     ensure_signed(origin)?;
+//!     let journal = risc0_verifier::verify_encoded(&receipt, &T::ImageId::get())
+//!         .map_err(|_| Error::<T>::InvalidReceipt)?;
+//!     Self::deposit_event(Event::Verified { journal });
+//!     Ok(())
+//! }
+//! ```
+
+use alloc::vec::Vec;
+
+use codec::{Decode, DecodeAll, Encode, Error, Input, MaxEncodedLen, Output};
+use risc0_binfmt::ExitCode;
+use risc0_zkp::{core::digest::Digest, verify::VerificationError};
+
+use crate::{Claim, Receipt};
+
+impl Encode for Claim {
+    fn size_hint(&self) -> usize {
+        Self::max_encoded_len()
+    }
+
+    fn encode_to<T: Output + ?Sized>(&self, dest: &mut T) {
+        dest.write(self.image_id.as_bytes());
+        dest.write(self.post_state_digest.as_bytes());
+        self.exit_code.into_pair().encode_to(dest);
+        dest.write(self.input.as_bytes());
+        dest.write(self.output.as_bytes());
+    }
+}
+
+impl Decode for Claim {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+        let image_id = decode_digest(input)?;
+        let post_state_digest = decode_digest(input)?;
+        let exit_code = match <(u32, u32)>::decode(input)? {
+            // The pair of a session limit decodes as a system split otherwise.
+            (2, 2) => ExitCode::SessionLimit,
+            (sys_exit, user_exit) => ExitCode::from_pair(sys_exit, user_exit)
+                .map_err(|_| Error::from("invalid exit code"))?,
+        };
+        Ok(Self {
+            image_id,
+            post_state_digest,
+            exit_code,
+            input: decode_digest(input)?,
+            output: decode_digest(input)?,
+        })
+    }
+}
+
+impl MaxEncodedLen for Claim {
+    fn max_encoded_len() -> usize {
+        4 * 32 + 2 * 4
+    }
+}
+
+fn decode_digest<I: Input>(input: &mut I) -> Result<Digest, Error> {
+    Ok(Digest::from(<[u8; 32]>::decode(input)?))
+}
+
+/// Decode a SCALE-encoded [Receipt] and verify that it proves a successful
+/// execution of the given image, returning its journal.
+///
+/// Bytes left over after the receipt are rejected, so that each receipt has
+/// exactly one encoding.
+pub fn verify_encoded(receipt: &[u8], image_id: &[u8; 32]) -> Result<Vec<u8>, VerificationError> {
+    let receipt = Receipt::decode_all(&mut &receipt[..])
+        .map_err(|_| VerificationError::ReceiptFormatError)?;
+    receipt.verify(&Digest::from(*image_id))?;
+    Ok(receipt.journal)
+}
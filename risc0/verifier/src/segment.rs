@@ -0,0 +1,124 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use alloc::vec::Vec;
+
+use hex::FromHex;
+use risc0_binfmt::{Digestible, ExitCode, SystemState};
+use risc0_circuit_rv32im::{
+    control_id::{BLAKE2B_CONTROL_ID, POSEIDON2_CONTROL_ID, SHA256_CONTROL_ID},
+    layout, CircuitImpl, CIRCUIT,
+};
+use risc0_core::field::baby_bear::BabyBear;
+use risc0_zkp::{
+    adapter::CircuitInfo as _,
+    core::{
+        digest::Digest,
+        hash::{
+            blake2b::Blake2bCpuHashSuite,
+            poseidon2::Poseidon2HashSuite,
+            sha::{cpu::Impl, Sha256HashSuite},
+            HashSuite,
+        },
+    },
+    layout::Buffer,
+    verify::VerificationError,
+};
+
+use crate::Claim;
+
+/// Verify the seal of a segment, made with the named hash function, and
+/// return its claim.
+///
+/// The hash function is one of `poseidon2`, `sha-256` and `blake2b`, as named
+/// by the `hashfn` field of a segment receipt, and the control ID of the seal
+/// must be one of the rv32im circuits of this release.
+pub fn verify_segment(seal: &[u32], hashfn: &str) -> Result<Claim, VerificationError> {
+    let suite: HashSuite<BabyBear> = match hashfn {
+        "blake2b" => Blake2bCpuHashSuite::new_suite(),
+        "poseidon2" => Poseidon2HashSuite::new_suite(),
+        "sha-256" => Sha256HashSuite::new_suite(),
+        _ => return Err(VerificationError::InvalidHashSuite),
+    };
+    let check_code = |_, control_id: &Digest| {
+        POSEIDON2_CONTROL_ID
+            .into_iter()
+            .chain(SHA256_CONTROL_ID)
+            .chain(BLAKE2B_CONTROL_ID)
+            .any(|x| Digest::from_hex(x).unwrap() == *control_id)
+            .then_some(())
+            .ok_or(VerificationError::ControlVerificationError {
+                control_id: *control_id,
+            })
+    };
+    risc0_zkp::verify::verify(&CIRCUIT, &suite, seal, check_code)?;
+
+    let (pre, post, exit_code, input, output) = decode_segment_claim(seal)?;
+    Ok(Claim {
+        image_id: pre.digest::<Impl>(),
+        post_state_digest: post.digest::<Impl>(),
+        exit_code,
+        input,
+        output,
+    })
+}
+
+/// Decode the claim of a segment from the globals of its seal, as the pre and
+/// post states, the exit code, the input digest and the output digest.
+///
+/// The seal is not verified.
+pub fn decode_segment_claim(
+    seal: &[u32],
+) -> Result<(SystemState, SystemState, ExitCode, Digest, Digest), VerificationError> {
+    if seal.len() < CircuitImpl::OUTPUT_SIZE {
+        return Err(VerificationError::ReceiptFormatError);
+    }
+    let elems = bytemuck::checked::try_cast_slice(&seal[..CircuitImpl::OUTPUT_SIZE])
+        .or(Err(VerificationError::ReceiptFormatError))?;
+    let io = layout::OutBuffer(elems);
+    let body = layout::LAYOUT.mux.body;
+    let pre = decode_system_state(io, body.global.pre)?;
+    let post = decode_system_state(io, body.global.post)?;
+    let input = decode_digest(io, body.global.input)?;
+    let output = decode_digest(io, body.global.output)?;
+
+    let sys_exit = io.get_u64(body.global.sys_exit_code) as u32;
+    let user_exit = io.get_u64(body.global.user_exit_code) as u32;
+    let exit_code =
+        ExitCode::from_pair(sys_exit, user_exit).or(Err(VerificationError::ReceiptFormatError))?;
+
+    Ok((pre, post, exit_code, input, output))
+}
+
+fn decode_digest(
+    io: layout::OutBuffer,
+    digest: &layout::GlobalDigest,
+) -> Result<Digest, VerificationError> {
+    let bytes: Vec<u8> = io
+        .tree(digest)
+        .get_bytes()
+        .or(Err(VerificationError::ReceiptFormatError))?;
+    Digest::try_from(bytes).or(Err(VerificationError::ReceiptFormatError))
+}
+
+fn decode_system_state(
+    io: layout::OutBuffer,
+    sys_state: &layout::SystemState,
+) -> Result<SystemState, VerificationError> {
+    let merkle_root = decode_digest(io, sys_state.image_id)?;
+    let pc = io
+        .tree(sys_state.pc)
+        .get_u32()
+        .or(Err(VerificationError::ReceiptFormatError))?;
+    Ok(SystemState { pc, merkle_root })
+}
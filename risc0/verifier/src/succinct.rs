@@ -0,0 +1,76 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use alloc::{collections::VecDeque, vec::Vec};
+
+use hex::FromHex;
+use risc0_binfmt::read_sha_halfs;
+use risc0_circuit_recursion::{
+    control_id::{ALLOWED_CONTROL_IDS, ALLOWED_CONTROL_ROOT},
+    CircuitImpl, CIRCUIT,
+};
+use risc0_core::field::baby_bear::BabyBearElem;
+use risc0_zkp::{
+    adapter::CircuitInfo as _,
+    core::{digest::Digest, hash::poseidon2::Poseidon2HashSuite},
+    verify::VerificationError,
+};
+
+use crate::Claim;
+
+/// Verify the seal of a succinct receipt for the given claim.
+///
+/// The seal must be made by one of the recursion programs of this release,
+/// with the control root of this release.
+pub fn verify_succinct(seal: &[u32], claim: &Claim) -> Result<(), VerificationError> {
+    let check_code = |_, control_id: &Digest| {
+        ALLOWED_CONTROL_IDS
+            .iter()
+            .any(|x| Digest::from_hex(x).unwrap() == *control_id)
+            .then_some(())
+            .ok_or(VerificationError::ControlVerificationError {
+                control_id: *control_id,
+            })
+    };
+    let suite = Poseidon2HashSuite::new_suite();
+    risc0_zkp::verify::verify(&CIRCUIT, &suite, seal, check_code)?;
+
+    if seal.len() < CircuitImpl::OUTPUT_SIZE {
+        return Err(VerificationError::ReceiptFormatError);
+    }
+    let output_elems: &[BabyBearElem] =
+        bytemuck::checked::try_cast_slice(&seal[..CircuitImpl::OUTPUT_SIZE])
+            .or(Err(VerificationError::ReceiptFormatError))?;
+    let mut output: VecDeque<u32> = output_elems.iter().map(|elem| elem.as_u32()).collect();
+
+    // The first output slot is the Poseidon2 control root, as eight elements
+    // interspersed with padding, and the second the claim digest, as SHA-256
+    // half words.
+    let control_root: Digest = output
+        .drain(0..16)
+        .step_by(2)
+        .collect::<Vec<_>>()
+        .try_into()
+        .or(Err(VerificationError::ReceiptFormatError))?;
+    if control_root != Digest::from_hex(ALLOWED_CONTROL_ROOT).unwrap() {
+        return Err(VerificationError::ControlVerificationError {
+            control_id: control_root,
+        });
+    }
+    let claim_digest =
+        read_sha_halfs(&mut output).or(Err(VerificationError::ReceiptFormatError))?;
+    if claim_digest != claim.digest() {
+        return Err(VerificationError::JournalDigestMismatch);
+    }
+    Ok(())
+}
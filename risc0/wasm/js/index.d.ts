@@ -0,0 +1,110 @@
+import type { Journal } from "./journal.js";
+
+export { Journal, JournalReader } from "./journal.js";
+
+/**
+ * An image ID: 64 hex digits, optionally prefixed with `0x`, 32 bytes, or the
+ * 8 words of the `_ID` constants generated for guests by risc0-build.
+ */
+export type ImageId = string | Uint8Array | Uint32Array | number[];
+
+export type ReceiptKind = "composite" | "succinct" | "compact" | "plonk" | "fake";
+
+export type ExitKind = "Halted" | "Paused" | "SystemSplit" | "SessionLimit";
+
+/** The claim of a receipt, with its digests in hex. */
+export interface ReceiptClaim {
+  imageId: string;
+  exitKind: ExitKind;
+  /** The exit code of the guest, when it halted or paused. */
+  exitUserCode?: number;
+  claimDigest: string;
+  inputDigest: string;
+  postStateDigest: string;
+}
+
+/**
+ * A decoded receipt. Its contents are only authenticated once the receipt is
+ * verified with `verifyReceipt`.
+ */
+export interface Receipt {
+  kind: ReceiptKind;
+  journal: Journal;
+  /** The claim, unless it cannot be extracted from the receipt. */
+  claim?: ReceiptClaim;
+  /** The total size of the seals, in bytes. */
+  sealSize: number;
+  /** The hash functions, or proof systems, the seals are verified with. */
+  hashfns: string[];
+}
+
+/**
+ * Load the WebAssembly module, which every other function needs. In Node it is
+ * read from the package; in browsers it is fetched next to the package, unless
+ * `module` gives its URL, response or bytes. Calling `init` again returns the
+ * same promise.
+ */
+export declare function init(
+  module?: string | URL | Response | BufferSource | WebAssembly.Module,
+): Promise<void>;
+
+/**
+ * Verify that a receipt, in the bincode encoding produced by `risc0-zkvm`,
+ * `r0vm` and Bonsai, proves a successful execution of the image with
+ * `imageId`. Throws an `Error` describing why if it does not.
+ */
+export declare function verifyReceipt(receipt: Uint8Array, imageId: ImageId): void;
+
+/** Decode a receipt, throwing an `Error` if it is malformed. */
+export declare function decodeReceipt(receipt: Uint8Array): Receipt;
+
+/** Normalize an image ID to 64 lowercase hex digits. */
+export declare function imageIdToHex(imageId: ImageId): string;
+
+/** Limits on the execution of a guest in the browser. */
+export interface ExecuteOptions {
+  /** The size of segments, as a power of two of cycles, at most 20. */
+  segmentPo2?: number;
+  /** The most cycles the guest may run for, by default 2^26. */
+  maxCycles?: number | bigint;
+}
+
+/** A summary of the execution of a guest. */
+export interface SessionInfo {
+  imageId: string;
+  exitKind: ExitKind;
+  /** The exit code of the guest, when it halted or paused. */
+  exitUserCode?: number;
+  journal: Journal;
+  segments: number;
+  userCycles: number;
+  totalCycles: number;
+}
+
+export interface ProveOptions {
+  /**
+   * Produce a fake receipt, which only verifies in dev mode. This build has no
+   * WebGPU prover, so proving fails unless this is set.
+   */
+  devMode?: boolean;
+}
+
+/**
+ * Execute a guest ELF in the browser, with `input` as its stdin, so that the
+ * input never leaves the client. Needs a build with the `execute` feature.
+ */
+export declare function executeGuest(
+  elf: Uint8Array,
+  input?: Uint8Array,
+  options?: ExecuteOptions,
+): SessionInfo;
+
+/**
+ * Execute a guest ELF and return a receipt of the execution, in bincode.
+ * Needs a build with the `execute` feature, and `devMode`.
+ */
+export declare function proveGuest(
+  elf: Uint8Array,
+  input?: Uint8Array,
+  options?: ProveOptions,
+): Uint8Array;
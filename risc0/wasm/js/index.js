@@ -0,0 +1,107 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+import initWasm, * as wasm from "../dist/risc0_wasm.js";
+import { Journal, fromHex, toHex } from "./journal.js";
+
+export { Journal, JournalReader } from "./journal.js";
+
+let loading;
+let loaded = false;
+
+export function init(module) {
+  loading ??= load(module);
+  return loading;
+}
+
+async function load(module) {
+  let source = module;
+  // Node has no fetch for file URLs, so the module is read from disk there.
+  if (source === undefined && globalThis.process?.versions?.node) {
+    const { readFile } = await import("node:fs/promises");
+    source = await readFile(new URL("../dist/risc0_wasm_bg.wasm", import.meta.url));
+  }
+  await initWasm(source);
+  loaded = true;
+}
+
+function bindings() {
+  if (!loaded) {
+    throw new Error("@risc0/verifier is not loaded, await init() first");
+  }
+  return wasm;
+}
+
+export function imageIdToHex(imageId) {
+  if (typeof imageId === "string") {
+    const hex = imageId.replace(/^0x/, "");
+    fromHex(hex);
+    if (hex.length !== 64) {
+      throw new Error(`an image ID is 32 bytes, not ${hex.length / 2}`);
+    }
+    return hex.toLowerCase();
+  }
+  if (imageId instanceof Uint8Array) {
+    if (imageId.length !== 32) {
+      throw new Error(`an image ID is 32 bytes, not ${imageId.length}`);
+    }
+    return toHex(imageId);
+  }
+  // The `[u32; 8]` form of image IDs generated for guests by risc0-build.
+  if (Array.isArray(imageId) || imageId instanceof Uint32Array) {
+    if (imageId.length !== 8) {
+      throw new Error(`an image ID is 8 words, not ${imageId.length}`);
+    }
+    const bytes = new Uint8Array(32);
+    const view = new DataView(bytes.buffer);
+    imageId.forEach((word, i) => view.setUint32(4 * i, word, true));
+    return toHex(bytes);
+  }
+  throw new TypeError("an image ID is a hex string, a Uint8Array or an array of 8 words");
+}
+
+export function verifyReceipt(receipt, imageId) {
+  bindings().verifyReceipt(receipt, imageIdToHex(imageId));
+}
+
+export function decodeReceipt(receipt) {
+  const info = JSON.parse(bindings().inspectReceipt(receipt));
+  return {
+    kind: info.kind,
+    journal: new Journal(fromHex(info.journal)),
+    claim: info.claim ?? undefined,
+    sealSize: info.sealSize,
+    hashfns: info.hashfns,
+  };
+}
+
+function executeBindings() {
+  const api = bindings();
+  if (api.executeGuest === undefined) {
+    throw new Error("@risc0/verifier was built without the execute feature");
+  }
+  return api;
+}
+
+export function executeGuest(elf, input = new Uint8Array(), options = {}) {
+  const maxCycles = options.maxCycles === undefined ? undefined : BigInt(options.maxCycles);
+  const info = JSON.parse(
+    executeBindings().executeGuest(elf, input, options.segmentPo2, maxCycles),
+  );
+  return { ...info, journal: new Journal(fromHex(info.journal)) };
+}
+
+export function proveGuest(elf, input = new Uint8Array(), options = {}) {
+  return executeBindings().proveGuest(elf, input, options.devMode === true);
+}
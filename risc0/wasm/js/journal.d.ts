@@ -0,0 +1,44 @@
+/** The journal of a receipt: the bytes its guest committed. */
+export declare class Journal {
+  constructor(bytes: Uint8Array | ArrayLike<number>);
+  readonly bytes: Uint8Array;
+  toHex(): string;
+  /** Read the values the guest committed with `env::commit`. */
+  reader(): JournalReader;
+}
+
+/**
+ * Reads values in the order a guest committed them with `env::commit`, in the
+ * word-oriented encoding of `risc0_zkvm::serde`. Each method throws if the
+ * journal ends before the value does.
+ *
+ * Bytes committed with `env::commit_slice` are read with `readRaw`.
+ */
+export declare class JournalReader {
+  constructor(bytes: Uint8Array);
+  /** The number of bytes not read yet. */
+  readonly remaining: number;
+  readRaw(len: number): Uint8Array;
+  readU8(): number;
+  readU16(): number;
+  readU32(): number;
+  readI8(): number;
+  readI16(): number;
+  readI32(): number;
+  readU64(): bigint;
+  readI64(): bigint;
+  readU128(): bigint;
+  readI128(): bigint;
+  readF32(): number;
+  readF64(): number;
+  readBool(): boolean;
+  readString(): string;
+  /** Read a byte string encoded with `serde_bytes`. */
+  readBytes(): Uint8Array;
+  /** Read a `Digest`, such as an image ID, as hex. */
+  readDigest(): string;
+  /** Read an `Option<T>`, with `undefined` for `None`. */
+  readOption<T>(readValue: (reader: JournalReader) => T): T | undefined;
+  /** Read a `Vec<T>`. A `Vec<u8>` is read with `readVec((r) => r.readU8())`. */
+  readVec<T>(readValue: (reader: JournalReader) => T): T[];
+}
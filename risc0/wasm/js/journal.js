@@ -0,0 +1,176 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+const WORD_SIZE = 4;
+
+export function toHex(bytes) {
+  return Array.from(bytes, (byte) => byte.toString(16).padStart(2, "0")).join("");
+}
+
+export function fromHex(hex) {
+  if (hex.length % 2 !== 0 || /[^0-9a-fA-F]/.test(hex)) {
+    throw new Error(`malformed hex: ${hex}`);
+  }
+  const bytes = new Uint8Array(hex.length / 2);
+  for (let i = 0; i < bytes.length; i++) {
+    bytes[i] = Number.parseInt(hex.slice(2 * i, 2 * i + 2), 16);
+  }
+  return bytes;
+}
+
+export class Journal {
+  constructor(bytes) {
+    this.bytes = bytes instanceof Uint8Array ? bytes : Uint8Array.from(bytes);
+  }
+
+  toHex() {
+    return toHex(this.bytes);
+  }
+
+  reader() {
+    return new JournalReader(this.bytes);
+  }
+}
+
+// Reads values in the order a guest committed them with `env::commit`, in the
+// word-oriented encoding of `risc0_zkvm::serde`.
+export class JournalReader {
+  #bytes;
+  #view;
+  #offset = 0;
+
+  constructor(bytes) {
+    this.#bytes = bytes;
+    this.#view = new DataView(bytes.buffer, bytes.byteOffset, bytes.byteLength);
+  }
+
+  get remaining() {
+    return this.#bytes.length - this.#offset;
+  }
+
+  #take(len) {
+    if (len > this.remaining) {
+      throw new Error(`journal ended: read ${len} bytes at offset ${this.#offset} of ${this.#bytes.length}`);
+    }
+    const start = this.#offset;
+    this.#offset += len;
+    return start;
+  }
+
+  readRaw(len) {
+    const start = this.#take(len);
+    return this.#bytes.slice(start, start + len);
+  }
+
+  readU32() {
+    return this.#view.getUint32(this.#take(WORD_SIZE), true);
+  }
+
+  readI32() {
+    return this.#view.getInt32(this.#take(WORD_SIZE), true);
+  }
+
+  readU8() {
+    return this.readU32() & 0xff;
+  }
+
+  readU16() {
+    return this.readU32() & 0xffff;
+  }
+
+  readI8() {
+    return (this.readI32() << 24) >> 24;
+  }
+
+  readI16() {
+    return (this.readI32() << 16) >> 16;
+  }
+
+  readBool() {
+    const value = this.readU32();
+    if (value > 1) {
+      throw new Error(`invalid bool ${value}`);
+    }
+    return value === 1;
+  }
+
+  readU64() {
+    const low = BigInt(this.readU32());
+    const high = BigInt(this.readU32());
+    return (high << 32n) | low;
+  }
+
+  readI64() {
+    return BigInt.asIntN(64, this.readU64());
+  }
+
+  readU128() {
+    const bytes = this.#readPadded(16);
+    let value = 0n;
+    for (let i = bytes.length - 1; i >= 0; i--) {
+      value = (value << 8n) | BigInt(bytes[i]);
+    }
+    return value;
+  }
+
+  readI128() {
+    return BigInt.asIntN(128, this.readU128());
+  }
+
+  readF32() {
+    return this.#view.getFloat32(this.#take(WORD_SIZE), true);
+  }
+
+  readF64() {
+    const start = this.#take(2 * WORD_SIZE);
+    return this.#view.getFloat64(start, true);
+  }
+
+  // Strings and `serde_bytes` are a length followed by bytes padded to a word.
+  #readPadded(len) {
+    const padded = Math.ceil(len / WORD_SIZE) * WORD_SIZE;
+    return this.readRaw(padded).slice(0, len);
+  }
+
+  readString() {
+    return new TextDecoder("utf-8", { fatal: true }).decode(this.#readPadded(this.readU32()));
+  }
+
+  readBytes() {
+    return this.#readPadded(this.readU32());
+  }
+
+  // A digest is eight words, whose bytes are those of the digest.
+  readDigest() {
+    return toHex(this.readRaw(8 * WORD_SIZE));
+  }
+
+  readOption(readValue) {
+    switch (this.readU32()) {
+      case 0:
+        return undefined;
+      case 1:
+        return readValue(this);
+      default:
+        throw new Error("invalid option tag");
+    }
+  }
+
+  // Sequences, such as a `Vec<T>`, are a length followed by the elements. A
+  // `Vec<u8>` is a `Vec` of words, one per byte.
+  readVec(readValue) {
+    const len = this.readU32();
+    return Array.from({ length: len }, () => readValue(this));
+  }
+}
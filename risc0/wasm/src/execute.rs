@@ -0,0 +1,139 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bindings for executing guests in the browser, so that private inputs never
+//! leave the client.
+//!
+//! There is no WebGPU prover yet, so the only receipts produced here are fake
+//! ones, and only when the caller asks for dev mode.
+
+use anyhow::{bail, ensure, Result};
+use risc0_zkvm::{
+    sha::Digestible, ExecutorEnv, ExecutorImpl, InnerReceipt, NullSegmentRef, Receipt, Session,
+};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+/// The largest segment a browser executes, as a power of two of cycles.
+pub const MAX_SEGMENT_PO2: u32 = 20;
+
+/// The default limit on the cycles of a session executed in the browser.
+pub const DEFAULT_MAX_CYCLES: u64 = 1 << 26;
+
+/// A summary of a session, returned as JSON by [execute_guest].
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionInfo {
+    image_id: String,
+    exit_kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exit_user_code: Option<u32>,
+    journal: String,
+    segments: usize,
+    user_cycles: u64,
+    total_cycles: u64,
+}
+
+fn execute(
+    elf: &[u8],
+    input: &[u8],
+    segment_po2: Option<u32>,
+    max_cycles: Option<u64>,
+) -> Result<Session> {
+    let segment_po2 = segment_po2.unwrap_or(MAX_SEGMENT_PO2);
+    ensure!(
+        segment_po2 <= MAX_SEGMENT_PO2,
+        "segments of 2^{segment_po2} cycles are too large for a browser, the limit is 2^{MAX_SEGMENT_PO2}"
+    );
+    let env = ExecutorEnv::builder()
+        .write_slice(input)
+        .segment_limit_po2(segment_po2)
+        .session_limit(Some(max_cycles.unwrap_or(DEFAULT_MAX_CYCLES)))
+        .build()?;
+    // Browsers have no file system to keep segments in, and nothing proves
+    // them, so they are dropped as they are produced.
+    ExecutorImpl::from_elf(env, elf)?.run_with_callback(|_| Ok(Box::new(NullSegmentRef)))
+}
+
+fn describe(session: &Session) -> Result<SessionInfo> {
+    let claim = session.claim()?;
+    let (exit_kind, exit_user_code) = crate::exit_kind(claim.exit_code);
+    Ok(SessionInfo {
+        image_id: claim.pre.digest().to_string(),
+        exit_kind,
+        exit_user_code,
+        journal: hex::encode(session.journal.as_ref().map_or(&[][..], |j| &j.bytes)),
+        segments: session.segments.len(),
+        user_cycles: session.user_cycles,
+        total_cycles: session.total_cycles,
+    })
+}
+
+fn prove(elf: &[u8], input: &[u8], dev_mode: bool) -> Result<Receipt> {
+    if !dev_mode {
+        bail!("this build cannot prove in the browser, pass devMode to produce a fake receipt");
+    }
+    let session = execute(elf, input, None, None)?;
+    let claim = session.claim()?;
+    Ok(Receipt::new(
+        InnerReceipt::Fake { claim },
+        session.journal.map(|j| j.bytes).unwrap_or_default(),
+    ))
+}
+
+/// Execute the guest ELF with the given input, which it reads from stdin, and
+/// describe the session as JSON.
+#[wasm_bindgen(js_name = executeGuest)]
+pub fn execute_guest(
+    elf: &[u8],
+    input: &[u8],
+    segment_po2: Option<u32>,
+    max_cycles: Option<u64>,
+) -> Result<String, JsError> {
+    let info = execute(elf, input, segment_po2, max_cycles)
+        .and_then(|session| describe(&session))
+        .map_err(|err| JsError::new(&format!("{err:#}")))?;
+    Ok(serde_json::to_string(&info).unwrap())
+}
+
+/// Execute the guest ELF and return a receipt of the execution, in bincode.
+///
+/// The receipt is fake, and only verifies in dev mode, so it is only produced
+/// when `dev_mode` is set.
+#[wasm_bindgen(js_name = proveGuest)]
+pub fn prove_guest(elf: &[u8], input: &[u8], dev_mode: bool) -> Result<Vec<u8>, JsError> {
+    let receipt = prove(elf, input, dev_mode).map_err(|err| JsError::new(&format!("{err:#}")))?;
+    Ok(bincode::serialize(&receipt).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prove_needs_dev_mode() {
+        let err = prove(&[], &[], false).unwrap_err().to_string();
+        assert!(err.contains("pass devMode"), "{err}");
+    }
+
+    #[test]
+    fn execute_errors() {
+        let err = execute(&[], &[], Some(MAX_SEGMENT_PO2 + 1), None)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("too large for a browser"), "{err}");
+        assert!(execute(b"not an elf", &[], None, None).is_err());
+    }
+}
@@ -0,0 +1,164 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! WebAssembly bindings for verifying RISC Zero receipts, built with
+//! `wasm-pack` into the `@risc0/verifier` npm package.
+//!
+//! The bindings are low level: receipts are passed in the bincode encoding of
+//! [Receipt], image IDs as hex, and descriptions of receipts are returned as
+//! JSON. The JavaScript wrapper in `js/` turns these into typed objects.
+//!
+//! With the `execute` feature, guests can also be executed in the browser.
+
+#[cfg(feature = "execute")]
+mod execute;
+
+use hex::FromHex;
+use risc0_zkvm::{
+    inspect,
+    sha::{Digest, Digestible},
+    ExitCode, Receipt,
+};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+/// A description of a receipt, returned as JSON by [inspect_receipt].
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReceiptInfo {
+    kind: &'static str,
+    journal: String,
+    claim: Option<ClaimInfo>,
+    seal_size: usize,
+    hashfns: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ClaimInfo {
+    image_id: String,
+    exit_kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exit_user_code: Option<u32>,
+    claim_digest: String,
+    input_digest: String,
+    post_state_digest: String,
+}
+
+fn decode_receipt(receipt: &[u8]) -> Result<Receipt, String> {
+    bincode::deserialize(receipt).map_err(|err| format!("malformed receipt: {err}"))
+}
+
+fn verify(receipt: &[u8], image_id: &str) -> Result<(), String> {
+    let image_id = Digest::from_hex(image_id.trim_start_matches("0x"))
+        .map_err(|err| format!("malformed image ID: {err}"))?;
+    decode_receipt(receipt)?
+        .verify(image_id)
+        .map_err(|err| err.to_string())
+}
+
+fn exit_kind(exit_code: ExitCode) -> (&'static str, Option<u32>) {
+    match exit_code {
+        ExitCode::Halted(code) => ("Halted", Some(code)),
+        ExitCode::Paused(code) => ("Paused", Some(code)),
+        ExitCode::SystemSplit => ("SystemSplit", None),
+        ExitCode::SessionLimit => ("SessionLimit", None),
+    }
+}
+
+fn describe(receipt: &Receipt) -> ReceiptInfo {
+    let inspection = inspect(receipt);
+    let claim = inspection.claim.as_ref().map(|claim| {
+        let (exit_kind, exit_user_code) = exit_kind(claim.exit_code);
+        ClaimInfo {
+            image_id: claim.pre.digest().to_string(),
+            exit_kind,
+            exit_user_code,
+            claim_digest: claim.digest().to_string(),
+            input_digest: claim.input.to_string(),
+            post_state_digest: claim.post.digest().to_string(),
+        }
+    });
+    ReceiptInfo {
+        // Drop the " receipt" of kinds such as "composite receipt".
+        kind: inspection.kind.trim_end_matches(" receipt"),
+        journal: hex::encode(&receipt.journal.bytes),
+        claim,
+        seal_size: inspection.seal_size(),
+        hashfns: inspection
+            .hashfns()
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+    }
+}
+
+/// Verify that the receipt proves a successful execution of the image with the
+/// given ID, in hex.
+#[wasm_bindgen(js_name = verifyReceipt)]
+pub fn verify_receipt(receipt: &[u8], image_id: &str) -> Result<(), JsError> {
+    verify(receipt, image_id).map_err(|err| JsError::new(&err))
+}
+
+/// Describe the kind, journal, claim and seals of the receipt, as JSON.
+///
+/// The description is only authenticated once the receipt is verified with
+/// [verify_receipt].
+#[wasm_bindgen(js_name = inspectReceipt)]
+pub fn inspect_receipt(receipt: &[u8]) -> Result<String, JsError> {
+    let info = describe(&decode_receipt(receipt).map_err(|err| JsError::new(&err))?);
+    Ok(serde_json::to_string(&info).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkvm::{InnerReceipt, MaybePruned, ReceiptClaim};
+
+    use super::*;
+
+    fn fake_receipt(image_id: Digest, journal: &[u8]) -> Receipt {
+        let claim = ReceiptClaim {
+            pre: MaybePruned::Pruned(image_id),
+            post: MaybePruned::Pruned(Digest::ZERO),
+            exit_code: ExitCode::Halted(0),
+            input: Digest::ZERO,
+            output: MaybePruned::Value(None),
+        };
+        Receipt::new(InnerReceipt::Fake { claim }, journal.to_vec())
+    }
+
+    #[test]
+    fn describe_fake() {
+        let image_id = Digest::from([1, 2, 3, 4, 5, 6, 7, 8]);
+        let receipt = fake_receipt(image_id, b"journal");
+        let info = serde_json::to_value(describe(&receipt)).unwrap();
+        assert_eq!(info["kind"], "fake");
+        assert_eq!(info["journal"], hex::encode(b"journal"));
+        assert_eq!(info["sealSize"], 0);
+        assert_eq!(info["claim"]["imageId"], image_id.to_string());
+        assert_eq!(info["claim"]["exitKind"], "Halted");
+        assert_eq!(info["claim"]["exitUserCode"], 0);
+    }
+
+    #[test]
+    fn verify_errors() {
+        let receipt = bincode::serialize(&fake_receipt(Digest::ZERO, &[])).unwrap();
+        let err = verify(&receipt, "not hex").unwrap_err();
+        assert!(err.starts_with("malformed image ID"), "{err}");
+        let err = verify(&receipt[1..], &Digest::ZERO.to_string()).unwrap_err();
+        assert!(err.starts_with("malformed receipt"), "{err}");
+        // Fake receipts only verify in dev mode, which a browser never is in.
+        assert!(verify(&receipt, &Digest::ZERO.to_string()).is_err());
+    }
+}
@@ -0,0 +1,63 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+import assert from "node:assert/strict";
+import { test } from "node:test";
+
+import { Journal, fromHex } from "../js/journal.js";
+
+function words(...values) {
+  const bytes = new Uint8Array(4 * values.length);
+  const view = new DataView(bytes.buffer);
+  values.forEach((value, i) => view.setUint32(4 * i, value, true));
+  return bytes;
+}
+
+test("reads the encoding of env::commit", () => {
+  // (u32, i32, bool, u64, String, Option<u8>, Vec<u16>) as encoded by
+  // risc0_zkvm::serde::to_vec.
+  const journal = new Journal([
+    ...words(7, 0xfffffffe, 1, 0x89abcdef, 0x01234567, 5),
+    ...new TextEncoder().encode("hello\0\0\0"),
+    ...words(1, 0xff, 2, 10, 20),
+  ]);
+  const reader = journal.reader();
+  assert.equal(reader.readU32(), 7);
+  assert.equal(reader.readI32(), -2);
+  assert.equal(reader.readBool(), true);
+  assert.equal(reader.readU64(), 0x0123456789abcdefn);
+  assert.equal(reader.readString(), "hello");
+  assert.equal(reader.readOption((r) => r.readU8()), 0xff);
+  assert.deepEqual(reader.readVec((r) => r.readU16()), [10, 20]);
+  assert.equal(reader.remaining, 0);
+  assert.throws(() => reader.readU32(), /journal ended/);
+});
+
+test("reads digests and wide integers", () => {
+  const digest = "0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20";
+  const reader = new Journal([
+    ...fromHex(digest),
+    ...words(0, 0, 0, 0x80000000, 0, 0, 0xffffffff),
+  ]).reader();
+  assert.equal(reader.readDigest(), digest);
+  assert.equal(reader.readI128(), -(1n << 127n));
+  assert.equal(reader.readOption((r) => r.readU32()), undefined);
+  assert.equal(reader.readI64(), -4294967296n);
+});
+
+test("rejects malformed values", () => {
+  assert.throws(() => new Journal(words(2)).reader().readBool(), /invalid bool/);
+  assert.throws(() => new Journal(words(9)).reader().readString(), /journal ended/);
+  assert.throws(() => fromHex("abc"), /malformed hex/);
+});
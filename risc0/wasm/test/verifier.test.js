@@ -0,0 +1,47 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+import assert from "node:assert/strict";
+import { before, test } from "node:test";
+
+import {
+  decodeReceipt,
+  imageIdToHex,
+  init,
+  proveGuest,
+  verifyReceipt,
+} from "../js/index.js";
+
+const IMAGE_ID = "0100000002000000030000000400000005000000060000000700000008000000";
+
+before(() => init());
+
+test("normalizes image IDs", () => {
+  assert.equal(imageIdToHex([1, 2, 3, 4, 5, 6, 7, 8]), IMAGE_ID);
+  assert.equal(imageIdToHex(Uint32Array.of(1, 2, 3, 4, 5, 6, 7, 8)), IMAGE_ID);
+  assert.equal(imageIdToHex(`0x${IMAGE_ID.toUpperCase()}`), IMAGE_ID);
+  assert.throws(() => imageIdToHex(new Uint8Array(31)), /32 bytes/);
+  assert.throws(() => imageIdToHex("1234"), /32 bytes/);
+});
+
+test("rejects malformed receipts", () => {
+  const receipt = new Uint8Array([1, 2, 3]);
+  assert.throws(() => verifyReceipt(receipt, IMAGE_ID), /malformed receipt/);
+  assert.throws(() => decodeReceipt(receipt), /malformed receipt/);
+});
+
+test("proves only in dev mode", () => {
+  // Builds without the execute feature cannot prove at all.
+  assert.throws(() => proveGuest(new Uint8Array()), /devMode|execute feature/);
+});
@@ -39,13 +39,24 @@ use crate::{
 #[non_exhaustive]
 pub enum VerificationError {
     ReceiptFormatError,
-    ControlVerificationError { control_id: Digest },
+    ControlVerificationError {
+        control_id: Digest,
+    },
     ImageVerificationError,
-    MerkleQueryOutOfRange { idx: usize, rows: usize },
+    MerkleQueryOutOfRange {
+        idx: usize,
+        rows: usize,
+    },
     InvalidProof,
     JournalDigestMismatch,
     UnexpectedExitCode,
     InvalidHashSuite,
+    MissingVerifyingKey,
+    LimitExceeded {
+        limit: &'static str,
+        value: usize,
+        max: usize,
+    },
 }
 
 impl fmt::Debug for VerificationError {
@@ -72,6 +83,12 @@ impl fmt::Display for VerificationError {
             }
             VerificationError::UnexpectedExitCode => write!(f, "Unexpected exit_code"),
             VerificationError::InvalidHashSuite => write!(f, "Invalid hash suite"),
+            VerificationError::MissingVerifyingKey => {
+                write!(f, "No verifying key is configured for this receipt")
+            }
+            VerificationError::LimitExceeded { limit, value, max } => {
+                write!(f, "{limit} of {value} exceeds the verifier limit of {max}")
+            }
         }
     }
 }
@@ -0,0 +1,39 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashMap, env};
+
+use risc0_build::{embed_methods_with_options, DockerOptions, GuestOptions};
+
+fn main() {
+    if env::var("CARGO_CFG_TARGET_OS").unwrap().contains("zkvm") {
+        // Guest shouldn't recursively depend on itself.
+        return;
+    }
+
+    // The image ID of the aggregator is only reproducible when it is built in
+    // docker, so releases should set RISC0_USE_DOCKER.
+    let use_docker = env::var("RISC0_USE_DOCKER").is_ok().then(|| DockerOptions {
+        root_dir: Some("../../..".into()),
+    });
+    embed_methods_with_options(HashMap::from([(
+        "risc0-zkvm-aggregator-guest",
+        GuestOptions {
+            use_docker,
+            ..Default::default()
+        },
+    )]));
+
+    println!("cargo:rerun-if-env-changed=RISC0_USE_DOCKER");
+}
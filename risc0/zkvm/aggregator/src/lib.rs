@@ -0,0 +1,29 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![doc = include_str!("../README.md")]
+
+use anyhow::Result;
+use risc0_zkvm::{default_prover, Receipt};
+
+include!(concat!(env!("OUT_DIR"), "/methods.rs"));
+
+/// Aggregate the receipts of unrelated sessions into one succinct [Receipt] of
+/// [AGGREGATOR_ELF], using the [default_prover].
+///
+/// See [Prover::aggregate](risc0_zkvm::Prover::aggregate) for what the receipt
+/// claims and which receipts can be aggregated.
+pub fn aggregate(receipts: &[Receipt]) -> Result<Receipt> {
+    default_prover().aggregate(AGGREGATOR_ELF, receipts)
+}
@@ -13,7 +13,7 @@
 // limitations under the License.
 
 fn main() {
-    #[cfg(feature = "prove")]
+    #[cfg(feature = "execute")]
     {
         std::env::set_var("PROTOC", protobuf_src::protoc());
         prost_build::compile_protos(
@@ -25,10 +25,13 @@ fn main() {
     #[cfg(any(feature = "client", feature = "prove"))]
     {
         std::env::set_var("PROTOC", protobuf_src::protoc());
-        prost_build::compile_protos(
-            &["src/host/protos/api.proto", "src/host/protos/core.proto"],
-            &["src/host/protos"],
-        )
-        .unwrap();
+        // Maps are ordered so that the encoding of a value is canonical.
+        prost_build::Config::new()
+            .btree_map([".protos.core"])
+            .compile_protos(
+                &["src/host/protos/api.proto", "src/host/protos/core.proto"],
+                &["src/host/protos"],
+            )
+            .unwrap();
     }
 }
@@ -21,23 +21,34 @@ extern crate alloc;
 
 use alloc::{
     alloc::{alloc_zeroed, Layout},
-    format, vec,
+    collections::BTreeMap,
+    format,
+    string::String,
+    vec,
+    vec::Vec,
+};
+use core::{
+    arch::asm,
+    cell::Cell,
+    sync::atomic::{AtomicU32, Ordering},
 };
-use core::arch::asm;
 
 use getrandom::getrandom;
 use risc0_zkp::core::hash::sha::testutil::test_sha_impl;
 use risc0_zkvm::{
-    guest::{env, memory_barrier, sha},
+    guest::{bn254, env, log, memory_barrier, sha, sig, thread, verified_file::VerifiedFile},
     sha::{Digest, Sha256},
-    ReceiptClaim,
+    Channels, ReceiptClaim,
 };
 use risc0_zkvm_methods::multi_test::{MultiTestSpec, SYS_MULTI_TEST};
 use risc0_zkvm_platform::{
     fileno,
     memory::{self, SYSTEM},
-    syscall::{bigint, sys_bigint, sys_log, sys_read, sys_read_words, sys_write},
-    PAGE_SIZE,
+    syscall::{
+        bigint, seek, sys_argc, sys_argv, sys_bigint, sys_getenv, sys_log, sys_read,
+        sys_read_words, sys_seek, sys_write,
+    },
+    PAGE_SIZE, WORD_SIZE,
 };
 
 risc0_zkvm::entry!(main);
@@ -98,6 +109,23 @@ fn main() {
             // Call an external function to make sure it's detected during profiling.
             profile_test_func1()
         }
+        MultiTestSpec::ProfileZones { panic } => {
+            risc0_zkvm::guest::profile_zone!("zone_outer");
+            let mut acc = 0u32;
+            for i in 0..1000 {
+                acc = acc.wrapping_mul(31).wrapping_add(i);
+            }
+            {
+                risc0_zkvm::guest::profile_zone!("zone_inner");
+                for i in 0..1000 {
+                    acc = acc.wrapping_mul(17).wrapping_add(i);
+                }
+                if panic {
+                    panic!("panicked in zone_inner");
+                }
+            }
+            env::commit(&acc);
+        }
         MultiTestSpec::Panic => {
             panic!("MultiTestSpec::Panic invoked");
         }
@@ -180,11 +208,272 @@ fn main() {
             }
             env::commit(&(buf, num_read));
         }
+        MultiTestSpec::SysSeek { fd } => {
+            let msg = b"hello world";
+            unsafe { sys_write(fd, msg.as_ptr(), msg.len()) };
+            let pos = sys_seek(fd, 6, seek::SET) as u32;
+            let mut buf = [0u8; 5];
+            let n = unsafe { sys_read(fd, buf.as_mut_ptr(), buf.len()) } as u32;
+            let end = sys_seek(fd, 0, seek::END) as u32;
+            env::commit(&(pos, n, buf, end));
+        }
+        MultiTestSpec::GreenThreads => {
+            risc0_zkvm::green_thread_local! {
+                static COUNT: Cell<u32> = Cell::new(0);
+            }
+            static LOG: AtomicU32 = AtomicU32::new(0);
+            fn log(id: u32) {
+                LOG.store(LOG.load(Ordering::Relaxed) * 10 + id, Ordering::Relaxed);
+            }
+            fn worker(id: u32) -> u32 {
+                for _ in 0..2 {
+                    log(id);
+                    COUNT.with(|count| count.set(count.get() + 1));
+                    thread::yield_now();
+                }
+                COUNT.with(|count| count.get())
+            }
+
+            let parker = thread::spawn(|| {
+                thread::park();
+                log(3);
+            });
+            let a = thread::spawn(|| worker(1));
+            let b = thread::spawn(|| worker(2));
+            let a = a.join().unwrap();
+            parker.thread().unpark();
+            let b = b.join().unwrap();
+            parker.join().unwrap();
+            let main = COUNT.with(|count| count.get());
+            env::commit(&(LOG.load(Ordering::Relaxed), a, b, main));
+        }
+        MultiTestSpec::HeapStats { nbytes } => {
+            let before = env::heap_stats();
+            let buf = vec![1u8; nbytes as usize];
+            memory_barrier(buf.as_ptr());
+            let after = env::heap_stats();
+            env::commit(&(
+                (after.used - before.used) as u32,
+                after.peak >= after.used,
+                (before.free - after.free) as u32,
+            ));
+        }
+        MultiTestSpec::MemoryStats {
+            heap_bytes,
+            stack_bytes,
+        } => {
+            // Use about 1 KiB of stack per call.
+            #[inline(never)]
+            fn use_stack(depth: u32) -> u32 {
+                let buf = [depth as u8; 1024];
+                memory_barrier(buf.as_ptr());
+                match depth {
+                    0 => buf[0] as u32,
+                    _ => use_stack(depth - 1) + buf[1] as u32,
+                }
+            }
+
+            let before = env::memory_stats();
+            let buf = vec![1u8; heap_bytes as usize];
+            memory_barrier(buf.as_ptr());
+            use_stack(stack_bytes / 1024);
+            let after = env::memory_stats();
+            env::commit(&(
+                (after.heap_used - before.heap_used) as u32,
+                before.stack_high_water as u32,
+                after.stack_high_water as u32,
+                before.pages_touched as u32,
+                after.pages_touched as u32,
+            ));
+        }
+        MultiTestSpec::ReadSeq => {
+            let before = env::heap_stats().used;
+            let items = env::read_seq::<u32>();
+            let len = items.len() as u32;
+            let sum: u64 = items.map(u64::from).sum();
+            let allocated = (env::heap_stats().used - before) as u32;
+            env::commit(&(len, sum, allocated));
+        }
+        MultiTestSpec::RandomBytes { len } => {
+            let seed = env::random_seed();
+            let bytes = env::random_bytes(len as usize);
+            env::commit(&(seed, bytes));
+        }
+        MultiTestSpec::HostTimestamp => {
+            env::host_timestamp();
+        }
+        MultiTestSpec::CommitWriter { rows } => {
+            use core::fmt::Write as _;
+
+            use risc0_zkvm::guest::env::Write as _;
+
+            let mut writer = env::commit_writer();
+            for i in 0..rows {
+                writeln!(writer, "row {i}").unwrap();
+            }
+            writer.write_slice(&[0xabu8; 3000]);
+        }
+        MultiTestSpec::CommittedPanic => {
+            env::commit(&1u32);
+            env::commit_panics();
+            panic!("committed panic {}", 2);
+        }
+        MultiTestSpec::Channels => {
+            env::commit(&7u32);
+            env::commit_channel_slice("result", b"result");
+            env::commit_channel("events", &1u32);
+            env::commit_channel("events", &2u32);
+        }
+        MultiTestSpec::LogEvents => {
+            log::info!("info {}", 1);
+            log::debug!(target: "multi_test::custom", "debug {}", 2);
+            log::trace!("trace");
+        }
+        MultiTestSpec::VerifiedFile { root, ranges } => {
+            let mut file = VerifiedFile::open(&root);
+            let reads: Vec<Vec<u8>> = ranges
+                .into_iter()
+                .map(|(start, end)| file.read_range(start..end))
+                .collect();
+            env::commit(&(file.len(), reads));
+        }
+        MultiTestSpec::VerifyEd25519 { sig, pk, msg } => {
+            let result = sig::verify_ed25519(
+                sig.as_slice().try_into().unwrap(),
+                pk.as_slice().try_into().unwrap(),
+                &msg,
+            );
+            env::commit(&result.is_ok());
+        }
+        MultiTestSpec::VerifySecp256k1 { sig, pk, msg_hash } => {
+            let result = sig::verify_secp256k1(
+                sig.as_slice().try_into().unwrap(),
+                &pk,
+                msg_hash.as_slice().try_into().unwrap(),
+            );
+            env::commit(&result.is_ok());
+        }
+        MultiTestSpec::VerifyRsa { sig, n, e, msg } => {
+            let result = sig::verify_rsa_pkcs1v15_sha256(&sig, &n, &e, &msg);
+            env::commit(&result.is_ok());
+        }
+        MultiTestSpec::Bn254PairingCheck { pairs } => {
+            let pairs: Vec<_> = pairs
+                .iter()
+                .map(|(p, q)| {
+                    (
+                        bn254::G1Affine::from_be_bytes(p.as_slice().try_into().unwrap()).unwrap(),
+                        bn254::G2Affine::from_be_bytes(q.as_slice().try_into().unwrap()).unwrap(),
+                    )
+                })
+                .collect();
+            env::commit(&bn254::pairing_check(&pairs));
+        }
+        MultiTestSpec::Bn254Fields { fq, fq2 } => {
+            let fq_from =
+                |bytes: &[u8]| bn254::Fq::from_be_bytes(bytes.try_into().unwrap()).unwrap();
+            let fq2_from =
+                |bytes: &[u8]| bn254::Fq2::new(fq_from(&bytes[32..]), fq_from(&bytes[..32]));
+            let fq2_to = |x: bn254::Fq2| [x.c1.to_be_bytes(), x.c0.to_be_bytes()].concat();
+
+            let (a, b) = (fq_from(&fq.0), fq_from(&fq.1));
+            let fq: Vec<Vec<u8>> = [a * b, a + b, a - b, -a, a.inverse().unwrap()]
+                .iter()
+                .map(|x| x.to_be_bytes().to_vec())
+                .collect();
+            let (a, b) = (fq2_from(&fq2.0), fq2_from(&fq2.1));
+            let fq2: Vec<Vec<u8>> = [a * b, a + b, a - b, a.square(), a.inverse().unwrap()]
+                .into_iter()
+                .map(fq2_to)
+                .collect();
+
+            // Pairings take values in the subgroup of Fq12 of norm one, in which
+            // the conjugate is the inverse.
+            let f = bn254::pairing(&bn254::G1Affine::generator(), &bn254::G2Affine::generator());
+            let inverse = f.inverse().unwrap();
+            let fq12 = [
+                f != bn254::Fq12::ONE,
+                f * inverse == bn254::Fq12::ONE,
+                f.square() == f * f,
+                f.conjugate() == inverse,
+                bn254::Fq::ZERO.inverse().is_none() && bn254::Fq2::ZERO.inverse().is_none(),
+            ];
+            env::commit(&(fq, fq2, fq12.to_vec()));
+        }
+        MultiTestSpec::Bn254Curve { g1, g2, scalar } => {
+            let scalar: [u8; 32] = scalar.as_slice().try_into().unwrap();
+            let p = bn254::G1Affine::from_be_bytes(g1.as_slice().try_into().unwrap()).unwrap();
+            let g1: Vec<Vec<u8>> = [p + p, p + p + p, p.mul(&scalar), p + -p]
+                .iter()
+                .map(|x| x.to_be_bytes().to_vec())
+                .collect();
+            let q = bn254::G2Affine::from_be_bytes(g2.as_slice().try_into().unwrap()).unwrap();
+            let g2: Vec<Vec<u8>> = [q + q, q + q + q, q.mul(&scalar), q + -q]
+                .iter()
+                .map(|x| x.to_be_bytes().to_vec())
+                .collect();
+            env::commit(&(g1, g2));
+        }
+        MultiTestSpec::Bn254Msm { scalars } => {
+            let scalars: Vec<[u8; 32]> = scalars
+                .iter()
+                .map(|scalar| scalar.as_slice().try_into().unwrap())
+                .collect();
+            let generator = bn254::G1Affine::generator();
+            let points: Vec<_> = core::iter::successors(Some(generator), |p| Some(*p + generator))
+                .take(scalars.len())
+                .collect();
+            let naive = points
+                .iter()
+                .zip(&scalars)
+                .fold(bn254::G1Affine::identity(), |acc, (p, scalar)| {
+                    acc + p.mul(scalar)
+                });
+            let msm = bn254::g1_msm(&points, &scalars);
+            env::commit(&(msm.to_be_bytes().to_vec(), naive.to_be_bytes().to_vec()));
+        }
+        MultiTestSpec::Bn254Decode { g1, g2 } => {
+            let g1: Vec<Option<String>> = g1
+                .iter()
+                .map(|p| {
+                    bn254::G1Affine::from_be_bytes(p.as_slice().try_into().unwrap())
+                        .err()
+                        .map(|err| format!("{err}"))
+                })
+                .collect();
+            let g2: Vec<Option<String>> = g2
+                .iter()
+                .map(|q| {
+                    bn254::G2Affine::from_be_bytes(q.as_slice().try_into().unwrap())
+                        .err()
+                        .map(|err| format!("{err}"))
+                })
+                .collect();
+            env::commit(&(g1, g2));
+        }
         MultiTestSpec::SysVerify(pairs) => {
             for (image_id, journal) in pairs.into_iter() {
                 env::verify(image_id, &journal).unwrap();
             }
         }
+        MultiTestSpec::SysVerifyWithChannels {
+            image_id,
+            journal,
+            channels,
+        } => {
+            let channels = Channels::from(channels.into_iter().collect::<BTreeMap<_, _>>());
+            env::verify_with_channels(image_id, &journal, &channels).unwrap();
+        }
+        MultiTestSpec::AuditAssumptions(pairs) => {
+            for (image_id, journal) in pairs.into_iter() {
+                env::verify(image_id, &journal).unwrap();
+            }
+            env::commit(&(env::assumptions().to_vec(), env::assumptions_digest()));
+        }
+        MultiTestSpec::SysCall { image_id, input } => {
+            let journal = env::call(image_id, &input).unwrap();
+            env::commit_slice(journal);
+        }
         MultiTestSpec::SysVerifyIntegrity { claim_words } => {
             let claim: ReceiptClaim = risc0_zkvm::serde::from_slice(&claim_words).unwrap();
             env::verify_integrity(&claim).unwrap();
@@ -332,5 +621,34 @@ fn main() {
                 assert_eq!(*value, 0);
             }
         }
+        MultiTestSpec::ArgsAndEnv { vars } => {
+            // Read the arguments and variables through the same syscalls as std::env.
+            let args: Vec<String> = (0..sys_argc())
+                .map(|idx| host_string(|buf, nwords| unsafe { sys_argv(buf, nwords, idx) }))
+                .map(Option::unwrap)
+                .collect();
+            let values: Vec<Option<String>> = vars
+                .iter()
+                .map(|var| {
+                    host_string(|buf, nwords| unsafe {
+                        sys_getenv(buf, nwords, var.as_ptr(), var.len())
+                    })
+                })
+                .collect();
+            env::commit(&(args, values));
+        }
+    }
+}
+
+// Read a string from the host by asking for its length, and then for its bytes in a buffer large
+// enough to hold them. A length of usize::MAX means there is no such string.
+fn host_string(read: impl Fn(*mut u32, usize) -> usize) -> Option<String> {
+    let len = read(core::ptr::null_mut(), 0);
+    if len == usize::MAX {
+        return None;
     }
+    let mut words = vec![0u32; len.div_ceil(WORD_SIZE)];
+    read(words.as_mut_ptr(), words.len());
+    let bytes = words.iter().flat_map(|word| word.to_le_bytes()).take(len);
+    Some(String::from_utf8(bytes.collect()).unwrap())
 }
@@ -15,7 +15,7 @@
 // Definitions for test selection codes used by the "multi_test" test.
 extern crate alloc;
 
-use alloc::vec::Vec;
+use alloc::{string::String, vec::Vec};
 
 use risc0_zkvm::{declare_syscall, sha::Digest};
 use risc0_zkvm_platform::syscall::bigint;
@@ -35,6 +35,9 @@ pub enum MultiTestSpec {
     },
     EventTrace,
     Profiler,
+    ProfileZones {
+        panic: bool,
+    },
     Panic,
     Fault,
     Halt(u8),
@@ -56,7 +59,85 @@ pub enum MultiTestSpec {
         // Position and length to do reads
         pos_and_len: Vec<(u32, u32)>,
     },
+    SysSeek {
+        fd: u32,
+    },
+    GreenThreads,
+    HeapStats {
+        nbytes: u32,
+    },
+    MemoryStats {
+        heap_bytes: u32,
+        stack_bytes: u32,
+    },
+    // Followed by a Vec<u32> on stdin.
+    ReadSeq,
+    RandomBytes {
+        len: u32,
+    },
+    HostTimestamp,
+    LogEvents,
+    CommittedPanic,
+    CommitWriter {
+        rows: u32,
+    },
+    Channels,
+    VerifiedFile {
+        root: Digest,
+        ranges: Vec<(u64, u64)>,
+    },
+    VerifyEd25519 {
+        sig: Vec<u8>,
+        pk: Vec<u8>,
+        msg: Vec<u8>,
+    },
+    VerifySecp256k1 {
+        sig: Vec<u8>,
+        pk: Vec<u8>,
+        msg_hash: Vec<u8>,
+    },
+    VerifyRsa {
+        sig: Vec<u8>,
+        n: Vec<u8>,
+        e: Vec<u8>,
+        msg: Vec<u8>,
+    },
+    Bn254PairingCheck {
+        // Pairs of G1 and G2 points, in the encoding of the Ethereum precompiles.
+        pairs: Vec<(Vec<u8>, Vec<u8>)>,
+    },
+    Bn254Fields {
+        // Two elements each of Fq and Fq2, big-endian, with each element of Fq2
+        // encoded as `c1 || c0`.
+        fq: (Vec<u8>, Vec<u8>),
+        fq2: (Vec<u8>, Vec<u8>),
+    },
+    Bn254Curve {
+        // A point of G1 and a point of G2, in the encoding of the Ethereum
+        // precompiles, and a big-endian scalar.
+        g1: Vec<u8>,
+        g2: Vec<u8>,
+        scalar: Vec<u8>,
+    },
+    Bn254Msm {
+        // Big-endian scalars for successive multiples of the G1 generator.
+        scalars: Vec<Vec<u8>>,
+    },
+    Bn254Decode {
+        g1: Vec<Vec<u8>>,
+        g2: Vec<Vec<u8>>,
+    },
     SysVerify(Vec<(Digest, Vec<u8>)>),
+    SysVerifyWithChannels {
+        image_id: Digest,
+        journal: Vec<u8>,
+        channels: Vec<(String, Vec<u8>)>,
+    },
+    AuditAssumptions(Vec<(Digest, Vec<u8>)>),
+    SysCall {
+        image_id: Digest,
+        input: Vec<u8>,
+    },
     SysVerifyIntegrity {
         // Define this field as a serialized vector to avoid circular dependency issues.
         claim_words: Vec<u32>,
@@ -90,6 +171,10 @@ pub enum MultiTestSpec {
     TooManySha,
     AlignedAlloc,
     AllocZeroed,
+    ArgsAndEnv {
+        /// Names of the environment variables to look up.
+        vars: Vec<String>,
+    },
 }
 
 declare_syscall!(pub SYS_MULTI_TEST);
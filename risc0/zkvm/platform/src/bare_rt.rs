@@ -0,0 +1,87 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A minimal runtime for bare-metal `#![no_std]` and `#![no_main]` guests,
+//! enabled by the `bare-metal` feature.
+//!
+//! Unlike the `rust-runtime`, it needs neither `alloc` nor `libm`: it provides
+//! only a panic handler, which formats the panic message on the stack, and,
+//! with the `entrypoint` feature, a `_start` that calls a C-style `main`. Guests
+//! that need a heap declare their own `#[global_allocator]`, and guests with
+//! their own entrypoint leave `entrypoint` off and name it with
+//! `risc0_build::GuestOptions::with_entry`.
+//!
+//! A guest depending on `risc0-zkvm-platform` with the `bare-metal` and
+//! `entrypoint` features, and built with
+//! `risc0_build::GuestOptions::with_bare_metal`, can be as small as:
+//!
+//! ```ignore
+//! #![no_std]
+//! #![no_main]
+//!
+//! #[no_mangle]
+//! extern "C" fn main(_argc: i32, _argv: *const *const u8) -> i32 {
+//!     0
+//! }
+//! ```
+
+use core::{
+    fmt::{self, Write},
+    panic::PanicInfo,
+};
+
+use crate::syscall::{sys_panic, sys_panic_location};
+
+/// The longest panic message reported by the panic handler; longer messages
+/// are truncated.
+pub const PANIC_MESSAGE_LEN: usize = 256;
+
+struct StackBuffer {
+    bytes: [u8; PANIC_MESSAGE_LEN],
+    len: usize,
+}
+
+impl fmt::Write for StackBuffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let n = s.len().min(PANIC_MESSAGE_LEN - self.len);
+        self.bytes[self.len..self.len + n].copy_from_slice(&s.as_bytes()[..n]);
+        self.len += n;
+        match n == s.len() {
+            true => Ok(()),
+            false => Err(fmt::Error),
+        }
+    }
+}
+
+#[panic_handler]
+fn panic_fault(panic_info: &PanicInfo) -> ! {
+    if let Some(location) = panic_info.location() {
+        let file = location.file();
+        unsafe {
+            sys_panic_location(
+                file.as_ptr(),
+                file.len(),
+                location.line(),
+                location.column(),
+            )
+        }
+    }
+    let mut msg = StackBuffer {
+        bytes: [0; PANIC_MESSAGE_LEN],
+        len: 0,
+    };
+    // A message that does not fit is reported up to where it was cut off.
+    let _ = write!(msg, "{}", panic_info);
+    unsafe { sys_panic(msg.bytes.as_ptr(), msg.len) }
+}
@@ -0,0 +1,223 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The guest heap.
+//!
+//! The heap runs from the end of the loaded ELF up to the [SYSTEM] region. By
+//! default it is managed by a bump allocator, which is cheap in cycles but
+//! never reuses freed memory. Enabling the `heap-linked-list` feature selects
+//! a linked-list allocator instead, which reuses freed memory at the cost of
+//! more cycles per allocation; guests that allocate and free repeatedly over a
+//! long execution should use it.
+//!
+//! The host may set a smaller maximum heap size. An allocation that does not
+//! fit ends execution with an out-of-memory error on the host, which reports
+//! the size of the allocation and how much of the heap was in use.
+//!
+//! [SYSTEM]: crate::memory::SYSTEM
+
+/// Statistics about the guest heap, as returned by [stats].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HeapStats {
+    /// Bytes in use. Under the bump allocator, freed memory remains in use.
+    pub used: usize,
+    /// The largest value `used` has reached.
+    pub peak: usize,
+    /// Bytes that are still available to allocate.
+    pub free: usize,
+}
+
+/// Return statistics about the guest heap.
+pub fn stats() -> HeapStats {
+    imp::stats()
+}
+
+pub(crate) use imp::alloc_aligned;
+#[cfg(target_os = "zkvm")]
+pub(crate) use imp::dealloc;
+
+#[cfg(target_os = "zkvm")]
+fn heap_start() -> usize {
+    extern "C" {
+        // This symbol is defined by the loader and marks the end
+        // of all elf sections, so this is where we start our
+        // heap.
+        //
+        // This is generated automatically by the linker; see
+        // https://lld.llvm.org/ELF/linker_script.html#sections-command
+        static _end: u8;
+    }
+    unsafe { (&_end) as *const u8 as usize }
+}
+
+#[cfg(not(target_os = "zkvm"))]
+fn heap_start() -> usize {
+    0
+}
+
+const HEAP_END: usize = crate::memory::SYSTEM.start();
+
+// The end of the heap, which is HEAP_END unless the host sets a heap limit.
+#[cfg(target_os = "zkvm")]
+fn heap_end() -> usize {
+    // Zero until the host has been asked for the limit.
+    static mut END: usize = 0;
+
+    // SAFETY: Single threaded, so nothing else can touch this while we're
+    // working.
+    unsafe {
+        if END == 0 {
+            let limit = crate::syscall::sys_heap_limit();
+            END = usize::min(HEAP_END, heap_start().saturating_add(limit));
+        }
+        END
+    }
+}
+
+#[cfg(not(target_os = "zkvm"))]
+fn heap_end() -> usize {
+    HEAP_END
+}
+
+fn out_of_memory(bytes: usize, used: usize) -> ! {
+    crate::syscall::sys_out_of_memory(bytes, used, heap_end() - heap_start())
+}
+
+#[cfg(any(not(target_os = "zkvm"), not(feature = "heap-linked-list")))]
+mod imp {
+    use super::{heap_end, heap_start, out_of_memory, HeapStats};
+    use crate::WORD_SIZE;
+
+    // Pointer to next heap address to use, or 0 if the heap has not yet been
+    // initialized.
+    static mut HEAP_POS: usize = 0;
+
+    fn heap_pos() -> usize {
+        // SAFETY: Single threaded, so nothing else can touch this while we're
+        // working.
+        match unsafe { HEAP_POS } {
+            0 => heap_start(),
+            pos => pos,
+        }
+    }
+
+    pub(crate) fn alloc_aligned(bytes: usize, align: usize) -> *mut u8 {
+        let start_pos = heap_pos();
+        let mut heap_pos = start_pos;
+
+        // Honor requested alignment if larger than word size.
+        // Note: align is typically a power of two.
+        let align = usize::max(align, WORD_SIZE);
+
+        let offset = heap_pos & (align - 1);
+        if offset != 0 {
+            heap_pos += align - offset;
+        }
+
+        let ptr = heap_pos as *mut u8;
+        heap_pos += bytes;
+
+        // Check to make sure heap doesn't collide with SYSTEM memory, or grow
+        // past the limit set by the host.
+        if heap_end() < heap_pos {
+            out_of_memory(bytes, start_pos - heap_start());
+        }
+
+        unsafe { HEAP_POS = heap_pos };
+        ptr
+    }
+
+    #[cfg(target_os = "zkvm")]
+    pub(crate) fn dealloc(_: *mut u8, _: usize, _: usize) {
+        // this allocator never deallocates memory
+    }
+
+    pub(super) fn stats() -> HeapStats {
+        let used = heap_pos() - heap_start();
+        HeapStats {
+            used,
+            peak: used,
+            free: heap_end() - heap_pos(),
+        }
+    }
+}
+
+#[cfg(all(target_os = "zkvm", feature = "heap-linked-list"))]
+mod imp {
+    use core::{alloc::Layout, cell::UnsafeCell, ptr::NonNull};
+
+    use linked_list_allocator::Heap;
+
+    use super::{heap_end, heap_start, out_of_memory, HeapStats};
+    use crate::WORD_SIZE;
+
+    struct State {
+        heap: Heap,
+        init: bool,
+        peak: usize,
+    }
+
+    struct Global(UnsafeCell<State>);
+
+    // SAFETY: The guest is single threaded.
+    unsafe impl Sync for Global {}
+
+    static STATE: Global = Global(UnsafeCell::new(State {
+        heap: Heap::empty(),
+        init: false,
+        peak: 0,
+    }));
+
+    fn state() -> &'static mut State {
+        // SAFETY: Single threaded, and the reference is not held across calls.
+        let state = unsafe { &mut *STATE.0.get() };
+        if !state.init {
+            let start = heap_start();
+            // SAFETY: The heap region is otherwise unused.
+            unsafe { state.heap.init(start as *mut u8, heap_end() - start) };
+            state.init = true;
+        }
+        state
+    }
+
+    fn layout(bytes: usize, align: usize) -> Layout {
+        Layout::from_size_align(bytes, usize::max(align, WORD_SIZE)).unwrap()
+    }
+
+    pub(crate) fn alloc_aligned(bytes: usize, align: usize) -> *mut u8 {
+        let state = state();
+        let Ok(ptr) = state.heap.allocate_first_fit(layout(bytes, align)) else {
+            out_of_memory(bytes, state.heap.used());
+        };
+        state.peak = usize::max(state.peak, state.heap.used());
+        ptr.as_ptr()
+    }
+
+    pub(crate) fn dealloc(ptr: *mut u8, bytes: usize, align: usize) {
+        if let Some(ptr) = NonNull::new(ptr) {
+            // SAFETY: The caller passes a pointer returned from alloc_aligned
+            // with the same size and alignment.
+            unsafe { state().heap.deallocate(ptr, layout(bytes, align)) }
+        }
+    }
+
+    pub(super) fn stats() -> HeapStats {
+        let state = state();
+        HeapStats {
+            used: state.heap.used(),
+            peak: state.peak,
+            free: state.heap.free(),
+        }
+    }
+}
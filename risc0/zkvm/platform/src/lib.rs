@@ -16,7 +16,17 @@
 #![no_std]
 #![allow(unused_variables)]
 
+#[cfg(all(feature = "bare-metal", feature = "rust-runtime"))]
+compile_error!("the `bare-metal` and `rust-runtime` features are mutually exclusive");
+
+#[cfg(all(feature = "bare-metal", target_os = "zkvm"))]
+pub mod bare_rt;
+#[cfg(all(feature = "entrypoint", target_os = "zkvm"))]
+mod entrypoint;
+#[cfg(feature = "export-syscalls")]
+pub mod heap;
 pub mod memory;
+pub mod spec;
 #[macro_use]
 pub mod syscall;
 #[cfg(all(feature = "export-getrandom", target_os = "zkvm"))]
@@ -15,73 +15,42 @@
 //! This module contains the components required to link a Rust binary.
 //!
 //! In particular:
-//! * It defines an entrypoint ensuring initialization and finalization are done
-//!   properly.
+//! * With the `entrypoint` feature, the crate defines an entrypoint ensuring
+//!   initialization and finalization are done properly.
 //! * It includes a panic handler.
-//! * It includes an allocator.
+//! * It includes an allocator, selected as described in [crate::heap].
 
 use core::{
     alloc::{GlobalAlloc, Layout},
     panic::PanicInfo,
 };
 
-use crate::syscall::{sys_alloc_aligned, sys_panic};
+use crate::syscall::{sys_alloc_aligned, sys_panic, sys_panic_location};
 
 extern crate alloc;
 
 /// panic! implementation for use in no_std guest programs.
 #[cfg_attr(feature = "panic-handler", panic_handler)]
 pub fn panic_fault(panic_info: &PanicInfo) -> ! {
+    if let Some(location) = panic_info.location() {
+        let file = location.file();
+        unsafe {
+            sys_panic_location(
+                file.as_ptr(),
+                file.len(),
+                location.line(),
+                location.column(),
+            )
+        }
+    }
     let msg = alloc::format!("{}", panic_info);
-    let msg_bytes = msg.as_bytes();
     unsafe { sys_panic(msg.as_ptr(), msg.len()) }
 }
 
-#[cfg(feature = "entrypoint")]
-mod entrypoint {
-    use crate::syscall::sys_halt;
-
-    #[no_mangle]
-    unsafe extern "C" fn __start() -> ! {
-        // This definition of __start differs from risc0_zkvm::guest in that it does not initialize the
-        // journal and will halt with empty output. It also assumes main follows the standard C
-        // convention, and uses the returned i32 value as the user exit code for halt.
-        let exit_code = {
-            extern "C" {
-                fn main(argc: i32, argv: *const *const u8) -> i32;
-            }
-
-            main(0, core::ptr::null())
-        };
-
-        const EMPTY_OUTPUT: [u32; 8] = [0; 8];
-        sys_halt(exit_code as u8, &EMPTY_OUTPUT);
-    }
-
-    static STACK_TOP: u32 = crate::memory::STACK_TOP;
-
-    // Entry point; sets up global pointer and stack pointer and passes
-    // to __start.  TODO: when asm_const is stablized, use that here
-    // instead of defining a symbol and dereferencing it.
-    core::arch::global_asm!(
-        r#"
-    .section .text._start
-    .globl _start
-    _start:
-        .option push;
-        .option norelax
-        la gp, __global_pointer$
-        .option pop
-        la sp, {0}
-        lw sp, 0(sp)
-        call __start;
-    "#,
-        sym STACK_TOP
-    );
-}
-
+#[cfg(not(feature = "heap-linked-list"))]
 struct BumpPointerAlloc;
 
+#[cfg(not(feature = "heap-linked-list"))]
 unsafe impl GlobalAlloc for BumpPointerAlloc {
     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
         sys_alloc_aligned(layout.size(), layout.align())
@@ -98,5 +67,24 @@ unsafe impl GlobalAlloc for BumpPointerAlloc {
     }
 }
 
+#[cfg(not(feature = "heap-linked-list"))]
 #[global_allocator]
 static HEAP: BumpPointerAlloc = BumpPointerAlloc;
+
+#[cfg(feature = "heap-linked-list")]
+struct LinkedListAlloc;
+
+#[cfg(feature = "heap-linked-list")]
+unsafe impl GlobalAlloc for LinkedListAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        sys_alloc_aligned(layout.size(), layout.align())
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        crate::heap::dealloc(ptr, layout.size(), layout.align())
+    }
+}
+
+#[cfg(feature = "heap-linked-list")]
+#[global_allocator]
+static HEAP: LinkedListAlloc = LinkedListAlloc;
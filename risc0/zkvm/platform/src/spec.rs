@@ -0,0 +1,730 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A machine-readable description of the guest ABI: the ecalls, the software
+//! syscalls and their register conventions, and the constants they use.
+//!
+//! Runtimes for other languages can generate their bindings from [SPEC],
+//! written as JSON by [Spec::write_json], instead of copying the numbers and
+//! names by hand. The `syscall_spec` tool in `risc0-tools` writes the JSON, as
+//! well as C and AssemblyScript headers.
+
+use core::fmt::{self, Write};
+
+use crate::{
+    fileno, memory,
+    syscall::{bigint, ecall, halt, nr, seek, SyscallName, MAX_BUF_BYTES},
+    PAGE_SIZE, WORD_SIZE,
+};
+
+/// A register passed to or returned from an ecall.
+#[derive(Clone, Copy, Debug)]
+pub struct Arg {
+    /// The ABI name of the register, such as `a0`.
+    pub register: &'static str,
+
+    /// The name of the value.
+    pub name: &'static str,
+
+    /// What the value means.
+    pub doc: &'static str,
+}
+
+/// An ecall, selected by the number in `t0`.
+#[derive(Clone, Copy, Debug)]
+pub struct Ecall {
+    /// The name of the ecall, as in [ecall].
+    pub name: &'static str,
+
+    /// The number the guest puts in `t0`.
+    pub number: u32,
+
+    /// The registers the ecall reads.
+    pub args: &'static [Arg],
+
+    /// The registers the ecall writes.
+    pub returns: &'static [Arg],
+
+    /// What the ecall does.
+    pub doc: &'static str,
+}
+
+/// A software syscall, made with the [ecall::SOFTWARE] ecall as described by
+/// [Spec::software_args] and [Spec::software_returns].
+#[derive(Clone, Copy, Debug)]
+pub struct Syscall {
+    /// The name of the syscall, whose NUL-terminated bytes the guest points
+    /// `a2` at.
+    pub name: SyscallName,
+
+    /// The arguments in `a3` to `a7`.
+    pub args: &'static [Arg],
+
+    /// What the host writes to the `from_host` buffer, if anything.
+    pub from_host: &'static str,
+
+    /// The values returned in `a0` and `a1`.
+    pub returns: &'static [Arg],
+
+    /// What the syscall does.
+    pub doc: &'static str,
+}
+
+/// A named constant.
+#[derive(Clone, Copy, Debug)]
+pub struct Constant {
+    /// The name of the constant.
+    pub name: &'static str,
+
+    /// The value of the constant.
+    pub value: u32,
+
+    /// What the constant means.
+    pub doc: &'static str,
+}
+
+/// A group of related constants, such as the file descriptors.
+#[derive(Clone, Copy, Debug)]
+pub struct ConstantGroup {
+    /// The name of the group.
+    pub name: &'static str,
+
+    /// The constants in the group.
+    pub constants: &'static [Constant],
+}
+
+/// The guest ABI of the zkVM.
+#[derive(Clone, Copy, Debug)]
+pub struct Spec {
+    /// The version of `risc0-zkvm-platform` the spec describes.
+    pub version: &'static str,
+
+    /// The constants used by the ecalls and syscalls.
+    pub constants: &'static [ConstantGroup],
+
+    /// The ecalls.
+    pub ecalls: &'static [Ecall],
+
+    /// The registers read by every software syscall.
+    pub software_args: &'static [Arg],
+
+    /// The registers written by every software syscall.
+    pub software_returns: &'static [Arg],
+
+    /// The software syscalls.
+    pub syscalls: &'static [Syscall],
+}
+
+const fn arg(register: &'static str, name: &'static str, doc: &'static str) -> Arg {
+    Arg {
+        register,
+        name,
+        doc,
+    }
+}
+
+const fn constant(name: &'static str, value: u32, doc: &'static str) -> Constant {
+    Constant { name, value, doc }
+}
+
+const NONE: &[Arg] = &[];
+
+/// The guest ABI of this version of the zkVM.
+pub const SPEC: Spec = Spec {
+    version: env!("CARGO_PKG_VERSION"),
+    constants: &[
+        ConstantGroup {
+            name: "platform",
+            constants: &[
+                constant(
+                    "WORD_SIZE",
+                    WORD_SIZE as u32,
+                    "Size of a machine word in bytes.",
+                ),
+                constant(
+                    "PAGE_SIZE",
+                    PAGE_SIZE as u32,
+                    "Size of a memory page in bytes.",
+                ),
+                constant(
+                    "MAX_BUF_BYTES",
+                    MAX_BUF_BYTES as u32,
+                    "Largest number of bytes moved by one SYS_READ or SYS_WRITE.",
+                ),
+            ],
+        },
+        ConstantGroup {
+            name: "memory",
+            constants: &[
+                constant(
+                    "GUEST_MIN_MEM",
+                    memory::GUEST_MIN_MEM as u32,
+                    "Lowest address guest code may use.",
+                ),
+                constant(
+                    "GUEST_MAX_MEM",
+                    memory::GUEST_MAX_MEM as u32,
+                    "End of the memory guest code may use.",
+                ),
+                constant("STACK_TOP", memory::STACK_TOP, "Initial stack pointer."),
+                constant(
+                    "TEXT_START",
+                    memory::TEXT_START,
+                    "Address guest code is linked at.",
+                ),
+            ],
+        },
+        ConstantGroup {
+            name: "fileno",
+            constants: &[
+                constant("STDIN", fileno::STDIN, "Private input from the host."),
+                constant("STDOUT", fileno::STDOUT, "Output to the host."),
+                constant("STDERR", fileno::STDERR, "Error output to the host."),
+                constant("JOURNAL", fileno::JOURNAL, "The public journal."),
+            ],
+        },
+        ConstantGroup {
+            name: "halt",
+            constants: &[
+                constant("TERMINATE", halt::TERMINATE, "End execution."),
+                constant(
+                    "PAUSE",
+                    halt::PAUSE,
+                    "Pause execution, which the host may resume.",
+                ),
+            ],
+        },
+        ConstantGroup {
+            name: "seek",
+            constants: &[
+                constant("SET", seek::SET, "Seek from the start of the file."),
+                constant("CUR", seek::CUR, "Seek from the current position."),
+                constant("END", seek::END, "Seek from the end of the file."),
+            ],
+        },
+        ConstantGroup {
+            name: "bigint",
+            constants: &[
+                constant(
+                    "OP_MULTIPLY",
+                    bigint::OP_MULTIPLY,
+                    "Multiply modulo the modulus.",
+                ),
+                constant(
+                    "WIDTH_WORDS",
+                    bigint::WIDTH_WORDS as u32,
+                    "Width of a bigint operand in words, least significant first.",
+                ),
+            ],
+        },
+    ],
+    ecalls: &[
+        Ecall {
+            name: "HALT",
+            number: ecall::HALT,
+            args: &[
+                arg(
+                    "a0",
+                    "mode",
+                    "TERMINATE or PAUSE, ORed with the user exit code shifted left by 8.",
+                ),
+                arg("a1", "out_state", "Pointer to the 8-word output digest."),
+            ],
+            returns: NONE,
+            doc: "Halt or pause the guest. A guest should halt from only one place, so that \
+                  the final program counter does not depend on its input.",
+        },
+        Ecall {
+            name: "INPUT",
+            number: ecall::INPUT,
+            args: &[arg("a0", "index", "Index of the word, from 0 to 7.")],
+            returns: &[arg("a0", "word", "The word of the input digest.")],
+            doc: "Read a word of the input digest committed to by the receipt.",
+        },
+        Ecall {
+            name: "SOFTWARE",
+            number: ecall::SOFTWARE,
+            args: NONE,
+            returns: NONE,
+            doc: "Make a software syscall, as described by software_args and \
+                  software_returns. The host is trusted for the result of software syscalls.",
+        },
+        Ecall {
+            name: "SHA",
+            number: ecall::SHA,
+            args: &[
+                arg("a0", "out_state", "Pointer to the 8-word output state."),
+                arg("a1", "in_state", "Pointer to the 8-word input state."),
+                arg(
+                    "a2",
+                    "block1",
+                    "Pointer to the first half of the first block.",
+                ),
+                arg(
+                    "a3",
+                    "block2",
+                    "Pointer to the second half of the first block.",
+                ),
+                arg(
+                    "a4",
+                    "count",
+                    "Number of contiguous 64-byte blocks, at most 1000.",
+                ),
+            ],
+            returns: NONE,
+            doc: "Run the SHA-256 compression function over blocks, in words as loaded \
+                  little-endian.",
+        },
+        Ecall {
+            name: "BIGINT",
+            number: ecall::BIGINT,
+            args: &[
+                arg("a0", "result", "Pointer to the 8-word result."),
+                arg("a1", "op", "The operation, such as OP_MULTIPLY."),
+                arg("a2", "x", "Pointer to the 8-word first operand."),
+                arg("a3", "y", "Pointer to the 8-word second operand."),
+                arg("a4", "modulus", "Pointer to the 8-word modulus."),
+            ],
+            returns: NONE,
+            doc: "Compute x * y modulo the modulus on 256-bit integers.",
+        },
+    ],
+    software_args: &[
+        arg("t0", "ecall", "SOFTWARE."),
+        arg(
+            "a0",
+            "from_host",
+            "Word-aligned pointer to a buffer the host writes to.",
+        ),
+        arg(
+            "a1",
+            "from_host_words",
+            "Size of the from_host buffer in words.",
+        ),
+        arg(
+            "a2",
+            "name",
+            "Pointer to the NUL-terminated name of the syscall.",
+        ),
+    ],
+    software_returns: &[
+        arg("a0", "a0", "First return value."),
+        arg("a1", "a1", "Second return value."),
+    ],
+    syscalls: &[
+        Syscall {
+            name: nr::SYS_ARGC,
+            args: NONE,
+            from_host: "",
+            returns: &[arg("a0", "argc", "Number of arguments.")],
+            doc: "Return the number of arguments given by the host.",
+        },
+        Syscall {
+            name: nr::SYS_ARGV,
+            args: &[arg("a3", "index", "Index of the argument.")],
+            from_host: "As much of the argument as fits.",
+            returns: &[arg("a0", "len", "Length of the argument in bytes.")],
+            doc: "Read an argument, usually once to get its length and again to fill a \
+                  buffer. The host fails execution if the index is out of range.",
+        },
+        Syscall {
+            name: nr::SYS_CALL,
+            args: &[
+                arg(
+                    "a3",
+                    "request",
+                    "Pointer to the image ID followed by the input.",
+                ),
+                arg("a4", "request_len", "Length of the request in bytes."),
+            ],
+            from_host: "The journal of the called guest.",
+            returns: &[arg("a0", "len", "Length of the journal in bytes.")],
+            doc: "Run another guest on an input, usually once to get the length of its \
+                  journal and again to fill a buffer. The guest must then verify the journal \
+                  with SYS_VERIFY.",
+        },
+        Syscall {
+            name: nr::SYS_CHANNEL_WRITE,
+            args: &[
+                arg("a3", "name", "Pointer to the channel name."),
+                arg("a4", "name_len", "Length of the channel name in bytes."),
+                arg("a5", "data", "Pointer to the data."),
+                arg("a6", "data_len", "Length of the data in bytes."),
+            ],
+            from_host: "",
+            returns: NONE,
+            doc: "Record data written to a named output channel. The guest commits to the \
+                  data itself.",
+        },
+        Syscall {
+            name: nr::SYS_CYCLE_COUNT,
+            args: NONE,
+            from_host: "",
+            returns: &[
+                arg("a0", "hi", "High word of the cycle count."),
+                arg("a1", "lo", "Low word of the cycle count."),
+            ],
+            doc: "Return the number of cycles run so far, as reported by the host.",
+        },
+        Syscall {
+            name: nr::SYS_GETENV,
+            args: &[
+                arg("a3", "name", "Pointer to the variable name."),
+                arg("a4", "name_len", "Length of the variable name in bytes."),
+            ],
+            from_host: "As much of the value as fits.",
+            returns: &[arg(
+                "a0",
+                "len",
+                "Length of the value in bytes, or 0xffffffff if it is not set.",
+            )],
+            doc: "Read an environment variable, usually once to get its length and again to \
+                  fill a buffer.",
+        },
+        Syscall {
+            name: nr::SYS_HEAP_LIMIT,
+            args: NONE,
+            from_host: "",
+            returns: &[arg(
+                "a0",
+                "limit",
+                "Largest heap size in bytes, or 0xffffffff for no limit.",
+            )],
+            doc: "Return the limit on the guest heap set by the host.",
+        },
+        Syscall {
+            name: nr::SYS_LOG,
+            args: &[
+                arg("a3", "msg", "Pointer to the message."),
+                arg("a4", "msg_len", "Length of the message in bytes."),
+            ],
+            from_host: "",
+            returns: NONE,
+            doc: "Print a message on the host.",
+        },
+        Syscall {
+            name: nr::SYS_LOG_EVENT,
+            args: &[
+                arg("a3", "level", "From 1 (error) to 5 (trace)."),
+                arg("a4", "target", "Pointer to the target."),
+                arg("a5", "target_len", "Length of the target in bytes."),
+                arg("a6", "msg", "Pointer to the message."),
+                arg("a7", "msg_len", "Length of the message in bytes."),
+            ],
+            from_host: "",
+            returns: NONE,
+            doc: "Send a leveled log event to the host.",
+        },
+        Syscall {
+            name: nr::SYS_MEMORY_STATS,
+            args: NONE,
+            from_host: "",
+            returns: &[
+                arg("a0", "stack_bytes", "Stack high-water mark in bytes."),
+                arg("a1", "pages", "Number of pages touched."),
+            ],
+            doc: "Return memory use tracked by the host.",
+        },
+        Syscall {
+            name: nr::SYS_MODINV,
+            args: &[
+                arg(
+                    "a3",
+                    "request",
+                    "Pointer to the 8-word value then the 8-word modulus.",
+                ),
+                arg("a4", "request_len", "Length of the request in bytes, 64."),
+            ],
+            from_host: "The 8-word modular inverse.",
+            returns: NONE,
+            doc: "Compute a modular inverse on the host. The guest must check the result.",
+        },
+        Syscall {
+            name: nr::SYS_MONT_R2,
+            args: &[
+                arg(
+                    "a3",
+                    "modulus",
+                    "Pointer to the modulus, least significant word first.",
+                ),
+                arg("a4", "modulus_len", "Length of the modulus in bytes."),
+            ],
+            from_host: "R^2 modulo the modulus, for Montgomery multiplication.",
+            returns: NONE,
+            doc: "Compute a Montgomery constant on the host. The guest must check the result.",
+        },
+        Syscall {
+            name: nr::SYS_OUT_OF_MEMORY,
+            args: &[
+                arg("a3", "bytes", "Size of the allocation that failed."),
+                arg("a4", "used", "Bytes in use by the heap."),
+                arg("a5", "limit", "Size of the heap in bytes."),
+            ],
+            from_host: "",
+            returns: NONE,
+            doc: "Report that the heap is exhausted. Execution fails.",
+        },
+        Syscall {
+            name: nr::SYS_PANIC,
+            args: &[
+                arg("a3", "msg", "Pointer to the message."),
+                arg("a4", "msg_len", "Length of the message in bytes."),
+            ],
+            from_host: "",
+            returns: NONE,
+            doc: "Report a panic. Execution fails.",
+        },
+        Syscall {
+            name: nr::SYS_PANIC_LOCATION,
+            args: &[
+                arg("a3", "file", "Pointer to the file name."),
+                arg("a4", "file_len", "Length of the file name in bytes."),
+                arg("a5", "line", "Line of the panic."),
+                arg("a6", "column", "Column of the panic."),
+            ],
+            from_host: "",
+            returns: NONE,
+            doc: "Report the source location of a panic, ahead of SYS_PANIC.",
+        },
+        Syscall {
+            name: nr::SYS_PROFILE_ZONE_ENTER,
+            args: &[
+                arg("a3", "name", "Pointer to the zone name."),
+                arg("a4", "name_len", "Length of the zone name in bytes."),
+            ],
+            from_host: "",
+            returns: NONE,
+            doc: "Enter a named zone of the host profiler.",
+        },
+        Syscall {
+            name: nr::SYS_PROFILE_ZONE_EXIT,
+            args: NONE,
+            from_host: "",
+            returns: NONE,
+            doc: "Leave the zone entered most recently.",
+        },
+        Syscall {
+            name: nr::SYS_RANDOM,
+            args: NONE,
+            from_host: "Random words.",
+            returns: NONE,
+            doc: "Fill the from_host buffer with randomness from the host.",
+        },
+        Syscall {
+            name: nr::SYS_READ,
+            args: &[
+                arg("a3", "fd", "The file descriptor."),
+                arg(
+                    "a4",
+                    "nbytes",
+                    "Number of bytes to read, at most MAX_BUF_BYTES.",
+                ),
+            ],
+            from_host: "The whole words read.",
+            returns: &[
+                arg(
+                    "a0",
+                    "nread",
+                    "Number of bytes read, less than nbytes at end of file.",
+                ),
+                arg(
+                    "a1",
+                    "last_word",
+                    "The 0 to 3 bytes read after the whole words.",
+                ),
+            ],
+            doc: "Read from a file descriptor.",
+        },
+        Syscall {
+            name: nr::SYS_SEEK,
+            args: &[
+                arg("a3", "fd", "The file descriptor."),
+                arg("a4", "whence", "SET, CUR or END."),
+                arg("a5", "offset_lo", "Low word of the signed offset."),
+                arg("a6", "offset_hi", "High word of the signed offset."),
+            ],
+            from_host: "",
+            returns: &[
+                arg("a0", "pos_lo", "Low word of the new position."),
+                arg("a1", "pos_hi", "High word of the new position."),
+            ],
+            doc: "Move the position of a seekable file descriptor.",
+        },
+        Syscall {
+            name: nr::SYS_TIME,
+            args: NONE,
+            from_host: "",
+            returns: &[
+                arg("a0", "hi", "High word of the time."),
+                arg("a1", "lo", "Low word of the time."),
+            ],
+            doc: "Return the host's wall-clock time in milliseconds since the Unix epoch, or \
+                  all ones if the host does not provide one.",
+        },
+        Syscall {
+            name: nr::SYS_VERIFIED_READ,
+            args: &[
+                arg(
+                    "a3",
+                    "request",
+                    "Pointer to the 8-word file root, then the operation.",
+                ),
+                arg("a4", "request_len", "Length of the request in bytes."),
+            ],
+            from_host: "The requested chunk of the file, with its Merkle proof.",
+            returns: &[arg("a0", "len", "Length of the response in bytes.")],
+            doc: "Read from a file verified against its Merkle root, usually once to get the \
+                  length of the response and again to fill a buffer.",
+        },
+        Syscall {
+            name: nr::SYS_VERIFY,
+            args: &[
+                arg(
+                    "a3",
+                    "request",
+                    "Pointer to the image ID, then the journal digest.",
+                ),
+                arg("a4", "request_len", "Length of the request in bytes, 64."),
+            ],
+            from_host: "The 8-word post state digest, then the system exit code.",
+            returns: &[arg("a0", "result", "0 on success.")],
+            doc: "Assume a receipt for an execution of the image ID with the journal digest.",
+        },
+        Syscall {
+            name: nr::SYS_VERIFY_INTEGRITY,
+            args: &[
+                arg("a3", "claim", "Pointer to the 8-word claim digest."),
+                arg(
+                    "a4",
+                    "claim_len",
+                    "Length of the claim digest in bytes, 32.",
+                ),
+            ],
+            from_host: "",
+            returns: &[arg("a0", "result", "0 on success.")],
+            doc: "Assume a receipt for the claim digest.",
+        },
+        Syscall {
+            name: nr::SYS_WRITE,
+            args: &[
+                arg("a3", "fd", "The file descriptor."),
+                arg("a4", "buf", "Pointer to the data."),
+                arg("a5", "nbytes", "Length of the data, at most MAX_BUF_BYTES."),
+            ],
+            from_host: "",
+            returns: NONE,
+            doc: "Write to a file descriptor.",
+        },
+    ],
+};
+
+impl Spec {
+    /// Write the spec as JSON.
+    pub fn write_json(&self, out: &mut impl Write) -> fmt::Result {
+        write!(out, "{{\"version\":")?;
+        write_str(out, self.version)?;
+        write!(out, ",\"constants\":[")?;
+        for (i, group) in self.constants.iter().enumerate() {
+            separator(out, i)?;
+            write!(out, "{{\"name\":")?;
+            write_str(out, group.name)?;
+            write!(out, ",\"constants\":[")?;
+            for (i, constant) in group.constants.iter().enumerate() {
+                separator(out, i)?;
+                write!(out, "{{\"name\":")?;
+                write_str(out, constant.name)?;
+                write!(out, ",\"value\":{},\"doc\":", constant.value)?;
+                write_str(out, constant.doc)?;
+                write!(out, "}}")?;
+            }
+            write!(out, "]}}")?;
+        }
+        write!(out, "],\"ecalls\":[")?;
+        for (i, ecall) in self.ecalls.iter().enumerate() {
+            separator(out, i)?;
+            write!(out, "{{\"name\":")?;
+            write_str(out, ecall.name)?;
+            write!(out, ",\"number\":{},\"args\":", ecall.number)?;
+            write_args(out, ecall.args)?;
+            write!(out, ",\"returns\":")?;
+            write_args(out, ecall.returns)?;
+            write!(out, ",\"doc\":")?;
+            write_str(out, ecall.doc)?;
+            write!(out, "}}")?;
+        }
+        write!(out, "],\"software_args\":")?;
+        write_args(out, self.software_args)?;
+        write!(out, ",\"software_returns\":")?;
+        write_args(out, self.software_returns)?;
+        write!(out, ",\"syscalls\":[")?;
+        for (i, syscall) in self.syscalls.iter().enumerate() {
+            separator(out, i)?;
+            write!(out, "{{\"name\":")?;
+            write_str(out, syscall.name.as_str())?;
+            write!(out, ",\"args\":")?;
+            write_args(out, syscall.args)?;
+            write!(out, ",\"from_host\":")?;
+            write_str(out, syscall.from_host)?;
+            write!(out, ",\"returns\":")?;
+            write_args(out, syscall.returns)?;
+            write!(out, ",\"doc\":")?;
+            write_str(out, syscall.doc)?;
+            write!(out, "}}")?;
+        }
+        write!(out, "]}}")
+    }
+}
+
+impl Syscall {
+    /// The name of the syscall without its module path, such as `SYS_READ`.
+    pub fn short_name(&self) -> &str {
+        let name = self.name.as_str();
+        name.rsplit("::").next().unwrap_or(name)
+    }
+}
+
+fn separator(out: &mut impl Write, i: usize) -> fmt::Result {
+    if i > 0 {
+        out.write_char(',')?;
+    }
+    Ok(())
+}
+
+fn write_args(out: &mut impl Write, args: &[Arg]) -> fmt::Result {
+    out.write_char('[')?;
+    for (i, arg) in args.iter().enumerate() {
+        separator(out, i)?;
+        write!(out, "{{\"register\":")?;
+        write_str(out, arg.register)?;
+        write!(out, ",\"name\":")?;
+        write_str(out, arg.name)?;
+        write!(out, ",\"doc\":")?;
+        write_str(out, arg.doc)?;
+        out.write_char('}')?;
+    }
+    out.write_char(']')
+}
+
+fn write_str(out: &mut impl Write, s: &str) -> fmt::Result {
+    out.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32)?,
+            c => out.write_char(c)?,
+        }
+    }
+    out.write_char('"')
+}
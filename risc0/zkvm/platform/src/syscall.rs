@@ -32,6 +32,13 @@ pub mod halt {
     pub const SPLIT: u32 = 2;
 }
 
+/// Values of `whence` for [sys_seek], as in POSIX `lseek`.
+pub mod seek {
+    pub const SET: u32 = 0;
+    pub const CUR: u32 = 1;
+    pub const END: u32 = 2;
+}
+
 pub mod reg_abi {
     pub const REG_ZERO: usize = 0; // zero constant
     pub const REG_RA: usize = 1; // return address
@@ -128,12 +135,26 @@ macro_rules! declare_syscall {
 pub mod nr {
     declare_syscall!(pub SYS_ARGC);
     declare_syscall!(pub SYS_ARGV);
+    declare_syscall!(pub SYS_CALL);
+    declare_syscall!(pub SYS_CHANNEL_WRITE);
     declare_syscall!(pub SYS_CYCLE_COUNT);
     declare_syscall!(pub SYS_GETENV);
+    declare_syscall!(pub SYS_HEAP_LIMIT);
     declare_syscall!(pub SYS_LOG);
+    declare_syscall!(pub SYS_LOG_EVENT);
+    declare_syscall!(pub SYS_MEMORY_STATS);
+    declare_syscall!(pub SYS_MODINV);
+    declare_syscall!(pub SYS_MONT_R2);
+    declare_syscall!(pub SYS_OUT_OF_MEMORY);
     declare_syscall!(pub SYS_PANIC);
+    declare_syscall!(pub SYS_PANIC_LOCATION);
+    declare_syscall!(pub SYS_PROFILE_ZONE_ENTER);
+    declare_syscall!(pub SYS_PROFILE_ZONE_EXIT);
     declare_syscall!(pub SYS_RANDOM);
     declare_syscall!(pub SYS_READ);
+    declare_syscall!(pub SYS_SEEK);
+    declare_syscall!(pub SYS_TIME);
+    declare_syscall!(pub SYS_VERIFIED_READ);
     declare_syscall!(pub SYS_VERIFY);
     declare_syscall!(pub SYS_VERIFY_INTEGRITY);
     declare_syscall!(pub SYS_WRITE);
@@ -420,6 +441,55 @@ pub unsafe extern "C" fn sys_panic(msg_ptr: *const u8, len: usize) -> ! {
     unreachable!()
 }
 
+/// Reports to the host that the heap cannot satisfy an allocation of `bytes`,
+/// and ends execution.
+///
+/// `used` is the number of bytes in use by the heap, and `limit` is its size.
+#[cfg_attr(feature = "export-syscalls", no_mangle)]
+pub extern "C" fn sys_out_of_memory(bytes: usize, used: usize, limit: usize) -> ! {
+    unsafe {
+        syscall_3(
+            nr::SYS_OUT_OF_MEMORY,
+            null_mut(),
+            0,
+            bytes as u32,
+            used as u32,
+            limit as u32,
+        );
+    }
+
+    // As a fallback for non-compliant hosts, issue an illegal instruction.
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        asm!("sw x0, 1(x0)")
+    };
+    unreachable!()
+}
+
+/// Reports the source location of a panic to the host, ahead of the
+/// [sys_panic] call that ends execution.
+///
+/// # Safety
+///
+/// `file_ptr` must be aligned and dereferenceable.
+#[cfg_attr(feature = "export-syscalls", no_mangle)]
+pub unsafe extern "C" fn sys_panic_location(
+    file_ptr: *const u8,
+    file_len: usize,
+    line: u32,
+    column: u32,
+) {
+    syscall_4(
+        nr::SYS_PANIC_LOCATION,
+        null_mut(),
+        0,
+        file_ptr as u32,
+        file_len as u32,
+        line,
+        column,
+    );
+}
+
 /// # Safety
 ///
 /// `msg_ptr` must be aligned and dereferenceable.
@@ -428,12 +498,117 @@ pub unsafe extern "C" fn sys_log(msg_ptr: *const u8, len: usize) {
     syscall_2(nr::SYS_LOG, null_mut(), 0, msg_ptr as u32, len as u32);
 }
 
+/// Tells the host profiler that the guest has entered the named zone.
+///
+/// Cycles spent until the matching [sys_profile_zone_exit] are attributed to
+/// a frame with this name. Hosts that are not profiling ignore this call.
+///
+/// # Safety
+///
+/// `name_ptr` must be aligned and dereferenceable.
+#[cfg_attr(feature = "export-syscalls", no_mangle)]
+pub unsafe extern "C" fn sys_profile_zone_enter(name_ptr: *const u8, name_len: usize) {
+    syscall_2(
+        nr::SYS_PROFILE_ZONE_ENTER,
+        null_mut(),
+        0,
+        name_ptr as u32,
+        name_len as u32,
+    );
+}
+
+/// Tells the host profiler that the guest has left the zone entered most
+/// recently with [sys_profile_zone_enter].
+#[cfg_attr(feature = "export-syscalls", no_mangle)]
+pub extern "C" fn sys_profile_zone_exit() {
+    unsafe { syscall_0(nr::SYS_PROFILE_ZONE_EXIT, null_mut(), 0) };
+}
+
+/// Sends a leveled log event to the host.
+///
+/// `level` runs from 1 (error) to 5 (trace). `target` names the part of the
+/// guest the event comes from, and is used by the host for filtering.
+///
+/// # Safety
+///
+/// `target_ptr` and `msg_ptr` must be aligned and dereferenceable.
+#[cfg_attr(feature = "export-syscalls", no_mangle)]
+pub unsafe extern "C" fn sys_log_event(
+    level: u32,
+    target_ptr: *const u8,
+    target_len: usize,
+    msg_ptr: *const u8,
+    msg_len: usize,
+) {
+    syscall_5(
+        nr::SYS_LOG_EVENT,
+        null_mut(),
+        0,
+        level,
+        target_ptr as u32,
+        target_len as u32,
+        msg_ptr as u32,
+        msg_len as u32,
+    );
+}
+
+/// Sends data written to a named output channel to the host.
+///
+/// The guest is responsible for committing to the data; the host only records
+/// it so that it can be included in the receipt claim.
+///
+/// # Safety
+///
+/// `name_ptr` and `data_ptr` must be aligned and dereferenceable.
+#[cfg_attr(feature = "export-syscalls", no_mangle)]
+pub unsafe extern "C" fn sys_channel_write(
+    name_ptr: *const u8,
+    name_len: usize,
+    data_ptr: *const u8,
+    data_len: usize,
+) {
+    syscall_4(
+        nr::SYS_CHANNEL_WRITE,
+        null_mut(),
+        0,
+        name_ptr as u32,
+        name_len as u32,
+        data_ptr as u32,
+        data_len as u32,
+    );
+}
+
 #[cfg_attr(feature = "export-syscalls", no_mangle)]
 pub extern "C" fn sys_cycle_count() -> u64 {
     let Return(hi, lo) = unsafe { syscall_0(nr::SYS_CYCLE_COUNT, null_mut(), 0) };
     ((hi as u64) << 32) + lo as u64
 }
 
+/// Returns the stack high-water mark in bytes, and the number of pages of
+/// guest memory touched so far, as tracked by the host.
+#[cfg_attr(feature = "export-syscalls", no_mangle)]
+pub extern "C" fn sys_memory_stats() -> Return {
+    unsafe { syscall_0(nr::SYS_MEMORY_STATS, null_mut(), 0) }
+}
+
+/// Returns the maximum size of the guest heap in bytes, as set by the host, or
+/// `usize::MAX` if the host does not limit it.
+#[cfg_attr(feature = "export-syscalls", no_mangle)]
+pub extern "C" fn sys_heap_limit() -> usize {
+    let Return(a0, _) = unsafe { syscall_0(nr::SYS_HEAP_LIMIT, null_mut(), 0) };
+    a0 as usize
+}
+
+/// Returns the host's wall-clock time, in milliseconds since the Unix epoch, or
+/// `u64::MAX` if the host does not provide one.
+///
+/// The time is asserted by the host and is not checked by the zkVM circuit.
+#[cfg_attr(feature = "export-syscalls", no_mangle)]
+pub extern "C" fn sys_time() -> u64 {
+    let Return(hi, lo) = unsafe { syscall_0(nr::SYS_TIME, null_mut(), 0) };
+    ((hi as u64) << 32) + lo as u64
+}
+
 /// Reads the given number of bytes into the given buffer, posix-style.  Returns
 /// the number of bytes actually read.  On end of file, returns 0.
 ///
@@ -594,6 +769,31 @@ pub unsafe extern "C" fn sys_write(fd: u32, write_ptr: *const u8, nbytes: usize)
     }
 }
 
+/// Moves the position of the given file descriptor, posix-style.
+///
+/// The new position is `offset` bytes from the start of the file, the current
+/// position, or the end of the file when `whence` is [seek::SET], [seek::CUR]
+/// or [seek::END] respectively. Returns the new position, measured from the
+/// start of the file.
+///
+/// The host fails execution if the file descriptor is not seekable.
+#[cfg_attr(feature = "export-syscalls", no_mangle)]
+pub extern "C" fn sys_seek(fd: u32, offset: i64, whence: u32) -> u64 {
+    let offset = offset as u64;
+    let Return(lo, hi) = unsafe {
+        syscall_4(
+            nr::SYS_SEEK,
+            null_mut(),
+            0,
+            fd,
+            whence,
+            offset as u32,
+            (offset >> 32) as u32,
+        )
+    };
+    ((hi as u64) << 32) | lo as u64
+}
+
 /// Retrieves the value of an environment variable, and stores as much
 /// of it as it can it in the memory at [out_words, out_words +
 /// out_nwords).
@@ -680,49 +880,7 @@ pub extern "C" fn sys_alloc_words(nwords: usize) -> *mut u32 {
 #[cfg(feature = "export-syscalls")]
 #[no_mangle]
 pub unsafe extern "C" fn sys_alloc_aligned(bytes: usize, align: usize) -> *mut u8 {
-    #[cfg(target_os = "zkvm")]
-    extern "C" {
-        // This symbol is defined by the loader and marks the end
-        // of all elf sections, so this is where we start our
-        // heap.
-        //
-        // This is generated automatically by the linker; see
-        // https://lld.llvm.org/ELF/linker_script.html#sections-command
-        static _end: u8;
-    }
-
-    // Pointer to next heap address to use, or 0 if the heap has not yet been
-    // initialized.
-    static mut HEAP_POS: usize = 0;
-
-    // SAFETY: Single threaded, so nothing else can touch this while we're working.
-    let mut heap_pos = unsafe { HEAP_POS };
-
-    #[cfg(target_os = "zkvm")]
-    if heap_pos == 0 {
-        heap_pos = unsafe { (&_end) as *const u8 as usize };
-    }
-
-    // Honor requested alignment if larger than word size.
-    // Note: align is typically a power of two.
-    let align = usize::max(align, WORD_SIZE);
-
-    let offset = heap_pos & (align - 1);
-    if offset != 0 {
-        heap_pos += align - offset;
-    }
-
-    let ptr = heap_pos as *mut u8;
-    heap_pos += bytes;
-
-    // Check to make sure heap doesn't collide with SYSTEM memory.
-    if crate::memory::SYSTEM.start() < heap_pos {
-        const MSG: &[u8] = "Out of memory!".as_bytes();
-        unsafe { sys_panic(MSG.as_ptr(), MSG.len()) };
-    }
-
-    unsafe { HEAP_POS = heap_pos };
-    ptr
+    crate::heap::alloc_aligned(bytes, align)
 }
 
 /// Send an image ID and journal hash to the host to request the post state digest and system exit
@@ -770,6 +928,42 @@ pub unsafe extern "C" fn sys_verify(
     }
 }
 
+/// Like [sys_verify], for a receipt whose output also commits to the named
+/// output channels with the given digest.
+///
+/// # Safety
+///
+/// `image_id`, `journal_digest`, `channels_digest` and `from_host_buf` must be
+/// aligned and dereferenceable.
+#[cfg(feature = "export-syscalls")]
+#[no_mangle]
+pub unsafe extern "C" fn sys_verify_channels(
+    image_id: *const [u32; DIGEST_WORDS],
+    journal_digest: *const [u32; DIGEST_WORDS],
+    channels_digest: *const [u32; DIGEST_WORDS],
+    from_host_buf: *mut [u32; DIGEST_WORDS + 1],
+) {
+    let mut to_host = [0u32; 3 * DIGEST_WORDS];
+    to_host[..DIGEST_WORDS].copy_from_slice(unsafe { &*image_id });
+    to_host[DIGEST_WORDS..2 * DIGEST_WORDS].copy_from_slice(unsafe { &*journal_digest });
+    to_host[2 * DIGEST_WORDS..].copy_from_slice(unsafe { &*channels_digest });
+
+    let Return(a0, _) = unsafe {
+        syscall_2(
+            nr::SYS_VERIFY,
+            from_host_buf as *mut u32,
+            DIGEST_WORDS + 1,
+            to_host.as_ptr() as u32,
+            3 * DIGEST_BYTES as u32,
+        )
+    };
+
+    if a0 != 0 {
+        const MSG: &[u8] = "sys_verify returned error result".as_bytes();
+        unsafe { sys_panic(MSG.as_ptr(), MSG.len()) };
+    }
+}
+
 /// Send a ReceiptClaim digest to the host to request verification.
 ///
 /// A cooperative prover will only return if there is a verifying proof
@@ -0,0 +1,294 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Selective disclosure of the output of a guest.
+//!
+//! Instead of committing its output to the journal, a guest can commit the
+//! root of a [DisclosureTree] over the output, split into chunks of a fixed
+//! size, with [commit_disclosable](crate::guest::env::commit_disclosable). The
+//! holder of the output can then reveal chosen chunks with a [Disclosure],
+//! which a verifier checks against the journal of the receipt, while the other
+//! chunks stay private.
+//!
+//! Each leaf of the tree is salted with a digest derived from a secret seed,
+//! so that a chunk that is not disclosed cannot be recovered by hashing
+//! guesses of it. The seed should be passed to the guest as private input, by
+//! the party that will produce the disclosures.
+
+use alloc::vec::Vec;
+
+use risc0_binfmt::tagged_struct;
+use risc0_zkp::verify::VerificationError;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    merkle_file::{path_root, MerkleTree},
+    sha::{Digest, Impl, Sha256},
+    Journal,
+};
+
+/// A Merkle tree over the chunks of the output of a guest, whose root the
+/// guest commits to its journal.
+///
+/// The leaves are salted with digests derived from `seed`, which must be kept
+/// secret for the chunks that are not disclosed to stay private.
+pub struct DisclosureTree<'a> {
+    data: &'a [u8],
+    chunk_size: u32,
+    seed: Digest,
+    tree: MerkleTree,
+}
+
+impl<'a> DisclosureTree<'a> {
+    /// Build the tree over `data`, split into chunks of `chunk_size` bytes,
+    /// with leaves salted by `seed`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    pub fn new(data: &'a [u8], chunk_size: u32, seed: Digest) -> Self {
+        assert!(chunk_size > 0, "disclosure chunk size must not be zero");
+        let leaves = data
+            .chunks(chunk_size as usize)
+            .enumerate()
+            .map(|(index, chunk)| leaf(&salt(&seed, index as u64), chunk))
+            .collect();
+        Self {
+            data,
+            chunk_size,
+            seed,
+            tree: MerkleTree::from_leaves(leaves, data.len() as u64),
+        }
+    }
+
+    /// The root of the tree, which binds the chunks together with the chunk
+    /// size and the length of the data.
+    pub fn root(&self) -> Digest {
+        root(
+            &self.tree.tree_root(),
+            self.chunk_size,
+            self.data.len() as u64,
+        )
+    }
+
+    /// The number of chunks the data is split into.
+    pub fn num_chunks(&self) -> u64 {
+        (self.data.len() as u64).div_ceil(self.chunk_size as u64)
+    }
+
+    /// Reveal the chunks at the given indices.
+    ///
+    /// Indices past the last chunk are ignored.
+    pub fn disclose(&self, indices: impl IntoIterator<Item = u64>) -> Disclosure {
+        let chunks = indices
+            .into_iter()
+            .filter(|&index| index < self.num_chunks())
+            .map(|index| {
+                let start = index as usize * self.chunk_size as usize;
+                let end = self.data.len().min(start + self.chunk_size as usize);
+                DisclosedChunk {
+                    index,
+                    salt: salt(&self.seed, index),
+                    data: self.data[start..end].to_vec(),
+                    proof: self.tree.proof(index as usize),
+                }
+            })
+            .collect();
+        Disclosure {
+            chunk_size: self.chunk_size,
+            len: self.data.len() as u64,
+            chunks,
+        }
+    }
+}
+
+/// Chunks revealed from the output of a guest, with proofs of their inclusion
+/// in the [DisclosureTree] committed by the guest.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Disclosure {
+    /// The size in bytes of the chunks of the tree.
+    pub chunk_size: u32,
+
+    /// The length in bytes of the output the tree is built over.
+    pub len: u64,
+
+    /// The revealed chunks.
+    pub chunks: Vec<DisclosedChunk>,
+}
+
+/// A chunk revealed by a [Disclosure].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisclosedChunk {
+    /// The position of the chunk in the output.
+    pub index: u64,
+
+    /// The salt of the leaf of the chunk.
+    pub salt: Digest,
+
+    /// The bytes of the chunk.
+    pub data: Vec<u8>,
+
+    /// The siblings on the path from the leaf of the chunk to the root.
+    pub proof: Vec<Digest>,
+}
+
+impl Disclosure {
+    /// Check the revealed chunks against the root of a [DisclosureTree].
+    ///
+    /// A disclosure must reveal at least one chunk, unless the data is empty.
+    pub fn verify(&self, root: &Digest) -> Result<(), VerificationError> {
+        if self.chunk_size == 0 {
+            return Err(VerificationError::ReceiptFormatError);
+        }
+        let num_chunks = self.len.div_ceil(self.chunk_size as u64);
+        let mut tree_root = None;
+        for chunk in &self.chunks {
+            let chunk_root = self.tree_root(chunk, num_chunks)?;
+            if *tree_root.get_or_insert(chunk_root) != chunk_root {
+                return Err(VerificationError::JournalDigestMismatch);
+            }
+        }
+        // Only the tree of empty data can be checked without a chunk.
+        let tree_root = match (tree_root, num_chunks) {
+            (Some(tree_root), _) => tree_root,
+            (None, 0) => Digest::ZERO,
+            (None, _) => return Err(VerificationError::ReceiptFormatError),
+        };
+        match self::root(&tree_root, self.chunk_size, self.len) == *root {
+            true => Ok(()),
+            false => Err(VerificationError::JournalDigestMismatch),
+        }
+    }
+
+    /// Check the revealed chunks against a journal that holds the root of a
+    /// [DisclosureTree], as committed by
+    /// [commit_disclosable](crate::guest::env::commit_disclosable).
+    ///
+    /// The journal should come from a verified receipt.
+    pub fn verify_journal(&self, journal: &Journal) -> Result<(), VerificationError> {
+        let root = Digest::try_from(journal.bytes.as_slice())
+            .map_err(|_| VerificationError::ReceiptFormatError)?;
+        self.verify(&root)
+    }
+
+    // The root of the tree computed from the path of a chunk, after checking
+    // that the chunk has the length its position in the output implies.
+    fn tree_root(
+        &self,
+        chunk: &DisclosedChunk,
+        num_chunks: u64,
+    ) -> Result<Digest, VerificationError> {
+        let start = chunk.index.saturating_mul(self.chunk_size as u64);
+        let expected_len = self.len.saturating_sub(start).min(self.chunk_size as u64);
+        if chunk.index >= num_chunks || chunk.data.len() as u64 != expected_len {
+            return Err(VerificationError::ReceiptFormatError);
+        }
+        path_root(
+            num_chunks,
+            chunk.index,
+            leaf(&chunk.salt, &chunk.data),
+            &chunk.proof,
+        )
+        .ok_or(VerificationError::ReceiptFormatError)
+    }
+}
+
+fn salt(seed: &Digest, index: u64) -> Digest {
+    tagged_struct::<Impl>(
+        "risc0.DisclosureSalt",
+        &[*seed],
+        &[index as u32, (index >> 32) as u32],
+    )
+}
+
+fn leaf(salt: &Digest, chunk: &[u8]) -> Digest {
+    tagged_struct::<Impl>(
+        "risc0.DisclosedChunk",
+        &[*salt, *Impl::hash_bytes(chunk)],
+        &[],
+    )
+}
+
+fn root(tree_root: &Digest, chunk_size: u32, len: u64) -> Digest {
+    tagged_struct::<Impl>(
+        "risc0.DisclosureTree",
+        &[*tree_root],
+        &[chunk_size, len as u32, (len >> 32) as u32],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkp::verify::VerificationError;
+
+    use super::DisclosureTree;
+    use crate::{sha::Digest, Journal};
+
+    fn data(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 7 + i / 251) as u8).collect()
+    }
+
+    #[test]
+    fn disclose_chunks() {
+        let seed = Digest::from([3; 8]);
+        for len in [1, 64, 100, 320] {
+            let data = data(len);
+            let tree = DisclosureTree::new(&data, 64, seed);
+            let journal = Journal::new(tree.root().as_bytes().to_vec());
+            let all = tree.disclose(0..tree.num_chunks());
+            all.verify_journal(&journal).unwrap();
+            let revealed: Vec<u8> = all
+                .chunks
+                .iter()
+                .flat_map(|chunk| chunk.data.clone())
+                .collect();
+            assert_eq!(revealed, data);
+
+            let last = tree.disclose([tree.num_chunks() - 1]);
+            last.verify(&tree.root()).unwrap();
+        }
+    }
+
+    #[test]
+    fn rejects_tampering() {
+        let data = data(300);
+        let tree = DisclosureTree::new(&data, 64, Digest::from([3; 8]));
+        let root = tree.root();
+        let disclosure = tree.disclose([1, 4]);
+        disclosure.verify(&root).unwrap();
+
+        let mut other = disclosure.clone();
+        other.chunks[0].data[0] ^= 1;
+        assert_eq!(
+            other.verify(&root),
+            Err(VerificationError::JournalDigestMismatch)
+        );
+        let mut other = disclosure.clone();
+        other.chunks[1].index = 3;
+        assert!(other.verify(&root).is_err());
+        let mut other = disclosure.clone();
+        other.len += 1;
+        assert!(other.verify(&root).is_err());
+        let mut other = disclosure.clone();
+        other.chunks.clear();
+        assert_eq!(
+            other.verify(&root),
+            Err(VerificationError::ReceiptFormatError)
+        );
+
+        // The salt hides chunks that are not disclosed.
+        let other = DisclosureTree::new(&data, 64, Digest::from([4; 8]));
+        assert_ne!(other.root(), root);
+    }
+}
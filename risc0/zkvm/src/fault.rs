@@ -0,0 +1,121 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Information about a guest panic, or a guest running out of memory.
+
+use alloc::{format, string::String, vec::Vec};
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// A panic in the guest.
+///
+/// On the host, a panic ends execution with an error that can be downcast to
+/// this type. A guest that calls [crate::guest::env::commit_panics] instead
+/// commits it to the journal and halts with [GuestPanic::EXIT_CODE], so that it
+/// is recorded in the receipt.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GuestPanic {
+    /// The panic message.
+    pub message: String,
+    /// The source file of the panic, if known.
+    pub file: Option<String>,
+    /// The line of the panic, if known.
+    pub line: Option<u32>,
+    /// The column of the panic, if known.
+    pub column: Option<u32>,
+    /// The functions on the guest call stack, innermost first.
+    ///
+    /// This is only filled in by the host when the profiler is enabled, and is
+    /// never committed by the guest.
+    pub backtrace: Vec<String>,
+}
+
+impl GuestPanic {
+    /// The user exit code of a guest that commits a panic.
+    pub const EXIT_CODE: u8 = 101;
+
+    /// Construct a [GuestPanic] from a formatted [core::panic::PanicInfo] and
+    /// its location.
+    pub(crate) fn new(formatted: &str, location: Option<(&str, u32, u32)>) -> Self {
+        let Some((file, line, column)) = location else {
+            return Self {
+                message: formatted.into(),
+                ..Default::default()
+            };
+        };
+        let prefix = format!("panicked at {file}:{line}:{column}:\n");
+        Self {
+            message: formatted.strip_prefix(&prefix).unwrap_or(formatted).into(),
+            file: Some(file.into()),
+            line: Some(line),
+            column: Some(column),
+            backtrace: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for GuestPanic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Guest panicked: {}", self.message)?;
+        if let (Some(file), Some(line), Some(column)) = (&self.file, self.line, self.column) {
+            write!(f, " at {file}:{line}:{column}")?;
+        }
+        for (i, frame) in self.backtrace.iter().enumerate() {
+            write!(f, "\n  {i}: {frame}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for GuestPanic {}
+
+/// A guest allocation that did not fit in the heap.
+///
+/// On the host, this ends execution with an error that can be downcast to this
+/// type. The heap is bounded by the guest memory layout, and by the limit set
+/// with `ExecutorEnvBuilder::heap_limit`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GuestOutOfMemory {
+    /// The size of the allocation, in bytes.
+    pub bytes: u32,
+    /// The number of bytes in use by the heap when the allocation was made.
+    pub used: u32,
+    /// The size of the heap, in bytes.
+    pub limit: u32,
+    /// The guest program counter at which execution ended.
+    pub pc: u32,
+    /// The functions on the guest call stack, innermost first.
+    ///
+    /// This is only filled in when the profiler is enabled.
+    pub backtrace: Vec<String>,
+}
+
+impl fmt::Display for GuestOutOfMemory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Guest out of memory: allocation of {} bytes at pc 0x{:08x}, with {} of {} heap bytes in use",
+            self.bytes, self.pc, self.used, self.limit
+        )?;
+        for (i, frame) in self.backtrace.iter().enumerate() {
+            write!(f, "\n  {i}: {frame}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for GuestOutOfMemory {}
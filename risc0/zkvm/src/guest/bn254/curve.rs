@@ -0,0 +1,401 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The BN254 groups G1, over `Fq`, and G2, over `Fq2` on the sextic twist.
+//!
+//! Points are kept in affine coordinates. Each addition needs an inversion, but
+//! the host supplies inversions for the price of a multiplication, so this is
+//! cheaper than projective coordinates in the guest.
+
+use alloc::{vec, vec::Vec};
+use core::ops::{Add, Mul, Neg, Sub};
+
+use super::{fields::Fq, fields::Fq2, Bn254Error};
+use crate::guest::sig::bigint::{bit, from_be_bytes, from_hex, U256};
+
+// The order of G1 and G2.
+pub(crate) const R: U256 =
+    from_hex("30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001");
+
+const G1_B: Fq = Fq::from_hex("0000000000000000000000000000000000000000000000000000000000000003");
+
+// 3 / (9 + u), the coefficient of the twist.
+const G2_B: Fq2 = Fq2::new(
+    Fq::from_hex("2b149d40ceb8aaae81be18991be06ac3b5b4c5e559dbefa33267e6dc24a138e5"),
+    Fq::from_hex("009713b03af0fed4cd2cafadeed8fdf4a74fa084e52d1852e4a2bd0685c315d2"),
+);
+
+const G1_GENERATOR: Point<Fq> = Point {
+    x: Fq::from_hex("0000000000000000000000000000000000000000000000000000000000000001"),
+    y: Fq::from_hex("0000000000000000000000000000000000000000000000000000000000000002"),
+    infinity: false,
+};
+
+const G2_GENERATOR: Point<Fq2> = Point {
+    x: Fq2::new(
+        Fq::from_hex("1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed"),
+        Fq::from_hex("198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2"),
+    ),
+    y: Fq2::new(
+        Fq::from_hex("12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa"),
+        Fq::from_hex("090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b"),
+    ),
+    infinity: false,
+};
+
+/// The operations on `Fq` and `Fq2` that the curve arithmetic needs.
+pub(crate) trait Field:
+    Copy + PartialEq + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Neg<Output = Self>
+{
+    const ZERO: Self;
+    fn is_zero(&self) -> bool;
+    fn inverse(&self) -> Option<Self>;
+}
+
+impl Field for Fq {
+    const ZERO: Self = Fq::ZERO;
+
+    fn is_zero(&self) -> bool {
+        Fq::is_zero(self)
+    }
+
+    fn inverse(&self) -> Option<Self> {
+        Fq::inverse(self)
+    }
+}
+
+impl Field for Fq2 {
+    const ZERO: Self = Fq2::ZERO;
+
+    fn is_zero(&self) -> bool {
+        Fq2::is_zero(self)
+    }
+
+    fn inverse(&self) -> Option<Self> {
+        Fq2::inverse(self)
+    }
+}
+
+/// An affine point on `y^2 = x^3 + b`, or the point at infinity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Point<F> {
+    pub(crate) x: F,
+    pub(crate) y: F,
+    pub(crate) infinity: bool,
+}
+
+impl<F: Field> Point<F> {
+    const IDENTITY: Self = Self {
+        x: F::ZERO,
+        y: F::ZERO,
+        infinity: true,
+    };
+
+    fn is_on_curve(&self, b: F) -> bool {
+        self.infinity || self.y * self.y == self.x * self.x * self.x + b
+    }
+
+    fn neg(&self) -> Self {
+        Self {
+            y: -self.y,
+            ..*self
+        }
+    }
+
+    /// The slope of the line through `self` and `other`, which is the tangent
+    /// if they are equal, or `None` if the line is vertical.
+    pub(crate) fn slope(&self, other: &Self) -> Option<F> {
+        if self.infinity || other.infinity {
+            return None;
+        }
+        if self.x == other.x {
+            if self.y != other.y || self.y.is_zero() {
+                return None;
+            }
+            let xx = self.x * self.x;
+            let inv = (self.y + self.y).inverse().unwrap();
+            Some((xx + xx + xx) * inv)
+        } else {
+            let inv = (other.x - self.x).inverse().unwrap();
+            Some((other.y - self.y) * inv)
+        }
+    }
+
+    /// The sum of `self` and `other`, given the slope of the line through them.
+    pub(crate) fn add_with_slope(&self, other: &Self, slope: F) -> Self {
+        let x = slope * slope - self.x - other.x;
+        let y = slope * (self.x - x) - self.y;
+        Self {
+            x,
+            y,
+            infinity: false,
+        }
+    }
+
+    pub(crate) fn add(&self, other: &Self) -> Self {
+        if self.infinity {
+            return *other;
+        }
+        if other.infinity {
+            return *self;
+        }
+        match self.slope(other) {
+            Some(slope) => self.add_with_slope(other, slope),
+            None => Self::IDENTITY,
+        }
+    }
+
+    pub(crate) fn mul(&self, scalar: &U256) -> Self {
+        let mut acc = Self::IDENTITY;
+        for idx in (0..256).rev() {
+            acc = acc.add(&acc);
+            if bit(scalar, idx) {
+                acc = acc.add(self);
+            }
+        }
+        acc
+    }
+}
+
+/// A point of G1, the group of points of `y^2 = x^3 + 3` over `Fq`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct G1Affine(pub(crate) Point<Fq>);
+
+impl G1Affine {
+    /// The point at infinity.
+    pub const fn identity() -> Self {
+        Self(Point::IDENTITY)
+    }
+
+    /// The generator `(1, 2)`.
+    pub const fn generator() -> Self {
+        Self(G1_GENERATOR)
+    }
+
+    /// Construct the point `(x, y)`, checking that it is on the curve.
+    ///
+    /// G1 has prime order, so every point on the curve is in the group.
+    pub fn new(x: Fq, y: Fq) -> Result<Self, Bn254Error> {
+        let point = Point {
+            x,
+            y,
+            infinity: false,
+        };
+        match point.is_on_curve(G1_B) {
+            true => Ok(Self(point)),
+            false => Err(Bn254Error::NotOnCurve),
+        }
+    }
+
+    /// Decode a point as the big-endian coordinates `x || y`, with the point
+    /// at infinity encoded as zeros, as in the Ethereum precompiles.
+    pub fn from_be_bytes(bytes: &[u8; 64]) -> Result<Self, Bn254Error> {
+        let coordinate = |range: core::ops::Range<usize>| {
+            Fq::from_be_bytes(bytes[range].try_into().unwrap())
+                .ok_or(Bn254Error::InvalidFieldElement)
+        };
+        let (x, y) = (coordinate(0..32)?, coordinate(32..64)?);
+        if x.is_zero() && y.is_zero() {
+            return Ok(Self::identity());
+        }
+        Self::new(x, y)
+    }
+
+    /// Encode this point as in [G1Affine::from_be_bytes].
+    pub fn to_be_bytes(&self) -> [u8; 64] {
+        let mut out = [0u8; 64];
+        if !self.0.infinity {
+            out[..32].copy_from_slice(&self.0.x.to_be_bytes());
+            out[32..].copy_from_slice(&self.0.y.to_be_bytes());
+        }
+        out
+    }
+
+    /// The coordinates `(x, y)`, or `None` for the point at infinity.
+    pub fn coordinates(&self) -> Option<(Fq, Fq)> {
+        (!self.0.infinity).then_some((self.0.x, self.0.y))
+    }
+
+    /// Whether this is the point at infinity.
+    pub fn is_identity(&self) -> bool {
+        self.0.infinity
+    }
+
+    /// Multiply by a big-endian scalar.
+    pub fn mul(&self, scalar: &[u8; 32]) -> Self {
+        Self(self.0.mul(&from_be_bytes(scalar)))
+    }
+}
+
+impl Add for G1Affine {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0.add(&rhs.0))
+    }
+}
+
+impl Neg for G1Affine {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(self.0.neg())
+    }
+}
+
+/// A point of G2, the order-r subgroup of `y^2 = x^3 + 3 / (9 + u)` over
+/// `Fq2`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct G2Affine(pub(crate) Point<Fq2>);
+
+impl G2Affine {
+    /// The point at infinity.
+    pub const fn identity() -> Self {
+        Self(Point::IDENTITY)
+    }
+
+    /// The generator used by Ethereum.
+    pub const fn generator() -> Self {
+        Self(G2_GENERATOR)
+    }
+
+    /// Construct the point `(x, y)`, checking that it is on the twist and in
+    /// the subgroup of order r.
+    ///
+    /// The subgroup check is a full scalar multiplication, so decoded points
+    /// are best reused.
+    pub fn new(x: Fq2, y: Fq2) -> Result<Self, Bn254Error> {
+        let point = Point {
+            x,
+            y,
+            infinity: false,
+        };
+        if !point.is_on_curve(G2_B) {
+            return Err(Bn254Error::NotOnCurve);
+        }
+        match point.mul(&R).infinity {
+            true => Ok(Self(point)),
+            false => Err(Bn254Error::NotInSubgroup),
+        }
+    }
+
+    /// Decode a point as the big-endian coordinates
+    /// `x.c1 || x.c0 || y.c1 || y.c0`, with the point at infinity encoded as
+    /// zeros, as in the Ethereum pairing precompile.
+    pub fn from_be_bytes(bytes: &[u8; 128]) -> Result<Self, Bn254Error> {
+        let coordinate = |offset: usize| {
+            let c1 = Fq::from_be_bytes(bytes[offset..offset + 32].try_into().unwrap());
+            let c0 = Fq::from_be_bytes(bytes[offset + 32..offset + 64].try_into().unwrap());
+            match (c0, c1) {
+                (Some(c0), Some(c1)) => Ok(Fq2::new(c0, c1)),
+                _ => Err(Bn254Error::InvalidFieldElement),
+            }
+        };
+        let (x, y) = (coordinate(0)?, coordinate(64)?);
+        if x.is_zero() && y.is_zero() {
+            return Ok(Self::identity());
+        }
+        Self::new(x, y)
+    }
+
+    /// Encode this point as in [G2Affine::from_be_bytes].
+    pub fn to_be_bytes(&self) -> [u8; 128] {
+        let mut out = [0u8; 128];
+        if !self.0.infinity {
+            for (chunk, coefficient) in
+                out.chunks_exact_mut(32)
+                    .zip([self.0.x.c1, self.0.x.c0, self.0.y.c1, self.0.y.c0])
+            {
+                chunk.copy_from_slice(&coefficient.to_be_bytes());
+            }
+        }
+        out
+    }
+
+    /// The coordinates `(x, y)`, or `None` for the point at infinity.
+    pub fn coordinates(&self) -> Option<(Fq2, Fq2)> {
+        (!self.0.infinity).then_some((self.0.x, self.0.y))
+    }
+
+    /// Whether this is the point at infinity.
+    pub fn is_identity(&self) -> bool {
+        self.0.infinity
+    }
+
+    /// Multiply by a big-endian scalar.
+    pub fn mul(&self, scalar: &[u8; 32]) -> Self {
+        Self(self.0.mul(&from_be_bytes(scalar)))
+    }
+}
+
+impl Add for G2Affine {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0.add(&rhs.0))
+    }
+}
+
+impl Neg for G2Affine {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(self.0.neg())
+    }
+}
+
+/// Compute `sum(scalars[i] * points[i])`, with big-endian scalars.
+///
+/// This uses Pippenger's bucket method, which for many points needs far fewer
+/// additions than multiplying each point separately.
+///
+/// Panics if `points` and `scalars` have different lengths.
+pub fn g1_msm(points: &[G1Affine], scalars: &[[u8; 32]]) -> G1Affine {
+    assert_eq!(
+        points.len(),
+        scalars.len(),
+        "each point needs exactly one scalar"
+    );
+    let scalars: Vec<U256> = scalars.iter().map(from_be_bytes).collect();
+
+    // A window of c bits costs about 256 / c * (n + 2^c) additions.
+    let window = match points.len() {
+        0..=3 => 1,
+        4..=31 => 3,
+        32..=255 => 5,
+        _ => 8,
+    };
+    let mut acc = Point::IDENTITY;
+    for start in (0..256).step_by(window).rev() {
+        for _ in 0..window {
+            acc = acc.add(&acc);
+        }
+        let mut buckets = vec![Point::IDENTITY; (1 << window) - 1];
+        for (point, scalar) in points.iter().zip(&scalars) {
+            let digit = (start..usize::min(start + window, 256))
+                .rev()
+                .fold(0, |digit, idx| digit << 1 | bit(scalar, idx) as usize);
+            if digit != 0 {
+                buckets[digit - 1] = buckets[digit - 1].add(&point.0);
+            }
+        }
+        // sum(i * buckets[i - 1]) as a running sum from the top.
+        let mut running = Point::IDENTITY;
+        for bucket in buckets.iter().rev() {
+            running = running.add(bucket);
+            acc = acc.add(&running);
+        }
+    }
+    G1Affine(acc)
+}
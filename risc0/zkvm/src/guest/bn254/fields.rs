@@ -0,0 +1,362 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The BN254 base field and its tower of extensions.
+//!
+//! The tower is the one used by Ethereum: `Fq2 = Fq[u] / (u^2 + 1)`,
+//! `Fq6 = Fq2[v] / (v^3 - (9 + u))`, and `Fq12 = Fq6[w] / (w^2 - v)`.
+
+use core::ops::{Add, Mul, Neg, Sub};
+
+use crate::guest::sig::bigint::{
+    add_mod, from_be_bytes, from_hex, inv_mod, is_zero, lt, mul_mod, sub_mod, to_be_bytes, ONE,
+    U256, ZERO,
+};
+
+// The field modulus.
+pub(crate) const P: U256 =
+    from_hex("30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47");
+
+/// An element of the BN254 base field.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Fq(pub(crate) U256);
+
+impl Fq {
+    /// The additive identity.
+    pub const ZERO: Self = Self(ZERO);
+
+    /// The multiplicative identity.
+    pub const ONE: Self = Self(ONE);
+
+    pub(crate) const fn from_hex(hex: &str) -> Self {
+        Self(from_hex(hex))
+    }
+
+    /// Decode a big-endian field element, or `None` if it is not less than the
+    /// modulus.
+    pub fn from_be_bytes(bytes: &[u8; 32]) -> Option<Self> {
+        let value = from_be_bytes(bytes);
+        lt(&value, &P).then_some(Self(value))
+    }
+
+    /// Encode this element as big-endian bytes.
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        to_be_bytes(&self.0)
+    }
+
+    /// Whether this is zero.
+    pub fn is_zero(&self) -> bool {
+        is_zero(&self.0)
+    }
+
+    /// Compute `self * self`.
+    pub fn square(&self) -> Self {
+        *self * *self
+    }
+
+    /// Compute `self + self`.
+    pub fn double(&self) -> Self {
+        *self + *self
+    }
+
+    /// The multiplicative inverse, or `None` for zero.
+    pub fn inverse(&self) -> Option<Self> {
+        inv_mod(&self.0, &P).map(Self)
+    }
+}
+
+impl Add for Fq {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(add_mod(&self.0, &rhs.0, &P))
+    }
+}
+
+impl Sub for Fq {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(sub_mod(&self.0, &rhs.0, &P))
+    }
+}
+
+impl Mul for Fq {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self(mul_mod(&self.0, &rhs.0, &P))
+    }
+}
+
+impl Neg for Fq {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::ZERO - self
+    }
+}
+
+/// An element `c0 + c1 * u` of the quadratic extension, with `u^2 = -1`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Fq2 {
+    /// The real part.
+    pub c0: Fq,
+    /// The imaginary part.
+    pub c1: Fq,
+}
+
+impl Fq2 {
+    /// The additive identity.
+    pub const ZERO: Self = Self::new(Fq::ZERO, Fq::ZERO);
+
+    /// The multiplicative identity.
+    pub const ONE: Self = Self::new(Fq::ONE, Fq::ZERO);
+
+    /// Construct `c0 + c1 * u`.
+    pub const fn new(c0: Fq, c1: Fq) -> Self {
+        Self { c0, c1 }
+    }
+
+    /// Whether this is zero.
+    pub fn is_zero(&self) -> bool {
+        self.c0.is_zero() && self.c1.is_zero()
+    }
+
+    /// Compute `self * self`.
+    pub fn square(&self) -> Self {
+        // (a + bu)^2 = (a + b)(a - b) + 2abu
+        let ab = self.c0 * self.c1;
+        Self::new((self.c0 + self.c1) * (self.c0 - self.c1), ab.double())
+    }
+
+    /// Compute `self + self`.
+    pub fn double(&self) -> Self {
+        *self + *self
+    }
+
+    /// The complex conjugate `c0 - c1 * u`, which is also the Frobenius map.
+    pub fn conjugate(&self) -> Self {
+        Self::new(self.c0, -self.c1)
+    }
+
+    /// Multiply by the element of `Fq`.
+    pub fn scale(&self, k: Fq) -> Self {
+        Self::new(self.c0 * k, self.c1 * k)
+    }
+
+    /// The multiplicative inverse, or `None` for zero.
+    pub fn inverse(&self) -> Option<Self> {
+        let norm = self.c0.square() + self.c1.square();
+        norm.inverse().map(|inv| self.conjugate().scale(inv))
+    }
+
+    // Multiply by the non-residue 9 + u that defines Fq6.
+    pub(crate) fn mul_by_nonresidue(&self) -> Self {
+        let nine = |a: Fq| a.double().double().double() + a;
+        Self::new(nine(self.c0) - self.c1, self.c0 + nine(self.c1))
+    }
+}
+
+impl Add for Fq2 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.c0 + rhs.c0, self.c1 + rhs.c1)
+    }
+}
+
+impl Sub for Fq2 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.c0 - rhs.c0, self.c1 - rhs.c1)
+    }
+}
+
+impl Mul for Fq2 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        // Karatsuba: three multiplications instead of four.
+        let aa = self.c0 * rhs.c0;
+        let bb = self.c1 * rhs.c1;
+        let cross = (self.c0 + self.c1) * (rhs.c0 + rhs.c1);
+        Self::new(aa - bb, cross - aa - bb)
+    }
+}
+
+impl Neg for Fq2 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.c0, -self.c1)
+    }
+}
+
+/// An element `c0 + c1 * v + c2 * v^2` of the sextic extension.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Fq6 {
+    pub(crate) c0: Fq2,
+    pub(crate) c1: Fq2,
+    pub(crate) c2: Fq2,
+}
+
+impl Fq6 {
+    pub(crate) const ZERO: Self = Self::new(Fq2::ZERO, Fq2::ZERO, Fq2::ZERO);
+
+    pub(crate) const ONE: Self = Self::new(Fq2::ONE, Fq2::ZERO, Fq2::ZERO);
+
+    pub(crate) const fn new(c0: Fq2, c1: Fq2, c2: Fq2) -> Self {
+        Self { c0, c1, c2 }
+    }
+
+    // Multiply by v, using v^3 = 9 + u.
+    fn mul_by_v(&self) -> Self {
+        Self::new(self.c2.mul_by_nonresidue(), self.c0, self.c1)
+    }
+
+    fn inverse(&self) -> Option<Self> {
+        let t0 = self.c0.square() - (self.c1 * self.c2).mul_by_nonresidue();
+        let t1 = self.c2.square().mul_by_nonresidue() - self.c0 * self.c1;
+        let t2 = self.c1.square() - self.c0 * self.c2;
+        let norm = self.c0 * t0 + (self.c2 * t1 + self.c1 * t2).mul_by_nonresidue();
+        norm.inverse()
+            .map(|inv| Self::new(t0 * inv, t1 * inv, t2 * inv))
+    }
+}
+
+impl Add for Fq6 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.c0 + rhs.c0, self.c1 + rhs.c1, self.c2 + rhs.c2)
+    }
+}
+
+impl Sub for Fq6 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.c0 - rhs.c0, self.c1 - rhs.c1, self.c2 - rhs.c2)
+    }
+}
+
+impl Mul for Fq6 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        // Karatsuba: six multiplications instead of nine.
+        let (a, b) = (self, rhs);
+        let t0 = a.c0 * b.c0;
+        let t1 = a.c1 * b.c1;
+        let t2 = a.c2 * b.c2;
+        let c0 = ((a.c1 + a.c2) * (b.c1 + b.c2) - t1 - t2).mul_by_nonresidue() + t0;
+        let c1 = (a.c0 + a.c1) * (b.c0 + b.c1) - t0 - t1 + t2.mul_by_nonresidue();
+        let c2 = (a.c0 + a.c2) * (b.c0 + b.c2) - t0 - t2 + t1;
+        Self::new(c0, c1, c2)
+    }
+}
+
+impl Neg for Fq6 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.c0, -self.c1, -self.c2)
+    }
+}
+
+/// An element `c0 + c1 * w` of the degree-12 extension, in which pairings take
+/// their value.
+///
+/// The coefficients are in `Fq6 = Fq2[v] / (v^3 - (9 + u))`, and `w^2 = v`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Fq12 {
+    pub(crate) c0: Fq6,
+    pub(crate) c1: Fq6,
+}
+
+impl Fq12 {
+    /// The multiplicative identity.
+    pub const ONE: Self = Self::new(Fq6::ONE, Fq6::ZERO);
+
+    pub(crate) const fn new(c0: Fq6, c1: Fq6) -> Self {
+        Self { c0, c1 }
+    }
+
+    /// The coefficients of this element in `Fq2`, in the order of the basis
+    /// `1, v, v^2, w, vw, v^2w`.
+    pub fn coefficients(&self) -> [Fq2; 6] {
+        [
+            self.c0.c0, self.c0.c1, self.c0.c2, self.c1.c0, self.c1.c1, self.c1.c2,
+        ]
+    }
+
+    /// Compute `self * self`.
+    pub fn square(&self) -> Self {
+        // (a + bw)^2 = a^2 + b^2 v + 2abw, with a^2 + b^2 v computed as
+        // (a + b)(a + bv) - ab - abv.
+        let ab = self.c0 * self.c1;
+        let c0 = (self.c0 + self.c1) * (self.c0 + self.c1.mul_by_v()) - ab - ab.mul_by_v();
+        Self::new(c0, ab + ab)
+    }
+
+    /// The conjugate `c0 - c1 * w`, which is the Frobenius map to the sixth
+    /// power, and the inverse of elements of norm one.
+    pub fn conjugate(&self) -> Self {
+        Self::new(self.c0, -self.c1)
+    }
+
+    /// The multiplicative inverse, or `None` for zero.
+    pub fn inverse(&self) -> Option<Self> {
+        let norm = self.c0 * self.c0 - (self.c1 * self.c1).mul_by_v();
+        norm.inverse()
+            .map(|inv| Self::new(self.c0 * inv, -(self.c1 * inv)))
+    }
+
+    /// Raise to the power of the little-endian words `exp`.
+    pub(crate) fn pow(&self, exp: &[u32]) -> Self {
+        let mut out = Self::ONE;
+        for word in exp.iter().rev() {
+            for idx in (0..32).rev() {
+                out = out.square();
+                if (word >> idx) & 1 == 1 {
+                    out = out * *self;
+                }
+            }
+        }
+        out
+    }
+
+    // Multiply by a line value l0 + (l1 + l2 v) w, whose other coefficients are
+    // zero.
+    pub(crate) fn mul_by_line(&self, l0: &Fq, l1: &Fq2, l2: &Fq2) -> Self {
+        let line = Self::new(
+            Fq6::new(Fq2::new(*l0, Fq::ZERO), Fq2::ZERO, Fq2::ZERO),
+            Fq6::new(*l1, *l2, Fq2::ZERO),
+        );
+        *self * line
+    }
+}
+
+impl Mul for Fq12 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let aa = self.c0 * rhs.c0;
+        let bb = self.c1 * rhs.c1;
+        let cross = (self.c0 + self.c1) * (rhs.c0 + rhs.c1);
+        Self::new(aa + bb.mul_by_v(), cross - aa - bb)
+    }
+}
@@ -0,0 +1,77 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The BN254 (alt_bn128) pairing-friendly curve on the bigint accelerator.
+//!
+//! BN254 is the curve of the Ethereum precompiles at addresses `0x06` to
+//! `0x08`, and of most Groth16 and PLONK verifiers deployed on chain. Its base
+//! field fits in 256 bits, so each field multiplication is a single call to the
+//! bigint accelerator, and inversions are supplied by the host and checked with
+//! one multiplication. This makes verifying a proof from another system in the
+//! guest practical.
+//!
+//! A Groth16 verifier checks
+//! `e(-A, B) * e(alpha, beta) * e(L, gamma) * e(C, delta) == 1`, where `L` is
+//! a multi-scalar multiplication of the verifying key by the public inputs:
+//!
+//! ```no_run
+//! use risc0_zkvm::guest::bn254::{g1_msm, pairing_check, G1Affine, G2Affine};
+//!
+//! # let (a, b, c) = (G1Affine::generator(), G2Affine::generator(), G1Affine::generator());
+//! # let (alpha, beta, gamma, delta) = (a, b, b, b);
+//! # let ic = [a, a];
+//! # let inputs = [[0u8; 32]];
+//! let mut scalars = vec![[0u8; 32]];
+//! scalars[0][31] = 1;
+//! scalars.extend_from_slice(&inputs);
+//! let l = g1_msm(&ic, &scalars);
+//! assert!(pairing_check(&[(-a, b), (alpha, beta), (l, gamma), (c, delta)]));
+//! ```
+
+mod curve;
+mod fields;
+mod pairing;
+
+use core::fmt;
+
+pub use self::{
+    curve::{g1_msm, G1Affine, G2Affine},
+    fields::{Fq, Fq12, Fq2},
+    pairing::{pairing, pairing_check},
+};
+
+/// The reason a point could not be decoded or constructed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Bn254Error {
+    /// A coordinate is not less than the field modulus.
+    InvalidFieldElement,
+    /// The point is not on the curve.
+    NotOnCurve,
+    /// The point is on the twist but not in G2.
+    NotInSubgroup,
+}
+
+impl fmt::Display for Bn254Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidFieldElement => write!(f, "invalid field element"),
+            Self::NotOnCurve => write!(f, "point is not on the curve"),
+            Self::NotInSubgroup => write!(f, "point is not in the prime-order subgroup"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Bn254Error {}
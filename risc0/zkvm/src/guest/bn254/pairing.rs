@@ -0,0 +1,145 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The optimal ate pairing.
+
+use alloc::vec::Vec;
+
+use super::{
+    curve::{G1Affine, G2Affine, Point},
+    fields::{Fq, Fq12, Fq2},
+};
+
+// 6x + 2 for the BN parameter x = 4965661367192848881.
+const ATE_LOOP_COUNT: u128 = 29793968203157093288;
+
+// The Frobenius map on the twist multiplies the conjugated coordinates by
+// (9 + u)^((p - 1) / 3) and (9 + u)^((p - 1) / 2).
+const FROBENIUS_X: Fq2 = Fq2::new(
+    Fq::from_hex("2fb347984f7911f74c0bec3cf559b143b78cc310c2c3330c99e39557176f553d"),
+    Fq::from_hex("16c9e55061ebae204ba4cc8bd75a079432ae2a1d0b7c9dce1665d51c640fcba2"),
+);
+const FROBENIUS_Y: Fq2 = Fq2::new(
+    Fq::from_hex("063cf305489af5dcdc5ec698b6e2f9b9dbaae0eda9c95998dc54014671a0135a"),
+    Fq::from_hex("07c03cbcac41049a0704b5a7ec796f2b21807dc98fa25bd282d37f632623b0e3"),
+);
+
+// Its square multiplies x by (9 + u)^((p^2 - 1) / 3), which is in Fq, and y by
+// -1.
+const FROBENIUS2_X: Fq =
+    Fq::from_hex("30644e72e131a0295e6dd9e7e0acccb0c28f069fbb966e3de4bd44e5607cfd48");
+
+// (p^2 + 1) (p^4 - p^2 + 1) / r, little-endian. This is the final exponent
+// after the factor p^6 - 1, which is applied with a conjugation and an
+// inversion.
+const FINAL_EXP: [u32; 40] = [
+    0x36e3f812, 0x5250a540, 0x96789051, 0xa5635f15, 0x4d5bd1d4, 0xd1138bf5, 0xbe36c7a2, 0xa8ce2533,
+    0x84e09bf6, 0x94f69f6b, 0x50ef3644, 0x42ad1f5e, 0x48c3454c, 0x0fcc420e, 0xecc9952c, 0x758e4408,
+    0x87c6042c, 0xc901bf18, 0xb14bb3b5, 0xa733cd65, 0xcf51b0d8, 0xdf6d76bd, 0x82eb59e1, 0xca64c0fd,
+    0xe39276a1, 0x1d2e5726, 0xa391cae9, 0xc2d1ea74, 0xc82d647e, 0x07409206, 0xa5afdd17, 0x051c6d1a,
+    0x19667af5, 0xb37f6019, 0x5084015b, 0x150e578c, 0xc23998e4, 0xfbdea556, 0xc52f5b83, 0x000fd14c,
+];
+
+struct Pair {
+    p: Point<Fq>,
+    q: Point<Fq2>,
+    t: Point<Fq2>,
+}
+
+impl Pair {
+    // Multiply f by the line through T and `other`, evaluated at P, and move T
+    // to T + other.
+    //
+    // With the twist (x, y) -> (x w^2, y w^3), the line through T with slope
+    // s is y_P - s x_P w + (s x_T - y_T) w^3. Vertical lines lie in Fq6, and
+    // are skipped since the final exponentiation maps them to one.
+    fn step(&mut self, f: &mut Fq12, other: &Point<Fq2>) {
+        let Some(slope) = self.t.slope(other) else {
+            self.t = self.t.add(other);
+            return;
+        };
+        let l1 = -(slope.scale(self.p.x));
+        let l2 = slope * self.t.x - self.t.y;
+        *f = f.mul_by_line(&self.p.y, &l1, &l2);
+        self.t = self.t.add_with_slope(other, slope);
+    }
+}
+
+/// Compute the product of the Miller loops of `pairs`, before the final
+/// exponentiation.
+pub(crate) fn multi_miller_loop(pairs: &[(G1Affine, G2Affine)]) -> Fq12 {
+    let mut pairs: Vec<Pair> = pairs
+        .iter()
+        .filter(|(p, q)| !p.is_identity() && !q.is_identity())
+        .map(|(p, q)| Pair {
+            p: p.0,
+            q: q.0,
+            t: q.0,
+        })
+        .collect();
+
+    let mut f = Fq12::ONE;
+    for idx in (0..ATE_LOOP_COUNT.ilog2()).rev() {
+        f = f.square();
+        for pair in pairs.iter_mut() {
+            let t = pair.t;
+            pair.step(&mut f, &t);
+            if (ATE_LOOP_COUNT >> idx) & 1 == 1 {
+                let q = pair.q;
+                pair.step(&mut f, &q);
+            }
+        }
+    }
+
+    // Finish with the lines to pi(Q) and -pi^2(Q).
+    for pair in pairs.iter_mut() {
+        let q1 = Point {
+            x: pair.q.x.conjugate() * FROBENIUS_X,
+            y: pair.q.y.conjugate() * FROBENIUS_Y,
+            infinity: false,
+        };
+        let q2 = Point {
+            x: pair.q.x.scale(FROBENIUS2_X),
+            ..pair.q
+        };
+        pair.step(&mut f, &q1);
+        pair.step(&mut f, &q2);
+    }
+    f
+}
+
+/// Raise the output of a Miller loop to `(p^12 - 1) / r`.
+pub(crate) fn final_exponentiation(f: &Fq12) -> Fq12 {
+    // The Miller loop of points in G1 and G2 is never zero.
+    let f = f.conjugate() * f.inverse().unwrap();
+    f.pow(&FINAL_EXP)
+}
+
+/// Compute the optimal ate pairing `e(p, q)`.
+///
+/// This is the reduced pairing, raised to exactly `(p^12 - 1) / r`. Some
+/// libraries, including arkworks, return a fixed power of it instead, so values
+/// from different libraries can differ even though [pairing_check] agrees.
+pub fn pairing(p: &G1Affine, q: &G2Affine) -> Fq12 {
+    final_exponentiation(&multi_miller_loop(&[(*p, *q)]))
+}
+
+/// Check whether the product of `e(p, q)` over `pairs` is one.
+///
+/// This is the check made by the Ethereum pairing precompile, and by Groth16
+/// verifiers. It is much cheaper than computing each pairing, since the Miller
+/// loops share their squarings and there is a single final exponentiation.
+pub fn pairing_check(pairs: &[(G1Affine, G2Affine)]) -> bool {
+    final_exponentiation(&multi_miller_loop(pairs)) == Fq12::ONE
+}
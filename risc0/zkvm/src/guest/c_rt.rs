@@ -0,0 +1,257 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The runtime for guests written in C and C++.
+//!
+//! With the `c-runtime` feature, this crate is built as a static library that
+//! C and C++ guests link against, and exports the functions declared in the
+//! `risc0.h` header shipped with `risc0-build`. The entrypoint is the `crt0.S`
+//! shipped alongside it, which calls [risc0_init], runs static constructors,
+//! calls `main`, and passes its result to [risc0_exit].
+//!
+//! Besides the zkVM I/O functions, this provides `malloc` and friends, and the
+//! C++ allocation and ABI functions that freestanding C++ needs, on top of the
+//! guest heap allocator.
+
+use alloc::alloc::{alloc, alloc_zeroed, dealloc, realloc as rust_realloc, Layout};
+use core::{ffi::c_void, ptr::null_mut};
+
+use risc0_zkvm_platform::{fileno, syscall::sys_read};
+
+use super::env::{self, FdWriter, Write as _};
+
+/// Prepare the guest environment. Called by `crt0.S` before anything else.
+#[no_mangle]
+pub extern "C" fn risc0_init() {
+    env::init();
+}
+
+/// Read up to `len` bytes from the guest input into `buf`, returning the
+/// number of bytes read, which is less than `len` only at the end of the input.
+///
+/// # Safety
+///
+/// `buf` must be valid for writes of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn risc0_read(buf: *mut u8, len: usize) -> usize {
+    let mut total = 0;
+    while total < len {
+        let nread = sys_read(fileno::STDIN, buf.add(total), len - total);
+        if nread == 0 {
+            break;
+        }
+        total += nread;
+    }
+    total
+}
+
+/// Write `len` bytes from `buf` to the file descriptor `fd`. Writes to the
+/// journal descriptor are committed, as with [risc0_commit].
+///
+/// # Safety
+///
+/// `buf` must be valid for reads of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn risc0_write(fd: u32, buf: *const u8, len: usize) {
+    let bytes = bytes(buf, len);
+    match fd {
+        fileno::JOURNAL => env::commit_slice(bytes),
+        _ => FdWriter::new(fd, |_| {}).write_slice(bytes),
+    }
+}
+
+/// Commit `len` bytes from `buf` to the journal.
+///
+/// # Safety
+///
+/// `buf` must be valid for reads of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn risc0_commit(buf: *const u8, len: usize) {
+    env::commit_slice(bytes(buf, len));
+}
+
+/// Return the number of cycles the guest has run so far.
+#[no_mangle]
+pub extern "C" fn risc0_cycle_count() -> u64 {
+    env::cycle_count()
+}
+
+/// Halt the guest with the given exit code, committing the journal.
+#[no_mangle]
+pub extern "C" fn risc0_exit(code: i32) -> ! {
+    env::exit(code as u8)
+}
+
+/// Abort the guest with a message, as a Rust guest does when it panics.
+///
+/// # Safety
+///
+/// `msg` must point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn risc0_abort(msg: *const u8) -> ! {
+    let msg = core::ffi::CStr::from_ptr(msg as _);
+    super::abort(msg.to_str().unwrap_or("risc0_abort"))
+}
+
+unsafe fn bytes<'a>(buf: *const u8, len: usize) -> &'a [u8] {
+    match len {
+        0 => &[],
+        _ => core::slice::from_raw_parts(buf, len),
+    }
+}
+
+// Each allocation is preceded by a header holding its size, so that `free`
+// and `realloc` can reconstruct its layout. The header is as large as the
+// alignment guaranteed by malloc, which is that of long double.
+const HEADER: usize = 16;
+
+fn layout(size: usize) -> Option<Layout> {
+    Layout::from_size_align(size.checked_add(HEADER)?, HEADER).ok()
+}
+
+unsafe fn finish(base: *mut u8, size: usize) -> *mut c_void {
+    if base.is_null() {
+        return null_mut();
+    }
+    (base as *mut usize).write(size);
+    base.add(HEADER) as *mut c_void
+}
+
+unsafe fn base_and_size(ptr: *mut c_void) -> (*mut u8, usize) {
+    let base = (ptr as *mut u8).sub(HEADER);
+    (base, (base as *const usize).read())
+}
+
+/// # Safety
+///
+/// Follows the contract of the C standard library function.
+#[no_mangle]
+pub unsafe extern "C" fn malloc(size: usize) -> *mut c_void {
+    match layout(size) {
+        Some(layout) => finish(alloc(layout), size),
+        None => null_mut(),
+    }
+}
+
+/// # Safety
+///
+/// Follows the contract of the C standard library function.
+#[no_mangle]
+pub unsafe extern "C" fn calloc(count: usize, size: usize) -> *mut c_void {
+    match count.checked_mul(size).and_then(layout) {
+        Some(layout) => finish(alloc_zeroed(layout), count * size),
+        None => null_mut(),
+    }
+}
+
+/// # Safety
+///
+/// Follows the contract of the C standard library function.
+#[no_mangle]
+pub unsafe extern "C" fn realloc(ptr: *mut c_void, size: usize) -> *mut c_void {
+    if ptr.is_null() {
+        return malloc(size);
+    }
+    let (base, old_size) = base_and_size(ptr);
+    match size.checked_add(HEADER) {
+        Some(new_size) => finish(
+            rust_realloc(base, layout(old_size).unwrap(), new_size),
+            size,
+        ),
+        None => null_mut(),
+    }
+}
+
+/// # Safety
+///
+/// Follows the contract of the C standard library function.
+#[no_mangle]
+pub unsafe extern "C" fn free(ptr: *mut c_void) {
+    if !ptr.is_null() {
+        let (base, size) = base_and_size(ptr);
+        dealloc(base, layout(size).unwrap());
+    }
+}
+
+// The C++ ABI on riscv32, where size_t is unsigned int. Exceptions are not
+// supported, so allocation failure aborts instead of throwing.
+#[allow(non_snake_case, non_upper_case_globals)]
+mod cxx {
+    use core::ffi::c_void;
+
+    use super::{free, malloc};
+
+    unsafe fn new(size: usize) -> *mut c_void {
+        let ptr = malloc(size);
+        if ptr.is_null() {
+            super::super::abort("operator new: out of memory");
+        }
+        ptr
+    }
+
+    // operator new(size_t)
+    #[no_mangle]
+    unsafe extern "C" fn _Znwj(size: usize) -> *mut c_void {
+        new(size)
+    }
+
+    // operator new[](size_t)
+    #[no_mangle]
+    unsafe extern "C" fn _Znaj(size: usize) -> *mut c_void {
+        new(size)
+    }
+
+    // operator delete(void*)
+    #[no_mangle]
+    unsafe extern "C" fn _ZdlPv(ptr: *mut c_void) {
+        free(ptr)
+    }
+
+    // operator delete[](void*)
+    #[no_mangle]
+    unsafe extern "C" fn _ZdaPv(ptr: *mut c_void) {
+        free(ptr)
+    }
+
+    // operator delete(void*, size_t)
+    #[no_mangle]
+    unsafe extern "C" fn _ZdlPvj(ptr: *mut c_void, _size: usize) {
+        free(ptr)
+    }
+
+    // operator delete[](void*, size_t)
+    #[no_mangle]
+    unsafe extern "C" fn _ZdaPvj(ptr: *mut c_void, _size: usize) {
+        free(ptr)
+    }
+
+    #[no_mangle]
+    extern "C" fn __cxa_pure_virtual() -> ! {
+        super::super::abort("pure virtual function called")
+    }
+
+    // The guest halts without returning from main, so destructors of static
+    // objects never run and need not be registered.
+    #[no_mangle]
+    extern "C" fn __cxa_atexit(
+        _dtor: extern "C" fn(*mut c_void),
+        _arg: *mut c_void,
+        _dso: *mut c_void,
+    ) -> i32 {
+        0
+    }
+
+    #[no_mangle]
+    static mut __dso_handle: *mut c_void = core::ptr::null_mut();
+}
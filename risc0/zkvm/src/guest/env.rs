@@ -28,7 +28,9 @@
 //!
 //! The zkVM supports verification of RISC Zero [receipts] in a guest program,
 //! enabling [proof composition]. This can be achieved using the [verify] and
-//! [verify_integrity] functions.
+//! [verify_integrity] functions. Another guest program can also be run
+//! directly as a subroutine using [call], which verifies its result in the
+//! same way.
 //!
 //! ## Input and Output
 //!
@@ -47,12 +49,20 @@
 //!
 //! Convenience functions to read and write to default file descriptors are
 //! provided. See [read], [write][write()], [commit] (and their `_slice`
-//! variants) for more information.
+//! variants) for more information. Inputs too large to hold in memory at once
+//! can be read element by element with [read_seq], and large outputs can be
+//! streamed into the journal with [commit_writer].
+//!
+//! Public output can also be split across named channels with
+//! [commit_channel], each committed to separately in the receipt claim, so a
+//! verifier can check the channels it needs without parsing the others.
 //!
 //! In order to access default file descriptors directly, see [stdin], [stdout],
 //! [stderr] and [journal]. These file descriptors are either [FdReader] or
 //! [FdWriter] instances, which can be used to read from or write to the host.
-//! To read from or write into them, use the [Read] and [Write] traits.
+//! To read from or write into them, use the [Read] and [Write] traits. With
+//! the `std` feature, they also implement the [std::io] traits, including
+//! [std::io::Seek] for file descriptors the host has made seekable.
 //!
 //! WARNING: Specifying a file descriptor with the same value of a default file
 //! descriptor is not recommended and may lead to unexpected behavior. A list of
@@ -63,19 +73,32 @@
 //! The zkVM provides utility functions to log messages to the debug console and
 //! to measure the number of processor cycles that have occurred since the guest
 //! began. These can be achieved using the [log] and [cycle_count] functions.
+//! Deterministic pseudo-random bytes for randomized algorithms are available
+//! from [random_bytes].
+//! Usage of the guest heap can be checked with [heap_stats], and overall
+//! memory usage, including the stack, with [memory_stats]. If the host
+//! provides it, its wall-clock time can be read and committed with
+//! [host_timestamp].
 //!
 //! [receipts]: crate::Receipt
 //! [proof composition]:https://www.risczero.com/blog/proof-composition
 //! [guest-optimization]: https://dev.risczero.com/api/zkvm/optimization#when-reading-data-as-raw-bytes-use-envread_slice
 
-use core::{cell::OnceCell, fmt, mem::MaybeUninit};
+use alloc::{collections::BTreeMap, string::String, vec, vec::Vec};
+use core::{
+    cell::{Cell, OnceCell},
+    fmt,
+    marker::PhantomData,
+    mem::MaybeUninit,
+};
 
 use bytemuck::Pod;
 use risc0_zkvm_platform::{
-    align_up, fileno,
+    align_up, fileno, heap,
     syscall::{
-        self, sys_alloc_words, sys_cycle_count, sys_halt, sys_input, sys_log, sys_pause, sys_read,
-        sys_read_words, sys_verify, sys_verify_integrity, sys_write, syscall_2, SyscallName,
+        self, nr::SYS_CALL, sys_alloc_words, sys_channel_write, sys_cycle_count, sys_halt,
+        sys_input, sys_log, sys_pause, sys_read, sys_read_words, sys_time, sys_verify,
+        sys_verify_channels, sys_verify_integrity, sys_write, syscall_2, SyscallName,
     },
     WORD_SIZE,
 };
@@ -85,18 +108,27 @@ use crate::{
     serde::{Deserializer, Serializer, WordRead, WordWrite},
     sha::{
         rust_crypto::{Digest as _, Sha256},
-        Digest, Digestible, DIGEST_WORDS,
+        Digest, Digestible, DIGEST_BYTES, DIGEST_WORDS,
     },
-    Assumptions, ExitCode, InvalidExitCodeError, MaybePruned, Output, PrunedValueError,
-    ReceiptClaim,
+    Assumptions, Channels, ExitCode, GuestPanic, HostTimestamp, InvalidExitCodeError, MaybePruned,
+    Output, PrunedValueError, ReceiptClaim,
 };
 
+pub use risc0_zkvm_platform::heap::HeapStats;
+
 static mut HASHER: OnceCell<Sha256> = OnceCell::new();
 
+/// Hashers for the named output channels written to by the guest.
+static mut CHANNEL_HASHERS: BTreeMap<String, Sha256> = BTreeMap::new();
+
 /// Digest of the running list of [Assumptions], generated by the [verify] and
 /// [verify_integrity] calls made by the guest.
 static mut ASSUMPTIONS_DIGEST: MaybePruned<Assumptions> = MaybePruned::Pruned(Digest::ZERO);
 
+/// Claim digests of the assumptions in [ASSUMPTIONS_DIGEST], in the order they
+/// were added.
+static mut ASSUMPTION_CLAIMS: Vec<Digest> = Vec::new();
+
 /// A random 16 byte value initialized to random data, provided by the host, on
 /// guest start and upon resuming from a pause. Setting this value ensures that
 /// the total memory image has at least 128 bits of entropy, preventing
@@ -117,9 +149,19 @@ pub(crate) fn finalize(halt: bool, user_exit: u8) {
     unsafe {
         let hasher = HASHER.take();
         let journal_digest: Digest = hasher.unwrap().finalize().as_slice().try_into().unwrap();
+        let channels = Channels(
+            core::mem::take(&mut *core::ptr::addr_of_mut!(CHANNEL_HASHERS))
+                .into_iter()
+                .map(|(name, hasher)| {
+                    let digest: Digest = hasher.finalize().as_slice().try_into().unwrap();
+                    (name, MaybePruned::Pruned(digest))
+                })
+                .collect(),
+        );
         let output = Output {
             journal: MaybePruned::Pruned(journal_digest),
             assumptions: MaybePruned::Pruned(ASSUMPTIONS_DIGEST.digest()),
+            channels: MaybePruned::Pruned(channels.digest()),
         };
         let output_words: [u32; 8] = output.digest().into();
 
@@ -194,7 +236,59 @@ pub fn verify(image_id: impl Into<Digest>, journal: &[impl Pod]) -> Result<(), V
             from_host_buf.as_mut_ptr(),
         )
     };
+    add_verified_assumption(image_id, journal_digest, Digest::ZERO, from_host_buf)
+}
 
+/// Verify there exists a receipt for an execution with `image_id`, `journal`
+/// and the named output `channels`.
+///
+/// This is the variant of [verify] for receipts of guests that commit to
+/// channels with [commit_channel]. The data of channels the guest does not need
+/// can be pruned, as the claim only commits to the digest of each channel.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use std::collections::BTreeMap;
+///
+/// use risc0_zkvm::{guest::env, Channels};
+///
+/// # let EVENTS_ID = Digest::ZERO;
+/// let channels = Channels::from(BTreeMap::from([(
+///     "events".to_string(),
+///     b"transfer".to_vec(),
+/// )]));
+/// env::verify_with_channels(EVENTS_ID, b"".as_slice(), &channels).unwrap();
+/// ```
+pub fn verify_with_channels(
+    image_id: impl Into<Digest>,
+    journal: &[impl Pod],
+    channels: &Channels,
+) -> Result<(), VerifyError> {
+    let image_id: Digest = image_id.into();
+    let journal_digest: Digest = bytemuck::cast_slice::<_, u8>(journal).digest();
+    let channels_digest = channels.digest();
+    let mut from_host_buf = MaybeUninit::<[u32; DIGEST_WORDS + 1]>::uninit();
+
+    unsafe {
+        sys_verify_channels(
+            image_id.as_ref(),
+            journal_digest.as_ref(),
+            channels_digest.as_ref(),
+            from_host_buf.as_mut_ptr(),
+        )
+    };
+    add_verified_assumption(image_id, journal_digest, channels_digest, from_host_buf)
+}
+
+/// Add the assumption for a receipt found by the host in response to
+/// [sys_verify] or [sys_verify_channels].
+fn add_verified_assumption(
+    image_id: Digest,
+    journal_digest: Digest,
+    channels_digest: Digest,
+    from_host_buf: MaybeUninit<[u32; DIGEST_WORDS + 1]>,
+) -> Result<(), VerifyError> {
     // Split the host buffer into the Digest and system exit code portions. This is statically
     // known to succeed, but the array APIs that would allow compile-time checked splitting are
     // unstable.
@@ -225,14 +319,51 @@ pub fn verify(image_id: impl Into<Digest>, journal: &[impl Pod]) -> Result<(), V
         output: Some(Output {
             journal: MaybePruned::Pruned(journal_digest),
             assumptions: MaybePruned::Pruned(Digest::ZERO),
+            channels: MaybePruned::Pruned(channels_digest),
         })
         .into(),
     };
-    unsafe { ASSUMPTIONS_DIGEST.add(assumption_claim.into()) };
+    add_assumption(assumption_claim.into());
 
     Ok(())
 }
 
+fn add_assumption(claim: MaybePruned<ReceiptClaim>) {
+    unsafe {
+        ASSUMPTION_CLAIMS.push(claim.digest());
+        ASSUMPTIONS_DIGEST.add(claim);
+    }
+}
+
+/// Return the claim digests of the assumptions added so far, in the order they
+/// were added by [verify], [verify_integrity] and [call].
+///
+/// A guest that aggregates many verifications can use this to audit what it
+/// has assumed, or to commit a summary of it. Assumptions are identified by
+/// claim digest alone; every assumption is resolved against the same set of
+/// recursion programs, so there is no control root per assumption.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use risc0_zkvm::guest::env;
+///
+/// env::verify(HELLO_WORLD_ID, b"hello world".as_slice()).unwrap();
+/// env::commit(&env::assumptions().to_vec());
+/// ```
+pub fn assumptions() -> &'static [Digest] {
+    unsafe { ASSUMPTION_CLAIMS.as_slice() }
+}
+
+/// Return the digest of the assumptions list, as it will appear in the
+/// [Output] of the receipt claim if the guest exits now.
+///
+/// The list holds the assumptions of [assumptions] with the most recent
+/// first.
+pub fn assumptions_digest() -> Digest {
+    unsafe { ASSUMPTIONS_DIGEST.digest() }
+}
+
 /// Error encountered during a call to [verify].
 ///
 /// Note that an error is only returned for "provable" errors. In particular, if
@@ -265,6 +396,35 @@ impl fmt::Display for VerifyError {
 #[cfg(feature = "std")]
 impl std::error::Error for VerifyError {}
 
+/// Run the guest program with `image_id` on the given `input` and return its
+/// journal.
+///
+/// The host executes the called program with `input` as its stdin, and the
+/// returned journal is checked with [verify]. The receipt for this execution
+/// will therefore be conditioned on a receipt for the called program, which the
+/// host proves and resolves along with the caller. The called program must be
+/// registered with the host using `ExecutorEnvBuilder::add_program`.
+///
+/// The called program runs with the limits of the caller, and its cycles count
+/// towards the session limit of the caller. Calls can be nested up to a depth of
+/// eight, after which the host fails the execution.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use risc0_zkvm::guest::env;
+///
+/// # let HELLO_WORLD_ID = Digest::ZERO;
+/// let journal = env::call(HELLO_WORLD_ID, b"world").unwrap();
+/// ```
+pub fn call(image_id: impl Into<Digest>, input: &[u8]) -> Result<&'static [u8], VerifyError> {
+    let image_id: Digest = image_id.into();
+    let to_host = [image_id.as_bytes(), input].concat();
+    let journal: &'static [u8] = send_recv_slice(SYS_CALL, &to_host);
+    verify(image_id, journal)?;
+    Ok(journal)
+}
+
 /// Verify that there exists a valid receipt with the specified
 /// [crate::ReceiptClaim].
 ///
@@ -296,12 +456,43 @@ pub fn verify_integrity(claim: &ReceiptClaim) -> Result<(), VerifyIntegrityError
 
     unsafe {
         sys_verify_integrity(claim_digest.as_ref());
-        ASSUMPTIONS_DIGEST.add(MaybePruned::Pruned(claim_digest));
     }
+    add_assumption(MaybePruned::Pruned(claim_digest));
 
     Ok(())
 }
 
+/// Verify the receipt claims sent by the host to aggregate them, and commit the
+/// list of their digests.
+///
+/// This is the body of an aggregation guest, used by the host's
+/// `Prover::aggregate` to combine the receipts of unrelated sessions, with any
+/// image IDs, into one. It reads a list of [ReceiptClaim]s from stdin, checks
+/// each with [verify_integrity], and commits the [Digest] of each claim to the
+/// journal, in order, as a `Vec<Digest>`. The guest of the
+/// `risc0-zkvm-aggregator` crate is just this call, so most hosts do not need
+/// to build their own.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use risc0_zkvm::guest::env;
+///
+/// fn main() {
+///     env::aggregate().unwrap();
+/// }
+/// ```
+pub fn aggregate() -> Result<Vec<Digest>, VerifyIntegrityError> {
+    let claims: Vec<ReceiptClaim> = read();
+    let mut digests = Vec::with_capacity(claims.len());
+    for claim in claims.iter() {
+        verify_integrity(claim)?;
+        digests.push(claim.digest());
+    }
+    commit(&digests);
+    Ok(digests)
+}
+
 /// Error encountered during a call to [verify_integrity].
 ///
 /// Note that an error is only returned for "provable" errors. In particular, if the host fails to
@@ -415,6 +606,35 @@ pub fn read_slice<T: Pod>(slice: &mut [T]) {
     stdin().read_slice(slice)
 }
 
+/// Read a sequence from the STDIN of the zkVM, deserializing one element at a
+/// time.
+///
+/// The input is expected in the same format [read] uses for a `Vec<T>`, so
+/// the host can write it with `ExecutorEnvBuilder::write`. Unlike [read], the
+/// elements are not collected into memory up front, so inputs larger than the
+/// guest heap can be processed incrementally.
+///
+/// # Example
+///
+/// ```no_run
+/// use risc0_zkvm::guest::env;
+///
+/// let sum: u64 = env::read_seq::<u32>().map(u64::from).sum();
+/// ```
+pub fn read_seq<T: DeserializeOwned>() -> SeqReader<FdReader, T> {
+    stdin().read_seq()
+}
+
+/// Return a reader for the raw bytes on the STDIN of the zkVM.
+///
+/// This suits streaming parsers that consume a [std::io::Read], such as
+/// `serde_json::from_reader`, as no more than the parser asks for is read at a
+/// time.
+#[cfg(feature = "std")]
+pub fn reader() -> impl std::io::Read {
+    stdin()
+}
+
 /// Serialize the given data and write it to the STDOUT of the zkVM.
 ///
 /// This is available to the host as the private output on the prover.
@@ -535,6 +755,70 @@ pub fn commit_slice<T: Pod>(slice: &[T]) {
     journal().write_slice(slice);
 }
 
+/// Commit the root of a [DisclosureTree](crate::DisclosureTree) over `data` to
+/// the journal, so that chosen chunks of `data` can be revealed later while the
+/// rest stays private.
+///
+/// The journal holds only the root. The host rebuilds the tree from `data`
+/// and the same `seed` to make a [Disclosure](crate::Disclosure), so the seed
+/// should be read from the host as private input.
+///
+/// # Example
+///
+/// ```no_run
+/// use risc0_zkvm::{guest::env, sha::Digest};
+///
+/// let seed: Digest = env::read();
+/// let output = b"public header, private body";
+/// env::commit_disclosable(output, 8, seed);
+/// ```
+pub fn commit_disclosable(data: &[u8], chunk_size: u32, seed: Digest) {
+    commit_slice(
+        crate::DisclosureTree::new(data, chunk_size, seed)
+            .root()
+            .as_bytes(),
+    );
+}
+
+/// Serialize the given data and commit it to the named output channel.
+///
+/// Like the journal, channels are included in the receipt and are available
+/// to the verifier, via `Receipt::channels`. Each channel has its own digest in
+/// the receipt claim, so a verifier can authenticate the channels it needs
+/// while the data of the others is pruned. Data committed to the same channel
+/// is appended in order.
+///
+/// Another guest verifies a receipt with channels using
+/// [verify_with_channels]. A guest that commits to channels and also has
+/// assumptions, from [verify], [verify_integrity] or [call], gets a receipt
+/// that can only be verified as a composite receipt: the resolve program cannot
+/// remove assumptions from a receipt with channels, so compressing it to a
+/// succinct or Groth16 receipt fails with a `ResolveChannelsError`.
+///
+/// # Example
+///
+/// ```no_run
+/// use risc0_zkvm::guest::env;
+///
+/// env::commit_channel("public_result", &42u32);
+/// env::commit_channel("events", &"transfer");
+/// ```
+pub fn commit_channel<T: Serialize>(name: &str, data: &T) {
+    channel(name).write(data)
+}
+
+/// Commit the given slice to the named output channel.
+///
+/// This is the [plain old data][bytemuck::Pod] variant of [commit_channel].
+pub fn commit_channel_slice<T: Pod>(name: &str, slice: &[T]) {
+    channel(name).write_slice(slice);
+}
+
+/// Return a writer for the named output channel.
+pub fn channel(name: &str) -> ChannelWriter<'_> {
+    ChannelWriter { name }
+}
+
 /// Return the number of processor cycles that have occurred since the guest
 /// began.
 ///
@@ -543,7 +827,173 @@ pub fn cycle_count() -> u64 {
     sys_cycle_count()
 }
 
+/// Return statistics about the guest heap.
+///
+/// The default bump allocator never reuses freed memory; a long-running guest
+/// that sees `free` run low should enable the `heap-linked-list` feature.
+pub fn heap_stats() -> HeapStats {
+    heap::stats()
+}
+
+/// Statistics about guest memory usage, as returned by [memory_stats].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryStats {
+    /// Bytes of the heap in use.
+    pub heap_used: usize,
+    /// The most bytes the stack has used so far, rounded up to a whole page
+    /// for frames that are no longer live.
+    pub stack_high_water: usize,
+    /// The number of distinct pages of guest memory touched so far, across
+    /// all segments. Each page is [PAGE_SIZE][crate::PAGE_SIZE] bytes.
+    pub pages_touched: usize,
+}
+
+/// Return statistics about guest memory usage.
+///
+/// This can be used to bound the working set of a guest, or to assert a
+/// memory ceiling in tests:
+///
+/// ```no_run
+/// use risc0_zkvm::guest::env;
+///
+/// let stats = env::memory_stats();
+/// assert!(stats.stack_high_water < 64 * 1024);
+/// ```
+///
+/// WARNING: The stack and page figures are provided by the host and are not
+/// checked by the zkVM circuit.
+pub fn memory_stats() -> MemoryStats {
+    let syscall::Return(stack_high_water, pages_touched) = syscall::sys_memory_stats();
+    MemoryStats {
+        heap_used: heap::stats().used,
+        stack_high_water: stack_high_water as usize,
+        pages_touched: pages_touched as usize,
+    }
+}
+
+/// Read the host's wall-clock time and commit it to the journal.
+///
+/// Returns `None`, and commits `None`, if the host has not enabled it with
+/// `ExecutorEnvBuilder::wall_clock`. The journal entry is an
+/// `Option<HostTimestamp>`, so that verifiers see that the time is asserted
+/// by the host rather than checked by the proof. Use [cycle_count] to measure
+/// the progress of the guest itself, for example to implement a timeout.
+pub fn host_timestamp() -> Option<HostTimestamp> {
+    let timestamp = match sys_time() {
+        u64::MAX => None,
+        unix_millis => Some(HostTimestamp { unix_millis }),
+    };
+    commit(&timestamp);
+    timestamp
+}
+
+// Whether a panic is committed to the journal; see commit_panics.
+static mut COMMIT_PANICS: bool = false;
+
+/// Record panics in the receipt instead of failing execution.
+///
+/// By default, a guest panic ends execution with an error and no receipt can
+/// be produced. After this is called, a panic commits a [GuestPanic] with its
+/// message and location to the journal, after anything already committed, and
+/// halts with [GuestPanic::EXIT_CODE]. A service can then triage the failure
+/// from the receipt alone.
+pub fn commit_panics() {
+    unsafe { COMMIT_PANICS = true };
+
+    // With std, panics go through the std panic handler, which calls the hook.
+    #[cfg(feature = "std")]
+    std::panic::set_hook(alloc::boxed::Box::new(|info| {
+        commit_panic(&alloc::format!("{info}"), info.location())
+    }));
+}
+
+/// Commit the panic and halt, if [commit_panics] was called.
+#[cfg_attr(not(any(target_os = "zkvm", feature = "std")), allow(dead_code))]
+pub(crate) fn commit_panic(formatted: &str, location: Option<&core::panic::Location>) {
+    if unsafe { COMMIT_PANICS } {
+        let location = location.map(|loc| (loc.file(), loc.line(), loc.column()));
+        commit(&GuestPanic::new(formatted, location));
+        exit(GuestPanic::EXIT_CODE);
+    }
+}
+
+/// Domain separation tag for the seed of [random_bytes].
+const RANDOM_SEED_TAG: &[u8] = b"risc0.guest.random";
+
+// The state of the generator behind random_bytes: its seed, and the index of
+// the next block to produce.
+struct RandomState(Cell<Option<(Digest, u32)>>);
+
+// SAFETY: The guest is single-core, and the state is copied in and out of the
+// cell rather than borrowed, so it is never accessed concurrently.
+unsafe impl Sync for RandomState {}
+
+static RANDOM_STATE: RandomState = RandomState(Cell::new(None));
+
+fn random_state() -> (Digest, u32) {
+    if let Some(state) = RANDOM_STATE.0.get() {
+        return state;
+    }
+    // SAFETY: Single threaded and the HASHER is initialized before main.
+    let journal = unsafe { HASHER.get().unwrap_unchecked() }
+        .clone()
+        .finalize();
+    let seed = Sha256::new()
+        .chain_update(RANDOM_SEED_TAG)
+        .chain_update(input_digest().as_bytes())
+        .chain_update(journal)
+        .finalize();
+    let state = (seed.as_slice().try_into().unwrap(), 0);
+    RANDOM_STATE.0.set(Some(state));
+    state
+}
+
+/// Return the seed of the generator used by [random_bytes].
+///
+/// The seed is the SHA-256 hash of `risc0.guest.random`, the [input_digest],
+/// and the digest of the journal as written before the first use of the
+/// generator. Committing the seed lets a verifier replay the random choices
+/// made by the guest.
+pub fn random_seed() -> Digest {
+    random_state().0
+}
+
+/// Fill `buf` with deterministic pseudo-random bytes.
+///
+/// Each successive 32 bytes are the SHA-256 hash of the [random_seed] and a
+/// little-endian `u32` block counter, which runs on across calls; the unused
+/// remainder of a block is discarded. Executions with the same inputs produce
+/// the same bytes, as proving requires.
+///
+/// The prover chooses the inputs, so these bytes are suitable for randomized
+/// algorithms but are not secret or unpredictable to the prover.
+pub fn fill_random(buf: &mut [u8]) {
+    let (seed, mut counter) = random_state();
+    for chunk in buf.chunks_mut(DIGEST_BYTES) {
+        let block = Sha256::new()
+            .chain_update(seed.as_bytes())
+            .chain_update(counter.to_le_bytes())
+            .finalize();
+        counter += 1;
+        chunk.copy_from_slice(&block[..chunk.len()]);
+    }
+    RANDOM_STATE.0.set(Some((seed, counter)));
+}
+
+/// Return `n` deterministic pseudo-random bytes.
+///
+/// See [fill_random] for how they are derived.
+pub fn random_bytes(n: usize) -> Vec<u8> {
+    let mut buf = vec![0u8; n];
+    fill_random(&mut buf);
+    buf
+}
+
 /// Print a message to the debug console.
+///
+/// The message is written to the host's stdout without a level. Prefer the
+/// macros in [crate::guest::log], which route leveled events into the host's
+/// `tracing` subscriber.
 pub fn log(msg: &str) {
     let msg = msg.as_bytes();
     unsafe {
@@ -561,6 +1011,37 @@ pub fn stderr() -> FdWriter<impl for<'a> Fn(&'a [u8])> {
     FdWriter::new(fileno::STDERR, |_| {})
 }
 
+/// Return a writer that streams bytes into the journal.
+///
+/// [commit] serializes a whole value at a time; this writer instead accepts
+/// output piece by piece, hashing it into the journal commitment and sending
+/// it to the host in fixed-size chunks, so large outputs can be produced in
+/// bounded memory. Bytes written with [std::io::Write] or [core::fmt::Write]
+/// are appended to the journal as they are, without serialization framing.
+///
+/// Buffered data is committed when the writer is dropped. The writer should
+/// be dropped before anything else is committed to the journal, so that the
+/// journal holds the outputs in the order they were written.
+///
+/// # Example
+///
+/// ```no_run
+/// use core::fmt::Write as _;
+///
+/// use risc0_zkvm::guest::env;
+///
+/// let mut writer = env::commit_writer();
+/// for i in 0..1000 {
+///     writeln!(writer, "row {i}").unwrap();
+/// }
+/// ```
+pub fn commit_writer() -> CommitWriter {
+    CommitWriter {
+        buf: [0u8; COMMIT_WRITER_BUF_BYTES],
+        len: 0,
+    }
+}
+
 /// Return a writer for the JOURNAL.
 pub fn journal() -> FdWriter<impl for<'a> Fn(&'a [u8])> {
     FdWriter::new(fileno::JOURNAL, |bytes| {
@@ -580,6 +1061,21 @@ pub trait Read {
 
     /// Read raw data from the host.
     fn read_slice<T: Pod>(&mut self, buf: &mut [T]);
+
+    /// Read a sequence from the host, deserializing one element at a time.
+    ///
+    /// See [read_seq] for details.
+    fn read_seq<T: DeserializeOwned>(mut self) -> SeqReader<Self, T>
+    where
+        Self: Sized,
+    {
+        let len: u32 = self.read();
+        SeqReader {
+            reader: self,
+            remaining: len as usize,
+            phantom: PhantomData,
+        }
+    }
 }
 
 impl<R: Read + ?Sized> Read for &mut R {
@@ -592,6 +1088,31 @@ impl<R: Read + ?Sized> Read for &mut R {
     }
 }
 
+/// An iterator over a sequence read from the host, returned by [read_seq].
+pub struct SeqReader<R, T> {
+    reader: R,
+    remaining: usize,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<R: Read, T: DeserializeOwned> Iterator for SeqReader<R, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(self.reader.read())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<R: Read, T: DeserializeOwned> ExactSizeIterator for SeqReader<R, T> {}
+
 /// Provides a FdReader which can read from any file descriptor
 pub struct FdReader {
     fd: u32,
@@ -677,6 +1198,13 @@ impl std::io::Read for FdReader {
     }
 }
 
+#[cfg(feature = "std")]
+impl std::io::Seek for FdReader {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        Ok(seek_fd(self.fd, pos))
+    }
+}
+
 /// Serializes and writes objects.
 pub trait Write {
     /// Write a serialized object.
@@ -696,6 +1224,146 @@ impl<W: Write + ?Sized> Write for &mut W {
     }
 }
 
+const COMMIT_WRITER_BUF_BYTES: usize = 1024;
+
+/// Streams bytes into the journal. See [commit_writer].
+pub struct CommitWriter {
+    buf: [u8; COMMIT_WRITER_BUF_BYTES],
+    len: usize,
+}
+
+impl CommitWriter {
+    fn write_bytes(&mut self, mut bytes: &[u8]) {
+        while !bytes.is_empty() {
+            // Write large inputs through once the buffer is empty.
+            if self.len == 0 && bytes.len() >= COMMIT_WRITER_BUF_BYTES {
+                journal().write_slice(bytes);
+                return;
+            }
+            let n = usize::min(COMMIT_WRITER_BUF_BYTES - self.len, bytes.len());
+            self.buf[self.len..self.len + n].copy_from_slice(&bytes[..n]);
+            self.len += n;
+            bytes = &bytes[n..];
+            if self.len == COMMIT_WRITER_BUF_BYTES {
+                self.flush_buf();
+            }
+        }
+    }
+
+    fn flush_buf(&mut self) {
+        if self.len > 0 {
+            journal().write_slice(&self.buf[..self.len]);
+            self.len = 0;
+        }
+    }
+}
+
+impl Drop for CommitWriter {
+    fn drop(&mut self) {
+        self.flush_buf();
+    }
+}
+
+impl Write for CommitWriter {
+    fn write<T: Serialize>(&mut self, val: T) {
+        val.serialize(&mut Serializer::new(self)).unwrap();
+    }
+
+    fn write_slice<T: Pod>(&mut self, buf: &[T]) {
+        self.write_bytes(bytemuck::cast_slice(buf));
+    }
+}
+
+impl WordWrite for CommitWriter {
+    fn write_words(&mut self, words: &[u32]) -> crate::serde::Result<()> {
+        self.write_bytes(bytemuck::cast_slice(words));
+        Ok(())
+    }
+
+    fn write_padded_bytes(&mut self, bytes: &[u8]) -> crate::serde::Result<()> {
+        self.write_bytes(bytes);
+        let unaligned = bytes.len() % WORD_SIZE;
+        if unaligned != 0 {
+            self.write_bytes(&[0u8; WORD_SIZE][..WORD_SIZE - unaligned]);
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Write for CommitWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.write_bytes(s.as_bytes());
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Write for CommitWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.write_bytes(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.flush_buf();
+        Ok(())
+    }
+}
+
+/// Writes to a named output channel, committing to the data written.
+///
+/// See [commit_channel] for how channels appear in the receipt.
+pub struct ChannelWriter<'a> {
+    name: &'a str,
+}
+
+impl<'a> ChannelWriter<'a> {
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        unsafe {
+            sys_channel_write(
+                self.name.as_ptr(),
+                self.name.len(),
+                bytes.as_ptr(),
+                bytes.len(),
+            );
+            match CHANNEL_HASHERS.get_mut(self.name) {
+                Some(hasher) => hasher.update(bytes),
+                None => {
+                    let mut hasher = Sha256::new();
+                    hasher.update(bytes);
+                    CHANNEL_HASHERS.insert(self.name.into(), hasher);
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Write for ChannelWriter<'a> {
+    fn write<T: Serialize>(&mut self, val: T) {
+        val.serialize(&mut Serializer::new(self)).unwrap();
+    }
+
+    fn write_slice<T: Pod>(&mut self, buf: &[T]) {
+        self.write_bytes(bytemuck::cast_slice(buf));
+    }
+}
+
+impl<'a> WordWrite for ChannelWriter<'a> {
+    fn write_words(&mut self, words: &[u32]) -> crate::serde::Result<()> {
+        self.write_bytes(bytemuck::cast_slice(words));
+        Ok(())
+    }
+
+    fn write_padded_bytes(&mut self, bytes: &[u8]) -> crate::serde::Result<()> {
+        self.write_bytes(bytes);
+        let unaligned = bytes.len() % WORD_SIZE;
+        if unaligned != 0 {
+            self.write_bytes(&[0u8; WORD_SIZE][..WORD_SIZE - unaligned]);
+        }
+        Ok(())
+    }
+}
+
 /// Provides a FdWriter which can write to any file descriptor.
 pub struct FdWriter<F: Fn(&[u8])> {
     fd: u32,
@@ -753,6 +1421,23 @@ impl<F: Fn(&[u8])> std::io::Write for FdWriter<F> {
     }
 }
 
+#[cfg(feature = "std")]
+impl<F: Fn(&[u8])> std::io::Seek for FdWriter<F> {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        Ok(seek_fd(self.fd, pos))
+    }
+}
+
+#[cfg(feature = "std")]
+fn seek_fd(fd: u32, pos: std::io::SeekFrom) -> u64 {
+    let (offset, whence) = match pos {
+        std::io::SeekFrom::Start(offset) => (offset as i64, syscall::seek::SET),
+        std::io::SeekFrom::Current(offset) => (offset, syscall::seek::CUR),
+        std::io::SeekFrom::End(offset) => (offset, syscall::seek::END),
+    };
+    syscall::sys_seek(fd, offset, whence)
+}
+
 /// Read the input digest from the input commitment.
 pub fn input_digest() -> Digest {
     Digest::new([
@@ -0,0 +1,312 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Functions for computing Keccak and SHA-3 hashes.
+//!
+//! The API follows the [tiny-keccak] crate, so guests can switch to it by
+//! changing their imports:
+//!
+//! ```
+//! use risc0_zkvm::guest::keccak::{Hasher, Keccak};
+//!
+//! let mut hasher = Keccak::v256();
+//! let mut output = [0u8; 32];
+//! hasher.update(b"hello ");
+//! hasher.update(b"world");
+//! hasher.finalize(&mut output);
+//! assert_eq!(output, risc0_zkvm::guest::keccak::keccak256(b"hello world"));
+//! ```
+//!
+//! The zkVM circuit has no Keccak accelerator, so the Keccak-f\[1600\]
+//! permutation runs in software. All hashing goes through [keccakf], so an
+//! accelerator can be adopted there without changing callers.
+//!
+//! [tiny-keccak]: https://crates.io/crates/tiny-keccak
+
+const ROUNDS: usize = 24;
+
+const RC: [u64; ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+// Rotation offsets and lane positions for the combined rho and pi steps,
+// following the lane visited at each step from position 1.
+const RHO: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+const PI: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+// Domain separation suffixes, including the first bit of the padding.
+const KECCAK_DELIM: u8 = 0x01;
+const SHA3_DELIM: u8 = 0x06;
+
+/// Apply the Keccak-f\[1600\] permutation to `state`.
+pub fn keccakf(state: &mut [u64; 25]) {
+    for rc in RC {
+        // Theta
+        let mut c = [0u64; 5];
+        for x in 0..5 {
+            c[x] = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in (0..25).step_by(5) {
+                state[y + x] ^= d;
+            }
+        }
+
+        // Rho and pi
+        let mut last = state[1];
+        for (rho, pi) in RHO.iter().zip(PI) {
+            let next = state[pi];
+            state[pi] = last.rotate_left(*rho);
+            last = next;
+        }
+
+        // Chi
+        for y in (0..25).step_by(5) {
+            let row = [
+                state[y],
+                state[y + 1],
+                state[y + 2],
+                state[y + 3],
+                state[y + 4],
+            ];
+            for x in 0..5 {
+                state[y + x] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+
+        // Iota
+        state[0] ^= rc;
+    }
+}
+
+/// A hash function that absorbs input and writes a digest.
+pub trait Hasher {
+    /// Absorb `input` into the hasher.
+    fn update(&mut self, input: &[u8]);
+
+    /// Pad the input and write the digest to `output`.
+    fn finalize(self, output: &mut [u8]);
+}
+
+#[derive(Clone)]
+struct Sponge {
+    state: [u64; 25],
+    rate: usize,
+    offset: usize,
+    delim: u8,
+}
+
+impl Sponge {
+    fn new(bits: usize, delim: u8) -> Self {
+        Self {
+            state: [0; 25],
+            rate: 200 - bits / 4,
+            offset: 0,
+            delim,
+        }
+    }
+
+    fn xor_byte(&mut self, idx: usize, byte: u8) {
+        self.state[idx / 8] ^= (byte as u64) << (8 * (idx % 8));
+    }
+
+    fn update(&mut self, input: &[u8]) {
+        for byte in input {
+            self.xor_byte(self.offset, *byte);
+            self.offset += 1;
+            if self.offset == self.rate {
+                keccakf(&mut self.state);
+                self.offset = 0;
+            }
+        }
+    }
+
+    fn finalize(mut self, output: &mut [u8]) {
+        self.xor_byte(self.offset, self.delim);
+        self.xor_byte(self.rate - 1, 0x80);
+        keccakf(&mut self.state);
+
+        for (i, byte) in output.iter_mut().enumerate() {
+            let idx = i % self.rate;
+            if i > 0 && idx == 0 {
+                keccakf(&mut self.state);
+            }
+            *byte = (self.state[idx / 8] >> (8 * (idx % 8))) as u8;
+        }
+    }
+}
+
+/// The original Keccak hash function, as used by Ethereum.
+#[derive(Clone)]
+pub struct Keccak(Sponge);
+
+impl Keccak {
+    /// Keccak with a 224-bit digest.
+    pub fn v224() -> Self {
+        Self(Sponge::new(224, KECCAK_DELIM))
+    }
+
+    /// Keccak with a 256-bit digest.
+    pub fn v256() -> Self {
+        Self(Sponge::new(256, KECCAK_DELIM))
+    }
+
+    /// Keccak with a 384-bit digest.
+    pub fn v384() -> Self {
+        Self(Sponge::new(384, KECCAK_DELIM))
+    }
+
+    /// Keccak with a 512-bit digest.
+    pub fn v512() -> Self {
+        Self(Sponge::new(512, KECCAK_DELIM))
+    }
+}
+
+impl Hasher for Keccak {
+    fn update(&mut self, input: &[u8]) {
+        self.0.update(input)
+    }
+
+    fn finalize(self, output: &mut [u8]) {
+        self.0.finalize(output)
+    }
+}
+
+/// The SHA-3 hash function, as standardized in FIPS 202.
+#[derive(Clone)]
+pub struct Sha3(Sponge);
+
+impl Sha3 {
+    /// SHA3-224.
+    pub fn v224() -> Self {
+        Self(Sponge::new(224, SHA3_DELIM))
+    }
+
+    /// SHA3-256.
+    pub fn v256() -> Self {
+        Self(Sponge::new(256, SHA3_DELIM))
+    }
+
+    /// SHA3-384.
+    pub fn v384() -> Self {
+        Self(Sponge::new(384, SHA3_DELIM))
+    }
+
+    /// SHA3-512.
+    pub fn v512() -> Self {
+        Self(Sponge::new(512, SHA3_DELIM))
+    }
+}
+
+impl Hasher for Sha3 {
+    fn update(&mut self, input: &[u8]) {
+        self.0.update(input)
+    }
+
+    fn finalize(self, output: &mut [u8]) {
+        self.0.finalize(output)
+    }
+}
+
+/// Compute the Keccak-256 hash of `data`.
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    let mut output = [0u8; 32];
+    hasher.update(data);
+    hasher.finalize(&mut output);
+    output
+}
+
+/// Compute the SHA3-256 hash of `data`.
+pub fn sha3_256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha3::v256();
+    let mut output = [0u8; 32];
+    hasher.update(data);
+    hasher.finalize(&mut output);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keccak256_vectors() {
+        assert_eq!(
+            hex::encode(keccak256(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        assert_eq!(
+            hex::encode(keccak256(b"hello world")),
+            "47173285a8d7341e5e972fc677286384f802f8ef42a5ec5f03bbfa254cb01fad"
+        );
+    }
+
+    #[test]
+    fn sha3_vectors() {
+        assert_eq!(
+            hex::encode(sha3_256(b"")),
+            "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a"
+        );
+        assert_eq!(
+            hex::encode(sha3_256(b"abc")),
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"
+        );
+    }
+
+    #[test]
+    fn multi_block() {
+        // Longer than the rate, and absorbed in pieces that straddle blocks.
+        let data = [0xa3u8; 200];
+        let mut hasher = Sha3::v256();
+        for chunk in data.chunks(7) {
+            hasher.update(chunk);
+        }
+        let mut output = [0u8; 32];
+        hasher.finalize(&mut output);
+        assert_eq!(output, sha3_256(&data));
+        assert_eq!(
+            hex::encode(output),
+            "79f38adec5c20307a98ef76e8324afbfd46cfd81b22e3973c65fa1bd9de31787"
+        );
+    }
+}
@@ -0,0 +1,271 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! KZG point evaluation for EIP-4844 blobs.
+//!
+//! A rollup guest that reads a blob from its input can check that it matches
+//! the versioned hash committed on chain, by verifying KZG opening proofs
+//! exactly as the point evaluation precompile at address `0x0a` does:
+//!
+//! ```no_run
+//! use risc0_zkvm::guest::{env, kzg};
+//!
+//! let input: Vec<u8> = env::read();
+//! let output = kzg::point_evaluation(&input).unwrap();
+//! env::commit_slice(&output);
+//! ```
+//!
+//! The pairing check uses the [bls12_381] crate. Its 381-bit field is wider
+//! than the 256-bit bigint accelerator, so the curve arithmetic runs in
+//! software and a single proof costs tens of millions of cycles; hashing the
+//! commitment uses the SHA-256 accelerator.
+//!
+//! This module requires the `kzg` feature.
+
+use core::fmt;
+
+use bls12_381::{
+    multi_miller_loop, G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt, Scalar,
+};
+
+use crate::sha::{Impl, Sha256};
+
+/// The version byte of a versioned hash for a KZG commitment.
+pub const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
+/// The number of field elements in a blob.
+pub const FIELD_ELEMENTS_PER_BLOB: u64 = 4096;
+
+/// The order of the BLS12-381 scalar field, big-endian.
+pub const BLS_MODULUS: [u8; 32] = [
+    0x73, 0xed, 0xa7, 0x53, 0x29, 0x9d, 0x7d, 0x48, 0x33, 0x39, 0xd8, 0x08, 0x09, 0xa1, 0xd8, 0x05,
+    0x53, 0xbd, 0xa4, 0x02, 0xff, 0xfe, 0x5b, 0xfe, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01,
+];
+
+/// The length of the input to [point_evaluation].
+pub const POINT_EVALUATION_INPUT_LEN: usize = 192;
+
+// [tau]G2 from the Ethereum KZG ceremony, compressed. This is the second G2
+// point of the trusted setup; the first is the generator.
+const SETUP_G2_TAU: [u8; 96] = [
+    0xb5, 0xbf, 0xd7, 0xdd, 0x8c, 0xde, 0xb1, 0x28, 0x84, 0x3b, 0xc2, 0x87, 0x23, 0x0a, 0xf3, 0x89,
+    0x26, 0x18, 0x70, 0x75, 0xcb, 0xfb, 0xef, 0xa8, 0x10, 0x09, 0xa2, 0xce, 0x61, 0x5a, 0xc5, 0x3d,
+    0x29, 0x14, 0xe5, 0x87, 0x0c, 0xb4, 0x52, 0xd2, 0xaf, 0xaa, 0xab, 0x24, 0xf3, 0x49, 0x9f, 0x72,
+    0x18, 0x5c, 0xbf, 0xee, 0x53, 0x49, 0x27, 0x14, 0x73, 0x44, 0x29, 0xb7, 0xb3, 0x86, 0x08, 0xe2,
+    0x39, 0x26, 0xc9, 0x11, 0xcc, 0xec, 0xea, 0xc9, 0xa3, 0x68, 0x51, 0x47, 0x7b, 0xa4, 0xc6, 0x0b,
+    0x08, 0x70, 0x41, 0xde, 0x62, 0x10, 0x00, 0xed, 0xc9, 0x8e, 0xda, 0xda, 0x20, 0xc1, 0xde, 0xf2,
+];
+
+/// An error from verifying a KZG proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KzgError {
+    /// The point evaluation input is not [POINT_EVALUATION_INPUT_LEN] bytes.
+    InvalidInputLength,
+    /// The versioned hash does not match the commitment.
+    VersionedHashMismatch,
+    /// A field element is not less than [BLS_MODULUS].
+    InvalidFieldElement,
+    /// The commitment or proof is not a valid compressed G1 point in the
+    /// prime-order subgroup.
+    InvalidPoint,
+    /// The proof does not show that the committed polynomial takes the claimed
+    /// value.
+    InvalidProof,
+}
+
+impl fmt::Display for KzgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidInputLength => write!(f, "invalid point evaluation input length"),
+            Self::VersionedHashMismatch => write!(f, "versioned hash does not match commitment"),
+            Self::InvalidFieldElement => write!(f, "invalid field element"),
+            Self::InvalidPoint => write!(f, "invalid G1 point"),
+            Self::InvalidProof => write!(f, "KZG proof does not verify"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for KzgError {}
+
+/// Compute the versioned hash of a KZG commitment, as included in blob
+/// transactions.
+pub fn kzg_to_versioned_hash(commitment: &[u8; 48]) -> [u8; 32] {
+    let mut hash: [u8; 32] = Impl::hash_bytes(commitment).as_bytes().try_into().unwrap();
+    hash[0] = VERSIONED_HASH_VERSION_KZG;
+    hash
+}
+
+fn field_element(bytes: &[u8; 32]) -> Result<Scalar, KzgError> {
+    let mut le = *bytes;
+    le.reverse();
+    Option::from(Scalar::from_bytes(&le)).ok_or(KzgError::InvalidFieldElement)
+}
+
+fn g1_point(bytes: &[u8; 48]) -> Result<G1Affine, KzgError> {
+    Option::from(G1Affine::from_compressed(bytes)).ok_or(KzgError::InvalidPoint)
+}
+
+/// Verify that the polynomial committed to by `commitment` evaluates to `y` at
+/// `z`.
+///
+/// The field elements `z` and `y` are big-endian, and must be less than
+/// [BLS_MODULUS].
+pub fn verify_kzg_proof(
+    commitment: &[u8; 48],
+    z: &[u8; 32],
+    y: &[u8; 32],
+    proof: &[u8; 48],
+) -> Result<(), KzgError> {
+    let z = field_element(z)?;
+    let y = field_element(y)?;
+    let commitment = g1_point(commitment)?;
+    let proof = g1_point(proof)?;
+    let tau = G2Affine::from_compressed(&SETUP_G2_TAU).unwrap();
+
+    // e(C - [y]G1, -G2) * e(proof, [tau - z]G2) == 1
+    let p_minus_y = G1Affine::from(G1Projective::from(commitment) - G1Affine::generator() * y);
+    let x_minus_z = G2Affine::from(G2Projective::from(tau) - G2Affine::generator() * z);
+    let check = multi_miller_loop(&[
+        (&p_minus_y, &G2Prepared::from(-G2Affine::generator())),
+        (&proof, &G2Prepared::from(x_minus_z)),
+    ])
+    .final_exponentiation();
+
+    match check == Gt::identity() {
+        true => Ok(()),
+        false => Err(KzgError::InvalidProof),
+    }
+}
+
+/// Run the EIP-4844 point evaluation precompile on `input`.
+///
+/// The input is the versioned hash, `z`, `y`, the commitment, and the proof,
+/// concatenated. On success, returns [FIELD_ELEMENTS_PER_BLOB] and
+/// [BLS_MODULUS] as two big-endian 32-byte words, as the precompile does.
+pub fn point_evaluation(input: &[u8]) -> Result<[u8; 64], KzgError> {
+    if input.len() != POINT_EVALUATION_INPUT_LEN {
+        return Err(KzgError::InvalidInputLength);
+    }
+    let versioned_hash: &[u8; 32] = input[..32].try_into().unwrap();
+    let z = input[32..64].try_into().unwrap();
+    let y = input[64..96].try_into().unwrap();
+    let commitment = input[96..144].try_into().unwrap();
+    let proof = input[144..].try_into().unwrap();
+
+    if kzg_to_versioned_hash(commitment) != *versioned_hash {
+        return Err(KzgError::VersionedHashMismatch);
+    }
+    verify_kzg_proof(commitment, z, y, proof)?;
+
+    let mut output = [0u8; 64];
+    output[24..32].copy_from_slice(&FIELD_ELEMENTS_PER_BLOB.to_be_bytes());
+    output[32..].copy_from_slice(&BLS_MODULUS);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    // [tau]G1 from the same ceremony, compressed.
+    const SETUP_G1_TAU: &str = "ad3eb50121139aa34db1d545093ac9374ab7bca2c0f3bf28e27c8dcd8fc7cb42d25926fc0c97b336e9f0fb35e5a04c81";
+
+    fn be_bytes(scalar: &Scalar) -> [u8; 32] {
+        let mut bytes = scalar.to_bytes();
+        bytes.reverse();
+        bytes
+    }
+
+    // Commit to p(x) = a + b * x and open it at z. The quotient
+    // (p(x) - p(z)) / (x - z) is the constant b.
+    fn opening(a: u64, b: u64, z: u64) -> ([u8; 48], [u8; 32], [u8; 32], [u8; 48]) {
+        let (a, b, z) = (Scalar::from(a), Scalar::from(b), Scalar::from(z));
+        let tau =
+            G1Affine::from_compressed(&hex::decode(SETUP_G1_TAU).unwrap().try_into().unwrap())
+                .unwrap();
+        let commitment = G1Affine::from(G1Affine::generator() * a + tau * b);
+        let proof = G1Affine::from(G1Affine::generator() * b);
+        (
+            commitment.to_compressed(),
+            be_bytes(&z),
+            be_bytes(&(a + b * z)),
+            proof.to_compressed(),
+        )
+    }
+
+    fn input(commitment: &[u8; 48], z: &[u8; 32], y: &[u8; 32], proof: &[u8; 48]) -> Vec<u8> {
+        let mut input = kzg_to_versioned_hash(commitment).to_vec();
+        input.extend_from_slice(z);
+        input.extend_from_slice(y);
+        input.extend_from_slice(commitment);
+        input.extend_from_slice(proof);
+        input
+    }
+
+    #[test]
+    fn verify_opening() {
+        let (commitment, z, y, proof) = opening(7, 3, 1234);
+        assert_eq!(verify_kzg_proof(&commitment, &z, &y, &proof), Ok(()));
+
+        let (_, _, wrong_y, _) = opening(7, 3, 1235);
+        assert_eq!(
+            verify_kzg_proof(&commitment, &z, &wrong_y, &proof),
+            Err(KzgError::InvalidProof)
+        );
+        assert_eq!(
+            verify_kzg_proof(&commitment, &z, &BLS_MODULUS, &proof),
+            Err(KzgError::InvalidFieldElement)
+        );
+        assert_eq!(
+            verify_kzg_proof(&[0; 48], &z, &y, &proof),
+            Err(KzgError::InvalidPoint)
+        );
+    }
+
+    #[test]
+    fn point_evaluation_precompile() {
+        let (commitment, z, y, proof) = opening(7, 3, 1234);
+        let mut input = input(&commitment, &z, &y, &proof);
+        let output = point_evaluation(&input).unwrap();
+        assert_eq!(
+            hex::encode(output),
+            "0000000000000000000000000000000000000000000000000000000000001000\
+             73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001"
+        );
+
+        assert_eq!(
+            point_evaluation(&input[1..]),
+            Err(KzgError::InvalidInputLength)
+        );
+        input[31] ^= 1;
+        assert_eq!(
+            point_evaluation(&input),
+            Err(KzgError::VersionedHashMismatch)
+        );
+    }
+
+    #[test]
+    fn versioned_hash() {
+        // The commitment to the zero polynomial is the point at infinity.
+        let mut infinity = [0u8; 48];
+        infinity[0] = 0xc0;
+        assert_eq!(
+            hex::encode(kzg_to_versioned_hash(&infinity)),
+            "010657f37554c781402a22917dee2f75def7ab966d7b770905398eba3c444014"
+        );
+    }
+}
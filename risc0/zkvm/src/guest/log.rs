@@ -0,0 +1,161 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Leveled logging from the guest.
+//!
+//! Events are sent to the host, which records them through its `tracing`
+//! subscriber. Each event keeps its level and target, so guest logs can be
+//! filtered alongside host logs, for example with
+//! `RUST_LOG=my_guest=debug`. The target defaults to the module path of the
+//! call site.
+//!
+//! ```no_run
+//! use risc0_zkvm::guest::log::{debug, info, warn};
+//!
+//! let count = 3;
+//! info!("processing {count} items");
+//! debug!(target: "my_guest::parser", "token {:?}", "abc");
+//! warn!("input was truncated");
+//! ```
+//!
+//! Log events are not part of the proof, and formatting them costs cycles even
+//! when the host discards them.
+
+use alloc::string::String;
+use core::fmt::{self, Write as _};
+
+use risc0_zkvm_platform::syscall::sys_log_event;
+
+#[doc(inline)]
+pub use crate::{
+    __guest_debug as debug, __guest_error as error, __guest_info as info, __guest_log as log,
+    __guest_trace as trace, __guest_warn as warn,
+};
+
+/// The verbosity of a log event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Level {
+    /// Errors that the guest cannot recover from.
+    Error = 1,
+    /// Conditions that may indicate a problem.
+    Warn = 2,
+    /// Informational messages.
+    Info = 3,
+    /// Details useful when debugging.
+    Debug = 4,
+    /// Very verbose details.
+    Trace = 5,
+}
+
+/// Send a log event with the given level and target to the host.
+///
+/// This is usually called through the [log!] macro and its per-level variants.
+pub fn log_event(level: Level, target: &str, args: fmt::Arguments) {
+    let mut msg = String::new();
+    let msg = match args.as_str() {
+        Some(msg) => msg,
+        None => {
+            msg.write_fmt(args).unwrap();
+            msg.as_str()
+        }
+    };
+    unsafe {
+        sys_log_event(
+            level as u32,
+            target.as_ptr(),
+            target.len(),
+            msg.as_ptr(),
+            msg.len(),
+        );
+    }
+}
+
+/// Log an event at the given [Level].
+///
+/// An optional `target: "..."` may precede the format arguments.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __guest_log {
+    (target: $target:expr, $level:expr, $($arg:tt)+) => {
+        $crate::guest::log::log_event($level, $target, ::core::format_args!($($arg)+))
+    };
+    ($level:expr, $($arg:tt)+) => {
+        $crate::guest::log::log_event(
+            $level,
+            ::core::module_path!(),
+            ::core::format_args!($($arg)+),
+        )
+    };
+}
+
+/// Log an event at [Level::Error].
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __guest_error {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::guest::log::log!(target: $target, $crate::guest::log::Level::Error, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::guest::log::log!($crate::guest::log::Level::Error, $($arg)+)
+    };
+}
+
+/// Log an event at [Level::Warn].
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __guest_warn {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::guest::log::log!(target: $target, $crate::guest::log::Level::Warn, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::guest::log::log!($crate::guest::log::Level::Warn, $($arg)+)
+    };
+}
+
+/// Log an event at [Level::Info].
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __guest_info {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::guest::log::log!(target: $target, $crate::guest::log::Level::Info, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::guest::log::log!($crate::guest::log::Level::Info, $($arg)+)
+    };
+}
+
+/// Log an event at [Level::Debug].
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __guest_debug {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::guest::log::log!(target: $target, $crate::guest::log::Level::Debug, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::guest::log::log!($crate::guest::log::Level::Debug, $($arg)+)
+    };
+}
+
+/// Log an event at [Level::Trace].
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __guest_trace {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::guest::log::log!(target: $target, $crate::guest::log::Level::Trace, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::guest::log::log!($crate::guest::log::Level::Trace, $($arg)+)
+    };
+}
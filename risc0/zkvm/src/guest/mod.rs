@@ -70,6 +70,12 @@
 //! required, as well as the use of the [crate::guest::entry] macro. When `std` is enabled, these
 //! three lines can be omitted and many features of `std` can be used.
 //!
+//! With `std`, a program written as a command-line tool can often run as a guest unmodified:
+//! `std::env::args` returns the arguments set with `ExecutorEnvBuilder::args`, `std::env::var`
+//! reads the variables set with `ExecutorEnvBuilder::env_var`, and `std::io::stdin`, `stdout` and
+//! `stderr` are connected to the streams of the executor environment. Listing all variables with
+//! `std::env::vars` is not supported, since the guest can only look variables up by name.
+//!
 //! If you encounter problems building zkVM guest code, you can see if we have a
 //! known workaround for your issue by looking in our
 //! [rust guest workarounds](https://github.com/risc0/risc0/issues?q=is%3Aissue+is%3Aopen+label%3A%22rust+guest+workarounds%22)
@@ -79,14 +85,30 @@
 
 #![deny(missing_docs)]
 
+pub mod bn254;
+#[cfg(all(feature = "c-runtime", target_os = "zkvm"))]
+pub mod c_rt;
 pub mod env;
+pub mod keccak;
+#[cfg(feature = "kzg")]
+pub mod kzg;
+pub mod log;
+pub mod profile;
+pub mod region;
 pub mod sha;
+pub mod sha512;
+pub mod sig;
+#[cfg(target_os = "zkvm")]
+pub mod thread;
+pub mod verified_file;
 
 #[cfg(target_os = "zkvm")]
 use core::arch::asm;
 
 use risc0_zkvm_platform::syscall::sys_panic;
 
+#[doc(inline)]
+pub use crate::__guest_profile_zone as profile_zone;
 pub use crate::entry;
 
 #[cfg(target_os = "zkvm")]
@@ -147,7 +169,9 @@ macro_rules! entry {
     };
 }
 
-#[cfg(target_os = "zkvm")]
+// C and C++ guests use the entrypoint in the crt0.S shipped with risc0-build,
+// which calls into the functions in [c_rt].
+#[cfg(all(target_os = "zkvm", not(feature = "c-runtime")))]
 #[no_mangle]
 unsafe extern "C" fn __start() -> ! {
     env::init();
@@ -163,13 +187,13 @@ unsafe extern "C" fn __start() -> ! {
     unreachable!();
 }
 
-#[cfg(target_os = "zkvm")]
+#[cfg(all(target_os = "zkvm", not(feature = "c-runtime")))]
 static STACK_TOP: u32 = risc0_zkvm_platform::memory::STACK_TOP;
 
 // Entry point; sets up global pointer and stack pointer and passes
 // to zkvm_start.  TODO: when asm_const is stablized, use that here
 // instead of defining a symbol and dereferencing it.
-#[cfg(target_os = "zkvm")]
+#[cfg(all(target_os = "zkvm", not(feature = "c-runtime")))]
 core::arch::global_asm!(
     r#"
 .section .text._start;
@@ -206,5 +230,6 @@ pub fn memory_barrier<T>(ptr: *const T) {
 #[cfg(all(target_os = "zkvm", not(feature = "std")))]
 #[panic_handler]
 fn panic_impl(panic_info: &core::panic::PanicInfo) -> ! {
+    env::commit_panic(&alloc::format!("{panic_info}"), panic_info.location());
     risc0_zkvm_platform::rust_rt::panic_fault(panic_info);
 }
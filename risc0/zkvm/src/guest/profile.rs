@@ -0,0 +1,70 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Named zones for the host profiler.
+//!
+//! The host profiler attributes cycles to the functions on the guest call
+//! stack. A zone adds a frame with a chosen name below the function that
+//! opened it, so cycles can be attributed to a phase of the guest, such as
+//! parsing its input, that spans several functions or only part of one.
+//!
+//! ```no_run
+//! use risc0_zkvm::guest::profile_zone;
+//!
+//! # fn parse() {}
+//! # fn verify() {}
+//! {
+//!     profile_zone!("parse");
+//!     parse();
+//! }
+//! profile_zone!("verify");
+//! verify();
+//! ```
+//!
+//! Entering and leaving a zone each cost one syscall. When the host is not
+//! profiling, zones have no other effect.
+
+use risc0_zkvm_platform::syscall::{sys_profile_zone_enter, sys_profile_zone_exit};
+
+/// A guard that keeps a profiler zone open until it is dropped.
+///
+/// This is usually created with the [crate::guest::profile_zone] macro.
+#[must_use = "the zone is closed when the guard is dropped"]
+pub struct ProfileZone(());
+
+impl ProfileZone {
+    /// Enter the zone with the given name.
+    pub fn enter(name: &str) -> Self {
+        unsafe { sys_profile_zone_enter(name.as_ptr(), name.len()) };
+        Self(())
+    }
+}
+
+impl Drop for ProfileZone {
+    fn drop(&mut self) {
+        sys_profile_zone_exit();
+    }
+}
+
+/// Attribute the cycles spent in the rest of the enclosing scope to a zone
+/// with the given name.
+///
+/// See the [crate::guest::profile] module.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __guest_profile_zone {
+    ($name:expr) => {
+        let _profile_zone = $crate::guest::profile::ProfileZone::enter($name);
+    };
+}
@@ -0,0 +1,304 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Read-only virtual regions that are paged in on demand.
+//!
+//! A [Region] presents a dataset that may be far larger than guest memory as
+//! an addressable range of bytes. Only a bounded number of pages are resident
+//! at a time. When a read touches a page that is not resident, the region
+//! faults: it evicts a resident page if it is full, and calls the
+//! [PageSource::load] handler of its source to fill the page.
+//!
+//! The handler is defined by the guest, which decides where pages come from
+//! and how they are verified. [VerifiedFile] is a source whose pages are
+//! fetched from the host and checked against a Merkle root:
+//!
+//! ```no_run
+//! use risc0_zkvm::{
+//!     guest::{env, region::Region, verified_file::VerifiedFile},
+//!     sha::Digest,
+//! };
+//!
+//! let root: Digest = env::read();
+//! // Keep at most 16 pages of the file in memory.
+//! let mut region = Region::new(VerifiedFile::open(&root), 16);
+//! let mut record = [0u8; 64];
+//! region.read_at(1 << 30, &mut record);
+//! ```
+//!
+//! The zkVM does not deliver memory faults to the guest, so a region is not
+//! mapped into the address space. Reads go through [Region::read_at] and
+//! [Region::get], which check residency in software.
+
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+
+use super::verified_file::VerifiedFile;
+use crate::merkle_file::MERKLE_FILE_CHUNK_SIZE;
+
+/// A read-only source of fixed-size pages, which fills a [Region] on demand.
+pub trait PageSource {
+    /// The size of each page in bytes.
+    fn page_size(&self) -> usize;
+
+    /// The length of the data in bytes.
+    fn len(&self) -> u64;
+
+    /// Whether the data is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Fill `page` with the contents of the page at `index`.
+    ///
+    /// `page` is [PageSource::page_size] bytes long, except for the last page,
+    /// which is truncated to the end of the data. This is called on every page
+    /// fault, and should panic if the contents cannot be verified.
+    fn load(&mut self, index: u64, page: &mut [u8]);
+}
+
+impl PageSource for VerifiedFile {
+    fn page_size(&self) -> usize {
+        MERKLE_FILE_CHUNK_SIZE
+    }
+
+    fn len(&self) -> u64 {
+        VerifiedFile::len(self)
+    }
+
+    fn load(&mut self, index: u64, page: &mut [u8]) {
+        let offset = index * MERKLE_FILE_CHUNK_SIZE as u64;
+        self.read_at(offset, page);
+    }
+}
+
+struct Page {
+    index: u64,
+    data: Vec<u8>,
+    referenced: bool,
+}
+
+/// A virtual region backed by a [PageSource], holding at most a fixed number
+/// of pages in memory.
+///
+/// Pages are evicted with the clock algorithm, which approximates evicting
+/// the least recently used page.
+pub struct Region<S: PageSource> {
+    source: S,
+    capacity: usize,
+    pages: Vec<Page>,
+    hand: usize,
+    faults: u64,
+}
+
+impl<S: PageSource> Region<S> {
+    /// Construct a region over `source` that keeps at most `capacity` pages
+    /// resident.
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(source: S, capacity: usize) -> Self {
+        assert!(capacity > 0, "a region must hold at least one page");
+        Self {
+            source,
+            capacity,
+            pages: Vec::with_capacity(capacity),
+            hand: 0,
+            faults: 0,
+        }
+    }
+
+    /// The length of the region in bytes.
+    pub fn len(&self) -> u64 {
+        self.source.len()
+    }
+
+    /// Whether the region is empty.
+    pub fn is_empty(&self) -> bool {
+        self.source.is_empty()
+    }
+
+    /// The number of page faults handled so far.
+    pub fn faults(&self) -> u64 {
+        self.faults
+    }
+
+    /// Return the source, dropping all resident pages.
+    pub fn into_source(self) -> S {
+        self.source
+    }
+
+    /// Read the byte at `offset`.
+    ///
+    /// Panics if `offset` is past the end of the region.
+    pub fn get(&mut self, offset: u64) -> u8 {
+        assert!(
+            offset < self.len(),
+            "offset {offset} is out of bounds for a region of {} bytes",
+            self.len()
+        );
+        let page_size = self.source.page_size() as u64;
+        self.page(offset / page_size)[(offset % page_size) as usize]
+    }
+
+    /// Read into `buf` from `offset`, returning the number of bytes read.
+    ///
+    /// This reads fewer than `buf.len()` bytes only at the end of the region.
+    pub fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> usize {
+        let page_size = self.source.page_size() as u64;
+        let end = u64::min(self.len(), offset.saturating_add(buf.len() as u64));
+        let mut pos = offset;
+        while pos < end {
+            let start = (pos % page_size) as usize;
+            let page = self.page(pos / page_size);
+            let n = usize::min(page.len() - start, (end - pos) as usize);
+            let dst = (pos - offset) as usize;
+            buf[dst..dst + n].copy_from_slice(&page[start..start + n]);
+            pos += n as u64;
+        }
+        end.saturating_sub(offset) as usize
+    }
+
+    /// Read the bytes in `range`.
+    ///
+    /// Panics if `range` extends past the end of the region.
+    pub fn read_range(&mut self, range: Range<u64>) -> Vec<u8> {
+        assert!(
+            range.start <= range.end && range.end <= self.len(),
+            "range {range:?} is out of bounds for a region of {} bytes",
+            self.len()
+        );
+        let mut buf = vec![0u8; (range.end - range.start) as usize];
+        self.read_at(range.start, &mut buf);
+        buf
+    }
+
+    fn page(&mut self, index: u64) -> &[u8] {
+        let slot = match self.pages.iter().position(|page| page.index == index) {
+            Some(slot) => slot,
+            None => self.fault(index),
+        };
+        let page = &mut self.pages[slot];
+        page.referenced = true;
+        &page.data
+    }
+
+    fn fault(&mut self, index: u64) -> usize {
+        self.faults += 1;
+        let page_size = self.source.page_size() as u64;
+        let len = u64::min(page_size, self.len() - index * page_size) as usize;
+
+        let slot = if self.pages.len() < self.capacity {
+            self.pages.push(Page {
+                index,
+                data: vec![0; len],
+                referenced: false,
+            });
+            self.pages.len() - 1
+        } else {
+            // Advance the hand past referenced pages, clearing their bits, and
+            // reuse the first page that has not been touched since the last
+            // sweep.
+            while self.pages[self.hand].referenced {
+                self.pages[self.hand].referenced = false;
+                self.hand = (self.hand + 1) % self.capacity;
+            }
+            let slot = self.hand;
+            self.hand = (self.hand + 1) % self.capacity;
+            let page = &mut self.pages[slot];
+            page.index = index;
+            page.data.resize(len, 0);
+            slot
+        };
+
+        self.source.load(index, &mut self.pages[slot].data);
+        slot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::{PageSource, Region};
+
+    // Pages whose bytes are the low bits of their offsets, recording each load.
+    struct Counting {
+        len: u64,
+        loads: Vec<u64>,
+    }
+
+    impl PageSource for Counting {
+        fn page_size(&self) -> usize {
+            8
+        }
+
+        fn len(&self) -> u64 {
+            self.len
+        }
+
+        fn load(&mut self, index: u64, page: &mut [u8]) {
+            self.loads.push(index);
+            for (i, byte) in page.iter_mut().enumerate() {
+                *byte = (index * 8 + i as u64) as u8;
+            }
+        }
+    }
+
+    fn region(len: u64, capacity: usize) -> Region<Counting> {
+        let source = Counting {
+            len,
+            loads: Vec::new(),
+        };
+        Region::new(source, capacity)
+    }
+
+    #[test]
+    fn reads_across_pages() {
+        let mut region = region(30, 2);
+        assert_eq!(region.read_range(5..20), (5..20).collect::<Vec<u8>>());
+        assert_eq!(region.get(29), 29);
+
+        let mut buf = [0u8; 8];
+        assert_eq!(region.read_at(26, &mut buf), 4);
+        assert_eq!(&buf[..4], &[26, 27, 28, 29]);
+        assert_eq!(region.read_at(40, &mut buf), 0);
+    }
+
+    #[test]
+    fn faults_only_on_missing_pages() {
+        let mut region = region(64, 2);
+        region.get(0);
+        region.get(7);
+        region.get(8);
+        region.get(1);
+        assert_eq!(region.faults(), 2);
+
+        // Both resident pages are referenced, so the sweep clears them and
+        // evicts page 0, then page 1 is evicted as the only unreferenced page.
+        region.get(16);
+        region.get(16);
+        region.get(24);
+        assert_eq!(region.faults(), 4);
+        assert_eq!(region.get(17), 17);
+        assert_eq!(region.get(25), 25);
+        assert_eq!(region.faults(), 4);
+        assert_eq!(region.into_source().loads, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn get_out_of_bounds() {
+        region(10, 1).get(10);
+    }
+}
@@ -0,0 +1,362 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Functions for computing SHA-512 and SHA-384 hashes.
+//!
+//! ```
+//! use risc0_zkvm::guest::sha512::{sha384, sha512, Sha512};
+//!
+//! let mut hasher = Sha512::new();
+//! hasher.update(b"hello ");
+//! hasher.update(b"world");
+//! assert_eq!(hasher.finalize(), sha512(b"hello world"));
+//! assert_eq!(sha384(b"hello world").len(), 48);
+//! ```
+//!
+//! The zkVM circuit accelerates SHA-256 only, and its compression function
+//! cannot be composed into SHA-512, which uses 64-bit words, different
+//! rotations, and 80 rounds. The SHA-512 compression function therefore runs
+//! in software. It keeps a rolling 16-word message schedule rather than
+//! expanding all 80 words up front, which saves the memory traffic of the
+//! larger schedule. All hashing goes through [compress], so an accelerator can
+//! be adopted there without changing callers.
+
+const K: [u64; 80] = [
+    0x428a2f98d728ae22,
+    0x7137449123ef65cd,
+    0xb5c0fbcfec4d3b2f,
+    0xe9b5dba58189dbbc,
+    0x3956c25bf348b538,
+    0x59f111f1b605d019,
+    0x923f82a4af194f9b,
+    0xab1c5ed5da6d8118,
+    0xd807aa98a3030242,
+    0x12835b0145706fbe,
+    0x243185be4ee4b28c,
+    0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f,
+    0x80deb1fe3b1696b1,
+    0x9bdc06a725c71235,
+    0xc19bf174cf692694,
+    0xe49b69c19ef14ad2,
+    0xefbe4786384f25e3,
+    0x0fc19dc68b8cd5b5,
+    0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275,
+    0x4a7484aa6ea6e483,
+    0x5cb0a9dcbd41fbd4,
+    0x76f988da831153b5,
+    0x983e5152ee66dfab,
+    0xa831c66d2db43210,
+    0xb00327c898fb213f,
+    0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2,
+    0xd5a79147930aa725,
+    0x06ca6351e003826f,
+    0x142929670a0e6e70,
+    0x27b70a8546d22ffc,
+    0x2e1b21385c26c926,
+    0x4d2c6dfc5ac42aed,
+    0x53380d139d95b3df,
+    0x650a73548baf63de,
+    0x766a0abb3c77b2a8,
+    0x81c2c92e47edaee6,
+    0x92722c851482353b,
+    0xa2bfe8a14cf10364,
+    0xa81a664bbc423001,
+    0xc24b8b70d0f89791,
+    0xc76c51a30654be30,
+    0xd192e819d6ef5218,
+    0xd69906245565a910,
+    0xf40e35855771202a,
+    0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8,
+    0x1e376c085141ab53,
+    0x2748774cdf8eeb99,
+    0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63,
+    0x4ed8aa4ae3418acb,
+    0x5b9cca4f7763e373,
+    0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc,
+    0x78a5636f43172f60,
+    0x84c87814a1f0ab72,
+    0x8cc702081a6439ec,
+    0x90befffa23631e28,
+    0xa4506cebde82bde9,
+    0xbef9a3f7b2c67915,
+    0xc67178f2e372532b,
+    0xca273eceea26619c,
+    0xd186b8c721c0c207,
+    0xeada7dd6cde0eb1e,
+    0xf57d4f7fee6ed178,
+    0x06f067aa72176fba,
+    0x0a637dc5a2c898a6,
+    0x113f9804bef90dae,
+    0x1b710b35131c471b,
+    0x28db77f523047d84,
+    0x32caab7b40c72493,
+    0x3c9ebe0a15c9bebc,
+    0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6,
+    0x597f299cfc657e2a,
+    0x5fcb6fab3ad6faec,
+    0x6c44198c4a475817,
+];
+
+const SHA512_INIT: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const SHA384_INIT: [u64; 8] = [
+    0xcbbb9d5dc1059ed8,
+    0x629a292a367cd507,
+    0x9159015a3070dd17,
+    0x152fecd8f70e5939,
+    0x67332667ffc00b31,
+    0x8eb44a8768581511,
+    0xdb0c2e0d64f98fa7,
+    0x47b5481dbefa4fa4,
+];
+
+/// The size of a SHA-512 or SHA-384 block, in bytes.
+pub const BLOCK_BYTES: usize = 128;
+
+// A Merkle-Damgard hasher over the SHA-512 compression function, shared by
+// SHA-512 and SHA-384, which differ only in their initial state and the
+// length of their output.
+#[derive(Clone)]
+struct Engine {
+    state: [u64; 8],
+    block: [u8; BLOCK_BYTES],
+    len: u128,
+}
+
+impl Engine {
+    const fn new(init: [u64; 8]) -> Self {
+        Self {
+            state: init,
+            block: [0; BLOCK_BYTES],
+            len: 0,
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let offset = (self.len % BLOCK_BYTES as u128) as usize;
+            let n = usize::min(BLOCK_BYTES - offset, data.len());
+            self.block[offset..offset + n].copy_from_slice(&data[..n]);
+            self.len += n as u128;
+            data = &data[n..];
+            if offset + n == BLOCK_BYTES {
+                compress(&mut self.state, &self.block);
+            }
+        }
+    }
+
+    fn finalize(mut self) -> [u64; 8] {
+        let bits = self.len * 8;
+        let offset = (self.len % BLOCK_BYTES as u128) as usize;
+        self.block[offset] = 0x80;
+        self.block[offset + 1..].fill(0);
+        if offset + 1 > BLOCK_BYTES - 16 {
+            compress(&mut self.state, &self.block);
+            self.block.fill(0);
+        }
+        self.block[BLOCK_BYTES - 16..].copy_from_slice(&bits.to_be_bytes());
+        compress(&mut self.state, &self.block);
+        self.state
+    }
+}
+
+fn to_bytes<const N: usize>(state: [u64; 8]) -> [u8; N] {
+    let mut out = [0u8; N];
+    for (chunk, word) in out.chunks_mut(8).zip(state) {
+        chunk.copy_from_slice(&word.to_be_bytes()[..chunk.len()]);
+    }
+    out
+}
+
+/// An incremental SHA-512 hasher.
+#[derive(Clone)]
+pub struct Sha512(Engine);
+
+impl Sha512 {
+    /// Construct a hasher with no data absorbed.
+    pub const fn new() -> Self {
+        Self(Engine::new(SHA512_INIT))
+    }
+
+    /// Absorb `data` into the hash.
+    pub fn update(&mut self, data: &[u8]) {
+        self.0.update(data)
+    }
+
+    /// Pad the input and return the digest.
+    pub fn finalize(self) -> [u8; 64] {
+        to_bytes(self.0.finalize())
+    }
+}
+
+impl Default for Sha512 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An incremental SHA-384 hasher.
+#[derive(Clone)]
+pub struct Sha384(Engine);
+
+impl Sha384 {
+    /// Construct a hasher with no data absorbed.
+    pub const fn new() -> Self {
+        Self(Engine::new(SHA384_INIT))
+    }
+
+    /// Absorb `data` into the hash.
+    pub fn update(&mut self, data: &[u8]) {
+        self.0.update(data)
+    }
+
+    /// Pad the input and return the digest.
+    pub fn finalize(self) -> [u8; 48] {
+        to_bytes(self.0.finalize())
+    }
+}
+
+impl Default for Sha384 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Compute the SHA-512 hash of `data`.
+pub fn sha512(data: &[u8]) -> [u8; 64] {
+    let mut hasher = Sha512::new();
+    hasher.update(data);
+    hasher.finalize()
+}
+
+/// Compute the SHA-384 hash of `data`.
+pub fn sha384(data: &[u8]) -> [u8; 48] {
+    let mut hasher = Sha384::new();
+    hasher.update(data);
+    hasher.finalize()
+}
+
+/// Apply the SHA-512 compression function to `state` with one block of input.
+pub fn compress(state: &mut [u64; 8], block: &[u8; BLOCK_BYTES]) {
+    let mut w = [0u64; 16];
+    for (w, chunk) in w.iter_mut().zip(block.chunks_exact(8)) {
+        *w = u64::from_be_bytes(chunk.try_into().unwrap());
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for (i, k) in K.iter().enumerate() {
+        // Extend the schedule in place: w[i % 16] holds w[i - 16] until it is
+        // replaced by w[i].
+        if i >= 16 {
+            let w15 = w[(i + 1) % 16];
+            let w2 = w[(i + 14) % 16];
+            let s0 = w15.rotate_right(1) ^ w15.rotate_right(8) ^ (w15 >> 7);
+            let s1 = w2.rotate_right(19) ^ w2.rotate_right(61) ^ (w2 >> 6);
+            w[i % 16] = w[i % 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[(i + 9) % 16])
+                .wrapping_add(s1);
+        }
+
+        let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(*k)
+            .wrapping_add(w[i % 16]);
+        let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+
+    for (state, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *state = state.wrapping_add(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sha512_vectors() {
+        assert_eq!(
+            hex::encode(sha512(b"")),
+            "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce\
+             47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e"
+        );
+        assert_eq!(
+            hex::encode(sha512(b"abc")),
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+             2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+        );
+    }
+
+    #[test]
+    fn sha384_vectors() {
+        assert_eq!(
+            hex::encode(sha384(b"")),
+            "38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da\
+             274edebfe76f65fbd51ad2f14898b95b"
+        );
+        assert_eq!(
+            hex::encode(sha384(b"abc")),
+            "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed\
+             8086072ba1e7cc2358baeca134c825a7"
+        );
+    }
+
+    #[test]
+    fn multi_block() {
+        // The two-block message from FIPS 180-2, absorbed in pieces that
+        // straddle the block boundary.
+        let data = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmn\
+                     hijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+        let mut hasher = Sha512::new();
+        for chunk in data.chunks(7) {
+            hasher.update(chunk);
+        }
+        assert_eq!(hasher.finalize(), sha512(data));
+        assert_eq!(
+            hex::encode(sha512(data)),
+            "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018\
+             501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909"
+        );
+    }
+}
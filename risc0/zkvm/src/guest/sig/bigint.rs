@@ -0,0 +1,186 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 256-bit modular arithmetic on the bigint accelerator.
+//!
+//! Values are little-endian arrays of words. Multiplication is done by the
+//! accelerator; addition and subtraction are cheap enough in software.
+
+use risc0_zkvm_platform::syscall::{bigint, nr::SYS_MODINV, sys_bigint, syscall_2};
+
+pub(crate) type U256 = [u32; bigint::WIDTH_WORDS];
+
+pub(crate) const ZERO: U256 = [0; bigint::WIDTH_WORDS];
+pub(crate) const ONE: U256 = [1, 0, 0, 0, 0, 0, 0, 0];
+
+/// Parse a big-endian hex string of exactly 64 digits.
+pub(crate) const fn from_hex(hex: &str) -> U256 {
+    let hex = hex.as_bytes();
+    assert!(hex.len() == 64);
+    let mut out = ZERO;
+    let mut i = 0;
+    while i < 64 {
+        let digit = match hex[i] {
+            b'0'..=b'9' => hex[i] - b'0',
+            b'a'..=b'f' => hex[i] - b'a' + 10,
+            b'A'..=b'F' => hex[i] - b'A' + 10,
+            _ => panic!("invalid hex digit"),
+        };
+        let nibble = 63 - i;
+        out[nibble / 8] |= (digit as u32) << (4 * (nibble % 8));
+        i += 1;
+    }
+    out
+}
+
+pub(crate) fn from_be_bytes(bytes: &[u8; 32]) -> U256 {
+    let mut out = ZERO;
+    for (word, chunk) in out.iter_mut().rev().zip(bytes.chunks_exact(4)) {
+        *word = u32::from_be_bytes(chunk.try_into().unwrap());
+    }
+    out
+}
+
+pub(crate) fn from_le_bytes(bytes: &[u8; 32]) -> U256 {
+    let mut out = ZERO;
+    for (word, chunk) in out.iter_mut().zip(bytes.chunks_exact(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+    out
+}
+
+pub(crate) fn to_be_bytes(value: &U256) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    for (chunk, word) in out.chunks_exact_mut(4).zip(value.iter().rev()) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    out
+}
+
+pub(crate) fn to_le_bytes(value: &U256) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    for (chunk, word) in out.chunks_exact_mut(4).zip(value) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    out
+}
+
+pub(crate) fn is_zero(a: &U256) -> bool {
+    a == &ZERO
+}
+
+pub(crate) fn bit(a: &U256, idx: usize) -> bool {
+    (a[idx / 32] >> (idx % 32)) & 1 == 1
+}
+
+/// Whether `a < b`.
+pub(crate) fn lt(a: &U256, b: &U256) -> bool {
+    for (a, b) in a.iter().zip(b).rev() {
+        if a != b {
+            return a < b;
+        }
+    }
+    false
+}
+
+fn adc(a: &U256, b: &U256) -> (U256, bool) {
+    let mut out = ZERO;
+    let mut carry = false;
+    for i in 0..out.len() {
+        let (sum, c1) = a[i].overflowing_add(b[i]);
+        let (sum, c2) = sum.overflowing_add(carry as u32);
+        out[i] = sum;
+        carry = c1 || c2;
+    }
+    (out, carry)
+}
+
+fn sbb(a: &U256, b: &U256) -> (U256, bool) {
+    let mut out = ZERO;
+    let mut borrow = false;
+    for i in 0..out.len() {
+        let (diff, b1) = a[i].overflowing_sub(b[i]);
+        let (diff, b2) = diff.overflowing_sub(borrow as u32);
+        out[i] = diff;
+        borrow = b1 || b2;
+    }
+    (out, borrow)
+}
+
+/// Compute `a + b mod m`, for `a, b < m`.
+pub(crate) fn add_mod(a: &U256, b: &U256, m: &U256) -> U256 {
+    let (sum, carry) = adc(a, b);
+    if carry || !lt(&sum, m) {
+        sbb(&sum, m).0
+    } else {
+        sum
+    }
+}
+
+/// Compute `a - b mod m`, for `a, b < m`.
+pub(crate) fn sub_mod(a: &U256, b: &U256, m: &U256) -> U256 {
+    let (diff, borrow) = sbb(a, b);
+    if borrow {
+        adc(&diff, m).0
+    } else {
+        diff
+    }
+}
+
+/// Compute `a * b mod m` on the accelerator.
+pub(crate) fn mul_mod(a: &U256, b: &U256, m: &U256) -> U256 {
+    let mut out = ZERO;
+    // SAFETY: All arguments are aligned, valid references.
+    unsafe { sys_bigint(&mut out, bigint::OP_MULTIPLY, a, b, m) };
+    out
+}
+
+/// Reduce any 256-bit `a` modulo `m`.
+pub(crate) fn reduce(a: &U256, m: &U256) -> U256 {
+    mul_mod(a, &ONE, m)
+}
+
+/// Compute `base ^ exp mod m`.
+pub(crate) fn pow_mod(base: &U256, exp: &U256, m: &U256) -> U256 {
+    let mut out = reduce(&ONE, m);
+    for idx in (0..256).rev() {
+        out = mul_mod(&out, &out, m);
+        if bit(exp, idx) {
+            out = mul_mod(&out, base, m);
+        }
+    }
+    out
+}
+
+/// Compute the inverse of `a` modulo `m`, or `None` if it has none.
+///
+/// The inverse is computed by the host and checked here with a single
+/// multiplication, which is far cheaper than computing it in the guest.
+pub(crate) fn inv_mod(a: &U256, m: &U256) -> Option<U256> {
+    let mut to_host = [0u32; 2 * bigint::WIDTH_WORDS];
+    to_host[..bigint::WIDTH_WORDS].copy_from_slice(a);
+    to_host[bigint::WIDTH_WORDS..].copy_from_slice(m);
+    let mut inv = ZERO;
+    // SAFETY: Both buffers are valid for the lengths given.
+    unsafe {
+        syscall_2(
+            SYS_MODINV,
+            inv.as_mut_ptr(),
+            inv.len(),
+            to_host.as_ptr() as u32,
+            core::mem::size_of_val(&to_host) as u32,
+        )
+    };
+    (mul_mod(a, &inv, m) == reduce(&ONE, m)).then_some(inv)
+}
@@ -0,0 +1,200 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Ed25519 signature verification, as specified in RFC 8032.
+
+use super::{
+    bigint::{
+        add_mod, bit, from_hex, from_le_bytes, inv_mod, lt, mul_mod, pow_mod, reduce, sub_mod,
+        to_le_bytes, ONE, U256, ZERO,
+    },
+    SignatureError,
+};
+use crate::guest::sha512::Sha512;
+
+// The field modulus, 2^255 - 19.
+const P: U256 = from_hex("7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffed");
+
+// The order of the prime-order subgroup.
+const L: U256 = from_hex("1000000000000000000000000000000014def9dea2f79cd65812631a5cf5d3ed");
+
+// 2^256 mod L, for reducing 512-bit hashes.
+const R256: U256 = from_hex("0ffffffffffffffffffffffffffffffec6ef5bf4737dcf70d6ec31748d98951d");
+
+// The curve constant d, and 2d.
+const D: U256 = from_hex("52036cee2b6ffe738cc740797779e89800700a4d4141d8ab75eb4dca135978a3");
+const D2: U256 = from_hex("2406d9dc56dffce7198e80f2eef3d13000e0149a8283b156ebd69b9426b2f159");
+
+// A square root of -1.
+const SQRT_M1: U256 = from_hex("2b8324804fc1df0b2b4d00993dfbd7a72f431806ad2fe478c4ee1b274a0ea0b0");
+
+// (P - 5) / 8, for computing square roots.
+const SQRT_EXP: U256 = from_hex("0ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffd");
+
+const BASE: Point = Point {
+    x: from_hex("216936d3cd6e53fec0a4e231fdd6dc5c692cc7609525a7b2c9562d608f25d51a"),
+    y: from_hex("6666666666666666666666666666666666666666666666666666666666666658"),
+    z: ONE,
+    t: from_hex("67875f0fd78b766566ea4e8e64abe37d20f09f80775152f56dde8ab3a5b7dda3"),
+};
+
+fn mul(a: &U256, b: &U256) -> U256 {
+    mul_mod(a, b, &P)
+}
+
+fn add(a: &U256, b: &U256) -> U256 {
+    add_mod(a, b, &P)
+}
+
+fn sub(a: &U256, b: &U256) -> U256 {
+    sub_mod(a, b, &P)
+}
+
+// A point in extended twisted Edwards coordinates, with x = X/Z, y = Y/Z and
+// xy = T/Z.
+#[derive(Clone, Copy)]
+struct Point {
+    x: U256,
+    y: U256,
+    z: U256,
+    t: U256,
+}
+
+impl Point {
+    const IDENTITY: Self = Self {
+        x: ZERO,
+        y: ONE,
+        z: ONE,
+        t: ZERO,
+    };
+
+    // Decode a point as described in RFC 8032, section 5.1.3.
+    fn decompress(bytes: &[u8; 32]) -> Option<Self> {
+        let sign = bytes[31] >> 7 == 1;
+        let mut y = *bytes;
+        y[31] &= 0x7f;
+        let y = from_le_bytes(&y);
+        if !lt(&y, &P) {
+            return None;
+        }
+
+        let y2 = mul(&y, &y);
+        let u = sub(&y2, &ONE);
+        let v = add(&mul(&D, &y2), &ONE);
+        let v3 = mul(&mul(&v, &v), &v);
+        let v7 = mul(&mul(&v3, &v3), &v);
+        let mut x = mul(&mul(&u, &v3), &pow_mod(&mul(&u, &v7), &SQRT_EXP, &P));
+
+        let vx2 = mul(&v, &mul(&x, &x));
+        if vx2 != u {
+            if vx2 != sub(&ZERO, &u) {
+                return None;
+            }
+            x = mul(&x, &SQRT_M1);
+        }
+        if x == ZERO && sign {
+            return None;
+        }
+        if bit(&x, 0) != sign {
+            x = sub(&ZERO, &x);
+        }
+
+        Some(Self {
+            x,
+            y,
+            z: ONE,
+            t: mul(&x, &y),
+        })
+    }
+
+    fn compress(&self) -> Option<[u8; 32]> {
+        let z_inv = inv_mod(&self.z, &P)?;
+        let x = mul(&self.x, &z_inv);
+        let y = mul(&self.y, &z_inv);
+        let mut bytes = to_le_bytes(&y);
+        bytes[31] |= (bit(&x, 0) as u8) << 7;
+        Some(bytes)
+    }
+
+    fn neg(&self) -> Self {
+        Self {
+            x: sub(&ZERO, &self.x),
+            y: self.y,
+            z: self.z,
+            t: sub(&ZERO, &self.t),
+        }
+    }
+
+    // https://hyperelliptic.org/EFD/g1p/auto-twisted-extended-1.html#addition-add-2008-hwcd-3
+    //
+    // The formula is complete, so it also serves for doubling.
+    fn add(&self, other: &Self) -> Self {
+        let a = mul(&sub(&self.y, &self.x), &sub(&other.y, &other.x));
+        let b = mul(&add(&self.y, &self.x), &add(&other.y, &other.x));
+        let c = mul(&mul(&self.t, &D2), &other.t);
+        let d = mul(&self.z, &other.z);
+        let d = add(&d, &d);
+        let e = sub(&b, &a);
+        let f = sub(&d, &c);
+        let g = add(&d, &c);
+        let h = add(&b, &a);
+        Self {
+            x: mul(&e, &f),
+            y: mul(&g, &h),
+            z: mul(&f, &g),
+            t: mul(&e, &h),
+        }
+    }
+}
+
+/// Verify an Ed25519 signature, as specified in RFC 8032.
+///
+/// `sig` is the 64-byte signature, `pk` the 32-byte public key, and `msg` the
+/// signed message. As in most implementations, the check is cofactorless:
+/// `[S]B - [k]A` must encode to the `R` half of the signature.
+pub fn verify_ed25519(sig: &[u8; 64], pk: &[u8; 32], msg: &[u8]) -> Result<(), SignatureError> {
+    let a = Point::decompress(pk).ok_or(SignatureError::InvalidPublicKey)?;
+    let s = from_le_bytes(sig[32..].try_into().unwrap());
+    if !lt(&s, &L) {
+        return Err(SignatureError::InvalidSignature);
+    }
+
+    let mut hasher = Sha512::new();
+    hasher.update(&sig[..32]);
+    hasher.update(pk);
+    hasher.update(msg);
+    let hash = hasher.finalize();
+    let lo = from_le_bytes(hash[..32].try_into().unwrap());
+    let hi = from_le_bytes(hash[32..].try_into().unwrap());
+    let k = add_mod(&reduce(&lo, &L), &mul_mod(&hi, &R256, &L), &L);
+
+    // Compute [S]B - [k]A with a single pass over the bits of both.
+    let neg_a = a.neg();
+    let b_minus_a = BASE.add(&neg_a);
+    let mut point = Point::IDENTITY;
+    for idx in (0..253).rev() {
+        point = point.add(&point);
+        match (bit(&s, idx), bit(&k, idx)) {
+            (true, true) => point = point.add(&b_minus_a),
+            (true, false) => point = point.add(&BASE),
+            (false, true) => point = point.add(&neg_a),
+            (false, false) => {}
+        }
+    }
+
+    match point.compress() == Some(sig[..32].try_into().unwrap()) {
+        true => Ok(()),
+        false => Err(SignatureError::Mismatch),
+    }
+}
@@ -0,0 +1,67 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Signature verification on the bigint accelerator.
+//!
+//! Verifying a signature with a pure-Rust crypto crate in the guest costs
+//! millions of cycles, almost all of them in field multiplication. The
+//! functions here perform each 256-bit modular multiplication with a single
+//! call to the bigint accelerator, and obtain modular inverses from the host,
+//! checking each one with a single multiplication. RSA works on larger
+//! numbers, built from products of 128-bit limbs on the accelerator.
+//!
+//! ```no_run
+//! use risc0_zkvm::guest::{env, sig};
+//!
+//! let (signature, public_key, message): ([u8; 64], [u8; 32], Vec<u8>) = env::read();
+//! sig::verify_ed25519(&signature, &public_key, &message).unwrap();
+//! ```
+
+pub(crate) mod bigint;
+mod ed25519;
+mod rsa;
+mod secp256k1;
+
+use core::fmt;
+
+pub use self::{
+    ed25519::verify_ed25519,
+    rsa::{rsa_modpow, verify_rsa_pkcs1v15_sha256},
+    secp256k1::verify_secp256k1,
+};
+
+/// The reason a signature failed to verify.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SignatureError {
+    /// The public key is not a valid encoding of a point on the curve.
+    InvalidPublicKey,
+    /// The signature is malformed, such as having a scalar out of range.
+    InvalidSignature,
+    /// The signature is well-formed but does not match the message and key.
+    Mismatch,
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPublicKey => write!(f, "invalid public key"),
+            Self::InvalidSignature => write!(f, "invalid signature"),
+            Self::Mismatch => write!(f, "signature does not match"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SignatureError {}
@@ -0,0 +1,301 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! RSA modular exponentiation for 2048, 3072, and 4096-bit moduli.
+//!
+//! Numbers are split into 128-bit limbs, so that the full product of two limbs
+//! fits in a single 256-bit multiplication on the bigint accelerator.
+//! Exponentiation uses Montgomery multiplication. Converting into Montgomery
+//! form needs `R^2 mod n`, which is costly to compute in the guest, so it is
+//! provided by the host and checked with a single Montgomery multiplication.
+
+use alloc::{vec, vec::Vec};
+
+use risc0_zkvm_platform::syscall::{bigint, nr::SYS_MONT_R2, sys_bigint, syscall_2};
+
+use super::{bigint::U256, SignatureError};
+use crate::sha::{Impl, Sha256};
+
+/// The supported modulus sizes, in bytes.
+const MODULUS_BYTES: [usize; 3] = [256, 384, 512];
+
+const LIMB_BYTES: usize = 16;
+
+// The DER encoding of the SHA-256 AlgorithmIdentifier, which precedes the hash
+// in a PKCS #1 v1.5 signature.
+const SHA256_DIGEST_INFO: [u8; 19] = [
+    0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+    0x00, 0x04, 0x20,
+];
+
+fn widen(a: u128) -> U256 {
+    let mut out = [0u32; bigint::WIDTH_WORDS];
+    for (i, word) in out[..4].iter_mut().enumerate() {
+        *word = (a >> (32 * i)) as u32;
+    }
+    out
+}
+
+/// Compute the full product of two limbs on the accelerator, as `(lo, hi)`.
+fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+    let (x, y) = (widen(a), widen(b));
+    let mut out = [0u32; bigint::WIDTH_WORDS];
+    // SAFETY: All arguments are aligned, valid references. A zero modulus
+    // gives the plain product, which cannot overflow 256 bits.
+    unsafe { sys_bigint(&mut out, bigint::OP_MULTIPLY, &x, &y, &[0; 8]) };
+    let limb = |words: &[u32]| {
+        words
+            .iter()
+            .rev()
+            .fold(0u128, |acc, &word| (acc << 32) | word as u128)
+    };
+    (limb(&out[..4]), limb(&out[4..]))
+}
+
+/// Whether `a >= b`, for numbers of the same length.
+fn ge(a: &[u128], b: &[u128]) -> bool {
+    for (a, b) in a.iter().zip(b).rev() {
+        if a != b {
+            return a > b;
+        }
+    }
+    true
+}
+
+/// Subtract `b` from `a` in place, returning the borrow.
+fn sub_assign(a: &mut [u128], b: &[u128]) -> bool {
+    let mut borrow = false;
+    for (a, b) in a.iter_mut().zip(b) {
+        let (diff, b1) = a.overflowing_sub(*b);
+        let (diff, b2) = diff.overflowing_sub(borrow as u128);
+        *a = diff;
+        borrow = b1 || b2;
+    }
+    borrow
+}
+
+fn from_be_bytes(bytes: &[u8]) -> Vec<u128> {
+    bytes
+        .rchunks(LIMB_BYTES)
+        .map(|chunk| {
+            let mut limb = [0u8; LIMB_BYTES];
+            limb[LIMB_BYTES - chunk.len()..].copy_from_slice(chunk);
+            u128::from_be_bytes(limb)
+        })
+        .collect()
+}
+
+fn to_be_bytes(limbs: &[u128]) -> Vec<u8> {
+    limbs
+        .iter()
+        .rev()
+        .flat_map(|limb| limb.to_be_bytes())
+        .collect()
+}
+
+/// An odd modulus with its top bit set, prepared for Montgomery
+/// multiplication with `R = 2^(128 * k)` for `k` limbs.
+struct Modulus {
+    n: Vec<u128>,
+    // -n^-1 mod 2^128.
+    n_prime: u128,
+    // R^2 mod n.
+    r2: Vec<u128>,
+    // R mod n, which is 1 in Montgomery form.
+    one: Vec<u128>,
+}
+
+impl Modulus {
+    fn new(modulus: &[u8]) -> Option<Self> {
+        if !MODULUS_BYTES.contains(&modulus.len()) || modulus[0] < 0x80 {
+            return None;
+        }
+        let n = from_be_bytes(modulus);
+        if n[0] & 1 == 0 {
+            return None;
+        }
+
+        // Newton's iteration doubles the number of correct low bits each step,
+        // starting from 3 bits for any odd n.
+        let mut inv = n[0];
+        for _ in 0..6 {
+            inv = inv.wrapping_mul(2u128.wrapping_sub(n[0].wrapping_mul(inv)));
+        }
+
+        // Since n > R / 2, R mod n is R - n.
+        let mut one = vec![0u128; n.len()];
+        sub_assign(&mut one, &n);
+
+        let mut this = Self {
+            n,
+            n_prime: inv.wrapping_neg(),
+            r2: Vec::new(),
+            one,
+        };
+
+        // Check the host's R^2 mod n: it is correct exactly when it is reduced
+        // and its Montgomery reduction is R mod n.
+        let r2 = r2_from_host(modulus);
+        let unit = this.unit();
+        if ge(&r2, &this.n) || this.mul(&r2, &unit) != this.one {
+            return None;
+        }
+        this.r2 = r2;
+        Some(this)
+    }
+
+    fn unit(&self) -> Vec<u128> {
+        let mut unit = vec![0u128; self.n.len()];
+        unit[0] = 1;
+        unit
+    }
+
+    /// Compute `a * b * R^-1 mod n`, for `a < R` and `b < n`.
+    fn mul(&self, a: &[u128], b: &[u128]) -> Vec<u128> {
+        let k = self.n.len();
+        let mut t = vec![0u128; k + 2];
+        for &a_i in a {
+            // t += a_i * b
+            let mut carry = 0u128;
+            for j in 0..k {
+                let (lo, hi) = mul_wide(a_i, b[j]);
+                let (sum, c1) = t[j].overflowing_add(lo);
+                let (sum, c2) = sum.overflowing_add(carry);
+                t[j] = sum;
+                carry = hi + c1 as u128 + c2 as u128;
+            }
+            let (sum, c) = t[k].overflowing_add(carry);
+            t[k] = sum;
+            t[k + 1] += c as u128;
+
+            // t = (t + m * n) / 2^128, choosing m so the division is exact.
+            let m = mul_wide(t[0], self.n_prime).0;
+            let (lo, hi) = mul_wide(m, self.n[0]);
+            let (_, c1) = t[0].overflowing_add(lo);
+            let mut carry = hi + c1 as u128;
+            for j in 1..k {
+                let (lo, hi) = mul_wide(m, self.n[j]);
+                let (sum, c1) = t[j].overflowing_add(lo);
+                let (sum, c2) = sum.overflowing_add(carry);
+                t[j - 1] = sum;
+                carry = hi + c1 as u128 + c2 as u128;
+            }
+            let (sum, c) = t[k].overflowing_add(carry);
+            t[k - 1] = sum;
+            t[k] = t[k + 1] + c as u128;
+            t[k + 1] = 0;
+        }
+
+        // The result is less than 2n, so at most one subtraction reduces it.
+        let overflow = t[k] != 0;
+        t.truncate(k);
+        if overflow || ge(&t, &self.n) {
+            sub_assign(&mut t, &self.n);
+        }
+        t
+    }
+
+    fn pow(&self, base: &[u128], exp: &[u8]) -> Vec<u128> {
+        let base = self.mul(base, &self.r2);
+        let mut acc = self.one.clone();
+        for byte in exp {
+            for idx in (0..8).rev() {
+                acc = self.mul(&acc, &acc);
+                if (byte >> idx) & 1 == 1 {
+                    acc = self.mul(&acc, &base);
+                }
+            }
+        }
+        self.mul(&acc, &self.unit())
+    }
+}
+
+/// Get `R^2 mod n` from the host, for the big-endian modulus `n`.
+fn r2_from_host(modulus: &[u8]) -> Vec<u128> {
+    let n_words: Vec<u32> = modulus
+        .rchunks(4)
+        .map(|chunk| u32::from_be_bytes(chunk.try_into().unwrap()))
+        .collect();
+    let mut r2 = vec![0u32; n_words.len()];
+    // SAFETY: Both buffers are valid for the lengths given.
+    unsafe {
+        syscall_2(
+            SYS_MONT_R2,
+            r2.as_mut_ptr(),
+            r2.len(),
+            n_words.as_ptr() as u32,
+            core::mem::size_of_val(n_words.as_slice()) as u32,
+        )
+    };
+    r2.chunks_exact(4)
+        .map(|words| {
+            words
+                .iter()
+                .rev()
+                .fold(0u128, |acc, &word| (acc << 32) | word as u128)
+        })
+        .collect()
+}
+
+/// Compute `base ^ exp mod modulus`, with all numbers big-endian.
+///
+/// The modulus must be 2048, 3072, or 4096 bits, odd, and have its top bit
+/// set, as RSA moduli do; `base` must be less than it. Returns
+/// [SignatureError::InvalidPublicKey] for an unsupported modulus and
+/// [SignatureError::InvalidSignature] for a base out of range. The result has
+/// the same length as the modulus.
+pub fn rsa_modpow(base: &[u8], exp: &[u8], modulus: &[u8]) -> Result<Vec<u8>, SignatureError> {
+    let modulus = Modulus::new(modulus).ok_or(SignatureError::InvalidPublicKey)?;
+    if base.len() > modulus.n.len() * LIMB_BYTES {
+        return Err(SignatureError::InvalidSignature);
+    }
+    let mut base = from_be_bytes(base);
+    base.resize(modulus.n.len(), 0);
+    if ge(&base, &modulus.n) {
+        return Err(SignatureError::InvalidSignature);
+    }
+    Ok(to_be_bytes(&modulus.pow(&base, exp)))
+}
+
+/// Verify an RSASSA-PKCS1-v1_5 signature with SHA-256 over `msg`.
+///
+/// This is the `RS256` algorithm of JSON Web Tokens. The public key is given
+/// by its big-endian modulus `n` and exponent `e`; see [rsa_modpow] for the
+/// supported moduli.
+pub fn verify_rsa_pkcs1v15_sha256(
+    sig: &[u8],
+    n: &[u8],
+    e: &[u8],
+    msg: &[u8],
+) -> Result<(), SignatureError> {
+    if sig.len() != n.len() {
+        return Err(SignatureError::InvalidSignature);
+    }
+    let em = rsa_modpow(sig, e, n)?;
+
+    // EM = 0x00 || 0x01 || 0xff.. || 0x00 || DigestInfo || H(msg)
+    let hash = Impl::hash_bytes(msg);
+    let mut expected = vec![0xffu8; em.len()];
+    expected[0] = 0x00;
+    expected[1] = 0x01;
+    let suffix = em.len() - SHA256_DIGEST_INFO.len() - hash.as_bytes().len();
+    expected[suffix - 1] = 0x00;
+    expected[suffix..suffix + SHA256_DIGEST_INFO.len()].copy_from_slice(&SHA256_DIGEST_INFO);
+    expected[suffix + SHA256_DIGEST_INFO.len()..].copy_from_slice(hash.as_bytes());
+
+    match em == expected {
+        true => Ok(()),
+        false => Err(SignatureError::Mismatch),
+    }
+}
@@ -0,0 +1,228 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! ECDSA signature verification over secp256k1.
+
+use super::{
+    bigint::{
+        add_mod, bit, from_be_bytes, from_hex, inv_mod, is_zero, lt, mul_mod, pow_mod, reduce,
+        sub_mod, ONE, U256, ZERO,
+    },
+    SignatureError,
+};
+
+// The field modulus.
+const P: U256 = from_hex("fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f");
+
+// The order of the group.
+const N: U256 = from_hex("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141");
+
+// (P + 1) / 4, for computing square roots since P = 3 mod 4.
+const SQRT_EXP: U256 = from_hex("3fffffffffffffffffffffffffffffffffffffffffffffffffffffffbfffff0c");
+
+const B: U256 = from_hex("0000000000000000000000000000000000000000000000000000000000000007");
+
+const G: Affine = Affine {
+    x: from_hex("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"),
+    y: from_hex("483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8"),
+};
+
+fn mul(a: &U256, b: &U256) -> U256 {
+    mul_mod(a, b, &P)
+}
+
+fn add(a: &U256, b: &U256) -> U256 {
+    add_mod(a, b, &P)
+}
+
+fn sub(a: &U256, b: &U256) -> U256 {
+    sub_mod(a, b, &P)
+}
+
+#[derive(Clone, Copy)]
+struct Affine {
+    x: U256,
+    y: U256,
+}
+
+impl Affine {
+    // Decode a SEC1 public key, in compressed or uncompressed form.
+    fn from_sec1(bytes: &[u8]) -> Option<Self> {
+        let x = from_be_bytes(bytes.get(1..33)?.try_into().unwrap());
+        if !lt(&x, &P) {
+            return None;
+        }
+        let rhs = add(&mul(&mul(&x, &x), &x), &B);
+        let y = match (bytes[0], bytes.len()) {
+            (0x04, 65) => {
+                let y = from_be_bytes(bytes[33..].try_into().unwrap());
+                if !lt(&y, &P) {
+                    return None;
+                }
+                y
+            }
+            (0x02 | 0x03, 33) => {
+                let y = pow_mod(&rhs, &SQRT_EXP, &P);
+                if bit(&y, 0) == (bytes[0] == 0x03) {
+                    y
+                } else {
+                    sub(&ZERO, &y)
+                }
+            }
+            _ => return None,
+        };
+        (mul(&y, &y) == rhs).then_some(Self { x, y })
+    }
+}
+
+// A point in Jacobian coordinates, where Z = 0 is the point at infinity.
+#[derive(Clone, Copy)]
+struct Jacobian {
+    x: U256,
+    y: U256,
+    z: U256,
+}
+
+impl Jacobian {
+    const INFINITY: Self = Self {
+        x: ZERO,
+        y: ZERO,
+        z: ZERO,
+    };
+
+    fn from_affine(point: &Affine) -> Self {
+        Self {
+            x: point.x,
+            y: point.y,
+            z: ONE,
+        }
+    }
+
+    fn is_infinity(&self) -> bool {
+        is_zero(&self.z)
+    }
+
+    // https://hyperelliptic.org/EFD/g1p/auto-shortw-jacobian-0.html#doubling-dbl-2009-l
+    fn double(&self) -> Self {
+        if self.is_infinity() || is_zero(&self.y) {
+            return Self::INFINITY;
+        }
+        let a = mul(&self.x, &self.x);
+        let b = mul(&self.y, &self.y);
+        let c = mul(&b, &b);
+        let x_plus_b = add(&self.x, &b);
+        let d = sub(&sub(&mul(&x_plus_b, &x_plus_b), &a), &c);
+        let d = add(&d, &d);
+        let e = add(&add(&a, &a), &a);
+        let f = mul(&e, &e);
+        let x = sub(&f, &add(&d, &d));
+        let c8 = add(&c, &c);
+        let c8 = add(&c8, &c8);
+        let c8 = add(&c8, &c8);
+        let y = sub(&mul(&e, &sub(&d, &x)), &c8);
+        let z = mul(&self.y, &self.z);
+        let z = add(&z, &z);
+        Self { x, y, z }
+    }
+
+    // https://hyperelliptic.org/EFD/g1p/auto-shortw-jacobian-0.html#addition-add-2007-bl
+    fn add(&self, other: &Self) -> Self {
+        if self.is_infinity() {
+            return *other;
+        }
+        if other.is_infinity() {
+            return *self;
+        }
+        let z1z1 = mul(&self.z, &self.z);
+        let z2z2 = mul(&other.z, &other.z);
+        let u1 = mul(&self.x, &z2z2);
+        let u2 = mul(&other.x, &z1z1);
+        let s1 = mul(&mul(&self.y, &other.z), &z2z2);
+        let s2 = mul(&mul(&other.y, &self.z), &z1z1);
+        let h = sub(&u2, &u1);
+        let r = sub(&s2, &s1);
+        if is_zero(&h) {
+            return match is_zero(&r) {
+                true => self.double(),
+                false => Self::INFINITY,
+            };
+        }
+        let h2 = add(&h, &h);
+        let i = mul(&h2, &h2);
+        let j = mul(&h, &i);
+        let r = add(&r, &r);
+        let v = mul(&u1, &i);
+        let x = sub(&sub(&mul(&r, &r), &j), &add(&v, &v));
+        let s1j = mul(&s1, &j);
+        let y = sub(&mul(&r, &sub(&v, &x)), &add(&s1j, &s1j));
+        let z1_plus_z2 = add(&self.z, &other.z);
+        let z = mul(&sub(&sub(&mul(&z1_plus_z2, &z1_plus_z2), &z1z1), &z2z2), &h);
+        Self { x, y, z }
+    }
+
+    fn to_affine(self) -> Option<Affine> {
+        let z_inv = inv_mod(&self.z, &P)?;
+        let z_inv2 = mul(&z_inv, &z_inv);
+        Some(Affine {
+            x: mul(&self.x, &z_inv2),
+            y: mul(&mul(&self.y, &z_inv2), &z_inv),
+        })
+    }
+}
+
+/// Verify an ECDSA signature over secp256k1.
+///
+/// `sig` is the signature as big-endian `r` and `s`, `pk` is a SEC1-encoded
+/// public key in compressed or uncompressed form, and `msg_hash` is the
+/// 32-byte hash of the signed message, such as its Keccak-256 hash for
+/// Ethereum. Signatures with a high `s` value are accepted; callers that
+/// require low-`s` normalization must check it themselves.
+pub fn verify_secp256k1(
+    sig: &[u8; 64],
+    pk: &[u8],
+    msg_hash: &[u8; 32],
+) -> Result<(), SignatureError> {
+    let q = Affine::from_sec1(pk).ok_or(SignatureError::InvalidPublicKey)?;
+    let r = from_be_bytes(sig[..32].try_into().unwrap());
+    let s = from_be_bytes(sig[32..].try_into().unwrap());
+    if is_zero(&r) || is_zero(&s) || !lt(&r, &N) || !lt(&s, &N) {
+        return Err(SignatureError::InvalidSignature);
+    }
+
+    let z = reduce(&from_be_bytes(msg_hash), &N);
+    let w = inv_mod(&s, &N).ok_or(SignatureError::InvalidSignature)?;
+    let u1 = mul_mod(&z, &w, &N);
+    let u2 = mul_mod(&r, &w, &N);
+
+    // Compute u1 * G + u2 * Q with a single pass over the bits of both.
+    let g = Jacobian::from_affine(&G);
+    let q = Jacobian::from_affine(&q);
+    let g_plus_q = g.add(&q);
+    let mut point = Jacobian::INFINITY;
+    for idx in (0..256).rev() {
+        point = point.double();
+        match (bit(&u1, idx), bit(&u2, idx)) {
+            (true, true) => point = point.add(&g_plus_q),
+            (true, false) => point = point.add(&g),
+            (false, true) => point = point.add(&q),
+            (false, false) => {}
+        }
+    }
+
+    let point = point.to_affine().ok_or(SignatureError::Mismatch)?;
+    match reduce(&point.x, &N) == r {
+        true => Ok(()),
+        false => Err(SignatureError::Mismatch),
+    }
+}
@@ -0,0 +1,519 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Cooperative green threads for the zkVM guest.
+//!
+//! The zkVM executes on a single core and has no preemption, so this module
+//! provides threads that are scheduled cooperatively: a thread runs until it
+//! calls [yield_now], [park] or [JoinHandle::join], at which point the next
+//! runnable thread is resumed in first-in, first-out order. Scheduling is
+//! therefore fully deterministic, which is required for the execution to be
+//! provable.
+//!
+//! The API mirrors [`std::thread`], so code written against it can usually be
+//! ported by swapping the import:
+//!
+//! ```ignore
+//! use risc0_zkvm::guest::thread;
+//!
+//! let handle = thread::spawn(|| {
+//!     thread::yield_now();
+//!     42
+//! });
+//! assert_eq!(handle.join().unwrap(), 42);
+//! ```
+//!
+//! Thread-local storage is declared with [crate::green_thread_local], since the
+//! standard `thread_local!` is shared by every green thread in the guest.
+//!
+//! When the main thread returns, the guest halts without waiting for other
+//! threads to finish. Locks from `std::sync` are not aware of green threads,
+//! so they must not be held across a yield point.
+//!
+//! [`std::thread`]: https://doc.rust-lang.org/std/thread/index.html
+
+use alloc::{
+    alloc::{alloc, dealloc, Layout},
+    boxed::Box,
+    collections::{BTreeMap, VecDeque},
+    rc::Rc,
+    string::String,
+};
+use core::{
+    any::Any,
+    cell::{Cell, RefCell, UnsafeCell},
+    fmt,
+    num::NonZeroU64,
+};
+
+/// The stack size of a spawned thread, unless set with [Builder::stack_size].
+pub const DEFAULT_STACK_SIZE: usize = 64 * 1024;
+
+const STACK_ALIGN: usize = 16;
+
+// The smallest stack a thread is given, enough for thread_start and the frame
+// of a small closure. Smaller stacks would be written past their end.
+const MIN_STACK_SIZE: usize = 4 * 1024;
+
+/// The result of [JoinHandle::join].
+///
+/// Panics in the guest abort execution, so joining a thread always succeeds;
+/// the error type is kept for compatibility with `std::thread::Result`.
+pub type Result<T> = core::result::Result<T, Box<dyn Any + Send + 'static>>;
+
+// Callee-saved registers of a suspended thread, in the order used by
+// _risc0_thread_switch.
+#[repr(C)]
+#[derive(Default)]
+struct Context {
+    ra: u32,
+    sp: u32,
+    s: [u32; 12],
+}
+
+core::arch::global_asm!(
+    r#"
+.section .text._risc0_thread_switch;
+.globl _risc0_thread_switch;
+_risc0_thread_switch:
+    sw ra, 0(a0)
+    sw sp, 4(a0)
+    sw s0, 8(a0)
+    sw s1, 12(a0)
+    sw s2, 16(a0)
+    sw s3, 20(a0)
+    sw s4, 24(a0)
+    sw s5, 28(a0)
+    sw s6, 32(a0)
+    sw s7, 36(a0)
+    sw s8, 40(a0)
+    sw s9, 44(a0)
+    sw s10, 48(a0)
+    sw s11, 52(a0)
+    lw ra, 0(a1)
+    lw sp, 4(a1)
+    lw s0, 8(a1)
+    lw s1, 12(a1)
+    lw s2, 16(a1)
+    lw s3, 20(a1)
+    lw s4, 24(a1)
+    lw s5, 28(a1)
+    lw s6, 32(a1)
+    lw s7, 36(a1)
+    lw s8, 40(a1)
+    lw s9, 44(a1)
+    lw s10, 48(a1)
+    lw s11, 52(a1)
+    ret
+"#
+);
+
+extern "C" {
+    // Save the current registers to `from` and resume the thread in `to`.
+    fn _risc0_thread_switch(from: *mut Context, to: *const Context);
+}
+
+struct Stack {
+    ptr: *mut u8,
+    layout: Layout,
+}
+
+impl Stack {
+    fn new(size: usize) -> Self {
+        let size = size.max(MIN_STACK_SIZE).next_multiple_of(STACK_ALIGN);
+        let layout = Layout::from_size_align(size, STACK_ALIGN).unwrap();
+        // SAFETY: The layout has a non-zero size and a valid alignment.
+        let ptr = unsafe { alloc(layout) };
+        if ptr.is_null() {
+            alloc::alloc::handle_alloc_error(layout);
+        }
+        Self { ptr, layout }
+    }
+
+    fn top(&self) -> u32 {
+        self.ptr as u32 + self.layout.size() as u32
+    }
+}
+
+impl Drop for Stack {
+    fn drop(&mut self) {
+        // SAFETY: The stack was allocated in Stack::new with this layout.
+        unsafe { dealloc(self.ptr, self.layout) }
+    }
+}
+
+struct Inner {
+    id: ThreadId,
+    name: Option<String>,
+    context: UnsafeCell<Context>,
+    // None for the main thread, which runs on the stack set up by _start.
+    stack: Option<Stack>,
+    main: Cell<Option<Box<dyn FnOnce()>>>,
+    finished: Cell<bool>,
+    joiner: Cell<Option<Rc<Inner>>>,
+    parked: Cell<bool>,
+    token: Cell<bool>,
+    locals: RefCell<BTreeMap<usize, Box<dyn Any>>>,
+}
+
+impl Inner {
+    fn new(id: ThreadId, name: Option<String>, stack: Option<Stack>) -> Self {
+        Self {
+            id,
+            name,
+            context: UnsafeCell::new(Context::default()),
+            stack,
+            main: Cell::new(None),
+            finished: Cell::new(false),
+            joiner: Cell::new(None),
+            parked: Cell::new(false),
+            token: Cell::new(false),
+            locals: RefCell::new(BTreeMap::new()),
+        }
+    }
+}
+
+struct Scheduler {
+    current: Rc<Inner>,
+    ready: VecDeque<Rc<Inner>>,
+    // The thread last switched away from, kept alive until the switch is
+    // complete since its stack and context are in use until then.
+    previous: Option<Rc<Inner>>,
+    next_id: u64,
+}
+
+impl Scheduler {
+    fn thread_id(&mut self) -> ThreadId {
+        let id = ThreadId(NonZeroU64::new(self.next_id).unwrap());
+        self.next_id += 1;
+        id
+    }
+}
+
+struct Global(UnsafeCell<Option<Scheduler>>);
+
+// SAFETY: The guest is single-core and threads are only switched inside this
+// module, so the scheduler is never accessed concurrently.
+unsafe impl Sync for Global {}
+
+static SCHEDULER: Global = Global(UnsafeCell::new(None));
+
+fn scheduler() -> &'static mut Scheduler {
+    // SAFETY: See Global. Callers do not hold the reference across a switch.
+    let sched = unsafe { &mut *SCHEDULER.0.get() };
+    sched.get_or_insert_with(|| Scheduler {
+        current: Rc::new(Inner::new(
+            ThreadId(NonZeroU64::MIN),
+            Some("main".into()),
+            None,
+        )),
+        ready: VecDeque::new(),
+        previous: None,
+        next_id: 2,
+    })
+}
+
+// Switch to the next runnable thread. The current thread is put at the back of
+// the ready queue if `requeue` is set; otherwise something else must make it
+// runnable again.
+fn switch(requeue: bool) {
+    let sched = scheduler();
+    let Some(next) = sched.ready.pop_front() else {
+        if requeue {
+            return;
+        }
+        crate::guest::abort("deadlock: every guest thread is blocked");
+    };
+    let prev = core::mem::replace(&mut sched.current, next);
+    let from = prev.context.get();
+    let to = sched.current.context.get();
+    if requeue {
+        sched.ready.push_back(prev.clone());
+    }
+    sched.previous = Some(prev);
+
+    // SAFETY: Both contexts are kept alive by the scheduler, and `to` was
+    // either saved by a previous switch or set up by spawn.
+    unsafe { _risc0_thread_switch(from, to) };
+    scheduler().previous = None;
+}
+
+extern "C" fn thread_start() -> ! {
+    let main = {
+        let sched = scheduler();
+        sched.previous = None;
+        sched.current.main.take().unwrap()
+    };
+    main();
+
+    let current = scheduler().current.clone();
+    current.finished.set(true);
+    current.locals.take();
+    if let Some(joiner) = current.joiner.take() {
+        scheduler().ready.push_back(joiner);
+    }
+    drop(current);
+    switch(false);
+    unreachable!();
+}
+
+/// A unique identifier for a thread.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ThreadId(NonZeroU64);
+
+/// A handle to a thread.
+#[derive(Clone)]
+pub struct Thread {
+    inner: Rc<Inner>,
+}
+
+// SAFETY: See Global. Threads never run concurrently, so sharing a handle
+// between them is no different to sharing it within one.
+unsafe impl Send for Thread {}
+unsafe impl Sync for Thread {}
+
+impl Thread {
+    /// The unique identifier of this thread.
+    pub fn id(&self) -> ThreadId {
+        self.inner.id
+    }
+
+    /// The name of this thread, if it has one.
+    pub fn name(&self) -> Option<&str> {
+        self.inner.name.as_deref()
+    }
+
+    /// Make this thread runnable if it is blocked in [park], or otherwise
+    /// cause its next call to [park] to return immediately.
+    pub fn unpark(&self) {
+        self.inner.token.set(true);
+        if self.inner.parked.replace(false) {
+            scheduler().ready.push_back(self.inner.clone());
+        }
+    }
+}
+
+impl fmt::Debug for Thread {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Thread")
+            .field("id", &self.id())
+            .field("name", &self.name())
+            .finish_non_exhaustive()
+    }
+}
+
+/// An owned permission to join on a thread.
+pub struct JoinHandle<T> {
+    thread: Thread,
+    packet: Rc<Cell<Option<T>>>,
+}
+
+impl<T> JoinHandle<T> {
+    /// The handle of the underlying thread.
+    pub fn thread(&self) -> &Thread {
+        &self.thread
+    }
+
+    /// Whether the thread has finished running.
+    pub fn is_finished(&self) -> bool {
+        self.thread.inner.finished.get()
+    }
+
+    /// Wait for the thread to finish and return its result.
+    pub fn join(self) -> Result<T> {
+        if !self.is_finished() {
+            self.thread
+                .inner
+                .joiner
+                .set(Some(scheduler().current.clone()));
+            switch(false);
+        }
+        Ok(self.packet.take().unwrap())
+    }
+}
+
+impl<T> fmt::Debug for JoinHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JoinHandle").finish_non_exhaustive()
+    }
+}
+
+/// A factory for configuring new threads.
+#[derive(Debug, Default)]
+pub struct Builder {
+    name: Option<String>,
+    stack_size: Option<usize>,
+}
+
+impl Builder {
+    /// Construct a [Builder] with the default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Name the thread.
+    pub fn name(mut self, name: String) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// Set the size of the stack of the thread, in bytes.
+    ///
+    /// Sizes below 4 KiB are rounded up to 4 KiB.
+    pub fn stack_size(mut self, size: usize) -> Self {
+        self.stack_size = Some(size);
+        self
+    }
+
+    /// Spawn a new thread running `f`.
+    ///
+    /// The thread first runs when the current thread yields. This never fails;
+    /// it returns a [Result] for compatibility with `std::thread::Builder`.
+    pub fn spawn<F, T>(self, f: F) -> Result<JoinHandle<T>>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let stack = Stack::new(self.stack_size.unwrap_or(DEFAULT_STACK_SIZE));
+        let inner = Rc::new(Inner::new(scheduler().thread_id(), self.name, Some(stack)));
+        let packet = Rc::new(Cell::new(None));
+        let result = packet.clone();
+        inner
+            .main
+            .set(Some(Box::new(move || result.set(Some(f())))));
+
+        // SAFETY: The thread has not started, so nothing else refers to its
+        // context.
+        let context = unsafe { &mut *inner.context.get() };
+        context.ra = thread_start as usize as u32;
+        context.sp = inner.stack.as_ref().unwrap().top();
+
+        scheduler().ready.push_back(inner.clone());
+        Ok(JoinHandle {
+            thread: Thread { inner },
+            packet,
+        })
+    }
+}
+
+/// Spawn a new thread running `f`, returning a [JoinHandle] for it.
+///
+/// See [Builder::spawn] to configure the thread.
+pub fn spawn<F, T>(f: F) -> JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    Builder::new().spawn(f).unwrap()
+}
+
+/// The handle of the calling thread.
+pub fn current() -> Thread {
+    Thread {
+        inner: scheduler().current.clone(),
+    }
+}
+
+/// Run other threads that are ready before continuing with this one.
+pub fn yield_now() {
+    switch(true);
+}
+
+/// Block until the thread is unparked with [Thread::unpark].
+///
+/// Execution aborts if every thread is blocked, since none could unpark
+/// another.
+pub fn park() {
+    let current = scheduler().current.clone();
+    if !current.token.get() {
+        current.parked.set(true);
+        drop(current);
+        switch(false);
+    }
+    scheduler().current.token.set(false);
+}
+
+/// A key for a value that each thread has its own copy of.
+///
+/// Keys are declared with [crate::green_thread_local]. The value for a thread
+/// is initialized on first access and dropped when the thread exits.
+pub struct LocalKey<T: 'static> {
+    init: fn() -> T,
+}
+
+impl<T: 'static> LocalKey<T> {
+    #[doc(hidden)]
+    pub const fn new(init: fn() -> T) -> Self {
+        Self { init }
+    }
+
+    /// Call `f` with a reference to the calling thread's value.
+    pub fn with<F, R>(&'static self, f: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        let key = self as *const Self as usize;
+        let current = scheduler().current.clone();
+        if !current.locals.borrow().contains_key(&key) {
+            // The initializer may itself access thread-locals, so the map
+            // must not be borrowed while it runs.
+            let value = Box::new((self.init)());
+            current.locals.borrow_mut().insert(key, value);
+        }
+        let value: *const T = current.locals.borrow()[&key].downcast_ref::<T>().unwrap();
+        // SAFETY: Values are boxed, so they stay put as other keys are
+        // inserted, and are only dropped when this thread exits.
+        f(unsafe { &*value })
+    }
+}
+
+impl<T: 'static> fmt::Debug for LocalKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalKey").finish_non_exhaustive()
+    }
+}
+
+/// Declare a thread-local static for [green threads](crate::guest::thread).
+///
+/// This takes the same form as the standard `thread_local!`, and declares
+/// statics of type [LocalKey](crate::guest::thread::LocalKey).
+///
+/// # Example
+///
+/// ```ignore
+/// use core::cell::Cell;
+///
+/// risc0_zkvm::green_thread_local! {
+///     static COUNTER: Cell<u32> = Cell::new(0);
+/// }
+///
+/// COUNTER.with(|counter| counter.set(counter.get() + 1));
+/// ```
+#[macro_export]
+macro_rules! green_thread_local {
+    () => {};
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty = $init:expr; $($rest:tt)*) => {
+        $(#[$attr])*
+        $vis static $name: $crate::guest::thread::LocalKey<$t> = {
+            fn init() -> $t {
+                $init
+            }
+            $crate::guest::thread::LocalKey::new(init)
+        };
+        $crate::green_thread_local!($($rest)*);
+    };
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty = $init:expr) => {
+        $crate::green_thread_local!($(#[$attr])* $vis static $name: $t = $init;);
+    };
+}
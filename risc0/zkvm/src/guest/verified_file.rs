@@ -0,0 +1,179 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reads from host files that are verified against a committed root.
+//!
+//! The host registers a file with `ExecutorEnvBuilder::add_verified_file`, and
+//! the guest opens it by the digest returned by [merkle_file_root][root]. Each
+//! chunk the guest reads comes with a Merkle proof that is checked against the
+//! root, so the guest does not trust the host for the contents, and only the
+//! chunks that are read are loaded into the guest.
+//!
+//! ```no_run
+//! use risc0_zkvm::{guest::{env, verified_file::VerifiedFile}, sha::Digest};
+//!
+//! let root: Digest = env::read();
+//! let mut file = VerifiedFile::open(&root);
+//! let mut header = [0u8; 16];
+//! file.read_at(0, &mut header);
+//! let record = file.read_range(4096..4160);
+//! ```
+//!
+//! A guest that commits the root to its journal proves which file it read.
+//!
+//! [root]: crate::merkle_file_root
+
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+
+use risc0_zkvm_platform::{syscall::nr::SYS_VERIFIED_READ, WORD_SIZE};
+
+use crate::{
+    guest::env,
+    merkle_file::{file_root, num_chunks, verify_chunk, MERKLE_FILE_CHUNK_SIZE},
+    sha::{Digest, DIGEST_WORDS},
+};
+
+// Requests the length and tree root of the file instead of a chunk.
+const HEADER: u32 = u32::MAX;
+
+/// A file provided by the host, whose reads are verified in the guest.
+pub struct VerifiedFile {
+    root: Digest,
+    len: u64,
+    tree_root: Digest,
+    // The most recently read chunk, so that sequential reads fetch each chunk
+    // once.
+    cached: Option<(u64, Vec<u8>)>,
+}
+
+impl VerifiedFile {
+    /// Open the file with the given root.
+    ///
+    /// Panics if the host has no such file, or if the length and tree root it
+    /// reports do not match `root`.
+    pub fn open(root: &Digest) -> Self {
+        let header = request(root, HEADER);
+        assert_eq!(
+            header.len(),
+            2 + DIGEST_WORDS,
+            "malformed verified file header"
+        );
+        let len = (header[1] as u64) << 32 | header[0] as u64;
+        let tree_root = Digest::try_from(&header[2..]).unwrap();
+        assert_eq!(
+            file_root(&tree_root, len),
+            *root,
+            "verified file header does not match the root"
+        );
+        Self {
+            root: *root,
+            len,
+            tree_root,
+            cached: None,
+        }
+    }
+
+    /// The root this file was opened with.
+    pub fn root(&self) -> &Digest {
+        &self.root
+    }
+
+    /// The length of the file in bytes.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Whether the file is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Read into `buf` from `offset`, returning the number of bytes read.
+    ///
+    /// This reads fewer than `buf.len()` bytes only at the end of the file.
+    pub fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> usize {
+        let end = u64::min(self.len, offset.saturating_add(buf.len() as u64));
+        let mut pos = offset;
+        while pos < end {
+            let index = pos / MERKLE_FILE_CHUNK_SIZE as u64;
+            let start = (pos % MERKLE_FILE_CHUNK_SIZE as u64) as usize;
+            let chunk = self.chunk(index);
+            let n = usize::min(chunk.len() - start, (end - pos) as usize);
+            let dst = (pos - offset) as usize;
+            buf[dst..dst + n].copy_from_slice(&chunk[start..start + n]);
+            pos += n as u64;
+        }
+        end.saturating_sub(offset) as usize
+    }
+
+    /// Read the bytes in `range`.
+    ///
+    /// Panics if `range` extends past the end of the file.
+    pub fn read_range(&mut self, range: Range<u64>) -> Vec<u8> {
+        assert!(
+            range.start <= range.end && range.end <= self.len,
+            "range {range:?} is out of bounds for a file of {} bytes",
+            self.len
+        );
+        let mut buf = vec![0u8; (range.end - range.start) as usize];
+        self.read_at(range.start, &mut buf);
+        buf
+    }
+
+    fn chunk(&mut self, index: u64) -> &[u8] {
+        if !matches!(&self.cached, Some((cached, _)) if *cached == index) {
+            self.cached = Some((index, self.fetch(index)));
+        }
+        &self.cached.as_ref().unwrap().1
+    }
+
+    fn fetch(&self, index: u64) -> Vec<u8> {
+        let num_chunks = num_chunks(self.len);
+        let chunk_len = match index + 1 == num_chunks {
+            true => self.len as usize - index as usize * MERKLE_FILE_CHUNK_SIZE,
+            false => MERKLE_FILE_CHUNK_SIZE,
+        };
+        let chunk_words = chunk_len.div_ceil(WORD_SIZE);
+
+        let response = request(&self.root, index as u32);
+        assert!(
+            response.len() >= chunk_words,
+            "malformed verified file chunk"
+        );
+        let (chunk, proof) = response.split_at(chunk_words);
+        let chunk = &bytemuck::cast_slice::<_, u8>(chunk)[..chunk_len];
+        let proof: Vec<Digest> = proof
+            .chunks(DIGEST_WORDS)
+            .map(|words| Digest::try_from(words).unwrap())
+            .collect();
+        assert!(
+            verify_chunk(&self.tree_root, num_chunks, index, chunk, &proof),
+            "verified file chunk {index} does not match the root"
+        );
+        chunk.to_vec()
+    }
+}
+
+fn request(root: &Digest, op: u32) -> Vec<u32> {
+    let mut to_host = [0u32; DIGEST_WORDS + 1];
+    to_host[..DIGEST_WORDS].copy_from_slice(root.as_words());
+    to_host[DIGEST_WORDS] = op;
+    let to_host: &[u8] = bytemuck::cast_slice(&to_host);
+
+    let nbytes = env::syscall(SYS_VERIFIED_READ, to_host, &mut []).0 as usize;
+    let mut from_host = vec![0u32; nbytes.div_ceil(WORD_SIZE)];
+    env::syscall(SYS_VERIFIED_READ, &[], &mut from_host);
+    from_host
+}
@@ -0,0 +1,90 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The canonical encoding of receipts and claims.
+//!
+//! Values are encoded as the protobuf messages of the same name in the
+//! `protos.core` package, whose schema is published as
+//! [`core.proto`](https://github.com/risc0/risc0/blob/main/risc0/zkvm/src/host/protos/core.proto)
+//! and [`base.proto`](https://github.com/risc0/risc0/blob/main/risc0/zkvm/src/host/protos/base.proto).
+//! Receipts, claims and the receipts holding each seal carry a version, and
+//! decoding fails on versions newer than this crate supports.
+//!
+//! Fields are written in field number order and fields with default values
+//! are omitted, so equal values always have the same encoding.
+
+use anyhow::{ensure, Result};
+use prost::Message;
+
+use super::pb;
+use crate::{InnerReceipt, Receipt, ReceiptClaim};
+
+fn encode<T, M>(value: &T) -> Vec<u8>
+where
+    T: Clone,
+    M: Message + From<T>,
+{
+    M::from(value.clone()).encode_to_vec()
+}
+
+fn decode<T, M>(bytes: &[u8]) -> Result<T>
+where
+    M: Message + Default + TryInto<T, Error = anyhow::Error>,
+{
+    let message = M::decode(bytes)?;
+    // Decoding accepts trailing fields and repeated fields, the last of which
+    // wins, so only accept bytes that are the encoding of the decoded value.
+    ensure!(
+        message.encode_to_vec() == bytes,
+        "bytes are not in canonical encoding"
+    );
+    message.try_into()
+}
+
+impl Receipt {
+    /// Encode this receipt in the canonical encoding.
+    pub fn to_canonical_bytes(&self) -> Vec<u8> {
+        encode::<_, pb::core::Receipt>(self)
+    }
+
+    /// Decode a receipt from the canonical encoding.
+    pub fn from_canonical_bytes(bytes: &[u8]) -> Result<Self> {
+        decode::<_, pb::core::Receipt>(bytes)
+    }
+}
+
+impl InnerReceipt {
+    /// Encode this inner receipt, including its seal, in the canonical
+    /// encoding.
+    pub fn to_canonical_bytes(&self) -> Vec<u8> {
+        encode::<_, pb::core::InnerReceipt>(self)
+    }
+
+    /// Decode an inner receipt from the canonical encoding.
+    pub fn from_canonical_bytes(bytes: &[u8]) -> Result<Self> {
+        decode::<_, pb::core::InnerReceipt>(bytes)
+    }
+}
+
+impl ReceiptClaim {
+    /// Encode this claim in the canonical encoding.
+    pub fn to_canonical_bytes(&self) -> Vec<u8> {
+        encode::<_, pb::core::ReceiptClaim>(self)
+    }
+
+    /// Decode a claim from the canonical encoding.
+    pub fn from_canonical_bytes(bytes: &[u8]) -> Result<Self> {
+        decode::<_, pb::core::ReceiptClaim>(bytes)
+    }
+}
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::path::Path;
+use std::{collections::HashMap, path::Path};
 
 use anyhow::{anyhow, bail, Result};
 use bytes::Bytes;
@@ -20,13 +20,12 @@ use prost::Message;
 
 use super::{
     malformed_err, pb, Asset, AssetRequest, ConnectionWrapper, Connector, ParentProcessConnector,
-    SessionInfo,
 };
 use crate::{
     get_version,
-    host::{api::SegmentInfo, client::prove::get_r0vm_path},
+    host::client::prove::get_r0vm_path,
     receipt::{Assumption, SegmentReceipt, SuccinctReceipt},
-    ExecutorEnv, Journal, ProveInfo, ProverOpts,
+    ExecutorEnv, Journal, ProveInfo, ProverOpts, SegmentInfo, SessionInfo,
 };
 
 /// A client implementation for interacting with a zkVM server.
@@ -83,6 +82,7 @@ impl Client {
                     }),
                 },
             )),
+            trace_context: trace_context(),
         };
         conn.send(request)?;
 
@@ -118,6 +118,7 @@ impl Client {
                     segments_out: Some(segments_out.try_into()?),
                 },
             )),
+            trace_context: trace_context(),
         };
         tracing::trace!("tx: {request:?}");
         conn.send(request)?;
@@ -149,6 +150,7 @@ impl Client {
                     receipt_out: Some(receipt_out.try_into()?),
                 },
             )),
+            trace_context: trace_context(),
         };
         tracing::trace!("tx: {request:?}");
         conn.send(request)?;
@@ -192,6 +194,7 @@ impl Client {
                 receipt: Some(receipt.try_into()?),
                 receipt_out: Some(receipt_out.try_into()?),
             })),
+            trace_context: trace_context(),
         };
         tracing::trace!("tx: {request:?}");
         conn.send(request)?;
@@ -235,6 +238,7 @@ impl Client {
                 right_receipt: Some(right_receipt.try_into()?),
                 receipt_out: Some(receipt_out.try_into()?),
             })),
+            trace_context: trace_context(),
         };
         tracing::trace!("tx: {request:?}");
         conn.send(request)?;
@@ -282,6 +286,7 @@ impl Client {
                     receipt_out: Some(receipt_out.try_into()?),
                 },
             )),
+            trace_context: trace_context(),
         };
         tracing::trace!("tx: {request:?}");
         conn.send(request)?;
@@ -326,6 +331,7 @@ impl Client {
                     receipt_out: Some(receipt_out.try_into()?),
                 },
             )),
+            trace_context: trace_context(),
         };
         tracing::trace!("tx: {request:?}");
         conn.send(request)?;
@@ -398,12 +404,20 @@ impl Client {
             write_fds: env.posix_io.borrow().write_fds.keys().cloned().collect(),
             segment_limit_po2: env.segment_limit_po2,
             session_limit: env.session_limit,
+            no_progress_limit: env.no_progress_limit,
+            heap_limit: env.heap_limit,
+            wall_clock: env.wall_clock,
             trace_events: (!env.trace.is_empty()).then_some(()),
             pprof_out: env
                 .pprof_out
                 .as_ref()
                 .map(|x| x.to_string_lossy().into())
                 .unwrap_or_default(),
+            flamegraph_out: env
+                .flamegraph_out
+                .as_ref()
+                .map(|x| x.to_string_lossy().into())
+                .unwrap_or_default(),
             assumptions: env
                 .assumptions
                 .borrow()
@@ -439,6 +453,16 @@ impl Client {
                 .as_ref()
                 .map(|x| x.path().to_string_lossy().into())
                 .unwrap_or_default(),
+            programs: env
+                .programs
+                .values()
+                .map(|elf| Asset::Inline(elf.clone()).try_into())
+                .collect::<Result<_>>()?,
+            verified_files: env
+                .verified_files
+                .values()
+                .map(|data| Asset::Inline(data.clone()).try_into())
+                .collect::<Result<_>>()?,
         })
     }
 
@@ -635,6 +659,17 @@ fn check_server_version(requested: &semver::Version, server: &semver::Version) -
     }
 }
 
+// The trace context of the caller, which the server continues.
+fn trace_context() -> HashMap<String, String> {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "otel")] {
+            crate::current_trace_context()
+        } else {
+            HashMap::new()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use semver::Version;
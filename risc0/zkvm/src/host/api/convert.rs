@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
 use anyhow::{anyhow, bail, Result};
 use prost::{Message, Name};
@@ -22,11 +22,12 @@ use risc0_zkp::core::digest::Digest;
 use super::{malformed_err, path_to_string, pb, Asset, AssetRequest};
 use crate::{
     receipt::{
-        segment::decode_receipt_claim_from_seal, CompositeReceipt, InnerReceipt, SegmentReceipt,
-        SuccinctReceipt,
+        segment::decode_receipt_claim_from_seal, CompactReceipt, CompositeReceipt, InnerReceipt,
+        PlonkReceipt, SegmentReceipt, SuccinctReceipt,
     },
-    Assumptions, ExitCode, Journal, MaybePruned, Output, ProveInfo, ProverOpts, Receipt,
-    ReceiptClaim, ReceiptKind, SessionStats, TraceEvent,
+    Assumptions, Channels, ExitCode, Journal, MaybePruned, Output, ProveInfo, ProverOpts,
+    ProvingCost, ProvingUsage, Receipt, ReceiptAnnotations, ReceiptClaim, ReceiptKind,
+    SessionStats, TraceEvent,
 };
 
 mod ver {
@@ -35,6 +36,9 @@ mod ver {
     pub const RECEIPT: CompatVersion = CompatVersion { value: 1 };
     pub const SEGMENT_RECEIPT: CompatVersion = CompatVersion { value: 1 };
     pub const SUCCINCT_RECEIPT: CompatVersion = CompatVersion { value: 1 };
+    pub const COMPACT_RECEIPT: CompatVersion = CompatVersion { value: 1 };
+    pub const PLONK_RECEIPT: CompatVersion = CompatVersion { value: 1 };
+    pub const RECEIPT_CLAIM: CompatVersion = CompatVersion { value: 1 };
 }
 
 impl TryFrom<AssetRequest> for pb::api::AssetRequest {
@@ -117,6 +121,11 @@ impl From<TraceEvent> for pb::api::TraceEvent {
                     },
                 )),
             },
+            TraceEvent::Paging { cycles } => Self {
+                kind: Some(pb::api::trace_event::Kind::Paging(
+                    pb::api::trace_event::Paging { cycles },
+                )),
+            },
         }
     }
 }
@@ -139,6 +148,9 @@ impl TryFrom<pb::api::TraceEvent> for TraceEvent {
                 addr: event.addr,
                 region: event.region,
             },
+            pb::api::trace_event::Kind::Paging(event) => TraceEvent::Paging {
+                cycles: event.cycles,
+            },
         })
     }
 }
@@ -203,6 +215,7 @@ impl From<pb::api::ProverOpts> for ProverOpts {
                 0 => ReceiptKind::Composite,
                 1 => ReceiptKind::Succinct,
                 2 => ReceiptKind::Compact,
+                3 => ReceiptKind::Plonk,
                 value => panic!("Unknown receipt kind number: {value}"),
             },
         }
@@ -214,7 +227,12 @@ impl From<ProverOpts> for pb::api::ProverOpts {
         Self {
             hashfn: opts.hashfn,
             prove_guest_errors: opts.prove_guest_errors,
-            receipt_kind: opts.receipt_kind as i32,
+            receipt_kind: match opts.receipt_kind {
+                ReceiptKind::Composite => pb::api::ReceiptKind::Composite,
+                ReceiptKind::Succinct => pb::api::ReceiptKind::Succinct,
+                ReceiptKind::Plonk => pb::api::ReceiptKind::Plonk,
+                ReceiptKind::Compact => pb::api::ReceiptKind::Compact,
+            } as i32,
         }
     }
 }
@@ -252,6 +270,8 @@ impl From<SessionStats> for pb::core::SessionStats {
             segments: value.segments.try_into().unwrap(),
             total_cycles: value.total_cycles,
             user_cycles: value.user_cycles,
+            usage: value.usage.map(Into::into),
+            cost: value.cost.map(Into::into),
         }
     }
 }
@@ -264,10 +284,51 @@ impl TryFrom<pb::core::SessionStats> for SessionStats {
             segments: value.segments.try_into()?,
             total_cycles: value.total_cycles,
             user_cycles: value.user_cycles,
+            usage: value.usage.map(Into::into),
+            cost: value.cost.map(Into::into),
         })
     }
 }
 
+impl From<ProvingUsage> for pb::core::ProvingUsage {
+    fn from(value: ProvingUsage) -> Self {
+        let nanos = |time: Duration| time.as_nanos().try_into().unwrap_or(u64::MAX);
+        Self {
+            wall_time_nanos: nanos(value.wall_time),
+            cpu_time_nanos: value.cpu_time.map(nanos),
+            gpu_wall_time_nanos: value.gpu_wall_time.map(nanos),
+        }
+    }
+}
+
+impl From<pb::core::ProvingUsage> for ProvingUsage {
+    fn from(value: pb::core::ProvingUsage) -> Self {
+        Self {
+            wall_time: Duration::from_nanos(value.wall_time_nanos),
+            cpu_time: value.cpu_time_nanos.map(Duration::from_nanos),
+            gpu_wall_time: value.gpu_wall_time_nanos.map(Duration::from_nanos),
+        }
+    }
+}
+
+impl From<ProvingCost> for pb::core::ProvingCost {
+    fn from(value: ProvingCost) -> Self {
+        Self {
+            usd: value.usd,
+            energy_joules: value.energy_joules,
+        }
+    }
+}
+
+impl From<pb::core::ProvingCost> for ProvingCost {
+    fn from(value: pb::core::ProvingCost) -> Self {
+        Self {
+            usd: value.usd,
+            energy_joules: value.energy_joules,
+        }
+    }
+}
+
 impl From<ProveInfo> for pb::core::ProveInfo {
     fn from(value: ProveInfo) -> Self {
         Self {
@@ -294,6 +355,7 @@ impl From<Receipt> for pb::core::Receipt {
             version: Some(ver::RECEIPT),
             inner: Some(value.inner.into()),
             journal: value.journal.bytes,
+            annotations: (!value.annotations.is_empty()).then(|| value.annotations.into()),
         }
     }
 }
@@ -309,10 +371,35 @@ impl TryFrom<pb::core::Receipt> for Receipt {
         Ok(Self {
             inner: value.inner.ok_or(malformed_err())?.try_into()?,
             journal: Journal::new(value.journal),
+            annotations: value.annotations.map(Into::into).unwrap_or_default(),
         })
     }
 }
 
+impl From<ReceiptAnnotations> for pb::core::ReceiptAnnotations {
+    fn from(value: ReceiptAnnotations) -> Self {
+        Self {
+            prover_version: value.prover_version,
+            proving_time_nanos: value
+                .proving_time
+                .map(|time| time.as_nanos().try_into().unwrap_or(u64::MAX)),
+            total_cycles: value.total_cycles,
+            tags: value.tags,
+        }
+    }
+}
+
+impl From<pb::core::ReceiptAnnotations> for ReceiptAnnotations {
+    fn from(value: pb::core::ReceiptAnnotations) -> Self {
+        Self {
+            prover_version: value.prover_version,
+            proving_time: value.proving_time_nanos.map(Duration::from_nanos),
+            total_cycles: value.total_cycles,
+            tags: value.tags,
+        }
+    }
+}
+
 impl From<SegmentReceipt> for pb::core::SegmentReceipt {
     fn from(value: SegmentReceipt) -> Self {
         Self {
@@ -392,6 +479,58 @@ impl TryFrom<pb::core::SuccinctReceipt> for SuccinctReceipt {
     }
 }
 
+impl From<CompactReceipt> for pb::core::Groth16Receipt {
+    fn from(value: CompactReceipt) -> Self {
+        Self {
+            version: Some(ver::COMPACT_RECEIPT),
+            seal: value.seal,
+            claim: Some(value.claim.into()),
+        }
+    }
+}
+
+impl TryFrom<pb::core::Groth16Receipt> for CompactReceipt {
+    type Error = anyhow::Error;
+
+    fn try_from(value: pb::core::Groth16Receipt) -> Result<Self> {
+        let version = value.version.ok_or(malformed_err())?.value;
+        if version > ver::COMPACT_RECEIPT.value {
+            bail!("Incompatible CompactReceipt version: {version}");
+        }
+
+        Ok(Self {
+            seal: value.seal,
+            claim: value.claim.ok_or(malformed_err())?.try_into()?,
+        })
+    }
+}
+
+impl From<PlonkReceipt> for pb::core::PlonkReceipt {
+    fn from(value: PlonkReceipt) -> Self {
+        Self {
+            version: Some(ver::PLONK_RECEIPT),
+            seal: value.seal,
+            claim: Some(value.claim.into()),
+        }
+    }
+}
+
+impl TryFrom<pb::core::PlonkReceipt> for PlonkReceipt {
+    type Error = anyhow::Error;
+
+    fn try_from(value: pb::core::PlonkReceipt) -> Result<Self> {
+        let version = value.version.ok_or(malformed_err())?.value;
+        if version > ver::PLONK_RECEIPT.value {
+            bail!("Incompatible PlonkReceipt version: {version}");
+        }
+
+        Ok(Self {
+            seal: value.seal,
+            claim: value.claim.ok_or(malformed_err())?.try_into()?,
+        })
+    }
+}
+
 impl From<InnerReceipt> for pb::core::InnerReceipt {
     fn from(value: InnerReceipt) -> Self {
         Self {
@@ -407,7 +546,10 @@ impl From<InnerReceipt> for pb::core::InnerReceipt {
                         claim: Some(claim.into()),
                     })
                 }
-                InnerReceipt::Compact(_) => unimplemented!(),
+                InnerReceipt::Compact(inner) => {
+                    pb::core::inner_receipt::Kind::Groth16(inner.into())
+                }
+                InnerReceipt::Plonk(inner) => pb::core::inner_receipt::Kind::Plonk(inner.into()),
             }),
         }
     }
@@ -419,7 +561,8 @@ impl TryFrom<pb::core::InnerReceipt> for InnerReceipt {
     fn try_from(value: pb::core::InnerReceipt) -> Result<Self> {
         Ok(match value.kind.ok_or(malformed_err())? {
             pb::core::inner_receipt::Kind::Composite(inner) => Self::Composite(inner.try_into()?),
-            pb::core::inner_receipt::Kind::Groth16(_) => unimplemented!(),
+            pb::core::inner_receipt::Kind::Groth16(inner) => Self::Compact(inner.try_into()?),
+            pb::core::inner_receipt::Kind::Plonk(inner) => Self::Plonk(inner.try_into()?),
             pb::core::inner_receipt::Kind::Succinct(inner) => Self::Succinct(inner.try_into()?),
             pb::core::inner_receipt::Kind::Fake(inner) => Self::Fake {
                 claim: inner.claim.ok_or(malformed_err())?.try_into()?,
@@ -500,6 +643,7 @@ impl From<ReceiptClaim> for pb::core::ReceiptClaim {
                 }
                 MaybePruned::Pruned(digest) => Some(MaybePruned::<Output>::Pruned(digest).into()),
             },
+            version: Some(ver::RECEIPT_CLAIM),
         }
     }
 }
@@ -508,6 +652,12 @@ impl TryFrom<pb::core::ReceiptClaim> for ReceiptClaim {
     type Error = anyhow::Error;
 
     fn try_from(value: pb::core::ReceiptClaim) -> Result<Self> {
+        // Claims encoded before the version was added have none.
+        if let Some(version) = value.version {
+            if version.value > ver::RECEIPT_CLAIM.value {
+                bail!("Incompatible ReceiptClaim version: {}", version.value);
+            }
+        }
         Ok(Self {
             pre: value.pre.ok_or(malformed_err())?.try_into()?,
             post: value.post.ok_or(malformed_err())?.try_into()?,
@@ -568,6 +718,7 @@ impl From<Output> for pb::core::Output {
         Self {
             journal: Some(value.journal.into()),
             assumptions: Some(value.assumptions.into()),
+            channels: Some(value.channels.into()),
         }
     }
 }
@@ -579,10 +730,50 @@ impl TryFrom<pb::core::Output> for Output {
         Ok(Self {
             journal: value.journal.ok_or(malformed_err())?.try_into()?,
             assumptions: value.assumptions.ok_or(malformed_err())?.try_into()?,
+            // Outputs encoded before channels were added have none.
+            channels: match value.channels {
+                Some(channels) => channels.try_into()?,
+                None => Channels::default().into(),
+            },
         })
     }
 }
 
+impl Name for pb::core::Channels {
+    const PACKAGE: &'static str = "risc0.protos.core";
+    const NAME: &'static str = "Channels";
+}
+
+impl AssociatedMessage for Channels {
+    type Message = pb::core::Channels;
+}
+
+impl From<Channels> for pb::core::Channels {
+    fn from(value: Channels) -> Self {
+        Self {
+            inner: value
+                .0
+                .into_iter()
+                .map(|(name, data)| (name, data.into()))
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<pb::core::Channels> for Channels {
+    type Error = anyhow::Error;
+
+    fn try_from(value: pb::core::Channels) -> Result<Self> {
+        Ok(Self(
+            value
+                .inner
+                .into_iter()
+                .map(|(name, data)| Ok((name, data.try_into()?)))
+                .collect::<Result<_>>()?,
+        ))
+    }
+}
+
 impl Name for pb::core::Assumptions {
     const PACKAGE: &'static str = "risc0.protos.core";
     const NAME: &'static str = "Assumptions";
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod canonical;
 pub(crate) mod client;
 pub(crate) mod convert;
 #[cfg(feature = "prove")]
@@ -38,8 +39,6 @@ use anyhow::{anyhow, Context, Result};
 use bytes::{Buf, BufMut, Bytes};
 use prost::Message;
 
-use crate::{ExitCode, Journal};
-
 mod pb {
     pub(crate) mod api {
         pub use crate::host::protos::api::*;
@@ -289,30 +288,6 @@ pub enum AssetRequest {
     Path(PathBuf),
 }
 
-/// Provides information about the result of execution.
-#[derive(Clone, Debug)]
-pub struct SessionInfo {
-    /// The number of user cycles for each segment.
-    pub segments: Vec<SegmentInfo>,
-
-    /// The data publicly committed by the guest program.
-    pub journal: Journal,
-
-    /// The [ExitCode] of the session.
-    pub exit_code: ExitCode,
-}
-
-/// Provides information about a segment of execution.
-#[derive(Clone, Debug)]
-pub struct SegmentInfo {
-    /// The number of cycles used for proving in powers of 2.
-    pub po2: u32,
-
-    /// The number of user cycles without any overhead for continuations or po2
-    /// padding.
-    pub cycles: u32,
-}
-
 impl Asset {
     /// Return the bytes for this asset.
     pub fn as_bytes(&self) -> Result<Bytes> {
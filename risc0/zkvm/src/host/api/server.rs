@@ -234,6 +234,11 @@ impl Server {
 
         let request: pb::api::ServerRequest = conn.recv()?;
         tracing::trace!("rx: {request:?}");
+        // The request is handled in the trace of the client, when it has one.
+        let span = tracing::info_span!("api_request");
+        #[cfg(feature = "otel")]
+        crate::set_trace_parent(&span, &request.trace_context);
+        let _entered = span.enter();
         match request.kind.ok_or(malformed_err())? {
             pb::api::server_request::Kind::Prove(request) => self.on_prove(conn, request),
             pb::api::server_request::Kind::Execute(request) => self.on_execute(conn, request),
@@ -601,6 +606,9 @@ fn build_env<'a>(
         env_builder.segment_limit_po2(segment_limit_po2);
     }
     env_builder.session_limit(request.session_limit);
+    env_builder.no_progress_limit(request.no_progress_limit);
+    env_builder.heap_limit(request.heap_limit);
+    env_builder.wall_clock(request.wall_clock);
     if request.trace_events.is_some() {
         let proxy = TraceProxy::new(conn.try_clone()?);
         env_builder.trace_callback(proxy);
@@ -608,9 +616,18 @@ fn build_env<'a>(
     if !request.pprof_out.is_empty() {
         env_builder.enable_profiler(Path::new(&request.pprof_out));
     }
+    if !request.flamegraph_out.is_empty() {
+        env_builder.enable_flamegraph(Path::new(&request.flamegraph_out));
+    }
     if !request.segment_path.is_empty() {
         env_builder.segment_path(Path::new(&request.segment_path));
     }
+    for program in request.programs.iter() {
+        env_builder.add_program(&program.as_bytes()?)?;
+    }
+    for file in request.verified_files.iter() {
+        env_builder.add_verified_file(file.as_bytes()?);
+    }
 
     for assumption in request.assumptions.iter() {
         match assumption.kind.as_ref().ok_or(malformed_err())? {
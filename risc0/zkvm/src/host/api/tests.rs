@@ -26,10 +26,10 @@ use risc0_zkvm_methods::{
 use tempfile::{tempdir, TempDir};
 use test_log::test;
 
-use super::{Asset, AssetRequest, ConnectionWrapper, Connector, TcpConnection};
+use super::{pb, Asset, AssetRequest, ConnectionWrapper, Connector, TcpConnection};
 use crate::{
-    receipt::SuccinctReceipt, ApiClient, ApiServer, ExecutorEnv, InnerReceipt, ProverOpts, Receipt,
-    SegmentReceipt, SessionInfo, VerifierContext,
+    receipt::SuccinctReceipt, sha::Digest, ApiClient, ApiServer, ExecutorEnv, InnerReceipt,
+    ProverOpts, Receipt, SegmentReceipt, SessionInfo, VerifierContext,
 };
 
 struct TestClientConnector {
@@ -153,6 +153,117 @@ fn with_server<T, F: FnOnce() -> Result<T>>(addr: SocketAddr, f: F) -> T {
     result
 }
 
+#[test]
+fn compact_receipt_roundtrip() {
+    use crate::receipt::{fake_claim, CompactReceipt};
+
+    let inner = InnerReceipt::Compact(CompactReceipt {
+        seal: vec![1, 2, 3, 4],
+        claim: fake_claim(b""),
+    });
+    let receipt = Receipt::new(inner, vec![5, 6]);
+    let pb: pb::core::Receipt = receipt.clone().into();
+    assert_eq!(Receipt::try_from(pb).unwrap(), receipt);
+}
+
+#[test]
+fn plonk_receipt_roundtrip() {
+    use crate::{receipt::PlonkReceipt, ExitCode, MaybePruned, ReceiptClaim};
+
+    let claim = ReceiptClaim {
+        pre: MaybePruned::Pruned(Digest::from([1u32; 8])),
+        post: MaybePruned::Pruned(Digest::from([2u32; 8])),
+        exit_code: ExitCode::Halted(0),
+        input: Digest::ZERO,
+        output: None.into(),
+    };
+    let inner = InnerReceipt::Plonk(PlonkReceipt {
+        seal: vec![1, 2, 3, 4],
+        claim,
+    });
+    let receipt = Receipt::new(inner, vec![5, 6]);
+    let pb: pb::core::Receipt = receipt.clone().into();
+    assert_eq!(Receipt::try_from(pb).unwrap(), receipt);
+}
+
+#[test]
+fn canonical_receipt_roundtrip() {
+    use prost::Message;
+
+    use crate::{
+        receipt::CompactReceipt, Assumptions, Channels, ExitCode, MaybePruned, Output, ReceiptClaim,
+    };
+
+    let mut channels = Channels::default();
+    for name in ["b", "a", "c"] {
+        channels
+            .0
+            .insert(name.into(), MaybePruned::Pruned(Digest::from([3u32; 8])));
+    }
+    let claim = ReceiptClaim {
+        pre: MaybePruned::Pruned(Digest::from([1u32; 8])),
+        post: MaybePruned::Pruned(Digest::from([2u32; 8])),
+        exit_code: ExitCode::Halted(0),
+        input: Digest::ZERO,
+        output: Some(Output {
+            journal: MaybePruned::Value(vec![5, 6]),
+            assumptions: Assumptions(vec![]).into(),
+            channels: channels.into(),
+        })
+        .into(),
+    };
+    let bytes = claim.to_canonical_bytes();
+    assert_eq!(ReceiptClaim::from_canonical_bytes(&bytes).unwrap(), claim);
+    assert_eq!(claim.clone().to_canonical_bytes(), bytes);
+
+    let receipt = Receipt::new(
+        InnerReceipt::Compact(CompactReceipt {
+            seal: vec![1, 2, 3, 4],
+            claim: claim.clone(),
+        }),
+        vec![5, 6],
+    );
+    let bytes = receipt.to_canonical_bytes();
+    assert_eq!(Receipt::from_canonical_bytes(&bytes).unwrap(), receipt);
+    assert_eq!(
+        InnerReceipt::from_canonical_bytes(&receipt.inner.to_canonical_bytes()).unwrap(),
+        receipt.inner
+    );
+
+    // Annotations are carried by the encoding, and empty annotations are omitted.
+    let annotated = receipt.clone().with_annotations(
+        crate::ReceiptAnnotations {
+            prover_version: Some("1.0.0".into()),
+            proving_time: Some(std::time::Duration::from_millis(1500)),
+            total_cycles: Some(1 << 20),
+            ..Default::default()
+        }
+        .with_tag("operator", "test"),
+    );
+    let annotated_bytes = annotated.to_canonical_bytes();
+    assert_ne!(annotated_bytes, bytes);
+    assert_eq!(
+        Receipt::from_canonical_bytes(&annotated_bytes).unwrap(),
+        annotated
+    );
+
+    // Appending a field that overrides the journal decodes, but is not canonical.
+    let mut extended = bytes.clone();
+    extended.extend(
+        pb::core::Receipt {
+            journal: vec![7],
+            ..Default::default()
+        }
+        .encode_to_vec(),
+    );
+    assert!(Receipt::from_canonical_bytes(&extended).is_err());
+
+    // Claims from a newer version are rejected.
+    let mut newer: pb::core::ReceiptClaim = claim.into();
+    newer.version = Some(pb::base::CompatVersion { value: 2 });
+    assert!(ReceiptClaim::from_canonical_bytes(&newer.encode_to_vec()).is_err());
+}
+
 #[test]
 fn execute() {
     let env = ExecutorEnv::builder()
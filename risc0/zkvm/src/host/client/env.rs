@@ -17,7 +17,7 @@
 use std::{
     cell::RefCell,
     collections::HashMap,
-    io::{BufRead, BufReader, Cursor, Read, Write},
+    io::{BufRead, BufReader, Cursor, Read, Seek, Write},
     mem,
     path::{Path, PathBuf},
     rc::Rc,
@@ -53,7 +53,7 @@ pub(crate) struct Assumptions {
     pub(crate) cached: Vec<Assumption>,
     // An ordered list of assumptions accessed during execution.
     // Each time an assumption is used, it is cloned and pushed to the head of the list.
-    #[cfg(feature = "prove")]
+    #[cfg(feature = "execute")]
     pub(crate) accessed: Vec<Assumption>,
 }
 
@@ -83,6 +83,10 @@ pub struct ExecutorEnv<'a> {
     pub(crate) args: Vec<String>,
     pub(crate) segment_limit_po2: Option<u32>,
     pub(crate) session_limit: Option<u64>,
+    pub(crate) no_progress_limit: Option<u64>,
+    pub(crate) split_on_fault: bool,
+    pub(crate) heap_limit: Option<u32>,
+    pub(crate) wall_clock: bool,
     pub(crate) posix_io: Rc<RefCell<PosixIo<'a>>>,
     pub(crate) slice_io: Rc<RefCell<SliceIoTable<'a>>>,
     pub(crate) input: Vec<u8>,
@@ -90,7 +94,26 @@ pub struct ExecutorEnv<'a> {
     pub(crate) assumptions: Rc<RefCell<Assumptions>>,
     pub(crate) segment_path: Option<SegmentPath>,
     pub(crate) pprof_out: Option<PathBuf>,
+    pub(crate) flamegraph_out: Option<PathBuf>,
     pub(crate) input_digest: Option<Digest>,
+    pub(crate) programs: HashMap<Digest, Bytes>,
+    pub(crate) verified_files: HashMap<Digest, Bytes>,
+    #[cfg(feature = "execute")]
+    pub(crate) calls: Rc<RefCell<Vec<crate::Session>>>,
+    // The cycles run by the programs called with `env::call`, including the
+    // programs they called in turn.
+    #[cfg(feature = "execute")]
+    pub(crate) call_cycles: Rc<std::cell::Cell<u64>>,
+    // How many calls deep this execution is, zero for the top-level guest.
+    #[cfg(feature = "execute")]
+    pub(crate) call_depth: usize,
+    // The data written to each named output channel during execution.
+    #[cfg(feature = "execute")]
+    pub(crate) channels: Rc<RefCell<std::collections::BTreeMap<String, Vec<u8>>>>,
+    #[cfg(feature = "execute")]
+    pub(crate) segment_store: Option<Arc<dyn crate::SegmentStore>>,
+    #[cfg(feature = "execute")]
+    pub(crate) receipt_store: Option<Arc<dyn crate::ReceiptStore>>,
 }
 
 impl<'a> ExecutorEnv<'a> {
@@ -124,6 +147,12 @@ impl<'a> ExecutorEnvBuilder<'a> {
     pub fn build(&mut self) -> Result<ExecutorEnv<'a>> {
         let mut inner = mem::take(&mut self.inner);
 
+        #[cfg(feature = "execute")]
+        anyhow::ensure!(
+            inner.segment_path.is_none() || inner.segment_store.is_none(),
+            "a segment path and a segment store cannot both be set"
+        );
+
         if !inner.input.is_empty() {
             let reader = Cursor::new(inner.input.clone());
             inner
@@ -138,6 +167,12 @@ impl<'a> ExecutorEnvBuilder<'a> {
             }
         }
 
+        if inner.flamegraph_out.is_none() {
+            if let Ok(env_var) = std::env::var("RISC0_FLAMEGRAPH_OUT") {
+                inner.flamegraph_out = Some(env_var.into());
+            }
+        }
+
         Ok(inner)
     }
 
@@ -167,6 +202,86 @@ impl<'a> ExecutorEnvBuilder<'a> {
         self
     }
 
+    /// Set a limit, in cycles, on how long the guest may run without making a
+    /// syscall.
+    ///
+    /// Guests read input, write to the journal, and otherwise interact with
+    /// the host through syscalls. A guest that exceeds this limit is assumed
+    /// to be stuck, and execution fails with a [crate::NoProgressError].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use risc0_zkvm::ExecutorEnv;
+    ///
+    /// let env = ExecutorEnv::builder()
+    ///     .no_progress_limit(Some(16 * 1024 * 1024)) // 16M cycles
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn no_progress_limit(&mut self, limit: Option<u64>) -> &mut Self {
+        self.inner.no_progress_limit = limit;
+        self
+    }
+
+    /// End the session at a guest fault, rather than failing execution with it.
+    ///
+    /// A fault, such as an illegal instruction or a misaligned access, makes
+    /// execution fail with a [crate::Trap] error by default. With this set, the
+    /// session instead ends at the faulting instruction, without running it,
+    /// with an exit code of [crate::ExitCode::SystemSplit] and the fault in
+    /// [crate::Session::fault]. [crate::ProverServer::prove_fault] proves that
+    /// the guest faulted.
+    ///
+    /// Programs run by the guest with `env::call` fail on a fault either way.
+    pub fn split_on_fault(&mut self, split_on_fault: bool) -> &mut Self {
+        self.inner.split_on_fault = split_on_fault;
+        self
+    }
+
+    /// Set a limit, in bytes, on the size of the guest heap.
+    ///
+    /// By default the heap may grow until it reaches the memory reserved by the
+    /// zkVM. A guest allocation that would grow it past this limit ends
+    /// execution with a [crate::GuestOutOfMemory] error, which reports the
+    /// size of the allocation and where it was made.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use risc0_zkvm::ExecutorEnv;
+    ///
+    /// let env = ExecutorEnv::builder()
+    ///     .heap_limit(Some(64 * 1024 * 1024)) // 64 MiB
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn heap_limit(&mut self, limit: Option<u32>) -> &mut Self {
+        self.inner.heap_limit = limit;
+        self
+    }
+
+    /// Provide the host's wall-clock time to the guest.
+    ///
+    /// When enabled, the guest can read the time with
+    /// [crate::guest::env::host_timestamp]. The time is asserted by the host
+    /// and is not checked by the proof.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use risc0_zkvm::ExecutorEnv;
+    ///
+    /// let env = ExecutorEnv::builder()
+    ///     .wall_clock(true)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn wall_clock(&mut self, enable: bool) -> &mut Self {
+        self.inner.wall_clock = enable;
+        self
+    }
+
     /// Add environment variables to the guest environment.
     ///
     /// # Example
@@ -191,6 +306,9 @@ impl<'a> ExecutorEnvBuilder<'a> {
 
     /// Add an argument array to the guest environment.
     ///
+    /// A guest with `std` reads these with `std::env::args`. As on other
+    /// platforms, the first argument is conventionally the program name.
+    ///
     /// # Example
     /// ```
     /// # use risc0_zkvm::ExecutorEnv;
@@ -207,6 +325,8 @@ impl<'a> ExecutorEnvBuilder<'a> {
 
     /// Add an environment variable to the guest environment.
     ///
+    /// A guest with `std` reads it with `std::env::var`.
+    ///
     /// # Example
     ///
     /// ```
@@ -307,6 +427,50 @@ impl<'a> ExecutorEnvBuilder<'a> {
         self
     }
 
+    /// Add a posix-style file descriptor for reading, writing and seeking.
+    ///
+    /// The file may be any host object with file semantics, such as a
+    /// [std::fs::File] or, for an in-memory file, a [Cursor] over a [Vec].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    ///
+    /// use risc0_zkvm::ExecutorEnv;
+    ///
+    /// let env = ExecutorEnv::builder()
+    ///     .file_fd(3, Cursor::new(b"contents".to_vec()))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn file_fd(&mut self, fd: u32, file: impl Read + Write + Seek + 'a) -> &mut Self {
+        self.inner.posix_io.borrow_mut().with_file(fd, file);
+        self
+    }
+
+    /// Add a posix-style file descriptor that reads from `reader` and writes
+    /// to `writer`, like a socket or a pair of pipes.
+    pub fn duplex_fd(
+        &mut self,
+        fd: u32,
+        reader: impl Read + 'a,
+        writer: impl Write + 'a,
+    ) -> &mut Self {
+        self.inner
+            .posix_io
+            .borrow_mut()
+            .with_read_fd(fd, reader)
+            .with_write_fd(fd, writer);
+        self
+    }
+
+    /// Add a posix-style file descriptor that is always at end of file when
+    /// read, and that discards data written to it.
+    pub fn null_fd(&mut self, fd: u32) -> &mut Self {
+        self.duplex_fd(fd, std::io::empty(), std::io::sink())
+    }
+
     /// Add a handler for simple I/O handling.
     pub fn slice_io(&mut self, channel: &str, handler: impl SliceIo + 'a) -> &mut Self {
         self.inner
@@ -349,6 +513,51 @@ impl<'a> ExecutorEnvBuilder<'a> {
         self
     }
 
+    /// Register a guest program that can be run by the guest using `env::call`.
+    ///
+    /// The program is keyed by its image ID. When the guest calls it, the
+    /// program is executed with the same limits, environment variables and
+    /// file descriptors as the caller, reading the input sent by the guest on
+    /// stdin, and the resulting claim is added as an assumption. The cycles of
+    /// the called program count towards the session limit of the caller.
+    /// Programs registered here are also available to the programs they call,
+    /// up to a depth of eight nested calls.
+    pub fn add_program(&mut self, elf: &[u8]) -> Result<&mut Self> {
+        let image_id = risc0_binfmt::compute_image_id(elf)?;
+        self.inner
+            .programs
+            .insert(image_id, Bytes::copy_from_slice(elf));
+        Ok(self)
+    }
+
+    /// Register a file that the guest can read with
+    /// [crate::guest::verified_file::VerifiedFile].
+    ///
+    /// The file is keyed by its [crate::merkle_file_root], which the guest
+    /// uses to open it. Reads in the guest are checked against that root.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use risc0_zkvm::{merkle_file_root, ExecutorEnv};
+    ///
+    /// let data = vec![0u8; 10_000];
+    /// let root = merkle_file_root(&data);
+    /// let env = ExecutorEnv::builder()
+    ///     .add_verified_file(data)
+    ///     .write(&root)
+    ///     .unwrap()
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn add_verified_file(&mut self, data: impl Into<Bytes>) -> &mut Self {
+        let data = data.into();
+        self.inner
+            .verified_files
+            .insert(crate::merkle_file_root(&data), data);
+        self
+    }
+
     /// Add a callback handler for raw trace messages.
     pub fn trace_callback(&mut self, callback: impl TraceCallback + 'a) -> &mut Self {
         self.inner.trace.push(Rc::new(RefCell::new(callback)));
@@ -356,17 +565,44 @@ impl<'a> ExecutorEnvBuilder<'a> {
     }
 
     /// Set the path where segments will be stored.
+    ///
+    /// This cannot be combined with a segment store.
     pub fn segment_path<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
         self.inner.segment_path = Some(SegmentPath::Path(path.as_ref().to_path_buf()));
         self
     }
 
+    /// Set the [SegmentStore](crate::SegmentStore) where segments will be
+    /// stored, in place of the segment path.
+    ///
+    /// This cannot be combined with a segment path.
+    #[cfg(feature = "execute")]
+    pub fn segment_store(&mut self, store: Arc<dyn crate::SegmentStore>) -> &mut Self {
+        self.inner.segment_store = Some(store);
+        self
+    }
+
+    /// Set the [ReceiptStore](crate::ReceiptStore) where the prover will
+    /// store each segment receipt of the resulting [crate::Session].
+    #[cfg(feature = "execute")]
+    pub fn receipt_store(&mut self, store: Arc<dyn crate::ReceiptStore>) -> &mut Self {
+        self.inner.receipt_store = Some(store);
+        self
+    }
+
     /// Enable the profiler and output results to the specified path.
     pub fn enable_profiler<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
         self.inner.pprof_out = Some(path.as_ref().to_path_buf());
         self
     }
 
+    /// Enable the profiler and output its samples to the specified path as
+    /// folded stacks, which flamegraph tools render.
+    pub fn enable_flamegraph<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+        self.inner.flamegraph_out = Some(path.as_ref().to_path_buf());
+        self
+    }
+
     /// Set the input digest.
     pub fn input_digest(&mut self, digest: Digest) -> &mut Self {
         self.inner.input_digest = Some(digest);
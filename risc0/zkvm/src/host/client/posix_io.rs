@@ -15,17 +15,21 @@
 use std::{
     cell::RefCell,
     collections::BTreeMap,
-    io::{stderr, stdout, BufRead, Cursor, Write},
+    io::{stderr, stdout, Cursor, Read, Seek, Write},
     rc::Rc,
 };
 
 use risc0_zkvm_platform::fileno;
 
 /// Posix-style I/O
+///
+/// Each file descriptor is backed by a reader, a writer, or both. A file
+/// descriptor registered with [PosixIo::with_file] is also seekable.
 #[derive(Clone)]
 pub struct PosixIo<'a> {
-    pub(crate) read_fds: BTreeMap<u32, Rc<RefCell<dyn BufRead + 'a>>>,
+    pub(crate) read_fds: BTreeMap<u32, Rc<RefCell<dyn Read + 'a>>>,
     pub(crate) write_fds: BTreeMap<u32, Rc<RefCell<dyn Write + 'a>>>,
+    pub(crate) seek_fds: BTreeMap<u32, Rc<RefCell<dyn Seek + 'a>>>,
 }
 
 impl<'a> Default for PosixIo<'a> {
@@ -33,6 +37,7 @@ impl<'a> Default for PosixIo<'a> {
         let mut new = Self {
             read_fds: Default::default(),
             write_fds: Default::default(),
+            seek_fds: Default::default(),
         };
         new.with_read_fd(fileno::STDIN, Cursor::new(vec![]))
             .with_write_fd(fileno::STDOUT, stdout())
@@ -42,13 +47,23 @@ impl<'a> Default for PosixIo<'a> {
 }
 
 impl<'a> PosixIo<'a> {
-    pub fn with_read_fd(&mut self, fd: u32, reader: impl BufRead + 'a) -> &mut Self {
+    pub fn with_read_fd(&mut self, fd: u32, reader: impl Read + 'a) -> &mut Self {
         self.read_fds.insert(fd, Rc::new(RefCell::new(reader)));
+        self.seek_fds.remove(&fd);
         self
     }
 
     pub fn with_write_fd(&mut self, fd: u32, writer: impl Write + 'a) -> &mut Self {
         self.write_fds.insert(fd, Rc::new(RefCell::new(writer)));
+        self.seek_fds.remove(&fd);
+        self
+    }
+
+    pub fn with_file(&mut self, fd: u32, file: impl Read + Write + Seek + 'a) -> &mut Self {
+        let file = Rc::new(RefCell::new(file));
+        self.read_fds.insert(fd, file.clone());
+        self.write_fds.insert(fd, file.clone());
+        self.seek_fds.insert(fd, file);
         self
     }
 }
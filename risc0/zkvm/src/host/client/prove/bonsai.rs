@@ -15,12 +15,15 @@
 use std::time::Duration;
 
 use anyhow::{anyhow, bail, ensure, Context, Result};
-use bonsai_sdk::alpha::Client;
+use bonsai_sdk::alpha::{Client, SessionId};
 
-use super::Prover;
+use super::{
+    remote::{proven_assumptions, verify_remote_receipt, RemoteProver, RemoteStatus},
+    Prover,
+};
 use crate::{
-    compute_image_id, is_dev_mode, sha::Digestible, CompactReceipt, ExecutorEnv, InnerReceipt,
-    ProveInfo, ProverOpts, Receipt, ReceiptKind, VerifierContext,
+    compute_image_id, is_dev_mode, CompactReceipt, ExecutorEnv, InnerReceipt, ProveInfo,
+    ProverOpts, Receipt, ReceiptKind, VerifierContext,
 };
 
 /// An implementation of a [Prover] that runs proof workloads via Bonsai.
@@ -40,101 +43,132 @@ impl BonsaiProver {
     }
 }
 
-impl Prover for BonsaiProver {
+impl BonsaiProver {
+    fn client(&self) -> Result<Client> {
+        let client = Client::from_env(crate::VERSION)?;
+        #[cfg(feature = "otel")]
+        let client = client.with_trace_context(&crate::current_trace_context());
+        Ok(client)
+    }
+}
+
+impl RemoteProver for BonsaiProver {
     fn get_name(&self) -> String {
         self.name.clone()
     }
 
-    fn prove_with_ctx(
+    fn submit(
         &self,
-        env: ExecutorEnv<'_>,
-        ctx: &VerifierContext,
         elf: &[u8],
-        opts: &ProverOpts,
-    ) -> Result<ProveInfo> {
-        let client = Client::from_env(crate::VERSION)?;
+        input: &[u8],
+        assumptions: &[Receipt],
+        _opts: &ProverOpts,
+    ) -> Result<String> {
+        let client = self.client()?;
 
         // Compute the ImageID and upload the ELF binary
-        let image_id = compute_image_id(elf)?;
-        let image_id_hex = hex::encode(image_id);
+        let image_id_hex = hex::encode(compute_image_id(elf)?);
         client.upload_img(&image_id_hex, elf.to_vec())?;
 
         // upload input data
-        let input_id = client.upload_input(env.input)?;
+        let input_id = client.upload_input(input.to_vec())?;
 
         // upload receipts
-        let mut receipts_ids: Vec<String> = vec![];
-        for assumption in &env.assumptions.borrow().cached {
-            let serialized_receipt = match assumption {
-                crate::Assumption::Proven(receipt) => bincode::serialize(receipt)?,
-                crate::Assumption::Unresolved(_) => bail!("Only proven receipts can be uploaded."), //TODO: improve the message
-            };
-            let receipt_id = client.upload_receipt(serialized_receipt)?;
-            receipts_ids.push(receipt_id);
-        }
+        let receipt_ids = assumptions
+            .iter()
+            .map(|receipt| Ok(client.upload_receipt(bincode::serialize(receipt)?)?))
+            .collect::<Result<Vec<_>>>()?;
 
-        // While this is the executor, we want to start a session on the bonsai prover.
-        // By doing so, we can return a session ID so that the prover can use it to
-        // retrieve the receipt.
-        let session = client.create_session(image_id_hex, input_id, receipts_ids)?;
-        tracing::debug!("Bonsai proving SessionID: {}", session.uuid);
+        Ok(client
+            .create_session(image_id_hex, input_id, receipt_ids)?
+            .uuid)
+    }
 
-        let succinct_prove_info = loop {
-            // The session has already been started in the executor. Poll bonsai to check if
-            // the proof request succeeded.
-            let res = session.status(&client)?;
-            if res.status == "RUNNING" {
-                // TODO(#1759): Improve upon this polling solution.
-                std::thread::sleep(Duration::from_secs(5));
-                continue;
-            }
-            if res.status == "SUCCEEDED" {
-                // Download the receipt, containing the output
-                let receipt_url = res
-                    .receipt_url
-                    .ok_or(anyhow!("API error, missing receipt on completed session"))?;
-
-                let stats = res
-                    .stats
-                    .context("Missing stats object on Bonsai status res")?;
-                tracing::debug!(
-                    "Bonsai usage: cycles: {} total_cycles: {}",
-                    stats.cycles,
-                    stats.total_cycles
-                );
+    fn status(&self, job_id: &str) -> Result<RemoteStatus> {
+        let res = SessionId::new(job_id.to_string()).status(&self.client()?)?;
+        Ok(match res.status.as_str() {
+            "RUNNING" => RemoteStatus::Running { stage: res.state },
+            "SUCCEEDED" => RemoteStatus::Succeeded,
+            status => RemoteStatus::Failed {
+                error: format!(
+                    "{status} err: {}",
+                    res.error_msg
+                        .unwrap_or("Bonsai workflow missing error_msg".into())
+                ),
+            },
+        })
+    }
 
-                let receipt_buf = client.download(&receipt_url)?;
-                let receipt: Receipt = bincode::deserialize(&receipt_buf)?;
+    fn fetch_receipt(&self, job_id: &str) -> Result<ProveInfo> {
+        let client = self.client()?;
+        let res = SessionId::new(job_id.to_string()).status(&client)?;
+        // Download the receipt, containing the output
+        let receipt_url = res
+            .receipt_url
+            .ok_or(anyhow!("API error, missing receipt on completed session"))?;
+        let stats = res
+            .stats
+            .context("Missing stats object on Bonsai status res")?;
+        tracing::debug!(
+            "Bonsai usage: cycles: {} total_cycles: {}",
+            stats.cycles,
+            stats.total_cycles
+        );
 
-                if opts.prove_guest_errors {
-                    receipt.verify_integrity_with_context(ctx)?;
-                    ensure!(
-                        receipt.claim()?.pre.digest() == image_id,
-                        "received unexpected image ID: expected {}, found {}",
-                        hex::encode(image_id),
-                        hex::encode(receipt.claim()?.pre.digest())
-                    );
-                } else {
-                    receipt.verify_with_context(ctx, image_id)?;
+        let receipt_buf = client.download(&receipt_url)?;
+        Ok(ProveInfo {
+            receipt: bincode::deserialize(&receipt_buf)?,
+            stats: crate::SessionStats {
+                segments: stats.segments,
+                total_cycles: stats.total_cycles,
+                user_cycles: stats.cycles,
+                usage: None,
+                cost: None,
+            },
+        })
+    }
+
+    fn cancel(&self, job_id: &str) -> Result<()> {
+        Ok(SessionId::new(job_id.to_string()).stop(&self.client()?)?)
+    }
+}
+
+impl Prover for BonsaiProver {
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn prove_with_ctx(
+        &self,
+        env: ExecutorEnv<'_>,
+        ctx: &VerifierContext,
+        elf: &[u8],
+        opts: &ProverOpts,
+    ) -> Result<ProveInfo> {
+        ensure!(
+            opts.receipt_kind != ReceiptKind::Plonk,
+            "BonsaiProver does not support PlonkReceipt"
+        );
+        let session_id =
+            self.submit(elf, &env.input, &proven_assumptions(&env, "Bonsai")?, opts)?;
+        tracing::debug!("Bonsai proving SessionID: {session_id}");
+        loop {
+            // The session has already been started in the executor. Poll bonsai to check if
+            // the proof request succeeded.
+            match self.status(&session_id)? {
+                RemoteStatus::Running { .. } => {
+                    // TODO(#1759): Improve upon this polling solution.
+                    std::thread::sleep(self.poll_interval());
+                }
+                RemoteStatus::Succeeded => break,
+                RemoteStatus::Failed { error } => {
+                    bail!("Bonsai prover workflow [{session_id}] exited: {error}")
                 }
-                break ProveInfo {
-                    receipt,
-                    stats: crate::SessionStats {
-                        segments: stats.segments,
-                        total_cycles: stats.total_cycles,
-                        user_cycles: stats.cycles,
-                    },
-                };
-            } else {
-                bail!(
-                    "Bonsai prover workflow [{}] exited: {} err: {}",
-                    session.uuid,
-                    res.status,
-                    res.error_msg
-                        .unwrap_or("Bonsai workflow missing error_msg".into()),
-                );
             }
-        };
+        }
+        let succinct_prove_info = self.fetch_receipt(&session_id)?;
+        verify_remote_receipt(&succinct_prove_info.receipt, ctx, elf, opts)?;
+
         match opts.receipt_kind {
             // If the caller requested a composite or succinct receipt, we are done.
             ReceiptKind::Composite | ReceiptKind::Succinct => {
@@ -142,10 +176,12 @@ impl Prover for BonsaiProver {
             }
             // If they requested a compact receipts, we need to continue.
             ReceiptKind::Compact => {}
+            ReceiptKind::Plonk => unreachable!("checked before proving"),
         }
 
         // Request that Bonsai compress further, to Groth16.
-        let snark_session = client.create_snark(session.uuid)?;
+        let client = self.client()?;
+        let snark_session = client.create_snark(session_id)?;
         let snark_receipt = loop {
             let res = snark_session.status(&client)?;
             match res.status.as_str() {
@@ -205,9 +241,10 @@ impl Prover for BonsaiProver {
             (InnerReceipt::Composite(_), ReceiptKind::Composite)
             | (InnerReceipt::Succinct(_), ReceiptKind::Composite | ReceiptKind::Succinct)
             | (
-                InnerReceipt::Compact(_),
-                ReceiptKind::Composite | ReceiptKind::Succinct | ReceiptKind::Compact,
-            ) => Ok(receipt.clone()),
+                InnerReceipt::Plonk(_),
+                ReceiptKind::Composite | ReceiptKind::Succinct | ReceiptKind::Plonk,
+            )
+            | (InnerReceipt::Compact(_), _) => Ok(receipt.clone()),
             // Compression is always a no-op in dev mode
             (InnerReceipt::Fake { .. }, _) => {
                 ensure!(
@@ -221,6 +258,9 @@ impl Prover for BonsaiProver {
             (_, ReceiptKind::Succinct) => {
                 bail!("BonsaiProver does not support compression on existing receipts");
             }
+            (_, ReceiptKind::Plonk) => {
+                bail!("BonsaiProver does not support PlonkReceipt");
+            }
             (_, ReceiptKind::Compact) => {
                 // Caller is requesting a CompactReceipt. Provide a hint on how to get one.
                 bail!([
@@ -82,8 +82,8 @@ impl ExternalProver {
                 InnerReceipt::Fake { .. } => bail!(
                     "compressing composite receipts with fake receipt assumptions is not supported"
                 ),
-                InnerReceipt::Compact(_) => bail!(
-                    "compressing composite receipts with Compact receipt assumptions is not supported"
+                InnerReceipt::Compact(_) | InnerReceipt::Plonk(_) => bail!(
+                    "compressing composite receipts with Compact or Plonk receipt assumptions is not supported"
                 ),
             },
         )
@@ -129,9 +129,10 @@ impl Prover for ExternalProver {
             (InnerReceipt::Composite(_), ReceiptKind::Composite)
             | (InnerReceipt::Succinct(_), ReceiptKind::Composite | ReceiptKind::Succinct)
             | (
-                InnerReceipt::Compact(_),
-                ReceiptKind::Composite | ReceiptKind::Succinct | ReceiptKind::Compact,
-            ) => Ok(receipt.clone()),
+                InnerReceipt::Plonk(_),
+                ReceiptKind::Composite | ReceiptKind::Succinct | ReceiptKind::Plonk,
+            )
+            | (InnerReceipt::Compact(_), _) => Ok(receipt.clone()),
             // Compression is always a no-op in dev mode
             (InnerReceipt::Fake { .. }, _) => {
                 ensure!(
@@ -147,12 +148,16 @@ impl Prover for ExternalProver {
                         &client, opts, composite,
                     )?),
                     journal: receipt.journal.clone(),
+                    annotations: receipt.annotations.clone(),
                 })
             }
             (_, ReceiptKind::Compact) => {
                 // TODO(#1760) Support compression to compact receipt in client/server API.
                 bail!("ExternalProver does not support compression to CompactReceipt");
             }
+            (_, ReceiptKind::Plonk) => {
+                bail!("ExternalProver does not support compression to PlonkReceipt");
+            }
         }
     }
 }
@@ -14,15 +14,21 @@
 
 use anyhow::Result;
 
-use super::{Executor, Prover, ProverOpts};
+use super::Executor;
+#[cfg(feature = "prove")]
+use super::{Prover, ProverOpts};
+#[cfg(feature = "prove")]
+use crate::{get_prover_server, ProveInfo, Receipt, VerifierContext};
 use crate::{
-    get_prover_server, host::server::session::NullSegmentRef, ExecutorEnv, ExecutorImpl, ProveInfo,
-    Receipt, SegmentInfo, SessionInfo, VerifierContext,
+    host::server::session::NullSegmentRef, ExecutorEnv, ExecutorImpl, SegmentInfo, SessionInfo,
 };
 
-/// A [Prover] implementation that selects a [crate::ProverServer] by calling
-/// [get_prover_server].
+/// A [Prover](super::Prover) implementation that selects a
+/// [ProverServer](crate::ProverServer) by calling `get_prover_server`.
+///
+/// Without the `prove` feature, this only implements [Executor].
 pub struct LocalProver {
+    #[cfg_attr(not(feature = "prove"), allow(dead_code))]
     name: String,
 }
 
@@ -35,6 +41,7 @@ impl LocalProver {
     }
 }
 
+#[cfg(feature = "prove")]
 impl Prover for LocalProver {
     fn prove_with_ctx(
         &self,
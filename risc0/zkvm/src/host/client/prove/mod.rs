@@ -12,19 +12,30 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "client")]
 pub(crate) mod bonsai;
+#[cfg(feature = "client")]
 pub(crate) mod external;
-#[cfg(feature = "prove")]
+#[cfg(feature = "execute")]
 pub(crate) mod local;
+#[cfg(feature = "client")]
+pub(crate) mod remote;
 
-use std::{path::PathBuf, rc::Rc};
+#[cfg(feature = "client")]
+use std::path::PathBuf;
+use std::rc::Rc;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
 
-use self::{bonsai::BonsaiProver, external::ExternalProver};
+#[cfg(feature = "client")]
+use self::{
+    bonsai::BonsaiProver,
+    external::ExternalProver,
+    remote::{registered_remote_prover, RemoteProverAdapter},
+};
 use crate::{
-    host::prove_info::ProveInfo, is_dev_mode, ExecutorEnv, Receipt, SessionInfo, VerifierContext,
+    host::prove_info::ProveInfo, ExecutorEnv, InnerReceipt, Receipt, SessionInfo, VerifierContext,
 };
 
 /// A Prover can execute a given ELF binary and produce a
@@ -99,14 +110,112 @@ pub trait Prover {
     /// [CompactReceipt](crate::CompactReceipt) is accomplished by running a Groth16 recursive
     /// verifier, refered to as the "STARK-to-SNARK" operation.
     ///
-    /// NOTE: Compression to [CompactReceipt](crate::CompactReceipt) is currently only supported on
-    /// x86 hosts, and requires Docker to be installed. See issue
-    /// [#1749](https://github.com/risc0/risc0/issues/1749) for more information.
+    /// NOTE: Compression to [CompactReceipt](crate::CompactReceipt) uses the native Groth16 prover
+    /// when `RISC0_GROTH16_DIR` names a directory holding the circuit artifacts, which works on any
+    /// host. Otherwise it is only supported on x86 hosts, and requires Docker to be installed. See
+    /// the `prove` module of `risc0-groth16` for more information.
     ///
     /// If the receipt is already at least as compressed as the requested compression level (e.g.
     /// it is already succinct or compact and a succinct receipt is required) this function is a
     /// no-op. As a result, it is idempotent.
     fn compress(&self, opts: &ProverOpts, receipt: &Receipt) -> Result<Receipt>;
+
+    /// Compress a [Receipt] as far as needed to satisfy the given [CompressionPolicy].
+    ///
+    /// Starting from the policy's target [ReceiptKind], this calls [Prover::compress] with
+    /// successively smaller kinds until the result satisfies the policy, running the lift, join,
+    /// resolve and identity_p254 steps as each transformation requires. Kinds whose hash function
+    /// the policy does not allow are skipped, so a Groth16 receipt is not produced unless the
+    /// policy allows `"groth16"`.
+    ///
+    /// ```no_run
+    /// use risc0_zkvm::{default_prover, CompressionPolicy, ReceiptKind};
+    /// # use risc0_zkvm::Receipt;
+    /// # let receipt: Receipt = unimplemented!();
+    ///
+    /// // Any receipt of at most 256 KiB that does not rely on a trusted setup.
+    /// let policy = CompressionPolicy::new(ReceiptKind::Composite)
+    ///     .with_max_seal_size(256 * 1024)
+    ///     .with_allowed_hashfns(["poseidon2", "sha-256"]);
+    /// let receipt = default_prover().compress_with_policy(&policy, &receipt).unwrap();
+    /// ```
+    fn compress_with_policy(
+        &self,
+        policy: &CompressionPolicy,
+        receipt: &Receipt,
+    ) -> Result<Receipt> {
+        let mut receipt = receipt.clone();
+        let mut rejected = Vec::new();
+        for kind in ReceiptKind::ALL
+            .into_iter()
+            .filter(|kind| *kind >= policy.target)
+        {
+            if !policy.allows_kind(kind) {
+                continue;
+            }
+            let compressed =
+                self.compress(&ProverOpts::default().with_receipt_kind(kind), &receipt)?;
+            match policy.check(&compressed) {
+                Ok(()) => return Ok(compressed),
+                Err(reason) => rejected.push(reason),
+            }
+            // A PLONK receipt cannot be compressed further, so the next kind starts from the
+            // receipt it was made from.
+            if !matches!(compressed.inner, InnerReceipt::Plonk(_)) {
+                receipt = compressed;
+            }
+        }
+        bail!(
+            "no receipt kind satisfies the compression policy: {}",
+            rejected.join("; ")
+        )
+    }
+
+    /// Aggregate the receipts of unrelated sessions, with any image IDs, into one succinct
+    /// [Receipt].
+    ///
+    /// The join program only joins the receipts of consecutive segments of one session, so
+    /// receipts are aggregated by proving `aggregator_elf`, a guest that calls
+    /// `env::aggregate`, with each receipt added as an assumption. The `risc0-zkvm-aggregator`
+    /// crate ships such a guest as `AGGREGATOR_ELF`, and its `aggregate` function proves it with
+    /// the [default_prover]. Compressing its receipt
+    /// resolves the assumptions, so the result is unconditional. Its journal is the list of the
+    /// [ReceiptClaim](crate::ReceiptClaim) digests of the aggregated receipts, in order, which
+    /// decodes as a `Vec<Digest>`.
+    ///
+    /// Every receipt must have an unpruned claim with no assumptions. A receipt of any exit code
+    /// can be aggregated, and its exit code is part of its claim.
+    ///
+    /// ```no_run
+    /// use risc0_zkvm::{default_prover, sha::{Digest, Digestible}};
+    /// use risc0_zkvm_aggregator::{AGGREGATOR_ELF, AGGREGATOR_ID};
+    /// # use risc0_zkvm::Receipt;
+    /// # let receipts: Vec<Receipt> = unimplemented!();
+    ///
+    /// let receipt = default_prover().aggregate(AGGREGATOR_ELF, &receipts).unwrap();
+    /// receipt.verify(AGGREGATOR_ID).unwrap();
+    /// let claims: Vec<Digest> = receipt.journal.decode().unwrap();
+    /// assert_eq!(claims[0], receipts[0].claim().unwrap().digest());
+    /// ```
+    fn aggregate(&self, aggregator_elf: &[u8], receipts: &[Receipt]) -> Result<Receipt> {
+        let claims = receipts
+            .iter()
+            .map(Receipt::claim)
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut env = ExecutorEnv::builder();
+        env.write(&claims)?;
+        for receipt in receipts {
+            env.add_assumption(receipt.clone());
+        }
+        let opts = ProverOpts::default().with_receipt_kind(ReceiptKind::Succinct);
+        let info = self.prove_with_ctx(
+            env.build()?,
+            &VerifierContext::default(),
+            aggregator_elf,
+            &opts,
+        )?;
+        Ok(info.receipt)
+    }
 }
 
 /// An Executor can execute a given ELF binary.
@@ -126,7 +235,14 @@ pub struct ProverOpts {
     /// When false, only prove execution sessions that end in a successful
     /// [crate::ExitCode] (i.e. `Halted(0)` or `Paused(0)`).
     /// When set to true, any completed execution session will be proven, including indicated
-    /// errors (e.g. `Halted(1)`) and sessions ending in `Fault`.
+    /// errors (e.g. `Halted(1)`).
+    ///
+    /// A guest fault, such as an illegal instruction or a misaligned access, does not complete
+    /// the session: execution fails with a [crate::Trap] error, unless the session is run with
+    /// [ExecutorEnvBuilder::split_on_fault](crate::ExecutorEnvBuilder::split_on_fault), which
+    /// ends it at the faulting instruction. The fault is then proven as a
+    /// [FaultReceipt](crate::FaultReceipt), by
+    /// [ProverServer::prove_fault](crate::ProverServer::prove_fault), whatever this option is.
     // TODO(1.0): This option exists to avoid wasting resourcing proving a guest that exited with
     // Fault or another abnormal exit conditional. It is currently not consistently enforced, and
     // it seems like this might not be the right place for this option and it's not clear at the
@@ -138,7 +254,9 @@ pub struct ProverOpts {
 }
 
 /// An enumeration of receipt kinds that can be requested to be generated.
-#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+///
+/// Kinds are ordered from the least to the most compressed.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 #[non_exhaustive]
 pub enum ReceiptKind {
     /// Request that a [CompositeReceipt][crate::CompositeReceipt] be generated.
@@ -150,6 +268,13 @@ pub enum ReceiptKind {
     ///
     /// Succinct receipts are constant in size, with respect to the execution length.
     Succinct,
+    /// Request that a [PlonkReceipt][crate::PlonkReceipt] be generated.
+    ///
+    /// PLONK receipts wrap a succinct receipt in a PLONK proof, which has a universal setup rather
+    /// than one specific to the circuit, and can be verified on chain. They are constant in size,
+    /// and a few times larger than compact receipts. A PLONK receipt cannot be compressed into a
+    /// compact receipt.
+    Plonk,
     /// Request that a [CompactReceipt][crate::CompactReceipt] be generated.
     ///
     /// Compact receipts are proven using Groth16, are constant in size, and are the smallest
@@ -157,6 +282,105 @@ pub enum ReceiptKind {
     Compact,
 }
 
+impl ReceiptKind {
+    const ALL: [Self; 4] = [Self::Composite, Self::Succinct, Self::Plonk, Self::Compact];
+}
+
+/// A declarative description of an acceptable receipt, for [Prover::compress_with_policy].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct CompressionPolicy {
+    /// The least compressed [ReceiptKind] that is acceptable.
+    pub target: ReceiptKind,
+    /// The largest acceptable seal, in bytes, as returned by [InnerReceipt::seal_size]. A receipt
+    /// with a larger seal is compressed further. When `None`, any size is acceptable.
+    pub max_seal_size: Option<usize>,
+    /// The hash functions that the receipt may rely on, such as `"poseidon2"` or `"sha-256"`.
+    /// Succinct receipts rely on `"poseidon2"`, and compact receipts on `"groth16"`. When
+    /// `None`, any hash function is acceptable.
+    pub allowed_hashfns: Option<Vec<String>>,
+}
+
+impl CompressionPolicy {
+    /// Construct a policy that accepts any receipt at least as compressed as `target`.
+    pub fn new(target: ReceiptKind) -> Self {
+        Self {
+            target,
+            max_seal_size: None,
+            allowed_hashfns: None,
+        }
+    }
+
+    /// Return [CompressionPolicy] with max_seal_size set to the given value.
+    pub fn with_max_seal_size(mut self, max_seal_size: usize) -> Self {
+        self.max_seal_size = Some(max_seal_size);
+        self
+    }
+
+    /// Return [CompressionPolicy] with allowed_hashfns set to the given values.
+    pub fn with_allowed_hashfns(
+        mut self,
+        hashfns: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.allowed_hashfns = Some(hashfns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    fn allows_hashfn(&self, hashfn: &str) -> bool {
+        match &self.allowed_hashfns {
+            Some(allowed) => allowed.iter().any(|x| x == hashfn),
+            None => true,
+        }
+    }
+
+    // Whether compressing to the given kind could produce an acceptable receipt. A composite
+    // receipt keeps the hash functions it was proven with, so it can only be checked once proven.
+    fn allows_kind(&self, kind: ReceiptKind) -> bool {
+        match kind {
+            ReceiptKind::Composite => true,
+            ReceiptKind::Succinct => self.allows_hashfn("poseidon2"),
+            ReceiptKind::Plonk => self.allows_hashfn("plonk"),
+            ReceiptKind::Compact => self.allows_hashfn("groth16"),
+        }
+    }
+
+    /// Check that the receipt satisfies this policy, returning the reason if it does not.
+    ///
+    /// Fake receipts satisfy every policy.
+    pub fn check(&self, receipt: &Receipt) -> Result<(), String> {
+        let kind = match &receipt.inner {
+            InnerReceipt::Composite(_) => ReceiptKind::Composite,
+            InnerReceipt::Succinct(_) => ReceiptKind::Succinct,
+            InnerReceipt::Plonk(_) => ReceiptKind::Plonk,
+            InnerReceipt::Compact(_) => ReceiptKind::Compact,
+            InnerReceipt::Fake { .. } => return Ok(()),
+        };
+        if kind < self.target {
+            return Err(format!(
+                "{kind:?} receipt is less compressed than {:?}",
+                self.target
+            ));
+        }
+        let size = receipt.inner.seal_size();
+        if let Some(max) = self.max_seal_size {
+            if size > max {
+                return Err(format!("{kind:?} seal of {size} bytes exceeds {max} bytes"));
+            }
+        }
+        if let Some(hashfn) = receipt
+            .inner
+            .hashfns()
+            .into_iter()
+            .find(|hashfn| !self.allows_hashfn(hashfn))
+        {
+            return Err(format!(
+                "{kind:?} receipt relies on {hashfn}, which is not allowed"
+            ));
+        }
+        Ok(())
+    }
+}
+
 impl Default for ProverOpts {
     /// Return [ProverOpts] with the Poseidon2 hash function and
     /// `prove_guest_errors` set to false.
@@ -197,7 +421,10 @@ impl ProverOpts {
         }
     }
 
-    /// Choose the prover that enables compact, snark receipts, only supported for x86_64 linux
+    /// Choose the prover that enables compact, snark receipts.
+    ///
+    /// Without the native Groth16 artifacts configured by `RISC0_GROTH16_DIR`, this is only
+    /// supported for x86_64 linux.
     pub fn compact() -> Self {
         Self {
             hashfn: "poseidon2".to_string(),
@@ -206,6 +433,18 @@ impl ProverOpts {
         }
     }
 
+    /// Choose the prover that enables PLONK receipts, which need no trusted setup specific to the
+    /// circuit.
+    ///
+    /// This requires the PLONK circuit artifacts configured by `RISC0_PLONK_DIR`.
+    pub fn plonk() -> Self {
+        Self {
+            hashfn: "poseidon2".to_string(),
+            prove_guest_errors: false,
+            receipt_kind: ReceiptKind::Plonk,
+        }
+    }
+
     /// Return [ProverOpts] with the hashfn set to the given value.
     pub fn with_hashfn(mut self, hashfn: String) -> Self {
         self.hashfn = hashfn;
@@ -234,6 +473,9 @@ impl ProverOpts {
 ///   requires the `prove` feature flag.
 /// * `ipc`: [ExternalProver] to prove using an `r0vm` sub-process. Note: `r0vm`
 ///   must be installed. To specify the path to `r0vm`, use `RISC0_SERVER_PATH`.
+/// * The name of a [RemoteProver](crate::RemoteProver) registered with
+///   [register_remote_prover](crate::register_remote_prover), to prove with a
+///   third-party proving service.
 ///
 /// If `RISC0_PROVER` is not specified, the following rules are used to select a
 /// [Prover]:
@@ -241,6 +483,7 @@ impl ProverOpts {
 ///   variables are set unless `RISC0_DEV_MODE` is enabled.
 /// * LocalProver if the `prove` feature flag is enabled.
 /// * [ExternalProver] otherwise.
+#[cfg(feature = "client")]
 pub fn default_prover() -> Rc<dyn Prover> {
     let explicit = std::env::var("RISC0_PROVER").unwrap_or_default();
     if !explicit.is_empty() {
@@ -249,11 +492,16 @@ pub fn default_prover() -> Rc<dyn Prover> {
             "ipc" => Rc::new(ExternalProver::new("ipc", get_r0vm_path())),
             #[cfg(feature = "prove")]
             "local" => Rc::new(self::local::LocalProver::new("local")),
-            _ => unimplemented!("Unsupported prover: {explicit}"),
+            name => match registered_remote_prover(name) {
+                Some(remote) => Rc::new(RemoteProverAdapter::new(remote.unwrap_or_else(|err| {
+                    panic!("failed to construct the {name} prover: {err:#}")
+                }))),
+                None => unimplemented!("Unsupported prover: {explicit}"),
+            },
         };
     }
 
-    if !is_dev_mode()
+    if !crate::is_dev_mode()
         && std::env::var("BONSAI_API_URL").is_ok()
         && std::env::var("BONSAI_API_KEY").is_ok()
     {
@@ -274,34 +522,35 @@ pub fn default_prover() -> Rc<dyn Prover> {
 /// The `RISC0_EXECUTOR` environment variable, if specified, will select the
 /// following [Executor] implementation:
 /// * `local`: LocalProver to execute locally in-process. Note: this is
-///   only available when the `prove` feature is enabled.
-/// * `ipc`: [ExternalProver] to execute using an `r0vm` sub-process. Note:
+///   only available when the `execute` feature is enabled.
+/// * `ipc`: ExternalProver to execute using an `r0vm` sub-process. Note:
 ///   `r0vm` must be installed. To specify the path to `r0vm`, use
-///   `RISC0_SERVER_PATH`.
+///   `RISC0_SERVER_PATH`. This requires the `client` feature flag.
 ///
 /// If `RISC0_EXECUTOR` is not specified, the following rules are used to select
 /// an [Executor]:
-/// * LocalProver if the `prove` feature flag is enabled.
-/// * [ExternalProver] otherwise.
+/// * LocalProver if the `execute` feature flag is enabled.
+/// * ExternalProver otherwise.
 pub fn default_executor() -> Rc<dyn Executor> {
     let explicit = std::env::var("RISC0_EXECUTOR").unwrap_or_default();
     if !explicit.is_empty() {
         return match explicit.to_lowercase().as_str() {
+            #[cfg(feature = "client")]
             "ipc" => Rc::new(ExternalProver::new("ipc", get_r0vm_path())),
-            #[cfg(feature = "prove")]
+            #[cfg(feature = "execute")]
             "local" => Rc::new(self::local::LocalProver::new("local")),
             _ => unimplemented!("Unsupported executor: {explicit}"),
         };
     }
 
-    if cfg!(feature = "prove") {
-        #[cfg(feature = "prove")]
-        return Rc::new(self::local::LocalProver::new("local"));
-    }
+    #[cfg(feature = "execute")]
+    return Rc::new(self::local::LocalProver::new("local"));
 
+    #[cfg(not(feature = "execute"))]
     Rc::new(ExternalProver::new("ipc", get_r0vm_path()))
 }
 
+#[cfg(feature = "client")]
 pub(crate) fn get_r0vm_path() -> PathBuf {
     std::env::var("RISC0_SERVER_PATH")
         .unwrap_or("r0vm".to_string())
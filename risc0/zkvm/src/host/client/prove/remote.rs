@@ -0,0 +1,296 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{rc::Rc, sync::Mutex, thread, time::Duration};
+
+use anyhow::{bail, ensure, Result};
+
+use super::{Prover, ProverOpts, ReceiptKind};
+use crate::{
+    compute_image_id, is_dev_mode, sha::Digestible, Assumption, ExecutorEnv, InnerReceipt,
+    ProveInfo, Receipt, VerifierContext,
+};
+
+/// The status of a job on a [RemoteProver].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RemoteStatus {
+    /// The job is queued or running, at the stage the service reports, if any.
+    Running {
+        /// The stage of the job, such as `ProveSegments: 2/5`.
+        stage: Option<String>,
+    },
+
+    /// The job has finished, and its receipt can be fetched.
+    Succeeded,
+
+    /// The job failed, timed out or was cancelled.
+    Failed {
+        /// The error reported by the service.
+        error: String,
+    },
+}
+
+/// A proving service that runs jobs on behalf of the host.
+///
+/// Implement this trait to integrate a proving service with
+/// [default_prover](super::default_prover): register the implementation with
+/// [register_remote_prover], and applications select it by setting `RISC0_PROVER` to its name,
+/// without changes to their code. [RemoteProverAdapter] turns a [RemoteProver] into a [Prover],
+/// submitting each guest and polling its job until it finishes.
+pub trait RemoteProver {
+    /// Return a name for this [RemoteProver].
+    fn get_name(&self) -> String;
+
+    /// Submit a job proving the given ELF binary with the given input and assumptions, returning
+    /// the ID of the job.
+    fn submit(
+        &self,
+        elf: &[u8],
+        input: &[u8],
+        assumptions: &[Receipt],
+        opts: &ProverOpts,
+    ) -> Result<String>;
+
+    /// Fetch the status of a job.
+    fn status(&self, job_id: &str) -> Result<RemoteStatus>;
+
+    /// Fetch the receipt of a job that has succeeded, along with the stats of its session.
+    fn fetch_receipt(&self, job_id: &str) -> Result<ProveInfo>;
+
+    /// Cancel a job that has not finished.
+    fn cancel(&self, job_id: &str) -> Result<()>;
+
+    /// How long to wait between polls of the status of a job.
+    fn poll_interval(&self) -> Duration {
+        Duration::from_secs(5)
+    }
+}
+
+/// A [Prover] that proves with a [RemoteProver].
+///
+/// Receipts returned by the service are verified before they are returned. Compressing an
+/// existing receipt is not supported, beyond returning receipts that are already compressed
+/// enough.
+pub struct RemoteProverAdapter {
+    remote: Rc<dyn RemoteProver>,
+}
+
+impl RemoteProverAdapter {
+    /// Construct a [RemoteProverAdapter] that proves with the given [RemoteProver].
+    pub fn new(remote: Rc<dyn RemoteProver>) -> Self {
+        Self { remote }
+    }
+}
+
+impl Prover for RemoteProverAdapter {
+    fn get_name(&self) -> String {
+        self.remote.get_name()
+    }
+
+    fn prove_with_ctx(
+        &self,
+        env: ExecutorEnv<'_>,
+        ctx: &VerifierContext,
+        elf: &[u8],
+        opts: &ProverOpts,
+    ) -> Result<ProveInfo> {
+        let name = self.remote.get_name();
+        let assumptions = proven_assumptions(&env, &name)?;
+        let job_id = self.remote.submit(elf, &env.input, &assumptions, opts)?;
+        tracing::debug!("{name} proving job ID: {job_id}");
+        loop {
+            match self.remote.status(&job_id)? {
+                RemoteStatus::Running { stage } => {
+                    tracing::debug!("{name} job {job_id} running: {stage:?}");
+                    thread::sleep(self.remote.poll_interval());
+                }
+                RemoteStatus::Succeeded => break,
+                RemoteStatus::Failed { error } => bail!("{name} job {job_id} failed: {error}"),
+            }
+        }
+
+        let prove_info = self.remote.fetch_receipt(&job_id)?;
+        verify_remote_receipt(&prove_info.receipt, ctx, elf, opts)?;
+        Ok(prove_info)
+    }
+
+    fn compress(&self, opts: &ProverOpts, receipt: &Receipt) -> Result<Receipt> {
+        let kind = match &receipt.inner {
+            InnerReceipt::Composite(_) => ReceiptKind::Composite,
+            InnerReceipt::Succinct(_) => ReceiptKind::Succinct,
+            InnerReceipt::Plonk(_) => ReceiptKind::Plonk,
+            InnerReceipt::Compact(_) => ReceiptKind::Compact,
+            // Compression is always a no-op in dev mode
+            InnerReceipt::Fake { .. } => {
+                ensure!(
+                    is_dev_mode(),
+                    "dev mode must be enabled to compress fake receipts"
+                );
+                return Ok(receipt.clone());
+            }
+        };
+        // Compression is a no-op when the receipt is at least as compressed as requested, except
+        // that a PLONK receipt is never a compact receipt.
+        let compressed = match (kind, opts.receipt_kind) {
+            (ReceiptKind::Plonk, ReceiptKind::Compact) => false,
+            (kind, target) => kind >= target,
+        };
+        if compressed {
+            return Ok(receipt.clone());
+        }
+        bail!(
+            "the {} prover does not support compression on existing receipts",
+            self.remote.get_name()
+        )
+    }
+}
+
+/// The receipts of the assumptions added to `env`, to send to the named remote prover.
+pub(crate) fn proven_assumptions(env: &ExecutorEnv<'_>, name: &str) -> Result<Vec<Receipt>> {
+    env.assumptions
+        .borrow()
+        .cached
+        .iter()
+        .map(|assumption| match assumption {
+            Assumption::Proven(receipt) => Ok(receipt.clone()),
+            Assumption::Unresolved(_) => {
+                bail!("only proven assumptions can be sent to the {name} prover")
+            }
+        })
+        .collect()
+}
+
+/// Verify a receipt returned by a remote prover for the given ELF binary.
+pub(crate) fn verify_remote_receipt(
+    receipt: &Receipt,
+    ctx: &VerifierContext,
+    elf: &[u8],
+    opts: &ProverOpts,
+) -> Result<()> {
+    let image_id = compute_image_id(elf)?;
+    if opts.prove_guest_errors {
+        receipt.verify_integrity_with_context(ctx)?;
+        ensure!(
+            receipt.claim()?.pre.digest() == image_id,
+            "received unexpected image ID: expected {}, found {}",
+            hex::encode(image_id),
+            hex::encode(receipt.claim()?.pre.digest())
+        );
+    } else {
+        receipt.verify_with_context(ctx, image_id)?;
+    }
+    Ok(())
+}
+
+type RemoteProverFactory = Box<dyn Fn() -> Result<Rc<dyn RemoteProver>> + Send + Sync>;
+
+static REMOTE_PROVERS: Mutex<Vec<(String, RemoteProverFactory)>> = Mutex::new(Vec::new());
+
+/// Register a [RemoteProver] under a name, so that [default_prover](super::default_prover) proves
+/// with it when `RISC0_PROVER` is set to that name.
+///
+/// The factory is called each time the prover is selected, such as to read its credentials from
+/// the environment. Registering a name again replaces the earlier registration. The names of the
+/// built-in provers, `bonsai`, `ipc` and `local`, take precedence over registered ones.
+pub fn register_remote_prover(
+    name: &str,
+    factory: impl Fn() -> Result<Rc<dyn RemoteProver>> + Send + Sync + 'static,
+) {
+    let name = name.to_lowercase();
+    let mut provers = REMOTE_PROVERS.lock().unwrap();
+    provers.retain(|(registered, _)| *registered != name);
+    provers.push((name, Box::new(factory)));
+}
+
+/// Construct the [RemoteProver] registered under `name`, if there is one.
+pub(crate) fn registered_remote_prover(name: &str) -> Option<Result<Rc<dyn RemoteProver>>> {
+    let provers = REMOTE_PROVERS.lock().unwrap();
+    provers
+        .iter()
+        .find(|(registered, _)| *registered == name)
+        .map(|(_, factory)| factory())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use anyhow::{bail, Result};
+    use risc0_zkvm_methods::MULTI_TEST_ELF;
+
+    use super::{
+        register_remote_prover, registered_remote_prover, RemoteProver, RemoteProverAdapter,
+        RemoteStatus,
+    };
+    use crate::{ExecutorEnv, ProveInfo, Prover, ProverOpts, Receipt};
+
+    #[derive(Default)]
+    struct FailingProver {
+        polls: Cell<usize>,
+    }
+
+    impl RemoteProver for FailingProver {
+        fn get_name(&self) -> String {
+            "failing".into()
+        }
+
+        fn submit(&self, _: &[u8], _: &[u8], _: &[Receipt], _: &ProverOpts) -> Result<String> {
+            Ok("job-1".into())
+        }
+
+        fn status(&self, job_id: &str) -> Result<RemoteStatus> {
+            assert_eq!(job_id, "job-1");
+            self.polls.set(self.polls.get() + 1);
+            Ok(match self.polls.get() {
+                1 => RemoteStatus::Running { stage: None },
+                _ => RemoteStatus::Failed {
+                    error: "out of quota".into(),
+                },
+            })
+        }
+
+        fn fetch_receipt(&self, _: &str) -> Result<ProveInfo> {
+            bail!("no receipt")
+        }
+
+        fn cancel(&self, _: &str) -> Result<()> {
+            Ok(())
+        }
+
+        fn poll_interval(&self) -> std::time::Duration {
+            std::time::Duration::ZERO
+        }
+    }
+
+    #[test]
+    fn failed_job() {
+        let remote = Rc::new(FailingProver::default());
+        let prover = RemoteProverAdapter::new(remote.clone());
+        let err = prover
+            .prove(ExecutorEnv::default(), MULTI_TEST_ELF)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("out of quota"), "{err}");
+        assert_eq!(remote.polls.get(), 2);
+    }
+
+    #[test]
+    fn registry() {
+        register_remote_prover("Failing", || Ok(Rc::new(FailingProver::default())));
+        let remote = registered_remote_prover("failing").unwrap().unwrap();
+        assert_eq!(remote.get_name(), "failing");
+        assert!(registered_remote_prover("missing").is_none());
+    }
+}
@@ -0,0 +1,187 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Estimates of what a proof cost, from the resources the prover used and a
+//! [RateCard] of prices and power draw supplied by the operator.
+
+use alloc::format;
+use core::time::Duration;
+
+use crate::{ProveInfo, ReceiptAnnotations};
+
+/// Resources used by a local prover to prove a session.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProvingUsage {
+    /// Wall-clock time spent proving.
+    pub wall_time: Duration,
+
+    /// CPU time of the whole prover process while proving, summed over all
+    /// threads.
+    ///
+    /// The prover runs on a pool of worker threads, so this is measured for
+    /// the process rather than the calling thread, and includes any other work
+    /// the process did at the same time. Only measured on Linux.
+    pub cpu_time: Option<Duration>,
+
+    /// Wall-clock time the GPU was held by the prover, for the CUDA and Metal
+    /// provers.
+    ///
+    /// The device is reserved for the whole proof, so this is the same as
+    /// [ProvingUsage::wall_time], not the time spent running kernels.
+    pub gpu_wall_time: Option<Duration>,
+}
+
+/// Prices and power draw of the hardware a prover runs on.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RateCard {
+    /// Price in US dollars of an hour of GPU time.
+    pub usd_per_gpu_hour: f64,
+
+    /// Price in US dollars of an hour of CPU time on one core.
+    pub usd_per_cpu_hour: f64,
+
+    /// Average power drawn by the GPU while proving, in watts.
+    pub gpu_watts: f64,
+
+    /// Average power drawn by one busy CPU core, in watts.
+    pub cpu_watts: f64,
+}
+
+/// The estimated cost and energy of a proof.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProvingCost {
+    /// Cost in US dollars.
+    pub usd: f64,
+
+    /// Energy in joules.
+    pub energy_joules: f64,
+}
+
+impl RateCard {
+    /// Estimate the cost and energy of the given usage.
+    ///
+    /// Without a measured CPU time, the prover is assumed to have kept one
+    /// core busy for the whole proof.
+    pub fn estimate(&self, usage: &ProvingUsage) -> ProvingCost {
+        let gpu_hours = usage.gpu_wall_time.unwrap_or_default().as_secs_f64() / 3600.0;
+        let cpu_hours = usage.cpu_time.unwrap_or(usage.wall_time).as_secs_f64() / 3600.0;
+        ProvingCost {
+            usd: gpu_hours * self.usd_per_gpu_hour + cpu_hours * self.usd_per_cpu_hour,
+            energy_joules: (gpu_hours * self.gpu_watts + cpu_hours * self.cpu_watts) * 3600.0,
+        }
+    }
+}
+
+impl ProvingCost {
+    /// The energy in kilowatt-hours.
+    pub fn energy_kwh(&self) -> f64 {
+        self.energy_joules / 3.6e6
+    }
+
+    /// Record this estimate in the `cost_usd` and `energy_kwh` tags of the
+    /// annotations.
+    pub fn annotate(&self, annotations: ReceiptAnnotations) -> ReceiptAnnotations {
+        annotations
+            .with_tag("cost_usd", format!("{:.6}", self.usd))
+            .with_tag("energy_kwh", format!("{:.6}", self.energy_kwh()))
+    }
+}
+
+impl ProveInfo {
+    /// Estimate the cost of this proof with the given rates, recording it in
+    /// the stats and in the annotations of the receipt.
+    ///
+    /// Nothing is recorded if the prover did not report its usage, as with
+    /// remote provers.
+    pub fn with_cost(mut self, rates: &RateCard) -> Self {
+        if let Some(usage) = &self.stats.usage {
+            let cost = rates.estimate(usage);
+            self.receipt.annotations = cost.annotate(self.receipt.annotations);
+            self.stats.cost = Some(cost);
+        }
+        self
+    }
+}
+
+/// The CPU time used by all threads of this process so far, where the
+/// platform reports it.
+#[cfg(all(feature = "prove", target_os = "linux"))]
+pub(crate) fn process_cpu_time() -> Option<Duration> {
+    // The utime and stime fields of /proc/self/stat, in clock ticks.
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    // SAFETY: sysconf only reads a configuration value.
+    let ticks_per_sec = u64::try_from(unsafe { libc::sysconf(libc::_SC_CLK_TCK) }).ok()?;
+    if ticks_per_sec == 0 {
+        return None;
+    }
+    let ticks = utime + stime;
+    Some(
+        Duration::from_secs(ticks / ticks_per_sec)
+            + Duration::from_secs(ticks % ticks_per_sec) / ticks_per_sec as u32,
+    )
+}
+
+#[cfg(all(feature = "prove", not(target_os = "linux")))]
+pub(crate) fn process_cpu_time() -> Option<Duration> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{ProvingUsage, RateCard};
+    use crate::ReceiptAnnotations;
+
+    #[test]
+    fn estimate() {
+        let rates = RateCard {
+            usd_per_gpu_hour: 2.0,
+            usd_per_cpu_hour: 0.04,
+            gpu_watts: 300.0,
+            cpu_watts: 10.0,
+        };
+        let hour = Duration::from_secs(3600);
+        let usage = ProvingUsage {
+            wall_time: hour / 2,
+            cpu_time: Some(hour),
+            gpu_wall_time: Some(hour / 2),
+        };
+        let cost = rates.estimate(&usage);
+        assert!((cost.usd - 1.04).abs() < 1e-9);
+        assert!((cost.energy_kwh() - 0.16).abs() < 1e-9);
+
+        let annotations = cost.annotate(ReceiptAnnotations::default());
+        assert_eq!(annotations.tags["cost_usd"], "1.040000");
+        assert_eq!(annotations.tags["energy_kwh"], "0.160000");
+
+        // Without a measured CPU time, one core is charged for the whole proof.
+        let usage = ProvingUsage {
+            wall_time: hour,
+            ..Default::default()
+        };
+        let cost = rates.estimate(&usage);
+        assert!((cost.usd - 0.04).abs() < 1e-9);
+        assert!((cost.energy_joules - 36_000.0).abs() < 1e-6);
+    }
+
+    #[cfg(all(feature = "prove", target_os = "linux"))]
+    #[test]
+    fn process_cpu_time() {
+        assert!(super::process_cpu_time().is_some());
+    }
+}
@@ -14,11 +14,14 @@
 
 #[cfg(any(feature = "client", feature = "prove"))]
 pub(crate) mod api;
-#[cfg(feature = "client")]
+#[cfg(any(feature = "client", feature = "execute"))]
 pub(crate) mod client;
+pub(crate) mod cost;
+#[cfg(feature = "otel")]
+pub(crate) mod otel;
 pub(crate) mod prove_info;
 pub(crate) mod recursion;
-#[cfg(feature = "prove")]
+#[cfg(feature = "execute")]
 pub(crate) mod server;
 
 #[cfg(any(feature = "client", feature = "prove"))]
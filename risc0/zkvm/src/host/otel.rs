@@ -0,0 +1,85 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Propagation of OpenTelemetry trace context across remote proving requests.
+//!
+//! The executor and prover emit [tracing] spans for each major phase, with
+//! attributes such as the po2 and index of a segment, the HAL proving it and
+//! the cycles executed. To export them as OpenTelemetry spans, install a
+//! `tracing_opentelemetry` layer in the subscriber of the host.
+//!
+//! A request to a remote prover, such as through the [ApiClient](crate::ApiClient),
+//! carries the [TraceContext] of the span it is made in, and the server proves
+//! it in a span that is a child of that context, so that one trace follows a
+//! proof from the client through every phase of the prover.
+
+use std::collections::HashMap;
+
+use opentelemetry::propagation::TextMapPropagator;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// A W3C trace context, as the `traceparent` and `tracestate` fields carried
+/// by a request.
+pub type TraceContext = HashMap<String, String>;
+
+/// The trace context of the current span, to attach to a remote request.
+///
+/// The context is empty when the current span is not exported through
+/// OpenTelemetry.
+pub fn current_trace_context() -> TraceContext {
+    let mut carrier = TraceContext::new();
+    TraceContextPropagator::new().inject_context(&Span::current().context(), &mut carrier);
+    carrier
+}
+
+/// Make `span` a child of the span that `trace_context` was taken from, such
+/// as on a server handling a remote request.
+///
+/// An empty or malformed trace context leaves `span` as it is.
+pub fn set_trace_parent(span: &Span, trace_context: &TraceContext) {
+    if trace_context.is_empty() {
+        return;
+    }
+    span.set_parent(TraceContextPropagator::new().extract(trace_context));
+}
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_sdk::trace::TracerProvider;
+    use tracing_subscriber::prelude::*;
+
+    use super::{current_trace_context, set_trace_parent, TraceContext};
+
+    #[test]
+    fn round_trip() {
+        let tracer = TracerProvider::builder().build().tracer("test");
+        let subscriber =
+            tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer));
+        tracing::subscriber::with_default(subscriber, || {
+            let trace_id = "4bf92f3577b34da6a3ce929d0e0e4736";
+            let parent = TraceContext::from([(
+                "traceparent".to_string(),
+                format!("00-{trace_id}-00f067aa0ba902b7-01"),
+            )]);
+            let span = tracing::info_span!("request");
+            set_trace_parent(&span, &parent);
+            let child = span.in_scope(current_trace_context);
+            assert!(child["traceparent"].contains(trace_id));
+            assert_ne!(child["traceparent"], parent["traceparent"]);
+        });
+    }
+}
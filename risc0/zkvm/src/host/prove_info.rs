@@ -14,7 +14,9 @@
 
 //! Struct containing information about a prover's execution including the receipt.
 
-use crate::Receipt;
+use alloc::vec::Vec;
+
+use crate::{ExitCode, Journal, ProvingCost, ProvingUsage, Receipt};
 
 /// Information returned by the prover including receipt as well as other information useful for debugging
 pub struct ProveInfo {
@@ -33,4 +35,32 @@ pub struct SessionStats {
     pub total_cycles: u64,
     /// User cycles run within guest
     pub user_cycles: u64,
+    /// Resources used to prove the session, if reported by the prover
+    pub usage: Option<ProvingUsage>,
+    /// Estimated cost of the proof, set by [ProveInfo::with_cost]
+    pub cost: Option<ProvingCost>,
+}
+
+/// Provides information about the result of execution.
+#[derive(Clone, Debug)]
+pub struct SessionInfo {
+    /// The number of user cycles for each segment.
+    pub segments: Vec<SegmentInfo>,
+
+    /// The data publicly committed by the guest program.
+    pub journal: Journal,
+
+    /// The [ExitCode] of the session.
+    pub exit_code: ExitCode,
+}
+
+/// Provides information about a segment of execution.
+#[derive(Clone, Debug)]
+pub struct SegmentInfo {
+    /// The number of cycles used for proving in powers of 2.
+    pub po2: u32,
+
+    /// The number of user cycles without any overhead for continuations or po2
+    /// padding.
+    pub cycles: u32,
 }
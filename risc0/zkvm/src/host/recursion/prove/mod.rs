@@ -21,7 +21,7 @@ pub mod zkr;
 
 use std::{collections::VecDeque, mem::take, rc::Rc};
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, ensure, Context, Result};
 use hex::FromHex;
 use merkle::MerkleGroup;
 use rand::thread_rng;
@@ -49,7 +49,7 @@ use serde::{Deserialize, Serialize};
 
 pub use self::program::Program;
 use crate::{
-    receipt::{succinct::valid_control_ids, SuccinctReceipt},
+    receipt::{merkle::ALLOWED_CODE_MERKLE_DEPTH, succinct::valid_control_ids, SuccinctReceipt},
     receipt_claim::{Merge, Output},
     sha::Digestible,
     HalPair, ReceiptClaim, SegmentReceipt,
@@ -59,11 +59,6 @@ use crate::{
 // messing up bootstrap dependencies.
 /// Number of rows to use for the recursion circuit witness as a power of 2.
 pub const RECURSION_PO2: usize = 18;
-/// Depth of the Merkle tree to use for encoding the set of allowed control IDs.
-/// NOTE: Changing this constant must be coordinated with the circuit. In order to avoid needing to
-/// change the circuit later, this is set to 8 which allows for enough control IDs to be encoded
-/// that we are unlikely to need more.
-const ALLOWED_CODE_MERKLE_DEPTH: usize = 8;
 /// Size of the code group in the taps of the recursion circuit.
 const RECURSION_CODE_SIZE: usize = 23;
 
@@ -80,6 +75,7 @@ pub struct RecursionReceipt {
 /// resulting in a recursion circuit STARK proof. This recursion proof has a single
 /// constant-time verification procedure, with respect to the original segment length, and is then
 /// used as the input to all other recursion programs (e.g. join, resolve, and identity_p254).
+#[tracing::instrument(skip_all, fields(po2 = RECURSION_PO2, index = segment_receipt.index))]
 pub fn lift(segment_receipt: &SegmentReceipt) -> Result<SuccinctReceipt> {
     tracing::debug!("Proving lift: claim = {:#?}", segment_receipt.claim);
     let mut prover = Prover::new_lift(&segment_receipt.seal, ProverOpts::default())?;
@@ -99,6 +95,11 @@ pub fn lift(segment_receipt: &SegmentReceipt) -> Result<SuccinctReceipt> {
 ///
 /// By repeated application of the join program, any number of receipts for execution spans within
 /// the same session can be compressed into a single receipt for the entire session.
+///
+/// The join program requires the post state of `a` to be the pre state of `b`, so it cannot join
+/// receipts of unrelated sessions. Use [Prover::aggregate](crate::Prover::aggregate) to aggregate
+/// those into one.
+#[tracing::instrument(skip_all, fields(po2 = RECURSION_PO2))]
 pub fn join(a: &SuccinctReceipt, b: &SuccinctReceipt) -> Result<SuccinctReceipt> {
     tracing::debug!("Proving join: a.claim = {:#?}", a.claim);
     tracing::debug!("Proving join: b.claim = {:#?}", b.claim);
@@ -131,6 +132,7 @@ pub fn join(a: &SuccinctReceipt, b: &SuccinctReceipt) -> Result<SuccinctReceipt>
 ///
 /// By applying the resolve program, a conditional receipt (i.e. a receipt for an execution using
 /// the `env::verify` API to logically verify a receipt) can be made into an unconditional receipt.
+#[tracing::instrument(skip_all, fields(po2 = RECURSION_PO2))]
 pub fn resolve(
     conditional: &SuccinctReceipt,
     assumption: &SuccinctReceipt,
@@ -174,11 +176,32 @@ pub fn resolve(
     })
 }
 
+/// Error returned by [resolve] when the conditional receipt commits to output channels.
+///
+/// The resolve program computes the output of the resolved claim from only the journal and the
+/// assumptions of the conditional receipt, so the assumptions of a receipt with channels cannot
+/// be resolved. Such a receipt can still be verified as a [crate::CompositeReceipt] along with
+/// the receipts for its assumptions.
+#[derive(Debug, Clone)]
+pub struct ResolveChannelsError;
+
+impl std::fmt::Display for ResolveChannelsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "cannot resolve the assumptions of a conditional receipt with output channels"
+        )
+    }
+}
+
+impl std::error::Error for ResolveChannelsError {}
+
 /// Prove the verification of a recursion receipt using the Poseidon254 hash function for FRI.
 ///
 /// The identity_p254 program is used as the last step in the prover pipeline before running the
 /// Groth16 prover. In Groth16 over BN254, it is much more efficient to verify a STARK that was
 /// produced with Poseidon over the BN254 base field compared to using Poseidon over BabyBear.
+#[tracing::instrument(skip_all, fields(po2 = RECURSION_PO2))]
 pub fn identity_p254(a: &SuccinctReceipt) -> Result<SuccinctReceipt> {
     let hal_pair = poseidon254_hal_pair();
     let (hal, circuit_hal) = (hal_pair.hal.as_ref(), hal_pair.circuit_hal.as_ref());
@@ -198,6 +221,7 @@ pub fn identity_p254(a: &SuccinctReceipt) -> Result<SuccinctReceipt> {
 pub struct ProverOpts {
     pub(crate) skip_seal: bool,
     suite: HashSuite<BabyBear>,
+    control_ids: Vec<Digest>,
 }
 
 impl ProverOpts {
@@ -208,6 +232,31 @@ impl ProverOpts {
     pub fn with_skip_seal(self, skip_seal: bool) -> Self {
         Self { skip_seal, ..self }
     }
+
+    /// Set the control IDs of the recursion programs that may be applied to the receipts.
+    ///
+    /// By default these are the programs of this release. Adding the control IDs of custom
+    /// recursion programs changes the control root, so every receipt in a recursion tree that
+    /// uses custom programs must be proven with the same control IDs.
+    pub fn with_control_ids(self, control_ids: Vec<Digest>) -> Self {
+        Self {
+            control_ids,
+            ..self
+        }
+    }
+
+    /// The root of the Merkle tree of allowed control IDs, which verifiers check is allowed by
+    /// their [VerifierPolicy](crate::VerifierPolicy).
+    pub fn control_root(&self) -> Digest {
+        self.allowed_tree().calc_root(self.suite.hashfn.as_ref())
+    }
+
+    fn allowed_tree(&self) -> MerkleGroup {
+        MerkleGroup {
+            depth: ALLOWED_CODE_MERKLE_DEPTH,
+            leaves: self.control_ids.clone(),
+        }
+    }
 }
 
 impl Default for ProverOpts {
@@ -215,6 +264,7 @@ impl Default for ProverOpts {
         ProverOpts {
             skip_seal: false,
             suite: Poseidon2HashSuite::new_suite(),
+            control_ids: valid_control_ids(),
         }
     }
 }
@@ -403,8 +453,11 @@ cfg_if::cfg_if! {
 /// Kinds of digests recognized by the recursion program language.
 // NOTE: Default is additionally a recognized type in the recursion program language. It's not
 // yet supported here because some of the code in this module assumes Poseidon2 is Default.
-enum DigestKind {
+pub enum DigestKind {
+    /// A Poseidon2 digest, read as eight Baby Bear field elements.
     Poseidon2,
+
+    /// A SHA-256 digest, read as sixteen 16-bit half words.
     Sha256,
 }
 
@@ -490,7 +543,7 @@ impl Prover {
     /// identity_p254).
     pub fn new_lift(seal: &[u32], opts: ProverOpts) -> Result<Self> {
         let hashfn = opts.suite.hashfn.as_ref();
-        let allowed_ids = Self::make_allowed_tree();
+        let allowed_ids = opts.allowed_tree();
         let merkle_root = allowed_ids.calc_root(hashfn);
 
         let mut iop = ReadIOP::new(seal, opts.suite.rng.as_ref());
@@ -509,6 +562,38 @@ impl Prover {
         Ok(prover)
     }
 
+    /// Initialize a recursion prover with a custom recursion program.
+    ///
+    /// Custom programs are compiled by Zirgen and loaded with [Program::from_encoded]. The
+    /// prover adds the control root of `opts` to the input tape, as the programs of this release
+    /// do, and the remaining input is added with [Prover::add_receipt] and
+    /// [Prover::add_input_digest] in the order the program reads it. For example, a program
+    /// that joins K receipts and checks a predicate over their journals would read K receipts
+    /// followed by the digests it needs to open their claims.
+    ///
+    /// The control ID of the program must be one of the control IDs of `opts`. Verifiers accept
+    /// the resulting receipts only if their [VerifierPolicy](crate::VerifierPolicy) allows both
+    /// that control ID and [ProverOpts::control_root].
+    pub fn new_custom(program: Program, opts: ProverOpts) -> Result<Self> {
+        let control_id = program.compute_control_id(opts.suite.clone());
+        ensure!(
+            opts.control_ids.contains(&control_id),
+            "control ID {control_id} of the custom program is not an allowed control ID"
+        );
+        let merkle_root = opts.control_root();
+
+        let mut prover = Prover::new(program, control_id, opts);
+        prover.add_input_digest(&merkle_root, DigestKind::Poseidon2);
+        Ok(prover)
+    }
+
+    /// Add a succinct receipt to the input tape of the recursion program, as its seal, the
+    /// inclusion proof of its control ID in the allowed tree, and its encoded claim.
+    pub fn add_receipt(&mut self, a: &SuccinctReceipt) -> Result<()> {
+        let allowed_ids = self.opts.allowed_tree();
+        self.add_segment_receipt(a, &allowed_ids)
+    }
+
     fn add_segment_receipt(
         &mut self,
         a: &SuccinctReceipt,
@@ -529,7 +614,7 @@ impl Prover {
     /// within the same session can be compressed into a single receipt for the entire session.
     pub fn new_join(a: &SuccinctReceipt, b: &SuccinctReceipt, opts: ProverOpts) -> Result<Self> {
         let hashfn = opts.suite.hashfn.as_ref();
-        let allowed_ids = Self::make_allowed_tree();
+        let allowed_ids = opts.allowed_tree();
         let merkle_root = allowed_ids.calc_root(hashfn);
 
         let (program, control_id) = zkr::join()?;
@@ -554,7 +639,7 @@ impl Prover {
     ) -> Result<Self> {
         // Construct the Merkle tree of all acceptable recursion predicate control IDs.
         let hashfn = opts.suite.hashfn.as_ref();
-        let allowed_ids = Self::make_allowed_tree();
+        let allowed_ids = opts.allowed_tree();
         let merkle_root = allowed_ids.calc_root(hashfn);
 
         // Load the resolve predicate as a Program and construct the prover.
@@ -571,6 +656,7 @@ impl Prover {
         let Output {
             assumptions,
             journal,
+            channels,
         } = cond
             .claim
             .output
@@ -587,6 +673,11 @@ impl Prover {
             .context("cannot resolve conditional receipt with pruned assumptions")?;
         assumptions_tail.resolve(&assum.claim.digest())?;
 
+        // The resolve predicate computes the output digest from only the journal and assumptions.
+        if channels.digest() != Digest::ZERO {
+            return Err(ResolveChannelsError.into());
+        }
+
         prover.add_input_digest(&assumptions_tail.digest(), DigestKind::Sha256);
         prover.add_input_digest(&journal.digest(), DigestKind::Sha256);
         Ok(prover)
@@ -598,7 +689,7 @@ impl Prover {
     /// hash function for FRI. See [identity_p254] for more information.
     pub fn new_identity(a: &SuccinctReceipt, opts: ProverOpts) -> Result<Self> {
         let hashfn = opts.suite.hashfn.as_ref();
-        let allowed_ids = Self::make_allowed_tree();
+        let allowed_ids = opts.allowed_tree();
         let merkle_root = allowed_ids.calc_root(hashfn);
 
         let (program, control_id) = zkr::identity()?;
@@ -609,12 +700,13 @@ impl Prover {
         Ok(prover)
     }
 
-    fn add_input(&mut self, input: &[u32]) {
+    /// Add words to the input for the recursion program, each encoding a Baby Bear field element.
+    pub fn add_input(&mut self, input: &[u32]) {
         self.input.extend(input);
     }
 
     /// Add a digest to the input for the recursion program.
-    fn add_input_digest(&mut self, digest: &Digest, kind: DigestKind) {
+    pub fn add_input_digest(&mut self, digest: &Digest, kind: DigestKind) {
         match kind {
             // Poseidon2 digests consist of  BabyBear field elems and do not need to be split.
             DigestKind::Poseidon2 => self.add_input(digest.as_words()),
@@ -631,6 +723,23 @@ impl Prover {
         }
     }
 
+    /// Run the prover and return a [SuccinctReceipt] for the given claim.
+    ///
+    /// This is the last step for custom recursion programs. It fails if the claim committed by
+    /// the program does not match `claim`, which may be pruned.
+    pub fn prove_claim(&mut self, claim: &ReceiptClaim) -> Result<SuccinctReceipt> {
+        let receipt = self.run()?;
+        let mut out_stream = VecDeque::<u32>::new();
+        out_stream.extend(receipt.output.iter());
+        let claim_decoded = ReceiptClaim::decode(&mut out_stream)?;
+        tracing::debug!("Proving custom program finished: decoded claim = {claim_decoded:#?}");
+        Ok(SuccinctReceipt {
+            seal: receipt.seal,
+            control_id: receipt.control_id,
+            claim: claim_decoded.merge(claim)?,
+        })
+    }
+
     /// Run the prover, producing a receipt of execution for the recursion circuit over the loaded
     /// program and input.
     #[tracing::instrument(skip_all)]
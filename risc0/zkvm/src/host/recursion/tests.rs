@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use hex::FromHex;
 use risc0_circuit_recursion::CircuitImpl;
 use risc0_zkp::{
     adapter::CircuitInfo,
@@ -22,8 +23,9 @@ use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF, MULTI_TEST_I
 use test_log::test;
 
 use super::{
-    identity_p254, join, lift, prove::poseidon254_hal_pair, prove::poseidon2_hal_pair, Prover,
-    ProverOpts as RecursionProverOpts,
+    identity_p254, join, lift,
+    prove::{poseidon254_hal_pair, poseidon2_hal_pair, zkr},
+    Prover, ProverOpts as RecursionProverOpts,
 };
 use crate::{
     default_prover, get_prover_server, host::client::prove::ReceiptKind, ExecutorEnv, ExecutorImpl,
@@ -294,6 +296,27 @@ fn test_recursion_lift_resolve_e2e() {
     succinct_receipt.verify(MULTI_TEST_ID).unwrap();
 }
 
+#[test]
+fn custom_program_control_ids() {
+    assert_eq!(
+        RecursionProverOpts::default().control_root(),
+        Digest::from_hex(ALLOWED_CONTROL_ROOT).unwrap()
+    );
+
+    // A custom program is only accepted with its control ID in the allowed tree, which then
+    // has a different root.
+    let (program, control_id) = zkr::identity().unwrap();
+    let opts = RecursionProverOpts::default().with_control_ids(vec![Digest::ZERO]);
+    assert!(Prover::new_custom(program.clone(), opts).is_err());
+
+    let opts = RecursionProverOpts::default().with_control_ids(vec![control_id]);
+    assert_ne!(
+        opts.control_root(),
+        Digest::from_hex(ALLOWED_CONTROL_ROOT).unwrap()
+    );
+    Prover::new_custom(program, opts).unwrap();
+}
+
 #[test]
 fn stable_root() {
     // This tests that none of the control IDs have changed unexpectedly.
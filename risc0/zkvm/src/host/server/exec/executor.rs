@@ -12,7 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{cell::RefCell, io::Write, mem, rc::Rc, sync::Arc, time::Instant};
+#[cfg(not(target_family = "wasm"))]
+use std::time::Instant;
+use std::{cell::RefCell, collections::BTreeSet, io::Write, mem, rc::Rc, sync::Arc};
 
 use anyhow::{Context as _, Result};
 use risc0_binfmt::{MemoryImage, Program};
@@ -24,17 +26,25 @@ use risc0_circuit_rv32im::prove::emu::{
     },
 };
 use risc0_zkp::core::digest::Digest;
-use risc0_zkvm_platform::{fileno, memory::GUEST_MAX_MEM, PAGE_SIZE};
+use risc0_zkvm_platform::{
+    fileno,
+    memory::GUEST_MAX_MEM,
+    syscall::nr::{SYS_PROFILE_ZONE_ENTER, SYS_PROFILE_ZONE_EXIT},
+    PAGE_SIZE,
+};
 use tempfile::tempdir;
+#[cfg(target_family = "wasm")]
+use web_time::Instant;
 
 use crate::{
-    host::client::env::SegmentPath, Assumption, Assumptions, ExecutorEnv, FileSegmentRef, Output,
-    Segment, SegmentRef, Session,
+    host::{client::env::SegmentPath, server::store::StoreSegmentRef},
+    Assumption, Assumptions, Channels, ExecutorEnv, FileSegmentRef, GuestOutOfMemory, GuestPanic,
+    Output, Segment, SegmentRef, Session,
 };
 
 use super::{
     profiler::Profiler,
-    syscall::{SyscallContext, SyscallTable},
+    syscall::{SysProfileZone, SyscallContext, SyscallTable},
 };
 
 // The Executor provides an implementation for the execution phase.
@@ -55,6 +65,10 @@ impl<'a> ExecutorImpl<'a> {
     /// work will be done in each segment. This is the execution phase:
     /// the guest program is executed to determine how its proof should be
     /// divided into subparts.
+    ///
+    /// Pages of a [MemoryImage] are shared between its clones, so a server
+    /// running many sessions of the same guest can load the image once and
+    /// pass a clone to each executor.
     pub fn new(env: ExecutorEnv<'a>, image: MemoryImage) -> Result<Self> {
         Self::with_details(env, image, None)
     }
@@ -76,10 +90,13 @@ impl<'a> ExecutorImpl<'a> {
     /// let mut exec = ExecutorImpl::from_elf(env, BENCH_ELF).unwrap();
     /// ```
     pub fn from_elf(mut env: ExecutorEnv<'a>, elf: &[u8]) -> Result<Self> {
+        // The profiler reads the symbols of the ELF, so it needs the ELF itself.
+        let elf = risc0_binfmt::decompress_elf(elf)?;
+        let elf = elf.as_ref();
         let program = Program::load_elf(elf, GUEST_MAX_MEM as u32)?;
         let image = MemoryImage::new(&program, PAGE_SIZE as u32)?;
 
-        let profiler = if env.pprof_out.is_some() {
+        let profiler = if env.pprof_out.is_some() || env.flamegraph_out.is_some() {
             let profiler = Rc::new(RefCell::new(Profiler::new(elf, None)?));
             env.trace.push(profiler.clone());
             Some(profiler)
@@ -95,7 +112,13 @@ impl<'a> ExecutorImpl<'a> {
         image: MemoryImage,
         profiler: Option<Rc<RefCell<Profiler>>>,
     ) -> Result<Self> {
-        let syscall_table = SyscallTable::new(&env);
+        let mut syscall_table = SyscallTable::new(&env);
+        if let Some(profiler) = &profiler {
+            let sys_profile_zone = SysProfileZone(Some(profiler.clone()));
+            syscall_table
+                .with_syscall(SYS_PROFILE_ZONE_ENTER, sys_profile_zone.clone())
+                .with_syscall(SYS_PROFILE_ZONE_EXIT, sys_profile_zone);
+        }
         Ok(Self {
             env,
             image,
@@ -107,6 +130,12 @@ impl<'a> ExecutorImpl<'a> {
     /// This will run the executor to get a [Session] which contain the results
     /// of the execution.
     pub fn run(&mut self) -> Result<Session> {
+        if let Some(store) = self.env.segment_store.clone() {
+            return self.run_with_callback(|segment| {
+                Ok(Box::new(StoreSegmentRef::new(&segment, store.clone())?))
+            });
+        }
+
         if self.env.segment_path.is_none() {
             self.env.segment_path = Some(SegmentPath::TempDir(Arc::new(tempdir()?)));
         }
@@ -117,10 +146,21 @@ impl<'a> ExecutorImpl<'a> {
 
     /// Run the executor until [crate::ExitCode::Halted] or
     /// [crate::ExitCode::Paused] is reached, producing a [Session] as a result.
+    #[tracing::instrument(
+        name = "execute",
+        skip_all,
+        fields(
+            segment_limit_po2 = tracing::field::Empty,
+            segments = tracing::field::Empty,
+            user_cycles = tracing::field::Empty,
+            total_cycles = tracing::field::Empty,
+        )
+    )]
     pub fn run_with_callback<F>(&mut self, mut callback: F) -> Result<Session>
     where
         F: FnMut(Segment) -> Result<Box<dyn SegmentRef>>,
     {
+        #[cfg(not(target_family = "wasm"))]
         nvtx::range_push!("execute");
 
         let journal = Journal::default();
@@ -133,6 +173,8 @@ impl<'a> ExecutorImpl<'a> {
             .env
             .segment_limit_po2
             .unwrap_or(DEFAULT_SEGMENT_LIMIT_PO2 as u32) as usize;
+        let span = tracing::Span::current();
+        span.record("segment_limit_po2", segment_limit_po2);
 
         let mut refs = Vec::new();
         let mut exec = Executor::new(
@@ -141,6 +183,8 @@ impl<'a> ExecutorImpl<'a> {
             self.env.input_digest,
             self.env.trace.clone(),
         );
+        exec.set_no_progress_limit(self.env.no_progress_limit);
+        exec.set_split_on_trap(self.env.split_on_fault);
 
         let start_time = Instant::now();
         let result = exec.run(segment_limit_po2, self.env.session_limit, |inner| {
@@ -171,6 +215,7 @@ impl<'a> ExecutorImpl<'a> {
                                         .collect::<Result<Vec<_>>>()?,
                                 )
                                 .into(),
+                                channels: Channels::from(self.env.channels.borrow().clone()).into(),
                             })
                         })
                 })
@@ -185,8 +230,14 @@ impl<'a> ExecutorImpl<'a> {
             let segment_ref = callback(segment)?;
             refs.push(segment_ref);
             Ok(())
-        })?;
+        });
+        let result = result.map_err(|err| self.with_backtrace(err))?;
         let elapsed = start_time.elapsed();
+        span.record("segments", refs.len());
+        span.record("user_cycles", result.user_cycles);
+        span.record("total_cycles", result.total_cycles);
+        #[cfg(feature = "metrics")]
+        crate::host::server::metrics::record_execution(result.user_cycles, elapsed);
 
         // Set the session_journal to the committed data iff the guest set a non-zero output.
         let session_journal = result
@@ -203,15 +254,22 @@ impl<'a> ExecutorImpl<'a> {
         // Take (clear out) the list of accessed assumptions.
         // Leave the assumptions cache so it can be used if execution is resumed from pause.
         let assumptions = mem::take(&mut self.env.assumptions.borrow_mut().accessed);
+        let calls = mem::take(&mut *self.env.calls.borrow_mut());
+        let channels = mem::take(&mut *self.env.channels.borrow_mut());
 
         if let Some(profiler) = self.profiler.take() {
-            let report = profiler.borrow_mut().finalize_to_vec();
-            std::fs::write(self.env.pprof_out.as_ref().unwrap(), report)?;
+            let mut profiler = profiler.borrow_mut();
+            if let Some(path) = &self.env.pprof_out {
+                std::fs::write(path, profiler.finalize_to_vec())?;
+            }
+            if let Some(path) = &self.env.flamegraph_out {
+                std::fs::write(path, profiler.finalize_to_folded())?;
+            }
         }
 
         self.image = result.post_image.clone();
 
-        let session = Session::new(
+        let mut session = Session::new(
             refs,
             self.env.input_digest.unwrap_or_default(),
             session_journal,
@@ -223,17 +281,40 @@ impl<'a> ExecutorImpl<'a> {
             result.pre_state,
             result.post_state,
         );
+        session.calls = calls;
+        session.fault = result.trap;
+        if session.journal.is_some() {
+            session.channels = channels.into();
+        }
+        session.receipt_store = self.env.receipt_store.clone();
 
         tracing::info_span!("executor").in_scope(|| {
             tracing::info!("execution time: {elapsed:?}");
             session.log();
         });
 
+        #[cfg(not(target_family = "wasm"))]
         nvtx::range_pop!();
         Ok(session)
     }
 }
 
+impl<'a> ExecutorImpl<'a> {
+    // Attach the guest call stack to a panic or an out-of-memory error, when the profiler is
+    // tracking it.
+    fn with_backtrace(&self, mut err: anyhow::Error) -> anyhow::Error {
+        let Some(profiler) = &self.profiler else {
+            return err;
+        };
+        if let Some(panic) = err.downcast_mut::<GuestPanic>() {
+            panic.backtrace = profiler.borrow().backtrace();
+        } else if let Some(oom) = err.downcast_mut::<GuestOutOfMemory>() {
+            oom.backtrace = profiler.borrow().backtrace();
+        }
+        err
+    }
+}
+
 struct ContextAdapter<'a> {
     ctx: &'a mut dyn NewSyscallContext,
 }
@@ -250,6 +331,14 @@ impl<'a> SyscallContext for ContextAdapter<'a> {
     fn load_u8(&mut self, addr: u32) -> Result<u8> {
         self.ctx.peek_u8(ByteAddr(addr))
     }
+
+    fn touched_pages(&self) -> BTreeSet<u32> {
+        self.ctx.touched_pages()
+    }
+
+    fn get_pc(&self) -> u32 {
+        self.ctx.get_pc().0
+    }
 }
 
 impl<'a> NewSyscall for ExecutorImpl<'a> {
@@ -266,6 +355,10 @@ impl<'a> NewSyscall for ExecutorImpl<'a> {
             .borrow_mut()
             .syscall(syscall, &mut ctx, into_guest)
     }
+
+    fn charged_cycles(&self) -> u64 {
+        self.env.call_cycles.get()
+    }
 }
 
 // Capture the journal output in a buffer that we can access afterwards.
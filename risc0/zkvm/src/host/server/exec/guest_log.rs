@@ -0,0 +1,106 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Records guest log events through the host's `tracing` subscriber.
+//!
+//! `tracing` filters on the static metadata of a callsite, and the targets of
+//! guest events are only known at runtime. Each distinct target and level is
+//! given its own callsite, which is leaked and registered the first time it is
+//! seen, so subscribers filter guest events just like host ones.
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+};
+
+use anyhow::{anyhow, Result};
+use tracing::{
+    callsite::{self, Callsite},
+    field::{FieldSet, Value},
+    metadata::Kind,
+    subscriber::Interest,
+    Event, Level, Metadata,
+};
+
+const FIELDS: &[&str] = &["message", "cycle"];
+
+struct GuestCallsite(OnceLock<Metadata<'static>>);
+
+impl Callsite for GuestCallsite {
+    fn set_interest(&self, _interest: Interest) {}
+
+    fn metadata(&self) -> &Metadata<'_> {
+        self.0.get().unwrap()
+    }
+}
+
+fn level(level: u32) -> Result<Level> {
+    Ok(match level {
+        1 => Level::ERROR,
+        2 => Level::WARN,
+        3 => Level::INFO,
+        4 => Level::DEBUG,
+        5 => Level::TRACE,
+        _ => return Err(anyhow!("Invalid guest log level {level}")),
+    })
+}
+
+fn metadata(target: &str, level: Level) -> &'static Metadata<'static> {
+    static CALLSITES: OnceLock<Mutex<HashMap<(String, Level), &'static GuestCallsite>>> =
+        OnceLock::new();
+
+    let mut callsites = CALLSITES.get_or_init(Default::default).lock().unwrap();
+    let callsite = *callsites
+        .entry((target.to_string(), level))
+        .or_insert_with(|| {
+            let callsite: &'static GuestCallsite =
+                Box::leak(Box::new(GuestCallsite(OnceLock::new())));
+            let target: &'static str = Box::leak(target.into());
+            let fields = FieldSet::new(FIELDS, callsite::Identifier(callsite));
+            callsite
+                .0
+                .set(Metadata::new(
+                    "guest log event",
+                    target,
+                    level,
+                    None,
+                    None,
+                    None,
+                    fields,
+                    Kind::EVENT,
+                ))
+                .unwrap();
+            callsite::register(callsite);
+            callsite
+        });
+    callsite.0.get().unwrap()
+}
+
+/// Record a log event from the guest at the given cycle.
+pub(crate) fn record(level: u32, target: &str, msg: &str, cycle: u64) -> Result<()> {
+    let metadata = metadata(target, self::level(level)?);
+    tracing::dispatcher::get_default(|dispatch| {
+        if !dispatch.enabled(metadata) {
+            return;
+        }
+        let fields = metadata.fields();
+        let mut iter = fields.iter();
+        let (message, cycle_field) = (iter.next().unwrap(), iter.next().unwrap());
+        let msg = tracing::field::display(msg);
+        let values: [(_, Option<&dyn Value>); 2] =
+            [(&message, Some(&msg)), (&cycle_field, Some(&cycle))];
+        dispatch.event(&Event::new(metadata, &fields.value_set(&values)));
+    });
+    Ok(())
+}
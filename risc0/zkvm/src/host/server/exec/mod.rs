@@ -19,7 +19,9 @@
 //! contains an execution trace of the specified program.
 
 pub(crate) mod executor;
+mod guest_log;
 pub(crate) mod profiler;
 pub(crate) mod syscall;
 #[cfg(test)]
 mod tests;
+pub(crate) mod time_travel;
@@ -18,10 +18,14 @@
 //! guest.  It does not trace full stack traces, but only provides the
 //! top level stack frame.  (More than one stack frame may show up
 //! in the case of inlined functions).
+//!
+//! Zones opened by the guest with `profile_zone!` show up as synthetic frames
+//! below the function that opened them. So do the cycles spent in ecalls, such
+//! as `[ecall sha]`, and in paging memory, as `[paging]`.
 
 use std::{
     cell::RefCell,
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fmt::Write,
     hash::{Hash, Hasher},
     rc::Rc,
@@ -36,7 +40,10 @@ use addr2line::{
 use anyhow::{anyhow, Result};
 use elf::{abi::STT_FUNC, endian::LittleEndian, ElfBytes};
 use prost::Message;
-use risc0_zkvm_platform::memory::TEXT_START;
+use risc0_zkvm_platform::{
+    memory::TEXT_START,
+    syscall::{ecall, reg_abi::REG_T0},
+};
 use rrs_lib::instruction_formats::{IType, JType, OPCODE_JAL, OPCODE_JALR};
 use rustc_demangle::demangle;
 
@@ -92,6 +99,25 @@ fn extract_call_stack_op(insn: u32) -> Option<CallStackOp> {
     }
 }
 
+// The encoding of the ecall instruction.
+const ECALL: u32 = 0x0000_0073;
+
+// The synthetic frame for the cycles of an ecall, by the ecall number in t0.
+fn ecall_frame(t0: u32) -> &'static str {
+    match t0 {
+        ecall::HALT => "[ecall halt]",
+        ecall::INPUT => "[ecall input]",
+        ecall::SOFTWARE => "[ecall software]",
+        ecall::SHA => "[ecall sha]",
+        ecall::BIGINT => "[ecall bigint]",
+        _ => "[ecall]",
+    }
+}
+
+// Call stack keys at or above this value are zones rather than return addresses. They lie outside
+// guest memory, so no program counter can collide with them.
+const ZONE_KEY_BASE: u32 = 0xf000_0000;
+
 /// Node in a tree tracking the call stacks and assigning cycles to stacks.
 ///
 /// Each node represents a unique call-stack as defined by a list of return
@@ -158,6 +184,18 @@ pub struct Profiler {
     // Current CallNode key in the stack
     current_key: u32,
 
+    // Open zones, outermost first, as a key and the depth of the call stack they sit at.
+    zone_stack: Vec<(u32, usize)>,
+
+    // Names of the zones seen so far, indexed by key minus ZONE_KEY_BASE.
+    zone_names: Vec<String>,
+
+    // The value of t0, which selects the ecall to run.
+    t0: u32,
+
+    // Whether the samples have been added to the profile.
+    finalized: bool,
+
     ctx: ObjectContext,
 
     profile: ProfileBuilder,
@@ -222,6 +260,10 @@ impl Profiler {
             current_node: Some(root),
             current_key: 0,
             call_stack_path: Vec::new(),
+            zone_stack: Vec::new(),
+            zone_names: Vec::new(),
+            t0: 0,
+            finalized: false,
             ctx,
             profile: ProfileBuilder::new(),
         };
@@ -263,6 +305,16 @@ impl Profiler {
 
     /// Returns the frames name at the given pc.
     pub fn lookup_pc(&self, pc: u64) -> Vec<Frame> {
+        if let Some(name) = pc
+            .checked_sub(ZONE_KEY_BASE as u64)
+            .and_then(|idx| self.zone_names.get(idx as usize))
+        {
+            return vec![Frame {
+                name: name.clone(),
+                lineno: 0,
+                filename: "unknown".to_string(),
+            }];
+        }
         let frames = if let Some(symbol) = self.profile.function_lookup.get(&pc).cloned() {
             let mut dwarf_frames = lookup_pc(pc as u32, &self.ctx);
             dwarf_frames.reverse();
@@ -296,6 +348,99 @@ impl Profiler {
         frames
     }
 
+    /// The names of the functions and zones on the current call stack, innermost first.
+    pub(crate) fn backtrace(&self) -> Vec<String> {
+        self.path()
+            .iter()
+            .rev()
+            .filter_map(|pc| self.lookup_pc(*pc as u64).into_iter().next())
+            .map(|frame| frame.name)
+            .collect()
+    }
+
+    /// Open a zone with the given name below the current function.
+    pub(crate) fn enter_zone(&mut self, name: &str) -> Result<()> {
+        let idx = match self.zone_names.iter().position(|zone| zone == name) {
+            Some(idx) => idx,
+            None => {
+                self.zone_names.push(name.to_string());
+                self.zone_names.len() - 1
+            }
+        };
+        let key = ZONE_KEY_BASE + idx as u32;
+        self.zone_stack.push((key, self.call_stack_path.len()));
+        self.update_current_node()
+    }
+
+    /// Close the zone opened most recently.
+    pub(crate) fn exit_zone(&mut self) -> Result<()> {
+        self.zone_stack
+            .pop()
+            .ok_or_else(|| anyhow!("attempted to exit a profile zone with none open"))?;
+        self.update_current_node()
+    }
+
+    // Count cycles at the current location, or in a synthetic frame below it.
+    fn charge(&mut self, cycles: usize, frame: Option<&str>) -> Result<()> {
+        if self.call_stack_path.is_empty() {
+            return Ok(());
+        }
+        if let Some(name) = frame {
+            self.enter_zone(name)?;
+        }
+        let current_node = self
+            .current_node
+            .as_ref()
+            .expect("current_node should always be Some after initialization");
+        *current_node
+            .borrow_mut()
+            .counts
+            .entry(self.current_key)
+            .or_default() += cycles;
+        if frame.is_some() {
+            self.exit_zone()?;
+        }
+        Ok(())
+    }
+
+    // The call stack with the open zones inserted at their depths.
+    fn path(&self) -> Vec<u32> {
+        let mut zones = self.zone_stack.iter().peekable();
+        let mut path = Vec::with_capacity(self.call_stack_path.len() + self.zone_stack.len());
+        for (depth, &pc) in self.call_stack_path.iter().enumerate() {
+            while let Some((key, _)) = zones.next_if(|(_, zone_depth)| *zone_depth <= depth) {
+                path.push(*key);
+            }
+            path.push(pc);
+        }
+        path.extend(zones.map(|(key, _)| *key));
+        path
+    }
+
+    // Point current_node and current_key at the tree node for the current path.
+    fn update_current_node(&mut self) -> Result<()> {
+        let path = self.path();
+        let mut curr_node = Rc::clone(&self.root);
+        for (i, &call_stack_key) in path.iter().enumerate() {
+            if i == path.len() - 1 {
+                self.current_node = Some(Rc::clone(&curr_node));
+                self.current_key = *path
+                    .last()
+                    .ok_or_else(|| anyhow!("attempted to access an empty call stack"))?;
+            }
+            let next_node = {
+                let mut curr_node_borrowed = curr_node.borrow_mut();
+                curr_node_borrowed
+                    .calls
+                    .entry(call_stack_key)
+                    .or_insert_with(|| Rc::new(RefCell::new(CallNode::default())))
+                    .clone()
+            };
+            curr_node = next_node;
+        }
+        Ok(())
+    }
+
     /// Walk the profile tree rooted at node_ref, adding all call stacks in the profile to the
     /// profile under construction. All call stacks encountered build on top of the base_stack.
     fn walk_stacks(&mut self, node_ref: Rc<RefCell<CallNode>>, base_stack: Vec<Frame>) {
@@ -342,19 +487,35 @@ impl Profiler {
     /// Count and save the profiling samples, write the results to `output_path`.
     #[cfg(test)]
     pub(crate) fn finalize(mut self) -> ProfileBuilder {
-        let root_ref = Rc::clone(&self.root);
-        tracing::debug!("{}", self.root.borrow().fmt(0, &self));
-        self.walk_stacks(root_ref, Vec::new());
+        self.add_samples();
         self.profile
     }
 
-    /// Count and save the profiling samples, consuming the profiler and
-    /// returning the compiled profile protobuf, encoded as bytes.
+    /// Count and save the profiling samples, returning the compiled profile
+    /// protobuf, encoded as bytes.
     pub fn finalize_to_vec(&mut self) -> Vec<u8> {
+        self.add_samples();
+        self.profile.profile.encode_to_vec()
+    }
+
+    /// Count and save the profiling samples, returning them as folded stacks:
+    /// one line per call stack, with the frames from the outermost separated
+    /// by `;`, followed by the cycles spent there. This is the input format of
+    /// flamegraph tools such as `inferno-flamegraph`.
+    pub fn finalize_to_folded(&mut self) -> String {
+        self.add_samples();
+        self.profile.folded()
+    }
+
+    // Walk the call tree once, however many outputs are produced.
+    fn add_samples(&mut self) {
+        if self.finalized {
+            return;
+        }
+        self.finalized = true;
         let root_ref = Rc::clone(&self.root);
         tracing::debug!("{}", self.root.borrow().fmt(0, self));
         self.walk_stacks(root_ref, Vec::new());
-        self.profile.profile.encode_to_vec()
     }
 }
 
@@ -367,18 +528,8 @@ impl TraceCallback for Profiler {
                 let orig_pc = self.pc;
                 let orig_insn = self.insn;
 
-                if !self.call_stack_path.is_empty() {
-                    let current_node = self
-                        .current_node
-                        .as_ref()
-                        .expect("current_node should always be Some after initialization");
-                    let mut current_node_borrowed = current_node.borrow_mut();
-                    current_node_borrowed
-                        .counts
-                        .entry(self.current_key)
-                        .and_modify(|e| *e += cycles as usize)
-                        .or_insert(cycles as usize);
-                }
+                let frame = (orig_insn == ECALL).then(|| ecall_frame(self.t0));
+                self.charge(cycles as usize, frame)?;
 
                 if let Some(op) = extract_call_stack_op(orig_insn) {
                     match op {
@@ -414,24 +565,13 @@ impl TraceCallback for Profiler {
                         }
                     }
 
-                    let mut curr_node = Rc::clone(&self.root);
-                    for (i, &call_stack_key) in self.call_stack_path.iter().enumerate() {
-                        if i == self.call_stack_path.len() - 1 {
-                            self.current_node = Some(Rc::clone(&curr_node));
-                            self.current_key = *self.call_stack_path.last().ok_or_else(|| {
-                                anyhow!("attempted to access an empty call stack")
-                            })?;
-                        }
-                        let next_node = {
-                            let mut curr_node_borrowed = curr_node.borrow_mut();
-                            curr_node_borrowed
-                                .calls
-                                .entry(call_stack_key)
-                                .or_insert_with(|| Rc::new(RefCell::new(CallNode::default())))
-                                .clone()
-                        };
-                        curr_node = next_node;
+                    // A zone can't outlive the function that opened it.
+                    let depth = self.call_stack_path.len();
+                    for (_, zone_depth) in self.zone_stack.iter_mut() {
+                        *zone_depth = usize::min(*zone_depth, depth);
                     }
+
+                    self.update_current_node()?;
                 }
 
                 // Update pc, insn, and cycle
@@ -439,8 +579,13 @@ impl TraceCallback for Profiler {
                 self.insn = insn;
                 self.cycle = cycle;
             }
-            TraceEvent::RegisterSet { .. } => (),
+            TraceEvent::RegisterSet { idx, value } => {
+                if idx == REG_T0 {
+                    self.t0 = value;
+                }
+            }
             TraceEvent::MemorySet { .. } => (),
+            TraceEvent::Paging { cycles } => self.charge(cycles as usize, Some("[paging]"))?,
         }
         Ok(())
     }
@@ -536,6 +681,32 @@ impl ProfileBuilder {
         self.profile.sample.push(sample)
     }
 
+    fn folded(&self) -> String {
+        // Samples at different addresses of the same stack are merged.
+        let mut stacks = BTreeMap::<String, i64>::new();
+        for sample in &self.profile.sample {
+            let stack = sample
+                .location_id
+                .iter()
+                .rev()
+                .flat_map(|id| self.profile.location[*id as usize - 1].line.iter().rev())
+                .map(|line| {
+                    let func = &self.profile.function[line.function_id as usize - 1];
+                    // `;` separates the frames, but can appear in type names such as `[u8; 32]`.
+                    self.profile.string_table[func.name as usize].replace(';', ",")
+                })
+                .collect::<Vec<_>>()
+                .join(";");
+            *stacks.entry(stack).or_default() += sample.value[0];
+        }
+        stacks
+            .into_iter()
+            .fold(String::new(), |mut output, (stack, count)| {
+                writeln!(output, "{stack} {count}").unwrap();
+                output
+            })
+    }
+
     /// Dereferences strings, etc. in the protobuf for testing purposes.
     /// Returns a tuple of (frames, program counter, cycles)
     #[cfg(test)]
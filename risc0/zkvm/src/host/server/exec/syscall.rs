@@ -14,33 +14,53 @@
 
 //! Handlers for two-way private I/O between host and guest.
 
-use std::{cell::RefCell, cmp::min, collections::HashMap, rc::Rc, str::from_utf8};
+#[cfg(not(target_family = "wasm"))]
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{
+    cell::{Cell, RefCell},
+    cmp::min,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    io::{Cursor, SeekFrom},
+    rc::Rc,
+    str::from_utf8,
+};
 
-use anyhow::{anyhow, bail, Result};
+use anyhow::{anyhow, bail, ensure, Result};
 use bytes::Bytes;
+use crypto_bigint::{Encoding as _, U256};
 use risc0_zkvm_platform::{
     fileno,
+    memory::{GUEST_MIN_MEM, STACK_TOP},
     syscall::{
         nr::{
-            SYS_ARGC, SYS_ARGV, SYS_CYCLE_COUNT, SYS_GETENV, SYS_LOG, SYS_PANIC, SYS_RANDOM,
-            SYS_READ, SYS_VERIFY, SYS_VERIFY_INTEGRITY, SYS_WRITE,
+            SYS_ARGC, SYS_ARGV, SYS_CALL, SYS_CHANNEL_WRITE, SYS_CYCLE_COUNT, SYS_GETENV,
+            SYS_HEAP_LIMIT, SYS_LOG, SYS_LOG_EVENT, SYS_MEMORY_STATS, SYS_MODINV, SYS_MONT_R2,
+            SYS_OUT_OF_MEMORY, SYS_PANIC, SYS_PANIC_LOCATION, SYS_PROFILE_ZONE_ENTER,
+            SYS_PROFILE_ZONE_EXIT, SYS_RANDOM, SYS_READ, SYS_SEEK, SYS_TIME, SYS_VERIFIED_READ,
+            SYS_VERIFY, SYS_VERIFY_INTEGRITY, SYS_WRITE,
         },
-        reg_abi::{REG_A3, REG_A4, REG_A5},
-        SyscallName, DIGEST_BYTES, DIGEST_WORDS,
+        reg_abi::{REG_A3, REG_A4, REG_A5, REG_A6, REG_A7, REG_SP},
+        seek, SyscallName, DIGEST_BYTES, DIGEST_WORDS,
     },
-    WORD_SIZE,
+    PAGE_SIZE, WORD_SIZE,
 };
+#[cfg(target_family = "wasm")]
+use web_time::{SystemTime, UNIX_EPOCH};
 
 use crate::{
     host::client::{
         env::{Assumptions, ExecutorEnv},
         posix_io::PosixIo,
-        slice_io::SliceIo,
+        slice_io::{SliceIo, SliceIoTable},
     },
+    merkle_file::{MerkleTree, MERKLE_FILE_CHUNK_SIZE},
     sha::{Digest, Digestible},
-    Assumption, MaybePruned, PrunedValueError, ReceiptClaim,
+    Assumption, ExecutorImpl, GuestOutOfMemory, GuestPanic, MaybePruned, PrunedValueError,
+    ReceiptClaim, Session,
 };
 
+use super::{guest_log, profiler::Profiler};
+
 /// A host-side implementation of a system call.
 pub trait Syscall {
     /// Invokes the system call.
@@ -63,6 +83,12 @@ pub trait SyscallContext {
     /// Loads an individual byte from memory.
     fn load_u8(&mut self, addr: u32) -> Result<u8>;
 
+    /// Returns the indices of the pages of guest memory touched so far.
+    fn touched_pages(&self) -> BTreeSet<u32>;
+
+    /// Returns the program counter of the syscall being executed.
+    fn get_pc(&self) -> u32;
+
     /// Loads bytes from the given region of memory.
     fn load_region(&mut self, addr: u32, size: u32) -> Result<Vec<u8>> {
         let mut region = Vec::new();
@@ -85,19 +111,40 @@ impl<'a> SyscallTable<'a> {
         };
 
         let sys_verify = SysVerify::new(env.assumptions.clone());
+        let sys_panic = SysPanic::default();
+        let sys_call = SysCall::new(env);
 
         let posix_io = env.posix_io.clone();
-        this.with_syscall(SYS_CYCLE_COUNT, SysCycleCount)
+        this.with_syscall(SYS_CHANNEL_WRITE, SysChannelWrite(env.channels.clone()))
+            .with_syscall(SYS_CYCLE_COUNT, SysCycleCount)
+            .with_syscall(SYS_HEAP_LIMIT, SysHeapLimit(env.heap_limit))
             .with_syscall(SYS_LOG, posix_io.clone())
-            .with_syscall(SYS_PANIC, SysPanic)
+            .with_syscall(SYS_LOG_EVENT, SysLogEvent)
+            .with_syscall(SYS_MEMORY_STATS, SysMemoryStats)
+            .with_syscall(SYS_MODINV, SysModInv)
+            .with_syscall(SYS_MONT_R2, SysMontR2)
+            .with_syscall(SYS_OUT_OF_MEMORY, SysOutOfMemory)
+            .with_syscall(SYS_PANIC, sys_panic.clone())
+            .with_syscall(SYS_PANIC_LOCATION, sys_panic)
+            .with_syscall(SYS_PROFILE_ZONE_ENTER, SysProfileZone(None))
+            .with_syscall(SYS_PROFILE_ZONE_EXIT, SysProfileZone(None))
             .with_syscall(SYS_RANDOM, SysRandom)
             .with_syscall(SYS_GETENV, SysGetenv(env.env_vars.clone()))
             .with_syscall(SYS_READ, posix_io.clone())
+            .with_syscall(SYS_SEEK, posix_io.clone())
+            .with_syscall(SYS_TIME, SysTime(env.wall_clock))
             .with_syscall(SYS_WRITE, posix_io)
+            .with_syscall(
+                SYS_VERIFIED_READ,
+                SysSliceIo::new(Rc::new(RefCell::new(SysVerifiedRead::new(
+                    env.verified_files.clone(),
+                )))),
+            )
             .with_syscall(SYS_VERIFY, sys_verify.clone())
             .with_syscall(SYS_VERIFY_INTEGRITY, sys_verify)
             .with_syscall(SYS_ARGC, Args(env.args.clone()))
-            .with_syscall(SYS_ARGV, Args(env.args.clone()));
+            .with_syscall(SYS_ARGV, Args(env.args.clone()))
+            .with_syscall(SYS_CALL, sys_call);
         for (syscall, handler) in env.slice_io.borrow().inner.iter() {
             let handler = SysSliceIo::new(handler.clone());
             this.inner
@@ -137,6 +184,77 @@ impl Syscall for SysCycleCount {
     }
 }
 
+pub(crate) struct SysHeapLimit(pub Option<u32>);
+impl Syscall for SysHeapLimit {
+    fn syscall(
+        &mut self,
+        _syscall: &str,
+        _ctx: &mut dyn SyscallContext,
+        _to_guest: &mut [u32],
+    ) -> Result<(u32, u32)> {
+        Ok((self.0.unwrap_or(u32::MAX), 0))
+    }
+}
+
+pub(crate) struct SysOutOfMemory;
+impl Syscall for SysOutOfMemory {
+    fn syscall(
+        &mut self,
+        _syscall: &str,
+        ctx: &mut dyn SyscallContext,
+        _to_guest: &mut [u32],
+    ) -> Result<(u32, u32)> {
+        Err(GuestOutOfMemory {
+            bytes: ctx.load_register(REG_A3),
+            used: ctx.load_register(REG_A4),
+            limit: ctx.load_register(REG_A5),
+            pc: ctx.get_pc(),
+            backtrace: Vec::new(),
+        }
+        .into())
+    }
+}
+
+pub(crate) struct SysMemoryStats;
+impl Syscall for SysMemoryStats {
+    fn syscall(
+        &mut self,
+        _syscall: &str,
+        ctx: &mut dyn SyscallContext,
+        _to_guest: &mut [u32],
+    ) -> Result<(u32, u32)> {
+        let pages = ctx.touched_pages();
+
+        // The stack grows down from STACK_TOP, so the lowest page touched below it bounds the
+        // deepest the stack has been. The current stack pointer refines this for the live frame.
+        let lowest_stack_page = pages
+            .iter()
+            .map(|&page_idx| page_idx * PAGE_SIZE as u32)
+            .find(|&addr| addr >= GUEST_MIN_MEM as u32 && addr < STACK_TOP)
+            .unwrap_or(STACK_TOP);
+        let sp = ctx.load_register(REG_SP);
+        let stack_high_water = STACK_TOP.saturating_sub(u32::min(lowest_stack_page, sp));
+
+        Ok((stack_high_water, pages.len() as u32))
+    }
+}
+
+pub(crate) struct SysTime(pub bool);
+impl Syscall for SysTime {
+    fn syscall(
+        &mut self,
+        _syscall: &str,
+        _ctx: &mut dyn SyscallContext,
+        _to_guest: &mut [u32],
+    ) -> Result<(u32, u32)> {
+        let millis = match self.0 {
+            true => SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64,
+            false => u64::MAX,
+        };
+        Ok(((millis >> 32) as u32, millis as u32))
+    }
+}
+
 pub(crate) struct SysGetenv(pub HashMap<String, String>);
 impl Syscall for SysGetenv {
     fn syscall(
@@ -162,19 +280,243 @@ impl Syscall for SysGetenv {
     }
 }
 
-pub(crate) struct SysPanic;
+// The source location reported by SYS_PANIC_LOCATION, as file, line, and column.
+type PanicLocation = Rc<RefCell<Option<(String, u32, u32)>>>;
+
+#[derive(Clone, Default)]
+pub(crate) struct SysPanic {
+    location: PanicLocation,
+}
+
 impl Syscall for SysPanic {
     fn syscall(
         &mut self,
-        _syscall: &str,
+        syscall: &str,
         ctx: &mut dyn SyscallContext,
         _to_guest: &mut [u32],
     ) -> Result<(u32, u32)> {
         let buf_ptr = ctx.load_register(REG_A3);
         let buf_len = ctx.load_register(REG_A4);
         let from_guest = ctx.load_region(buf_ptr, buf_len)?;
+
+        if syscall == SYS_PANIC_LOCATION.as_str() {
+            let line = ctx.load_register(REG_A5);
+            let column = ctx.load_register(REG_A6);
+            let file = from_utf8(&from_guest)?.to_string();
+            *self.location.borrow_mut() = Some((file, line, column));
+            return Ok((0, 0));
+        }
+
         let msg = from_utf8(&from_guest)?;
-        bail!("Guest panicked: {msg}");
+        let location = self.location.borrow_mut().take();
+        let location = location
+            .as_ref()
+            .map(|(file, line, column)| (file.as_str(), *line, *column));
+        Err(GuestPanic::new(msg, location).into())
+    }
+}
+
+// Zones are recorded by the profiler, and ignored when there is none.
+#[derive(Clone)]
+pub(crate) struct SysProfileZone(pub Option<Rc<RefCell<Profiler>>>);
+impl Syscall for SysProfileZone {
+    fn syscall(
+        &mut self,
+        syscall: &str,
+        ctx: &mut dyn SyscallContext,
+        _to_guest: &mut [u32],
+    ) -> Result<(u32, u32)> {
+        let Some(profiler) = &self.0 else {
+            return Ok((0, 0));
+        };
+        if syscall == SYS_PROFILE_ZONE_EXIT.as_str() {
+            profiler.borrow_mut().exit_zone()?;
+        } else {
+            let name_ptr = ctx.load_register(REG_A3);
+            let name_len = ctx.load_register(REG_A4);
+            let name = String::from_utf8(ctx.load_region(name_ptr, name_len)?)?;
+            profiler.borrow_mut().enter_zone(&name)?;
+        }
+        Ok((0, 0))
+    }
+}
+
+pub(crate) struct SysChannelWrite(Rc<RefCell<BTreeMap<String, Vec<u8>>>>);
+impl Syscall for SysChannelWrite {
+    fn syscall(
+        &mut self,
+        _syscall: &str,
+        ctx: &mut dyn SyscallContext,
+        _to_guest: &mut [u32],
+    ) -> Result<(u32, u32)> {
+        let name_ptr = ctx.load_register(REG_A3);
+        let name_len = ctx.load_register(REG_A4);
+        let data_ptr = ctx.load_register(REG_A5);
+        let data_len = ctx.load_register(REG_A6);
+        let name = String::from_utf8(ctx.load_region(name_ptr, name_len)?)?;
+        let data = ctx.load_region(data_ptr, data_len)?;
+        self.0.borrow_mut().entry(name).or_default().extend(data);
+        Ok((0, 0))
+    }
+}
+
+pub(crate) struct SysLogEvent;
+impl Syscall for SysLogEvent {
+    fn syscall(
+        &mut self,
+        _syscall: &str,
+        ctx: &mut dyn SyscallContext,
+        _to_guest: &mut [u32],
+    ) -> Result<(u32, u32)> {
+        let level = ctx.load_register(REG_A3);
+        let target_ptr = ctx.load_register(REG_A4);
+        let target_len = ctx.load_register(REG_A5);
+        let msg_ptr = ctx.load_register(REG_A6);
+        let msg_len = ctx.load_register(REG_A7);
+        let target = ctx.load_region(target_ptr, target_len)?;
+        let msg = ctx.load_region(msg_ptr, msg_len)?;
+        guest_log::record(
+            level,
+            from_utf8(&target)?,
+            &String::from_utf8_lossy(&msg),
+            ctx.get_cycle(),
+        )?;
+        Ok((0, 0))
+    }
+}
+
+pub(crate) struct SysModInv;
+impl Syscall for SysModInv {
+    fn syscall(
+        &mut self,
+        _syscall: &str,
+        ctx: &mut dyn SyscallContext,
+        to_guest: &mut [u32],
+    ) -> Result<(u32, u32)> {
+        let buf_ptr = ctx.load_register(REG_A3);
+        let buf_len = ctx.load_register(REG_A4);
+        ensure!(
+            buf_len == 2 * U256::BYTES as u32 && to_guest.len() * WORD_SIZE == U256::BYTES,
+            "SYS_MODINV expects two 256-bit operands"
+        );
+        let from_guest = ctx.load_region(buf_ptr, buf_len)?;
+        let (x, modulus) = from_guest.split_at(U256::BYTES);
+        let (inv, is_some) = U256::from_le_slice(x).inv_mod(&U256::from_le_slice(modulus));
+
+        // The guest checks the result, so an operand with no inverse gets zero.
+        let inv = if bool::from(is_some) { inv } else { U256::ZERO };
+        bytemuck::cast_slice_mut(to_guest).clone_from_slice(&inv.to_le_bytes());
+        Ok((0, 0))
+    }
+}
+
+// Computes R^2 mod n for Montgomery multiplication with R = 2^(32 * words),
+// where n is the number of words of the modulus.
+pub(crate) struct SysMontR2;
+impl Syscall for SysMontR2 {
+    fn syscall(
+        &mut self,
+        _syscall: &str,
+        ctx: &mut dyn SyscallContext,
+        to_guest: &mut [u32],
+    ) -> Result<(u32, u32)> {
+        let buf_ptr = ctx.load_register(REG_A3);
+        let buf_len = ctx.load_register(REG_A4);
+        ensure!(
+            buf_len as usize == to_guest.len() * WORD_SIZE,
+            "SYS_MONT_R2 expects a result as long as the modulus"
+        );
+        let n: Vec<u32> = bytemuck::pod_collect_to_vec(&ctx.load_region(buf_ptr, buf_len)?);
+        ensure!(
+            n.iter().any(|&word| word != 0),
+            "SYS_MONT_R2 modulus is zero"
+        );
+
+        // Double 1 modulo n, 2 * 32 * words times. Keeping r < n, each doubling
+        // needs at most one subtraction.
+        let mut r = vec![0u32; n.len()];
+        r[0] = 1;
+        reduce_once(&mut r, false, &n);
+        for _ in 0..2 * 32 * n.len() {
+            let mut carry = 0;
+            for word in r.iter_mut() {
+                let next = *word >> 31;
+                *word = (*word << 1) | carry;
+                carry = next;
+            }
+            reduce_once(&mut r, carry != 0, &n);
+        }
+        to_guest.copy_from_slice(&r);
+        Ok((0, 0))
+    }
+}
+
+// Subtract n from (overflow, r) if it is at least n.
+fn reduce_once(r: &mut [u32], overflow: bool, n: &[u32]) {
+    let ge = overflow || r.iter().rev().cmp(n.iter().rev()).is_ge();
+    if ge {
+        let mut borrow = false;
+        for (r, n) in r.iter_mut().zip(n) {
+            let (diff, b1) = r.overflowing_sub(*n);
+            let (diff, b2) = diff.overflowing_sub(borrow as u32);
+            *r = diff;
+            borrow = b1 || b2;
+        }
+    }
+}
+
+// Serves chunks of verified files, with their Merkle proofs. Trees are built
+// the first time a file is opened.
+pub(crate) struct SysVerifiedRead {
+    files: HashMap<Digest, Bytes>,
+    trees: HashMap<Digest, MerkleTree>,
+}
+
+impl SysVerifiedRead {
+    fn new(files: HashMap<Digest, Bytes>) -> Self {
+        Self {
+            files,
+            trees: HashMap::new(),
+        }
+    }
+}
+
+impl SliceIo for SysVerifiedRead {
+    fn handle_io(&mut self, _syscall: &str, from_guest: Bytes) -> Result<Bytes> {
+        ensure!(
+            from_guest.len() == DIGEST_BYTES + WORD_SIZE,
+            "SYS_VERIFIED_READ: malformed request"
+        );
+        let root = Digest::try_from(&from_guest[..DIGEST_BYTES])?;
+        let op = u32::from_le_bytes(from_guest[DIGEST_BYTES..].try_into()?);
+        let data = self
+            .files
+            .get(&root)
+            .ok_or_else(|| anyhow!("SYS_VERIFIED_READ: no verified file with root {root}"))?;
+        let tree = self
+            .trees
+            .entry(root)
+            .or_insert_with(|| MerkleTree::new(data));
+
+        let mut words: Vec<u32> = Vec::new();
+        if op == u32::MAX {
+            let len = data.len() as u64;
+            words.extend([len as u32, (len >> 32) as u32]);
+            words.extend(tree.tree_root().as_words());
+        } else {
+            let index = op as usize;
+            let chunk = data
+                .chunks(MERKLE_FILE_CHUNK_SIZE)
+                .nth(index)
+                .ok_or_else(|| anyhow!("SYS_VERIFIED_READ: chunk {index} is out of bounds"))?;
+            let mut chunk_words = vec![0u32; chunk.len().div_ceil(WORD_SIZE)];
+            bytemuck::cast_slice_mut(&mut chunk_words)[..chunk.len()].copy_from_slice(chunk);
+            words.extend(chunk_words);
+            for digest in tree.proof(index) {
+                words.extend(digest.as_words());
+            }
+        }
+        Ok(bytemuck::cast_slice(&words).to_vec().into())
     }
 }
 
@@ -232,11 +574,14 @@ impl SysVerify {
     }
 
     fn sys_verify(&mut self, mut from_guest: Vec<u8>, to_guest: &mut [u32]) -> Result<(u32, u32)> {
-        if from_guest.len() != DIGEST_BYTES * 2 {
+        // The guest sends a channels digest after the journal digest if the receipt it verifies
+        // has channels.
+        if from_guest.len() != DIGEST_BYTES * 2 && from_guest.len() != DIGEST_BYTES * 3 {
             bail!(
-                "sys_verify call with input of length {} bytes; expected {}",
+                "sys_verify call with input of length {} bytes; expected {} or {}",
                 from_guest.len(),
-                DIGEST_BYTES * 2
+                DIGEST_BYTES * 2,
+                DIGEST_BYTES * 3
             );
         }
         if to_guest.len() != DIGEST_WORDS + 1 {
@@ -247,6 +592,13 @@ impl SysVerify {
             );
         }
 
+        let channels_digest: Digest = match from_guest.len() {
+            len if len == DIGEST_BYTES * 3 => from_guest
+                .split_off(DIGEST_BYTES * 2)
+                .try_into()
+                .map_err(|vec| anyhow!("failed to convert to [u8; DIGEST_BYTES]: {vec:?}"))?,
+            _ => Digest::ZERO,
+        };
         let journal_digest: Digest = from_guest
             .split_off(DIGEST_BYTES)
             .try_into()
@@ -266,7 +618,12 @@ impl SysVerify {
         let mut assumption: Option<Assumption> = None;
         for cached_assumption in self.assumptions.borrow().cached.iter() {
             let assumption_claim = cached_assumption.claim()?;
-            let cmp_result = Self::sys_verify_cmp(&assumption_claim, &image_id, &journal_digest);
+            let cmp_result = Self::sys_verify_cmp(
+                &assumption_claim,
+                &image_id,
+                &journal_digest,
+                &channels_digest,
+            );
             let (post_state_digest, sys_exit_code) = match cmp_result {
                 Ok(None) => continue,
                 // If the required values to compare were pruned, go the next assumption.
@@ -302,17 +659,21 @@ impl SysVerify {
         claim: &MaybePruned<ReceiptClaim>,
         image_id: &Digest,
         journal_digest: &Digest,
+        channels_digest: &Digest,
     ) -> Result<Option<(Digest, u32)>, PrunedValueError> {
-        let assumption_journal_digest = claim
-            .as_value()?
-            .output
-            .as_value()?
-            .as_ref()
+        let output = claim.as_value()?.output.as_value()?.as_ref();
+        let assumption_journal_digest = output
             .map(|output| output.journal.digest())
             .unwrap_or(Digest::ZERO);
+        let assumption_channels_digest = output
+            .map(|output| output.channels.digest())
+            .unwrap_or(Digest::ZERO);
         let assumption_image_id = claim.as_value()?.pre.digest();
 
-        if &assumption_journal_digest != journal_digest || &assumption_image_id != image_id {
+        if &assumption_journal_digest != journal_digest
+            || &assumption_channels_digest != channels_digest
+            || &assumption_image_id != image_id
+        {
             return Ok(None);
         }
 
@@ -399,6 +760,158 @@ impl Syscall for Args {
     }
 }
 
+/// The maximum depth of nested calls made with `env::call`.
+///
+/// Each nested call is executed on the host's stack, so this bounds the stack
+/// used by a program that calls itself.
+pub const MAX_CALL_DEPTH: usize = 8;
+
+/// Runs another guest program on behalf of the guest.
+///
+/// The called program is executed to completion with the input sent by the
+/// guest, and its journal is returned. The resulting claim is added to the
+/// assumptions so that the guest's subsequent `env::verify` can be resolved,
+/// and the [Session] is kept so it can be proven along with the caller.
+///
+/// The called program inherits the limits, environment variables and I/O of
+/// the caller, and its cycles are charged to the session limit of the caller.
+pub(crate) struct SysCall<'a> {
+    programs: HashMap<Digest, Bytes>,
+    env_vars: HashMap<String, String>,
+    segment_limit_po2: Option<u32>,
+    session_limit: Option<u64>,
+    no_progress_limit: Option<u64>,
+    heap_limit: Option<u32>,
+    wall_clock: bool,
+    posix_io: PosixIo<'a>,
+    slice_io: Rc<RefCell<SliceIoTable<'a>>>,
+    verified_files: HashMap<Digest, Bytes>,
+    depth: usize,
+    assumptions: Rc<RefCell<Assumptions>>,
+    calls: Rc<RefCell<Vec<Session>>>,
+    cycles: Rc<Cell<u64>>,
+    stored_result: Option<Bytes>,
+}
+
+impl<'a> SysCall<'a> {
+    fn new(env: &ExecutorEnv<'a>) -> Self {
+        Self {
+            programs: env.programs.clone(),
+            env_vars: env.env_vars.clone(),
+            segment_limit_po2: env.segment_limit_po2,
+            session_limit: env.session_limit,
+            no_progress_limit: env.no_progress_limit,
+            heap_limit: env.heap_limit,
+            wall_clock: env.wall_clock,
+            posix_io: env.posix_io.borrow().clone(),
+            slice_io: env.slice_io.clone(),
+            verified_files: env.verified_files.clone(),
+            depth: env.call_depth,
+            assumptions: env.assumptions.clone(),
+            calls: env.calls.clone(),
+            cycles: env.call_cycles.clone(),
+            stored_result: None,
+        }
+    }
+
+    fn call(&mut self, ctx: &mut dyn SyscallContext, from_guest: &[u8]) -> Result<Bytes> {
+        ensure!(
+            from_guest.len() >= DIGEST_BYTES,
+            "sys_call call with input of length {} bytes; expected at least {DIGEST_BYTES}",
+            from_guest.len()
+        );
+        ensure!(
+            self.depth < MAX_CALL_DEPTH,
+            "sys_call: calls are limited to a depth of {MAX_CALL_DEPTH}"
+        );
+        let image_id = Digest::try_from(&from_guest[..DIGEST_BYTES])?;
+        let elf = self
+            .programs
+            .get(&image_id)
+            .ok_or_else(|| anyhow!("sys_call: no program registered for image ID: {image_id}"))?;
+
+        tracing::debug!("SYS_CALL: {image_id}");
+
+        // The called program may only use the cycles the caller has left.
+        let used_cycles = ctx.get_cycle() + self.cycles.get();
+        let session_limit = match self.session_limit {
+            Some(limit) => {
+                ensure!(used_cycles < limit, "Session limit exceeded");
+                Some(limit - used_cycles)
+            }
+            None => None,
+        };
+
+        let mut posix_io = self.posix_io.clone();
+        posix_io.with_read_fd(
+            fileno::STDIN,
+            Cursor::new(from_guest[DIGEST_BYTES..].to_vec()),
+        );
+        let call_cycles = Rc::new(Cell::new(0));
+        let env = ExecutorEnv {
+            env_vars: self.env_vars.clone(),
+            segment_limit_po2: self.segment_limit_po2,
+            session_limit,
+            no_progress_limit: self.no_progress_limit,
+            heap_limit: self.heap_limit,
+            wall_clock: self.wall_clock,
+            posix_io: Rc::new(RefCell::new(posix_io)),
+            slice_io: self.slice_io.clone(),
+            programs: self.programs.clone(),
+            verified_files: self.verified_files.clone(),
+            call_cycles: call_cycles.clone(),
+            call_depth: self.depth + 1,
+            ..Default::default()
+        };
+        let session = ExecutorImpl::from_elf(env, elf)?.run()?;
+        self.cycles
+            .set(self.cycles.get() + session.user_cycles + call_cycles.get());
+        ensure!(
+            session.exit_code.is_ok(),
+            "sys_call: program {image_id} exited with {:?}",
+            session.exit_code
+        );
+
+        let journal = session.journal.clone().unwrap_or_default().bytes;
+        self.assumptions
+            .borrow_mut()
+            .cached
+            .push(Assumption::Unresolved(session.claim()?.into()));
+        self.calls.borrow_mut().push(session);
+        Ok(journal.into())
+    }
+}
+
+/// Like [SysSliceIo], the guest makes two calls: the first runs the called
+/// program and returns the length of its journal, and the second returns the
+/// journal.
+impl<'a> Syscall for SysCall<'a> {
+    fn syscall(
+        &mut self,
+        _syscall: &str,
+        ctx: &mut dyn SyscallContext,
+        to_guest: &mut [u32],
+    ) -> Result<(u32, u32)> {
+        Ok(match self.stored_result.take() {
+            None => {
+                let buf_ptr = ctx.load_register(REG_A3);
+                let buf_len = ctx.load_register(REG_A4);
+                let from_guest = ctx.load_region(buf_ptr, buf_len)?;
+                let result = self.call(ctx, &from_guest)?;
+                let len = result.len() as u32;
+                self.stored_result = Some(result);
+                (len, 0)
+            }
+            Some(stored) => {
+                let to_guest_bytes: &mut [u8] = bytemuck::cast_slice_mut(to_guest);
+                ensure!(stored.len() <= to_guest_bytes.len());
+                to_guest_bytes[..stored.len()].clone_from_slice(&stored);
+                (0, 0)
+            }
+        })
+    }
+}
+
 /// A wrapper around a SliceIo that exposes it as a Syscall handler.
 pub struct SysSliceIo<'a> {
     handler: Rc<RefCell<dyn SliceIo + 'a>>,
@@ -469,6 +982,8 @@ impl<'a> Syscall for PosixIo<'a> {
             self.sys_read(ctx, to_guest)
         } else if syscall == SYS_WRITE.as_str() {
             self.sys_write(ctx)
+        } else if syscall == SYS_SEEK.as_str() {
+            self.sys_seek(ctx)
         } else if syscall == SYS_LOG.as_str() {
             self.sys_log(ctx)
         } else {
@@ -566,6 +1081,29 @@ impl<'a> PosixIo<'a> {
         Ok((0, 0))
     }
 
+    fn sys_seek(&mut self, ctx: &mut dyn SyscallContext) -> Result<(u32, u32)> {
+        let fd = ctx.load_register(REG_A3);
+        let whence = ctx.load_register(REG_A4);
+        let offset_lo = ctx.load_register(REG_A5);
+        let offset_hi = ctx.load_register(REG_A6);
+        let offset = (((offset_hi as u64) << 32) | offset_lo as u64) as i64;
+        let file = self
+            .seek_fds
+            .get_mut(&fd)
+            .ok_or(anyhow!("Bad seek file descriptor {fd}"))?;
+
+        tracing::trace!("sys_seek(fd: {fd}, offset: {offset}, whence: {whence})");
+
+        let pos = match whence {
+            seek::SET => SeekFrom::Start(offset.try_into()?),
+            seek::CUR => SeekFrom::Current(offset),
+            seek::END => SeekFrom::End(offset),
+            _ => bail!("Invalid seek whence {whence}"),
+        };
+        let pos = file.borrow_mut().seek(pos)?;
+        Ok((pos as u32, (pos >> 32) as u32))
+    }
+
     fn sys_log(&mut self, ctx: &mut dyn SyscallContext) -> Result<(u32, u32)> {
         let buf_ptr = ctx.load_register(REG_A3);
         let buf_len = ctx.load_register(REG_A4);
@@ -16,7 +16,7 @@ use std::{
     collections::{BTreeMap, HashSet},
     io::Cursor,
     str::from_utf8,
-    sync::Mutex,
+    sync::{Arc, Mutex},
 };
 
 use anyhow::Result;
@@ -26,7 +26,12 @@ use risc0_zkvm_methods::{
     multi_test::{MultiTestSpec, SYS_MULTI_TEST},
     BLST_ELF, HELLO_COMMIT_ELF, MULTI_TEST_ELF, RAND_ELF, SLICE_IO_ELF, STANDARD_LIB_ELF,
 };
-use risc0_zkvm_platform::{fileno, syscall::nr::SYS_RANDOM, PAGE_SIZE, WORD_SIZE};
+use risc0_zkvm_platform::{
+    fileno,
+    memory::{GUEST_MIN_MEM, STACK_TOP},
+    syscall::nr::SYS_RANDOM,
+    PAGE_SIZE, WORD_SIZE,
+};
 use sha2::{Digest as _, Sha256};
 use test_log::test;
 
@@ -40,7 +45,8 @@ use crate::{
     },
     serde::to_vec,
     sha::{Digest, Digestible},
-    ExecutorEnv, ExecutorImpl, ExitCode,
+    ExecutorEnv, ExecutorImpl, ExitCode, GuestOutOfMemory, GuestPanic, HostTimestamp,
+    InMemoryStore, LocalDirStore, SegmentStore, Session,
 };
 
 fn run_test(spec: MultiTestSpec) {
@@ -414,6 +420,672 @@ fn short_read_combinations() {
     }
 }
 
+#[test]
+fn green_threads() {
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::GreenThreads)
+        .unwrap()
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    let (log, a, b, main): (u32, u32, u32, u32) = session.journal.unwrap().decode().unwrap();
+    // Threads interleave in FIFO order at each yield, and the parked thread
+    // only runs once unparked.
+    assert_eq!(log, 12123);
+    assert_eq!((a, b, main), (2, 2, 0));
+}
+
+#[test]
+fn heap_stats() {
+    const NBYTES: u32 = 1 << 20;
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::HeapStats { nbytes: NBYTES })
+        .unwrap()
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    let (used, peak_ok, freed): (u32, bool, u32) = session.journal.unwrap().decode().unwrap();
+    assert!(used >= NBYTES);
+    assert!(peak_ok);
+    assert_eq!(used, freed);
+}
+
+#[test]
+fn heap_limit() {
+    const LIMIT: u32 = 1 << 19;
+    const NBYTES: u32 = 1 << 20;
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::HeapStats { nbytes: NBYTES })
+        .unwrap()
+        .heap_limit(Some(LIMIT))
+        .build()
+        .unwrap();
+    let err = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .err()
+        .unwrap();
+    let oom = err.downcast_ref::<GuestOutOfMemory>().unwrap();
+    assert_eq!(oom.bytes, NBYTES);
+    assert_eq!(oom.limit, LIMIT);
+    assert!(oom.used < LIMIT);
+    assert!(err.to_string().contains("allocation of 1048576 bytes"));
+}
+
+#[test]
+fn memory_stats() {
+    const HEAP_BYTES: u32 = 256 * 1024;
+    const STACK_BYTES: u32 = 32 * 1024;
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::MemoryStats {
+            heap_bytes: HEAP_BYTES,
+            stack_bytes: STACK_BYTES,
+        })
+        .unwrap()
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    let (heap_used, stack_before, stack_after, pages_before, pages_after): (
+        u32,
+        u32,
+        u32,
+        u32,
+        u32,
+    ) = session.journal.unwrap().decode().unwrap();
+    assert!(heap_used >= HEAP_BYTES);
+    assert!(stack_before > 0);
+    assert!(stack_after >= STACK_BYTES);
+    assert!(stack_after <= STACK_TOP - GUEST_MIN_MEM as u32);
+    assert!(pages_after >= pages_before + (HEAP_BYTES + STACK_BYTES) / PAGE_SIZE as u32);
+}
+
+#[test]
+fn read_seq() {
+    let items: Vec<u32> = (0..10_000).collect();
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::ReadSeq)
+        .unwrap()
+        .write(&items)
+        .unwrap()
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    let (len, sum, allocated): (u32, u64, u32) = session.journal.unwrap().decode().unwrap();
+    assert_eq!(len, items.len() as u32);
+    assert_eq!(sum, items.iter().copied().map(u64::from).sum::<u64>());
+    // Elements are deserialized one at a time, never buffered on the heap.
+    assert_eq!(allocated, 0);
+}
+
+#[test]
+fn random_bytes() {
+    let run = || {
+        let env = ExecutorEnv::builder()
+            .write(&MultiTestSpec::RandomBytes { len: 40 })
+            .unwrap()
+            .build()
+            .unwrap();
+        let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+            .unwrap()
+            .run()
+            .unwrap();
+        let (seed, bytes): (Digest, Vec<u8>) = session.journal.unwrap().decode().unwrap();
+        (seed, bytes)
+    };
+    let (seed, bytes) = run();
+    assert_eq!(run(), (seed, bytes.clone()));
+
+    // Nothing has been read into the input digest or written to the journal
+    // when the guest first draws random bytes.
+    let expected_seed = Sha256::new()
+        .chain_update(b"risc0.guest.random")
+        .chain_update(Digest::ZERO.as_bytes())
+        .chain_update(Sha256::digest(b""))
+        .finalize();
+    assert_eq!(seed.as_bytes(), expected_seed.as_slice());
+
+    let block = |counter: u32| {
+        Sha256::new()
+            .chain_update(seed.as_bytes())
+            .chain_update(counter.to_le_bytes())
+            .finalize()
+    };
+    assert_eq!(bytes[..32], block(0)[..]);
+    assert_eq!(bytes[32..], block(1)[..8]);
+}
+
+#[test]
+fn host_timestamp() {
+    let run = |wall_clock| {
+        let env = ExecutorEnv::builder()
+            .write(&MultiTestSpec::HostTimestamp)
+            .unwrap()
+            .wall_clock(wall_clock)
+            .build()
+            .unwrap();
+        let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+            .unwrap()
+            .run()
+            .unwrap();
+        session
+            .journal
+            .unwrap()
+            .decode::<Option<HostTimestamp>>()
+            .unwrap()
+    };
+
+    let now = || {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64
+    };
+    let before = now();
+    let timestamp = run(true).unwrap();
+    assert!((before..=now()).contains(&timestamp.unix_millis));
+
+    assert_eq!(run(false), None);
+}
+
+#[test]
+fn guest_log_events() {
+    use tracing::{field::Visit, level_filters::LevelFilter, Level};
+    use tracing_subscriber::{layer::SubscriberExt as _, Layer};
+
+    #[derive(Clone, Default)]
+    struct Collect(Arc<Mutex<Vec<(String, Level, String)>>>);
+
+    impl<S: tracing::Subscriber> Layer<S> for Collect {
+        fn on_event(
+            &self,
+            event: &tracing::Event<'_>,
+            _ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            struct Message(String);
+            impl Visit for Message {
+                fn record_debug(
+                    &mut self,
+                    field: &tracing::field::Field,
+                    value: &dyn std::fmt::Debug,
+                ) {
+                    if field.name() == "message" {
+                        self.0 = format!("{value:?}");
+                    }
+                }
+            }
+
+            let metadata = event.metadata();
+            if !metadata.target().starts_with("multi_test") {
+                return;
+            }
+            let mut msg = Message(String::new());
+            event.record(&mut msg);
+            self.0
+                .lock()
+                .unwrap()
+                .push((metadata.target().to_string(), *metadata.level(), msg.0));
+        }
+    }
+
+    let collect = Collect::default();
+    let subscriber =
+        tracing_subscriber::registry().with(collect.clone().with_filter(LevelFilter::DEBUG));
+    tracing::subscriber::with_default(subscriber, || {
+        let env = ExecutorEnv::builder()
+            .write(&MultiTestSpec::LogEvents)
+            .unwrap()
+            .build()
+            .unwrap();
+        ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+            .unwrap()
+            .run()
+            .unwrap();
+    });
+
+    // The trace event is filtered out by the subscriber.
+    assert_eq!(
+        *collect.0.lock().unwrap(),
+        [
+            ("multi_test".to_string(), Level::INFO, "info 1".to_string()),
+            (
+                "multi_test::custom".to_string(),
+                Level::DEBUG,
+                "debug 2".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn verified_file() {
+    let data: Vec<u8> = (0..5000u32).map(|i| (i * 31 % 251) as u8).collect();
+    let root = crate::merkle_file_root(&data);
+    // Ranges within a chunk, across chunks, at the end, and empty.
+    let ranges = vec![(10, 20), (1000, 3100), (4990, 5000), (7, 7)];
+    let env = ExecutorEnv::builder()
+        .add_verified_file(data.clone())
+        .write(&MultiTestSpec::VerifiedFile {
+            root,
+            ranges: ranges.clone(),
+        })
+        .unwrap()
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    let (len, reads): (u64, Vec<Vec<u8>>) = session.journal.unwrap().decode().unwrap();
+    assert_eq!(len, data.len() as u64);
+    for ((start, end), read) in ranges.into_iter().zip(reads) {
+        assert_eq!(read, &data[start as usize..end as usize]);
+    }
+
+    // Opening a file that the host does not have fails.
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::VerifiedFile {
+            root,
+            ranges: vec![],
+        })
+        .unwrap()
+        .build()
+        .unwrap();
+    let err = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("no verified file"));
+}
+
+#[test]
+fn output_channels() {
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::Channels)
+        .unwrap()
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    assert_eq!(
+        session.journal.as_ref().unwrap().decode::<u32>().unwrap(),
+        7
+    );
+    assert_eq!(session.channels.get("result"), Some(b"result".as_slice()));
+    assert_eq!(
+        session.channels.get("events"),
+        Some([1u8, 0, 0, 0, 2, 0, 0, 0].as_slice())
+    );
+
+    // The output digest committed by the guest matches the claim on the host.
+    let claim = session.claim().unwrap();
+    let segment = session.segments.last().unwrap().resolve().unwrap();
+    assert_eq!(segment.inner.output_digest, Some(claim.output.digest()));
+}
+
+#[test]
+fn signature_verification() {
+    let verify = |spec| {
+        let env = ExecutorEnv::builder()
+            .write(&spec)
+            .unwrap()
+            .build()
+            .unwrap();
+        let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+            .unwrap()
+            .run()
+            .unwrap();
+        session.journal.unwrap().decode::<bool>().unwrap()
+    };
+
+    let ed25519 = |msg: &[u8]| {
+        MultiTestSpec::VerifyEd25519 {
+        sig: hex::decode("85503eb6bd9ccb4d59447f6177ee7bd9c96f92860a9bbc664c180f421772b5a76b074252ec4c271683891c35d5296808965be6283a7fbe902daae80280d10608").unwrap(),
+        pk: hex::decode("07e627e2dd68274ec18e45ef7355b5904245f60af372740ae5f07986b70c5eba").unwrap(),
+        msg: msg.to_vec(),
+    }
+    };
+    let msg = hex::decode("9abf0251b25ae00036fe293eeacc53e968204ecda7d9fe7f0459075c0d02f4ce76bd53bd9d07f1a7953c92ee10cd05d33a82").unwrap();
+    assert!(verify(ed25519(&msg)));
+    assert!(!verify(ed25519(b"tampered")));
+
+    let secp256k1 = |msg_hash: &str| {
+        MultiTestSpec::VerifySecp256k1 {
+        sig: hex::decode("458bac561efa1893f93fa1b410109ca99056396c7ab65cd2353d03ed5a5fced7063ba105c699fcda605a835763a510f5624a5b46d75c59837a59b8a3f7c1669a").unwrap(),
+        pk: hex::decode("0390aa42cc1cd41537683337d69c5a0ed86c1680e7bc965b78e05c8218a86d0dca").unwrap(),
+        msg_hash: hex::decode(msg_hash).unwrap(),
+    }
+    };
+    assert!(verify(secp256k1(
+        "972a356f212815ac2342d5d76a8fd19f7f6f9a8073d84383d1beaa5bf54b937d"
+    )));
+    assert!(!verify(secp256k1(
+        "66b35dae5a112a95b7eb1c8b658247dd472f9b5c1c09b545da1efddd23e685ea"
+    )));
+
+    // RS256 with the key in rsa2048-priv.pem.
+    let rsa = |msg: &[u8]| {
+        MultiTestSpec::VerifyRsa {
+        sig: hex::decode("ac16670c051dce7fe8523a486c2ce5d1a5466afbb6950929ef5b842531a1f7fd83e2fb632871da97fd1802654302ed8e3276ebfd945357492c051c49642bb14741f5357519aa6e518847f8d9e15c93405fdf7f4fae39267d76cda28678633f8c804191f16ab4f7e0c6145e5cbd015c599c4fb6e764be16549dfc50af73039ff7d65f9c79d0d2539c0c079a013c9116b46f8483611a0922d0474d2ea024e98ec4236705be8edf5fd8e029651e2341ec1bbc26baad0765b8a5c3e84c8cced61dbb63f5676b428bfcc7d834a1e3c8002e5157d85477cdc1ad6c04e1f6fd96c71d3ed49f22ec94d6d6dfeeccc6b44ac0377c4ed10447689b6ee46ca115db485b0246").unwrap(),
+        n: hex::decode("b6c42c515f10a6aaf282c63edbe24243a170f3fa2633bd4833637f47ca4f6f36e03a5d29efc3191ac80f390d874b39e30f414fcec1fca0ed81e547edc2cd382c76f61c9018973db9fa537972a7c701f6b77e0982dfc15fc01927ee5e7cd94b4f599ff07013a7c8281bdf22dcbc9ad7cabb7c4311c982f58edb7213ad4558b332266d743aed8192d1884cadb8b14739a8dada66dc970806d9c7ac450cb13d0d7c575fb198534fc61bc41bc0f0574e0e0130c7bbbfbdfdc9f6a6e2e3e2aff1cbeac89ba57884528d55cfb08327a1e8c89f4e003cf2888e933241d9d695bcbbacdc90b44e3e095fa37058ea25b13f5e295cbeac6de838ab8c50af61e298975b872f").unwrap(),
+        e: hex::decode("010001").unwrap(),
+        msg: msg.to_vec(),
+    }
+    };
+    assert!(verify(rsa(b"rs256 signed message")));
+    assert!(!verify(rsa(b"tampered")));
+}
+
+#[test]
+fn bn254_pairing_check() {
+    let check = |pairs: &[(&str, &str)]| {
+        let pairs = pairs
+            .iter()
+            .map(|(p, q)| (hex::decode(p).unwrap(), hex::decode(q).unwrap()))
+            .collect();
+        let env = ExecutorEnv::builder()
+            .write(&MultiTestSpec::Bn254PairingCheck { pairs })
+            .unwrap()
+            .build()
+            .unwrap();
+        let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+            .unwrap()
+            .run()
+            .unwrap();
+        session.journal.unwrap().decode::<bool>().unwrap()
+    };
+
+    // The generators of G1 and G2, and the negation of the G1 generator.
+    let g1 = "0000000000000000000000000000000000000000000000000000000000000001\
+              0000000000000000000000000000000000000000000000000000000000000002";
+    let neg_g1 = "0000000000000000000000000000000000000000000000000000000000000001\
+                  30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45";
+    let g2 = "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2\
+              1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed\
+              090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b\
+              12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa";
+    assert!(check(&[(g1, g2), (neg_g1, g2)]));
+    assert!(!check(&[(g1, g2), (g1, g2)]));
+    assert!(check(&[]));
+
+    // e(aP, bQ) * e(-abP, Q) == 1, for a = 0x1cde...1024 and b = 0x1234.
+    let a_g1 = "1fc7c9a9ffbc08e9c5716228cbf998b0fa8e05e5d3f4935f8896d20706c60e3f\
+                058ee3f48612244229d2e7940ada68885c238be9c1a047fbe3aa540a8a6e85d5";
+    let b_g2 = "27417f38b1c254f73adfa7e5db4238b8b17a5d122bccc10a76b220dadf3fce13\
+                2e08166dfcb4c0c5adc2c73c3ffe3a5980c5fe7ec261aad9a3d1c03463caba2c\
+                300c897f638bb730123ab0dc0fc9d8efc5396399aa192b49103237970765309e\
+                110dbe9269a6028dab9243c3fe782f056f2267cc414a45259b3aab2d03c4721b";
+    let neg_ab_g1 = "148a77e05d96d80c083611a8deacacf7dea0d7e36ed94d3b24898178b4081d93\
+                     0c8f678bb8701f3c6091df11549c2f2208cbe528ffb1ed9c1321bf1ab031ed84";
+    let neg_ab_plus_one_g1 = "1dbcd5f857f3abc9976aab666f27e496e270f20625c5428bd919059bdccd83d0\
+                              0fb45af42b4a69cf9201c272497d0fe3e713af37f21fa9d92d6ed985f181c850";
+    assert!(check(&[(a_g1, b_g2), (neg_ab_g1, g2)]));
+    assert!(!check(&[(a_g1, b_g2), (neg_ab_plus_one_g1, g2)]));
+}
+
+fn run_bn254<T: serde::de::DeserializeOwned>(spec: MultiTestSpec) -> T {
+    let env = ExecutorEnv::builder()
+        .write(&spec)
+        .unwrap()
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    session.journal.unwrap().decode().unwrap()
+}
+
+#[test]
+fn bn254_fields() {
+    let decode = |hex: &str| hex::decode(hex).unwrap();
+    let (fq, fq2, fq12): (Vec<Vec<u8>>, Vec<Vec<u8>>, Vec<bool>) =
+        run_bn254(MultiTestSpec::Bn254Fields {
+            fq: (
+                decode("0d014f6388442a859db92ed04b870eb1ad870e3a3ba64ebe7d07ad58f4da378b"),
+                decode("2459c6e42eb0753254265a21e49230598d74ac98440c21b3fb8220fd19b36e23"),
+            ),
+            fq2: (
+                decode(
+                    "21018aacc4762246213a921c2a09779722d53295faa639aa48cd372dc681c26a\
+                     013806bbbb3aeefa6e04dc6826a11cfe6ba07e6dd62f93009b5ad0b52e7b9df2",
+                ),
+                decode(
+                    "0360f9e9cce0ca9896471e849faf4338c271e9eff3c8047c43f8962ef2c81e0f\
+                     280eae23dca17a41c6171b7b8a2aaac2998d51a29697e30c0e5855fe6cdd545d",
+                ),
+            ),
+        });
+    let fq: Vec<String> = fq.iter().map(hex::encode).collect();
+    assert_eq!(
+        fq,
+        [
+            // a * b
+            "2face01a9d80e14c50cea56d27a3fa3179b6c53ae5e134675055e4ec009bb89d",
+            // a + b, which wraps around the modulus
+            "00f6c7d4d5c2ff8e398f433bae97e6ada37a50411740a5e53c69423f3610a867",
+            // a - b, which wraps around zero
+            "190bd6f23ac5557d01e31a64e87636b5b793cc33600bf797bda61872b3a3c6af",
+            // -a
+            "2362ff0f58ed75a41a9716e635fa49abe9fa5c572ccb7bcebf18debde3a2c5bc",
+            // 1 / a
+            "0d2638bc77058f707c0d4ffaf5aaaff167e7453f9e2ccf3d8741a2dc5dfaaad2",
+        ]
+    );
+    let fq2: Vec<String> = fq2.iter().map(hex::encode).collect();
+    assert_eq!(
+        fq2,
+        [
+            // a * b
+            "07ed96c501bfa63c554404e16bd9e598c8ee49cd00e0c53021890981e08d9b72\
+             1f6e819214cfca410b7b323dde34f272a69f4dba8c95096e22b17395fe033208",
+            // a + b
+            "246284969156ecdeb781b0a0c9b8bacfe5471c85ee6e3e268cc5cd5cb949e079\
+             2946b4df97dc693c341bf7e3b0cbc7c1052dd0106cc7760ca9b326b39b58f24f",
+            // a - b
+            "1da090c2f79557ad8af373978a5a345e606348a606de352e04d4a0fed3b9a45b\
+             098da70abfcb14e2603e06a31df7ca996994975ca8097a81c92306cd9a1b46dc",
+            // a^2
+            "2c60fa704d386050f0cb97a350eed1348b5095b10761f84d1b8b7c8eedac2583\
+             0d6aceae76c0c4a6994a1402c7d9f55cfbb13d8c0f82d5408288aba7383c56ad",
+            // 1 / a
+            "2760f4ebb0fd473afcca72ddefa0fe97395ddabce0d18201ba6bf2d4a581ca10\
+             2d112cc7a192674c6d295af2bc63ba24b87b4de8b4fb5d3a52597f8c206425f4",
+        ]
+    );
+    // e(G1, G2) is not one, and the arithmetic of Fq12 is consistent on it. Zero
+    // has no inverse.
+    assert_eq!(fq12, [true; 5]);
+}
+
+#[test]
+fn bn254_curve() {
+    let (g1, g2): (Vec<Vec<u8>>, Vec<Vec<u8>>) = run_bn254(MultiTestSpec::Bn254Curve {
+        g1: hex::decode(
+            "0000000000000000000000000000000000000000000000000000000000000001\
+             0000000000000000000000000000000000000000000000000000000000000002",
+        )
+        .unwrap(),
+        g2: hex::decode(
+            "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2\
+             1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed\
+             090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b\
+             12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+        )
+        .unwrap(),
+        scalar: hex::decode("1cde78f9d82b1852d6fcccb32cadbbe7fec37de54c260992d78a414db4ea1024")
+            .unwrap(),
+    });
+
+    // 2G, 3G, kG and G - G, for the generators.
+    let g1: Vec<String> = g1.iter().map(hex::encode).collect();
+    assert_eq!(
+        g1,
+        [
+            "030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3\
+             15ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4",
+            "0769bf9ac56bea3ff40232bcb1b6bd159315d84715b8e679f2d355961915abf0\
+             2ab799bee0489429554fdb7c8d086475319e63b40b9c5b57cdf1ff3dd9fe2261",
+            "1fc7c9a9ffbc08e9c5716228cbf998b0fa8e05e5d3f4935f8896d20706c60e3f\
+             058ee3f48612244229d2e7940ada68885c238be9c1a047fbe3aa540a8a6e85d5",
+            "0".repeat(128).as_str(),
+        ]
+    );
+    let g2: Vec<String> = g2.iter().map(hex::encode).collect();
+    assert_eq!(
+        g2,
+        [
+            "203e205db4f19b37b60121b83a7333706db86431c6d835849957ed8c3928ad79\
+             27dc7234fd11d3e8c36c59277c3e6f149d5cd3cfa9a62aee49f8130962b4b3b9\
+             195e8aa5b7827463722b8c153931579d3505566b4edf48d498e185f0509de152\
+             04bb53b8977e5f92a0bc372742c4830944a59b4fe6b1c0466e2a6dad122b5d2e",
+            "1014772f57bb9742735191cd5dcfe4ebbc04156b6878a0a7c9824f32ffb66e85\
+             06064e784db10e9051e52826e192715e8d7e478cb09a5e0012defa0694fbc7f5\
+             021e2335f3354bb7922ffcc2f38d3323dd9453ac49b55441452aeaca147711b2\
+             058e1d5681b5b9e0074b0f9c8d2c68a069b920d74521e79765036d57666c5597",
+            "085b3dafc2ed9b778339d4f05fd567322c3123c4a9a0bfa5b683f26179f12f99\
+             2f54ea55db5f60b00bb5f1634ec2d8eee298d0a090644a281742327ecd8d279e\
+             17ace9cfbe44c4c10290519bf35f630d027820dd77d79b8d0ee94f4c0131293c\
+             205a1fbf3fbd17379031df715eb0fb7b07c5325af4d26529a3fea1d362093804",
+            "0".repeat(256).as_str(),
+        ]
+    );
+}
+
+#[test]
+fn bn254_msm() {
+    // Enough points for each window size up to 5 bits. The scalars of the
+    // larger sums are short, to keep the naive sum cheap.
+    for (len, scalar_bytes) in [(0, 32), (1, 32), (5, 32), (33, 8)] {
+        let scalars: Vec<Vec<u8>> = (0..len)
+            .map(|idx| {
+                let mut scalar = vec![0u8; 32];
+                for (pos, byte) in scalar.iter_mut().enumerate().skip(32 - scalar_bytes) {
+                    *byte = (idx * 31 + pos * 7 + 3) as u8;
+                }
+                scalar
+            })
+            .collect();
+        let (msm, naive): (Vec<u8>, Vec<u8>) = run_bn254(MultiTestSpec::Bn254Msm { scalars });
+        assert_eq!(msm, naive, "{len} points");
+    }
+}
+
+#[test]
+fn bn254_decode() {
+    let infinity = "0".repeat(128);
+    let g1 = [
+        // The generator.
+        "0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000002",
+        // The point at infinity.
+        infinity.as_str(),
+        // (1, 3) is not on the curve.
+        "0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000003",
+        // A coordinate equal to the modulus.
+        "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47\
+         0000000000000000000000000000000000000000000000000000000000000002",
+    ];
+    let g2 = [
+        // The generator.
+        "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2\
+         1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed\
+         090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b\
+         12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+        // The generator with y swapped for x, which is not on the twist.
+        "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b\
+         12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa\
+         198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2\
+         1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+        // A point on the twist with x = 1, which is not in G2.
+        "0000000000000000000000000000000000000000000000000000000000000000\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0d1271953ed9ea0836846e70a1934187998c7f790cb4d7511b7f8da82de048a4\
+         2869111d5381f072f8e2728fdb825a51aadd70e52c9830e9ab4b871c0531f1bb",
+    ];
+    let (g1, g2): (Vec<Option<String>>, Vec<Option<String>>) =
+        run_bn254(MultiTestSpec::Bn254Decode {
+            g1: g1.iter().map(|p| hex::decode(p).unwrap()).collect(),
+            g2: g2.iter().map(|q| hex::decode(q).unwrap()).collect(),
+        });
+    let not_on_curve = Some("point is not on the curve".to_string());
+    assert_eq!(
+        g1,
+        [
+            None,
+            None,
+            not_on_curve.clone(),
+            Some("invalid field element".to_string())
+        ]
+    );
+    assert_eq!(
+        g2,
+        [
+            None,
+            not_on_curve,
+            Some("point is not in the prime-order subgroup".to_string())
+        ]
+    );
+}
+
+#[test]
+fn posix_style_seek() {
+    const FD: u32 = 123;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("file");
+    let file = std::fs::File::options()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&path)
+        .unwrap();
+
+    let env = ExecutorEnv::builder()
+        .file_fd(FD, file)
+        .write(&MultiTestSpec::SysSeek { fd: FD })
+        .unwrap()
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    let (pos, nread, buf, end): (u32, u32, [u8; 5], u32) =
+        session.journal.unwrap().decode().unwrap();
+    assert_eq!((pos, nread, &buf, end), (6, 5, b"world", 11));
+    assert_eq!(std::fs::read(&path).unwrap(), b"hello world");
+
+    // Streams are not seekable.
+    let env = ExecutorEnv::builder()
+        .duplex_fd(FD, b"hello world".as_slice(), std::io::sink())
+        .write(&MultiTestSpec::SysSeek { fd: FD })
+        .unwrap()
+        .build()
+        .unwrap();
+    let err = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("Bad seek file descriptor"));
+}
+
 #[test]
 fn unaligned_short_read() {
     const FD: u32 = 123;
@@ -490,6 +1162,120 @@ fn large_io_bytes() {
     assert_eq!(&buf, actual);
 }
 
+#[test]
+fn time_travel() {
+    const ADDR: u32 = 0x08000224;
+
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::EventTrace)
+        .unwrap()
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    let segment = session.segments[0].resolve().unwrap();
+    let mut time_travel = segment.time_travel().unwrap();
+    assert_eq!(time_travel.cycle(), time_travel.cycles());
+    assert_eq!(time_travel.load_u32(ADDR).unwrap(), 1337);
+
+    // The guest writes 1336 and then 1337 to the same address.
+    let write = time_travel.last_write(ADDR).unwrap().clone();
+    assert_eq!((write.old, write.new), (1336, 1337));
+    assert!(write.pc.is_some());
+
+    // Step back over the write and check that it is undone.
+    time_travel.seek(write.cycle + 1).unwrap();
+    let undone = time_travel.step_back().unwrap();
+    assert!(undone.contains(&write));
+    assert_eq!(time_travel.cycle(), write.cycle);
+    assert_eq!(time_travel.load_u32(ADDR).unwrap(), 1336);
+    assert_eq!(time_travel.last_write(ADDR).unwrap().new, 1336);
+
+    time_travel.seek(0).unwrap();
+    assert!(time_travel.step_back().is_none());
+    assert!(time_travel.seek(time_travel.cycles() + 1).is_err());
+}
+
+#[test]
+fn session_time_travel() {
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::BusyLoop { cycles: 1 << 15 })
+        .unwrap()
+        .segment_limit_po2(14) // 16k cycles
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    assert!(session.segments.len() > 1);
+    let first = session.segments[0]
+        .resolve()
+        .unwrap()
+        .time_travel()
+        .unwrap();
+
+    // A cycle past the first segment is found in the second.
+    let time_travel = session.time_travel(first.cycles() + 10).unwrap();
+    assert_eq!(time_travel.segment_index(), 1);
+    assert_eq!(time_travel.cycle(), 10);
+
+    let time_travel = session.time_travel(10).unwrap();
+    assert_eq!(time_travel.segment_index(), 0);
+    assert_eq!(time_travel.cycle(), 10);
+    assert!(session.time_travel(usize::MAX).is_err());
+}
+
+#[test]
+fn segment_store() {
+    fn run(store: Arc<dyn SegmentStore>) -> Session {
+        let env = ExecutorEnv::builder()
+            .write(&MultiTestSpec::BusyLoop { cycles: 1 << 15 })
+            .unwrap()
+            .segment_limit_po2(14) // 16k cycles
+            .segment_store(store)
+            .build()
+            .unwrap();
+        ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+            .unwrap()
+            .run()
+            .unwrap()
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let local = Arc::new(LocalDirStore::new(dir.path()).unwrap());
+    let memory = Arc::new(InMemoryStore::new());
+    for store in [
+        local as Arc<dyn SegmentStore>,
+        memory as Arc<dyn SegmentStore>,
+    ] {
+        let session = run(store.clone());
+        assert!(session.segments.len() > 1);
+        assert_eq!(store.list_segments().unwrap().len(), session.segments.len());
+        for (idx, segment_ref) in session.segments.iter().enumerate() {
+            assert_eq!(segment_ref.resolve().unwrap().index, idx as u32);
+        }
+        assert!(store.get_segment(&Digest::ZERO).is_err());
+    }
+
+    // A segment changed on disk no longer matches its digest.
+    let local = LocalDirStore::new(dir.path()).unwrap();
+    let digest = local.list_segments().unwrap()[0];
+    let path = dir.path().join("segments").join(hex::encode(digest));
+    let mut contents = std::fs::read(&path).unwrap();
+    contents[0] ^= 1;
+    std::fs::write(&path, contents).unwrap();
+    assert!(local.get_segment(&digest).is_err());
+
+    assert!(ExecutorEnv::builder()
+        .segment_path(dir.path())
+        .segment_store(Arc::new(InMemoryStore::new()))
+        .build()
+        .is_err());
+}
+
 mod sys_verify {
     use risc0_zkvm_methods::{
         multi_test::MultiTestSpec, HELLO_COMMIT_ELF, HELLO_COMMIT_ID, MULTI_TEST_ELF, MULTI_TEST_ID,
@@ -497,8 +1283,10 @@ mod sys_verify {
     use test_log::test;
 
     use crate::{
-        serde::to_vec, sha::Digestible, ExecutorEnv, ExecutorEnvBuilder, ExecutorImpl, ExitCode,
-        MaybePruned, ReceiptClaim, Session,
+        serde::to_vec,
+        sha::{Digest, Digestible},
+        Assumptions, ExecutorEnv, ExecutorEnvBuilder, ExecutorImpl, ExitCode, MaybePruned,
+        ReceiptClaim, Session,
     };
 
     fn exec_hello_commit() -> Session {
@@ -572,6 +1360,199 @@ mod sys_verify {
             .is_err());
     }
 
+    #[test]
+    fn sys_verify_with_channels() {
+        let env = ExecutorEnv::builder()
+            .write(&MultiTestSpec::Channels)
+            .unwrap()
+            .build()
+            .unwrap();
+        let channels_session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+            .unwrap()
+            .run()
+            .unwrap();
+        let journal = channels_session.journal.clone().unwrap().bytes;
+        let channels = channels_session
+            .channels
+            .iter()
+            .map(|(name, data)| (name.clone(), data.as_value().unwrap().clone()))
+            .collect();
+
+        let exec = |spec: &MultiTestSpec| {
+            let env = ExecutorEnv::builder()
+                .write(spec)
+                .unwrap()
+                .add_assumption(channels_session.claim().unwrap())
+                .build()
+                .unwrap();
+            ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap().run()
+        };
+
+        // Test that the receipt is verified along with its channels.
+        let session = exec(&MultiTestSpec::SysVerifyWithChannels {
+            image_id: MULTI_TEST_ID.into(),
+            journal: journal.clone(),
+            channels,
+        })
+        .unwrap();
+        let mut expected = Assumptions::default();
+        expected.add(channels_session.claim().unwrap().into());
+        let output = session.claim().unwrap().output.value().unwrap().unwrap();
+        assert_eq!(output.assumptions.digest(), expected.digest());
+
+        // Test that env::verify does not match a receipt with channels.
+        assert!(exec(&MultiTestSpec::SysVerify(vec![(
+            MULTI_TEST_ID.into(),
+            journal
+        )]))
+        .is_err());
+    }
+
+    #[test]
+    fn audit_assumptions() {
+        let hello_commit_session = exec_hello_commit();
+        let halt_session = exec_halt(0);
+        let claims = [
+            hello_commit_session.claim().unwrap(),
+            halt_session.claim().unwrap(),
+        ];
+
+        let spec = &MultiTestSpec::AuditAssumptions(vec![
+            (
+                HELLO_COMMIT_ID.into(),
+                hello_commit_session.journal.clone().unwrap().bytes,
+            ),
+            (MULTI_TEST_ID.into(), Vec::new()),
+        ]);
+        let env = ExecutorEnv::builder()
+            .write(&spec)
+            .unwrap()
+            .add_assumption(claims[0].clone())
+            .add_assumption(claims[1].clone())
+            .build()
+            .unwrap();
+        let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+            .unwrap()
+            .run()
+            .unwrap();
+
+        let (assumptions, assumptions_digest): (Vec<Digest>, Digest) =
+            session.journal.as_ref().unwrap().decode().unwrap();
+        assert_eq!(
+            assumptions,
+            claims
+                .iter()
+                .map(|claim| claim.digest())
+                .collect::<Vec<_>>()
+        );
+        let mut expected = Assumptions::default();
+        for claim in claims {
+            expected.add(claim.into());
+        }
+        assert_eq!(assumptions_digest, expected.digest());
+    }
+
+    #[test]
+    fn sys_call() {
+        let hello_commit_session = exec_hello_commit();
+
+        let spec = &MultiTestSpec::SysCall {
+            image_id: HELLO_COMMIT_ID.into(),
+            input: Vec::new(),
+        };
+
+        // Test that the called program is run and its journal returned.
+        let env = ExecutorEnv::builder()
+            .write(&spec)
+            .unwrap()
+            .add_program(HELLO_COMMIT_ELF)
+            .unwrap()
+            .build()
+            .unwrap();
+        let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+            .unwrap()
+            .run()
+            .unwrap();
+        assert_eq!(session.exit_code, ExitCode::Halted(0));
+        assert_eq!(session.journal, hello_commit_session.journal);
+        assert_eq!(session.calls.len(), 1);
+        assert_eq!(session.assumptions.len(), 1);
+
+        // The call is resolved by the session, so the claim has no assumptions.
+        let output = session.claim().unwrap().output.value().unwrap().unwrap();
+        assert_eq!(output.assumptions.digest(), Digest::ZERO);
+
+        // Test that it does not work when the program is not added.
+        let env = ExecutorEnv::builder()
+            .write(&spec)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert!(ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+            .unwrap()
+            .run()
+            .is_err());
+    }
+
+    fn exec_sys_call(spec: &MultiTestSpec, session_limit: Option<u64>) -> anyhow::Result<Session> {
+        let env = ExecutorEnv::builder()
+            .write(spec)
+            .unwrap()
+            .add_program(HELLO_COMMIT_ELF)
+            .unwrap()
+            .add_program(MULTI_TEST_ELF)
+            .unwrap()
+            .session_limit(session_limit)
+            .build()
+            .unwrap();
+        ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap().run()
+    }
+
+    #[test]
+    fn sys_call_session_limit() {
+        let spec = &MultiTestSpec::SysCall {
+            image_id: HELLO_COMMIT_ID.into(),
+            input: Vec::new(),
+        };
+        let session = exec_sys_call(spec, None).unwrap();
+        let caller_cycles = session.user_cycles;
+        let callee_cycles = session.calls[0].user_cycles;
+
+        // The cycles of the called program count towards the session limit.
+        let err = exec_sys_call(spec, Some(caller_cycles + callee_cycles / 2))
+            .err()
+            .unwrap();
+        assert!(format!("{err:?}").contains("Session limit exceeded"));
+        exec_sys_call(spec, Some(caller_cycles + callee_cycles + 1)).unwrap();
+    }
+
+    #[test]
+    fn sys_call_depth() {
+        // Build a chain of calls of the multi test program to itself, ending in
+        // a call to hello commit.
+        let nested_spec = |depth: usize| {
+            let mut spec = MultiTestSpec::SysCall {
+                image_id: HELLO_COMMIT_ID.into(),
+                input: Vec::new(),
+            };
+            for _ in 1..depth {
+                spec = MultiTestSpec::SysCall {
+                    image_id: MULTI_TEST_ID.into(),
+                    input: bytemuck::cast_slice(&to_vec(&spec).unwrap()).to_vec(),
+                };
+            }
+            spec
+        };
+
+        let session = exec_sys_call(&nested_spec(crate::MAX_CALL_DEPTH), None).unwrap();
+        assert_eq!(session.exit_code, ExitCode::Halted(0));
+
+        let err = exec_sys_call(&nested_spec(crate::MAX_CALL_DEPTH + 1), None)
+            .err()
+            .unwrap();
+        assert!(format!("{err:?}").contains("calls are limited to a depth of"));
+    }
+
     #[test]
     fn sys_verify_halt_codes() {
         for code in [0u8, 1, 2, 255] {
@@ -832,6 +1813,32 @@ fn args() {
     }
 }
 
+#[test]
+fn multi_test_args_and_env() {
+    let args = ["prog", "--flag", "two words"].map(String::from);
+    let env = ExecutorEnv::builder()
+        .args(&args)
+        .env_var("VAR1", "val1")
+        .env_var("EMPTY", "")
+        .write(&MultiTestSpec::ArgsAndEnv {
+            vars: ["VAR1", "EMPTY", "UNSET"].map(String::from).to_vec(),
+        })
+        .unwrap()
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    let (guest_args, values): (Vec<String>, Vec<Option<String>>) =
+        session.journal.unwrap().decode().unwrap();
+    assert_eq!(guest_args, args);
+    assert_eq!(
+        values,
+        [Some("val1".to_string()), Some(String::new()), None]
+    );
+}
+
 #[test]
 fn buf_read() {
     // Host-provided input is 7 bytes, while the guest requests to read 9.
@@ -903,6 +1910,122 @@ fn panic() {
     let mut exec = ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap();
     let err = exec.run().err().unwrap();
     assert!(err.to_string().contains("MultiTestSpec::Panic invoked"));
+
+    let panic = err.downcast_ref::<GuestPanic>().unwrap();
+    assert_eq!(panic.message, "MultiTestSpec::Panic invoked");
+    assert!(panic.file.as_ref().unwrap().ends_with("multi_test.rs"));
+    assert!(panic.line.is_some());
+    assert!(panic.backtrace.is_empty());
+
+    // With the profiler enabled, the panic carries the guest call stack.
+    let dir = tempfile::tempdir().unwrap();
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::Panic)
+        .unwrap()
+        .enable_profiler(dir.path().join("profile.pb"))
+        .build()
+        .unwrap();
+    let mut exec = ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap();
+    let err = exec.run().err().unwrap();
+    let panic = err.downcast_ref::<GuestPanic>().unwrap();
+    assert!(panic
+        .backtrace
+        .iter()
+        .any(|frame| frame.contains("multi_test")));
+}
+
+#[test]
+fn profile_zones() {
+    let dir = tempfile::tempdir().unwrap();
+    let profile_path = dir.path().join("profile.pb");
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::ProfileZones { panic: false })
+        .unwrap()
+        .enable_profiler(&profile_path)
+        .build()
+        .unwrap();
+    ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    let profile = std::fs::read(&profile_path).unwrap();
+    for zone in ["zone_outer", "zone_inner"] {
+        assert!(profile.windows(zone.len()).any(|w| w == zone.as_bytes()));
+    }
+
+    // Zones are synthetic frames on the call stack, and nest inside each other.
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::ProfileZones { panic: true })
+        .unwrap()
+        .enable_profiler(dir.path().join("panic.pb"))
+        .build()
+        .unwrap();
+    let err = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .err()
+        .unwrap();
+    let backtrace = &err.downcast_ref::<GuestPanic>().unwrap().backtrace;
+    let position = |name: &str| backtrace.iter().position(|frame| frame == name).unwrap();
+    assert!(position("zone_inner") < position("zone_outer"));
+
+    // Without the profiler, zones have no effect.
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::ProfileZones { panic: false })
+        .unwrap()
+        .build()
+        .unwrap();
+    ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+}
+
+#[test]
+fn commit_writer() {
+    const ROWS: u32 = 500;
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::CommitWriter { rows: ROWS })
+        .unwrap()
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+
+    let mut expected: Vec<u8> = (0..ROWS)
+        .flat_map(|i| format!("row {i}\n").into_bytes())
+        .collect();
+    expected.extend([0xab; 3000]);
+    assert_eq!(session.journal.as_ref().unwrap().bytes, expected);
+
+    // The journal digest committed by the guest matches the streamed bytes.
+    let claim = session.claim().unwrap();
+    let segment = session.segments.last().unwrap().resolve().unwrap();
+    assert_eq!(segment.inner.output_digest, Some(claim.output.digest()));
+}
+
+#[test]
+fn committed_panic() {
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::CommittedPanic)
+        .unwrap()
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    assert_eq!(
+        session.exit_code,
+        ExitCode::Halted(GuestPanic::EXIT_CODE as u32)
+    );
+
+    let (committed, panic): (u32, GuestPanic) = session.journal.unwrap().decode().unwrap();
+    assert_eq!(committed, 1);
+    assert_eq!(panic.message, "committed panic 2");
+    assert!(panic.file.unwrap().ends_with("multi_test.rs"));
 }
 
 #[test]
@@ -1004,6 +2127,36 @@ fn profiler() {
     assert!(check(&fr, addr), "{fr:#?} {addr}");
 }
 
+#[test]
+fn profiler_folded() {
+    let mut profiler = Profiler::new(MULTI_TEST_ELF, Some("multi_test.elf")).unwrap();
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::Profiler)
+        .unwrap()
+        .trace_callback(&mut profiler)
+        .build()
+        .unwrap();
+    ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+
+    let folded = profiler.finalize_to_folded();
+    let stacks: Vec<_> = folded
+        .lines()
+        .map(|line| line.rsplit_once(' ').unwrap())
+        .collect();
+    assert!(stacks.iter().all(|(_, count)| count.parse::<u64>().is_ok()));
+    // Frames are listed from the outermost, and paging is attributed to the code that caused it.
+    assert!(stacks
+        .iter()
+        .any(|(stack, _)| stack.contains("profile_test_func1;profile_test_func2")));
+    assert!(stacks.iter().any(|(stack, _)| stack.ends_with(";[paging]")));
+
+    // The pprof profile can still be produced from the same samples.
+    assert!(!profiler.finalize_to_vec().is_empty());
+}
+
 #[test]
 fn oom() {
     let env = ExecutorEnv::builder()
@@ -1132,6 +2285,35 @@ fn out_of_bounds_ecall() {
     run_test(MultiTestSpec::OutOfBoundsEcall);
 }
 
+#[test]
+fn no_progress_limit() {
+    let spec = MultiTestSpec::BusyLoop { cycles: 1 << 18 };
+    let env = ExecutorEnv::builder()
+        .write(&spec)
+        .unwrap()
+        .no_progress_limit(Some(1 << 16))
+        .build()
+        .unwrap();
+    let err = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .err()
+        .unwrap();
+    assert!(err.downcast_ref::<crate::NoProgressError>().is_some());
+
+    // The same guest completes when given enough cycles between syscalls.
+    let env = ExecutorEnv::builder()
+        .write(&spec)
+        .unwrap()
+        .no_progress_limit(Some(1 << 20))
+        .build()
+        .unwrap();
+    assert!(ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .is_ok());
+}
+
 #[cfg(feature = "docker")]
 mod docker {
     use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF};
@@ -0,0 +1,225 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reverse (time-travel) debugging of guest execution.
+//!
+//! A [Segment] records everything needed to deterministically re-execute it.
+//! [Segment::time_travel] replays the segment once and indexes every memory
+//! write, after which the machine state just before any cycle can be
+//! reconstructed and execution can be walked backwards.
+//! [Session::time_travel] finds the segment of a session holding a given cycle
+//! and replays it to that cycle.
+
+use std::collections::HashMap;
+
+use anyhow::{bail, ensure, Result};
+use risc0_binfmt::MemoryImage;
+use risc0_circuit_rv32im::prove::emu::preflight::Back;
+use risc0_zkvm_platform::{memory::SYSTEM, WORD_SIZE};
+
+use crate::{Segment, Session};
+
+/// A write to a word of memory observed while replaying a [Segment].
+///
+/// Registers are memory-mapped in the zkVM, so register writes are reported
+/// as writes to the [SYSTEM] region; see [TimeTravel::load_register].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemoryWrite {
+    /// The cycle, relative to the start of the segment body, of the write.
+    pub cycle: usize,
+
+    /// The program counter of the instruction performing the write, if the
+    /// write was made by an instruction.
+    pub pc: Option<u32>,
+
+    /// The byte address of the word written.
+    pub addr: u32,
+
+    /// The value of the word before the write.
+    pub old: u32,
+
+    /// The value of the word after the write.
+    pub new: u32,
+}
+
+/// A reversible view over the execution of a [Segment].
+///
+/// The view has a cursor, initially at the end of the segment. Machine state
+/// queries return the state just before the cycle at the cursor.
+pub struct TimeTravel {
+    index: u32,
+    image: MemoryImage,
+    writes: Vec<MemoryWrite>,
+    pcs: Vec<Option<u32>>,
+    cursor: usize,
+}
+
+impl Segment {
+    /// Replay this [Segment] to build a [TimeTravel] view of its execution.
+    pub fn time_travel(&self) -> Result<TimeTravel> {
+        let trace = self.inner.preflight()?;
+        let image = self.inner.partial_image.clone();
+
+        let pcs: Vec<_> = trace
+            .body
+            .cycles
+            .iter()
+            .map(|cycle| match cycle.back {
+                Some(Back::Body { pc }) | Some(Back::Halt { pc, .. }) => Some(pc.0),
+                _ => None,
+            })
+            .collect();
+
+        // Memory transactions record the value of a word after each load or
+        // store, so a write shows up as a change from the last known value.
+        let mut current = HashMap::new();
+        let mut writes = Vec::new();
+        for txn in trace.body.txns.iter() {
+            let addr = txn.addr.baddr().0;
+            let old = match current.get(&addr) {
+                Some(value) => *value,
+                None => load_u32(&image, addr)?,
+            };
+            if old != txn.data {
+                writes.push(MemoryWrite {
+                    cycle: txn.cycle,
+                    pc: pcs.get(txn.cycle).copied().flatten(),
+                    addr,
+                    old,
+                    new: txn.data,
+                });
+            }
+            current.insert(addr, txn.data);
+        }
+
+        let cursor = pcs.len();
+        Ok(TimeTravel {
+            index: self.index,
+            image,
+            writes,
+            pcs,
+            cursor,
+        })
+    }
+}
+
+impl Session {
+    /// Replay the segment holding the given cycle of this [Session] to build a
+    /// [TimeTravel] view of its execution, with the cursor at that cycle.
+    ///
+    /// Cycles are counted across the session as the body cycles of each
+    /// segment, as in [TimeTravel::cycles], so the first cycle of a segment
+    /// follows the last of the one before. Each segment before the one holding
+    /// the cycle is replayed to count its cycles.
+    pub fn time_travel(&self, cycle: usize) -> Result<TimeTravel> {
+        let mut start = 0;
+        let mut segments = self.segments.iter().peekable();
+        while let Some(segment_ref) = segments.next() {
+            let mut time_travel = segment_ref.resolve()?.time_travel()?;
+            let end = start + time_travel.cycles();
+            // The end of the last segment is a valid cursor too.
+            if cycle < end || (cycle == end && segments.peek().is_none()) {
+                time_travel.seek(cycle - start)?;
+                return Ok(time_travel);
+            }
+            start = end;
+        }
+        bail!("cycle {cycle} is past the end of the session ({start} cycles)")
+    }
+}
+
+impl TimeTravel {
+    /// The index of the segment within its [Session].
+    pub fn segment_index(&self) -> u32 {
+        self.index
+    }
+
+    /// The number of body cycles in the segment.
+    pub fn cycles(&self) -> usize {
+        self.pcs.len()
+    }
+
+    /// The cycle at the cursor.
+    pub fn cycle(&self) -> usize {
+        self.cursor
+    }
+
+    /// Move the cursor to the given cycle.
+    pub fn seek(&mut self, cycle: usize) -> Result<()> {
+        ensure!(
+            cycle <= self.cycles(),
+            "cycle {cycle} is past the end of the segment ({} cycles)",
+            self.cycles()
+        );
+        self.cursor = cycle;
+        Ok(())
+    }
+
+    /// Move the cursor back by one cycle, returning the writes that are
+    /// undone, most recent first. Returns `None` at the start of the segment.
+    pub fn step_back(&mut self) -> Option<Vec<MemoryWrite>> {
+        if self.cursor == 0 {
+            return None;
+        }
+        let cycle = self.cursor - 1;
+        let undone = self
+            .writes_before()
+            .take_while(|write| write.cycle == cycle)
+            .cloned()
+            .collect();
+        self.cursor = cycle;
+        Some(undone)
+    }
+
+    /// The program counter of the instruction at the cursor, if any.
+    pub fn pc(&self) -> Option<u32> {
+        self.pcs.get(self.cursor).copied().flatten()
+    }
+
+    /// All writes made before the cursor, most recent first.
+    pub fn writes_before(&self) -> impl Iterator<Item = &MemoryWrite> {
+        let end = self
+            .writes
+            .partition_point(|write| write.cycle < self.cursor);
+        self.writes[..end].iter().rev()
+    }
+
+    /// The most recent write to the word containing `addr` before the cursor.
+    ///
+    /// This answers "what wrote this memory cell?"; `None` means the value
+    /// was present at the start of the segment.
+    pub fn last_write(&self, addr: u32) -> Option<&MemoryWrite> {
+        let addr = addr - addr % WORD_SIZE as u32;
+        self.writes_before().find(|write| write.addr == addr)
+    }
+
+    /// Load the word containing `addr` as it was just before the cursor.
+    pub fn load_u32(&self, addr: u32) -> Result<u32> {
+        match self.last_write(addr) {
+            Some(write) => Ok(write.new),
+            None => load_u32(&self.image, addr - addr % WORD_SIZE as u32),
+        }
+    }
+
+    /// Load the given register as it was just before the cursor.
+    pub fn load_register(&self, idx: usize) -> Result<u32> {
+        self.load_u32((SYSTEM.start() + idx * WORD_SIZE) as u32)
+    }
+}
+
+fn load_u32(image: &MemoryImage, addr: u32) -> Result<u32> {
+    let mut bytes = [0u8; WORD_SIZE];
+    image.load_region_in_page(addr, &mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
@@ -0,0 +1,347 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Export of the instruction trace of a [Segment] in a columnar format.
+//!
+//! A [SegmentTrace] has one row for each instruction the segment executes,
+//! holding the state before that instruction, and the following columns of
+//! `u32` values:
+//!
+//! | column       | value                                  |
+//! |--------------|----------------------------------------|
+//! | `pc`         | program counter of the instruction     |
+//! | `insn`       | encoded instruction                    |
+//! | `x0`..`x31`  | registers                              |
+//!
+//! The public values are those committed to by the [ReceiptClaim] of the
+//! segment: its index and po2, the pc and Merkle root of the pre and post
+//! states (`pre_root_0`..`pre_root_7`), the system and user exit codes, and
+//! the input and output digests. Digests are split into their eight words.
+//! `has_output` is 1 if the segment has an output digest, and 0 otherwise.
+//!
+//! [SegmentTrace::write] writes a trace as little-endian `u32` words, so that
+//! it can be loaded with tools such as `numpy.fromfile`:
+//!
+//! ```text
+//! "R0TRACE1"                                8 bytes
+//! P, then P names                           public values
+//! P values
+//! C, N, then C names                        C columns of N rows
+//! C x N values                              each column in turn
+//! ```
+//!
+//! Each name is its length in bytes followed by its UTF-8 bytes, padded with
+//! zeros to a whole number of words.
+//!
+//! The columns do not include the paging, SHA-256 and bigint cycles of the
+//! rv32im circuit, and so a proof of this trace alone does not prove the
+//! memory accesses of the segment.
+//!
+//! [ReceiptClaim]: crate::ReceiptClaim
+
+use std::io::{Read, Write};
+
+use anyhow::{bail, ensure, Result};
+use risc0_core::field::baby_bear::BabyBearElem;
+use risc0_zkp::core::digest::Digest;
+use risc0_zkvm_platform::WORD_SIZE;
+
+use crate::Segment;
+
+const MAGIC: &[u8; 8] = b"R0TRACE1";
+
+/// The instruction trace and public values of a [Segment].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SegmentTrace {
+    /// The named public values of the segment.
+    pub public_values: Vec<(String, u32)>,
+
+    /// The columns of the trace, all with the same number of rows.
+    pub columns: Vec<TraceColumn>,
+}
+
+/// A named column of a [SegmentTrace].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceColumn {
+    /// The name of the column, such as `pc` or `x10`.
+    pub name: String,
+
+    /// One value for each row.
+    pub values: Vec<u32>,
+}
+
+/// A matrix of BabyBear elements stored row by row, laid out as the
+/// `RowMajorMatrix` of Plonky3.
+///
+/// The BabyBear field of the zkVM is the one used by Plonky3, so `values` can
+/// be converted one by one with `BabyBear::from_canonical_u32`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RowMajorMatrix {
+    /// The elements of the matrix, row by row.
+    pub values: Vec<BabyBearElem>,
+
+    /// The number of columns.
+    pub width: usize,
+}
+
+impl RowMajorMatrix {
+    /// The number of rows.
+    pub fn height(&self) -> usize {
+        self.values.len().checked_div(self.width).unwrap_or(0)
+    }
+}
+
+impl Segment {
+    /// Replay this segment, returning its instruction trace and public values.
+    pub fn export_trace(&self) -> Result<SegmentTrace> {
+        let segment = &self.inner;
+        let rows = segment.instruction_trace()?;
+
+        let mut public_values = vec![
+            ("index".to_string(), segment.index as u32),
+            ("po2".to_string(), segment.po2 as u32),
+            ("pre_pc".to_string(), segment.pre_state.pc),
+        ];
+        push_digest(
+            &mut public_values,
+            "pre_root",
+            &segment.pre_state.merkle_root,
+        );
+        public_values.push(("post_pc".to_string(), segment.post_state.pc));
+        push_digest(
+            &mut public_values,
+            "post_root",
+            &segment.post_state.merkle_root,
+        );
+        let (exit_system, exit_user) = segment.exit_code.into_pair();
+        public_values.push(("exit_system".to_string(), exit_system));
+        public_values.push(("exit_user".to_string(), exit_user));
+        push_digest(&mut public_values, "input", &segment.input_digest);
+        let output = segment.output_digest.unwrap_or(Digest::ZERO);
+        push_digest(&mut public_values, "output", &output);
+        public_values.push((
+            "has_output".to_string(),
+            segment.output_digest.is_some() as u32,
+        ));
+
+        let mut columns = vec![
+            TraceColumn::new("pc", rows.iter().map(|row| row.pc)),
+            TraceColumn::new("insn", rows.iter().map(|row| row.insn)),
+        ];
+        for idx in 0..32 {
+            columns.push(TraceColumn::new(
+                &format!("x{idx}"),
+                rows.iter().map(|row| row.registers[idx]),
+            ));
+        }
+
+        Ok(SegmentTrace {
+            public_values,
+            columns,
+        })
+    }
+}
+
+impl TraceColumn {
+    fn new(name: &str, values: impl Iterator<Item = u32>) -> Self {
+        Self {
+            name: name.to_string(),
+            values: values.collect(),
+        }
+    }
+}
+
+impl SegmentTrace {
+    /// The number of rows in the trace.
+    pub fn rows(&self) -> usize {
+        self.columns.first().map_or(0, |column| column.values.len())
+    }
+
+    /// The column with the given name, if any.
+    pub fn column(&self, name: &str) -> Option<&[u32]> {
+        self.columns
+            .iter()
+            .find(|column| column.name == name)
+            .map(|column| column.values.as_slice())
+    }
+
+    /// Convert the columns to a matrix of BabyBear elements, for proving with
+    /// Plonky3.
+    ///
+    /// A `u32` does not fit in a BabyBear element, so each column becomes two:
+    /// its low 16 bits, then its high 16 bits. The rows are not padded, and an
+    /// AIR that needs a power of two rows must pad them as its constraints
+    /// allow.
+    pub fn to_row_major(&self) -> RowMajorMatrix {
+        let width = 2 * self.columns.len();
+        let mut values = Vec::with_capacity(width * self.rows());
+        for row in 0..self.rows() {
+            for column in &self.columns {
+                let value = column.values[row];
+                values.push(BabyBearElem::new(value & 0xffff));
+                values.push(BabyBearElem::new(value >> 16));
+            }
+        }
+        RowMajorMatrix { values, width }
+    }
+
+    /// Write the trace in the format described in the [module
+    /// documentation](self).
+    pub fn write(&self, mut writer: impl Write) -> Result<()> {
+        let rows = self.rows();
+        ensure!(
+            self.columns
+                .iter()
+                .all(|column| column.values.len() == rows),
+            "columns of a trace must have the same number of rows"
+        );
+
+        writer.write_all(MAGIC)?;
+        write_u32(&mut writer, self.public_values.len().try_into()?)?;
+        for (name, _) in &self.public_values {
+            write_name(&mut writer, name)?;
+        }
+        for (_, value) in &self.public_values {
+            write_u32(&mut writer, *value)?;
+        }
+        write_u32(&mut writer, self.columns.len().try_into()?)?;
+        write_u32(&mut writer, rows.try_into()?)?;
+        for column in &self.columns {
+            write_name(&mut writer, &column.name)?;
+        }
+        for column in &self.columns {
+            for value in &column.values {
+                write_u32(&mut writer, *value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a trace written by [SegmentTrace::write].
+    pub fn read(mut reader: impl Read) -> Result<Self> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            bail!("not a segment trace");
+        }
+
+        let count = read_u32(&mut reader)?;
+        let names = (0..count)
+            .map(|_| read_name(&mut reader))
+            .collect::<Result<Vec<_>>>()?;
+        let public_values = names
+            .into_iter()
+            .map(|name| Ok((name, read_u32(&mut reader)?)))
+            .collect::<Result<_>>()?;
+
+        let count = read_u32(&mut reader)?;
+        let rows = read_u32(&mut reader)?;
+        let names = (0..count)
+            .map(|_| read_name(&mut reader))
+            .collect::<Result<Vec<_>>>()?;
+        let columns = names
+            .into_iter()
+            .map(|name| {
+                let values = (0..rows)
+                    .map(|_| read_u32(&mut reader))
+                    .collect::<Result<_>>()?;
+                Ok(TraceColumn { name, values })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            public_values,
+            columns,
+        })
+    }
+}
+
+fn push_digest(values: &mut Vec<(String, u32)>, name: &str, digest: &Digest) {
+    for (idx, word) in digest.as_words().iter().enumerate() {
+        values.push((format!("{name}_{idx}"), *word));
+    }
+}
+
+fn write_u32(writer: &mut impl Write, value: u32) -> Result<()> {
+    writer.write_all(&value.to_le_bytes())?;
+    Ok(())
+}
+
+fn write_name(writer: &mut impl Write, name: &str) -> Result<()> {
+    write_u32(writer, name.len().try_into()?)?;
+    writer.write_all(name.as_bytes())?;
+    let padding = name.len().next_multiple_of(WORD_SIZE) - name.len();
+    writer.write_all(&[0; WORD_SIZE][..padding])?;
+    Ok(())
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32> {
+    let mut bytes = [0u8; WORD_SIZE];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_name(reader: &mut impl Read) -> Result<String> {
+    let len = read_u32(reader)? as usize;
+    let mut bytes = vec![0u8; len.next_multiple_of(WORD_SIZE)];
+    reader.read_exact(&mut bytes)?;
+    bytes.truncate(len);
+    Ok(String::from_utf8(bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF};
+
+    use super::SegmentTrace;
+    use crate::{ExecutorEnv, ExecutorImpl, ExitCode};
+
+    #[test]
+    fn export() {
+        let env = ExecutorEnv::builder()
+            .write(&MultiTestSpec::BusyLoop { cycles: 1 << 12 })
+            .unwrap()
+            .build()
+            .unwrap();
+        let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+            .unwrap()
+            .run()
+            .unwrap();
+        assert_eq!(session.exit_code, ExitCode::Halted(0));
+        let segment = session.segments[0].resolve().unwrap();
+        let trace = segment.export_trace().unwrap();
+
+        assert_eq!(trace.columns.len(), 34);
+        assert!(trace.rows() as u64 <= session.user_cycles);
+        assert_eq!(trace.column("pc").unwrap()[0], segment.inner.pre_state.pc);
+        assert!(trace.column("x0").unwrap().iter().all(|x| *x == 0));
+        let exit_system = trace
+            .public_values
+            .iter()
+            .find(|(name, _)| name == "exit_system")
+            .unwrap();
+        assert_eq!(exit_system.1, ExitCode::Halted(0).into_pair().0);
+
+        let mut bytes = Vec::new();
+        trace.write(&mut bytes).unwrap();
+        assert_eq!(SegmentTrace::read(bytes.as_slice()).unwrap(), trace);
+
+        let matrix = trace.to_row_major();
+        assert_eq!(matrix.width, 68);
+        assert_eq!(matrix.height(), trace.rows());
+        let insn = trace.column("insn").unwrap()[0];
+        assert_eq!(u32::from(matrix.values[2]), insn & 0xffff);
+        assert_eq!(u32::from(matrix.values[3]), insn >> 16);
+    }
+}
@@ -0,0 +1,81 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{ensure, Result};
+use risc0_binfmt::{ExitCode, MemoryImage};
+use risc0_circuit_rv32im::prove::emu::trap::{self, Trap};
+use risc0_zkp::core::digest::Digest;
+use serde::{Deserialize, Serialize};
+
+use crate::{sha::Digestible, Receipt, VerifierContext};
+
+/// A proof that a guest faulted, such as by running an illegal instruction or
+/// by a misaligned access.
+///
+/// The rv32im circuit has no exit code for a fault, so the [Receipt] proves the
+/// execution of the guest up to the faulting instruction, ending with
+/// [ExitCode::SystemSplit] at its pc. The pages are the memory of the post
+/// state of the receipt that the instruction reads, along with the page table
+/// pages that open them against its Merkle root. [FaultReceipt::verify]
+/// replays the instruction from these pages to show that it faults.
+///
+/// The guest does not commit a journal when it faults. A guest that verified
+/// receipts or called other programs before faulting has assumptions in its
+/// session, which a session ending in [ExitCode::SystemSplit] cannot claim, so
+/// its fault cannot be proven.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FaultReceipt {
+    /// The receipt of the execution of the guest up to the faulting
+    /// instruction.
+    pub receipt: Receipt,
+
+    /// The pages of memory read by the faulting instruction.
+    pub pages: MemoryImage,
+}
+
+impl FaultReceipt {
+    /// Verify that the guest with the given image ID faulted, and return the
+    /// fault.
+    pub fn verify(&self, image_id: impl Into<Digest>) -> Result<Trap> {
+        self.verify_with_context(&VerifierContext::default(), image_id)
+    }
+
+    /// Verify that the guest with the given image ID faulted, using the
+    /// specified [VerifierContext], and return the fault.
+    pub fn verify_with_context(
+        &self,
+        ctx: &VerifierContext,
+        image_id: impl Into<Digest>,
+    ) -> Result<Trap> {
+        self.receipt.verify_integrity_with_context(ctx)?;
+        let claim = self.receipt.claim()?;
+        ensure!(
+            claim.pre.digest() == image_id.into(),
+            "receipt is not of the given image ID"
+        );
+        ensure!(
+            claim.exit_code == ExitCode::SystemSplit && claim.output.is_none(),
+            "receipt does not end before an instruction, with exit code {:?}",
+            claim.exit_code
+        );
+
+        // Replay checks the root page first, which the system state is computed from.
+        let trap = trap::replay(&self.pages)?;
+        ensure!(
+            self.pages.get_system_state().digest() == claim.post.digest(),
+            "pages are not of the post state of the receipt"
+        );
+        Ok(trap)
+    }
+}
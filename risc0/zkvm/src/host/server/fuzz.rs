@@ -0,0 +1,418 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fuzzing of guest programs.
+//!
+//! A [GuestFuzzer] runs a guest in the executor on arbitrary inputs, given to
+//! the guest as stdin, and reports runs that panic, fault, run out of memory or
+//! exceed a cycle budget as a [Finding]. When given a native build of the same
+//! code, it also reports inputs on which the guest and the native build
+//! disagree.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use risc0_binfmt::{MemoryImage, Program};
+use risc0_circuit_rv32im::prove::emu::exec::NoProgressError;
+use risc0_zkvm_platform::{memory::GUEST_MAX_MEM, PAGE_SIZE};
+
+use crate::{
+    host::server::{exec::executor::ExecutorImpl, session::null_callback},
+    ExecutorEnv, ExitCode, GuestOutOfMemory, GuestPanic,
+};
+
+/// Limits on each run of a guest under a [GuestFuzzer].
+#[derive(Clone, Debug)]
+pub struct FuzzConfig {
+    /// Largest number of user cycles a run may take before it is reported as
+    /// a [Finding::CycleLimit].
+    pub max_cycles: u64,
+
+    /// Number of cycles a guest may run without making a syscall before it is
+    /// reported as a [Finding::NoProgress], if any.
+    pub no_progress_limit: Option<u64>,
+
+    /// Limit on the guest heap, in bytes, if any.
+    pub heap_limit: Option<u32>,
+
+    /// Largest number of runs [GuestFuzzer::minimize] makes.
+    pub max_minimize_runs: usize,
+}
+
+impl Default for FuzzConfig {
+    fn default() -> Self {
+        Self {
+            max_cycles: 64 * 1024 * 1024,
+            no_progress_limit: None,
+            heap_limit: None,
+            max_minimize_runs: 1024,
+        }
+    }
+}
+
+/// The result of a run that ended normally.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Outcome {
+    /// The exit code of the run.
+    pub exit_code: ExitCode,
+
+    /// The journal committed by the run.
+    pub journal: Vec<u8>,
+}
+
+impl Outcome {
+    /// The outcome of a run that halted successfully with the given journal.
+    pub fn halted(journal: impl Into<Vec<u8>>) -> Self {
+        Self {
+            exit_code: ExitCode::Halted(0),
+            journal: journal.into(),
+        }
+    }
+}
+
+/// A problem found by running a guest on an input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Finding {
+    /// The guest panicked.
+    Panic(GuestPanic),
+
+    /// The guest ran out of heap memory.
+    OutOfMemory(GuestOutOfMemory),
+
+    /// The guest ran for more than [FuzzConfig::max_cycles].
+    CycleLimit {
+        /// The limit that was exceeded.
+        max_cycles: u64,
+    },
+
+    /// The guest stopped making syscalls for more than
+    /// [FuzzConfig::no_progress_limit] cycles.
+    NoProgress(NoProgressError),
+
+    /// Execution failed in some other way, such as a memory fault or an
+    /// invalid instruction.
+    Fault(String),
+
+    /// The guest and its native build returned different outcomes.
+    Divergence {
+        /// The outcome of the guest.
+        guest: Outcome,
+
+        /// The outcome of the native build.
+        native: Outcome,
+    },
+}
+
+impl Finding {
+    fn from_error(err: anyhow::Error, config: &FuzzConfig) -> Self {
+        let err = match err.downcast::<GuestPanic>() {
+            Ok(panic) => return Finding::Panic(panic),
+            Err(err) => err,
+        };
+        let err = match err.downcast::<GuestOutOfMemory>() {
+            Ok(oom) => return Finding::OutOfMemory(oom),
+            Err(err) => err,
+        };
+        let err = match err.downcast::<NoProgressError>() {
+            Ok(no_progress) => return Finding::NoProgress(no_progress),
+            Err(err) => err,
+        };
+        if err.to_string().contains("Session limit exceeded") {
+            return Finding::CycleLimit {
+                max_cycles: config.max_cycles,
+            };
+        }
+        Finding::Fault(format!("{err:#}"))
+    }
+
+    /// Whether this finding is the same bug as `other`, as judged by
+    /// [GuestFuzzer::minimize].
+    ///
+    /// Panics match on their message and location, and other findings on
+    /// their kind, so that a smaller input which fails differently is not
+    /// taken as a reduction.
+    pub fn matches(&self, other: &Finding) -> bool {
+        match (self, other) {
+            (Finding::Panic(a), Finding::Panic(b)) => {
+                a.message == b.message && a.file == b.file && a.line == b.line
+            }
+            (Finding::Fault(a), Finding::Fault(b)) => a == b,
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Finding::Panic(panic) => write!(f, "{panic}"),
+            Finding::OutOfMemory(oom) => write!(f, "{oom}"),
+            Finding::CycleLimit { max_cycles } => {
+                write!(f, "Guest exceeded the limit of {max_cycles} cycles")
+            }
+            Finding::NoProgress(no_progress) => write!(f, "{no_progress}"),
+            Finding::Fault(err) => write!(f, "Guest fault: {err}"),
+            Finding::Divergence { guest, native } => write!(
+                f,
+                "Guest diverged from native build: guest exited with {:?} and journal 0x{}, \
+                 native exited with {:?} and journal 0x{}",
+                guest.exit_code,
+                hex::encode(&guest.journal),
+                native.exit_code,
+                hex::encode(&native.journal)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Finding {}
+
+type NativeFn = dyn Fn(&[u8]) -> Outcome + Send + Sync;
+
+/// Runs a guest on arbitrary inputs, looking for [Finding]s.
+///
+/// The memory image of the guest is loaded once, and shared by every run.
+///
+/// A fuzzer plugs into [cargo-fuzz] by panicking on findings from
+/// [GuestFuzzer::fuzz_one], which libFuzzer records as a crash:
+///
+/// ```ignore
+/// #![no_main]
+///
+/// use std::sync::OnceLock;
+///
+/// use libfuzzer_sys::fuzz_target;
+/// use risc0_zkvm::fuzz::{GuestFuzzer, Outcome};
+///
+/// static FUZZER: OnceLock<GuestFuzzer> = OnceLock::new();
+///
+/// fuzz_target!(|data: &[u8]| {
+///     FUZZER
+///         .get_or_init(|| {
+///             GuestFuzzer::new(methods::PARSER_ELF)
+///                 .unwrap()
+///                 .with_native(|input| Outcome::halted(parser::run(input)))
+///         })
+///         .fuzz_one(data)
+/// });
+/// ```
+///
+/// Crashing inputs saved by libFuzzer can then be rechecked with
+/// [GuestFuzzer::check_corpus] and reduced with [GuestFuzzer::minimize].
+///
+/// [cargo-fuzz]: https://rust-fuzz.github.io/book/cargo-fuzz.html
+pub struct GuestFuzzer {
+    image: MemoryImage,
+    config: FuzzConfig,
+    native: Option<Box<NativeFn>>,
+}
+
+impl GuestFuzzer {
+    /// Construct a fuzzer for the given guest ELF, with the default
+    /// [FuzzConfig].
+    pub fn new(elf: &[u8]) -> Result<Self> {
+        let elf = risc0_binfmt::decompress_elf(elf)?;
+        let program = Program::load_elf(&elf, GUEST_MAX_MEM as u32)?;
+        let image = MemoryImage::new(&program, PAGE_SIZE as u32)?;
+        Ok(Self {
+            image,
+            config: FuzzConfig::default(),
+            native: None,
+        })
+    }
+
+    /// Set the limits on each run.
+    pub fn with_config(mut self, config: FuzzConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Cross-check the guest against a native build of the same code.
+    ///
+    /// The function is given the same input as the guest, and should return
+    /// the exit code and journal the guest is expected to produce. It is only
+    /// called on inputs for which the guest ran to completion.
+    pub fn with_native(
+        mut self,
+        native: impl Fn(&[u8]) -> Outcome + Send + Sync + 'static,
+    ) -> Self {
+        self.native = Some(Box::new(native));
+        self
+    }
+
+    /// Run the guest on an input, without cross-checking it.
+    pub fn execute(&self, input: &[u8]) -> Result<Outcome, Finding> {
+        let run = || -> Result<Outcome> {
+            let env = ExecutorEnv::builder()
+                .stdin(input)
+                .session_limit(Some(self.config.max_cycles))
+                .no_progress_limit(self.config.no_progress_limit)
+                .heap_limit(self.config.heap_limit)
+                .build()?;
+            let session =
+                ExecutorImpl::new(env, self.image.clone())?.run_with_callback(null_callback)?;
+            Ok(Outcome {
+                exit_code: session.exit_code,
+                journal: session
+                    .journal
+                    .map(|journal| journal.bytes)
+                    .unwrap_or_default(),
+            })
+        };
+        run().map_err(|err| Finding::from_error(err, &self.config))
+    }
+
+    /// Run the guest on an input, and cross-check it against the native
+    /// build, if any.
+    pub fn check(&self, input: &[u8]) -> Result<Outcome, Finding> {
+        let guest = self.execute(input)?;
+        if let Some(native) = &self.native {
+            let native = native(input);
+            if native != guest {
+                return Err(Finding::Divergence { guest, native });
+            }
+        }
+        Ok(guest)
+    }
+
+    /// Check an input, panicking with the [Finding] if there is one.
+    ///
+    /// This is the body of a `fuzz_target!`.
+    pub fn fuzz_one(&self, input: &[u8]) {
+        if let Err(finding) = self.check(input) {
+            panic!("{finding}");
+        }
+    }
+
+    /// Reduce an input to a smaller one with a matching [Finding].
+    ///
+    /// This removes ever smaller chunks of the input for as long as the
+    /// finding still reproduces, up to [FuzzConfig::max_minimize_runs] runs.
+    /// The input must produce a finding.
+    pub fn minimize(&self, input: &[u8]) -> Result<(Vec<u8>, Finding)> {
+        let mut finding = match self.check(input) {
+            Ok(_) => return Err(anyhow::anyhow!("input does not produce a finding")),
+            Err(finding) => finding,
+        };
+        let mut input = input.to_vec();
+        let mut runs = 0;
+        let mut chunk = input.len().next_power_of_two();
+        while chunk > 0 {
+            let mut start = 0;
+            while start < input.len() {
+                if runs == self.config.max_minimize_runs {
+                    return Ok((input, finding));
+                }
+                runs += 1;
+                let end = (start + chunk).min(input.len());
+                let candidate = [&input[..start], &input[end..]].concat();
+                match self.check(&candidate) {
+                    Err(reduced) if reduced.matches(&finding) => {
+                        input = candidate;
+                        finding = reduced;
+                    }
+                    _ => start = end,
+                }
+            }
+            chunk /= 2;
+        }
+        Ok((input, finding))
+    }
+
+    /// Check each input of a corpus, such as the directory that libFuzzer
+    /// saves crashing inputs to, returning those with findings.
+    pub fn check_corpus(&self, dir: impl AsRef<Path>) -> Result<Vec<(PathBuf, Finding)>> {
+        let mut findings = Vec::new();
+        for entry in std::fs::read_dir(dir.as_ref()).context("reading corpus")? {
+            let path = entry?.path();
+            if !path.is_file() {
+                continue;
+            }
+            if let Err(finding) = self.check(&std::fs::read(&path)?) {
+                findings.push((path, finding));
+            }
+        }
+        findings.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF};
+
+    use super::{Finding, FuzzConfig, GuestFuzzer, Outcome};
+    use crate::{serde::to_vec, ExitCode};
+
+    fn input(spec: &MultiTestSpec) -> Vec<u8> {
+        bytemuck::cast_slice(&to_vec(spec).unwrap()).to_vec()
+    }
+
+    #[test]
+    fn findings() {
+        let fuzzer = GuestFuzzer::new(MULTI_TEST_ELF)
+            .unwrap()
+            .with_config(FuzzConfig {
+                max_cycles: 1 << 20,
+                ..Default::default()
+            });
+
+        let outcome = fuzzer.check(&input(&MultiTestSpec::DoNothing)).unwrap();
+        assert_eq!(outcome.exit_code, ExitCode::Halted(0));
+
+        match fuzzer.check(&input(&MultiTestSpec::Panic)) {
+            Err(Finding::Panic(panic)) => assert_eq!(panic.message, "MultiTestSpec::Panic invoked"),
+            result => panic!("expected a panic, got {result:?}"),
+        }
+        assert!(matches!(
+            fuzzer.check(&input(&MultiTestSpec::Fault)),
+            Err(Finding::Fault(_))
+        ));
+        assert_eq!(
+            fuzzer.check(&input(&MultiTestSpec::BusyLoop { cycles: 1 << 21 })),
+            Err(Finding::CycleLimit {
+                max_cycles: 1 << 20
+            })
+        );
+    }
+
+    #[test]
+    fn divergence() {
+        let fuzzer = GuestFuzzer::new(MULTI_TEST_ELF)
+            .unwrap()
+            .with_native(|_| Outcome::halted(b"native".as_slice()));
+        match fuzzer.check(&input(&MultiTestSpec::DoNothing)) {
+            Err(Finding::Divergence { guest, native }) => {
+                assert_eq!(guest.exit_code, ExitCode::Halted(0));
+                assert_eq!(native.journal, b"native");
+            }
+            result => panic!("expected a divergence, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn minimize() {
+        let fuzzer = GuestFuzzer::new(MULTI_TEST_ELF).unwrap();
+        let spec = input(&MultiTestSpec::Panic);
+        let padded = [spec.as_slice(), &[0xff; 28]].concat();
+        let (minimized, finding) = fuzzer.minimize(&padded).unwrap();
+        assert_eq!(minimized, spec);
+        assert!(matches!(finding, Finding::Panic(_)));
+
+        assert!(fuzzer.minimize(&input(&MultiTestSpec::DoNothing)).is_err());
+    }
+}
@@ -0,0 +1,79 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Metrics reported by the executor and prover.
+//!
+//! Metrics are reported through the [metrics] facade, so they are discarded
+//! unless a recorder is installed. To serve them to Prometheus, install an
+//! exporter such as `metrics-exporter-prometheus` at startup.
+//!
+//! The following metrics are reported:
+//!
+//! | Name                                   | Type      | Labels | Description                                  |
+//! | -------------------------------------- | --------- | ------ | -------------------------------------------- |
+//! | `risc0_sessions_executed_total`        | counter   |        | Sessions run to completion by the executor.  |
+//! | `risc0_execution_cycles_per_second`    | histogram |        | User cycles executed per second, by session. |
+//! | `risc0_segments_proven_total`          | counter   | `po2`  | Segments proven.                             |
+//! | `risc0_segment_prove_seconds`          | histogram | `po2`  | Time taken to prove a segment.               |
+//! | `risc0_verification_failures_total`    | counter   | `kind` | Receipts that failed self-verification.      |
+//! | `risc0_host_memory_peak_bytes`         | gauge     |        | Peak resident memory of the host process.    |
+//!
+//! Memory is only reported for the host, and only on Linux. Device memory used
+//! by the CUDA and Metal provers is not reported, as the HALs do not track
+//! their allocations.
+
+use std::time::Duration;
+
+/// Record a completed execution of `user_cycles` cycles.
+pub(crate) fn record_execution(user_cycles: u64, elapsed: Duration) {
+    metrics::counter!("risc0_sessions_executed_total").increment(1);
+    if !elapsed.is_zero() {
+        metrics::histogram!("risc0_execution_cycles_per_second")
+            .record(user_cycles as f64 / elapsed.as_secs_f64());
+    }
+    record_memory_peak();
+}
+
+/// Record that a segment of size `po2` was proven.
+pub(crate) fn record_segment_proven(po2: usize, elapsed: Duration) {
+    let po2 = po2.to_string();
+    metrics::counter!("risc0_segments_proven_total", "po2" => po2.clone()).increment(1);
+    metrics::histogram!("risc0_segment_prove_seconds", "po2" => po2).record(elapsed);
+    record_memory_peak();
+}
+
+/// Record that a receipt produced by the prover failed verification.
+pub(crate) fn record_verification_failure(kind: &'static str) {
+    metrics::counter!("risc0_verification_failures_total", "kind" => kind).increment(1);
+}
+
+fn record_memory_peak() {
+    if let Some(peak) = host_memory_peak() {
+        metrics::gauge!("risc0_host_memory_peak_bytes").set(peak as f64);
+    }
+}
+
+// The peak resident set size of this process, where the platform reports it.
+#[cfg(target_os = "linux")]
+fn host_memory_peak() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn host_memory_peak() -> Option<u64> {
+    None
+}
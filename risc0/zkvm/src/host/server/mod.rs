@@ -13,8 +13,14 @@
 // limitations under the License.
 
 pub(crate) mod exec;
+pub(crate) mod export;
+pub(crate) mod fault;
+pub(crate) mod fuzz;
+#[cfg(feature = "metrics")]
+pub(crate) mod metrics;
 #[cfg(feature = "prove")]
 pub(crate) mod prove;
 pub(crate) mod session;
+pub(crate) mod store;
 #[cfg(test)]
 mod testutils;
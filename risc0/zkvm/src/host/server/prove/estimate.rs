@@ -0,0 +1,328 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Estimate the cost of proving a session before committing resources to it.
+
+use std::{collections::BTreeMap, time::Duration};
+
+use anyhow::{bail, ensure, Context as _, Result};
+use risc0_zkp::{MAX_CYCLES_PO2, MIN_CYCLES_PO2};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ExecutorEnv, ExecutorImpl, NullSegmentRef, ProverOpts, ReceiptKind, Session, RECURSION_PO2,
+};
+
+// The segment po2 of the default calibrations.
+const DEFAULT_CALIBRATION_PO2: usize = 20;
+
+// Time taken by the Groth16 prover to compress an identity_p254 receipt. This
+// is uncalibrated, like the default calibrations, and is not measured by
+// `cargo risczero benchmark`.
+const GROTH16_TIME: Duration = Duration::from_secs(30);
+
+// Time taken by snarkjs to wrap an identity_p254 receipt in a PLONK proof,
+// which has several times as many gates as the Groth16 circuit has
+// constraints. Uncalibrated, as above.
+const PLONK_TIME: Duration = Duration::from_secs(600);
+
+/// A proving backend that an estimate is made for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum HalKind {
+    /// The CPU prover.
+    Cpu,
+    /// The CUDA prover.
+    Cuda,
+    /// The Metal prover.
+    Metal,
+}
+
+/// Predicted cost of proving on a particular [HalKind].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HalEstimate {
+    /// The backend this estimate is for.
+    pub hal: HalKind,
+    /// Total time to prove every segment and compress the receipt.
+    pub prover_time: Duration,
+    /// Peak memory use, in bytes, of the largest single proof.
+    pub peak_memory: u64,
+    /// Whether the estimate comes from a [Calibration] measured on a machine,
+    /// rather than from the uncalibrated [Calibration::defaults].
+    pub calibrated: bool,
+}
+
+/// Predicted cost of proving a session, as returned by [estimate].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CostEstimate {
+    /// The number of segments to prove.
+    pub segments: usize,
+    /// The number of user cycles.
+    pub user_cycles: u64,
+    /// The number of cycles that the prover experiences, including padding.
+    pub total_cycles: u64,
+    /// An estimate for each calibrated HAL.
+    pub hals: Vec<HalEstimate>,
+}
+
+/// The throughput of a HAL on some machine, from which proving costs are
+/// predicted.
+///
+/// Costs at other po2 values than the one measured are scaled by the n*log(n)
+/// complexity of the underlying NTTs.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Calibration {
+    /// The backend that was measured.
+    pub hal: HalKind,
+    /// The po2 of the segments that were measured.
+    pub po2: usize,
+    /// Time to prove one segment.
+    pub segment_time: Duration,
+    /// Peak memory use while proving one segment, in bytes per cycle.
+    pub bytes_per_cycle: u64,
+    /// Whether these figures were measured, by `cargo risczero benchmark`,
+    /// rather than taken from the uncalibrated [Calibration::defaults].
+    pub measured: bool,
+}
+
+// The parts of the JSON report of `cargo risczero benchmark` that calibrations
+// are read from.
+#[derive(Deserialize)]
+struct BenchmarkReport {
+    results: Vec<BenchmarkResult>,
+}
+
+#[derive(Deserialize)]
+struct BenchmarkResult {
+    workload: String,
+    hal: String,
+    po2: usize,
+    segments: usize,
+    prove_secs: f64,
+    peak_memory: u64,
+}
+
+impl Calibration {
+    /// The calibrations used by [estimate] and [estimate_elf], for the HALs
+    /// enabled in this build.
+    ///
+    /// These are uncalibrated: they guess the order of magnitude of each HAL,
+    /// and were not measured on any particular machine. For costs to quote or
+    /// plan capacity with, measure the machine that will prove with
+    /// `cargo risczero benchmark --json`, read the report with
+    /// [Calibration::from_benchmark], and pass the result to
+    /// [estimate_with_calibrations].
+    pub fn defaults() -> Vec<Calibration> {
+        let calibration = |hal, segment_time| Calibration {
+            hal,
+            po2: DEFAULT_CALIBRATION_PO2,
+            segment_time,
+            bytes_per_cycle: 9 * 1024,
+            measured: false,
+        };
+        let mut calibrations = vec![calibration(HalKind::Cpu, Duration::from_secs(28))];
+        if cfg!(feature = "cuda") {
+            calibrations.push(calibration(HalKind::Cuda, Duration::from_millis(2400)));
+        }
+        if cfg!(feature = "metal") {
+            calibrations.push(calibration(HalKind::Metal, Duration::from_secs(12)));
+        }
+        calibrations
+    }
+
+    /// Read calibrations from the JSON report written by
+    /// `cargo risczero benchmark --json`, one for each HAL in the report.
+    ///
+    /// Each HAL is calibrated from its result for the `loop` workload at the
+    /// largest po2 measured, which runs for about one segment of pure
+    /// computation. Results for a po2 outside of the segment sizes the prover
+    /// supports are rejected.
+    pub fn from_benchmark(json: &[u8]) -> Result<Vec<Calibration>> {
+        let report: BenchmarkReport =
+            serde_json::from_slice(json).context("invalid benchmark report")?;
+        let mut calibrations = BTreeMap::new();
+        for result in report.results {
+            if result.workload != "loop" || result.segments == 0 {
+                continue;
+            }
+            let hal = match result.hal.as_str() {
+                "cpu" => HalKind::Cpu,
+                "cuda" => HalKind::Cuda,
+                "metal" => HalKind::Metal,
+                hal => bail!("unknown HAL in benchmark report: {hal}"),
+            };
+            check_po2(result.po2)?;
+            let calibration = Calibration {
+                hal,
+                po2: result.po2,
+                segment_time: Duration::from_secs_f64(result.prove_secs / result.segments as f64),
+                bytes_per_cycle: result.peak_memory >> result.po2,
+                measured: true,
+            };
+            calibrations
+                .entry(hal)
+                .and_modify(|current: &mut Calibration| {
+                    if calibration.po2 > current.po2 {
+                        *current = calibration.clone();
+                    }
+                })
+                .or_insert(calibration);
+        }
+        if calibrations.is_empty() {
+            bail!("benchmark report has no results for the loop workload");
+        }
+        Ok(calibrations.into_values().collect())
+    }
+
+    // Only called on calibrations whose po2 passed [check_po2], which is never
+    // zero, for segments of at most MAX_CYCLES_PO2.
+    fn time(&self, po2: usize) -> Duration {
+        let calibration_work = (self.po2 << self.po2) as f64;
+        let work = (po2 << po2) as f64;
+        self.segment_time.mul_f64(work / calibration_work)
+    }
+
+    fn memory(&self, po2: usize) -> u64 {
+        self.bytes_per_cycle.saturating_mul(1 << po2)
+    }
+}
+
+fn check_po2(po2: usize) -> Result<()> {
+    ensure!(
+        (MIN_CYCLES_PO2..=MAX_CYCLES_PO2).contains(&po2),
+        "calibration po2 of {po2} is outside of {MIN_CYCLES_PO2}..={MAX_CYCLES_PO2}"
+    );
+    Ok(())
+}
+
+/// Estimate the cost of proving the given [Session] with the given options.
+///
+/// Each segment of the session is resolved to find its size; for sessions
+/// that are expensive to resolve, see [estimate_elf].
+///
+/// Estimates are derived from the uncalibrated [Calibration::defaults], which
+/// only give the order of magnitude of the cost, and are marked as such by
+/// [HalEstimate::calibrated].
+/// See [estimate_with_calibrations] for estimates from the throughput of a
+/// particular machine.
+pub fn estimate(session: &Session, opts: &ProverOpts) -> Result<CostEstimate> {
+    estimate_with_calibrations(session, opts, &Calibration::defaults())
+}
+
+/// Estimate the cost of proving the given [Session] with the given options,
+/// with an estimate for each of the given calibrations.
+///
+/// Returns an error if the po2 of a calibration is not a supported segment
+/// size.
+pub fn estimate_with_calibrations(
+    session: &Session,
+    opts: &ProverOpts,
+    calibrations: &[Calibration],
+) -> Result<CostEstimate> {
+    let po2s = session
+        .segments
+        .iter()
+        .map(|segment_ref| Ok(segment_ref.resolve()?.po2()))
+        .collect::<Result<Vec<_>>>()?;
+    from_po2s(
+        &po2s,
+        session.user_cycles,
+        session.total_cycles,
+        opts,
+        calibrations,
+    )
+}
+
+/// Estimate the cost of proving the specified ELF binary with the given
+/// options.
+///
+/// The guest is executed without keeping any segments, which is much cheaper
+/// than proving it. See [estimate] for the accuracy of the result.
+pub fn estimate_elf(env: ExecutorEnv<'_>, elf: &[u8], opts: &ProverOpts) -> Result<CostEstimate> {
+    estimate_elf_with_calibrations(env, elf, opts, &Calibration::defaults())
+}
+
+/// Estimate the cost of proving the specified ELF binary with the given
+/// options, with an estimate for each of the given calibrations.
+pub fn estimate_elf_with_calibrations(
+    env: ExecutorEnv<'_>,
+    elf: &[u8],
+    opts: &ProverOpts,
+    calibrations: &[Calibration],
+) -> Result<CostEstimate> {
+    let mut po2s = Vec::new();
+    let session = ExecutorImpl::from_elf(env, elf)?.run_with_callback(|segment| {
+        po2s.push(segment.po2());
+        Ok(Box::new(NullSegmentRef))
+    })?;
+    from_po2s(
+        &po2s,
+        session.user_cycles,
+        session.total_cycles,
+        opts,
+        calibrations,
+    )
+}
+
+fn from_po2s(
+    po2s: &[usize],
+    user_cycles: u64,
+    total_cycles: u64,
+    opts: &ProverOpts,
+    calibrations: &[Calibration],
+) -> Result<CostEstimate> {
+    for calibration in calibrations {
+        check_po2(calibration.po2)?;
+    }
+
+    // Every segment is lifted and all but one are joined to produce a
+    // succinct receipt. Groth16 compression adds an identity_p254 step.
+    let recursions = match opts.receipt_kind {
+        ReceiptKind::Composite => 0,
+        ReceiptKind::Succinct => (2 * po2s.len()).saturating_sub(1),
+        ReceiptKind::Plonk | ReceiptKind::Compact => 2 * po2s.len(),
+    };
+
+    let hals = calibrations
+        .iter()
+        .map(|calibration| {
+            let segment_time: Duration = po2s.iter().map(|po2| calibration.time(*po2)).sum();
+            let recursion_time = calibration.time(RECURSION_PO2) * recursions as u32;
+            let groth16_time = match opts.receipt_kind {
+                ReceiptKind::Plonk => PLONK_TIME,
+                ReceiptKind::Compact => GROTH16_TIME,
+                _ => Duration::ZERO,
+            };
+
+            let mut peak_po2 = po2s.iter().copied().max().unwrap_or_default();
+            if recursions > 0 {
+                peak_po2 = peak_po2.max(RECURSION_PO2);
+            }
+
+            HalEstimate {
+                hal: calibration.hal,
+                prover_time: segment_time + recursion_time + groth16_time,
+                peak_memory: calibration.memory(peak_po2),
+                calibrated: calibration.measured,
+            }
+        })
+        .collect();
+
+    Ok(CostEstimate {
+        segments: po2s.len(),
+        user_cycles,
+        total_cycles,
+        hals,
+    })
+}
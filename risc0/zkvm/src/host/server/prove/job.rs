@@ -0,0 +1,293 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A crash-safe, on-disk journal for proving jobs.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, ensure, Context as _, Result};
+use risc0_binfmt::{ExitCode, SystemState};
+use serde::{Deserialize, Serialize};
+
+use super::ProverServer;
+use crate::{
+    host::{
+        prove_info::{ProveInfo, SessionStats},
+        server::store::write_atomic,
+    },
+    receipt::{InnerReceipt, SegmentReceipt},
+    sha::{Digest, Digestible},
+    Assumption, CompositeReceipt, ExecutorEnv, ExecutorImpl, ProverOpts, Receipt, Segment,
+    SegmentRef, Session, VerifierContext,
+};
+
+const STATE_FILE: &str = "job.bincode";
+const RECEIPT_FILE: &str = "receipt.bincode";
+const SEGMENTS_DIR: &str = "segments";
+const RECEIPTS_DIR: &str = "receipts";
+
+/// The parts of a [Session] needed to assemble its receipt once all segments
+/// are proven.
+#[derive(Serialize, Deserialize)]
+struct SessionInfo {
+    input: Digest,
+    journal: Option<Vec<u8>>,
+    exit_code: ExitCode,
+    assumptions: Vec<Assumption>,
+    user_cycles: u64,
+    total_cycles: u64,
+    pre_state: SystemState,
+    post_state: SystemState,
+}
+
+/// The state of a job, written to disk after every step.
+#[derive(Default, Serialize, Deserialize)]
+struct JobState {
+    // Paths of executed segments, relative to the job directory.
+    segments: Vec<PathBuf>,
+    // Paths of segment receipts, relative to the job directory, indexed by segment.
+    proven: Vec<Option<PathBuf>>,
+    // Set once execution has completed.
+    session: Option<SessionInfo>,
+    // Set once the final receipt has been written.
+    complete: bool,
+}
+
+/// A proving job whose progress is journaled to a directory.
+///
+/// Each executed segment, each proven segment receipt, and the final receipt
+/// are written to the job directory, and the job state is updated atomically
+/// after every step. If the process is interrupted after execution has
+/// completed, [ProvingJob::recover] reopens the job and [ProvingJob::prove]
+/// proves only the segments that do not yet have a receipt.
+pub struct ProvingJob {
+    dir: PathBuf,
+    state: JobState,
+}
+
+impl ProvingJob {
+    /// Execute the given ELF binary, journaling each segment to `job_dir`.
+    ///
+    /// An interrupted execution cannot be resumed; the job must be started
+    /// again.
+    pub fn execute(job_dir: impl AsRef<Path>, env: ExecutorEnv<'_>, elf: &[u8]) -> Result<Self> {
+        let mut job = Self::create(job_dir)?;
+        let mut exec = ExecutorImpl::from_elf(env, elf)?;
+        let session = exec.run_with_callback(|segment| job.record_segment(&segment))?;
+        job.record_session(&session)?;
+        Ok(job)
+    }
+
+    /// Create a job in `job_dir` from a [Session] that has already been
+    /// executed.
+    pub fn from_session(job_dir: impl AsRef<Path>, session: &Session) -> Result<Self> {
+        let mut job = Self::create(job_dir)?;
+        for segment_ref in session.segments.iter() {
+            job.record_segment(&segment_ref.resolve()?)?;
+        }
+        job.record_session(session)?;
+        Ok(job)
+    }
+
+    /// Reopen the job journaled in `job_dir`, for example after a crash.
+    pub fn recover(job_dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = job_dir.as_ref().to_path_buf();
+        let contents = fs::read(dir.join(STATE_FILE))
+            .with_context(|| format!("failed to read job state in {}", dir.display()))?;
+        let state: JobState = bincode::deserialize(&contents)?;
+        ensure!(
+            state.session.is_some(),
+            "job in {} was interrupted during execution and must be restarted",
+            dir.display()
+        );
+        Ok(Self { dir, state })
+    }
+
+    /// The number of segments in this job.
+    pub fn segment_count(&self) -> usize {
+        self.state.segments.len()
+    }
+
+    /// The number of segments that have been proven.
+    pub fn proven_count(&self) -> usize {
+        self.state.proven.iter().filter(|x| x.is_some()).count()
+    }
+
+    /// Returns true if the final receipt has been produced.
+    pub fn is_complete(&self) -> bool {
+        self.state.complete
+    }
+
+    /// Prove the remaining segments of this job and return the final receipt,
+    /// compressed to the [crate::ReceiptKind] requested in `opts`.
+    ///
+    /// If the job is already complete, the stored receipt is returned.
+    pub fn prove(
+        &mut self,
+        prover: &dyn ProverServer,
+        ctx: &VerifierContext,
+        opts: &ProverOpts,
+    ) -> Result<ProveInfo> {
+        let Some(session) = self.state.session.as_ref() else {
+            bail!("job execution has not completed");
+        };
+        let stats = SessionStats {
+            segments: self.state.segments.len(),
+            total_cycles: session.total_cycles,
+            user_cycles: session.user_cycles,
+            usage: None,
+            cost: None,
+        };
+
+        if self.state.complete {
+            let receipt = bincode::deserialize(&fs::read(self.dir.join(RECEIPT_FILE))?)?;
+            return Ok(ProveInfo { receipt, stats });
+        }
+
+        self.prove_segments(prover, ctx, || false)?;
+        let receipt = self.assemble()?;
+        receipt.verify_integrity_with_context(ctx)?;
+        let receipt = prover.compress(opts, &receipt)?;
+        write_atomic(&self.dir.join(RECEIPT_FILE), &bincode::serialize(&receipt)?)?;
+        self.state.complete = true;
+        self.save()?;
+
+        Ok(ProveInfo { receipt, stats })
+    }
+
+    /// Prove the remaining segments of this job until `pause` returns true,
+    /// returning true if every segment is proven.
+    ///
+    /// `pause` is called before each segment, so that a scheduler can stop the
+    /// job at a segment boundary to run another, and continue it later with
+    /// another call to this method or to [ProvingJob::prove].
+    pub fn prove_segments(
+        &mut self,
+        prover: &dyn ProverServer,
+        ctx: &VerifierContext,
+        mut pause: impl FnMut() -> bool,
+    ) -> Result<bool> {
+        ensure!(
+            self.state.session.is_some(),
+            "job execution has not completed"
+        );
+        for idx in 0..self.state.segments.len() {
+            if self.state.proven[idx].is_some() {
+                continue;
+            }
+            if pause() {
+                return Ok(false);
+            }
+            let segment: Segment =
+                bincode::deserialize(&fs::read(self.dir.join(&self.state.segments[idx]))?)?;
+            let receipt = prover.prove_segment(ctx, &segment)?;
+            let path = Path::new(RECEIPTS_DIR).join(format!("{idx}.bincode"));
+            write_atomic(&self.dir.join(&path), &bincode::serialize(&receipt)?)?;
+            self.state.proven[idx] = Some(path);
+            self.save()?;
+        }
+        Ok(true)
+    }
+
+    fn create(job_dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = job_dir.as_ref().to_path_buf();
+        ensure!(
+            !dir.join(STATE_FILE).exists(),
+            "a job already exists in {}",
+            dir.display()
+        );
+        fs::create_dir_all(dir.join(SEGMENTS_DIR))?;
+        fs::create_dir_all(dir.join(RECEIPTS_DIR))?;
+        let job = Self {
+            dir,
+            state: JobState::default(),
+        };
+        job.save()?;
+        Ok(job)
+    }
+
+    fn record_segment(&mut self, segment: &Segment) -> Result<Box<dyn SegmentRef>> {
+        let path = Path::new(SEGMENTS_DIR).join(format!("{}.bincode", segment.index));
+        write_atomic(&self.dir.join(&path), &bincode::serialize(segment)?)?;
+        self.state.segments.push(path.clone());
+        self.state.proven.push(None);
+        self.save()?;
+        Ok(Box::new(JobSegmentRef(self.dir.join(path))))
+    }
+
+    fn record_session(&mut self, session: &Session) -> Result<()> {
+        ensure!(
+            session.calls.is_empty(),
+            "proving jobs do not support sessions with calls to other programs"
+        );
+        self.state.session = Some(SessionInfo {
+            input: session.input,
+            journal: session.journal.as_ref().map(|x| x.bytes.clone()),
+            exit_code: session.exit_code,
+            assumptions: session.assumptions.clone(),
+            user_cycles: session.user_cycles,
+            total_cycles: session.total_cycles,
+            pre_state: session.pre_state.clone(),
+            post_state: session.post_state.clone(),
+        });
+        self.save()
+    }
+
+    fn assemble(&self) -> Result<Receipt> {
+        let session = self.state.session.as_ref().unwrap();
+        let segments = self
+            .state
+            .proven
+            .iter()
+            .map(|path| -> Result<SegmentReceipt> {
+                let path = path.as_ref().unwrap();
+                Ok(bincode::deserialize(&fs::read(self.dir.join(path))?)?)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let assumptions = session
+            .assumptions
+            .iter()
+            .map(|x| Ok(x.as_receipt()?.inner.clone()))
+            .collect::<Result<Vec<_>>>()?;
+        let journal = session.journal.clone().unwrap_or_default();
+        let composite_receipt = CompositeReceipt {
+            segments,
+            assumptions,
+            journal_digest: session.journal.as_ref().map(|x| x.digest()),
+        };
+        Ok(Receipt::new(
+            InnerReceipt::Composite(composite_receipt),
+            journal,
+        ))
+    }
+
+    fn save(&self) -> Result<()> {
+        write_atomic(
+            &self.dir.join(STATE_FILE),
+            &bincode::serialize(&self.state)?,
+        )
+    }
+}
+
+/// A [SegmentRef] to a segment stored in a job directory.
+struct JobSegmentRef(PathBuf);
+
+impl SegmentRef for JobSegmentRef {
+    fn resolve(&self) -> Result<Segment> {
+        Ok(bincode::deserialize(&fs::read(&self.0)?)?)
+    }
+}
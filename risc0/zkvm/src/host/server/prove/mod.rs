@@ -15,6 +15,10 @@
 //! Run the zkVM guest and prove its results.
 
 mod dev_mode;
+pub(crate) mod estimate;
+pub(crate) mod job;
+pub(crate) mod pipeline;
+pub(crate) mod plan;
 mod prover_impl;
 #[cfg(test)]
 mod tests;
@@ -23,16 +27,22 @@ use std::rc::Rc;
 
 use anyhow::{anyhow, bail, ensure, Result};
 use cfg_if::cfg_if;
+use risc0_circuit_rv32im::prove::emu::trap;
 use risc0_core::field::baby_bear::{BabyBear, Elem, ExtElem};
 use risc0_zkp::hal::{CircuitHal, Hal};
 
-use self::{dev_mode::DevModeProver, prover_impl::ProverImpl};
+use self::{
+    dev_mode::DevModeProver,
+    plan::{CompressionPlan, JoinTree},
+    prover_impl::ProverImpl,
+};
 use crate::{
     host::prove_info::ProveInfo,
     is_dev_mode,
     receipt::{CompositeReceipt, InnerReceipt, SegmentReceipt, SuccinctReceipt},
-    stark_to_snark, CompactReceipt, ExecutorEnv, ExecutorImpl, ProverOpts, Receipt, ReceiptKind,
-    Segment, Session, VerifierContext,
+    stark_to_plonk, stark_to_snark, Assumption, CompactReceipt, ExecutorEnv, ExecutorImpl,
+    FaultReceipt, PlonkReceipt, ProverOpts, Receipt, ReceiptKind, Segment, Session,
+    VerifierContext,
 };
 
 /// A ProverServer can execute a given ELF binary and produce a [ProveInfo] which contains a [crate::Receipt]
@@ -58,6 +68,18 @@ pub trait ProverServer {
     /// Prove the specified [Session].
     fn prove_session(&self, ctx: &VerifierContext, session: &Session) -> Result<ProveInfo>;
 
+    /// Prove that the guest of the specified [Session] faulted.
+    ///
+    /// The session must have been run with
+    /// [ExecutorEnvBuilder::split_on_fault](crate::ExecutorEnvBuilder::split_on_fault),
+    /// and have ended in a [Session::fault].
+    fn prove_fault(&self, ctx: &VerifierContext, session: &Session) -> Result<FaultReceipt> {
+        ensure!(session.fault.is_some(), "session did not end in a fault");
+        let (_, pages) = trap::open(&session.post_image)?;
+        let receipt = self.prove_session(ctx, session)?.receipt;
+        Ok(FaultReceipt { receipt, pages })
+    }
+
     /// Prove the specified [Segment].
     fn prove_segment(&self, ctx: &VerifierContext, segment: &Segment) -> Result<SegmentReceipt>;
 
@@ -87,41 +109,63 @@ pub trait ProverServer {
     /// accomplishes this by iterative application of the recursion programs including lift, join,
     /// and resolve.
     fn compsite_to_succinct(&self, receipt: &CompositeReceipt) -> Result<SuccinctReceipt> {
+        let plan = CompressionPlan::sequential(receipt).ok_or(anyhow!(
+            "malformed composite receipt has no continuation segment receipts"
+        ))?;
+        self.composite_to_succinct_with_plan(receipt, &plan)
+    }
+
+    /// Compress a [CompositeReceipt] into a single [SuccinctReceipt], joining segments in the
+    /// order given by a [CompressionPlan].
+    ///
+    /// The plan must match the shape of the receipt, as checked by [CompressionPlan::check].
+    fn composite_to_succinct_with_plan(
+        &self,
+        receipt: &CompositeReceipt,
+        plan: &CompressionPlan,
+    ) -> Result<SuccinctReceipt> {
+        plan.check(receipt)?;
+
         // Compress all receipts in the top-level session into one succinct receipt for the session.
-        let continuation_receipt = receipt
-            .segments
-            .iter()
-            .try_fold(
-                None,
-                |left: Option<SuccinctReceipt>, right: &SegmentReceipt| -> Result<_> {
-                    Ok(Some(match left {
-                        Some(left) => self.join(&left, &self.lift(right)?)?,
-                        None => self.lift(right)?,
-                    }))
-                },
-            )?
-            .ok_or(anyhow!(
-                "malformed composite receipt has no continuation segment receipts"
-            ))?;
+        let continuation_receipt = self.join_tree(&receipt.segments, &plan.joins)?;
 
         // Compress assumptions and resolve them to get the final succinct receipt.
-        receipt.assumptions.iter().try_fold(
+        receipt.assumptions.iter().zip(&plan.assumptions).try_fold(
             continuation_receipt,
-            |conditional: SuccinctReceipt, assumption: &InnerReceipt| match assumption {
+            |conditional: SuccinctReceipt, (assumption, assumption_plan)| match assumption {
                 InnerReceipt::Succinct(assumption) => self.resolve(&conditional, assumption),
                 InnerReceipt::Composite(assumption) => {
-                    self.resolve(&conditional, &self.compsite_to_succinct(assumption)?)
+                    // CompressionPlan::check ensures composite assumptions have a plan.
+                    let assumption_plan = assumption_plan.as_ref().unwrap();
+                    let assumption =
+                        self.composite_to_succinct_with_plan(assumption, assumption_plan)?;
+                    self.resolve(&conditional, &assumption)
                 }
                 InnerReceipt::Fake { .. } => bail!(
                     "compressing composite receipts with fake receipt assumptions is not supported"
                 ),
-                InnerReceipt::Compact(_) => bail!(
-                    "compressing composite receipts with Compact receipt assumptions is not supported"
-                )
+                // The recursion circuit has no Groth16 verifier, so these can only be checked
+                // natively, as CompositeReceipt verification does.
+                InnerReceipt::Compact(_) | InnerReceipt::Plonk(_) => bail!(
+                    "compressing composite receipts with Compact or Plonk receipt assumptions is \
+                    not supported; use ReceiptKind::Composite, or provide a SuccinctReceipt for \
+                    the assumption"
+                ),
             },
         )
     }
 
+    /// Lift and join the segment receipts as the given [JoinTree] describes.
+    fn join_tree(&self, segments: &[SegmentReceipt], tree: &JoinTree) -> Result<SuccinctReceipt> {
+        match tree {
+            JoinTree::Segment(index) => self.lift(&segments[*index]),
+            JoinTree::Join(left, right) => self.join(
+                &self.join_tree(segments, left)?,
+                &self.join_tree(segments, right)?,
+            ),
+        }
+    }
+
     /// Compress a [SuccinctReceipt] into a [CompactReceipt].
     fn succinct_to_compact(&self, receipt: &SuccinctReceipt) -> Result<CompactReceipt> {
         let ident_receipt = self.identity_p254(receipt).unwrap();
@@ -134,6 +178,18 @@ pub trait ProverServer {
         })
     }
 
+    /// Compress a [SuccinctReceipt] into a [PlonkReceipt].
+    fn succinct_to_plonk(&self, receipt: &SuccinctReceipt) -> Result<PlonkReceipt> {
+        let ident_receipt = self.identity_p254(receipt)?;
+        let seal_bytes = ident_receipt.get_seal_bytes();
+
+        let seal = stark_to_plonk(&seal_bytes)?.to_vec();
+        Ok(PlonkReceipt {
+            seal,
+            claim: receipt.claim.clone(),
+        })
+    }
+
     /// Compress a receipt into one with a smaller representation.
     ///
     /// The requested target representation is determined by the [ReceiptKind] specified on the
@@ -150,6 +206,14 @@ pub trait ProverServer {
                         receipt.journal.bytes.clone(),
                     ))
                 }
+                ReceiptKind::Plonk => {
+                    let succinct_receipt = self.compsite_to_succinct(inner)?;
+                    let plonk_receipt = self.succinct_to_plonk(&succinct_receipt)?;
+                    Ok(Receipt::new(
+                        InnerReceipt::Plonk(plonk_receipt),
+                        receipt.journal.bytes.clone(),
+                    ))
+                }
                 ReceiptKind::Compact => {
                     let succinct_receipt = self.compsite_to_succinct(inner)?;
                     let compact_receipt = self.succinct_to_compact(&succinct_receipt)?;
@@ -161,6 +225,13 @@ pub trait ProverServer {
             },
             InnerReceipt::Succinct(inner) => match opts.receipt_kind {
                 ReceiptKind::Composite | ReceiptKind::Succinct => Ok(receipt.clone()),
+                ReceiptKind::Plonk => {
+                    let plonk_receipt = self.succinct_to_plonk(inner)?;
+                    Ok(Receipt::new(
+                        InnerReceipt::Plonk(plonk_receipt),
+                        receipt.journal.bytes.clone(),
+                    ))
+                }
                 ReceiptKind::Compact => {
                     let compact_receipt = self.succinct_to_compact(inner)?;
                     Ok(Receipt::new(
@@ -170,9 +241,21 @@ pub trait ProverServer {
                 }
             },
             InnerReceipt::Compact(_) => match opts.receipt_kind {
-                ReceiptKind::Composite | ReceiptKind::Succinct | ReceiptKind::Compact => {
+                ReceiptKind::Composite
+                | ReceiptKind::Succinct
+                | ReceiptKind::Plonk
+                | ReceiptKind::Compact => Ok(receipt.clone()),
+            },
+            InnerReceipt::Plonk(_) => match opts.receipt_kind {
+                ReceiptKind::Composite | ReceiptKind::Succinct | ReceiptKind::Plonk => {
                     Ok(receipt.clone())
                 }
+                ReceiptKind::Compact => {
+                    bail!(
+                        "a PlonkReceipt cannot be compressed into a CompactReceipt; compress \
+                        the SuccinctReceipt it was made from instead"
+                    )
+                }
             },
             InnerReceipt::Fake { claim: _ } => {
                 ensure!(
@@ -206,6 +289,27 @@ impl Session {
         let prover = get_prover_server(&ProverOpts::default())?;
         prover.prove_session(&VerifierContext::default(), self)
     }
+
+    /// Collect the receipts for the assumptions of this [Session], proving any
+    /// that are backed by a call to another guest program.
+    pub(crate) fn prove_assumptions(
+        &self,
+        prover: &dyn ProverServer,
+        ctx: &VerifierContext,
+    ) -> Result<Vec<InnerReceipt>> {
+        // TODO(#982): Support unresolved assumptions here.
+        self.assumptions
+            .iter()
+            .map(|x| {
+                if let Assumption::Unresolved(claim) = x {
+                    if let Some(call) = self.find_call(claim) {
+                        return Ok(prover.prove_session(ctx, call)?.receipt.inner);
+                    }
+                }
+                Ok(x.as_receipt()?.inner.clone())
+            })
+            .collect()
+    }
 }
 
 #[cfg(feature = "cuda")]
@@ -282,7 +386,6 @@ mod metal {
     }
 }
 
-#[allow(dead_code)]
 mod cpu {
     use std::rc::Rc;
 
@@ -327,3 +430,38 @@ pub fn get_prover_server(opts: &ProverOpts) -> Result<Rc<dyn ProverServer>> {
         }
     }
 }
+
+/// The HALs that [get_prover_server_with_hal] can prove with in this build,
+/// starting with the one [get_prover_server] selects.
+pub fn available_hals() -> &'static [&'static str] {
+    cfg_if! {
+        if #[cfg(feature = "cuda")] {
+            &["cuda", "cpu"]
+        } else if #[cfg(feature = "metal")] {
+            &["metal", "cpu"]
+        } else {
+            &["cpu"]
+        }
+    }
+}
+
+/// Select a [ProverServer] that proves with the named HAL, one of
+/// [available_hals], such as to compare the CPU with an accelerator.
+pub fn get_prover_server_with_hal(hal: &str, opts: &ProverOpts) -> Result<Rc<dyn ProverServer>> {
+    if is_dev_mode() {
+        eprintln!("WARNING: proving in dev mode. This will not generate valid, secure proofs.");
+        return Ok(Rc::new(DevModeProver));
+    }
+
+    match hal {
+        #[cfg(feature = "cuda")]
+        "cuda" => cuda::get_prover_server(opts),
+        #[cfg(feature = "metal")]
+        "metal" => metal::get_prover_server(opts),
+        "cpu" => cpu::get_prover_server(opts),
+        _ => bail!(
+            "HAL {hal} is not available in this build, which has {:?}",
+            available_hals()
+        ),
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pipelined execution and proving of a session.
+
+use std::{sync::mpsc, thread};
+
+use anyhow::{anyhow, bail, ensure, Result};
+
+use super::get_prover_server;
+use crate::{
+    host::{prove_info::ProveInfo, server::session::SessionEvents},
+    is_dev_mode,
+    receipt::{InnerReceipt, SegmentReceipt},
+    sha::Digestible,
+    CompositeReceipt, ExecutorEnv, ExecutorImpl, NullSegmentRef, ProverOpts, Receipt, Segment,
+    VerifierContext,
+};
+
+/// Prove the specified ELF binary, proving each segment while the next one is
+/// being executed.
+///
+/// Segments are handed from the executor to a proving thread through a queue
+/// holding at most `queue_depth` segments. When the queue is full, execution
+/// waits for the prover to catch up, which bounds the memory used by pending
+/// segments. The final receipt is compressed to the [crate::ReceiptKind]
+/// requested in `opts`.
+///
+/// As with [ProverServer::prove_session](super::ProverServer::prove_session),
+/// each segment receipt is verified against `ctx` and put in the receipt store
+/// of `env`, if any, and the `hooks` are called for each segment. This happens
+/// on the calling thread as the executor hands over segments, so
+/// [SessionEvents::on_pre_prove_segment] is called when a segment is queued
+/// for proving, and [SessionEvents::on_post_prove_segment] once its receipt has
+/// been verified.
+pub fn prove_pipelined(
+    env: ExecutorEnv<'_>,
+    elf: &[u8],
+    opts: &ProverOpts,
+    ctx: &VerifierContext,
+    hooks: Vec<Box<dyn SessionEvents>>,
+    queue_depth: usize,
+) -> Result<ProveInfo> {
+    let prover = get_prover_server(opts)?;
+    if is_dev_mode() {
+        // Segments are not proven in dev mode, so there is nothing to overlap.
+        let mut session = ExecutorImpl::from_elf(env, elf)?.run()?;
+        session.hooks = hooks;
+        return prover.prove_session(ctx, &session);
+    }
+
+    let receipt_store = env.receipt_store.clone();
+    let mut segments = Vec::new();
+    let mut finish = |(segment, receipt): (Segment, SegmentReceipt)| -> Result<()> {
+        receipt.verify_integrity_with_context(ctx)?;
+        if let Some(store) = &receipt_store {
+            store.put_receipt(&receipt)?;
+        }
+        segments.push(receipt);
+        for hook in &hooks {
+            hook.on_post_prove_segment(&segment);
+        }
+        Ok(())
+    };
+
+    let (sender, receiver) = mpsc::sync_channel(queue_depth);
+    // Proven segments are returned to this thread, which drains them each time
+    // the executor hands over a segment, so at most a few are ever held here.
+    let (proven_sender, proven) = mpsc::channel();
+    let session = thread::scope(|scope| {
+        let worker = scope.spawn(move || -> Result<()> {
+            let prover = get_prover_server(opts)?;
+            // Receipts are checked against `ctx` on the calling thread, as a
+            // [VerifierContext] cannot be sent to this one.
+            let ctx = VerifierContext::default();
+            for segment in receiver {
+                let receipt = prover.prove_segment(&ctx, &segment)?;
+                if proven_sender.send((segment, receipt)).is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        });
+
+        let session = ExecutorImpl::from_elf(env, elf).and_then(|mut exec| {
+            exec.run_with_callback(|segment| {
+                proven.try_iter().try_for_each(&mut finish)?;
+                for hook in &hooks {
+                    hook.on_pre_prove_segment(&segment);
+                }
+                sender
+                    .send(segment)
+                    .map_err(|_| anyhow!("segment prover exited"))?;
+                Ok(Box::new(NullSegmentRef))
+            })
+        });
+        drop(sender);
+        // Finish the segments still being proven once execution is done.
+        let session = session.and_then(|session| {
+            proven.iter().try_for_each(&mut finish)?;
+            Ok(session)
+        });
+        drop(proven);
+
+        // Report a proving error over the errors it causes on this thread.
+        worker
+            .join()
+            .map_err(|_| anyhow!("segment prover panicked"))??;
+        session
+    })?;
+
+    if segments.len() != session.segments.len() {
+        bail!(
+            "proved {} segments, expected {}",
+            segments.len(),
+            session.segments.len()
+        );
+    }
+
+    let composite_receipt = CompositeReceipt {
+        segments,
+        assumptions: session.prove_assumptions(prover.as_ref(), ctx)?,
+        journal_digest: session.journal.as_ref().map(|journal| journal.digest()),
+    };
+    composite_receipt.verify_integrity_with_context(ctx)?;
+    ensure!(
+        composite_receipt.claim()?.digest() == session.claim()?.digest(),
+        "session and composite receipt claim do not match"
+    );
+
+    let receipt = Receipt::new(
+        InnerReceipt::Composite(composite_receipt),
+        session.journal.clone().unwrap_or_default().bytes,
+    );
+    let receipt = prover.compress(opts, &receipt)?;
+    receipt.verify_integrity_with_context(ctx)?;
+
+    Ok(ProveInfo {
+        receipt,
+        stats: session.stats(),
+    })
+}
@@ -0,0 +1,223 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Plans for compressing a [CompositeReceipt] with the recursion programs.
+
+use anyhow::{bail, ensure, Result};
+
+use crate::{receipt::CompositeReceipt, InnerReceipt};
+
+/// The order in which the lifted segments of a [CompositeReceipt] are joined.
+///
+/// The leaves are segment indices. Joins only combine adjacent ranges of
+/// segments, so the leaves must list every segment once, in order. The two
+/// sides of a join are independent, and can be proven in any order or in
+/// parallel.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JoinTree {
+    /// Lift the segment with this index.
+    Segment(usize),
+
+    /// Join the receipts of two subtrees, the left one proving the earlier
+    /// segments.
+    Join(Box<JoinTree>, Box<JoinTree>),
+}
+
+impl JoinTree {
+    /// Join the segments one at a time, from the first: `n - 1` joins deep.
+    ///
+    /// Returns `None` if `n` is zero.
+    pub fn sequential(n: usize) -> Option<Self> {
+        (0..n)
+            .map(Self::Segment)
+            .reduce(|left, right| Self::Join(Box::new(left), Box::new(right)))
+    }
+
+    /// Join the segments as a balanced binary tree: `ceil(log2(n))` joins deep.
+    ///
+    /// Returns `None` if `n` is zero.
+    pub fn balanced(n: usize) -> Option<Self> {
+        (n > 0).then(|| Self::balanced_range(0, n))
+    }
+
+    fn balanced_range(start: usize, end: usize) -> Self {
+        if end - start == 1 {
+            return Self::Segment(start);
+        }
+        let mid = start + (end - start).div_ceil(2);
+        Self::Join(
+            Box::new(Self::balanced_range(start, mid)),
+            Box::new(Self::balanced_range(mid, end)),
+        )
+    }
+
+    /// The number of joins on the longest path from a segment to the root.
+    pub fn depth(&self) -> usize {
+        match self {
+            Self::Segment(_) => 0,
+            Self::Join(left, right) => 1 + left.depth().max(right.depth()),
+        }
+    }
+
+    /// The segment indices of the leaves, from left to right.
+    pub fn segments(&self) -> Vec<usize> {
+        let mut segments = vec![];
+        self.collect_segments(&mut segments);
+        segments
+    }
+
+    fn collect_segments(&self, segments: &mut Vec<usize>) {
+        match self {
+            Self::Segment(index) => segments.push(*index),
+            Self::Join(left, right) => {
+                left.collect_segments(segments);
+                right.collect_segments(segments);
+            }
+        }
+    }
+}
+
+/// A plan for compressing a [CompositeReceipt] into a
+/// [SuccinctReceipt](crate::SuccinctReceipt), as run by
+/// [ProverServer::composite_to_succinct_with_plan](crate::ProverServer::composite_to_succinct_with_plan).
+///
+/// The segments are lifted and joined as `joins` describes, then the
+/// assumptions are resolved, one at a time, in the order of
+/// [CompositeReceipt::assumptions]. That order is not part of the plan: the
+/// resolve program only removes the first assumption of the list in the
+/// claim, so no other order can succeed. Assumptions that are composite
+/// receipts are compressed first, with their own plans, and these can run in
+/// any order or in parallel.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompressionPlan {
+    /// How the segments are joined.
+    pub joins: JoinTree,
+
+    /// The plans for the assumptions, by index in
+    /// [CompositeReceipt::assumptions]. Only composite receipts have a plan;
+    /// the entries for other receipts are `None`.
+    pub assumptions: Vec<Option<CompressionPlan>>,
+}
+
+impl CompressionPlan {
+    /// The plan that [ProverServer::compsite_to_succinct](crate::ProverServer::compsite_to_succinct)
+    /// runs, which joins the segments of each receipt sequentially.
+    ///
+    /// Returns `None` if the receipt, or a composite assumption of it, has no
+    /// segments.
+    pub fn sequential(receipt: &CompositeReceipt) -> Option<Self> {
+        Self::with_joins(receipt, &JoinTree::sequential)
+    }
+
+    /// A plan that joins the segments of each receipt as a balanced tree,
+    /// which keeps the depth of the joins logarithmic in the number of
+    /// segments.
+    ///
+    /// Returns `None` if the receipt, or a composite assumption of it, has no
+    /// segments.
+    pub fn balanced(receipt: &CompositeReceipt) -> Option<Self> {
+        Self::with_joins(receipt, &JoinTree::balanced)
+    }
+
+    fn with_joins(
+        receipt: &CompositeReceipt,
+        joins: &dyn Fn(usize) -> Option<JoinTree>,
+    ) -> Option<Self> {
+        Some(Self {
+            joins: joins(receipt.segments.len())?,
+            assumptions: receipt
+                .assumptions
+                .iter()
+                .map(|assumption| match assumption {
+                    InnerReceipt::Composite(assumption) => {
+                        Self::with_joins(assumption, joins).map(Some)
+                    }
+                    _ => Some(None),
+                })
+                .collect::<Option<_>>()?,
+        })
+    }
+
+    /// The number of recursion programs on the longest path to the final
+    /// receipt: the lifts, joins and resolves that must run one after another
+    /// however much of the plan runs in parallel.
+    pub fn depth(&self) -> usize {
+        // The resolves run in sequence, each after the compression of its assumption.
+        self.assumptions
+            .iter()
+            .fold(self.joins.depth() + 1, |depth, plan| {
+                let assumption = plan.as_ref().map_or(0, Self::depth);
+                depth.max(assumption) + 1
+            })
+    }
+
+    /// Check that the plan matches the shape of the receipt.
+    pub fn check(&self, receipt: &CompositeReceipt) -> Result<()> {
+        let segments = self.joins.segments();
+        ensure!(
+            segments.iter().copied().eq(0..receipt.segments.len()),
+            "compression plan joins segments {segments:?}, but the receipt has {} segments, \
+             which must be joined in order",
+            receipt.segments.len()
+        );
+        ensure!(
+            self.assumptions.len() == receipt.assumptions.len(),
+            "compression plan has {} assumptions, but the receipt has {}",
+            self.assumptions.len(),
+            receipt.assumptions.len()
+        );
+        for (index, (plan, assumption)) in self
+            .assumptions
+            .iter()
+            .zip(&receipt.assumptions)
+            .enumerate()
+        {
+            match (plan, assumption) {
+                (Some(plan), InnerReceipt::Composite(assumption)) => plan.check(assumption)?,
+                (None, InnerReceipt::Composite(_)) => {
+                    bail!("compression plan has no plan for composite assumption {index}")
+                }
+                (Some(_), _) => bail!(
+                    "compression plan has a plan for assumption {index}, which is not composite"
+                ),
+                (None, _) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JoinTree;
+
+    #[test]
+    fn join_trees() {
+        assert_eq!(JoinTree::sequential(0), None);
+        assert_eq!(JoinTree::balanced(0), None);
+        assert_eq!(JoinTree::balanced(1), Some(JoinTree::Segment(0)));
+
+        for n in 1..20 {
+            let sequential = JoinTree::sequential(n).unwrap();
+            let balanced = JoinTree::balanced(n).unwrap();
+            assert_eq!(sequential.segments(), (0..n).collect::<Vec<_>>());
+            assert_eq!(balanced.segments(), (0..n).collect::<Vec<_>>());
+            assert_eq!(sequential.depth(), n - 1);
+            assert_eq!(
+                balanced.depth(),
+                n.next_power_of_two().trailing_zeros() as usize
+            );
+        }
+    }
+}
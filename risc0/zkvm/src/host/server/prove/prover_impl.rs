@@ -20,12 +20,13 @@ use super::{HalPair, ProverServer};
 use crate::{
     host::{
         client::prove::ReceiptKind,
+        cost::process_cpu_time,
         prove_info::ProveInfo,
         recursion::{identity_p254, join, lift, resolve},
     },
     receipt::{InnerReceipt, SegmentReceipt, SuccinctReceipt},
     sha::Digestible,
-    CompositeReceipt, Receipt, Segment, Session, VerifierContext,
+    CompositeReceipt, ProvingUsage, Receipt, ReceiptAnnotations, Segment, Session, VerifierContext,
 };
 
 /// An implementation of a Prover that runs locally.
@@ -59,6 +60,16 @@ where
     H: Hal<Field = BabyBear, Elem = Elem, ExtElem = ExtElem>,
     C: CircuitHal<H>,
 {
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            hal = %self.name,
+            receipt_kind = ?self.receipt_kind,
+            segments = session.segments.len(),
+            user_cycles = session.user_cycles,
+            total_cycles = session.total_cycles,
+        )
+    )]
     fn prove_session(&self, ctx: &VerifierContext, session: &Session) -> Result<ProveInfo> {
         tracing::debug!(
             "prove_session: {}, exit_code = {:?}, journal = {:?}, segments: {}",
@@ -67,23 +78,24 @@ where
             session.journal.as_ref().map(hex::encode),
             session.segments.len()
         );
+        let start_time = std::time::Instant::now();
+        let start_cpu_time = process_cpu_time();
         let mut segments = Vec::new();
         for segment_ref in session.segments.iter() {
             let segment = segment_ref.resolve()?;
             for hook in &session.hooks {
                 hook.on_pre_prove_segment(&segment);
             }
-            segments.push(self.prove_segment(ctx, &segment)?);
+            let receipt = self.prove_segment(ctx, &segment)?;
+            if let Some(store) = &session.receipt_store {
+                store.put_receipt(&receipt)?;
+            }
+            segments.push(receipt);
             for hook in &session.hooks {
                 hook.on_post_prove_segment(&segment);
             }
         }
-        // TODO(#982): Support unresolved assumptions here.
-        let assumptions = session
-            .assumptions
-            .iter()
-            .map(|x| Ok(x.as_receipt()?.inner.clone()))
-            .collect::<Result<Vec<_>>>()?;
+        let assumptions = session.prove_assumptions(self, ctx)?;
         let composite_receipt = CompositeReceipt {
             segments,
             assumptions,
@@ -91,7 +103,9 @@ where
         };
 
         // Verify the receipt to catch if something is broken in the proving process.
-        composite_receipt.verify_integrity_with_context(ctx)?;
+        composite_receipt
+            .verify_integrity_with_context(ctx)
+            .inspect_err(|_| record_verification_failure("composite"))?;
         if composite_receipt.claim()?.digest() != session.claim()?.digest() {
             tracing::debug!("composite receipt and session claim do not match");
             tracing::debug!("composite receipt claim: {:#?}", composite_receipt.claim()?);
@@ -116,6 +130,14 @@ where
                     session.journal.clone().unwrap_or_default().bytes,
                 )
             }
+            ReceiptKind::Plonk => {
+                let succinct_receipt = self.compsite_to_succinct(&composite_receipt)?;
+                let plonk_receipt = self.succinct_to_plonk(&succinct_receipt)?;
+                Receipt::new(
+                    InnerReceipt::Plonk(plonk_receipt),
+                    session.journal.clone().unwrap_or_default().bytes,
+                )
+            }
             ReceiptKind::Compact => {
                 let succinct_receipt = self.compsite_to_succinct(&composite_receipt)?;
                 let compact_receipt = self.succinct_to_compact(&succinct_receipt)?;
@@ -126,8 +148,21 @@ where
             }
         };
 
-        // Verify the receipt to catch if something is broken in the proving process.
-        receipt.verify_integrity_with_context(ctx)?;
+        // Verify the receipt to catch if something is broken in the proving process. Unless the
+        // caller configured a PLONK verifying key, a PLONK receipt is checked against the key
+        // that accompanies the proving key.
+        let plonk_ctx;
+        let ctx = match (&receipt.inner, &ctx.plonk_verifying_key) {
+            (InnerReceipt::Plonk(_), None) => {
+                plonk_ctx = VerifierContext::default()
+                    .with_plonk_verifying_key(risc0_groth16::prove::plonk_verifying_key()?);
+                &plonk_ctx
+            }
+            _ => ctx,
+        };
+        receipt
+            .verify_integrity_with_context(ctx)
+            .inspect_err(|_| record_verification_failure("receipt"))?;
         if receipt.claim()?.digest() != session.claim()?.digest() {
             tracing::debug!("receipt and session claim do not match");
             tracing::debug!("receipt claim: {:#?}", receipt.claim()?);
@@ -139,12 +174,35 @@ where
             );
         }
 
-        Ok(ProveInfo {
-            receipt,
-            stats: session.stats(),
-        })
+        let wall_time = start_time.elapsed();
+        let cpu_time = match (start_cpu_time, process_cpu_time()) {
+            (Some(start), Some(end)) => Some(end.saturating_sub(start)),
+            _ => None,
+        };
+        let mut stats = session.stats();
+        stats.usage = Some(ProvingUsage {
+            wall_time,
+            cpu_time,
+            gpu_wall_time: matches!(self.name.as_str(), "cuda" | "metal").then_some(wall_time),
+        });
+        let receipt = receipt.with_annotations(ReceiptAnnotations {
+            prover_version: Some(crate::VERSION.into()),
+            proving_time: Some(wall_time),
+            total_cycles: Some(stats.total_cycles),
+            ..Default::default()
+        });
+        Ok(ProveInfo { receipt, stats })
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            hal = %self.name,
+            index = segment.index,
+            po2 = segment.po2(),
+            cycles = segment.inner.insn_cycles,
+        )
+    )]
     fn prove_segment(&self, ctx: &VerifierContext, segment: &Segment) -> Result<SegmentReceipt> {
         use risc0_circuit_rv32im::prove::{engine::SegmentProverImpl, SegmentProver as _};
 
@@ -154,7 +212,11 @@ where
 
         let prover =
             SegmentProverImpl::new(self.hal_pair.hal.clone(), self.hal_pair.circuit_hal.clone());
+        #[cfg(feature = "metrics")]
+        let start_time = std::time::Instant::now();
         let seal = prover.prove_segment(&segment.inner)?;
+        #[cfg(feature = "metrics")]
+        crate::host::server::metrics::record_segment_proven(segment.po2(), start_time.elapsed());
 
         let mut claim = decode_receipt_claim_from_seal(&seal)?;
         claim.output = segment.output.clone().into();
@@ -165,19 +227,24 @@ where
             hashfn,
             claim,
         };
-        receipt.verify_integrity_with_context(ctx)?;
+        receipt
+            .verify_integrity_with_context(ctx)
+            .inspect_err(|_| record_verification_failure("segment"))?;
 
         Ok(receipt)
     }
 
+    #[tracing::instrument(skip_all, fields(hal = %self.name))]
     fn lift(&self, receipt: &SegmentReceipt) -> Result<SuccinctReceipt> {
         lift(receipt)
     }
 
+    #[tracing::instrument(skip_all, fields(hal = %self.name))]
     fn join(&self, a: &SuccinctReceipt, b: &SuccinctReceipt) -> Result<SuccinctReceipt> {
         join(a, b)
     }
 
+    #[tracing::instrument(skip_all, fields(hal = %self.name))]
     fn resolve(
         &self,
         conditional: &SuccinctReceipt,
@@ -186,7 +253,13 @@ where
         resolve(conditional, assumption)
     }
 
+    #[tracing::instrument(skip_all, fields(hal = %self.name))]
     fn identity_p254(&self, a: &SuccinctReceipt) -> Result<SuccinctReceipt> {
         identity_p254(a)
     }
 }
+
+fn record_verification_failure(_kind: &'static str) {
+    #[cfg(feature = "metrics")]
+    crate::host::server::metrics::record_verification_failure(_kind);
+}
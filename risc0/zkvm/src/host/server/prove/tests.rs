@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::rc::Rc;
+use std::{rc::Rc, time::Duration};
 
 use anyhow::Result;
 use risc0_binfmt::MemoryImage;
@@ -26,12 +26,15 @@ use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF, MULTI_TEST_I
 use risc0_zkvm_platform::{memory, PAGE_SIZE, WORD_SIZE};
 use test_log::test;
 
-use super::{get_prover_server, HalPair, ProverImpl};
+use super::{get_prover_server, job::ProvingJob, HalPair, ProverImpl};
 use crate::{
+    default_prover,
     host::server::testutils,
     serde::{from_slice, to_vec},
-    ExecutorEnv, ExecutorImpl, ExitCode, ProveInfo, ProverOpts, ProverServer, Receipt, ReceiptKind,
-    Session, VerifierContext,
+    sha::Digestible,
+    CompressionPolicy, ExecutorEnv, ExecutorImpl, ExitCode, InnerReceipt, ProveInfo, ProverOpts,
+    ProverServer, Receipt, ReceiptKind, ReceiptStreamReader, ReceiptStreamWriter, Session,
+    TrapCause, VerifierContext,
 };
 
 fn prover_opts_fast() -> ProverOpts {
@@ -82,6 +85,30 @@ fn prove_nothing_succinct() {
         .unwrap(); // ensure that we got a succinct receipt.
 }
 
+#[test]
+fn compress_with_policy() {
+    let receipt = prove_nothing("poseidon2").unwrap().receipt;
+    let prover = default_prover();
+
+    // The receipt already satisfies a policy that accepts composite receipts.
+    let policy = CompressionPolicy::new(ReceiptKind::Composite);
+    let compressed = prover.compress_with_policy(&policy, &receipt).unwrap();
+    compressed.inner.composite().unwrap();
+
+    let policy = CompressionPolicy::new(ReceiptKind::Succinct).with_allowed_hashfns(["poseidon2"]);
+    let compressed = prover.compress_with_policy(&policy, &receipt).unwrap();
+    compressed.inner.succinct().unwrap();
+    compressed.verify(MULTI_TEST_ID).unwrap();
+
+    // Neither the composite receipt, nor any compressed kind, uses only SHA-256.
+    let policy = CompressionPolicy::new(ReceiptKind::Composite).with_allowed_hashfns(["sha-256"]);
+    let err = prover
+        .compress_with_policy(&policy, &receipt)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("relies on poseidon2"), "{err}");
+}
+
 #[test]
 fn hashfn_poseidon2() {
     prove_nothing("poseidon2").unwrap();
@@ -258,6 +285,43 @@ fn memory_io() {
     assert!(err.contains("LoadAddressMisaligned"), "{err}");
 }
 
+#[test]
+fn prove_fault() {
+    const POS: usize = crate::align_up(
+        (memory::TEXT_START + memory::STACK_TOP) as usize / 2,
+        WORD_SIZE,
+    );
+
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::ReadWriteMem {
+            values: vec![(POS as u32 + 1, 0)],
+        })
+        .unwrap()
+        .split_on_fault(true)
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    assert_eq!(session.exit_code, ExitCode::SystemSplit);
+    let fault = session.fault.clone().unwrap();
+    assert_eq!(fault.cause, TrapCause::LoadAddressMisaligned);
+    assert_eq!(session.post_state.pc, fault.pc);
+
+    let prover = get_prover_server(&prover_opts_fast()).unwrap();
+    let receipt = prover
+        .prove_fault(&VerifierContext::default(), &session)
+        .unwrap();
+    assert_eq!(receipt.verify(MULTI_TEST_ID).unwrap(), fault);
+    assert!(receipt.verify(Digest::ZERO).is_err());
+
+    // The pages must be those of the post state of the receipt.
+    let mut other = receipt.clone();
+    other.pages.pc += WORD_SIZE as u32;
+    assert!(other.verify(MULTI_TEST_ID).is_err());
+}
+
 #[test]
 fn session_events() {
     use std::{cell::RefCell, rc::Rc};
@@ -476,6 +540,170 @@ fn continuation() {
     }
 }
 
+#[test]
+fn proving_job_recover() {
+    let program = testutil::simple_loop();
+    let image = MemoryImage::new(&program, PAGE_SIZE as u32).unwrap();
+
+    let env = ExecutorEnv::builder()
+        .segment_limit_po2(14) // 16k cycles
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::new(env, image).unwrap().run().unwrap();
+
+    let job_dir = tempfile::tempdir().unwrap();
+    let job = ProvingJob::from_session(job_dir.path(), &session).unwrap();
+    assert_eq!(job.segment_count(), 2);
+    assert_eq!(job.proven_count(), 0);
+    drop(job);
+
+    // Reopen the job from disk as if the process had been interrupted.
+    let opts = prover_opts_fast();
+    let prover = get_prover_server(&opts).unwrap();
+    let ctx = VerifierContext::default();
+    let mut job = ProvingJob::recover(job_dir.path()).unwrap();
+
+    // Pause the job after its first segment, then resume it.
+    let mut boundaries = 0;
+    let proven = job
+        .prove_segments(prover.as_ref(), &ctx, || {
+            boundaries += 1;
+            boundaries > 1
+        })
+        .unwrap();
+    assert!(!proven);
+    assert_eq!(job.proven_count(), 1);
+
+    let receipt = job.prove(prover.as_ref(), &ctx, &opts).unwrap().receipt;
+    assert!(job.is_complete());
+    assert_eq!(job.proven_count(), 2);
+    assert_eq!(
+        receipt.claim().unwrap().digest(),
+        session.claim().unwrap().digest()
+    );
+
+    // A completed job returns the stored receipt.
+    let recovered = ProvingJob::recover(job_dir.path())
+        .unwrap()
+        .prove(prover.as_ref(), &ctx, &opts)
+        .unwrap()
+        .receipt;
+    assert_eq!(
+        recovered.claim().unwrap().digest(),
+        receipt.claim().unwrap().digest()
+    );
+}
+
+#[test]
+fn receipt_stream_resume() {
+    let program = testutil::simple_loop();
+    let image = MemoryImage::new(&program, PAGE_SIZE as u32).unwrap();
+    let image_id = image.compute_id();
+
+    let env = ExecutorEnv::builder()
+        .segment_limit_po2(14) // 16k cycles
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::new(env, image).unwrap().run().unwrap();
+    let receipt = prove_session_fast(&session);
+    let bytes = ReceiptStreamWriter::new(Vec::new(), &receipt)
+        .unwrap()
+        .finish()
+        .unwrap();
+
+    // Interrupt the download in the middle of the second chunk.
+    let ctx = VerifierContext::default();
+    let mut reader = ReceiptStreamReader::new(bytes.as_slice(), &ctx).unwrap();
+    assert_eq!(reader.manifest().chunks.len(), 2);
+    let offset = reader.offset() as usize;
+    let cut = offset + 4 + reader.manifest().chunks[0].len as usize + 16;
+    reader.resume(&bytes[..cut]);
+    assert_eq!(reader.read_segment().unwrap().unwrap().index, 0);
+    reader.read_segment().unwrap_err();
+    assert_eq!(reader.segments().len(), 1);
+
+    let offset = reader.offset() as usize;
+    reader.resume(&bytes[offset..]);
+    let streamed = reader.finish(image_id).unwrap();
+    assert_eq!(streamed.journal, receipt.journal);
+    assert_eq!(
+        streamed.claim().unwrap().digest(),
+        receipt.claim().unwrap().digest()
+    );
+
+    // Segments are verified as they arrive, not only at the end.
+    let mut bad = receipt.clone();
+    if let InnerReceipt::Composite(ref mut composite) = bad.inner {
+        composite.segments[1].claim.input.as_mut_words()[0] ^= 1;
+    }
+    let bytes = ReceiptStreamWriter::new(Vec::new(), &bad)
+        .unwrap()
+        .finish()
+        .unwrap();
+    let mut reader = ReceiptStreamReader::new(bytes.as_slice(), &ctx).unwrap();
+    reader.read_segment().unwrap();
+    reader.read_segment().unwrap_err();
+}
+
+#[test]
+fn prove_pipelined() {
+    use std::{cell::RefCell, sync::Arc};
+
+    use crate::{InMemoryStore, ReceiptStore, Segment, SessionEvents};
+
+    struct Logger(Rc<RefCell<Vec<(bool, u32)>>>);
+
+    impl SessionEvents for Logger {
+        fn on_pre_prove_segment(&self, segment: &Segment) {
+            self.0.borrow_mut().push((false, segment.index));
+        }
+
+        fn on_post_prove_segment(&self, segment: &Segment) {
+            self.0.borrow_mut().push((true, segment.index));
+        }
+    }
+
+    let store = Arc::new(InMemoryStore::new());
+    let env = || {
+        ExecutorEnv::builder()
+            .write(&MultiTestSpec::BusyLoop { cycles: 1 << 15 })
+            .unwrap()
+            .segment_limit_po2(14) // 16k cycles
+            .receipt_store(store.clone())
+            .build()
+            .unwrap()
+    };
+    let session = ExecutorImpl::from_elf(env(), MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    assert!(session.segments.len() > 1);
+
+    // A queue depth of one forces execution to wait on the prover.
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let info = super::pipeline::prove_pipelined(
+        env(),
+        MULTI_TEST_ELF,
+        &prover_opts_fast(),
+        &VerifierContext::default(),
+        vec![Box::new(Logger(events.clone()))],
+        1,
+    )
+    .unwrap();
+    assert_eq!(info.stats.segments, session.segments.len());
+    info.receipt.verify(MULTI_TEST_ID).unwrap();
+    assert_eq!(store.list_receipts().unwrap().len(), session.segments.len());
+
+    // Each segment is queued before its receipt is finished, in order.
+    let events = events.take();
+    assert_eq!(events.len(), 2 * session.segments.len());
+    for idx in 0..session.segments.len() as u32 {
+        let queued = events.iter().position(|&x| x == (false, idx)).unwrap();
+        let proven = events.iter().position(|&x| x == (true, idx)).unwrap();
+        assert!(queued < proven);
+    }
+}
+
 #[test]
 fn sys_input() {
     use hex::FromHex;
@@ -532,17 +760,25 @@ mod docker {
             ReceiptKind::Composite => match receipt.inner {
                 InnerReceipt::Composite(_)
                 | InnerReceipt::Succinct(_)
+                | InnerReceipt::Plonk(_)
                 | InnerReceipt::Compact(_) => {}
                 InnerReceipt::Fake { .. } => panic!("unexpected fake receipt"),
             },
             ReceiptKind::Succinct => match receipt.inner {
-                InnerReceipt::Succinct(_) | InnerReceipt::Compact(_) => {}
+                InnerReceipt::Succinct(_) | InnerReceipt::Plonk(_) | InnerReceipt::Compact(_) => {}
                 InnerReceipt::Composite(_) => panic!("expected receipt to be succinct or smaller"),
                 InnerReceipt::Fake { .. } => panic!("unexpected fake receipt"),
             },
+            ReceiptKind::Plonk => match receipt.inner {
+                InnerReceipt::Plonk(_) | InnerReceipt::Compact(_) => {}
+                InnerReceipt::Succinct(_) | InnerReceipt::Composite(_) => {
+                    panic!("expected receipt to be plonk or smaller")
+                }
+                InnerReceipt::Fake { .. } => panic!("unexpected fake receipt"),
+            },
             ReceiptKind::Compact => match receipt.inner {
                 InnerReceipt::Compact(_) => {}
-                InnerReceipt::Succinct(_) | InnerReceipt::Composite(_) => {
+                InnerReceipt::Plonk(_) | InnerReceipt::Succinct(_) | InnerReceipt::Composite(_) => {
                     panic!("expected receipt to be compact or smaller")
                 }
                 InnerReceipt::Fake { .. } => panic!("unexpected fake receipt"),
@@ -644,6 +880,7 @@ mod sys_verify {
     use std::sync::OnceLock;
 
     use crate::ReceiptKind;
+    use risc0_zkvm_aggregator::{AGGREGATOR_ELF, AGGREGATOR_ID};
     use risc0_zkvm_methods::{
         multi_test::MultiTestSpec, HELLO_COMMIT_ELF, HELLO_COMMIT_ID, MULTI_TEST_ELF, MULTI_TEST_ID,
     };
@@ -651,8 +888,9 @@ mod sys_verify {
 
     use super::{get_prover_server, prover_opts_fast};
     use crate::{
-        serde::to_vec, sha::Digestible, ExecutorEnv, ExecutorEnvBuilder, ExitCode, ProverOpts,
-        Receipt,
+        serde::to_vec,
+        sha::{Digest, Digestible},
+        ExecutorEnv, ExecutorEnvBuilder, ExitCode, LocalProver, Prover as _, ProverOpts, Receipt,
     };
 
     fn prove_hello_commit() -> Receipt {
@@ -696,6 +934,27 @@ mod sys_verify {
         ONCE.get_or_init(|| prove_hello_commit())
     }
 
+    #[test]
+    fn aggregate() {
+        // Receipts of two unrelated sessions, with different image IDs.
+        let halt_receipt = prove_halt(0);
+        let receipts = [hello_commit_receipt().clone(), halt_receipt.clone()];
+
+        let receipt = LocalProver::new("local")
+            .aggregate(AGGREGATOR_ELF, &receipts)
+            .unwrap();
+        receipt.inner.succinct().unwrap();
+        receipt.verify(AGGREGATOR_ID).unwrap();
+        let claims: Vec<Digest> = receipt.journal.decode().unwrap();
+        assert_eq!(
+            claims,
+            vec![
+                hello_commit_receipt().claim().unwrap().digest(),
+                halt_receipt.claim().unwrap().digest(),
+            ]
+        );
+    }
+
     #[test]
     fn sys_verify_1() {
         let spec = MultiTestSpec::SysVerify(vec![(
@@ -890,3 +1149,92 @@ mod soundness {
         assert_eq!(security, 98.32892);
     }
 }
+
+#[test]
+fn estimate() {
+    let env = || {
+        ExecutorEnv::builder()
+            .write(&MultiTestSpec::BusyLoop { cycles: 1 << 15 })
+            .unwrap()
+            .segment_limit_po2(14) // 16k cycles
+            .build()
+            .unwrap()
+    };
+    let session = ExecutorImpl::from_elf(env(), MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+
+    let composite = super::estimate::estimate(&session, &ProverOpts::composite()).unwrap();
+    assert_eq!(composite.segments, session.segments.len());
+    assert_eq!(composite.total_cycles, session.total_cycles);
+    assert_eq!(composite.hals[0].hal, super::estimate::HalKind::Cpu);
+    assert!(!composite.hals[0].calibrated);
+
+    // A dry run predicts the same cost as a session.
+    let dry_run =
+        super::estimate::estimate_elf(env(), MULTI_TEST_ELF, &ProverOpts::composite()).unwrap();
+    assert_eq!(dry_run.segments, composite.segments);
+    assert_eq!(dry_run.hals[0].prover_time, composite.hals[0].prover_time);
+
+    // Compressing the receipt costs more than leaving it composite.
+    let succinct = super::estimate::estimate(&session, &ProverOpts::succinct()).unwrap();
+    assert!(succinct.hals[0].prover_time > composite.hals[0].prover_time);
+}
+
+#[test]
+fn estimate_with_benchmark_calibrations() {
+    use super::estimate::{estimate_with_calibrations, Calibration, HalKind};
+
+    let report = br#"{
+        "version": "1.0.0",
+        "hashfn": "poseidon2",
+        "results": [
+            {"workload": "loop", "hal": "cpu", "po2": 14, "segments": 1, "user_cycles": 16000,
+             "total_cycles": 16384, "execute_secs": 0.1, "prove_secs": 5.0,
+             "cycles_per_sec": 3276.8, "peak_memory": 100000000, "seal_size": 200000},
+            {"workload": "loop", "hal": "cpu", "po2": 15, "segments": 2, "user_cycles": 64000,
+             "total_cycles": 65536, "execute_secs": 0.2, "prove_secs": 16.0,
+             "cycles_per_sec": 4096.0, "peak_memory": 335544320, "seal_size": 200000},
+            {"workload": "paging", "hal": "cpu", "po2": 16, "segments": 1, "user_cycles": 60000,
+             "total_cycles": 65536, "execute_secs": 0.2, "prove_secs": 1.0,
+             "cycles_per_sec": 65536.0, "peak_memory": 1, "seal_size": 200000}
+        ]
+    }"#;
+    let calibrations = Calibration::from_benchmark(report).unwrap();
+    assert_eq!(calibrations.len(), 1);
+    let cpu = &calibrations[0];
+    assert_eq!(cpu.hal, HalKind::Cpu);
+    assert_eq!(cpu.po2, 15);
+    assert_eq!(cpu.segment_time, Duration::from_secs(8));
+    assert_eq!(cpu.bytes_per_cycle, 10240);
+    assert!(cpu.measured);
+    assert!(Calibration::from_benchmark(br#"{"results": []}"#).is_err());
+    let out_of_range = br#"{"results": [{"workload": "loop", "hal": "cpu", "po2": 0,
+        "segments": 1, "prove_secs": 1.0, "peak_memory": 1}]}"#;
+    assert!(Calibration::from_benchmark(out_of_range).is_err());
+
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::BusyLoop { cycles: 1 << 15 })
+        .unwrap()
+        .segment_limit_po2(15)
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    let composite =
+        estimate_with_calibrations(&session, &ProverOpts::composite(), &calibrations).unwrap();
+    assert_eq!(composite.hals.len(), 1);
+    assert!(composite.hals[0].prover_time >= Duration::from_secs(8));
+    assert_eq!(composite.hals[0].peak_memory, 10240 << 15);
+    assert!(composite.hals[0].calibrated);
+
+    // A calibration made by hand is checked like one read from a report.
+    let uncheckable = [Calibration {
+        po2: 0,
+        ..cpu.clone()
+    }];
+    assert!(estimate_with_calibrations(&session, &ProverOpts::composite(), &uncheckable).is_err());
+}
@@ -15,17 +15,18 @@
 //! This module defines [Session] and [Segment] which provides a way to share
 //! execution traces between the execution phase and the proving phase.
 
-use std::{collections::BTreeSet, fs, path::PathBuf};
+use std::{collections::BTreeSet, fs, path::PathBuf, sync::Arc};
 
 use anyhow::{ensure, Result};
 use risc0_binfmt::{MemoryImage, SystemState};
-use risc0_circuit_rv32im::prove::segment::Segment as CircuitSegment;
+use risc0_circuit_rv32im::prove::{emu::trap::Trap, segment::Segment as CircuitSegment};
 use serde::{Deserialize, Serialize};
 
 use crate::{
     host::{client::env::SegmentPath, prove_info::SessionStats},
-    sha::Digest,
-    Assumption, Assumptions, ExitCode, Journal, Output, ReceiptClaim,
+    sha::{Digest, Digestible},
+    Assumption, Assumptions, Channels, ExitCode, Journal, MaybePruned, Output, ReceiptClaim,
+    ReceiptStore,
 };
 
 #[derive(Clone, Default, Serialize, Deserialize, Debug)]
@@ -43,7 +44,8 @@ pub struct PageFaults {
 pub struct Session {
     /// The constituent [Segment]s of the Session. The final [Segment] will have
     /// an [ExitCode] of [Halted](ExitCode::Halted), [Paused](ExitCode::Paused),
-    /// or [SessionLimit](ExitCode::SessionLimit), and all other [Segment]s (if
+    /// or [SessionLimit](ExitCode::SessionLimit), or [ExitCode::SystemSplit]
+    /// for a session ended by a [Session::fault], and all other [Segment]s (if
     /// any) will have [ExitCode::SystemSplit].
     pub segments: Vec<Box<dyn SegmentRef>>,
 
@@ -62,9 +64,20 @@ pub struct Session {
     /// The list of assumptions made by the guest and resolved by the host.
     pub assumptions: Vec<Assumption>,
 
+    /// The [Session]s of other guest programs run by the guest using
+    /// `env::call`. Each one backs an unresolved assumption in
+    /// [Session::assumptions] and is proven along with this [Session].
+    pub calls: Vec<Session>,
+
+    /// The data committed by the guest to each named output channel.
+    pub channels: Channels,
+
     /// The hooks to be called during the proving phase.
     pub hooks: Vec<Box<dyn SessionEvents>>,
 
+    /// Where the prover stores segment receipts, if anywhere.
+    pub receipt_store: Option<Arc<dyn ReceiptStore>>,
+
     /// The number of user cycles without any overhead for continuations or po2
     /// padding.
     pub user_cycles: u64,
@@ -78,6 +91,13 @@ pub struct Session {
 
     /// The system state of the final [MemoryImage] at the end of execution.
     pub post_state: SystemState,
+
+    /// The fault that ended the session, for a session run with
+    /// [ExecutorEnvBuilder::split_on_fault](crate::ExecutorEnvBuilder::split_on_fault).
+    /// Such a session ends with [ExitCode::SystemSplit] at the faulting
+    /// instruction, and [ProverServer::prove_fault](crate::ProverServer::prove_fault)
+    /// proves the fault.
+    pub fault: Option<Trap>,
 }
 
 /// The execution trace of a portion of a program.
@@ -149,11 +169,15 @@ impl Session {
             exit_code,
             post_image,
             assumptions,
+            calls: Vec::new(),
+            channels: Channels::default(),
             hooks: Vec::new(),
+            receipt_store: None,
             user_cycles,
             total_cycles,
             pre_state,
             post_state,
+            fault: None,
         }
     }
 
@@ -180,11 +204,16 @@ impl Session {
                                 .iter()
                                 .filter_map(|a| match a {
                                     Assumption::Proven(_) => None,
+                                    // Assumptions backed by a call are proven with this session.
+                                    Assumption::Unresolved(r) if self.find_call(r).is_some() => {
+                                        None
+                                    }
                                     Assumption::Unresolved(r) => Some(r.clone()),
                                 })
                                 .collect::<Vec<_>>(),
                         )
                         .into(),
+                        channels: self.channels.clone().into(),
                     })
                 })
                 .transpose()?
@@ -211,6 +240,14 @@ impl Session {
         })
     }
 
+    /// Find the call [Session] whose claim matches the given assumption, if any.
+    pub(crate) fn find_call(&self, claim: &MaybePruned<ReceiptClaim>) -> Option<&Session> {
+        let digest = claim.digest();
+        self.calls
+            .iter()
+            .find(|call| matches!(call.claim(), Ok(call_claim) if call_claim.digest() == digest))
+    }
+
     /// Log cycle information for this [Session].
     ///
     /// This logs the total and user cycles for this [Session] at the INFO level.
@@ -231,6 +268,8 @@ impl Session {
             segments: self.segments.len(),
             total_cycles: self.total_cycles,
             user_cycles: self.user_cycles,
+            usage: None,
+            cost: None,
         }
     }
 }
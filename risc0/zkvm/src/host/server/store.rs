@@ -0,0 +1,260 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pluggable storage for [Segment]s and [SegmentReceipt]s.
+//!
+//! Objects are content-addressed: `put` returns the SHA-256 digest of the
+//! serialized object, which is then used to fetch it again, and `get` checks
+//! that the object it fetches still hashes to that digest. Implement
+//! [SegmentStore] or [ReceiptStore] to persist objects somewhere other than the
+//! local filesystem, such as an object store or a database. With the
+//! `object-store` feature, [BucketStore] keeps them in an S3 or GCS bucket.
+
+#[cfg(feature = "object-store")]
+mod bucket;
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Write as _,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{anyhow, ensure, Context as _, Result};
+use hex::FromHex as _;
+
+use crate::{
+    receipt::SegmentReceipt,
+    sha::{Digest, Impl, Sha256},
+    Segment, SegmentRef,
+};
+
+#[cfg(feature = "object-store")]
+pub use self::bucket::BucketStore;
+
+const SEGMENTS_DIR: &str = "segments";
+const RECEIPTS_DIR: &str = "receipts";
+
+/// A store for [Segment]s produced by the executor.
+pub trait SegmentStore: Send + Sync {
+    /// Store a [Segment], returning its digest.
+    fn put_segment(&self, segment: &Segment) -> Result<Digest>;
+
+    /// Fetch the [Segment] with the given digest.
+    fn get_segment(&self, digest: &Digest) -> Result<Segment>;
+
+    /// List the digests of all stored [Segment]s.
+    fn list_segments(&self) -> Result<Vec<Digest>>;
+}
+
+/// A store for [SegmentReceipt]s produced by the prover.
+pub trait ReceiptStore: Send + Sync {
+    /// Store a [SegmentReceipt], returning its digest.
+    fn put_receipt(&self, receipt: &SegmentReceipt) -> Result<Digest>;
+
+    /// Fetch the [SegmentReceipt] with the given digest.
+    fn get_receipt(&self, digest: &Digest) -> Result<SegmentReceipt>;
+
+    /// List the digests of all stored [SegmentReceipt]s.
+    fn list_receipts(&self) -> Result<Vec<Digest>>;
+}
+
+/// A [SegmentRef] to a [Segment] held in a [SegmentStore].
+pub struct StoreSegmentRef {
+    store: Arc<dyn SegmentStore>,
+    digest: Digest,
+}
+
+impl StoreSegmentRef {
+    /// Put `segment` into `store` and return a reference to it.
+    pub fn new(segment: &Segment, store: Arc<dyn SegmentStore>) -> Result<Self> {
+        let digest = store.put_segment(segment)?;
+        Ok(Self { store, digest })
+    }
+
+    /// The digest of the referenced [Segment].
+    pub fn digest(&self) -> Digest {
+        self.digest
+    }
+}
+
+impl SegmentRef for StoreSegmentRef {
+    fn resolve(&self) -> Result<Segment> {
+        self.store.get_segment(&self.digest)
+    }
+}
+
+/// A store that keeps [Segment]s and [SegmentReceipt]s in a local directory.
+///
+/// Segments and receipts are kept in the `segments` and `receipts`
+/// subdirectories respectively, with each object in a file named by its
+/// digest.
+pub struct LocalDirStore {
+    dir: PathBuf,
+}
+
+impl LocalDirStore {
+    /// Construct a [LocalDirStore] rooted at `dir`, creating it if needed.
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(dir.join(SEGMENTS_DIR))?;
+        fs::create_dir_all(dir.join(RECEIPTS_DIR))?;
+        Ok(Self { dir })
+    }
+
+    fn put(&self, kind: &str, contents: Vec<u8>) -> Result<Digest> {
+        let digest = *Impl::hash_bytes(&contents);
+        write_atomic(&self.path(kind, &digest), &contents)?;
+        Ok(digest)
+    }
+
+    fn get(&self, kind: &str, digest: &Digest) -> Result<Vec<u8>> {
+        let path = self.path(kind, digest);
+        let contents =
+            fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+        check_digest(digest, &contents)?;
+        Ok(contents)
+    }
+
+    fn list(&self, kind: &str) -> Result<Vec<Digest>> {
+        let mut digests = Vec::new();
+        for entry in fs::read_dir(self.dir.join(kind))? {
+            let name = entry?.file_name();
+            let Some(digest) = name.to_str().and_then(|x| Digest::from_hex(x).ok()) else {
+                continue;
+            };
+            digests.push(digest);
+        }
+        digests.sort();
+        Ok(digests)
+    }
+
+    fn path(&self, kind: &str, digest: &Digest) -> PathBuf {
+        self.dir.join(kind).join(hex::encode(digest))
+    }
+}
+
+impl SegmentStore for LocalDirStore {
+    fn put_segment(&self, segment: &Segment) -> Result<Digest> {
+        self.put(SEGMENTS_DIR, bincode::serialize(segment)?)
+    }
+
+    fn get_segment(&self, digest: &Digest) -> Result<Segment> {
+        Ok(bincode::deserialize(&self.get(SEGMENTS_DIR, digest)?)?)
+    }
+
+    fn list_segments(&self) -> Result<Vec<Digest>> {
+        self.list(SEGMENTS_DIR)
+    }
+}
+
+impl ReceiptStore for LocalDirStore {
+    fn put_receipt(&self, receipt: &SegmentReceipt) -> Result<Digest> {
+        self.put(RECEIPTS_DIR, bincode::serialize(receipt)?)
+    }
+
+    fn get_receipt(&self, digest: &Digest) -> Result<SegmentReceipt> {
+        Ok(bincode::deserialize(&self.get(RECEIPTS_DIR, digest)?)?)
+    }
+
+    fn list_receipts(&self) -> Result<Vec<Digest>> {
+        self.list(RECEIPTS_DIR)
+    }
+}
+
+/// Check that an object fetched by `digest` hashes to it, so that an object
+/// corrupted or replaced in the store is never returned.
+fn check_digest(digest: &Digest, contents: &[u8]) -> Result<()> {
+    ensure!(
+        *Impl::hash_bytes(contents) == *digest,
+        "object {digest} does not match its digest"
+    );
+    Ok(())
+}
+
+/// Write `contents` to `path` such that a crash leaves either the old or the
+/// new file in place, never a partial one.
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let dir = path.parent().context("path has no parent directory")?;
+    // A uniquely named temporary file in the same directory, so that concurrent
+    // writers never share one and the rename stays on one filesystem.
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    file.write_all(contents)?;
+    file.as_file().sync_all()?;
+    file.persist(path)?;
+    // The rename itself is only durable once the directory is synced.
+    #[cfg(unix)]
+    fs::File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+/// A store that keeps [Segment]s and [SegmentReceipt]s in memory.
+#[derive(Default)]
+pub struct InMemoryStore {
+    segments: Mutex<BTreeMap<Digest, Vec<u8>>>,
+    receipts: Mutex<BTreeMap<Digest, Vec<u8>>>,
+}
+
+impl InMemoryStore {
+    /// Construct an empty [InMemoryStore].
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn put(map: &Mutex<BTreeMap<Digest, Vec<u8>>>, contents: Vec<u8>) -> Digest {
+    let digest = *Impl::hash_bytes(&contents);
+    map.lock().unwrap().insert(digest, contents);
+    digest
+}
+
+fn get(map: &Mutex<BTreeMap<Digest, Vec<u8>>>, digest: &Digest) -> Result<Vec<u8>> {
+    let contents = map
+        .lock()
+        .unwrap()
+        .get(digest)
+        .cloned()
+        .ok_or_else(|| anyhow!("no object with digest {digest}"))?;
+    check_digest(digest, &contents)?;
+    Ok(contents)
+}
+
+impl SegmentStore for InMemoryStore {
+    fn put_segment(&self, segment: &Segment) -> Result<Digest> {
+        Ok(put(&self.segments, bincode::serialize(segment)?))
+    }
+
+    fn get_segment(&self, digest: &Digest) -> Result<Segment> {
+        Ok(bincode::deserialize(&get(&self.segments, digest)?)?)
+    }
+
+    fn list_segments(&self) -> Result<Vec<Digest>> {
+        Ok(self.segments.lock().unwrap().keys().copied().collect())
+    }
+}
+
+impl ReceiptStore for InMemoryStore {
+    fn put_receipt(&self, receipt: &SegmentReceipt) -> Result<Digest> {
+        Ok(put(&self.receipts, bincode::serialize(receipt)?))
+    }
+
+    fn get_receipt(&self, digest: &Digest) -> Result<SegmentReceipt> {
+        Ok(bincode::deserialize(&get(&self.receipts, digest)?)?)
+    }
+
+    fn list_receipts(&self) -> Result<Vec<Digest>> {
+        Ok(self.receipts.lock().unwrap().keys().copied().collect())
+    }
+}
@@ -0,0 +1,288 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::BTreeSet,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context as _, Result};
+use futures::TryStreamExt as _;
+use hex::FromHex as _;
+use object_store::{
+    aws::AmazonS3Builder, gcp::GoogleCloudStorageBuilder, path::Path, ObjectMeta, ObjectStore,
+};
+use tokio::runtime::Runtime;
+
+use super::{check_digest, ReceiptStore, SegmentStore, RECEIPTS_DIR, SEGMENTS_DIR};
+use crate::{
+    receipt::SegmentReceipt,
+    sha::{Digest, Impl, Sha256},
+    Segment,
+};
+
+/// A store that keeps [Segment]s and [SegmentReceipt]s in an object store
+/// bucket, such as Amazon S3, Google Cloud Storage, or a service compatible
+/// with either.
+///
+/// Segments and receipts are addressed by the digest of their serialized form,
+/// as in [LocalDirStore](super::LocalDirStore), and are checked against it on
+/// every fetch, since the bucket is not trusted.
+///
+/// The store makes blocking calls on its own runtime, and so must not be used
+/// from within an async context.
+pub struct BucketStore {
+    store: Arc<dyn ObjectStore>,
+    prefix: Path,
+    runtime: Runtime,
+}
+
+impl BucketStore {
+    /// Construct a [BucketStore] that keeps its objects under `prefix` in
+    /// `store`.
+    pub fn new(store: Arc<dyn ObjectStore>, prefix: impl Into<Path>) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Ok(Self {
+            store,
+            prefix: prefix.into(),
+            runtime,
+        })
+    }
+
+    /// Construct a [BucketStore] from a URL such as `s3://bucket/prefix` or
+    /// `gs://bucket/prefix`.
+    ///
+    /// Credentials, regions and endpoints are read from the environment, as by
+    /// the `aws` and `gcloud` command line tools. Set `AWS_ENDPOINT` to use a
+    /// service compatible with S3.
+    pub fn from_url(url: &str) -> Result<Self> {
+        let (scheme, path) = url
+            .split_once("://")
+            .with_context(|| format!("not a bucket URL: {url}"))?;
+        let prefix = path.split_once('/').map_or("", |(_, prefix)| prefix);
+        let store: Arc<dyn ObjectStore> = match scheme {
+            "s3" | "s3a" => Arc::new(AmazonS3Builder::from_env().with_url(url).build()?),
+            "gs" => Arc::new(
+                GoogleCloudStorageBuilder::from_env()
+                    .with_url(url)
+                    .build()?,
+            ),
+            _ => bail!("unsupported bucket URL scheme: {scheme}"),
+        };
+        Self::new(store, Path::parse(prefix)?)
+    }
+
+    /// Delete the [Segment] with the given digest.
+    pub fn delete_segment(&self, digest: &Digest) -> Result<()> {
+        self.delete(SEGMENTS_DIR, digest)
+    }
+
+    /// Delete the [SegmentReceipt] with the given digest.
+    pub fn delete_receipt(&self, digest: &Digest) -> Result<()> {
+        self.delete(RECEIPTS_DIR, digest)
+    }
+
+    /// Delete the [Segment]s that are not in `live` and were stored at least
+    /// `min_age` ago, returning their digests.
+    ///
+    /// Segments are left behind by sessions that are abandoned before they are
+    /// proven. `min_age` protects the segments of sessions that are still
+    /// running, whose digests the caller does not know yet.
+    pub fn collect_segments(
+        &self,
+        live: &BTreeSet<Digest>,
+        min_age: Duration,
+    ) -> Result<Vec<Digest>> {
+        let cutoff = SystemTime::now()
+            .duration_since(UNIX_EPOCH)?
+            .saturating_sub(min_age);
+        let mut deleted = Vec::new();
+        for (digest, meta) in self.list(SEGMENTS_DIR)? {
+            if live.contains(&digest) || meta.last_modified.timestamp() > cutoff.as_secs() as i64 {
+                continue;
+            }
+            self.delete_segment(&digest)?;
+            deleted.push(digest);
+        }
+        Ok(deleted)
+    }
+
+    fn put(&self, kind: &str, contents: Vec<u8>) -> Result<Digest> {
+        let digest = *Impl::hash_bytes(&contents);
+        let path = self.path(kind, &digest);
+        self.runtime
+            .block_on(self.store.put(&path, contents.into()))
+            .with_context(|| format!("failed to write {path}"))?;
+        Ok(digest)
+    }
+
+    fn get(&self, kind: &str, digest: &Digest) -> Result<Vec<u8>> {
+        let path = self.path(kind, digest);
+        let bytes = self
+            .runtime
+            .block_on(async { self.store.get(&path).await?.bytes().await })
+            .with_context(|| format!("failed to read {path}"))?;
+        check_digest(digest, &bytes)?;
+        Ok(bytes.to_vec())
+    }
+
+    fn delete(&self, kind: &str, digest: &Digest) -> Result<()> {
+        let path = self.path(kind, digest);
+        self.runtime
+            .block_on(self.store.delete(&path))
+            .with_context(|| format!("failed to delete {path}"))
+    }
+
+    fn list(&self, kind: &str) -> Result<Vec<(Digest, ObjectMeta)>> {
+        let objects: Vec<ObjectMeta> = self.runtime.block_on(
+            self.store
+                .list(Some(&self.prefix.child(kind)))
+                .try_collect(),
+        )?;
+        let mut digests: Vec<_> = objects
+            .into_iter()
+            .filter_map(|meta| {
+                let digest = Digest::from_hex(meta.location.filename()?).ok()?;
+                Some((digest, meta))
+            })
+            .collect();
+        digests.sort_by_key(|(digest, _)| *digest);
+        Ok(digests)
+    }
+
+    fn path(&self, kind: &str, digest: &Digest) -> Path {
+        self.prefix.child(kind).child(hex::encode(digest))
+    }
+}
+
+impl SegmentStore for BucketStore {
+    fn put_segment(&self, segment: &Segment) -> Result<Digest> {
+        self.put(SEGMENTS_DIR, bincode::serialize(segment)?)
+    }
+
+    fn get_segment(&self, digest: &Digest) -> Result<Segment> {
+        Ok(bincode::deserialize(&self.get(SEGMENTS_DIR, digest)?)?)
+    }
+
+    fn list_segments(&self) -> Result<Vec<Digest>> {
+        Ok(self
+            .list(SEGMENTS_DIR)?
+            .into_iter()
+            .map(|(digest, _)| digest)
+            .collect())
+    }
+}
+
+impl ReceiptStore for BucketStore {
+    fn put_receipt(&self, receipt: &SegmentReceipt) -> Result<Digest> {
+        self.put(RECEIPTS_DIR, bincode::serialize(receipt)?)
+    }
+
+    fn get_receipt(&self, digest: &Digest) -> Result<SegmentReceipt> {
+        Ok(bincode::deserialize(&self.get(RECEIPTS_DIR, digest)?)?)
+    }
+
+    fn list_receipts(&self) -> Result<Vec<Digest>> {
+        Ok(self
+            .list(RECEIPTS_DIR)?
+            .into_iter()
+            .map(|(digest, _)| digest)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeSet, sync::Arc, time::Duration};
+
+    use object_store::{memory::InMemory, path::Path, ObjectStore};
+    use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF};
+
+    use super::BucketStore;
+    use crate::{
+        receipt::fake_claim,
+        sha::{Impl, Sha256},
+        ExecutorEnv, ExecutorImpl, ReceiptStore, SegmentReceipt, SegmentStore, Session,
+    };
+
+    fn run(store: Arc<BucketStore>) -> Session {
+        let env = ExecutorEnv::builder()
+            .write(&MultiTestSpec::BusyLoop { cycles: 1 << 15 })
+            .unwrap()
+            .segment_limit_po2(14) // 16k cycles
+            .segment_store(store)
+            .build()
+            .unwrap();
+        ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+            .unwrap()
+            .run()
+            .unwrap()
+    }
+
+    #[test]
+    fn segments() {
+        let objects = Arc::new(InMemory::new());
+        let store = Arc::new(BucketStore::new(objects.clone(), "jobs").unwrap());
+        let session = run(store.clone());
+        let digests = store.list_segments().unwrap();
+        assert_eq!(digests.len(), session.segments.len());
+        for (idx, segment_ref) in session.segments.iter().enumerate() {
+            assert_eq!(segment_ref.resolve().unwrap().index, idx as u32);
+        }
+
+        // A segment that no longer hashes to its digest is rejected.
+        let path = Path::from(format!("jobs/segments/{}", hex::encode(digests[0])));
+        let mut contents = store
+            .runtime
+            .block_on(async { objects.get(&path).await?.bytes().await })
+            .unwrap()
+            .to_vec();
+        contents[0] ^= 1;
+        store
+            .runtime
+            .block_on(objects.put(&path, contents.into()))
+            .unwrap();
+        assert!(store.get_segment(&digests[0]).is_err());
+
+        let live = BTreeSet::from([digests[1]]);
+        let hour = Duration::from_secs(3600);
+        assert!(store.collect_segments(&live, hour).unwrap().is_empty());
+        let deleted = store.collect_segments(&live, Duration::ZERO).unwrap();
+        assert_eq!(deleted.len(), digests.len() - 1);
+        assert!(!deleted.contains(&digests[1]));
+        assert_eq!(store.list_segments().unwrap(), vec![digests[1]]);
+    }
+
+    #[test]
+    fn receipts() {
+        let store = BucketStore::new(Arc::new(InMemory::new()), "").unwrap();
+        let receipt = SegmentReceipt {
+            seal: vec![],
+            index: 0,
+            hashfn: "poseidon2".into(),
+            claim: fake_claim(b""),
+        };
+        let digest = store.put_receipt(&receipt).unwrap();
+        let contents = bincode::serialize(&receipt).unwrap();
+        assert_eq!(digest, *Impl::hash_bytes(&contents));
+        assert_eq!(store.list_receipts().unwrap(), vec![digest]);
+        assert_eq!(store.get_receipt(&digest).unwrap().claim, receipt.claim);
+
+        store.delete_receipt(&digest).unwrap();
+        assert!(store.list_receipts().unwrap().is_empty());
+    }
+}
@@ -48,14 +48,18 @@
 //! Note that in order to use `risc0-zkvm` in the guest, you must disable the
 //! "prove" feature by setting `default-features = false`.
 //!
-//! | Feature          | Target(s)         | Implies    | Description                                                                                                                                                  |
-//! | ---------------- | ----------------- | ---------- | ------------------------------------------------------------------------------------------------------------------------------------------------------------ |
-//! | client           | all except rv32im | std        | Enables the client API.                                                                                                                                      |
-//! | cuda             |                   | prove, std | Enables CUDA GPU acceleration for the prover. Requires CUDA toolkit to be installed.                                                                         |
-//! | disable-dev-mode | all except rv32im |            | Disables dev mode so that proving and verifying may not be faked. Used to prevent a misplaced `RISC0_DEV_MODE` from breaking security in production systems. |
-//! | metal            | macos             | prove, std | Enables Metal GPU acceleration for the prover.                                                                                                               |
-//! | prove            | all except rv32im | std        | Enables the prover, incompatible within the zkvm guest.                                                                                                      |
-//! | std              | all               |            | Support for the Rust stdlib.                                                                                                                                 |
+//! | Feature          | Target(s)         | Implies         | Description                                                                                                                                                  |
+//! | ---------------- | ----------------- | --------------- | ------------------------------------------------------------------------------------------------------------------------------------------------------------ |
+//! | client           | all except rv32im | std             | Enables the client API.                                                                                                                                      |
+//! | cuda             |                   | prove, std      | Enables CUDA GPU acceleration for the prover. Requires CUDA toolkit to be installed.                                                                         |
+//! | disable-dev-mode | all except rv32im |                 | Disables dev mode so that proving and verifying may not be faked. Used to prevent a misplaced `RISC0_DEV_MODE` from breaking security in production systems. |
+//! | execute          | all except rv32im | std             | Enables the executor without the prover, for hosts that only execute guests.                                                                                 |
+//! | heap-linked-list | rv32im            |                 | Uses a guest heap allocator that reuses freed memory, instead of the default bump allocator.                                                                 |
+//! | metal            | macos             | prove, std      | Enables Metal GPU acceleration for the prover.                                                                                                               |
+//! | metrics          | all except rv32im | execute         | Reports executor and prover metrics through the `metrics` crate facade.                                                                                      |
+//! | otel             | all except rv32im | client          | Propagates OpenTelemetry trace context through remote proving requests.                                                                                      |
+//! | prove            | all except rv32im | client, execute | Enables the prover, incompatible within the zkvm guest.                                                                                                      |
+//! | std              | all               |                 | Support for the Rust stdlib.                                                                                                                                 |
 //!
 //! [`cargo risczero` tool]: https://crates.io/crates/cargo-risczero
 //! [dev-docs]: https://dev.risczero.com
@@ -71,13 +75,17 @@
 
 extern crate alloc;
 
+mod disclosure;
+mod fault;
 pub mod guest;
 #[cfg(not(target_os = "zkvm"))]
 mod host;
+mod merkle_file;
 mod receipt;
 mod receipt_claim;
 pub mod serde;
 pub mod sha;
+mod timestamp;
 
 /// Re-exports for recursion
 #[cfg(all(not(target_os = "zkvm"), feature = "prove"))]
@@ -85,67 +93,125 @@ pub mod recursion {
     pub use super::host::recursion::*;
 }
 
+/// Fuzzing of guest programs, for use with `cargo fuzz`.
+#[cfg(all(not(target_os = "zkvm"), feature = "execute"))]
+pub mod fuzz {
+    pub use super::host::server::fuzz::*;
+}
+
+/// Export of segment traces, for proving with other STARK frameworks.
+#[cfg(all(not(target_os = "zkvm"), feature = "execute"))]
+pub mod export {
+    pub use super::host::server::export::*;
+}
+
 pub use anyhow::Result;
 #[cfg(not(target_os = "zkvm"))]
-#[cfg(any(feature = "client", feature = "prove"))]
+#[cfg(any(feature = "client", feature = "execute"))]
 pub use bytes::Bytes;
 pub use risc0_binfmt::{ExitCode, InvalidExitCodeError, SystemState};
 pub use risc0_zkvm_platform::{align_up, declare_syscall, memory::GUEST_MAX_MEM, PAGE_SIZE};
 
-pub use self::receipt_claim::{Assumptions, MaybePruned, Output, PrunedValueError, ReceiptClaim};
+#[cfg(all(not(target_os = "zkvm"), feature = "otel"))]
+pub use self::host::otel::{current_trace_context, set_trace_parent, TraceContext};
+#[cfg(all(not(target_os = "zkvm"), feature = "object-store"))]
+pub use self::host::server::store::BucketStore;
+#[cfg(all(not(target_os = "zkvm"), feature = "client"))]
+pub use self::host::{
+    api::{client::Client as ApiClient, Asset, AssetRequest, Connector},
+    client::prove::{
+        bonsai::BonsaiProver,
+        default_prover,
+        external::ExternalProver,
+        remote::{register_remote_prover, RemoteProver, RemoteProverAdapter, RemoteStatus},
+    },
+};
+pub use self::{
+    disclosure::{DisclosedChunk, Disclosure, DisclosureTree},
+    fault::{GuestOutOfMemory, GuestPanic},
+    merkle_file::{merkle_file_root, MERKLE_FILE_CHUNK_SIZE},
+    receipt_claim::{
+        Assumptions, Channels, ClaimSummary, MaybePruned, Output, PrunedValueError, ReceiptClaim,
+    },
+    timestamp::HostTimestamp,
+};
+#[cfg(all(not(target_os = "zkvm"), any(feature = "client", feature = "execute")))]
+pub use {
+    self::host::client::{
+        env::{ExecutorEnv, ExecutorEnvBuilder},
+        prove::{default_executor, CompressionPolicy, Executor, Prover, ProverOpts, ReceiptKind},
+    },
+    risc0_circuit_rv32im::trace::{TraceCallback, TraceEvent},
+};
 #[cfg(all(not(target_os = "zkvm"), feature = "prove",))]
 pub use {
     self::host::{
         api::server::Server as ApiServer,
-        client::prove::local::LocalProver,
-        recursion::RECURSION_PO2,
-        server::{
-            exec::executor::ExecutorImpl,
-            prove::{get_prover_server, HalPair, ProverServer},
-            session::{
-                FileSegmentRef, NullSegmentRef, Segment, SegmentRef, Session, SessionEvents,
-                SimpleSegmentRef,
+        recursion::{ResolveChannelsError, RECURSION_PO2},
+        server::prove::{
+            available_hals,
+            estimate::{
+                estimate, estimate_elf, estimate_elf_with_calibrations, estimate_with_calibrations,
+                Calibration, CostEstimate, HalEstimate, HalKind,
             },
+            get_prover_server, get_prover_server_with_hal,
+            job::ProvingJob,
+            pipeline::prove_pipelined,
+            plan::{CompressionPlan, JoinTree},
+            HalPair, ProverServer,
         },
     },
     risc0_circuit_rv32im::prove::engine::loader::Loader,
     risc0_groth16::{
-        docker::stark_to_snark, to_json as seal_to_json, ProofJson as Groth16ProofJson,
+        stark_to_plonk, stark_to_snark, to_json as seal_to_json, ProofJson as Groth16ProofJson,
     },
 };
-#[cfg(all(not(target_os = "zkvm"), feature = "client"))]
+#[cfg(all(not(target_os = "zkvm"), feature = "execute"))]
 pub use {
     self::host::{
-        api::{
-            client::Client as ApiClient, Asset, AssetRequest, Connector, SegmentInfo, SessionInfo,
-        },
-        client::{
-            env::{ExecutorEnv, ExecutorEnvBuilder},
-            prove::{
-                bonsai::BonsaiProver, default_executor, default_prover, external::ExternalProver,
-                Executor, Prover, ProverOpts, ReceiptKind,
+        client::prove::local::LocalProver,
+        server::{
+            exec::{
+                executor::ExecutorImpl,
+                syscall::MAX_CALL_DEPTH,
+                time_travel::{MemoryWrite, TimeTravel},
+            },
+            fault::FaultReceipt,
+            session::{
+                FileSegmentRef, NullSegmentRef, Segment, SegmentRef, Session, SessionEvents,
+                SimpleSegmentRef,
             },
+            store::{InMemoryStore, LocalDirStore, ReceiptStore, SegmentStore, StoreSegmentRef},
         },
     },
-    risc0_circuit_rv32im::trace::{TraceCallback, TraceEvent},
+    risc0_circuit_rv32im::prove::emu::{exec::NoProgressError, rv32im::TrapCause, trap::Trap},
 };
 #[cfg(not(target_os = "zkvm"))]
 pub use {
     self::host::{
-        prove_info::{ProveInfo, SessionStats},
+        cost::{ProvingCost, ProvingUsage, RateCard},
+        prove_info::{ProveInfo, SegmentInfo, SessionInfo, SessionStats},
         recursion::ALLOWED_CONTROL_ROOT,
     },
     risc0_binfmt::compute_image_id,
     risc0_circuit_rv32im::control_id::POSEIDON2_CONTROL_ID,
-    risc0_groth16::Seal as Groth16Seal,
+    risc0_groth16::{
+        Seal as Groth16Seal, VerifierParameters as Groth16VerifierParameters,
+        VerifyCall as Groth16VerifyCall,
+    },
 };
 
-#[cfg(any(not(target_os = "zkvm"), feature = "std"))]
-pub use receipt::CompactReceipt;
 pub use receipt::{
-    Assumption, CompositeReceipt, InnerReceipt, Journal, Receipt, SegmentReceipt, SuccinctReceipt,
-    VerifierContext,
+    diagnose, inspect, AllowListPolicy, Assumption, AttestationError, CircuitVersion,
+    CompositeReceipt, InnerReceipt, Inspection, Journal, JournalHasher, MerkleProof,
+    ProverIdentity, Receipt, ReceiptAnnotations, Report, SealInfo, SegmentReceipt, SignatureScheme,
+    SignatureVerifier, SignedReceipt, SuccinctReceipt, VerifierContext, VerifierLimits,
+    VerifierPolicy, VersionRegistry,
 };
+#[cfg(feature = "std")]
+pub use receipt::{ChunkInfo, ReceiptStreamReader, ReceiptStreamWriter, StreamManifest};
+#[cfg(any(not(target_os = "zkvm"), feature = "std"))]
+pub use receipt::{CompactReceipt, PlonkReceipt};
 
 use semver::Version;
 
@@ -0,0 +1,201 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Merkle commitments to files that the guest reads from the host.
+//!
+//! A file is split into chunks of [MERKLE_FILE_CHUNK_SIZE] bytes, and the
+//! SHA-256 hashes of the chunks form the leaves of a binary Merkle tree. At
+//! each level an unpaired last node is carried up unchanged. The root of the
+//! file binds the root of the tree together with the length of the file.
+
+use alloc::{vec, vec::Vec};
+
+use risc0_binfmt::tagged_struct;
+
+use crate::sha::{Digest, Impl, Sha256};
+
+/// The size in bytes of the chunks that a verified file is split into.
+pub const MERKLE_FILE_CHUNK_SIZE: usize = 1024;
+
+/// Compute the root digest of `data`, as used to open it in the guest with
+/// [crate::guest::verified_file::VerifiedFile::open].
+pub fn merkle_file_root(data: &[u8]) -> Digest {
+    MerkleTree::new(data).root()
+}
+
+pub(crate) fn file_root(tree_root: &Digest, len: u64) -> Digest {
+    tagged_struct::<Impl>(
+        "risc0.MerkleFile",
+        &[*tree_root],
+        &[len as u32, (len >> 32) as u32],
+    )
+}
+
+pub(crate) fn num_chunks(len: u64) -> u64 {
+    len.div_ceil(MERKLE_FILE_CHUNK_SIZE as u64)
+}
+
+fn leaf(chunk: &[u8]) -> Digest {
+    *Impl::hash_bytes(chunk)
+}
+
+fn parent(left: &Digest, right: &Digest) -> Digest {
+    *Impl::hash_pair(left, right)
+}
+
+/// A Merkle tree over the chunks of a file.
+pub(crate) struct MerkleTree {
+    len: u64,
+    // The leaves, followed by each level up to the root.
+    levels: Vec<Vec<Digest>>,
+}
+
+impl MerkleTree {
+    pub(crate) fn new(data: &[u8]) -> Self {
+        let leaves = data.chunks(MERKLE_FILE_CHUNK_SIZE).map(leaf).collect();
+        Self::from_leaves(leaves, data.len() as u64)
+    }
+
+    /// A tree over the given leaves, for data of `len` bytes.
+    pub(crate) fn from_leaves(leaves: Vec<Digest>, len: u64) -> Self {
+        let mut levels = vec![leaves];
+        while levels.last().unwrap().len() > 1 {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => parent(left, right),
+                    [node] => *node,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        Self { len, levels }
+    }
+
+    /// The root of the tree, or zero for an empty file.
+    pub(crate) fn tree_root(&self) -> Digest {
+        self.levels
+            .last()
+            .and_then(|level| level.first())
+            .copied()
+            .unwrap_or(Digest::ZERO)
+    }
+
+    pub(crate) fn root(&self) -> Digest {
+        file_root(&self.tree_root(), self.len)
+    }
+
+    /// The siblings on the path from the leaf at `index` to the root.
+    pub(crate) fn proof(&self, index: usize) -> Vec<Digest> {
+        let mut proof = Vec::new();
+        let mut index = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+        proof
+    }
+}
+
+/// Check that `chunk` is at `index` in a tree of `num_chunks` leaves with the
+/// given root.
+pub(crate) fn verify_chunk(
+    tree_root: &Digest,
+    num_chunks: u64,
+    index: u64,
+    chunk: &[u8],
+    proof: &[Digest],
+) -> bool {
+    path_root(num_chunks, index, leaf(chunk), proof) == Some(*tree_root)
+}
+
+/// The root of a tree of `num_leaves` leaves with `leaf` at `index`, computed
+/// from the siblings on its path, or `None` if the path is not of that length.
+pub(crate) fn path_root(
+    num_leaves: u64,
+    index: u64,
+    leaf: Digest,
+    proof: &[Digest],
+) -> Option<Digest> {
+    let mut node = leaf;
+    let mut index = index;
+    let mut width = num_leaves;
+    let mut proof = proof.iter();
+    while width > 1 {
+        if index ^ 1 < width {
+            let sibling = proof.next()?;
+            node = match index & 1 {
+                0 => parent(&node, sibling),
+                _ => parent(sibling, &node),
+            };
+        }
+        index /= 2;
+        width = width.div_ceil(2);
+    }
+    proof.next().is_none().then_some(node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 7 + i / 251) as u8).collect()
+    }
+
+    #[test]
+    fn proofs_verify() {
+        for num_chunks in [1, 2, 3, 5, 8, 11] {
+            let data = data(num_chunks * MERKLE_FILE_CHUNK_SIZE - 100);
+            let tree = MerkleTree::new(&data);
+            for (index, chunk) in data.chunks(MERKLE_FILE_CHUNK_SIZE).enumerate() {
+                let proof = tree.proof(index);
+                assert!(verify_chunk(
+                    &tree.tree_root(),
+                    num_chunks as u64,
+                    index as u64,
+                    chunk,
+                    &proof
+                ));
+
+                let mut tampered = chunk.to_vec();
+                tampered[0] ^= 1;
+                assert!(!verify_chunk(
+                    &tree.tree_root(),
+                    num_chunks as u64,
+                    index as u64,
+                    &tampered,
+                    &proof
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn root_binds_length() {
+        let data = data(2 * MERKLE_FILE_CHUNK_SIZE);
+        let tree = MerkleTree::new(&data);
+        assert_eq!(tree.root(), file_root(&tree.tree_root(), data.len() as u64));
+        assert_ne!(
+            tree.root(),
+            file_root(&tree.tree_root(), data.len() as u64 + 1)
+        );
+        assert_ne!(merkle_file_root(&[]), merkle_file_root(&[0]));
+    }
+}
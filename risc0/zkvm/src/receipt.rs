@@ -14,13 +14,31 @@
 
 //! Manages the output and cryptographic data for a proven computation.
 
+pub(crate) mod annotations;
+pub(crate) mod attestation;
 #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
 pub(crate) mod compact;
 pub(crate) mod composite;
+pub(crate) mod diagnose;
+pub(crate) mod inspect;
+pub(crate) mod limits;
+pub(crate) mod merkle;
+#[cfg(any(not(target_os = "zkvm"), feature = "std"))]
+pub(crate) mod plonk;
+pub(crate) mod policy;
 pub(crate) mod segment;
+#[cfg(feature = "std")]
+pub(crate) mod stream;
 pub(crate) mod succinct;
-
-use alloc::{collections::BTreeMap, string::String, vec, vec::Vec};
+pub(crate) mod versions;
+
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    string::String,
+    vec,
+    vec::Vec,
+};
 use core::fmt::Debug;
 
 use anyhow::Result;
@@ -35,18 +53,35 @@ use risc0_zkp::{
     },
     verify::VerificationError,
 };
+use risc0_zkvm_platform::WORD_SIZE;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::{
     serde::{from_slice, Error},
-    sha::{Digestible, Sha256},
-    Assumptions, MaybePruned, Output, ReceiptClaim,
+    sha::{self, Digestible, Sha256},
+    Assumptions, Channels, ClaimSummary, ExitCode, MaybePruned, Output, ReceiptClaim,
 };
 
+#[cfg(feature = "std")]
+pub use self::stream::{ChunkInfo, ReceiptStreamReader, ReceiptStreamWriter, StreamManifest};
 #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
-pub use self::compact::CompactReceipt;
+pub use self::{compact::CompactReceipt, plonk::PlonkReceipt};
 
-pub use self::{composite::CompositeReceipt, segment::SegmentReceipt, succinct::SuccinctReceipt};
+pub use self::{
+    annotations::ReceiptAnnotations,
+    attestation::{
+        AttestationError, ProverIdentity, SignatureScheme, SignatureVerifier, SignedReceipt,
+    },
+    composite::CompositeReceipt,
+    diagnose::{diagnose, Report},
+    inspect::{inspect, Inspection, SealInfo},
+    limits::VerifierLimits,
+    merkle::MerkleProof,
+    policy::{AllowListPolicy, VerifierPolicy},
+    segment::SegmentReceipt,
+    succinct::SuccinctReceipt,
+    versions::{CircuitVersion, VersionRegistry},
+};
 
 /// A receipt attesting to the execution of a guest program.
 ///
@@ -105,6 +140,10 @@ pub use self::{composite::CompositeReceipt, segment::SegmentReceipt, succinct::S
 /// The public outputs of the [Receipt] are contained in the [Receipt::journal].
 /// You can use [Journal::decode] to deserialize the journal as typed and
 /// structured data, or access the [Journal::bytes] directly.
+///
+/// To exchange receipts with other languages, encode them with
+/// `Receipt::to_canonical_bytes`, a versioned protobuf encoding with a
+/// published schema.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[cfg_attr(test, derive(PartialEq))]
 pub struct Receipt {
@@ -116,6 +155,13 @@ pub struct Receipt {
     /// This data is cryptographically authenticated in
     /// [Receipt::verify].
     pub journal: Journal,
+
+    /// Operational metadata about the receipt, such as the prover version.
+    ///
+    /// Unlike the journal, annotations are not authenticated, and are ignored
+    /// by verification.
+    #[serde(default)]
+    pub annotations: ReceiptAnnotations,
 }
 
 impl Receipt {
@@ -124,9 +170,16 @@ impl Receipt {
         Self {
             inner,
             journal: Journal::new(journal),
+            annotations: ReceiptAnnotations::default(),
         }
     }
 
+    /// Replace the annotations of this receipt.
+    pub fn with_annotations(mut self, annotations: ReceiptAnnotations) -> Self {
+        self.annotations = annotations;
+        self
+    }
+
     /// Verify that this receipt proves a successful execution of the zkVM from
     /// the given `image_id`.
     ///
@@ -153,43 +206,24 @@ impl Receipt {
         image_id: impl Into<Digest>,
     ) -> Result<(), VerificationError> {
         tracing::debug!("Receipt::verify_with_context");
-        self.inner.verify_integrity_with_context(ctx)?;
-
-        // NOTE: Post-state digest and input digest are unconstrained by this method.
-        let claim = self.inner.claim()?;
-        if claim.pre.digest() != image_id.into() {
-            return Err(VerificationError::ImageVerificationError);
-        }
-
-        // Check the exit code. This verification method requires execution to be
-        // successful.
-        if !claim.exit_code.is_ok() {
-            return Err(VerificationError::UnexpectedExitCode);
-        };
-
-        // Finally check the output hash in the decoded claim against the expected
-        // output.
-        let expected_output = Output {
-            journal: MaybePruned::Pruned(self.journal.digest()),
-            // It is expected that there are no (unresolved) assumptions.
-            assumptions: Assumptions(vec![]).into(),
-        };
-
-        if claim.output.digest() != expected_output.digest() {
-            let empty_output = claim.output.is_none() && self.journal.bytes.is_empty();
-            if !empty_output {
-                tracing::debug!(
-                    "journal: 0x{}, expected output digest: 0x{}, decoded output digest: 0x{}",
-                    hex::encode(&self.journal.bytes),
-                    hex::encode(expected_output.digest()),
-                    hex::encode(claim.output.digest()),
-                );
-                return Err(VerificationError::JournalDigestMismatch);
-            }
-            tracing::debug!("accepting zero digest for output of receipt with empty journal");
-        }
+        self.verify_with_journal_digest(ctx, image_id, self.journal.digest())
+    }
 
-        Ok(())
+    /// Verify that this receipt proves a successful execution of the zkVM from
+    /// the given `image_id`, committing a journal with the given digest.
+    ///
+    /// This is [Receipt::verify_with_context] for a journal that is not held in
+    /// memory: [Receipt::journal] is ignored, and may be left empty, and the
+    /// digest can be computed from the journal in chunks with a
+    /// [JournalHasher].
+    pub fn verify_with_journal_digest(
+        &self,
+        ctx: &VerifierContext,
+        image_id: impl Into<Digest>,
+        journal_digest: Digest,
+    ) -> Result<(), VerificationError> {
+        self.inner.verify_integrity_with_context(ctx)?;
+        check_claim(&self.inner.claim()?, image_id.into(), journal_digest)
     }
 
     /// Verify the integrity of this receipt, ensuring the claim and journal
@@ -220,6 +254,7 @@ impl Receipt {
             // because we don't have a enough information to open the assumptions list unless we
             // require it be empty.
             assumptions: Assumptions(vec![]).into(),
+            channels: claim.output.channels(),
         });
 
         if claim.output.digest() != expected_output.digest() {
@@ -243,6 +278,144 @@ impl Receipt {
     pub fn claim(&self) -> Result<ReceiptClaim, VerificationError> {
         self.inner.claim()
     }
+
+    /// Summarize the claim of this receipt.
+    ///
+    /// The summary is read from the claim without verifying the seal, so it should only be
+    /// trusted after the receipt is verified.
+    pub fn claim_summary(&self) -> Result<ClaimSummary, VerificationError> {
+        Ok(self.claim()?.summary())
+    }
+
+    /// The exit code of the execution proven by this receipt.
+    pub fn exit_code(&self) -> Result<ExitCode, VerificationError> {
+        Ok(self.claim()?.exit_code)
+    }
+
+    /// The image ID of the guest, which is the digest of its [SystemState](crate::SystemState) before execution.
+    pub fn image_id(&self) -> Result<Digest, VerificationError> {
+        Ok(self.claim()?.pre.digest())
+    }
+
+    /// The digest of the input to the guest.
+    pub fn input_digest(&self) -> Result<Digest, VerificationError> {
+        Ok(self.claim()?.input)
+    }
+
+    /// The digest of the output of the guest, which commits to its journal, assumptions and
+    /// channels.
+    pub fn output_digest(&self) -> Result<Digest, VerificationError> {
+        Ok(self.claim()?.output.digest())
+    }
+
+    /// The digest of the journal of this receipt.
+    pub fn journal_digest(&self) -> Digest {
+        self.journal.digest()
+    }
+
+    /// The assumptions of the claim of this receipt, which are empty for an unconditional
+    /// receipt.
+    pub fn assumptions(&self) -> Result<Assumptions, VerificationError> {
+        match self.claim()?.output {
+            MaybePruned::Value(Some(output)) => output
+                .assumptions
+                .value()
+                .map_err(|_| VerificationError::ReceiptFormatError),
+            MaybePruned::Value(None) => Ok(Assumptions::default()),
+            MaybePruned::Pruned(_) => Err(VerificationError::ReceiptFormatError),
+        }
+    }
+
+    /// Extract the named output [Channels] from the claim of this receipt.
+    ///
+    /// Like the journal, the channels are authenticated by [Receipt::verify]. A channel whose
+    /// data was pruned from the claim is listed, but [Channels::get] returns `None` for it.
+    pub fn channels(&self) -> Result<Channels, VerificationError> {
+        match self.claim()?.output.channels() {
+            MaybePruned::Value(channels) => Ok(channels),
+            MaybePruned::Pruned(_) => Err(VerificationError::ReceiptFormatError),
+        }
+    }
+
+    /// Convert this receipt into a [risc0_verifier::Receipt], to verify it with the minimal
+    /// `risc0-verifier` crate, such as in a Substrate runtime.
+    ///
+    /// Only succinct receipts, and composite receipts of a single segment with no assumptions,
+    /// can be converted.
+    pub fn to_verifier_receipt(&self) -> Result<risc0_verifier::Receipt, VerificationError> {
+        let seal = match &self.inner {
+            InnerReceipt::Succinct(receipt) => risc0_verifier::Seal::Succinct {
+                seal: receipt.seal.clone(),
+            },
+            InnerReceipt::Composite(receipt) => {
+                match (&receipt.segments[..], &receipt.assumptions[..]) {
+                    ([segment], []) => risc0_verifier::Seal::Segment {
+                        seal: segment.seal.clone(),
+                        hashfn: segment.hashfn.clone(),
+                    },
+                    _ => return Err(VerificationError::ReceiptFormatError),
+                }
+            }
+            _ => return Err(VerificationError::ReceiptFormatError),
+        };
+        let summary = self.claim_summary()?;
+        Ok(risc0_verifier::Receipt {
+            seal,
+            claim: risc0_verifier::Claim {
+                image_id: summary.image_id,
+                post_state_digest: summary.post_state_digest,
+                exit_code: summary.exit_code,
+                input: summary.input,
+                output: summary.output,
+            },
+            journal: self.journal.bytes.clone(),
+        })
+    }
+}
+
+// Check a claim from a verified seal against the expected image ID and
+// journal digest, requiring a successful exit.
+fn check_claim(
+    claim: &ReceiptClaim,
+    image_id: Digest,
+    journal_digest: Digest,
+) -> Result<(), VerificationError> {
+    // NOTE: Post-state digest and input digest are unconstrained by this method.
+    if claim.pre.digest() != image_id {
+        return Err(VerificationError::ImageVerificationError);
+    }
+
+    // Check the exit code. This verification method requires execution to be
+    // successful.
+    if !claim.exit_code.is_ok() {
+        return Err(VerificationError::UnexpectedExitCode);
+    };
+
+    // Finally check the output hash in the decoded claim against the expected
+    // output.
+    let expected_output = Output {
+        journal: MaybePruned::Pruned(journal_digest),
+        // It is expected that there are no (unresolved) assumptions.
+        assumptions: Assumptions(vec![]).into(),
+        // Channels are committed to by the claim, rather than carried alongside it.
+        channels: claim.output.channels(),
+    };
+
+    if claim.output.digest() != expected_output.digest() {
+        let empty_output = claim.output.is_none() && journal_digest == *sha::Impl::hash_bytes(&[]);
+        if !empty_output {
+            tracing::debug!(
+                "journal digest: 0x{}, expected output digest: 0x{}, decoded output digest: 0x{}",
+                hex::encode(journal_digest),
+                hex::encode(expected_output.digest()),
+                hex::encode(claim.output.digest()),
+            );
+            return Err(VerificationError::JournalDigestMismatch);
+        }
+        tracing::debug!("accepting zero digest for output of receipt with empty journal");
+    }
+
+    Ok(())
 }
 
 /// A journal is a record of all public commitments for a given proof session.
@@ -276,6 +449,64 @@ impl AsRef<[u8]> for Journal {
     }
 }
 
+/// Computes the digest of a journal from its bytes in chunks.
+///
+/// This lets a receipt be checked against a journal that is too large to hold
+/// in memory, with [Receipt::verify_with_journal_digest] or
+/// [JournalHasher::verify].
+///
+/// ```
+/// use risc0_zkvm::{sha::Digestible, Journal, JournalHasher};
+///
+/// let mut hasher = JournalHasher::new();
+/// hasher.update(b"hello ");
+/// hasher.update(b"world");
+/// assert_eq!(hasher.finalize(), Journal::new(b"hello world".to_vec()).digest());
+/// ```
+#[derive(Clone, Default)]
+pub struct JournalHasher {
+    hasher: sha2::Sha256,
+}
+
+impl JournalHasher {
+    /// Construct a hasher for an empty journal.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a chunk of the journal.
+    pub fn update(&mut self, chunk: &[u8]) {
+        sha2::Digest::update(&mut self.hasher, chunk);
+    }
+
+    /// The digest of the journal, as given by [Journal::digest].
+    pub fn finalize(self) -> Digest {
+        Digest::try_from(sha2::Digest::finalize(self.hasher).as_slice()).unwrap()
+    }
+
+    /// Verify that the receipt proves a successful execution of the zkVM from
+    /// the given `image_id`, committing the journal fed to this hasher.
+    pub fn verify(
+        self,
+        receipt: &Receipt,
+        image_id: impl Into<Digest>,
+    ) -> Result<(), VerificationError> {
+        receipt.verify_with_journal_digest(&VerifierContext::default(), image_id, self.finalize())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Write for JournalHasher {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 /// An inner receipt can take the form of a [CompositeReceipt] or a
 /// [SuccinctReceipt].
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -305,6 +536,12 @@ pub enum InnerReceipt {
         /// [ReceiptClaim] for this fake receipt.
         claim: ReceiptClaim,
     },
+
+    /// The [PlonkReceipt].
+    // NOTE: New variants must be appended, since serialized receipts identify
+    // the variant by its index.
+    #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
+    Plonk(PlonkReceipt),
 }
 
 impl InnerReceipt {
@@ -318,7 +555,9 @@ impl InnerReceipt {
         match self {
             InnerReceipt::Composite(x) => x.verify_integrity_with_context(ctx),
             #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
-            InnerReceipt::Compact(x) => x.verify_integrity(),
+            InnerReceipt::Compact(x) => x.verify_integrity_with_context(ctx),
+            #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
+            InnerReceipt::Plonk(x) => x.verify_integrity_with_context(ctx),
             InnerReceipt::Succinct(x) => x.verify_integrity_with_context(ctx),
             InnerReceipt::Fake { .. } => {
                 #[cfg(feature = "std")]
@@ -349,6 +588,16 @@ impl InnerReceipt {
         }
     }
 
+    /// Returns the [InnerReceipt::Plonk] arm.
+    #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
+    pub fn plonk(&self) -> Result<&PlonkReceipt, VerificationError> {
+        if let InnerReceipt::Plonk(x) = self {
+            Ok(x)
+        } else {
+            Err(VerificationError::ReceiptFormatError)
+        }
+    }
+
     /// Returns the [InnerReceipt::Succinct] arm.
     pub fn succinct(&self) -> Result<&SuccinctReceipt, VerificationError> {
         if let InnerReceipt::Succinct(x) = self {
@@ -358,12 +607,55 @@ impl InnerReceipt {
         }
     }
 
+    /// Return the total size of the seals in this receipt, in bytes.
+    ///
+    /// For a [CompositeReceipt], this includes the seals of its segments and assumptions. A fake
+    /// receipt has no seal.
+    pub fn seal_size(&self) -> usize {
+        match self {
+            InnerReceipt::Composite(x) => {
+                let segments: usize = x.segments.iter().map(|s| s.seal.len() * WORD_SIZE).sum();
+                let assumptions: usize = x.assumptions.iter().map(Self::seal_size).sum();
+                segments + assumptions
+            }
+            InnerReceipt::Succinct(x) => x.seal.len() * WORD_SIZE,
+            #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
+            InnerReceipt::Compact(x) => x.seal.len(),
+            #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
+            InnerReceipt::Plonk(x) => x.seal.len(),
+            InnerReceipt::Fake { .. } => 0,
+        }
+    }
+
+    /// Return the hash functions that the seals of this receipt are verified with.
+    ///
+    /// Succinct receipts are verified with `"poseidon2"`, and compact and PLONK receipts are
+    /// reported as `"groth16"` and `"plonk"`.
+    pub fn hashfns(&self) -> BTreeSet<String> {
+        match self {
+            InnerReceipt::Composite(x) => x
+                .segments
+                .iter()
+                .map(|s| s.hashfn.clone())
+                .chain(x.assumptions.iter().flat_map(Self::hashfns))
+                .collect(),
+            InnerReceipt::Succinct(_) => BTreeSet::from([String::from("poseidon2")]),
+            #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
+            InnerReceipt::Compact(_) => BTreeSet::from([String::from("groth16")]),
+            #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
+            InnerReceipt::Plonk(_) => BTreeSet::from([String::from("plonk")]),
+            InnerReceipt::Fake { .. } => BTreeSet::new(),
+        }
+    }
+
     /// Extract the [ReceiptClaim] from this receipt.
     pub fn claim(&self) -> Result<ReceiptClaim, VerificationError> {
         match self {
             InnerReceipt::Composite(ref receipt) => receipt.claim(),
             #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
             InnerReceipt::Compact(ref compact_receipt) => Ok(compact_receipt.claim.clone()),
+            #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
+            InnerReceipt::Plonk(ref plonk_receipt) => Ok(plonk_receipt.claim.clone()),
             InnerReceipt::Succinct(ref succinct_receipt) => Ok(succinct_receipt.claim.clone()),
             InnerReceipt::Fake { claim } => Ok(claim.clone()),
         }
@@ -379,6 +671,12 @@ pub enum Assumption {
     /// Upon proving, this receipt will be used as proof of the assumption that results from a call
     /// to `env::verify`, and the resulting receipt will be unconditional. As a result,
     /// [Receipt::verify] will return true and the verifier will accept the receipt.
+    ///
+    /// Any kind of receipt may be used. A [CompactReceipt], such as one taken from an on-chain
+    /// verifier, is checked natively when the [CompositeReceipt] proving the execution is
+    /// verified, so the guest does not pay for a Groth16 verifier. Such a receipt cannot be
+    /// compressed into a [SuccinctReceipt], which requires every assumption to be resolved in the
+    /// recursion circuit.
     Proven(Receipt),
 
     /// [ReceiptClaim] digest for an assumption that is not directly proven
@@ -436,6 +734,103 @@ impl From<ReceiptClaim> for Assumption {
 pub struct VerifierContext {
     /// A registry of hash functions to be used by the verification process.
     pub suites: BTreeMap<String, HashSuite<BabyBear>>,
+
+    /// The verifying key for [PlonkReceipt] verification, if any.
+    #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
+    pub plonk_verifying_key: Option<risc0_groth16::PlonkVerifyingKey>,
+
+    /// The parameters for [CompactReceipt] verification, if not those of this release.
+    #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
+    pub groth16_verifier_parameters: Option<risc0_groth16::VerifierParameters>,
+
+    /// The policy deciding which hash suites, circuits and control roots are accepted.
+    pub policy: Box<dyn VerifierPolicy>,
+
+    /// Bounds on the size and structure of the receipts that are verified.
+    pub limits: VerifierLimits,
+}
+
+impl VerifierContext {
+    /// Accept only the receipts allowed by the given policy.
+    ///
+    /// The default policy is [AllowListPolicy::current].
+    pub fn with_policy(mut self, policy: impl VerifierPolicy + 'static) -> Self {
+        self.policy = Box::new(policy);
+        self
+    }
+
+    /// Reject receipts over the given limits before verifying them.
+    ///
+    /// The default limits are [VerifierLimits::default].
+    pub fn with_limits(mut self, limits: VerifierLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Return the suite of the named hash function, if it is registered and allowed by the
+    /// policy.
+    pub(crate) fn suite(&self, hashfn: &str) -> Result<&HashSuite<BabyBear>, VerificationError> {
+        if !self.policy.allow_hash_suite(hashfn) {
+            tracing::debug!("hash suite {hashfn} is not allowed by the verifier policy");
+            return Err(VerificationError::InvalidHashSuite);
+        }
+        self.suites
+            .get(hashfn)
+            .ok_or(VerificationError::InvalidHashSuite)
+    }
+
+    /// Check that the circuit identified by `control_id` is allowed by the policy at `po2`.
+    pub(crate) fn check_control_id(
+        &self,
+        control_id: &Digest,
+        po2: u32,
+    ) -> Result<(), VerificationError> {
+        self.limits.check_po2(po2)?;
+        match self.policy.allow_control_id(control_id, po2) {
+            true => Ok(()),
+            false => Err(VerificationError::ControlVerificationError {
+                control_id: *control_id,
+            }),
+        }
+    }
+
+    /// Check that the control root is allowed by the policy.
+    pub(crate) fn check_control_root(
+        &self,
+        control_root: &Digest,
+    ) -> Result<(), VerificationError> {
+        match self.policy.allow_control_root(control_root) {
+            true => Ok(()),
+            false => Err(VerificationError::ControlVerificationError {
+                control_id: *control_root,
+            }),
+        }
+    }
+
+    /// Verify [PlonkReceipt]s with the given key.
+    ///
+    /// The key is the one computed by `snarkjs plonk setup` for the STARK-to-SNARK circuit, and
+    /// can be decoded from its JSON export with [risc0_groth16::PlonkVerifyingKeyJson].
+    #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
+    pub fn with_plonk_verifying_key(mut self, vk: risc0_groth16::PlonkVerifyingKey) -> Self {
+        self.plonk_verifying_key = Some(vk);
+        self
+    }
+
+    /// Verify [CompactReceipt]s against the given parameters instead of the compiled-in ones.
+    ///
+    /// This is for deployments that run their own Groth16 ceremony or use another version of the
+    /// circuit. The verifying key can be loaded, pinned to a digest, with
+    /// [risc0_groth16::verifier::read_verifying_key]. Its control root must also be allowed by the
+    /// [VerifierPolicy].
+    #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
+    pub fn with_groth16_verifier_parameters(
+        mut self,
+        params: risc0_groth16::VerifierParameters,
+    ) -> Self {
+        self.groth16_verifier_parameters = Some(params);
+        self
+    }
 }
 
 impl Default for VerifierContext {
@@ -446,6 +841,139 @@ impl Default for VerifierContext {
                 ("poseidon2".into(), Poseidon2HashSuite::new_suite()),
                 ("sha-256".into(), Sha256HashSuite::new_suite()),
             ]),
+            #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
+            plonk_verifying_key: None,
+            #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
+            groth16_verifier_parameters: None,
+            policy: Box::new(AllowListPolicy::current()),
+            limits: VerifierLimits::default(),
+        }
+    }
+}
+
+/// A claim for tests, of an execution of image ID `[1; 8]` that halted with exit code 0 in post
+/// state `[2; 8]` after committing the given journal.
+#[cfg(test)]
+pub(crate) fn fake_claim(journal: &[u8]) -> ReceiptClaim {
+    ReceiptClaim {
+        post: MaybePruned::Pruned(Digest::from([2; 8])),
+        ..ReceiptClaim::ok(Digest::from([1; 8]), journal.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkp::{core::digest::Digest, verify::VerificationError};
+
+    use super::{check_claim, fake_claim, JournalHasher, Receipt};
+    use crate::{
+        sha::Digestible, CompactReceipt, CompositeReceipt, ExitCode, Groth16VerifyCall,
+        InnerReceipt, Journal, PlonkReceipt, SuccinctReceipt,
+    };
+
+    #[test]
+    fn streaming_journal_digest() {
+        let journal: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+        let mut hasher = JournalHasher::new();
+        for chunk in journal.chunks(333) {
+            hasher.update(chunk);
         }
+        let journal_digest = hasher.finalize();
+        assert_eq!(journal_digest, Journal::new(journal.clone()).digest());
+
+        let image_id = Digest::from([1; 8]);
+        let claim = fake_claim(&journal);
+        check_claim(&claim, image_id, journal_digest).unwrap();
+        assert_eq!(
+            check_claim(&claim, image_id, JournalHasher::new().finalize()),
+            Err(VerificationError::JournalDigestMismatch)
+        );
+        assert_eq!(
+            check_claim(&claim, Digest::ZERO, journal_digest),
+            Err(VerificationError::ImageVerificationError)
+        );
+    }
+
+    #[test]
+    fn inner_receipt_variant_indices() {
+        // Serialized receipts identify the kind of receipt by the index of its variant, so the
+        // indices of existing variants must never change.
+        fn variant_index(inner: InnerReceipt) -> u32 {
+            crate::serde::to_vec(&inner).unwrap()[0]
+        }
+
+        let claim = fake_claim(&[]);
+        let composite = CompositeReceipt {
+            segments: Vec::new(),
+            assumptions: Vec::new(),
+            journal_digest: None,
+        };
+        assert_eq!(variant_index(InnerReceipt::Composite(composite)), 0);
+        assert_eq!(
+            variant_index(InnerReceipt::Fake {
+                claim: claim.clone()
+            }),
+            3
+        );
+        assert_eq!(
+            variant_index(InnerReceipt::Plonk(PlonkReceipt {
+                seal: Vec::new(),
+                claim
+            })),
+            4
+        );
+    }
+
+    #[test]
+    fn compact_verify_call() {
+        let journal = Journal::new(vec![1, 2, 3]);
+        let mut receipt = CompactReceipt {
+            seal: vec![7; 256],
+            claim: fake_claim(&journal.bytes),
+        };
+        let params = CompactReceipt::verifier_parameters().unwrap();
+        let call = receipt.verify_call(&params, journal.digest()).unwrap();
+        assert_eq!(call.image_id, Digest::from([1; 8]));
+        assert_eq!(call.post_state_digest, Digest::from([2; 8]));
+        assert_eq!(call.journal_digest, journal.digest());
+        assert_eq!(call.seal[..4], params.selector());
+        assert_eq!(
+            Groth16VerifyCall::abi_decode(&call.abi_encode()).unwrap(),
+            call
+        );
+
+        assert!(receipt.verify_call(&params, Digest::ZERO).is_err());
+        receipt.claim.exit_code = ExitCode::Halted(1);
+        assert!(receipt.verify_call(&params, journal.digest()).is_err());
+    }
+
+    #[test]
+    fn to_verifier_receipt() {
+        let journal = vec![1, 2, 3];
+        let claim = fake_claim(&journal);
+        let receipt = Receipt::new(
+            InnerReceipt::Succinct(SuccinctReceipt {
+                seal: vec![1, 2, 3],
+                control_id: Digest::ZERO,
+                claim: claim.clone(),
+            }),
+            journal.clone(),
+        );
+        let converted = receipt.to_verifier_receipt().unwrap();
+        assert_eq!(
+            converted.seal,
+            risc0_verifier::Seal::Succinct {
+                seal: vec![1, 2, 3]
+            }
+        );
+        assert_eq!(converted.claim.digest(), claim.digest());
+        assert_eq!(converted.journal, journal);
+        converted
+            .claim
+            .verify_journal(&Digest::from([1; 8]), &journal)
+            .unwrap();
+
+        let fake = Receipt::new(InnerReceipt::Fake { claim }, journal);
+        assert!(fake.to_verifier_receipt().is_err());
     }
 }
@@ -0,0 +1,53 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::{collections::BTreeMap, string::String};
+use core::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Operational metadata attached to a [Receipt](crate::Receipt), such as the
+/// version of the prover that made it and what proving cost.
+///
+/// **Annotations are not verified.** They are not committed to by the seal or
+/// the claim, [Receipt::verify](crate::Receipt::verify) ignores them, and
+/// anyone who handles the receipt can change them. Use them for monitoring and
+/// accounting, never for a decision that relies on the receipt being valid.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceiptAnnotations {
+    /// The version of the prover that made the receipt, as reported by it.
+    pub prover_version: Option<String>,
+
+    /// The wall-clock time spent proving, as reported by the prover.
+    pub proving_time: Option<Duration>,
+
+    /// The total cycles of the execution, as reported by the prover.
+    pub total_cycles: Option<u64>,
+
+    /// Free-form tags, such as the operator, region or cost of a proving job.
+    pub tags: BTreeMap<String, String>,
+}
+
+impl ReceiptAnnotations {
+    /// Whether there are no annotations.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Add a tag, replacing any tag with the same key.
+    pub fn with_tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+}
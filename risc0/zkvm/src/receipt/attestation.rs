@@ -0,0 +1,331 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Receipts signed by the prover that produced them.
+//!
+//! A [SignedReceipt] attributes a receipt to a prover: it carries the
+//! [ProverIdentity] of the prover and its signature over an attestation
+//! message, which commits to the claim digest of the receipt and to the
+//! identity. The signature says nothing about the validity of the receipt,
+//! which is still checked with the zkVM verifier.
+//!
+//! No signature implementation is bundled. Signing is done by a closure, and
+//! signatures are checked by a [SignatureVerifier], so any Ed25519 or ECDSA
+//! library can be used on the host, and [guest::sig](crate::guest::sig) in
+//! the guest.
+
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+use risc0_binfmt::{tagged_struct, Digestible};
+use risc0_zkp::{core::digest::Digest, verify::VerificationError};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    sha::{self, Sha256},
+    Receipt, VerifierContext,
+};
+
+/// A signature scheme for receipt attestations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum SignatureScheme {
+    /// Ed25519, as specified in RFC 8032, over the 32-byte attestation
+    /// message.
+    Ed25519,
+
+    /// ECDSA over secp256k1, with the attestation message used as the
+    /// message hash.
+    EcdsaSecp256k1,
+
+    /// ECDSA over NIST P-256, with the attestation message used as the
+    /// message hash.
+    EcdsaP256,
+}
+
+impl SignatureScheme {
+    fn id(&self) -> u32 {
+        match self {
+            Self::Ed25519 => 0,
+            Self::EcdsaSecp256k1 => 1,
+            Self::EcdsaP256 => 2,
+        }
+    }
+}
+
+/// The identity of a prover that signs receipts.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProverIdentity {
+    /// A name for the prover, such as its address in a marketplace.
+    pub name: String,
+
+    /// The scheme of the signing key of the prover.
+    pub scheme: SignatureScheme,
+
+    /// The public key of the prover, encoded as usual for the scheme: 32
+    /// bytes for Ed25519, SEC1 for ECDSA.
+    pub public_key: Vec<u8>,
+}
+
+impl Digestible for ProverIdentity {
+    fn digest<S: Sha256>(&self) -> Digest {
+        tagged_struct::<S>(
+            "risc0.ProverIdentity",
+            &[
+                *S::hash_bytes(self.name.as_bytes()),
+                *S::hash_bytes(&self.public_key),
+            ],
+            &[self.scheme.id()],
+        )
+    }
+}
+
+/// Checks signatures over attestation messages.
+pub trait SignatureVerifier {
+    /// Check that `signature` is a signature of `message` by `public_key` in
+    /// the given scheme.
+    fn verify(
+        &self,
+        scheme: SignatureScheme,
+        public_key: &[u8],
+        message: &Digest,
+        signature: &[u8],
+    ) -> Result<(), AttestationError>;
+}
+
+/// A [Receipt] with the identity and signature of the prover that produced it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignedReceipt {
+    /// The signed receipt.
+    pub receipt: Receipt,
+
+    /// The prover that signed the receipt.
+    pub prover: ProverIdentity,
+
+    /// Signature by the prover over [SignedReceipt::message].
+    pub signature: Vec<u8>,
+}
+
+impl SignedReceipt {
+    /// Sign a receipt as the given prover.
+    ///
+    /// `sign` is called with the attestation message and returns the
+    /// signature over its 32 bytes. Signers that can fail should sign
+    /// [SignedReceipt::message] themselves and build the envelope directly.
+    pub fn sign(
+        receipt: Receipt,
+        prover: ProverIdentity,
+        sign: impl FnOnce(&Digest) -> Vec<u8>,
+    ) -> Result<Self, VerificationError> {
+        let message = attestation_message(&receipt, &prover)?;
+        let signature = sign(&message);
+        Ok(Self {
+            receipt,
+            prover,
+            signature,
+        })
+    }
+
+    /// The message signed by the prover, which commits to the claim of the
+    /// receipt and to the identity of the prover.
+    pub fn message(&self) -> Result<Digest, VerificationError> {
+        attestation_message(&self.receipt, &self.prover)
+    }
+
+    /// Check the signature of the prover, without verifying the receipt.
+    pub fn verify_signature(
+        &self,
+        verifier: &impl SignatureVerifier,
+    ) -> Result<(), AttestationError> {
+        verifier.verify(
+            self.prover.scheme,
+            &self.prover.public_key,
+            &self.message()?,
+            &self.signature,
+        )
+    }
+
+    /// Verify the receipt for the given image ID, as with [Receipt::verify],
+    /// and check the signature of the prover.
+    pub fn verify(
+        &self,
+        image_id: impl Into<Digest>,
+        verifier: &impl SignatureVerifier,
+    ) -> Result<(), AttestationError> {
+        self.verify_with_context(&VerifierContext::default(), image_id, verifier)
+    }
+
+    /// Verify the receipt for the given image ID, as with
+    /// [Receipt::verify_with_context], and check the signature of the prover.
+    pub fn verify_with_context(
+        &self,
+        ctx: &VerifierContext,
+        image_id: impl Into<Digest>,
+        verifier: &impl SignatureVerifier,
+    ) -> Result<(), AttestationError> {
+        self.receipt.verify_with_context(ctx, image_id)?;
+        self.verify_signature(verifier)
+    }
+}
+
+fn attestation_message(
+    receipt: &Receipt,
+    prover: &ProverIdentity,
+) -> Result<Digest, VerificationError> {
+    let claim = receipt.claim()?.digest::<sha::Impl>();
+    Ok(tagged_struct::<sha::Impl>(
+        "risc0.ReceiptAttestation",
+        &[claim, prover.digest::<sha::Impl>()],
+        &[],
+    ))
+}
+
+/// The reason a [SignedReceipt] failed to verify.
+#[derive(Debug, PartialEq)]
+#[non_exhaustive]
+pub enum AttestationError {
+    /// The receipt itself failed to verify.
+    Receipt(VerificationError),
+    /// The signature scheme of the prover is not supported by the verifier.
+    UnsupportedScheme(SignatureScheme),
+    /// The public key or signature is malformed, or the signature does not
+    /// match.
+    InvalidSignature,
+}
+
+impl fmt::Display for AttestationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Receipt(e) => write!(f, "receipt verification failed: {e}"),
+            Self::UnsupportedScheme(scheme) => {
+                write!(f, "unsupported signature scheme: {scheme:?}")
+            }
+            Self::InvalidSignature => write!(f, "invalid prover signature"),
+        }
+    }
+}
+
+impl From<VerificationError> for AttestationError {
+    fn from(e: VerificationError) -> Self {
+        Self::Receipt(e)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AttestationError {}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkp::core::digest::Digest;
+
+    use super::{
+        AttestationError, ProverIdentity, SignatureScheme, SignatureVerifier, SignedReceipt,
+    };
+    use crate::{
+        receipt::fake_claim, sha::Digestible, ExitCode, InnerReceipt, Receipt, ReceiptClaim,
+    };
+
+    // A stand-in for a real scheme: the signature is the message with each
+    // byte XORed with the first byte of the key.
+    struct XorVerifier;
+
+    fn xor_sign(key: &[u8], message: &Digest) -> Vec<u8> {
+        message.as_bytes().iter().map(|b| b ^ key[0]).collect()
+    }
+
+    impl SignatureVerifier for XorVerifier {
+        fn verify(
+            &self,
+            scheme: SignatureScheme,
+            public_key: &[u8],
+            message: &Digest,
+            signature: &[u8],
+        ) -> Result<(), AttestationError> {
+            if scheme != SignatureScheme::Ed25519 {
+                return Err(AttestationError::UnsupportedScheme(scheme));
+            }
+            match xor_sign(public_key, message) == signature {
+                true => Ok(()),
+                false => Err(AttestationError::InvalidSignature),
+            }
+        }
+    }
+
+    fn claim(exit_code: ExitCode, journal: &[u8]) -> ReceiptClaim {
+        ReceiptClaim {
+            exit_code,
+            ..fake_claim(journal)
+        }
+    }
+
+    fn receipt(journal: &[u8]) -> Receipt {
+        let claim = claim(ExitCode::Halted(0), journal);
+        Receipt::new(InnerReceipt::Fake { claim }, journal.to_vec())
+    }
+
+    fn prover(name: &str) -> ProverIdentity {
+        ProverIdentity {
+            name: name.into(),
+            scheme: SignatureScheme::Ed25519,
+            public_key: vec![7; 32],
+        }
+    }
+
+    #[test]
+    fn signed_receipt() {
+        let signed = SignedReceipt::sign(receipt(b"journal"), prover("alice"), |message| {
+            xor_sign(&[7], message)
+        })
+        .unwrap();
+        signed.verify_signature(&XorVerifier).unwrap();
+
+        // The message commits to the claim and to the prover.
+        let claim = signed.receipt.claim().unwrap();
+        assert_ne!(signed.message().unwrap(), claim.digest());
+        let mut other = signed.clone();
+        other.receipt = receipt(b"other journal");
+        assert_eq!(
+            other.verify_signature(&XorVerifier),
+            Err(AttestationError::InvalidSignature)
+        );
+        let mut other = signed.clone();
+        other.prover.name = "mallory".into();
+        assert_eq!(
+            other.verify_signature(&XorVerifier),
+            Err(AttestationError::InvalidSignature)
+        );
+        let mut other = signed.clone();
+        other.prover.scheme = SignatureScheme::EcdsaP256;
+        assert_eq!(
+            other.verify_signature(&XorVerifier),
+            Err(AttestationError::UnsupportedScheme(
+                SignatureScheme::EcdsaP256
+            ))
+        );
+    }
+
+    #[test]
+    fn verify_checks_receipt() {
+        let claim = claim(ExitCode::Paused(1), b"journal");
+        let receipt = Receipt::new(InnerReceipt::Fake { claim }, b"journal".to_vec());
+        let signed =
+            SignedReceipt::sign(receipt, prover("alice"), |message| xor_sign(&[7], message))
+                .unwrap();
+        signed.verify_signature(&XorVerifier).unwrap();
+        assert!(matches!(
+            signed.verify(Digest::from([1; 8]), &XorVerifier),
+            Err(AttestationError::Receipt(_))
+        ));
+    }
+}
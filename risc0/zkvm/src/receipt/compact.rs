@@ -15,17 +15,21 @@
 use alloc::{vec, vec::Vec};
 use core::fmt::Debug;
 
-use anyhow::Result;
+use anyhow::{anyhow, ensure, Result};
 use hex::FromHex;
 use risc0_circuit_recursion::control_id::{ALLOWED_CONTROL_ROOT, BN254_CONTROL_ID};
 use risc0_groth16::{
-    fr_from_hex_string, split_digest, verifier::prepared_verifying_key, Seal, Verifier,
+    fr_from_hex_string, split_digest, verifier::verifying_key, Seal, Verifier, VerifierParameters,
+    VerifyCall,
 };
 use risc0_zkp::{core::digest::Digest, verify::VerificationError};
 use serde::{Deserialize, Serialize};
 
 // Make succinct receipt available through this `receipt` module.
-use crate::{sha::Digestible, ReceiptClaim};
+use crate::{
+    sha::Digestible, Assumptions, Channels, ExitCode, MaybePruned, Output, ReceiptClaim,
+    VerifierContext,
+};
 
 /// A receipt composed of a Groth16 over the BN_254 curve
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -43,19 +47,37 @@ impl CompactReceipt {
     /// Verify the integrity of this receipt, ensuring the claim is attested
     /// to by the seal.
     pub fn verify_integrity(&self) -> Result<(), VerificationError> {
-        let (a0, a1) = split_digest(
-            Digest::from_hex(ALLOWED_CONTROL_ROOT)
-                .map_err(|_| VerificationError::ReceiptFormatError)?,
-        )
-        .map_err(|_| VerificationError::ReceiptFormatError)?;
+        self.verify_integrity_with_context(&VerifierContext::default())
+    }
+
+    /// Verify the integrity of this receipt, ensuring the claim is attested
+    /// to by the seal and that the policy of the context allows it.
+    pub fn verify_integrity_with_context(
+        &self,
+        ctx: &VerifierContext,
+    ) -> Result<(), VerificationError> {
+        ctx.limits.check_seal_len(self.seal.len())?;
+        if !ctx.policy.allow_hash_suite("groth16") {
+            return Err(VerificationError::InvalidHashSuite);
+        }
+        let default_params;
+        let params = match ctx.groth16_verifier_parameters.as_ref() {
+            Some(params) => params,
+            None => {
+                default_params = Self::verifier_parameters()
+                    .map_err(|_| VerificationError::ReceiptFormatError)?;
+                &default_params
+            }
+        };
+        ctx.check_control_root(&params.control_root)?;
+        let (a0, a1) =
+            split_digest(params.control_root).map_err(|_| VerificationError::ReceiptFormatError)?;
         let (c0, c1) =
             split_digest(self.claim.digest()).map_err(|_| VerificationError::ReceiptFormatError)?;
-        let id_p254_hash = fr_from_hex_string(BN254_CONTROL_ID)
-            .map_err(|_| VerificationError::ReceiptFormatError)?;
         Verifier::new(
             &Seal::from_vec(&self.seal).map_err(|_| VerificationError::ReceiptFormatError)?,
-            vec![a0, a1, c0, c1, id_p254_hash],
-            prepared_verifying_key().map_err(|_| VerificationError::ReceiptFormatError)?,
+            vec![a0, a1, c0, c1, params.bn254_control_id],
+            params.prepared_verifying_key(),
         )
         .map_err(|_| VerificationError::ReceiptFormatError)?
         .verify()
@@ -64,4 +86,54 @@ impl CompactReceipt {
         // Everything passed
         Ok(())
     }
+
+    /// The parameters that compact receipts are verified against by default, to
+    /// which a Solidity verifier generated from them is bound.
+    pub fn verifier_parameters() -> Result<VerifierParameters> {
+        Ok(VerifierParameters {
+            control_root: Digest::from_hex(ALLOWED_CONTROL_ROOT).map_err(|err| anyhow!(err))?,
+            bn254_control_id: fr_from_hex_string(BN254_CONTROL_ID)?,
+            verifying_key: verifying_key()?,
+        })
+    }
+
+    /// The call to the `verify` function of a Solidity verifier generated from
+    /// `params` that checks this receipt on-chain, where `journal_digest` is
+    /// the digest of the journal of the receipt.
+    ///
+    /// The verifier recomputes the claim from the image ID, post state digest
+    /// and journal digest, assuming an execution that halted with exit code
+    /// zero, with no input, assumptions or channels. Receipts of any other
+    /// claim are rejected here, rather than reverting on-chain.
+    pub fn verify_call(
+        &self,
+        params: &VerifierParameters,
+        journal_digest: Digest,
+    ) -> Result<VerifyCall> {
+        let image_id = self.claim.pre.digest();
+        let post_state_digest = self.claim.post.digest();
+        let expected = ReceiptClaim {
+            pre: MaybePruned::Pruned(image_id),
+            post: MaybePruned::Pruned(post_state_digest),
+            exit_code: ExitCode::Halted(0),
+            input: Digest::ZERO,
+            output: Some(Output {
+                journal: MaybePruned::Pruned(journal_digest),
+                assumptions: Assumptions(vec![]).into(),
+                channels: Channels::default().into(),
+            })
+            .into(),
+        };
+        ensure!(
+            expected.digest() == self.claim.digest(),
+            "receipt claim cannot be verified by the Solidity verifier: it must halt with exit \
+             code zero, with the given journal and no input, assumptions or channels"
+        );
+        Ok(params.verify_call(
+            &Seal::from_vec(&self.seal)?,
+            image_id,
+            post_state_digest,
+            journal_digest,
+        ))
+    }
 }
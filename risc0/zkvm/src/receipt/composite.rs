@@ -56,58 +56,89 @@ impl CompositeReceipt {
         ctx: &VerifierContext,
     ) -> Result<(), VerificationError> {
         tracing::debug!("CompositeReceipt::verify_integrity_with_context");
-        // Verify the continuation, by verifying every segment receipt in order.
-        let (final_receipt, receipts) = self
+        self.verify_assumptions_with_context(ctx)?;
+
+        // Verify the continuation, by verifying every segment receipt and its chaining to its
+        // neighbors.
+        let final_receipt = self
+            .segments
+            .last()
+            .ok_or(VerificationError::ReceiptFormatError)?;
+        for index in 0..self.segments.len() {
+            self.verify_segment_with_context(index, ctx)?;
+        }
+
+        // Verify decoded output digest is consistent with the journal_digest
+        // and assumptions.
+        self.verify_output_consistency(&final_receipt.claim)?;
+
+        Ok(())
+    }
+
+    /// Verify the segment at `index`, and the chaining of its claim to the claims of the
+    /// segments before and after it, without verifying any other seal.
+    ///
+    /// Segments can be verified this way in parallel, or on separate machines, to find which
+    /// segment of a receipt is bad. The receipt is valid once all of its segments pass and
+    /// [CompositeReceipt::verify_integrity_with_context] passes, which also checks the
+    /// assumptions and the output.
+    pub fn verify_segment_with_context(
+        &self,
+        index: usize,
+        ctx: &VerifierContext,
+    ) -> Result<(), VerificationError> {
+        let receipt = self
             .segments
-            .as_slice()
-            .split_last()
+            .get(index)
             .ok_or(VerificationError::ReceiptFormatError)?;
+        receipt.verify_integrity_with_context(ctx)?;
+        tracing::debug!("segment {index} claim: {:#?}", receipt.claim);
+        self.check_segment_chaining(index)
+    }
 
-        // Verify each segment and its chaining to the next.
-        let mut expected_pre_state_digest = None;
-        for receipt in receipts {
+    // Verify all assumption receipts attached to this composite receipt.
+    pub(crate) fn verify_assumptions_with_context(
+        &self,
+        ctx: &VerifierContext,
+    ) -> Result<(), VerificationError> {
+        ctx.limits.check_assumptions(self.assumptions.len())?;
+        for receipt in self.assumptions.iter() {
+            ctx.limits.check_depth(receipt)?;
+        }
+        for receipt in self.assumptions.iter() {
+            tracing::debug!("verifying assumption: {:?}", receipt.claim()?.digest());
             receipt.verify_integrity_with_context(ctx)?;
-            tracing::debug!("claim: {:#?}", receipt.claim);
-            if let Some(id) = expected_pre_state_digest {
-                if id != receipt.claim.pre.digest() {
-                    return Err(VerificationError::ImageVerificationError);
-                }
+        }
+        Ok(())
+    }
+
+    // Check that the segment at `index` starts where the one before it stops, and that every
+    // segment but the last stops in a split, with the next one starting where it stops.
+    pub(crate) fn check_segment_chaining(&self, index: usize) -> Result<(), VerificationError> {
+        let claim = &self.segments[index].claim;
+        let post_digest = |claim: &ReceiptClaim| {
+            Ok(claim
+                .post
+                .as_value()
+                .map_err(|_| VerificationError::ReceiptFormatError)?
+                .digest())
+        };
+        if let Some(prev) = index.checked_sub(1).map(|i| &self.segments[i].claim) {
+            if post_digest(prev)? != claim.pre.digest() {
+                return Err(VerificationError::ImageVerificationError);
             }
-            if receipt.claim.exit_code != ExitCode::SystemSplit {
+        }
+        if let Some(next) = self.segments.get(index + 1).map(|next| &next.claim) {
+            if claim.exit_code != ExitCode::SystemSplit {
                 return Err(VerificationError::UnexpectedExitCode);
             }
-            if !receipt.claim.output.is_none() {
+            if !claim.output.is_none() {
                 return Err(VerificationError::ReceiptFormatError);
             }
-            expected_pre_state_digest = Some(
-                receipt
-                    .claim
-                    .post
-                    .as_value()
-                    .map_err(|_| VerificationError::ReceiptFormatError)?
-                    .digest(),
-            );
-        }
-
-        // Verify the last receipt in the continuation.
-        final_receipt.verify_integrity_with_context(ctx)?;
-        tracing::debug!("final: {:#?}", final_receipt.claim);
-        if let Some(id) = expected_pre_state_digest {
-            if id != final_receipt.claim.pre.digest() {
+            if post_digest(claim)? != next.pre.digest() {
                 return Err(VerificationError::ImageVerificationError);
             }
         }
-
-        // Verify all assumption receipts attached to this composite receipt.
-        for receipt in self.assumptions.iter() {
-            tracing::debug!("verifying assumption: {:?}", receipt.claim()?.digest());
-            receipt.verify_integrity_with_context(ctx)?;
-        }
-
-        // Verify decoded output digest is consistent with the journal_digest
-        // and assumptions.
-        self.verify_output_consistency(&final_receipt.claim)?;
-
         Ok(())
     }
 
@@ -141,6 +172,7 @@ impl CompositeReceipt {
                     // CompositeReceipt.
                     // NOTE: Proven assumptions are not included in the CompositeReceipt claim.
                     assumptions: Assumptions(vec![]).into(),
+                    channels: last_claim.output.channels(),
                 })
             })
             .transpose()?;
@@ -169,6 +201,7 @@ impl CompositeReceipt {
                         .ok_or(VerificationError::ReceiptFormatError)?,
                 ),
                 assumptions: self.assumptions_claim()?.into(),
+                channels: claim.output.channels(),
             };
 
             // If these digests do not match, this receipt is internally inconsistent.
@@ -221,3 +254,74 @@ impl CompositeReceipt {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use risc0_binfmt::{ExitCode, SystemState};
+    use risc0_zkp::{core::digest::Digest, verify::VerificationError};
+
+    use super::CompositeReceipt;
+    use crate::{sha::Digestible, MaybePruned, ReceiptClaim, SegmentReceipt};
+
+    fn state(pc: u32) -> SystemState {
+        SystemState {
+            pc,
+            merkle_root: Digest::ZERO,
+        }
+    }
+
+    fn segment(index: u32, pre: u32, post: u32, exit_code: ExitCode) -> SegmentReceipt {
+        SegmentReceipt {
+            seal: vec![],
+            index,
+            hashfn: "poseidon2".into(),
+            claim: ReceiptClaim {
+                pre: state(pre).into(),
+                post: state(post).into(),
+                exit_code,
+                input: Digest::ZERO,
+                output: None.into(),
+            },
+        }
+    }
+
+    #[test]
+    fn segment_chaining() {
+        let mut receipt = CompositeReceipt {
+            segments: vec![
+                segment(0, 0, 4, ExitCode::SystemSplit),
+                segment(1, 4, 8, ExitCode::SystemSplit),
+                segment(2, 8, 12, ExitCode::Halted(0)),
+            ],
+            assumptions: vec![],
+            journal_digest: None,
+        };
+        for index in 0..3 {
+            receipt.check_segment_chaining(index).unwrap();
+        }
+
+        // A broken link is reported by the segments on both sides of it, and only by them.
+        receipt.segments[2].claim.pre = state(9).into();
+        receipt.check_segment_chaining(0).unwrap();
+        for index in 1..3 {
+            assert_eq!(
+                receipt.check_segment_chaining(index),
+                Err(VerificationError::ImageVerificationError)
+            );
+        }
+
+        receipt.segments[2].claim.pre = state(8).into();
+        receipt.segments[1].claim.exit_code = ExitCode::Halted(0);
+        assert_eq!(
+            receipt.check_segment_chaining(1),
+            Err(VerificationError::UnexpectedExitCode)
+        );
+
+        receipt.segments[1].claim.exit_code = ExitCode::SystemSplit;
+        receipt.segments[0].claim.post = MaybePruned::Pruned(state(4).digest());
+        assert_eq!(
+            receipt.check_segment_chaining(1),
+            Err(VerificationError::ReceiptFormatError)
+        );
+    }
+}
@@ -0,0 +1,278 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt;
+
+use risc0_zkp::{core::digest::Digest, verify::VerificationError};
+
+use super::{check_claim, InnerReceipt, Receipt, VerifierContext};
+use crate::{sha::Digestible, Assumptions, MaybePruned, ReceiptClaim};
+
+/// An explanation of why a receipt failed to verify, produced by [diagnose].
+///
+/// The [Display](fmt::Display) form is meant for people debugging an
+/// integration, and its wording may change between releases.
+#[derive(Debug)]
+pub struct Report {
+    /// The error [Receipt::verify_with_context] fails with, or `None` if the
+    /// receipt verifies.
+    pub error: Option<VerificationError>,
+
+    /// The part of the receipt the failure was found in, such as
+    /// `segment 3 of composite receipt`, or `claim`.
+    pub location: Option<String>,
+
+    /// What was found wrong, one finding per entry.
+    pub details: Vec<String>,
+}
+
+impl Report {
+    /// Whether the receipt verified.
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Some(error) = &self.error else {
+            return write!(f, "receipt verified");
+        };
+        write!(f, "verification failed")?;
+        if let Some(location) = &self.location {
+            write!(f, " in {location}")?;
+        }
+        write!(f, ": {error}")?;
+        for detail in &self.details {
+            write!(f, "\n  {detail}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Verify a receipt as [Receipt::verify_with_context] does, and explain the
+/// failure if it does not verify.
+///
+/// When the seal fails, the parts of the receipt are verified again one by one
+/// to find the segment or assumption at fault, so this can take twice as long
+/// as verification. Seals are checked before the claim, so the error is
+/// always the one [Receipt::verify_with_context] returns.
+pub fn diagnose(receipt: &Receipt, ctx: &VerifierContext, image_id: impl Into<Digest>) -> Report {
+    let image_id = image_id.into();
+    let mut report = Report {
+        error: None,
+        location: None,
+        details: vec![],
+    };
+
+    if let Err(err) = receipt.inner.verify_integrity_with_context(ctx) {
+        report.location = Some(locate(&receipt.inner, ctx));
+        report.details = explain_seal(&receipt.inner, &err);
+        report.error = Some(err);
+        return report;
+    }
+
+    let journal_digest = receipt.journal.digest();
+    let result = receipt.inner.claim().and_then(|claim| {
+        check_claim(&claim, image_id, journal_digest).map_err(|err| {
+            report.details = explain_claim(&claim, image_id, journal_digest, &err);
+            err
+        })
+    });
+    if let Err(err) = result {
+        report.location = Some("claim".into());
+        report.error = Some(err);
+    }
+    report
+}
+
+pub(super) fn kind(inner: &InnerReceipt) -> &'static str {
+    match inner {
+        InnerReceipt::Composite(_) => "composite receipt",
+        InnerReceipt::Succinct(_) => "succinct receipt",
+        #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
+        InnerReceipt::Compact(_) => "compact receipt",
+        #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
+        InnerReceipt::Plonk(_) => "plonk receipt",
+        InnerReceipt::Fake { .. } => "fake receipt",
+    }
+}
+
+// Find the innermost part of a receipt whose seal fails, by verifying the
+// segments and assumptions of composite receipts in turn.
+fn locate(inner: &InnerReceipt, ctx: &VerifierContext) -> String {
+    let InnerReceipt::Composite(composite) = inner else {
+        return kind(inner).into();
+    };
+    for index in 0..composite.segments.len() {
+        if composite.verify_segment_with_context(index, ctx).is_err() {
+            return format!("segment {index} of composite receipt");
+        }
+    }
+    for (index, assumption) in composite.assumptions.iter().enumerate() {
+        if assumption.verify_integrity_with_context(ctx).is_err() {
+            return format!(
+                "{} in assumption {index} of composite receipt",
+                locate(assumption, ctx)
+            );
+        }
+    }
+    kind(inner).into()
+}
+
+fn explain_seal(inner: &InnerReceipt, err: &VerificationError) -> Vec<String> {
+    let detail = match err {
+        VerificationError::InvalidProof | VerificationError::MerkleQueryOutOfRange { .. } => {
+            match inner {
+                InnerReceipt::Fake { .. } => {
+                    "fake receipts are only accepted in dev mode, with RISC0_DEV_MODE set".into()
+                }
+                _ => "the seal does not prove the claim: it is corrupted, or the claim was \
+                      changed after proving"
+                    .into(),
+            }
+        }
+        VerificationError::ControlVerificationError { control_id } => format!(
+            "control ID {control_id} is not allowed by the verifier policy; the receipt may \
+             have been produced by a version of risc0-zkvm other than {}",
+            crate::VERSION
+        ),
+        VerificationError::InvalidHashSuite => format!(
+            "the receipt is verified with {:?}, and one of these is not supported by the \
+             verifier or not allowed by its policy",
+            inner.hashfns()
+        ),
+        VerificationError::ImageVerificationError => {
+            "a segment does not start in the state the segment before it ends in".into()
+        }
+        VerificationError::UnexpectedExitCode => {
+            "a segment other than the last does not end in a split".into()
+        }
+        VerificationError::ReceiptFormatError => {
+            "the receipt is malformed: it has no segments, or a part of a claim needed to \
+             verify it is pruned"
+                .into()
+        }
+        VerificationError::MissingVerifyingKey => {
+            "the VerifierContext has no verifying key for this kind of receipt".into()
+        }
+        _ => return vec![],
+    };
+    vec![detail]
+}
+
+fn explain_claim(
+    claim: &ReceiptClaim,
+    image_id: Digest,
+    journal_digest: Digest,
+    err: &VerificationError,
+) -> Vec<String> {
+    match err {
+        VerificationError::ImageVerificationError => vec![format!(
+            "the receipt is for image ID {}, but image ID {image_id} was expected",
+            claim.pre.digest()
+        )],
+        VerificationError::UnexpectedExitCode => vec![format!(
+            "the guest exited with {:?}, but verification requires Halted(0) or Paused(0)",
+            claim.exit_code
+        )],
+        VerificationError::JournalDigestMismatch => match &claim.output {
+            MaybePruned::Value(Some(output)) => {
+                let mut details = vec![];
+                let claimed = output.journal.digest();
+                if claimed != journal_digest {
+                    details.push(format!(
+                        "the journal has digest {journal_digest}, but the claim commits to a \
+                         journal with digest {claimed}"
+                    ));
+                }
+                if output.assumptions.digest() != Assumptions::default().digest() {
+                    details.push(
+                        "the receipt is conditional on assumptions, which must be resolved \
+                         before it verifies"
+                            .to_string(),
+                    );
+                }
+                details
+            }
+            MaybePruned::Value(None) => vec![format!(
+                "the claim has no output, but the journal is not empty: it has digest \
+                 {journal_digest}"
+            )],
+            MaybePruned::Pruned(output) => vec![format!(
+                "the output of the claim is pruned to digest {output}, which does not commit \
+                 to the journal with digest {journal_digest}"
+            )],
+        },
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkp::{core::digest::Digest, verify::VerificationError};
+
+    use super::{diagnose, explain_claim};
+    use crate::{receipt::fake_claim, sha::Digestible, InnerReceipt, Receipt, VerifierContext};
+
+    #[test]
+    fn fake_receipt() {
+        let receipt = Receipt::new(
+            InnerReceipt::Fake {
+                claim: fake_claim(b""),
+            },
+            vec![],
+        );
+        let report = diagnose(&receipt, &VerifierContext::default(), Digest::from([1; 8]));
+        assert_eq!(report.error, Some(VerificationError::InvalidProof));
+        assert_eq!(report.location.as_deref(), Some("fake receipt"));
+        assert!(report.to_string().contains("RISC0_DEV_MODE"));
+    }
+
+    #[test]
+    fn claim_details() {
+        let claim = fake_claim(b"journal");
+        let expected = Digest::from([2; 8]);
+        let details = explain_claim(
+            &claim,
+            expected,
+            Digest::ZERO,
+            &VerificationError::ImageVerificationError,
+        );
+        assert_eq!(
+            details,
+            [format!(
+                "the receipt is for image ID {}, but image ID {expected} was expected",
+                Digest::from([1; 8])
+            )]
+        );
+
+        let journal = b"other".to_vec().digest();
+        let details = explain_claim(
+            &claim,
+            expected,
+            journal,
+            &VerificationError::JournalDigestMismatch,
+        );
+        assert_eq!(details.len(), 1);
+        assert!(details[0].contains(&journal.to_string()));
+        assert!(details[0].contains(&b"journal".to_vec().digest().to_string()));
+    }
+}
@@ -0,0 +1,319 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::{
+    collections::BTreeSet,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt;
+
+use risc0_circuit_rv32im::CircuitImpl;
+use risc0_zkp::{adapter::CircuitInfo, core::digest::Digest};
+use risc0_zkvm_platform::WORD_SIZE;
+
+use super::{diagnose::kind, policy::current_control_ids, InnerReceipt, Receipt};
+use crate::{sha::Digestible, MaybePruned, ReceiptAnnotations, ReceiptClaim};
+
+const JOURNAL_PREVIEW_LEN: usize = 64;
+
+/// The structure of a receipt, produced by [inspect].
+///
+/// Nothing in an inspection is verified. The [Display](fmt::Display) form is
+/// meant for people supporting or debugging an integration, and its wording
+/// may change between releases.
+#[derive(Clone, Debug)]
+pub struct Inspection {
+    /// The kind of the receipt, such as `composite receipt`.
+    pub kind: &'static str,
+
+    /// The claim of the receipt, or `None` if it cannot be extracted, such as
+    /// from a composite receipt with no segments.
+    pub claim: Option<ReceiptClaim>,
+
+    /// The length of the journal, in bytes.
+    pub journal_len: usize,
+
+    /// The digest of the journal.
+    pub journal_digest: Digest,
+
+    /// The first 64 bytes of the journal.
+    pub journal_preview: Vec<u8>,
+
+    /// The seals of the receipt, in the order they are verified.
+    pub seals: Vec<SealInfo>,
+
+    /// The control IDs of succinct seals that this build does not know, which
+    /// were made by the recursion programs of another release.
+    pub unknown_control_ids: Vec<Digest>,
+
+    /// The annotations of the receipt, which include the version of the prover
+    /// that made it, as reported by the prover.
+    pub annotations: ReceiptAnnotations,
+}
+
+/// A seal in a receipt, as reported by [Inspection::seals].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SealInfo {
+    /// The part of the receipt the seal is in, such as `segment 2`, or
+    /// `assumption 0, segment 1` within a composite receipt.
+    pub location: String,
+
+    /// The hash function, or proof system, the seal is verified with.
+    pub hashfn: String,
+
+    /// The size of the seal, in bytes.
+    pub size: usize,
+
+    /// The segment size of the seal, in powers of 2 cycles, for segment seals.
+    pub po2: Option<u32>,
+
+    /// The control ID, for succinct seals.
+    pub control_id: Option<Digest>,
+}
+
+impl Inspection {
+    /// The total size of the seals, in bytes.
+    pub fn seal_size(&self) -> usize {
+        self.seals.iter().map(|seal| seal.size).sum()
+    }
+
+    /// The hash functions, or proof systems, the seals are verified with.
+    pub fn hashfns(&self) -> BTreeSet<&str> {
+        self.seals.iter().map(|seal| seal.hashfn.as_str()).collect()
+    }
+}
+
+impl fmt::Display for Inspection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.kind)?;
+        match &self.claim {
+            Some(claim) => {
+                writeln!(f, "  image ID:     {}", claim.pre.digest())?;
+                writeln!(f, "  exit code:    {:?}", claim.exit_code)?;
+                writeln!(f, "  claim:        {}", claim.digest())?;
+                writeln!(f, "  input:        {}", claim.input)?;
+                writeln!(f, "  post state:   {}", claim.post.digest())?;
+                match &claim.output {
+                    MaybePruned::Value(Some(output)) => {
+                        writeln!(f, "  assumptions:  {}", output.assumptions.digest())?
+                    }
+                    MaybePruned::Value(None) => writeln!(f, "  output:       none")?,
+                    MaybePruned::Pruned(digest) => writeln!(f, "  output:       pruned {digest}")?,
+                }
+            }
+            None => writeln!(f, "  claim:        unavailable")?,
+        }
+
+        write!(
+            f,
+            "  journal:      {} bytes, digest {}",
+            self.journal_len, self.journal_digest
+        )?;
+        if !self.journal_preview.is_empty() {
+            let ellipsis = match self.journal_len > self.journal_preview.len() {
+                true => "...",
+                false => "",
+            };
+            write!(
+                f,
+                "\n                {}{ellipsis}",
+                hex::encode(&self.journal_preview)
+            )?;
+        }
+
+        write!(
+            f,
+            "\n  seals:        {} bytes in {} seals, verified with {:?}",
+            self.seal_size(),
+            self.seals.len(),
+            self.hashfns()
+        )?;
+        for seal in &self.seals {
+            write!(
+                f,
+                "\n    {}: {}, {} bytes",
+                seal.location, seal.hashfn, seal.size
+            )?;
+            if let Some(po2) = seal.po2 {
+                write!(f, ", po2 {po2}")?;
+            }
+            if let Some(control_id) = seal.control_id {
+                write!(f, ", control ID {control_id}")?;
+            }
+        }
+        for control_id in &self.unknown_control_ids {
+            write!(
+                f,
+                "\n  control ID {control_id} is not known to zkVM {}",
+                crate::VERSION
+            )?;
+        }
+
+        if let Some(version) = &self.annotations.prover_version {
+            write!(f, "\n  prover:       {version}")?;
+        }
+        for (key, value) in &self.annotations.tags {
+            write!(f, "\n  tag {key}:  {value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Describe the structure of a receipt of any kind, without verifying it.
+pub fn inspect(receipt: &Receipt) -> Inspection {
+    let mut seals = vec![];
+    collect_seals(&receipt.inner, "", &mut seals);
+    let known: BTreeSet<Digest> = current_control_ids().collect();
+    let unknown_control_ids = seals
+        .iter()
+        .filter_map(|seal| seal.control_id)
+        .filter(|control_id| !known.contains(control_id))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    let journal = &receipt.journal.bytes;
+    Inspection {
+        kind: kind(&receipt.inner),
+        claim: receipt.inner.claim().ok(),
+        journal_len: journal.len(),
+        journal_digest: receipt.journal.digest(),
+        journal_preview: journal[..journal.len().min(JOURNAL_PREVIEW_LEN)].to_vec(),
+        seals,
+        unknown_control_ids,
+        annotations: receipt.annotations.clone(),
+    }
+}
+
+fn collect_seals(inner: &InnerReceipt, prefix: &str, seals: &mut Vec<SealInfo>) {
+    let seal = |location: &str, hashfn: &str, size| SealInfo {
+        location: format!("{prefix}{location}"),
+        hashfn: hashfn.to_string(),
+        size,
+        po2: None,
+        control_id: None,
+    };
+    match inner {
+        InnerReceipt::Composite(composite) => {
+            for segment in &composite.segments {
+                seals.push(SealInfo {
+                    // The globals of the seal are followed by the po2 the segment was proven at.
+                    po2: segment.seal.get(CircuitImpl::OUTPUT_SIZE).copied(),
+                    ..seal(
+                        &format!("segment {}", segment.index),
+                        &segment.hashfn,
+                        segment.seal.len() * WORD_SIZE,
+                    )
+                });
+            }
+            for (index, assumption) in composite.assumptions.iter().enumerate() {
+                collect_seals(assumption, &format!("{prefix}assumption {index}, "), seals);
+            }
+        }
+        InnerReceipt::Succinct(succinct) => seals.push(SealInfo {
+            control_id: Some(succinct.control_id),
+            ..seal("succinct", "poseidon2", succinct.seal.len() * WORD_SIZE)
+        }),
+        #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
+        InnerReceipt::Compact(compact) => {
+            seals.push(seal("compact", "groth16", compact.seal.len()))
+        }
+        #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
+        InnerReceipt::Plonk(plonk) => seals.push(seal("plonk", "plonk", plonk.seal.len())),
+        InnerReceipt::Fake { .. } => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::inspect;
+    use crate::{
+        receipt::fake_claim, sha::Digest, CompositeReceipt, ExitCode, InnerReceipt, MaybePruned,
+        Receipt, ReceiptClaim, SegmentReceipt, SuccinctReceipt,
+    };
+
+    #[test]
+    fn composite() {
+        let segment = |index| SegmentReceipt {
+            seal: vec![0; 200],
+            index,
+            hashfn: "poseidon2".into(),
+            claim: fake_claim(b""),
+        };
+        let succinct = SuccinctReceipt {
+            seal: vec![0; 10],
+            control_id: Digest::from([2; 8]),
+            claim: fake_claim(b""),
+        };
+        let receipt = Receipt::new(
+            InnerReceipt::Composite(CompositeReceipt {
+                segments: vec![segment(0), segment(1)],
+                assumptions: vec![InnerReceipt::Succinct(succinct)],
+                journal_digest: None,
+            }),
+            b"journal".to_vec(),
+        );
+
+        let inspection = inspect(&receipt);
+        assert_eq!(inspection.kind, "composite receipt");
+        assert_eq!(inspection.journal_preview, b"journal");
+        let locations: Vec<_> = inspection
+            .seals
+            .iter()
+            .map(|seal| seal.location.as_str())
+            .collect();
+        assert_eq!(
+            locations,
+            ["segment 0", "segment 1", "assumption 0, succinct"]
+        );
+        assert_eq!(inspection.seals[0].po2, Some(0));
+        assert_eq!(inspection.seal_size(), 200 * 4 * 2 + 10 * 4);
+        assert_eq!(inspection.unknown_control_ids, [Digest::from([2; 8])]);
+
+        let text = inspect(&receipt).to_string();
+        assert!(
+            text.contains("segment 1: poseidon2, 800 bytes, po2 0"),
+            "{text}"
+        );
+    }
+
+    #[test]
+    fn fake() {
+        let receipt = Receipt::new(
+            InnerReceipt::Fake {
+                claim: ReceiptClaim {
+                    output: MaybePruned::Pruned(Digest::ZERO),
+                    ..fake_claim(b"")
+                },
+            },
+            vec![0; 100],
+        );
+        let inspection = inspect(&receipt);
+        assert!(inspection.seals.is_empty());
+        assert_eq!(inspection.journal_preview.len(), 64);
+        assert_eq!(
+            inspection.claim.as_ref().unwrap().exit_code,
+            ExitCode::Halted(0)
+        );
+        let text = inspection.to_string();
+        assert!(
+            text.contains(&format!("image ID:     {}", Digest::from([1; 8]))),
+            "{text}"
+        );
+        assert!(text.contains("output:       pruned"), "{text}");
+    }
+}
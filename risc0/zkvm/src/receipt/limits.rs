@@ -0,0 +1,187 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use risc0_zkp::{verify::VerificationError, MAX_CYCLES_PO2};
+
+use super::InnerReceipt;
+
+/// Bounds on the work a [VerifierContext](crate::VerifierContext) does for a
+/// single receipt.
+///
+/// Each limit is checked before the data it bounds is parsed, and a receipt
+/// over a limit is rejected with [VerificationError::LimitExceeded]. The
+/// default limits accept every receipt this release can produce; services
+/// verifying untrusted receipts should lower them to what they expect.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifierLimits {
+    /// Maximum length in bytes of each seal.
+    pub max_seal_len: usize,
+
+    /// Maximum po2 of the trace proven by a STARK seal.
+    pub max_po2: u32,
+
+    /// Maximum nesting of receipts attached to a
+    /// [CompositeReceipt](crate::CompositeReceipt) as assumptions. A receipt
+    /// without assumptions has a depth of zero.
+    pub max_depth: usize,
+
+    /// Maximum number of assumption receipts attached to a
+    /// [CompositeReceipt](crate::CompositeReceipt).
+    pub max_assumptions: usize,
+}
+
+impl Default for VerifierLimits {
+    fn default() -> Self {
+        Self {
+            max_seal_len: usize::MAX,
+            max_po2: MAX_CYCLES_PO2 as u32,
+            max_depth: usize::MAX,
+            max_assumptions: usize::MAX,
+        }
+    }
+}
+
+impl VerifierLimits {
+    /// Limit the length in bytes of each seal.
+    pub fn with_max_seal_len(mut self, max_seal_len: usize) -> Self {
+        self.max_seal_len = max_seal_len;
+        self
+    }
+
+    /// Limit the po2 of STARK seals.
+    pub fn with_max_po2(mut self, max_po2: u32) -> Self {
+        self.max_po2 = max_po2;
+        self
+    }
+
+    /// Limit the nesting of assumption receipts.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Limit the number of assumption receipts of each composite receipt.
+    pub fn with_max_assumptions(mut self, max_assumptions: usize) -> Self {
+        self.max_assumptions = max_assumptions;
+        self
+    }
+
+    pub(crate) fn check_seal_len(&self, len: usize) -> Result<(), VerificationError> {
+        check("seal length", len, self.max_seal_len)
+    }
+
+    pub(crate) fn check_po2(&self, po2: u32) -> Result<(), VerificationError> {
+        check("po2", po2 as usize, self.max_po2 as usize)
+    }
+
+    pub(crate) fn check_assumptions(&self, count: usize) -> Result<(), VerificationError> {
+        check("assumption count", count, self.max_assumptions)
+    }
+
+    pub(crate) fn check_depth(&self, receipt: &InnerReceipt) -> Result<(), VerificationError> {
+        match exceeds_depth(receipt, self.max_depth) {
+            // The depth is only computed up to the point it exceeds the limit.
+            true => Err(VerificationError::LimitExceeded {
+                limit: "assumption depth",
+                value: self.max_depth.saturating_add(1),
+                max: self.max_depth,
+            }),
+            false => Ok(()),
+        }
+    }
+}
+
+fn check(limit: &'static str, value: usize, max: usize) -> Result<(), VerificationError> {
+    match value <= max {
+        true => Ok(()),
+        false => Err(VerificationError::LimitExceeded { limit, value, max }),
+    }
+}
+
+// Whether the assumption depth of the receipt is over `max`, recursing no
+// deeper than the limit so that adversarial nesting is cut off early.
+fn exceeds_depth(receipt: &InnerReceipt, max: usize) -> bool {
+    let InnerReceipt::Composite(composite) = receipt else {
+        return false;
+    };
+    if composite.assumptions.is_empty() {
+        return false;
+    }
+    max == 0
+        || composite
+            .assumptions
+            .iter()
+            .any(|assumption| exceeds_depth(assumption, max - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkp::{core::digest::Digest, verify::VerificationError};
+
+    use super::VerifierLimits;
+    use crate::{receipt::fake_claim, CompositeReceipt, InnerReceipt, VerifierContext};
+
+    fn fake() -> InnerReceipt {
+        InnerReceipt::Fake {
+            claim: fake_claim(b""),
+        }
+    }
+
+    fn nested(depth: usize) -> InnerReceipt {
+        (0..depth).fold(fake(), |inner, _| {
+            InnerReceipt::Composite(CompositeReceipt {
+                segments: vec![],
+                assumptions: vec![inner],
+                journal_digest: None,
+            })
+        })
+    }
+
+    #[test]
+    fn depth() {
+        let limits = VerifierLimits::default().with_max_depth(2);
+        limits.check_depth(&fake()).unwrap();
+        limits.check_depth(&nested(2)).unwrap();
+        assert_eq!(
+            limits.check_depth(&nested(3)),
+            Err(VerificationError::LimitExceeded {
+                limit: "assumption depth",
+                value: 3,
+                max: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn composite_limits() {
+        let composite = InnerReceipt::Composite(CompositeReceipt {
+            segments: vec![],
+            assumptions: vec![fake(), fake(), fake()],
+            journal_digest: None,
+        });
+        let ctx = VerifierContext::default()
+            .with_limits(VerifierLimits::default().with_max_assumptions(2));
+        assert_eq!(
+            composite.verify_integrity_with_context(&ctx),
+            Err(VerificationError::LimitExceeded {
+                limit: "assumption count",
+                value: 3,
+                max: 2,
+            })
+        );
+
+        let ctx = VerifierContext::default().with_limits(VerifierLimits::default().with_max_po2(0));
+        assert!(ctx.check_control_id(&Digest::ZERO, 1).is_err());
+    }
+}
@@ -0,0 +1,209 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Merkle trees of control IDs, whose roots constrain the recursion programs
+//! that may be applied to a receipt.
+
+use alloc::vec::Vec;
+
+use anyhow::{bail, Result};
+use risc0_core::field::baby_bear::BabyBear;
+use risc0_zkp::{
+    core::{
+        digest::{Digest, DIGEST_WORDS},
+        hash::HashFn,
+    },
+    verify::VerificationError,
+};
+use serde::{Deserialize, Serialize};
+
+static EMPTY_DIGEST: Digest = Digest::new([0; DIGEST_WORDS]);
+
+/// Depth of the Merkle tree to use for encoding the set of allowed control IDs.
+/// NOTE: Changing this constant must be coordinated with the circuit. In order to avoid needing to
+/// change the circuit later, this is set to 8 which allows for enough control IDs to be encoded
+/// that we are unlikely to need more.
+pub(crate) const ALLOWED_CODE_MERKLE_DEPTH: usize = 8;
+
+/// A Merkle tree of fixed depth, padded with zero digests.
+pub struct MerkleGroup {
+    /// Depth of the tree, which has `2^depth` leaves.
+    pub depth: usize,
+    /// The leaves of the tree, with the rest of the `2^depth` leaves empty.
+    pub leaves: Vec<Digest>,
+}
+
+/// An inclusion proof of a leaf in a [MerkleGroup].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    /// Index of the leaf in the tree.
+    pub index: usize,
+    /// The siblings of the path from the leaf to the root, starting at the leaf.
+    pub digests: Vec<Digest>,
+}
+
+impl MerkleProof {
+    /// The root of the tree in which this proof places `leaf`.
+    pub fn root(&self, leaf: &Digest, hashfn: &dyn HashFn<BabyBear>) -> Digest {
+        let mut index = self.index;
+        let mut cur = *leaf;
+        for sibling in self.digests.iter() {
+            cur = if index & 1 == 0 {
+                *hashfn.hash_pair(&cur, sibling)
+            } else {
+                *hashfn.hash_pair(sibling, &cur)
+            };
+            index >>= 1;
+        }
+        cur
+    }
+
+    /// Check that this proof places `leaf` in the tree with the given root.
+    pub fn verify(
+        &self,
+        leaf: &Digest,
+        root: &Digest,
+        hashfn: &dyn HashFn<BabyBear>,
+    ) -> Result<(), VerificationError> {
+        // An index past the end of the tree would alias a leaf of it.
+        if self.index >> self.digests.len() != 0 || self.root(leaf, hashfn) != *root {
+            return Err(VerificationError::ControlVerificationError { control_id: *leaf });
+        }
+        Ok(())
+    }
+}
+
+impl MerkleGroup {
+    /// The root of the tree.
+    pub fn calc_root(&self, hashfn: &dyn HashFn<BabyBear>) -> Digest {
+        self.calc_range_root(0, 1 << self.depth, hashfn)
+    }
+
+    fn leaf_or_empty(&self, index: usize) -> &Digest {
+        self.leaves.get(index).unwrap_or(&EMPTY_DIGEST)
+    }
+
+    fn calc_range_root(&self, start: usize, end: usize, hashfn: &dyn HashFn<BabyBear>) -> Digest {
+        assert!(start < end);
+        let res = if start + 1 == end {
+            *self.leaf_or_empty(start)
+        } else {
+            let mid = (start + end) / 2;
+            assert_eq!(mid - start, end - mid);
+
+            let left = self.calc_range_root(start, mid, hashfn);
+            let right = self.calc_range_root(mid, end, hashfn);
+            *hashfn.hash_pair(&left, &right)
+        };
+        res
+    }
+
+    /// Prove the inclusion of `control_id` in the tree.
+    pub fn get_proof(
+        &self,
+        control_id: &Digest,
+        hashfn: &dyn HashFn<BabyBear>,
+    ) -> Result<MerkleProof> {
+        let Some(index) = self.leaves.iter().position(|elem| elem == control_id) else {
+            bail!("Unable to find {control_id:?} in merkle group");
+        };
+        Ok(MerkleProof {
+            index,
+            digests: self.get_proof_by_index(index, hashfn),
+        })
+    }
+
+    /// The siblings of the path from the leaf at `index` to the root.
+    pub fn get_proof_by_index(
+        &self,
+        mut index: usize,
+        hashfn: &dyn HashFn<BabyBear>,
+    ) -> Vec<Digest> {
+        let mut proof: Vec<Digest> = Vec::with_capacity(self.depth);
+
+        let mut cur: Digest = self.leaves[index];
+        for i in 0..self.depth {
+            let sibling_start = (index ^ 1) << i;
+            let sibling_end = sibling_start + (1 << i);
+            let sibling = self.calc_range_root(sibling_start, sibling_end, hashfn);
+            cur = if index & 1 == 0 {
+                *hashfn.hash_pair(&cur, &sibling)
+            } else {
+                *hashfn.hash_pair(&sibling, &cur)
+            };
+            proof.push(sibling);
+            index >>= 1;
+        }
+
+        proof
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkp::core::hash::poseidon2::Poseidon2HashSuite;
+
+    use super::*;
+
+    fn shared_levels(a: &[Digest], b: &[Digest]) -> usize {
+        a.iter()
+            .rev()
+            .zip(b.iter().rev())
+            .position(|(a_elem, b_elem)| a_elem != b_elem)
+            .unwrap_or(std::cmp::min(a.len(), b.len()))
+    }
+
+    #[test]
+    fn basics() {
+        let digest1 = Digest::new([1, 2, 3, 4, 5, 6, 7, 8]);
+        let digest2 = Digest::new([9, 10, 11, 12, 13, 14, 15, 16]);
+        let digest3 = Digest::new([17, 18, 19, 20, 21, 22, 23, 24]);
+
+        let suite = Poseidon2HashSuite::new_suite();
+        let hashfn = suite.hashfn.as_ref();
+
+        let grp = MerkleGroup {
+            depth: 4,
+            leaves: Vec::from([digest1, digest2, digest3]),
+        };
+        let root = grp.calc_root(hashfn);
+        tracing::trace!("Root: {root:?}");
+        let proof1 = grp.get_proof_by_index(0, hashfn);
+        tracing::trace!("Proof1: {proof1:?}");
+        let proof2 = grp.get_proof_by_index(1, hashfn);
+        tracing::trace!("Proof2: {proof2:?}");
+        let proof3 = grp.get_proof_by_index(2, hashfn);
+        tracing::trace!("Proof3: {proof3:?}");
+
+        // Digest1 and digest2 should share 3 levels of proof, whereas proof2 and proof3
+        // should only share 2
+        assert_eq!(shared_levels(&proof1, &proof2), 3);
+        assert_eq!(shared_levels(&proof2, &proof3), 2);
+        assert_eq!(shared_levels(&proof1, &proof3), 2);
+
+        for (index, leaf) in grp.leaves.iter().enumerate() {
+            let proof = MerkleProof {
+                index,
+                digests: grp.get_proof_by_index(index, hashfn),
+            };
+            proof.verify(leaf, &root, hashfn).unwrap();
+            assert!(proof.verify(&digest1, &Digest::ZERO, hashfn).is_err());
+        }
+        let proof = MerkleProof {
+            index: 16,
+            digests: proof1,
+        };
+        assert!(proof.verify(&digest1, &root, hashfn).is_err());
+    }
+}
@@ -0,0 +1,73 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+use anyhow::Result;
+use hex::FromHex;
+use risc0_circuit_recursion::control_id::{ALLOWED_CONTROL_ROOT, BN254_CONTROL_ID};
+use risc0_groth16::{fr_from_hex_string, split_digest, PlonkSeal};
+use risc0_zkp::{core::digest::Digest, verify::VerificationError};
+use serde::{Deserialize, Serialize};
+
+use crate::{sha::Digestible, ReceiptClaim, VerifierContext};
+
+/// A receipt composed of a PLONK proof over the BN_254 curve.
+///
+/// This wraps the same claim as a [CompactReceipt](crate::CompactReceipt), in
+/// a proof system with a universal setup. Its verifying key is determined by
+/// the circuit and the powers-of-tau ceremony, and must be supplied through
+/// [VerifierContext::with_plonk_verifying_key].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct PlonkReceipt {
+    /// A PLONK proof of a zkVM execution with the associated claim.
+    pub seal: Vec<u8>,
+
+    /// [ReceiptClaim] containing information about the execution that this
+    /// receipt proves.
+    pub claim: ReceiptClaim,
+}
+
+impl PlonkReceipt {
+    /// Verify the integrity of this receipt, ensuring the claim is attested
+    /// to by the seal.
+    pub fn verify_integrity_with_context(
+        &self,
+        ctx: &VerifierContext,
+    ) -> Result<(), VerificationError> {
+        ctx.limits.check_seal_len(self.seal.len())?;
+        if !ctx.policy.allow_hash_suite("plonk") {
+            return Err(VerificationError::InvalidHashSuite);
+        }
+        let vk = ctx
+            .plonk_verifying_key
+            .as_ref()
+            .ok_or(VerificationError::MissingVerifyingKey)?;
+        let control_root = Digest::from_hex(ALLOWED_CONTROL_ROOT)
+            .map_err(|_| VerificationError::ReceiptFormatError)?;
+        ctx.check_control_root(&control_root)?;
+        let (a0, a1) =
+            split_digest(control_root).map_err(|_| VerificationError::ReceiptFormatError)?;
+        let (c0, c1) =
+            split_digest(self.claim.digest()).map_err(|_| VerificationError::ReceiptFormatError)?;
+        let id_p254_hash = fr_from_hex_string(BN254_CONTROL_ID)
+            .map_err(|_| VerificationError::ReceiptFormatError)?;
+        let seal =
+            PlonkSeal::from_vec(&self.seal).map_err(|_| VerificationError::ReceiptFormatError)?;
+        vk.verify(&seal, &[a0, a1, c0, c1, id_p254_hash])
+            .map_err(|_| VerificationError::InvalidProof)
+    }
+}
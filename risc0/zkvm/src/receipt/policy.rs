@@ -0,0 +1,177 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::{collections::BTreeSet, string::String};
+use core::ops::RangeInclusive;
+
+use hex::FromHex;
+use risc0_circuit_recursion::control_id::ALLOWED_CONTROL_ROOT;
+use risc0_circuit_rv32im::control_id::{
+    BLAKE2B_CONTROL_ID, POSEIDON2_CONTROL_ID, SHA256_CONTROL_ID,
+};
+use risc0_zkp::{core::digest::Digest, MAX_CYCLES_PO2};
+
+use super::succinct::valid_control_ids;
+
+/// Decides which receipts a [VerifierContext](crate::VerifierContext) accepts.
+///
+/// Each check is made before the seal it applies to is verified, and a
+/// receipt is rejected unless every check passes. Implementations should deny
+/// anything they do not explicitly allow.
+pub trait VerifierPolicy {
+    /// Whether seals may be verified with the named hash suite, such as
+    /// `poseidon2`, or with the named SNARK, `groth16` or `plonk`.
+    fn allow_hash_suite(&self, hashfn: &str) -> bool;
+
+    /// Whether a seal of the circuit identified by `control_id`, over a trace
+    /// of `2^po2` rows, is acceptable.
+    ///
+    /// This is checked for the rv32im circuit of segment receipts and for the
+    /// recursion programs of succinct receipts.
+    fn allow_control_id(&self, control_id: &Digest, po2: u32) -> bool;
+
+    /// Whether a receipt produced by recursion programs from the tree with the
+    /// given root is acceptable.
+    fn allow_control_root(&self, control_root: &Digest) -> bool;
+}
+
+/// A [VerifierPolicy] that allows only what has been added to it.
+///
+/// [AllowListPolicy::default] allows nothing, and [AllowListPolicy::current]
+/// allows the circuits of this release, which is the policy of
+/// [VerifierContext::default](crate::VerifierContext::default).
+#[derive(Clone, Debug, Default)]
+pub struct AllowListPolicy {
+    hash_suites: BTreeSet<String>,
+    control_ids: BTreeSet<Digest>,
+    control_roots: BTreeSet<Digest>,
+    po2: Option<RangeInclusive<u32>>,
+}
+
+impl AllowListPolicy {
+    /// Allow the hash suites, control IDs and control root of this release,
+    /// for any supported po2.
+    pub fn current() -> Self {
+        Self::release(
+            current_control_ids(),
+            Digest::from_hex(ALLOWED_CONTROL_ROOT).unwrap(),
+        )
+    }
+
+    // Allow the hash suites of this release, with the given circuits and control root.
+    pub(crate) fn release(
+        control_ids: impl IntoIterator<Item = Digest>,
+        control_root: Digest,
+    ) -> Self {
+        let mut policy = Self::default()
+            .with_po2_range(0..=MAX_CYCLES_PO2 as u32)
+            .with_control_root(control_root);
+        for hashfn in ["blake2b", "poseidon2", "sha-256", "groth16", "plonk"] {
+            policy = policy.with_hash_suite(hashfn);
+        }
+        for control_id in control_ids {
+            policy = policy.with_control_id(control_id);
+        }
+        policy
+    }
+
+    /// Allow the named hash suite.
+    pub fn with_hash_suite(mut self, hashfn: &str) -> Self {
+        self.hash_suites.insert(hashfn.into());
+        self
+    }
+
+    /// Allow the circuit or recursion program with the given control ID.
+    pub fn with_control_id(mut self, control_id: Digest) -> Self {
+        self.control_ids.insert(control_id);
+        self
+    }
+
+    /// Allow receipts from the recursion programs with the given control root.
+    pub fn with_control_root(mut self, control_root: Digest) -> Self {
+        self.control_roots.insert(control_root);
+        self
+    }
+
+    /// Allow only seals whose po2 is in the given range, replacing any range
+    /// set before.
+    pub fn with_po2_range(mut self, po2: RangeInclusive<u32>) -> Self {
+        self.po2 = Some(po2);
+        self
+    }
+}
+
+// The control IDs of the rv32im circuits and recursion programs of this release.
+pub(crate) fn current_control_ids() -> impl Iterator<Item = Digest> {
+    POSEIDON2_CONTROL_ID
+        .into_iter()
+        .chain(SHA256_CONTROL_ID)
+        .chain(BLAKE2B_CONTROL_ID)
+        .map(|x| Digest::from_hex(x).unwrap())
+        .chain(valid_control_ids())
+}
+
+impl VerifierPolicy for AllowListPolicy {
+    fn allow_hash_suite(&self, hashfn: &str) -> bool {
+        self.hash_suites.contains(hashfn)
+    }
+
+    fn allow_control_id(&self, control_id: &Digest, po2: u32) -> bool {
+        self.po2.as_ref().is_some_and(|range| range.contains(&po2))
+            && self.control_ids.contains(control_id)
+    }
+
+    fn allow_control_root(&self, control_root: &Digest) -> bool {
+        self.control_roots.contains(control_root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex::FromHex;
+    use risc0_circuit_recursion::control_id::{ALLOWED_CONTROL_IDS, ALLOWED_CONTROL_ROOT};
+    use risc0_circuit_rv32im::control_id::POSEIDON2_CONTROL_ID;
+    use risc0_zkp::core::digest::Digest;
+
+    use super::{AllowListPolicy, VerifierPolicy};
+
+    #[test]
+    fn default_denies_everything() {
+        let policy = AllowListPolicy::default();
+        let control_id = Digest::from_hex(POSEIDON2_CONTROL_ID[0]).unwrap();
+        assert!(!policy.allow_hash_suite("poseidon2"));
+        assert!(!policy.allow_control_id(&control_id, 16));
+        assert!(!policy.allow_control_root(&Digest::from_hex(ALLOWED_CONTROL_ROOT).unwrap()));
+
+        // A control ID alone is not enough without a po2 range.
+        let policy = policy.with_control_id(control_id);
+        assert!(!policy.allow_control_id(&control_id, 16));
+        let policy = policy.with_po2_range(14..=20);
+        assert!(policy.allow_control_id(&control_id, 16));
+        assert!(!policy.allow_control_id(&control_id, 21));
+    }
+
+    #[test]
+    fn current_allows_release() {
+        let policy = AllowListPolicy::current();
+        for hashfn in ["poseidon2", "sha-256", "groth16", "plonk"] {
+            assert!(policy.allow_hash_suite(hashfn));
+        }
+        assert!(!policy.allow_hash_suite("md5"));
+        assert!(policy.allow_control_id(&Digest::from_hex(ALLOWED_CONTROL_IDS[0]).unwrap(), 0));
+        assert!(!policy.allow_control_id(&Digest::ZERO, 16));
+        assert!(policy.allow_control_root(&Digest::from_hex(ALLOWED_CONTROL_ROOT).unwrap()));
+        assert!(!policy.allow_control_root(&Digest::ZERO));
+    }
+}
@@ -16,15 +16,9 @@ use alloc::{string::String, vec::Vec};
 use core::fmt::Debug;
 
 use anyhow::Result;
-use hex::FromHex;
-use risc0_binfmt::{ExitCode, SystemState};
-use risc0_circuit_rv32im::{
-    control_id::{BLAKE2B_CONTROL_ID, POSEIDON2_CONTROL_ID, SHA256_CONTROL_ID},
-    layout, CircuitImpl, CIRCUIT,
-};
-use risc0_zkp::{
-    adapter::CircuitInfo as _, core::digest::Digest, layout::Buffer, verify::VerificationError,
-};
+use risc0_circuit_rv32im::CIRCUIT;
+use risc0_zkp::{core::digest::Digest, verify::VerificationError};
+use risc0_zkvm_platform::WORD_SIZE;
 use serde::{Deserialize, Serialize};
 
 // Make succinct receipt available through this `receipt` module.
@@ -58,14 +52,6 @@ pub struct SegmentReceipt {
 }
 
 impl SegmentReceipt {
-    fn allowed_control_ids() -> impl Iterator<Item = Digest> {
-        POSEIDON2_CONTROL_ID
-            .into_iter()
-            .chain(SHA256_CONTROL_ID)
-            .chain(BLAKE2B_CONTROL_ID)
-            .map(|x| Digest::from_hex(x).unwrap())
-    }
-
     /// Verify the integrity of this receipt, ensuring the claim is attested
     /// to by the seal.
     pub fn verify_integrity_with_context(
@@ -73,18 +59,9 @@ impl SegmentReceipt {
         ctx: &VerifierContext,
     ) -> Result<(), VerificationError> {
         tracing::debug!("SegmentReceipt::verify_integrity_with_context");
-        let check_code = |_, control_id: &Digest| -> Result<(), VerificationError> {
-            Self::allowed_control_ids()
-                .find(|x| x == control_id)
-                .map(|_| ())
-                .ok_or(VerificationError::ControlVerificationError {
-                    control_id: *control_id,
-                })
-        };
-        let suite = ctx
-            .suites
-            .get(&self.hashfn)
-            .ok_or(VerificationError::InvalidHashSuite)?;
+        ctx.limits.check_seal_len(self.seal.len() * WORD_SIZE)?;
+        let check_code = |po2, control_id: &Digest| ctx.check_control_id(control_id, po2);
+        let suite = ctx.suite(&self.hashfn)?;
         risc0_zkp::verify::verify(&CIRCUIT, suite, &self.seal, check_code)?;
 
         // Receipt is consistent with the claim encoded on the seal. Now check against the
@@ -107,48 +84,10 @@ impl SegmentReceipt {
     }
 }
 
-fn decode_system_state_from_io(
-    io: layout::OutBuffer,
-    sys_state: &layout::SystemState,
-) -> Result<SystemState, VerificationError> {
-    let bytes: Vec<u8> = io
-        .tree(sys_state.image_id)
-        .get_bytes()
-        .or(Err(VerificationError::ReceiptFormatError))?;
-    let pc = io
-        .tree(sys_state.pc)
-        .get_u32()
-        .or(Err(VerificationError::ReceiptFormatError))?;
-    let merkle_root = Digest::try_from(bytes).or(Err(VerificationError::ReceiptFormatError))?;
-    Ok(SystemState { pc, merkle_root })
-}
-
 pub(crate) fn decode_receipt_claim_from_seal(
     seal: &[u32],
 ) -> Result<ReceiptClaim, VerificationError> {
-    let elems = bytemuck::checked::cast_slice(&seal[..CircuitImpl::OUTPUT_SIZE]);
-    let io = layout::OutBuffer(elems);
-    let body = layout::LAYOUT.mux.body;
-    let pre = decode_system_state_from_io(io, body.global.pre)?;
-    let post = decode_system_state_from_io(io, body.global.post)?;
-
-    let input_bytes: Vec<u8> = io
-        .tree(body.global.input)
-        .get_bytes()
-        .or(Err(VerificationError::ReceiptFormatError))?;
-    let input = Digest::try_from(input_bytes).or(Err(VerificationError::ReceiptFormatError))?;
-
-    let output_bytes: Vec<u8> = io
-        .tree(body.global.output)
-        .get_bytes()
-        .or(Err(VerificationError::ReceiptFormatError))?;
-    let output = Digest::try_from(output_bytes).or(Err(VerificationError::ReceiptFormatError))?;
-
-    let sys_exit = io.get_u64(body.global.sys_exit_code) as u32;
-    let user_exit = io.get_u64(body.global.user_exit_code) as u32;
-    let exit_code =
-        ExitCode::from_pair(sys_exit, user_exit).or(Err(VerificationError::ReceiptFormatError))?;
-
+    let (pre, post, exit_code, input, output) = risc0_verifier::decode_segment_claim(seal)?;
     Ok(ReceiptClaim {
         pre: pre.into(),
         post: post.into(),
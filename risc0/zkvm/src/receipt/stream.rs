@@ -0,0 +1,449 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A chunked wire format for [CompositeReceipt]s.
+//!
+//! A stream starts with a header and a [StreamManifest], followed by one chunk
+//! per segment receipt:
+//!
+//! ```text
+//! "R0RS" | version: u32 | manifest frame | chunk frame 0 | chunk frame 1 | ...
+//! ```
+//!
+//! Each frame is a little-endian `u32` length followed by that many bytes of
+//! the value, encoded with [crate::serde] as little-endian words. The manifest
+//! lists the length and SHA-256 digest of every chunk, along with the journal
+//! and the assumption receipts, so that a chunk can be checked as soon as it
+//! arrives and a download can resume at any chunk boundary.
+
+use std::io::{Read, Write};
+
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use risc0_zkp::{core::digest::Digest, verify::VerificationError};
+use risc0_zkvm_platform::WORD_SIZE;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::{
+    check_claim, CompositeReceipt, InnerReceipt, Journal, Receipt, ReceiptAnnotations,
+    SegmentReceipt, VerifierContext,
+};
+use crate::{
+    serde::{from_slice, to_vec},
+    sha::{self, Digestible, Sha256},
+};
+
+const MAGIC: &[u8; 4] = b"R0RS";
+const VERSION: u32 = 1;
+const HEADER_LEN: u64 = 8;
+const FRAME_HEADER_LEN: u64 = 4;
+
+/// The length and digest of one chunk of a receipt stream.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ChunkInfo {
+    /// Length in bytes of the encoded segment receipt.
+    pub len: u32,
+
+    /// SHA-256 digest of the encoded segment receipt.
+    pub digest: Digest,
+}
+
+/// Everything in a receipt stream apart from the segment receipts.
+///
+/// The manifest is not authenticated by itself: its chunk digests only catch
+/// corruption in transfer. The receipt it describes is verified as usual, as
+/// its chunks are read by a [ReceiptStreamReader].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StreamManifest {
+    /// One entry per segment receipt, in order.
+    pub chunks: Vec<ChunkInfo>,
+
+    /// The assumption receipts of the [CompositeReceipt].
+    pub assumptions: Vec<InnerReceipt>,
+
+    /// The journal of the [Receipt].
+    pub journal: Journal,
+
+    /// The journal digest of the [CompositeReceipt].
+    pub journal_digest: Option<Digest>,
+
+    /// The annotations of the [Receipt].
+    pub annotations: ReceiptAnnotations,
+}
+
+impl StreamManifest {
+    /// Total length in bytes of the chunk frames.
+    pub fn chunks_len(&self) -> u64 {
+        self.chunks
+            .iter()
+            .map(|chunk| FRAME_HEADER_LEN + chunk.len as u64)
+            .sum()
+    }
+}
+
+/// Writes a [Receipt] with a [CompositeReceipt] as a chunked stream.
+pub struct ReceiptStreamWriter<'a, W: Write> {
+    inner: W,
+    segments: &'a [SegmentReceipt],
+    manifest: StreamManifest,
+    manifest_len: u64,
+    next: usize,
+}
+
+impl<'a, W: Write> ReceiptStreamWriter<'a, W> {
+    /// Start a stream of the given receipt, writing its header and manifest.
+    pub fn new(inner: W, receipt: &'a Receipt) -> Result<Self> {
+        let mut writer = Self::resume(inner, receipt, 0)?;
+        let manifest = encode(&writer.manifest)?;
+        writer.inner.write_all(MAGIC)?;
+        writer.inner.write_all(&VERSION.to_le_bytes())?;
+        write_frame(&mut writer.inner, &manifest)?;
+        Ok(writer)
+    }
+
+    /// Continue a stream of the given receipt at chunk `next`, for a reader
+    /// that already has the header, the manifest and the chunks before it.
+    ///
+    /// Nothing is written until [ReceiptStreamWriter::write_chunk] is called.
+    pub fn resume(inner: W, receipt: &'a Receipt, next: usize) -> Result<Self> {
+        let composite = receipt.inner.composite()?;
+        ensure!(
+            next <= composite.segments.len(),
+            "chunk {next} is past the end of a stream of {} chunks",
+            composite.segments.len()
+        );
+        let chunks = composite
+            .segments
+            .iter()
+            .map(|segment| {
+                let bytes = encode(segment)?;
+                Ok(ChunkInfo {
+                    len: bytes.len().try_into()?,
+                    digest: *sha::Impl::hash_bytes(&bytes),
+                })
+            })
+            .collect::<Result<_>>()?;
+        let manifest = StreamManifest {
+            chunks,
+            assumptions: composite.assumptions.clone(),
+            journal: receipt.journal.clone(),
+            journal_digest: composite.journal_digest,
+            annotations: receipt.annotations.clone(),
+        };
+        let manifest_len = encode(&manifest)?.len() as u64;
+        Ok(Self {
+            inner,
+            segments: &composite.segments,
+            manifest,
+            manifest_len,
+            next,
+        })
+    }
+
+    /// The manifest of this stream.
+    pub fn manifest(&self) -> &StreamManifest {
+        &self.manifest
+    }
+
+    /// The byte offset in the stream of the next chunk to be written.
+    pub fn offset(&self) -> u64 {
+        chunk_offset(&self.manifest, self.manifest_len, self.next)
+    }
+
+    /// Write the next chunk, returning false if all chunks have been written.
+    pub fn write_chunk(&mut self) -> Result<bool> {
+        let Some(segment) = self.segments.get(self.next) else {
+            return Ok(false);
+        };
+        write_frame(&mut self.inner, &encode(segment)?)?;
+        self.next += 1;
+        Ok(true)
+    }
+
+    /// Write the remaining chunks and flush the stream.
+    pub fn finish(mut self) -> Result<W> {
+        while self.write_chunk()? {}
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+/// Reads a receipt stream, verifying each segment receipt as it arrives.
+///
+/// The segments read so far stay verified when the underlying reader fails, so
+/// that an interrupted download can continue with
+/// [ReceiptStreamReader::resume] from [ReceiptStreamReader::offset], rather
+/// than from the start.
+pub struct ReceiptStreamReader<'a, R: Read> {
+    inner: R,
+    ctx: &'a VerifierContext,
+    manifest: StreamManifest,
+    manifest_len: u64,
+    receipt: CompositeReceipt,
+}
+
+impl<'a, R: Read> ReceiptStreamReader<'a, R> {
+    /// Start reading a stream, reading its header and manifest and verifying
+    /// the assumption receipts.
+    pub fn new(mut inner: R, ctx: &'a VerifierContext) -> Result<Self> {
+        let mut header = [0u8; HEADER_LEN as usize];
+        inner
+            .read_exact(&mut header)
+            .context("failed to read receipt stream header")?;
+        ensure!(&header[..4] == MAGIC, "not a receipt stream");
+        let version = u32::from_le_bytes(header[4..].try_into()?);
+        ensure!(
+            version == VERSION,
+            "unsupported receipt stream version: {version}"
+        );
+
+        let bytes = read_frame(&mut inner, None)?;
+        let manifest: StreamManifest = decode(&bytes).context("invalid receipt stream manifest")?;
+        ensure!(
+            !manifest.chunks.is_empty(),
+            "receipt stream has no segments"
+        );
+        let receipt = CompositeReceipt {
+            segments: Vec::with_capacity(manifest.chunks.len()),
+            assumptions: manifest.assumptions.clone(),
+            journal_digest: manifest.journal_digest,
+        };
+        receipt.verify_assumptions_with_context(ctx)?;
+        Ok(Self {
+            inner,
+            ctx,
+            manifest,
+            manifest_len: bytes.len() as u64,
+            receipt,
+        })
+    }
+
+    /// The manifest of this stream.
+    pub fn manifest(&self) -> &StreamManifest {
+        &self.manifest
+    }
+
+    /// The segment receipts read and verified so far.
+    pub fn segments(&self) -> &[SegmentReceipt] {
+        &self.receipt.segments
+    }
+
+    /// The byte offset in the stream of the next chunk to be read.
+    pub fn offset(&self) -> u64 {
+        chunk_offset(
+            &self.manifest,
+            self.manifest_len,
+            self.receipt.segments.len(),
+        )
+    }
+
+    /// Continue reading from `inner`, which must start at
+    /// [ReceiptStreamReader::offset] of the stream.
+    pub fn resume(&mut self, inner: R) {
+        self.inner = inner;
+    }
+
+    /// Read and verify the next segment receipt, including its chaining to the
+    /// segment before it. Returns `None` once all segments have been read.
+    pub fn read_segment(&mut self) -> Result<Option<&SegmentReceipt>> {
+        let index = self.receipt.segments.len();
+        let Some(chunk) = self.manifest.chunks.get(index).copied() else {
+            return Ok(None);
+        };
+        let bytes = read_frame(&mut self.inner, Some(chunk.len))
+            .with_context(|| format!("failed to read chunk {index}"))?;
+        if *sha::Impl::hash_bytes(&bytes) != chunk.digest {
+            bail!("digest mismatch for chunk {index}");
+        }
+        let segment: SegmentReceipt =
+            decode(&bytes).with_context(|| format!("invalid chunk {index}"))?;
+
+        self.receipt.segments.push(segment);
+        let verified = self
+            .receipt
+            .verify_segment_with_context(index, self.ctx)
+            .and_then(|()| match index.checked_sub(1) {
+                Some(prev) => self.receipt.check_segment_chaining(prev),
+                None => Ok(()),
+            });
+        if let Err(err) = verified {
+            self.receipt.segments.pop();
+            return Err(anyhow!(err).context(format!("failed to verify segment {index}")));
+        }
+        Ok(self.receipt.segments.last())
+    }
+
+    /// Read the remaining segments, and check that the receipt proves a
+    /// successful execution from the given `image_id`, as in
+    /// [Receipt::verify_with_context].
+    pub fn finish(mut self, image_id: impl Into<Digest>) -> Result<Receipt> {
+        while self.read_segment()?.is_some() {}
+        let claim = self.receipt.claim()?;
+        check_claim(&claim, image_id.into(), self.manifest.journal.digest())?;
+        let mut receipt = Receipt::new(
+            InnerReceipt::Composite(self.receipt),
+            self.manifest.journal.bytes,
+        );
+        receipt.annotations = self.manifest.annotations;
+        Ok(receipt)
+    }
+}
+
+fn chunk_offset(manifest: &StreamManifest, manifest_len: u64, index: usize) -> u64 {
+    HEADER_LEN
+        + FRAME_HEADER_LEN
+        + manifest_len
+        + manifest.chunks[..index]
+            .iter()
+            .map(|chunk| FRAME_HEADER_LEN + chunk.len as u64)
+            .sum::<u64>()
+}
+
+fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    Ok(to_vec(value)?
+        .into_iter()
+        .flat_map(u32::to_le_bytes)
+        .collect())
+}
+
+fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    if bytes.len() % WORD_SIZE != 0 {
+        return Err(VerificationError::ReceiptFormatError.into());
+    }
+    let words: Vec<u32> = bytes
+        .chunks_exact(WORD_SIZE)
+        .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+        .collect();
+    Ok(from_slice(&words)?)
+}
+
+fn write_frame(writer: &mut impl Write, bytes: &[u8]) -> Result<()> {
+    let len: u32 = bytes.len().try_into()?;
+    writer.write_all(&len.to_le_bytes())?;
+    writer.write_all(bytes)?;
+    Ok(())
+}
+
+fn read_frame(reader: &mut impl Read, expected_len: Option<u32>) -> Result<Vec<u8>> {
+    let mut len = [0u8; FRAME_HEADER_LEN as usize];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len);
+    if let Some(expected_len) = expected_len {
+        ensure!(
+            len == expected_len,
+            "frame of {len} bytes, expected {expected_len}"
+        );
+    }
+    let mut bytes = Vec::new();
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    ensure!(bytes.len() == len as usize, "truncated frame");
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_binfmt::{ExitCode, SystemState};
+    use risc0_zkp::core::digest::Digest;
+
+    use super::{ReceiptStreamReader, ReceiptStreamWriter};
+    use crate::{
+        CompositeReceipt, InnerReceipt, Receipt, ReceiptClaim, SegmentReceipt, VerifierContext,
+    };
+
+    fn receipt() -> Receipt {
+        let segment = |index: u32, pre: u32, post: u32, exit_code| SegmentReceipt {
+            seal: vec![index; 16],
+            index,
+            hashfn: "poseidon2".into(),
+            claim: ReceiptClaim {
+                pre: SystemState {
+                    pc: pre,
+                    merkle_root: Digest::ZERO,
+                }
+                .into(),
+                post: SystemState {
+                    pc: post,
+                    merkle_root: Digest::ZERO,
+                }
+                .into(),
+                exit_code,
+                input: Digest::ZERO,
+                output: None.into(),
+            },
+        };
+        let composite = CompositeReceipt {
+            segments: vec![
+                segment(0, 0, 4, ExitCode::SystemSplit),
+                segment(1, 4, 8, ExitCode::SystemSplit),
+                segment(2, 8, 12, ExitCode::Halted(0)),
+            ],
+            assumptions: vec![],
+            journal_digest: None,
+        };
+        Receipt::new(InnerReceipt::Composite(composite), vec![])
+    }
+
+    fn stream(receipt: &Receipt) -> Vec<u8> {
+        ReceiptStreamWriter::new(Vec::new(), receipt)
+            .unwrap()
+            .finish()
+            .unwrap()
+    }
+
+    #[test]
+    fn resume_writer() {
+        let receipt = receipt();
+        let bytes = stream(&receipt);
+
+        let mut writer = ReceiptStreamWriter::new(Vec::new(), &receipt).unwrap();
+        writer.write_chunk().unwrap();
+        let offset = writer.offset() as usize;
+        assert_eq!(writer.finish().unwrap(), bytes);
+
+        let tail = ReceiptStreamWriter::resume(Vec::new(), &receipt, 1)
+            .unwrap()
+            .finish()
+            .unwrap();
+        assert_eq!(tail, bytes[offset..]);
+        assert_eq!(
+            ReceiptStreamWriter::resume(Vec::new(), &receipt, 3)
+                .unwrap()
+                .offset(),
+            bytes.len() as u64
+        );
+    }
+
+    #[test]
+    fn corrupt_chunk() {
+        let receipt = receipt();
+        let mut bytes = stream(&receipt);
+        let ctx = VerifierContext::default();
+        let reader = ReceiptStreamReader::new(bytes.as_slice(), &ctx).unwrap();
+        assert_eq!(reader.manifest().chunks.len(), 3);
+        let offset = reader.offset() as usize;
+        assert_eq!(
+            offset as u64 + reader.manifest().chunks_len(),
+            bytes.len() as u64
+        );
+
+        // The corrupted chunk is rejected before its seal is verified.
+        bytes[offset + 8] ^= 1;
+        let mut reader = ReceiptStreamReader::new(bytes.as_slice(), &ctx).unwrap();
+        let err = reader.read_segment().unwrap_err();
+        assert_eq!(err.to_string(), "digest mismatch for chunk 0");
+        assert!(reader.segments().is_empty());
+
+        bytes[..4].copy_from_slice(b"ABCD");
+        assert!(ReceiptStreamReader::new(bytes.as_slice(), &ctx).is_err());
+    }
+}
@@ -16,14 +16,17 @@ use alloc::{collections::VecDeque, vec::Vec};
 
 use hex::FromHex;
 use risc0_binfmt::read_sha_halfs;
-use risc0_circuit_recursion::{
-    control_id::{ALLOWED_CONTROL_IDS, ALLOWED_CONTROL_ROOT},
-    CircuitImpl, CIRCUIT,
-};
+use risc0_circuit_recursion::{control_id::ALLOWED_CONTROL_IDS, CircuitImpl, CIRCUIT};
 use risc0_core::field::baby_bear::BabyBearElem;
-use risc0_zkp::{adapter::CircuitInfo, core::digest::Digest, verify::VerificationError};
+use risc0_zkp::{
+    adapter::CircuitInfo,
+    core::{digest::Digest, hash::poseidon2::Poseidon2HashSuite},
+    verify::VerificationError,
+};
+use risc0_zkvm_platform::WORD_SIZE;
 use serde::{Deserialize, Serialize};
 
+use super::merkle::{MerkleGroup, MerkleProof, ALLOWED_CODE_MERKLE_DEPTH};
 use crate::{receipt::VerifierContext, sha::Digestible, ReceiptClaim};
 
 /// Return the allowed Control IDs that can be used by a zkr program.
@@ -67,25 +70,15 @@ impl SuccinctReceipt {
         &self,
         ctx: &VerifierContext,
     ) -> Result<(), VerificationError> {
-        // Assemble the list of control IDs, and therefore circuit variants, we will
-        // accept.
-        let valid_ids = valid_control_ids();
-        let check_code = |_, control_id: &Digest| -> Result<(), VerificationError> {
-            valid_ids
-                .iter()
-                .find(|x| *x == control_id)
-                .map(|_| ())
-                .ok_or(VerificationError::ControlVerificationError {
-                    control_id: *control_id,
-                })
-        };
+        ctx.limits.check_seal_len(self.seal.len() * WORD_SIZE)?;
+
+        // The policy decides which recursion programs, and therefore circuit variants, we
+        // will accept.
+        let check_code = |po2, control_id: &Digest| ctx.check_control_id(control_id, po2);
 
         // All receipts from the recursion circuit use Poseidon2 as the FRI hash
         // function.
-        let suite = ctx
-            .suites
-            .get("poseidon2")
-            .ok_or(VerificationError::InvalidHashSuite)?;
+        let suite = ctx.suite("poseidon2")?;
 
         // Verify the receipt itself is correct, and therefore the encoded globals are
         // reliable.
@@ -110,15 +103,11 @@ impl SuccinctReceipt {
             .collect::<Vec<_>>()
             .try_into()
             .map_err(|_| VerificationError::ReceiptFormatError)?;
-        let allowed_root = Digest::from_hex(ALLOWED_CONTROL_ROOT).unwrap();
-        if control_root != allowed_root {
+        if let Err(err) = ctx.check_control_root(&control_root) {
             tracing::debug!(
-                "succinct receipt does not match the expected control root: decoded: {:#?}, expected: {allowed_root:?}",
-                control_root,
+                "succinct receipt control root is not allowed by the verifier policy: {control_root:?}"
             );
-            return Err(VerificationError::ControlVerificationError {
-                control_id: control_root,
-            });
+            return Err(err);
         }
 
         // Verify the output hash matches that data
@@ -139,4 +128,113 @@ impl SuccinctReceipt {
     pub fn get_seal_bytes(&self) -> Vec<u8> {
         self.seal.iter().flat_map(|x| x.to_le_bytes()).collect()
     }
+
+    /// Prove that the control ID of this receipt is in the tree of the given allowed control
+    /// IDs, built as the recursion programs build theirs.
+    ///
+    /// The root of the tree is the control root that a
+    /// [VerifierPolicy](crate::VerifierPolicy) allows, so a deployment with its own set of
+    /// recursion programs can show that a receipt was made by one of them without publishing the
+    /// whole set.
+    pub fn control_inclusion_proof(
+        &self,
+        control_ids: &[Digest],
+    ) -> Result<MerkleProof, VerificationError> {
+        allowed_tree(control_ids)?
+            .get_proof(
+                &self.control_id,
+                Poseidon2HashSuite::new_suite().hashfn.as_ref(),
+            )
+            .map_err(|_| VerificationError::ControlVerificationError {
+                control_id: self.control_id,
+            })
+    }
+
+    /// The root of the tree of the given allowed control IDs, which a
+    /// [VerifierPolicy](crate::VerifierPolicy) allows to accept receipts made by their recursion
+    /// programs.
+    pub fn allowed_control_root(control_ids: &[Digest]) -> Result<Digest, VerificationError> {
+        Ok(allowed_tree(control_ids)?.calc_root(Poseidon2HashSuite::new_suite().hashfn.as_ref()))
+    }
+
+    /// Verify that the proof places the control ID of this receipt in the tree of allowed
+    /// control IDs with the given root.
+    ///
+    /// This checks only the proof, not the seal, which is verified with
+    /// [SuccinctReceipt::verify_integrity_with_context].
+    pub fn verify_control_inclusion(
+        &self,
+        proof: &MerkleProof,
+        control_root: &Digest,
+    ) -> Result<(), VerificationError> {
+        if proof.digests.len() != ALLOWED_CODE_MERKLE_DEPTH {
+            return Err(VerificationError::ReceiptFormatError);
+        }
+        proof.verify(
+            &self.control_id,
+            control_root,
+            Poseidon2HashSuite::new_suite().hashfn.as_ref(),
+        )
+    }
+}
+
+fn allowed_tree(control_ids: &[Digest]) -> Result<MerkleGroup, VerificationError> {
+    if control_ids.len() > 1 << ALLOWED_CODE_MERKLE_DEPTH {
+        return Err(VerificationError::ReceiptFormatError);
+    }
+    Ok(MerkleGroup {
+        depth: ALLOWED_CODE_MERKLE_DEPTH,
+        leaves: control_ids.to_vec(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use hex::FromHex;
+    use risc0_circuit_recursion::control_id::ALLOWED_CONTROL_ROOT;
+    use risc0_zkp::core::digest::Digest;
+
+    use super::{valid_control_ids, SuccinctReceipt};
+    use crate::receipt::fake_claim;
+
+    fn receipt(control_id: Digest) -> SuccinctReceipt {
+        SuccinctReceipt {
+            seal: vec![],
+            control_id,
+            claim: fake_claim(b""),
+        }
+    }
+
+    #[test]
+    fn control_inclusion() {
+        let control_ids = valid_control_ids();
+        let receipt = receipt(control_ids[3]);
+        let proof = receipt.control_inclusion_proof(&control_ids).unwrap();
+        let allowed_root = Digest::from_hex(ALLOWED_CONTROL_ROOT).unwrap();
+        assert_eq!(
+            SuccinctReceipt::allowed_control_root(&control_ids).unwrap(),
+            allowed_root
+        );
+        receipt
+            .verify_control_inclusion(&proof, &allowed_root)
+            .unwrap();
+
+        // A custom set has its own root, which the proof for the built-in set does not open to.
+        let custom = vec![Digest::from([7; 8]), control_ids[3]];
+        let custom_proof = receipt.control_inclusion_proof(&custom).unwrap();
+        assert_eq!(custom_proof.index, 1);
+        let custom_root = SuccinctReceipt::allowed_control_root(&custom).unwrap();
+        receipt
+            .verify_control_inclusion(&custom_proof, &custom_root)
+            .unwrap();
+        assert!(receipt
+            .verify_control_inclusion(&proof, &custom_root)
+            .is_err());
+
+        let other = SuccinctReceipt {
+            control_id: Digest::ZERO,
+            ..receipt
+        };
+        assert!(other.control_inclusion_proof(&custom).is_err());
+    }
 }
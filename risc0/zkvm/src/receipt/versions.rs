@@ -0,0 +1,202 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::{collections::BTreeSet, string::String, vec::Vec};
+
+use hex::FromHex;
+use risc0_circuit_recursion::control_id::ALLOWED_CONTROL_ROOT;
+use risc0_zkp::{core::digest::Digest, verify::VerificationError};
+
+use super::policy::current_control_ids;
+use crate::{AllowListPolicy, InnerReceipt, Receipt, VerifierContext, VerifierLimits};
+
+/// The identifiers of the circuits of one release of the zkVM.
+///
+/// A receipt made by a release is verified against its control IDs, its
+/// control root and, for [CompactReceipt](crate::CompactReceipt)s, its Groth16
+/// parameters. Only releases whose circuits this build can verify should be
+/// registered: releases that change the control IDs or keys without changing
+/// the constraints of the circuits.
+#[derive(Clone, Debug)]
+pub struct CircuitVersion {
+    /// The release, such as `1.0`.
+    pub name: String,
+
+    /// The control IDs of the rv32im circuits and recursion programs.
+    pub control_ids: BTreeSet<Digest>,
+
+    /// The root of the tree of allowed recursion control IDs.
+    pub control_root: Digest,
+
+    /// The parameters that compact receipts are verified against.
+    #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
+    pub groth16_verifier_parameters: risc0_groth16::VerifierParameters,
+}
+
+impl CircuitVersion {
+    /// The circuits of this build.
+    pub fn current() -> Self {
+        Self {
+            name: crate::VERSION.into(),
+            control_ids: current_control_ids().collect(),
+            control_root: Digest::from_hex(ALLOWED_CONTROL_ROOT).unwrap(),
+            #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
+            groth16_verifier_parameters: crate::CompactReceipt::verifier_parameters().unwrap(),
+        }
+    }
+
+    /// A [VerifierContext] that accepts the receipts of this release only.
+    pub fn verifier_context(&self) -> VerifierContext {
+        let ctx = VerifierContext::default().with_policy(AllowListPolicy::release(
+            self.control_ids.iter().copied(),
+            self.control_root,
+        ));
+        #[cfg(any(not(target_os = "zkvm"), feature = "std"))]
+        let ctx = ctx.with_groth16_verifier_parameters(self.groth16_verifier_parameters.clone());
+        ctx
+    }
+}
+
+/// The circuit versions a verifier knows, and which of them it accepts.
+///
+/// This lets a service accept receipts from the previous release while
+/// provers are upgraded. Registering a version does not enable it: each
+/// version other than [CircuitVersion::current] is accepted only once it is
+/// enabled by name.
+#[derive(Clone, Debug, Default)]
+pub struct VersionRegistry {
+    versions: Vec<CircuitVersion>,
+    enabled: BTreeSet<String>,
+    limits: VerifierLimits,
+}
+
+impl VersionRegistry {
+    /// A registry of the versions bundled with this build, with only the
+    /// current version enabled.
+    pub fn bundled() -> Self {
+        let current = CircuitVersion::current();
+        let name = current.name.clone();
+        Self::default().with_version(current).with_enabled(&name)
+    }
+
+    /// Register a version. Versions are tried in the order they are
+    /// registered, so releases should be added from newest to oldest.
+    pub fn with_version(mut self, version: CircuitVersion) -> Self {
+        self.versions.push(version);
+        self
+    }
+
+    /// Accept receipts of the registered version with the given name.
+    pub fn with_enabled(mut self, name: &str) -> Self {
+        self.enabled.insert(name.into());
+        self
+    }
+
+    /// Apply the given limits when verifying with any version.
+    pub fn with_limits(mut self, limits: VerifierLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// The enabled versions, in the order they are tried.
+    pub fn enabled(&self) -> impl Iterator<Item = &CircuitVersion> {
+        self.versions
+            .iter()
+            .filter(|version| self.enabled.contains(&version.name))
+    }
+
+    /// Verify a receipt for the given image ID, as [Receipt::verify] does, with
+    /// the enabled version that produced it, and return that version.
+    ///
+    /// A [SuccinctReceipt](crate::SuccinctReceipt) is verified only with the
+    /// versions that include its control ID. Other receipts do not record the
+    /// version that produced them, so they are verified with each enabled
+    /// version in turn; a receipt of another version fails early, on its
+    /// control IDs or Groth16 parameters. When no version accepts the receipt,
+    /// the error is that of the first version whose control IDs it matches.
+    pub fn verify(
+        &self,
+        receipt: &Receipt,
+        image_id: impl Into<Digest>,
+    ) -> Result<&CircuitVersion, VerificationError> {
+        let image_id = image_id.into();
+        let (mut matched, mut last) = (None, None);
+        for version in self.enabled() {
+            if let InnerReceipt::Succinct(succinct) = &receipt.inner {
+                if !version.control_ids.contains(&succinct.control_id) {
+                    continue;
+                }
+            }
+            let ctx = version.verifier_context().with_limits(self.limits.clone());
+            match receipt.verify_with_context(&ctx, image_id) {
+                Ok(()) => return Ok(version),
+                Err(err @ VerificationError::ControlVerificationError { .. }) => last = Some(err),
+                Err(err) => {
+                    matched.get_or_insert(err);
+                }
+            }
+        }
+        if let Some(err) = matched.or(last) {
+            return Err(err);
+        }
+
+        // No enabled version can have produced the receipt: report why a verifier that
+        // accepts nothing rejects it. Only fake receipts in dev mode pass such a verifier.
+        let ctx = VerifierContext::default()
+            .with_policy(AllowListPolicy::default())
+            .with_limits(self.limits.clone());
+        Err(receipt
+            .verify_with_context(&ctx, image_id)
+            .err()
+            .unwrap_or(VerificationError::InvalidProof))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkp::core::digest::Digest;
+
+    use super::{CircuitVersion, VersionRegistry};
+
+    fn previous() -> CircuitVersion {
+        let mut version = CircuitVersion::current();
+        version.name = "0.0.1".into();
+        version.control_ids = [Digest::from([1; 8])].into();
+        version.control_root = Digest::from([2; 8]);
+        version
+    }
+
+    #[test]
+    fn opt_in() {
+        let registry = VersionRegistry::bundled().with_version(previous());
+        let names: Vec<_> = registry.enabled().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, [crate::VERSION]);
+
+        let registry = registry.with_enabled("0.0.1");
+        let names: Vec<_> = registry.enabled().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, [crate::VERSION, "0.0.1"]);
+    }
+
+    #[test]
+    fn version_context() {
+        let current = CircuitVersion::current();
+        let ctx = previous().verifier_context();
+        assert!(ctx.policy.allow_control_root(&Digest::from([2; 8])));
+        assert!(!ctx.policy.allow_control_root(&current.control_root));
+        assert!(ctx.policy.allow_control_id(&Digest::from([1; 8]), 16));
+        assert!(!ctx
+            .policy
+            .allow_control_id(current.control_ids.first().unwrap(), 16));
+    }
+}
@@ -19,7 +19,11 @@
 //! information such as the exit code and the starting and ending system state (i.e. the state of
 //! memory).
 
-use alloc::{collections::VecDeque, vec::Vec};
+use alloc::{
+    collections::{BTreeMap, VecDeque},
+    string::String,
+    vec::Vec,
+};
 use core::{fmt, ops::Deref};
 
 use anyhow::{anyhow, ensure};
@@ -64,6 +68,23 @@ pub struct ReceiptClaim {
 }
 
 impl ReceiptClaim {
+    /// Construct a [ReceiptClaim] for an execution of the given image ID that halted with exit
+    /// code 0, committing the given journal and no assumptions.
+    ///
+    /// The post state is not known from the image ID and journal, so it is left zeroed.
+    pub fn ok(image_id: impl Into<Digest>, journal: impl Into<MaybePruned<Vec<u8>>>) -> Self {
+        Self {
+            pre: MaybePruned::Pruned(image_id.into()),
+            post: MaybePruned::Value(SystemState {
+                pc: 0,
+                merkle_root: Digest::ZERO,
+            }),
+            exit_code: ExitCode::Halted(0),
+            input: Digest::ZERO,
+            output: Some(Output::new(journal.into(), Assumptions::default().into())).into(),
+        }
+    }
+
     /// Decode a [ReceiptClaim] from a list of [u32]'s
     pub fn decode(flat: &mut VecDeque<u32>) -> Result<Self, DecodeError> {
         let input = read_sha_halfs(flat)?;
@@ -98,6 +119,81 @@ impl ReceiptClaim {
         write_sha_halfs(flat, &self.output.digest::<sha::Impl>());
         Ok(())
     }
+
+    /// Summarize the claim, reducing each field to the digest or value available from it.
+    pub fn summary(&self) -> ClaimSummary {
+        let output = self.output.as_value().ok().map(Option::as_ref);
+        ClaimSummary {
+            image_id: self.pre.digest::<sha::Impl>(),
+            post_state_digest: self.post.digest::<sha::Impl>(),
+            exit_code: self.exit_code,
+            input: self.input,
+            output: self.output.digest::<sha::Impl>(),
+            journal: output.map(|output| output.map(|x| x.journal.digest::<sha::Impl>())),
+            assumptions: output.and_then(|output| match output {
+                Some(output) => output
+                    .assumptions
+                    .as_value()
+                    .ok()
+                    .map(|x| x.iter().map(|x| x.digest::<sha::Impl>()).collect()),
+                None => Some(Vec::new()),
+            }),
+        }
+    }
+}
+
+/// A summary of a [ReceiptClaim], as returned by [ReceiptClaim::summary].
+///
+/// The image ID, post state, input and output are always known by their digests, even when the
+/// claim is pruned. The journal digest and assumptions are only known if the output is not
+/// pruned, and `None` otherwise.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ClaimSummary {
+    /// Digest of the [SystemState] just before execution, which is the image ID.
+    pub image_id: Digest,
+
+    /// Digest of the [SystemState] just after execution.
+    pub post_state_digest: Digest,
+
+    /// The exit code for the execution.
+    pub exit_code: ExitCode,
+
+    /// Digest of the input to the guest.
+    pub input: Digest,
+
+    /// Digest of the [Output], which is zero if there is no output.
+    pub output: Digest,
+
+    /// Digest of the journal, which is `Some(None)` if there is no output.
+    pub journal: Option<Option<Digest>>,
+
+    /// Digests of the claims of the assumptions, in order.
+    pub assumptions: Option<Vec<Digest>>,
+}
+
+impl fmt::Display for ClaimSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "image id: {}", self.image_id)?;
+        writeln!(f, "post state: {}", self.post_state_digest)?;
+        writeln!(f, "exit code: {:?}", self.exit_code)?;
+        writeln!(f, "input: {}", self.input)?;
+        writeln!(f, "output: {}", self.output)?;
+        match self.journal {
+            Some(Some(journal)) => writeln!(f, "journal: {journal}")?,
+            Some(None) => writeln!(f, "journal: none")?,
+            None => writeln!(f, "journal: pruned")?,
+        }
+        match &self.assumptions {
+            Some(assumptions) => {
+                write!(f, "assumptions: {}", assumptions.len())?;
+                for assumption in assumptions {
+                    write!(f, "\n  {assumption}")?;
+                }
+                Ok(())
+            }
+            None => write!(f, "assumptions: pruned"),
+        }
+    }
 }
 
 impl Digestible for ReceiptClaim {
@@ -150,8 +246,12 @@ impl From<InvalidExitCodeError> for DecodeError {
 #[cfg(feature = "std")]
 impl std::error::Error for DecodeError {}
 
-/// Output field in the [ReceiptClaim], committing to a claimed journal and assumptions list.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Output field in the [ReceiptClaim], committing to a claimed journal, assumptions list,
+/// and named output channels.
+///
+/// An [Output] without channels is serialized exactly as it was before channels were added, so
+/// receipts serialized by earlier versions can still be deserialized.
+#[derive(Clone, Debug)]
 #[cfg_attr(test, derive(PartialEq))]
 pub struct Output {
     /// The journal committed to by the guest execution.
@@ -166,15 +266,212 @@ pub struct Output {
     /// be trusted to correspond to a genuine execution). The claims can be checked by additional
     /// verifying a [crate::Receipt] for every digest in the assumptions list.
     pub assumptions: MaybePruned<Assumptions>,
+
+    /// The named output channels committed to by the guest with `env::commit_channel`.
+    pub channels: MaybePruned<Channels>,
+}
+
+impl Output {
+    /// Construct an [Output] with the given journal and assumptions, and no channels.
+    pub fn new(journal: MaybePruned<Vec<u8>>, assumptions: MaybePruned<Assumptions>) -> Self {
+        Self {
+            journal,
+            assumptions,
+            channels: Channels::default().into(),
+        }
+    }
+
+    fn has_channels(&self) -> bool {
+        self.channels.digest::<sha::Impl>() != Digest::ZERO
+    }
+}
+
+// The encoding of an [Output] in binary formats. Outputs without channels use the first two
+// variants, whose tags and fields line up with the encoding of the journal's [MaybePruned]
+// followed by the assumptions. This is the encoding of the [Output] struct from before channels
+// were added.
+#[derive(Deserialize)]
+enum OutputEncoding {
+    Journal(Vec<u8>, MaybePruned<Assumptions>),
+    PrunedJournal(Digest, MaybePruned<Assumptions>),
+    WithChannels(
+        MaybePruned<Vec<u8>>,
+        MaybePruned<Assumptions>,
+        MaybePruned<Channels>,
+    ),
+}
+
+// The encoding of an [Output] in human-readable formats, in which the channels are left out when
+// there are none.
+#[derive(Deserialize)]
+struct OutputFields {
+    journal: MaybePruned<Vec<u8>>,
+    assumptions: MaybePruned<Assumptions>,
+    #[serde(default)]
+    channels: MaybePruned<Channels>,
+}
+
+impl Serialize for Output {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{SerializeStruct as _, SerializeTupleVariant as _};
+
+        if serializer.is_human_readable() {
+            let mut state = serializer.serialize_struct("Output", 3)?;
+            state.serialize_field("journal", &self.journal)?;
+            state.serialize_field("assumptions", &self.assumptions)?;
+            match self.has_channels() {
+                true => state.serialize_field("channels", &self.channels)?,
+                false => state.skip_field("channels")?,
+            }
+            return state.end();
+        }
+
+        let mut state = match (self.has_channels(), &self.journal) {
+            (false, MaybePruned::Value(journal)) => {
+                let mut state = serializer.serialize_tuple_variant("Output", 0, "Journal", 2)?;
+                state.serialize_field(journal)?;
+                state
+            }
+            (false, MaybePruned::Pruned(digest)) => {
+                let mut state =
+                    serializer.serialize_tuple_variant("Output", 1, "PrunedJournal", 2)?;
+                state.serialize_field(digest)?;
+                state
+            }
+            (true, journal) => {
+                let mut state =
+                    serializer.serialize_tuple_variant("Output", 2, "WithChannels", 3)?;
+                state.serialize_field(journal)?;
+                state
+            }
+        };
+        state.serialize_field(&self.assumptions)?;
+        if self.has_channels() {
+            state.serialize_field(&self.channels)?;
+        }
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Output {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let fields = OutputFields::deserialize(deserializer)?;
+            return Ok(Self {
+                journal: fields.journal,
+                assumptions: fields.assumptions,
+                channels: fields.channels,
+            });
+        }
+
+        Ok(match OutputEncoding::deserialize(deserializer)? {
+            OutputEncoding::Journal(journal, assumptions) => Self::new(journal.into(), assumptions),
+            OutputEncoding::PrunedJournal(digest, assumptions) => {
+                Self::new(MaybePruned::Pruned(digest), assumptions)
+            }
+            OutputEncoding::WithChannels(journal, assumptions, channels) => Self {
+                journal,
+                assumptions,
+                channels,
+            },
+        })
+    }
 }
 
 impl Digestible for Output {
     /// Hash the [Output] to get a digest of the struct.
+    ///
+    /// When there are no channels, the digest is the same as that of an [Output] with only a
+    /// journal and assumptions, so receipts of guests that do not use channels are unchanged.
     fn digest<S: Sha256>(&self) -> Digest {
-        tagged_struct::<S>(
-            "risc0.Output",
-            &[self.journal.digest::<S>(), self.assumptions.digest::<S>()],
-            &[],
+        let journal = self.journal.digest::<S>();
+        let assumptions = self.assumptions.digest::<S>();
+        let channels = self.channels.digest::<S>();
+        match channels == Digest::ZERO {
+            true => tagged_struct::<S>("risc0.Output", &[journal, assumptions], &[]),
+            false => tagged_struct::<S>("risc0.Output", &[journal, assumptions, channels], &[]),
+        }
+    }
+}
+
+impl MaybePruned<Option<Output>> {
+    /// The channels of the output, or no channels if there is no output or it is pruned.
+    pub(crate) fn channels(&self) -> MaybePruned<Channels> {
+        match self {
+            MaybePruned::Value(Some(output)) => output.channels.clone(),
+            _ => Channels::default().into(),
+        }
+    }
+}
+
+/// Named output channels, each committing to its own data.
+///
+/// Channels let a guest split its public output into independent parts, such as a result and a
+/// log of events. Each channel has its own digest within the [Output], so the data of channels a
+/// verifier does not need can be pruned without affecting the claim.
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct Channels(pub BTreeMap<String, MaybePruned<Vec<u8>>>);
+
+impl Channels {
+    /// Get the data committed to the named channel.
+    ///
+    /// Returns `None` if there is no such channel or if its data is pruned.
+    pub fn get(&self, name: &str) -> Option<&[u8]> {
+        match self.0.get(name)? {
+            MaybePruned::Value(data) => Some(data),
+            MaybePruned::Pruned(_) => None,
+        }
+    }
+
+    /// Prune the data of every channel not named in `names`.
+    pub fn retain(&mut self, names: &[&str]) {
+        for (name, data) in self.0.iter_mut() {
+            if !names.contains(&name.as_str()) {
+                *data = MaybePruned::Pruned(data.digest::<sha::Impl>());
+            }
+        }
+    }
+}
+
+impl From<BTreeMap<String, Vec<u8>>> for Channels {
+    fn from(channels: BTreeMap<String, Vec<u8>>) -> Self {
+        Self(
+            channels
+                .into_iter()
+                .map(|(name, data)| (name, data.into()))
+                .collect(),
+        )
+    }
+}
+
+impl Deref for Channels {
+    type Target = BTreeMap<String, MaybePruned<Vec<u8>>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Digestible for Channels {
+    /// Hash the [Channels] to get a digest of the struct.
+    ///
+    /// Channels are hashed in order of their names, and each one commits to its name and the
+    /// digest of its data.
+    fn digest<S: Sha256>(&self) -> Digest {
+        tagged_list::<S>(
+            "risc0.Channels",
+            &self
+                .0
+                .iter()
+                .map(|(name, data)| {
+                    tagged_struct::<S>(
+                        "risc0.Channel",
+                        &[*S::hash_bytes(name.as_bytes()), data.digest::<S>()],
+                        &[],
+                    )
+                })
+                .collect::<Vec<_>>(),
         )
     }
 }
@@ -528,11 +825,30 @@ impl Merge for Assumptions {
     }
 }
 
+impl Merge for Channels {
+    fn merge(&self, other: &Self) -> Result<Self, MergeInequalityError> {
+        if !self.0.keys().eq(other.0.keys()) {
+            return Err(MergeInequalityError(
+                self.digest::<sha::Impl>(),
+                other.digest::<sha::Impl>(),
+            ));
+        }
+        Ok(Channels(
+            self.0
+                .iter()
+                .zip(other.0.values())
+                .map(|((name, left), right)| Ok((name.clone(), left.merge(right)?)))
+                .collect::<Result<BTreeMap<_, _>, _>>()?,
+        ))
+    }
+}
+
 impl Merge for Output {
     fn merge(&self, other: &Self) -> Result<Self, MergeInequalityError> {
         Ok(Self {
             journal: self.journal.merge(&other.journal)?,
             assumptions: self.assumptions.merge(&other.assumptions)?,
+            channels: self.channels.merge(&other.channels)?,
         })
     }
 }
@@ -570,10 +886,16 @@ impl Merge for ReceiptClaim {
 
 #[cfg(test)]
 mod tests {
+    use alloc::collections::BTreeMap;
+
     use hex::FromHex;
+    use risc0_binfmt::tagged_struct;
 
-    use super::{Assumptions, ExitCode, MaybePruned, Merge, Output, ReceiptClaim, SystemState};
-    use crate::sha::{Digest, Digestible};
+    use super::{
+        Assumptions, Channels, ClaimSummary, ExitCode, MaybePruned, Merge, Output, ReceiptClaim,
+        SystemState,
+    };
+    use crate::sha::{self, Digest, Digestible};
 
     /// Testing utility for randomly pruning structs.
     trait RandPrune {
@@ -620,6 +942,7 @@ mod tests {
                     .map(|o| Output {
                         journal: o.journal.rand_prune(),
                         assumptions: o.assumptions.rand_prune(),
+                        channels: o.channels.rand_prune(),
                     })
                     .into(),
                 (Self::Pruned(x), _) => Self::Pruned(x.clone()),
@@ -637,6 +960,21 @@ mod tests {
         }
     }
 
+    impl RandPrune for MaybePruned<Channels> {
+        fn rand_prune(&self) -> Self {
+            match (self, rand::random::<bool>()) {
+                (Self::Value(x), true) => Self::Pruned(x.digest()),
+                (Self::Value(x), false) => Channels(
+                    x.0.iter()
+                        .map(|(name, data)| (name.clone(), data.rand_prune()))
+                        .collect(),
+                )
+                .into(),
+                (Self::Pruned(x), _) => Self::Pruned(x.clone()),
+            }
+        }
+    }
+
     impl RandPrune for MaybePruned<Assumptions> {
         fn rand_prune(&self) -> Self {
             match (self, rand::random::<bool>()) {
@@ -674,6 +1012,10 @@ mod tests {
                     MaybePruned::Pruned(Digest::ZERO),
                     MaybePruned::Pruned(Digest::ZERO),
                 ])),
+                channels: MaybePruned::Value(Channels(BTreeMap::from([
+                    ("events".into(), MaybePruned::Value(b"event".to_vec())),
+                    ("result".into(), MaybePruned::Value(b"result".to_vec())),
+                ]))),
             })),
         });
 
@@ -685,4 +1027,164 @@ mod tests {
             assert_eq!(left.merge(&right).unwrap().digest(), claim.digest());
         }
     }
+
+    #[test]
+    fn output_digest_without_channels() {
+        let mut output = Output {
+            journal: MaybePruned::Value(b"hello world".to_vec()),
+            assumptions: Assumptions(vec![]).into(),
+            channels: Channels::default().into(),
+        };
+        assert_eq!(
+            output.digest(),
+            tagged_struct::<sha::Impl>(
+                "risc0.Output",
+                &[output.journal.digest(), output.assumptions.digest()],
+                &[],
+            )
+        );
+
+        let without_channels = output.digest();
+        output.channels = Channels(BTreeMap::from([(
+            "events".into(),
+            MaybePruned::Value(b"event".to_vec()),
+        )]))
+        .into();
+        assert_ne!(output.digest(), without_channels);
+    }
+
+    #[test]
+    fn claim_summary() {
+        let assumption = MaybePruned::Pruned(Digest::from([1; 8]));
+        let mut claim = ReceiptClaim {
+            pre: MaybePruned::Pruned(Digest::from([2; 8])),
+            post: SystemState {
+                pc: 0,
+                merkle_root: Digest::ZERO,
+            }
+            .into(),
+            exit_code: ExitCode::Halted(0),
+            input: Digest::ZERO,
+            output: Some(Output {
+                journal: MaybePruned::Value(b"hello world".to_vec()),
+                assumptions: Assumptions(vec![assumption.clone()]).into(),
+                channels: Channels::default().into(),
+            })
+            .into(),
+        };
+        assert_eq!(
+            claim.summary(),
+            ClaimSummary {
+                image_id: Digest::from([2; 8]),
+                post_state_digest: claim.post.digest(),
+                exit_code: ExitCode::Halted(0),
+                input: Digest::ZERO,
+                output: claim.output.digest(),
+                journal: Some(Some(b"hello world".digest())),
+                assumptions: Some(vec![assumption.digest()]),
+            }
+        );
+
+        claim.output = MaybePruned::Pruned(claim.output.digest());
+        let summary = claim.summary();
+        assert_eq!((&summary.journal, &summary.assumptions), (&None, &None));
+        assert!(summary.to_string().contains("journal: pruned"));
+
+        claim.output = None.into();
+        let summary = claim.summary();
+        assert_eq!(summary.output, Digest::ZERO);
+        assert_eq!(summary.journal, Some(None));
+        assert_eq!(summary.assumptions, Some(vec![]));
+    }
+
+    #[test]
+    fn verifier_claim_digest() {
+        let journal = b"hello world".to_vec();
+        let claim = ReceiptClaim {
+            pre: MaybePruned::Pruned(Digest::from([1; 8])),
+            post: MaybePruned::Pruned(Digest::from([2; 8])),
+            exit_code: ExitCode::Paused(0),
+            input: Digest::from([3; 8]),
+            output: Some(Output {
+                journal: MaybePruned::Value(journal.clone()),
+                assumptions: Assumptions(vec![]).into(),
+                channels: Channels::default().into(),
+            })
+            .into(),
+        };
+        let summary = claim.summary();
+        let verifier_claim = risc0_verifier::Claim {
+            image_id: summary.image_id,
+            post_state_digest: summary.post_state_digest,
+            exit_code: summary.exit_code,
+            input: summary.input,
+            output: summary.output,
+        };
+        assert_eq!(
+            risc0_verifier::output_digest(&journal),
+            claim.output.digest()
+        );
+        assert_eq!(verifier_claim.digest(), claim.digest());
+        verifier_claim
+            .verify_journal(&Digest::from([1; 8]), &journal)
+            .unwrap();
+        assert!(verifier_claim
+            .verify_journal(&Digest::from([1; 8]), b"other")
+            .is_err());
+    }
+
+    #[test]
+    fn retain_channels() {
+        let channels = Channels(BTreeMap::from([
+            ("events".into(), MaybePruned::Value(b"event".to_vec())),
+            ("result".into(), MaybePruned::Value(b"result".to_vec())),
+        ]));
+        let mut retained = channels.clone();
+        retained.retain(&["result"]);
+        assert_eq!(retained.get("result"), Some(b"result".as_slice()));
+        assert_eq!(retained.get("events"), None);
+        assert_eq!(retained.digest(), channels.digest());
+    }
+
+    #[test]
+    fn output_encoding() {
+        // The encoding of an output from before channels were added.
+        #[derive(serde::Serialize)]
+        struct LegacyOutput {
+            journal: MaybePruned<Vec<u8>>,
+            assumptions: MaybePruned<Assumptions>,
+        }
+
+        for journal in [
+            MaybePruned::Value(b"hello world".to_vec()),
+            MaybePruned::Pruned(Digest::from([1; 8])),
+        ] {
+            let legacy = LegacyOutput {
+                journal: journal.clone(),
+                assumptions: Assumptions(vec![]).into(),
+            };
+            let output = Output::new(journal, Assumptions(vec![]).into());
+            let encoded = crate::serde::to_vec(&legacy).unwrap();
+            assert_eq!(crate::serde::to_vec(&output).unwrap(), encoded);
+            assert_eq!(
+                crate::serde::from_slice::<Output, _>(&encoded).unwrap(),
+                output
+            );
+
+            let json = serde_json::to_string(&legacy).unwrap();
+            assert_eq!(serde_json::to_string(&output).unwrap(), json);
+            assert_eq!(serde_json::from_str::<Output>(&json).unwrap(), output);
+        }
+
+        let mut output = Output::new(b"hello world".to_vec().into(), Assumptions(vec![]).into());
+        output.channels =
+            Channels::from(BTreeMap::from([("events".into(), b"event".to_vec())])).into();
+        let encoded = crate::serde::to_vec(&output).unwrap();
+        assert_eq!(
+            crate::serde::from_slice::<Output, _>(&encoded).unwrap(),
+            output
+        );
+        let json = serde_json::to_string(&output).unwrap();
+        assert_eq!(serde_json::from_str::<Output>(&json).unwrap(), output);
+    }
 }
@@ -0,0 +1,29 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A wall-clock time reported by the host.
+
+use serde::{Deserialize, Serialize};
+
+/// A wall-clock time asserted by the host, as committed to the journal by
+/// [crate::guest::env::host_timestamp].
+///
+/// The zkVM does not check this value: a receipt proves only that the host
+/// reported this time to the guest, not that it was the time of execution.
+/// Verifiers should treat it as a claim by the prover.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct HostTimestamp {
+    /// Milliseconds since the Unix epoch.
+    pub unix_millis: u64,
+}
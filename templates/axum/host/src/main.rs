@@ -0,0 +1,68 @@
+// These constants represent the RISC-V ELF and the image ID generated by risc0-build.
+// The ELF is used for proving and the ID is used for verification.
+use methods::{
+    {{guest_elf}}, {{guest_id}}
+};
+use axum::{http::StatusCode, routing::post, Json, Router};
+use risc0_zkvm::{default_prover, ExecutorEnv, Receipt};
+use serde::{Deserialize, Serialize};
+
+// TODO: Replace the input and output with those of your guest.
+#[derive(Deserialize)]
+struct ProveRequest {
+    input: u32,
+}
+
+#[derive(Serialize)]
+struct ProveResponse {
+    output: u32,
+    // The receipt, serialized with bincode and encoded as hex, which clients
+    // can verify against the image ID of the guest.
+    receipt: String,
+}
+
+type Error = (StatusCode, String);
+
+fn internal_error(err: impl std::fmt::Display) -> Error {
+    (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+}
+
+fn prove_input(input: u32) -> anyhow::Result<Receipt> {
+    let env = ExecutorEnv::builder().write(&input)?.build()?;
+    let receipt = default_prover()
+        .prove(env, {{guest_elf}})?
+        .receipt;
+    receipt.verify({{guest_id}})?;
+    Ok(receipt)
+}
+
+async fn prove(Json(request): Json<ProveRequest>) -> Result<Json<ProveResponse>, Error> {
+    // Proving takes a while and keeps a CPU busy, so it runs on a blocking
+    // thread rather than holding up the server.
+    let receipt = tokio::task::spawn_blocking(move || prove_input(request.input))
+        .await
+        .map_err(internal_error)?
+        .map_err(internal_error)?;
+
+    let output = receipt.journal.decode().map_err(internal_error)?;
+    let receipt = bincode::serialize(&receipt).map_err(internal_error)?;
+    Ok(Json(ProveResponse {
+        output,
+        receipt: hex::encode(receipt),
+    }))
+}
+
+#[tokio::main]
+async fn main() {
+    // Initialize tracing. In order to view logs, run `RUST_LOG=info cargo run`
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::filter::EnvFilter::from_default_env())
+        .init();
+
+    let addr = std::env::var("LISTEN_ADDR").unwrap_or_else(|_| "127.0.0.1:3000".to_string());
+    let app = Router::new().route("/prove", post(prove));
+
+    let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
+    println!("Listening on http://{addr}");
+    axum::serve(listener, app).await.unwrap();
+}
@@ -0,0 +1,62 @@
+// These constants represent the RISC-V ELF and the image ID generated by risc0-build.
+// The ELF is used for proving and the ID is used for verification.
+use methods::{
+    {{guest_elf}}, {{guest_id}}
+};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use bonsai_sdk::alpha::Client;
+use risc0_zkvm::{compute_image_id, serde::to_vec, Receipt};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+fn main() -> Result<()> {
+    // Initialize tracing. In order to view logs, run `RUST_LOG=info cargo run`
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::filter::EnvFilter::from_default_env())
+        .init();
+
+    // The client reads the URL and API key of Bonsai from BONSAI_API_URL and
+    // BONSAI_API_KEY.
+    let client = Client::from_env(risc0_zkvm::VERSION)
+        .context("set BONSAI_API_URL and BONSAI_API_KEY to prove on Bonsai")?;
+
+    // Upload the guest, keyed by its image ID. Bonsai keeps images, so this
+    // is skipped when it already has the guest.
+    let image_id = hex::encode(compute_image_id({{guest_elf}})?);
+    client.upload_img(&image_id, {{guest_elf}}.to_vec())?;
+
+    // TODO: Replace the input with that of your guest. Inputs are uploaded in
+    // the format the guest reads with `env::read`.
+    let input: u32 = 15 * u32::pow(2, 27) + 1;
+    let input = to_vec(&input)?;
+    let input_id = client.upload_input(bytemuck::cast_slice(&input).to_vec())?;
+
+    let session = client.create_session(image_id, input_id, vec![])?;
+    println!("Proving in Bonsai session {}", session.uuid);
+    let receipt_url = loop {
+        let status = session.status(&client)?;
+        match status.status.as_str() {
+            "RUNNING" => {
+                println!("Session is {}", status.state.unwrap_or_default());
+                std::thread::sleep(POLL_INTERVAL);
+            }
+            "SUCCEEDED" => break status.receipt_url.context("Bonsai returned no receipt")?,
+            _ => bail!(
+                "session ended with status {}: {}",
+                status.status,
+                status.error_msg.unwrap_or_default()
+            ),
+        }
+    };
+
+    let receipt: Receipt = bincode::deserialize(&client.download(&receipt_url)?)?;
+    receipt.verify({{guest_id}})?;
+
+    // TODO: Implement code for retrieving receipt journal here.
+    let output: u32 = receipt.journal.decode()?;
+    println!("Verified the receipt of session {}, with output {output}", session.uuid);
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: Apache-2.0
+pragma solidity ^0.8.20;
+
+import {IRiscZeroVerifier} from "./IRiscZeroVerifier.sol";
+
+/// @notice Stores the output of the guest, once a receipt proves that the guest
+/// committed it.
+/// TODO: Replace the output and its decoding with those of your guest.
+contract GuestOutput {
+    IRiscZeroVerifier public immutable verifier;
+    bytes32 public immutable imageId;
+    uint32 public output;
+
+    constructor(IRiscZeroVerifier _verifier, bytes32 _imageId) {
+        verifier = _verifier;
+        imageId = _imageId;
+    }
+
+    /// @notice Set the output to the value committed in the journal of a
+    /// proven execution of the guest.
+    function set(bytes calldata journal, bytes32 postStateDigest, bytes calldata seal) external {
+        verifier.verify(seal, imageId, postStateDigest, sha256(journal));
+
+        // The guest commits a u32, which the zkVM serializes as 4 bytes in
+        // little-endian order.
+        require(journal.length == 4, "unexpected journal length");
+        output = uint32(uint8(journal[0])) | (uint32(uint8(journal[1])) << 8) | (uint32(uint8(journal[2])) << 16)
+            | (uint32(uint8(journal[3])) << 24);
+    }
+}
@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: Apache-2.0
+pragma solidity ^0.8.20;
+
+/// @notice The interface of the verifier generated by
+/// `cargo risczero solidity-verifier`.
+interface IRiscZeroVerifier {
+    /// @notice Verify that the seal proves a successful execution of the
+    /// given image, ending in the given post state and committing a journal
+    /// with the given digest.
+    /// @dev Reverts if the seal is not valid.
+    function verify(bytes calldata seal, bytes32 imageId, bytes32 postStateDigest, bytes32 journalDigest)
+        external
+        view;
+}
@@ -0,0 +1,61 @@
+// These constants represent the RISC-V ELF and the image ID generated by risc0-build.
+// The ELF is used for proving and the ID is used for verification.
+use methods::{
+    {{guest_elf}}, {{guest_id}}
+};
+use anyhow::{Context, Result};
+use risc0_zkvm::{
+    default_prover,
+    sha::{Digest, Digestible},
+    CompactReceipt, ExecutorEnv, ProverOpts, VerifierContext,
+};
+use serde::Serialize;
+
+// The arguments of `GuestOutput.set`, in hex, and the image ID the contract
+// is deployed with.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Proof {
+    image_id: String,
+    journal: String,
+    post_state_digest: String,
+    seal: String,
+}
+
+fn main() -> Result<()> {
+    // Initialize tracing. In order to view logs, run `RUST_LOG=info cargo run`
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::filter::EnvFilter::from_default_env())
+        .init();
+
+    // TODO: Replace the input with that of your guest.
+    let input: u32 = 15 * u32::pow(2, 27) + 1;
+    let env = ExecutorEnv::builder().write(&input)?.build()?;
+
+    // Prove a compact receipt, whose Groth16 seal is verified on-chain.
+    let receipt = default_prover()
+        .prove_with_ctx(
+            env,
+            &VerifierContext::default(),
+            {{guest_elf}},
+            &ProverOpts::compact(),
+        )?
+        .receipt;
+    receipt.verify({{guest_id}})?;
+
+    // The verifier contract takes seals that start with the selector of the
+    // parameters it was generated for.
+    let compact = receipt.inner.compact().context("expected a compact receipt")?;
+    let mut seal = CompactReceipt::verifier_parameters()?.selector().to_vec();
+    seal.extend_from_slice(&compact.seal);
+
+    let proof = Proof {
+        image_id: format!("0x{}", Digest::from({{guest_id}})),
+        journal: format!("0x{}", hex::encode(&receipt.journal.bytes)),
+        post_state_digest: format!("0x{}", receipt.claim()?.post.digest()),
+        seal: format!("0x{}", hex::encode(seal)),
+    };
+    println!("{}", serde_json::to_string_pretty(&proof)?);
+
+    Ok(())
+}